@@ -1,5 +1,7 @@
 //! Shared error types
 
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use thiserror::Error;
 
 /// Core errors shared between server and client
@@ -14,3 +16,83 @@ pub enum CoreError {
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 }
+
+/// A single field that failed validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationError {
+    /// Path of the offending field, e.g. `expected_observations[1].stations`
+    pub field: String,
+    /// Stable machine readable reason, e.g. `required`
+    pub code: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(
+        field: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Every validation failure found in a request, so clients can show them all at once
+#[derive(Error, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationErrors {
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(
+        &mut self,
+        field: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.errors.push(ValidationError::new(field, code, message));
+    }
+
+    /// Add the errors of a nested validation, prefixing their fields with `prefix`
+    pub fn extend_nested(&mut self, prefix: &str, nested: ValidationErrors) {
+        self.errors
+            .extend(nested.errors.into_iter().map(|error| ValidationError {
+                field: format!("{}.{}", prefix, error.field),
+                ..error
+            }));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// `Ok` when nothing failed
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.errors.iter().map(ToString::to_string).collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
@@ -0,0 +1,168 @@
+//! Memo the coordinator puts on ticket hold invoices, so a wallet's payment history says which
+//! coordinator, competition and ticket a payment was for
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+use crate::CoreError;
+
+/// Longest coordinator name kept in a memo, BOLT11 descriptions are capped at 639 bytes
+pub const MAX_MEMO_NAME_LEN: usize = 64;
+/// Hex characters of the competition id kept in a memo
+pub const COMPETITION_SHORT_ID_LEN: usize = 8;
+
+/// `n=<coordinator>;c=<competition short id>;t=<ticket id>`, followed by `;r=<escrow txid>`
+/// when the ticket has an escrow transaction. Parsing ignores keys it doesn't know so fields
+/// can be added later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvoiceMemo {
+    pub coordinator: String,
+    /// First 8 hex characters of the competition id
+    pub competition: String,
+    pub ticket_id: String,
+    /// Txid of the escrow (refund) transaction backing the ticket
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escrow_txid: Option<String>,
+}
+
+impl InvoiceMemo {
+    pub fn new(
+        coordinator: &str,
+        competition_id: &str,
+        ticket_id: &str,
+        escrow_txid: Option<String>,
+    ) -> Self {
+        Self {
+            coordinator: memo_name(coordinator),
+            competition: competition_short_id(competition_id),
+            ticket_id: ticket_id.to_string(),
+            escrow_txid,
+        }
+    }
+
+    /// Whether this memo was written for the ticket in the given competition
+    pub fn matches(&self, competition_id: &str, ticket_id: &str) -> bool {
+        self.competition == competition_short_id(competition_id)
+            && self.ticket_id.eq_ignore_ascii_case(ticket_id)
+    }
+}
+
+/// Leading hex characters of a competition id, dashes dropped
+pub fn competition_short_id(competition_id: &str) -> String {
+    competition_id
+        .chars()
+        .filter(|c| *c != '-')
+        .take(COMPETITION_SHORT_ID_LEN)
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// The coordinator name without the memo's separators, trimmed to `MAX_MEMO_NAME_LEN`
+fn memo_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ';' | '=') && !c.is_control())
+        .take(MAX_MEMO_NAME_LEN)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+impl fmt::Display for InvoiceMemo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={};c={};t={}",
+            self.coordinator, self.competition, self.ticket_id
+        )?;
+        if let Some(escrow_txid) = &self.escrow_txid {
+            write!(f, ";r={}", escrow_txid)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for InvoiceMemo {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut coordinator, mut competition, mut ticket_id, mut escrow_txid) =
+            (None, None, None, None);
+        for field in s.trim().split(';') {
+            let Some((key, value)) = field.split_once('=') else {
+                return Err(CoreError::Validation(format!(
+                    "invoice memo field without a value: {}",
+                    field
+                )));
+            };
+            let value = Some(value.to_string());
+            match key {
+                "n" => coordinator = value,
+                "c" => competition = value,
+                "t" => ticket_id = value,
+                "r" => escrow_txid = value,
+                _ => {}
+            }
+        }
+        let missing = |key: &str| CoreError::Validation(format!("invoice memo has no {}", key));
+        Ok(Self {
+            coordinator: coordinator.ok_or_else(|| missing("coordinator"))?,
+            competition: competition.ok_or_else(|| missing("competition"))?,
+            ticket_id: ticket_id.ok_or_else(|| missing("ticket"))?,
+            escrow_txid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPETITION_ID: &str = "0191d2a4-7c3b-7e21-9a3f-5b8c2d1e0f47";
+    const TICKET_ID: &str = "0191d2a5-0a11-7b42-8c9d-1e2f3a4b5c6d";
+
+    #[test]
+    fn test_memo_round_trips() {
+        let memo = InvoiceMemo::new(
+            "Weather; Wagers=fun",
+            COMPETITION_ID,
+            TICKET_ID,
+            Some("ab".repeat(32)),
+        );
+        let encoded = memo.to_string();
+        assert_eq!(
+            encoded,
+            format!(
+                "n=Weather Wagersfun;c=0191d2a4;t={};r={}",
+                TICKET_ID,
+                "ab".repeat(32)
+            )
+        );
+        assert_eq!(encoded.parse::<InvoiceMemo>().unwrap(), memo);
+        assert!(memo.matches(COMPETITION_ID, &TICKET_ID.to_uppercase()));
+        assert!(!memo.matches("0191d2a5-7c3b-7e21-9a3f-5b8c2d1e0f47", TICKET_ID));
+
+        let without_escrow = InvoiceMemo::new("coordinator", COMPETITION_ID, TICKET_ID, None);
+        assert_eq!(
+            without_escrow
+                .to_string()
+                .parse::<InvoiceMemo>()
+                .unwrap()
+                .escrow_txid,
+            None
+        );
+    }
+
+    #[test]
+    fn test_memo_parsing_ignores_unknown_keys_and_needs_the_ticket() {
+        let memo: InvoiceMemo = format!("n=c;c=0191d2a4;t={};v=2", TICKET_ID)
+            .parse()
+            .unwrap();
+        assert!(memo.matches(COMPETITION_ID, TICKET_ID));
+
+        assert!("n=c;c=0191d2a4".parse::<InvoiceMemo>().is_err());
+        // The memo older coordinators wrote
+        assert!(format!("c:{};r:[1, 2]", COMPETITION_ID)
+            .parse::<InvoiceMemo>()
+            .is_err());
+    }
+}
@@ -3,9 +3,15 @@
 //! This crate contains types that are shared between the server and browser client.
 
 pub mod errors;
+pub mod invoice_memo;
+pub mod receipt;
+pub mod sats;
 pub mod types;
 pub mod validation;
 
 pub use errors::*;
+pub use invoice_memo::*;
+pub use receipt::*;
+pub use sats::*;
 pub use types::*;
 pub use validation::*;
@@ -0,0 +1,104 @@
+//! Verification of the receipts the coordinator signs for ticket payments and accepted entries,
+//! shared so the server and the browser client check them the same way.
+use bitcoin::{
+    hashes::{sha256, Hash},
+    secp256k1::{schnorr::Signature, Message, Secp256k1, XOnlyPublicKey},
+};
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::SignedReceipt;
+
+#[derive(Error, Debug)]
+pub enum ReceiptError {
+    #[error("Invalid receipt json: {0}")]
+    Payload(#[from] serde_json::Error),
+    #[error("Invalid pubkey: {0}")]
+    Pubkey(String),
+    #[error("Receipt is signed by {0}, not the coordinator")]
+    WrongSigner(String),
+    #[error("Invalid signature: {0}")]
+    Signature(String),
+}
+
+/// What a receipt's signature commits to, the sha256 of its payload json
+pub fn receipt_message(payload: &str) -> Message {
+    Message::from_digest(sha256::Hash::hash(payload.as_bytes()).to_byte_array())
+}
+
+/// Check `receipt` was signed by one of `coordinator_pubkeys`, the hex x-only keys served at the
+/// coordinator's `/api/v1/info`, and return its payload json. Older keys stay published after a
+/// rotation, so receipts signed before it keep verifying.
+pub fn verify_receipt_signature<'a, S: AsRef<str>>(
+    receipt: &'a SignedReceipt,
+    coordinator_pubkeys: &[S],
+) -> Result<&'a str, ReceiptError> {
+    let signer = coordinator_pubkeys
+        .iter()
+        .map(AsRef::as_ref)
+        .find(|pubkey| receipt.pubkey.eq_ignore_ascii_case(pubkey))
+        .ok_or_else(|| ReceiptError::WrongSigner(receipt.pubkey.clone()))?;
+    let pubkey =
+        XOnlyPublicKey::from_str(signer).map_err(|e| ReceiptError::Pubkey(e.to_string()))?;
+    let signature = Signature::from_str(&receipt.signature)
+        .map_err(|e| ReceiptError::Signature(e.to_string()))?;
+    Secp256k1::verification_only()
+        .verify_schnorr(&signature, &receipt_message(&receipt.payload), &pubkey)
+        .map_err(|e| ReceiptError::Signature(e.to_string()))?;
+
+    Ok(&receipt.payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::{Keypair, SecretKey};
+
+    fn signed_receipt(secret: u8, payload: &str) -> SignedReceipt {
+        let secp = Secp256k1::new();
+        let keypair =
+            Keypair::from_secret_key(&secp, &SecretKey::from_slice(&[secret; 32]).unwrap());
+        SignedReceipt {
+            payload: payload.to_string(),
+            pubkey: keypair.x_only_public_key().0.to_string(),
+            signature: secp
+                .sign_schnorr_no_aux_rand(&receipt_message(payload), &keypair)
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn test_receipt_verifies_against_any_published_key() {
+        let old = signed_receipt(7, r#"{"kind":"ticket_payment","amount_sats":1100}"#);
+        let new = signed_receipt(8, r#"{"kind":"entry_accepted","amount_sats":1100}"#);
+        let published = [old.pubkey.clone(), new.pubkey.clone()];
+
+        // A receipt signed before the key rotation still verifies
+        assert_eq!(
+            verify_receipt_signature(&old, &published).unwrap(),
+            old.payload
+        );
+        assert_eq!(
+            verify_receipt_signature(&new, &published).unwrap(),
+            new.payload
+        );
+
+        // A receipt is only as good as the keys it is checked against
+        assert!(matches!(
+            verify_receipt_signature(&old, &[new.pubkey.as_str()]),
+            Err(ReceiptError::WrongSigner(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampered_receipt_fails_verification() {
+        let mut receipt = signed_receipt(7, r#"{"kind":"ticket_payment","amount_sats":1100}"#);
+        let published = [receipt.pubkey.clone()];
+
+        receipt.payload = receipt.payload.replace("1100", "9100");
+        assert!(matches!(
+            verify_receipt_signature(&receipt, &published),
+            Err(ReceiptError::Signature(_))
+        ));
+    }
+}
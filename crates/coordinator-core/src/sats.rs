@@ -0,0 +1,107 @@
+//! Amounts of money, always counted in satoshis
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An amount in satoshis. Serializes as a plain number so existing JSON keeps parsing, and the
+/// arithmetic is checked or saturating so fee math can't silently wrap.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Sats(pub u64);
+
+impl Sats {
+    pub const ZERO: Sats = Sats(0);
+    pub const MAX: Sats = Sats(u64::MAX);
+
+    pub const fn to_sat(self) -> u64 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Sats) -> Option<Sats> {
+        self.0.checked_add(other.0).map(Sats)
+    }
+
+    pub fn checked_sub(self, other: Sats) -> Option<Sats> {
+        self.0.checked_sub(other.0).map(Sats)
+    }
+
+    pub fn checked_mul(self, factor: u64) -> Option<Sats> {
+        self.0.checked_mul(factor).map(Sats)
+    }
+
+    pub fn saturating_add(self, other: Sats) -> Sats {
+        Sats(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Sats) -> Sats {
+        Sats(self.0.saturating_sub(other.0))
+    }
+
+    pub fn saturating_mul(self, factor: u64) -> Sats {
+        Sats(self.0.saturating_mul(factor))
+    }
+
+    /// `percent` of this amount, rounded half up. `None` only when the result doesn't fit,
+    /// which takes a percentage above 100.
+    pub fn checked_percent(self, percent: u64) -> Option<Sats> {
+        let scaled = (self.0 as u128 * percent as u128 + 50) / 100;
+        u64::try_from(scaled).ok().map(Sats)
+    }
+
+    /// Sum of `amounts`, `None` if it overflows
+    pub fn checked_sum(amounts: impl IntoIterator<Item = Sats>) -> Option<Sats> {
+        amounts
+            .into_iter()
+            .try_fold(Sats::ZERO, |total, amount| total.checked_add(amount))
+    }
+}
+
+impl From<u64> for Sats {
+    fn from(sats: u64) -> Self {
+        Sats(sats)
+    }
+}
+
+impl From<Sats> for u64 {
+    fn from(sats: Sats) -> Self {
+        sats.0
+    }
+}
+
+impl fmt::Display for Sats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sats_arithmetic_is_checked() {
+        assert_eq!(Sats(1_000).checked_add(Sats(100)), Some(Sats(1_100)));
+        assert_eq!(Sats::MAX.checked_add(Sats(1)), None);
+        assert_eq!(Sats(5).checked_sub(Sats(6)), None);
+        assert_eq!(Sats(u64::MAX / 2 + 1).checked_mul(2), None);
+        assert_eq!(Sats::MAX.saturating_add(Sats(1)), Sats::MAX);
+        assert_eq!(Sats(5).saturating_sub(Sats(6)), Sats::ZERO);
+
+        assert_eq!(Sats(1_000).checked_percent(10), Some(Sats(100)));
+        assert_eq!(Sats(5).checked_percent(10), Some(Sats(1)));
+        assert_eq!(Sats::MAX.checked_percent(100), Some(Sats::MAX));
+        assert_eq!(Sats::MAX.checked_percent(101), None);
+
+        assert_eq!(Sats::checked_sum([Sats(1), Sats(2)]), Some(Sats(3)));
+        assert_eq!(Sats::checked_sum([Sats::MAX, Sats(1)]), None);
+    }
+
+    #[test]
+    fn test_sats_serializes_as_a_plain_number() {
+        assert_eq!(serde_json::to_string(&Sats(1_000)).unwrap(), "1000");
+        assert_eq!(serde_json::from_str::<Sats>("3000").unwrap(), Sats(3_000));
+        assert!(serde_json::from_str::<Sats>("-1").is_err());
+    }
+}
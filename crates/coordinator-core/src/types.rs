@@ -1,6 +1,75 @@
 //! Shared types between coordinator server and WASM client
 
 use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+use crate::CoreError;
+
+/// Bitcoin network the coordinator and player wallets run on.
+/// Strings match rust-bitcoin's network names, with `mainnet` accepted as an alias for `bitcoin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BitcoinNetwork {
+    #[serde(alias = "mainnet")]
+    Bitcoin,
+    Testnet,
+    Testnet4,
+    Signet,
+    Regtest,
+}
+
+impl BitcoinNetwork {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BitcoinNetwork::Bitcoin => "bitcoin",
+            BitcoinNetwork::Testnet => "testnet",
+            BitcoinNetwork::Testnet4 => "testnet4",
+            BitcoinNetwork::Signet => "signet",
+            BitcoinNetwork::Regtest => "regtest",
+        }
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        *self == BitcoinNetwork::Bitcoin
+    }
+}
+
+impl fmt::Display for BitcoinNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Map the coordinator's network names onto rust-bitcoin's so the server and wallet agree
+impl From<BitcoinNetwork> for bitcoin::Network {
+    fn from(network: BitcoinNetwork) -> Self {
+        match network {
+            BitcoinNetwork::Bitcoin => bitcoin::Network::Bitcoin,
+            BitcoinNetwork::Testnet => bitcoin::Network::Testnet,
+            BitcoinNetwork::Testnet4 => bitcoin::Network::Testnet4,
+            BitcoinNetwork::Signet => bitcoin::Network::Signet,
+            BitcoinNetwork::Regtest => bitcoin::Network::Regtest,
+        }
+    }
+}
+
+impl FromStr for BitcoinNetwork {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "bitcoin" | "mainnet" => Ok(BitcoinNetwork::Bitcoin),
+            "testnet" => Ok(BitcoinNetwork::Testnet),
+            "testnet4" => Ok(BitcoinNetwork::Testnet4),
+            "signet" => Ok(BitcoinNetwork::Signet),
+            "regtest" => Ok(BitcoinNetwork::Regtest),
+            other => Err(CoreError::Validation(format!(
+                "unknown bitcoin network: {}",
+                other
+            ))),
+        }
+    }
+}
 
 /// Comparison type for predictions
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -103,3 +172,14 @@ pub enum CompetitionStatusKind {
     Failed,
     Cancelled,
 }
+
+/// A receipt payload signed by the coordinator, in the pubkey/signature shape of
+/// `CoordinatorInfo`. `signature` is a hex BIP340 signature over the sha256 of `payload` by the
+/// x-only `pubkey` the coordinator publishes at `/api/v1/info`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedReceipt {
+    /// JSON encoded receipt, verified as is rather than re-serialized
+    pub payload: String,
+    pub pubkey: String,
+    pub signature: String,
+}
@@ -1,27 +1,285 @@
 //! Validation utilities shared between server and client
 
-use crate::{CoreError, ObservationChoice};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 
-/// Validate observation choices
-pub fn validate_observations(observations: &[ObservationChoice]) -> Result<(), CoreError> {
+use crate::{ObservationChoice, ValidationErrors};
+
+/// Version byte NIP-44 v2 payloads start with
+pub const NIP44_VERSION: u8 = 2;
+/// Smallest decoded NIP-44 v2 payload: version, 32 byte nonce, 2 byte length prefix, 32 bytes of
+/// padded plaintext and the 32 byte MAC
+pub const NIP44_MIN_PAYLOAD_LEN: usize = 1 + 32 + 2 + 32 + 32;
+/// Longest entry secret ciphertext accepted, in base64 characters. Entry secrets are 32 byte hex
+/// strings, which encrypt to 176 characters.
+pub const MAX_ENTRY_CIPHERTEXT_LEN: usize = 1024;
+
+/// Validate observation choices, reporting every invalid choice
+pub fn validate_observations(observations: &[ObservationChoice]) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
     if observations.is_empty() {
-        return Err(CoreError::Validation(
-            "at least one observation required".into(),
-        ));
+        errors.push(
+            "observations",
+            "required",
+            "at least one observation required",
+        );
     }
 
-    for obs in observations {
+    for (index, obs) in observations.iter().enumerate() {
         if obs.source_id.is_empty() {
-            return Err(CoreError::InvalidObservation(
-                "source_id cannot be empty".into(),
-            ));
+            errors.push(
+                format!("observations[{}].source_id", index),
+                "required",
+                "source_id cannot be empty",
+            );
         }
         if obs.metric.is_empty() {
-            return Err(CoreError::InvalidObservation(
-                "metric cannot be empty".into(),
-            ));
+            errors.push(
+                format!("observations[{}].metric", index),
+                "required",
+                "metric cannot be empty",
+            );
+        }
+    }
+
+    errors.into_result()
+}
+
+/// Validate the number of values an entry picks against the competition's limit
+pub fn validate_value_count(
+    field: &str,
+    value_count: usize,
+    max_values: usize,
+) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    if value_count > max_values {
+        errors.push(
+            field,
+            "too_many_values",
+            format!(
+                "too many value choices, max allowed {} but got {}",
+                max_values, value_count
+            ),
+        );
+    }
+    errors.into_result()
+}
+
+/// Validate the keys and secrets an entry carries, so what a client stores with the coordinator
+/// and gets backed up over nostr is what it needs for recovery and nothing more
+pub fn validate_entry_secrets(
+    ephemeral_pubkey: &str,
+    ephemeral_privatekey_encrypted: &str,
+    payout_hash: &str,
+    payout_preimage_encrypted: &str,
+) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    check_compressed_pubkey(&mut errors, "ephemeral_pubkey", ephemeral_pubkey);
+    check_nip44_payload(
+        &mut errors,
+        "ephemeral_privatekey_encrypted",
+        ephemeral_privatekey_encrypted,
+    );
+    check_hex_bytes(&mut errors, "payout_hash", payout_hash, 32);
+    check_nip44_payload(
+        &mut errors,
+        "payout_preimage_encrypted",
+        payout_preimage_encrypted,
+    );
+    errors.into_result()
+}
+
+/// Structural NIP-44 v2 check, the coordinator can't decrypt these so it only makes sure they
+/// look like something the player's nostr key can
+fn check_nip44_payload(errors: &mut ValidationErrors, field: &str, payload: &str) {
+    if payload.len() > MAX_ENTRY_CIPHERTEXT_LEN {
+        errors.push(
+            field,
+            "too_long",
+            format!(
+                "must be at most {} characters, got {}",
+                MAX_ENTRY_CIPHERTEXT_LEN,
+                payload.len()
+            ),
+        );
+        return;
+    }
+    let Ok(decoded) = BASE64.decode(payload) else {
+        errors.push(field, "invalid_base64", "must be a base64 NIP-44 payload");
+        return;
+    };
+    if decoded.first() != Some(&NIP44_VERSION) {
+        errors.push(
+            field,
+            "unsupported_version",
+            format!("must be a NIP-44 v{} payload", NIP44_VERSION),
+        );
+    } else if decoded.len() < NIP44_MIN_PAYLOAD_LEN {
+        errors.push(
+            field,
+            "too_short",
+            format!(
+                "NIP-44 payloads are at least {} bytes, got {}",
+                NIP44_MIN_PAYLOAD_LEN,
+                decoded.len()
+            ),
+        );
+    }
+}
+
+fn check_hex_bytes(errors: &mut ValidationErrors, field: &str, value: &str, len: usize) {
+    match hex::decode(value) {
+        Ok(bytes) if bytes.len() == len => {}
+        Ok(bytes) => errors.push(
+            field,
+            "invalid_length",
+            format!("must be {} bytes, got {}", len, bytes.len()),
+        ),
+        Err(_) => errors.push(field, "invalid_hex", "must be hex encoded"),
+    }
+}
+
+/// Length and prefix of a compressed secp256k1 point, whether it is on the curve is left to
+/// callers with a secp256k1 library at hand
+fn check_compressed_pubkey(errors: &mut ValidationErrors, field: &str, value: &str) {
+    match hex::decode(value) {
+        Ok(bytes) if bytes.len() == 33 && matches!(bytes[0], 0x02 | 0x03) => {}
+        Ok(_) => errors.push(
+            field,
+            "invalid_pubkey",
+            "must be a 33 byte compressed public key",
+        ),
+        Err(_) => errors.push(field, "invalid_hex", "must be hex encoded"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Comparison;
+
+    /// What NIP-44 v2 encryption of a 32 byte hex secret produces: version, nonce, 66 bytes of
+    /// length prefixed padded plaintext and the MAC
+    fn nip44_payload(version: u8, plaintext_len: usize) -> String {
+        let mut payload = vec![version];
+        payload.extend([7u8; 32]);
+        payload.extend(vec![1u8; 2 + plaintext_len]);
+        payload.extend([9u8; 32]);
+        BASE64.encode(payload)
+    }
+
+    fn codes(result: Result<(), ValidationErrors>) -> Vec<(String, String)> {
+        result
+            .err()
+            .map(|errors| {
+                errors
+                    .errors
+                    .into_iter()
+                    .map(|error| (error.field, error.code))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_entry_secrets_accept_what_clients_produce() {
+        let encrypted = nip44_payload(NIP44_VERSION, 64);
+        assert_eq!(encrypted.len(), 176);
+        assert!(validate_entry_secrets(
+            &format!("02{}", "11".repeat(32)),
+            &encrypted,
+            &"ab".repeat(32),
+            &encrypted,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_entry_secrets_report_each_field() {
+        let encrypted = nip44_payload(NIP44_VERSION, 64);
+        assert_eq!(
+            codes(validate_entry_secrets(
+                &format!("04{}", "11".repeat(32)),
+                &"A".repeat(MAX_ENTRY_CIPHERTEXT_LEN + 4),
+                "not hex",
+                "%%%",
+            )),
+            vec![
+                ("ephemeral_pubkey".to_string(), "invalid_pubkey".to_string()),
+                (
+                    "ephemeral_privatekey_encrypted".to_string(),
+                    "too_long".to_string()
+                ),
+                ("payout_hash".to_string(), "invalid_hex".to_string()),
+                (
+                    "payout_preimage_encrypted".to_string(),
+                    "invalid_base64".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            codes(validate_entry_secrets(
+                &format!("03{}", "11".repeat(32)),
+                &nip44_payload(1, 64),
+                &"ab".repeat(16),
+                &nip44_payload(NIP44_VERSION, 0),
+            )),
+            vec![
+                (
+                    "ephemeral_privatekey_encrypted".to_string(),
+                    "unsupported_version".to_string()
+                ),
+                ("payout_hash".to_string(), "invalid_length".to_string()),
+                (
+                    "payout_preimage_encrypted".to_string(),
+                    "too_short".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            codes(validate_entry_secrets("", "", "", &encrypted)).len(),
+            3,
+            "empty values are rejected rather than stored"
+        );
+    }
+
+    fn choice(source_id: &str, metric: &str) -> ObservationChoice {
+        ObservationChoice {
+            source_id: source_id.to_string(),
+            metric: metric.to_string(),
+            prediction: Comparison::Over,
         }
     }
 
-    Ok(())
+    #[test]
+    fn test_validate_observations_reports_every_invalid_choice() {
+        let errors = validate_observations(&[
+            choice("", "temp_high"),
+            choice("KORD", "temp_low"),
+            choice("KSEA", ""),
+        ])
+        .unwrap_err();
+
+        let fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["observations[0].source_id", "observations[2].metric"]
+        );
+        assert!(errors.errors.iter().all(|e| e.code == "required"));
+    }
+
+    #[test]
+    fn test_validation_errors_serialize_as_errors_list() {
+        let mut errors = ValidationErrors::new();
+        errors.push("id", "invalid_uuid_version", "id must be a UUIDv7");
+        let mut nested = ValidationErrors::new();
+        nested.push("stations", "unknown_location", "not in this competition");
+        errors.extend_nested("expected_observations[0]", nested);
+
+        let json = serde_json::to_value(&errors).unwrap();
+        assert_eq!(json["errors"][0]["code"], "invalid_uuid_version");
+        assert_eq!(
+            json["errors"][1]["field"],
+            "expected_observations[0].stations"
+        );
+    }
 }
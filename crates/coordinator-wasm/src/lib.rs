@@ -3,6 +3,10 @@
 //! This crate provides browser-side functionality for:
 //! - Nostr authentication (NIP-98)
 //! - Escrow PSBT signing
+//! - Checking a ticket's invoice memo and fee breakdown before paying it
+//! - Verifying coordinator signed payment and entry receipts
+//! - Recovering an entry's signing material from the account's recovery key
+//! - Checking an entry's keys and encrypted secrets the way the coordinator does
 //! - Keymeld SDK integration for remote MuSig2 signing (requires `keymeld` feature)
 
 use wasm_bindgen::prelude::*;
@@ -10,6 +14,8 @@ use wasm_bindgen::prelude::*;
 #[cfg(feature = "keymeld")]
 pub mod keymeld;
 pub mod nostr;
+pub mod payment;
+pub mod receipt;
 pub mod wallet;
 
 // Re-export coordinator-core types
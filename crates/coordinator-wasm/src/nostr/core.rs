@@ -1,16 +1,36 @@
-use super::{CustomSigner, NostrError, SignerType};
+use super::{CustomSigner, NostrError, RelayConnector, RelayHealth, RelaySet, SignerType};
+use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use nostr_sdk::{
     hashes::{sha256::Hash as Sha256Hash, Hash},
     prelude::*,
     Client, Event, Keys, PublicKey, SecretKey, UnsignedEvent,
 };
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+/// How long a single relay connection attempt may take before it counts as failed
+const RELAY_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Clone)]
 pub struct NostrClientCore {
     inner: Option<Client>,
     pub signer: Option<CustomSigner>,
+    relays: RelaySet,
+}
+
+/// Connects relays through the nostr client's pool
+struct ClientConnector(Client);
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl RelayConnector for ClientConnector {
+    async fn connect(&self, url: &str) -> Result<(), String> {
+        self.0.add_relay(url).await.map_err(|e| e.to_string())?;
+        self.0
+            .try_connect_relay(url, RELAY_CONNECT_TIMEOUT)
+            .await
+            .map_err(|e| e.to_string())
+    }
 }
 
 impl Default for NostrClientCore {
@@ -24,6 +44,7 @@ impl NostrClientCore {
         Self {
             inner: None,
             signer: None,
+            relays: RelaySet::default(),
         }
     }
 
@@ -31,6 +52,7 @@ impl NostrClientCore {
         &mut self,
         signer_type: SignerType,
         private_key: Option<String>,
+        relays: Option<Vec<String>>,
     ) -> Result<(), NostrError> {
         let signer = match signer_type {
             SignerType::PrivateKey => {
@@ -52,17 +74,19 @@ impl NostrClientCore {
 
         let client = Client::new(signer.clone());
 
+        let relay_set = RelaySet::new(relays.unwrap_or_default());
         self.signer = Some(signer);
         self.inner = Some(client.clone());
+        self.relays = relay_set.clone();
 
         // Connect to relays in background - don't block initialization
         // Relay connections are only needed for publishing/subscribing to Nostr events,
         // not for HTTP authentication which only requires local signing
         wasm_bindgen_futures::spawn_local(async move {
-            // Add relays (ignore errors - best effort)
-            let _ = client.add_relay("wss://relay.damus.io").await;
-            let _ = client.add_relay("wss://relay.primal.net").await;
-            client.connect().await;
+            let connector = ClientConnector(client);
+            relay_set.connect_all(&connector).await;
+            #[cfg(target_arch = "wasm32")]
+            relay_set.retry_failed(&connector, sleep_ms).await;
         });
 
         Ok(())
@@ -96,6 +120,16 @@ impl NostrClientCore {
         }
     }
 
+    /// Each configured relay's connection state and last error
+    pub fn get_relay_status(&self) -> Vec<RelayHealth> {
+        self.relays.status()
+    }
+
+    /// Set once no relay could be connected, flows relying on local signing carry on regardless
+    pub fn relay_warning(&self) -> Option<String> {
+        self.relays.warning()
+    }
+
     pub async fn sign_event(&self, unsigned: UnsignedEvent) -> Result<Event, NostrError> {
         match &self.signer {
             Some(signer) => Ok(signer.sign_event(unsigned).await?),
@@ -177,3 +211,14 @@ impl NostrClientCore {
         Ok(format!("Nostr {}", BASE64.encode(event.as_json())))
     }
 }
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep_ms(ms: u64) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        if let Some(window) = web_sys::window() {
+            let _ =
+                window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms as i32);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
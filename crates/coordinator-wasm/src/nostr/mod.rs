@@ -1,5 +1,7 @@
 mod core;
 mod password_crypto;
+mod recovery;
+mod relays;
 mod types;
 
 #[cfg(target_arch = "wasm32")]
@@ -7,6 +9,8 @@ mod wasm;
 
 pub use core::NostrClientCore;
 pub use password_crypto::*;
+pub use recovery::*;
+pub use relays::{RelayConnector, RelayHealth, RelaySet, RelayState, DEFAULT_RELAYS};
 pub use types::{CustomSigner, SignerType};
 
 use thiserror::Error;
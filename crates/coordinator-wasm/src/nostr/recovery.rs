@@ -0,0 +1,175 @@
+//! Recovery of an entry's signing material from the account's recovery key (nsec)
+//!
+//! Entries store the ephemeral private key and payout preimage NIP-44 encrypted by the account's
+//! nostr key to itself, so the nsec alone is enough to claim a payout on a new device.
+
+use nostr_sdk::{nips::nip44, FromBech32, Keys, SecretKey};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+#[derive(Error, Debug)]
+pub enum RecoveryError {
+    #[error("Invalid recovery key, expected an nsec: {0}")]
+    InvalidNsec(String),
+    #[error(
+        "Failed to decrypt the {0} with this recovery key, it may belong to another account: {1}"
+    )]
+    WrongKey(&'static str, String),
+    #[error("Decrypted {0} is not a 32 byte hex value")]
+    InvalidSecret(&'static str),
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<RecoveryError> for JsValue {
+    fn from(error: RecoveryError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+/// An entry's secrets in the hex form the wallet uses when signing and claiming payouts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntrySecrets {
+    pub ephemeral_private_key: String,
+    pub payout_preimage: String,
+}
+
+/// Decrypt an entry's `ephemeral_privatekey_encrypted` and `payout_preimage_encrypted` with the
+/// account's nsec
+pub fn decrypt_entry_secrets(
+    nsec: &str,
+    ephemeral_privatekey_encrypted: &str,
+    payout_preimage_encrypted: &str,
+) -> Result<EntrySecrets, RecoveryError> {
+    let secret_key = SecretKey::from_bech32(nsec.trim())
+        .map_err(|e| RecoveryError::InvalidNsec(e.to_string()))?;
+    let keys = Keys::new(secret_key);
+
+    let decrypt = |name: &'static str, encrypted: &str| {
+        let secret = nip44::decrypt(keys.secret_key(), &keys.public_key(), encrypted)
+            .map_err(|e| RecoveryError::WrongKey(name, e.to_string()))?;
+        match hex::decode(&secret) {
+            Ok(bytes) if bytes.len() == 32 => Ok(secret),
+            _ => Err(RecoveryError::InvalidSecret(name)),
+        }
+    };
+
+    Ok(EntrySecrets {
+        ephemeral_private_key: decrypt("ephemeral private key", ephemeral_privatekey_encrypted)?,
+        payout_preimage: decrypt("payout preimage", payout_preimage_encrypted)?,
+    })
+}
+
+/// Recover an entry's ephemeral private key and payout preimage from the recovery key, returns
+/// `{ ephemeral_private_key, payout_preimage }`
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(js_name = "recoverEntrySecrets")]
+pub fn recover_entry_secrets(
+    nsec: &str,
+    ephemeral_privatekey_encrypted: &str,
+    payout_preimage_encrypted: &str,
+) -> Result<JsValue, JsValue> {
+    let secrets = decrypt_entry_secrets(
+        nsec,
+        ephemeral_privatekey_encrypted,
+        payout_preimage_encrypted,
+    )?;
+    serde_wasm_bindgen::to_value(&secrets).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Run the coordinator's checks on an entry's keys and encrypted secrets before submitting it,
+/// rejects with the JSON `{ errors: [{ field, code, message }] }` the coordinator would answer
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(js_name = "validateEntrySecrets")]
+pub fn validate_entry_secrets(
+    ephemeral_pubkey: &str,
+    ephemeral_privatekey_encrypted: &str,
+    payout_hash: &str,
+    payout_preimage_encrypted: &str,
+) -> Result<(), JsValue> {
+    coordinator_core::validate_entry_secrets(
+        ephemeral_pubkey,
+        ephemeral_privatekey_encrypted,
+        payout_hash,
+        payout_preimage_encrypted,
+    )
+    .map_err(|errors| {
+        JsValue::from_str(&serde_json::to_string(&errors).unwrap_or_else(|e| e.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::bitcoin::hashes::{sha256, Hash};
+    use dlctix::secp::Scalar;
+    use nostr_sdk::ToBech32;
+
+    const NSEC: &str = "nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5";
+
+    fn encrypt_to_self(keys: &Keys, content: &str) -> String {
+        nip44::encrypt(
+            keys.secret_key(),
+            &keys.public_key(),
+            content,
+            nip44::Version::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_coordinator_accepts_the_entry_secrets_the_wallet_produces() {
+        let keys = Keys::parse(NSEC).unwrap();
+        let ephemeral_private_key = [3u8; 32];
+        let payout_preimage = [9u8; 32];
+        // The wallet hands the pubkey out as a dlctix point and hashes the preimage for the
+        // payout hash
+        let ephemeral_pubkey = Scalar::from_slice(&ephemeral_private_key)
+            .unwrap()
+            .base_point_mul()
+            .to_string();
+        let payout_hash = hex::encode(sha256::Hash::hash(&payout_preimage).to_byte_array());
+
+        assert!(coordinator_core::validate_entry_secrets(
+            &ephemeral_pubkey,
+            &encrypt_to_self(&keys, &hex::encode(ephemeral_private_key)),
+            &payout_hash,
+            &encrypt_to_self(&keys, &hex::encode(payout_preimage)),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_decrypt_entry_secrets_with_recovery_key() {
+        let keys = Keys::parse(NSEC).unwrap();
+        let ephemeral_private_key = hex::encode([3u8; 32]);
+        let payout_preimage = hex::encode([9u8; 32]);
+        let ephemeral_encrypted = encrypt_to_self(&keys, &ephemeral_private_key);
+        let preimage_encrypted = encrypt_to_self(&keys, &payout_preimage);
+
+        let secrets =
+            decrypt_entry_secrets(NSEC, &ephemeral_encrypted, &preimage_encrypted).unwrap();
+        assert_eq!(secrets.ephemeral_private_key, ephemeral_private_key);
+        assert_eq!(secrets.payout_preimage, payout_preimage);
+
+        let other = Keys::generate().secret_key().to_bech32().unwrap();
+        assert!(matches!(
+            decrypt_entry_secrets(&other, &ephemeral_encrypted, &preimage_encrypted),
+            Err(RecoveryError::WrongKey("ephemeral private key", _))
+        ));
+
+        let npub = keys.public_key().to_bech32().unwrap();
+        assert!(matches!(
+            decrypt_entry_secrets(&npub, &ephemeral_encrypted, &preimage_encrypted),
+            Err(RecoveryError::InvalidNsec(_))
+        ));
+
+        let not_a_key = encrypt_to_self(&keys, "hello");
+        assert!(matches!(
+            decrypt_entry_secrets(NSEC, &ephemeral_encrypted, &not_a_key),
+            Err(RecoveryError::InvalidSecret("payout preimage"))
+        ));
+    }
+}
@@ -0,0 +1,295 @@
+//! Relays the browser nostr client connects to. Each relay's connection is tracked so players
+//! behind restrictive networks can see why DMs aren't arriving, and relays that failed are
+//! retried with backoff while the others carry on.
+use async_trait::async_trait;
+use serde::Serialize;
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+/// Used when neither the coordinator nor the player picked any relays
+pub const DEFAULT_RELAYS: &[&str] = &["wss://relay.damus.io", "wss://relay.primal.net"];
+
+const INITIAL_RETRY_DELAY_MS: u64 = 2_000;
+const MAX_RETRY_DELAY_MS: u64 = 5 * 60 * 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayState {
+    Connecting,
+    Connected,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RelayHealth {
+    pub url: String,
+    pub state: RelayState,
+    pub last_error: Option<String>,
+    /// Failed connection attempts in a row
+    pub failures: u32,
+}
+
+impl RelayHealth {
+    fn new(url: String) -> Self {
+        Self {
+            url,
+            state: RelayState::Connecting,
+            last_error: None,
+            failures: 0,
+        }
+    }
+
+    /// How long to wait before the next attempt, doubling with every failure
+    pub fn retry_delay_ms(&self) -> u64 {
+        let doublings = self.failures.saturating_sub(1).min(16);
+        (INITIAL_RETRY_DELAY_MS << doublings).min(MAX_RETRY_DELAY_MS)
+    }
+}
+
+/// Opens the connection to a single relay
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait RelayConnector {
+    async fn connect(&self, url: &str) -> Result<(), String>;
+}
+
+/// The client's relays and how their connections stand, shared with the background task that
+/// connects them
+#[derive(Debug, Clone)]
+pub struct RelaySet {
+    relays: Arc<Mutex<Vec<RelayHealth>>>,
+}
+
+impl Default for RelaySet {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl RelaySet {
+    /// The relays to use, `DEFAULT_RELAYS` when none are given
+    pub fn new(urls: Vec<String>) -> Self {
+        let mut relays: Vec<RelayHealth> = Vec::new();
+        for url in urls
+            .iter()
+            .map(|url| url.trim())
+            .filter(|url| !url.is_empty())
+        {
+            if !relays.iter().any(|relay| relay.url == url) {
+                relays.push(RelayHealth::new(url.to_string()));
+            }
+        }
+        if relays.is_empty() {
+            relays = DEFAULT_RELAYS
+                .iter()
+                .map(|url| RelayHealth::new(url.to_string()))
+                .collect();
+        }
+        Self {
+            relays: Arc::new(Mutex::new(relays)),
+        }
+    }
+
+    pub fn status(&self) -> Vec<RelayHealth> {
+        self.relays.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.status().into_iter().map(|relay| relay.url).collect()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.status()
+            .iter()
+            .any(|relay| relay.state == RelayState::Connected)
+    }
+
+    /// What to tell the player once every relay has failed at least once and none is connected,
+    /// `None` while one is healthy or still on its first attempt
+    pub fn warning(&self) -> Option<String> {
+        let status = self.status();
+        if status.is_empty()
+            || status
+                .iter()
+                .any(|relay| relay.state == RelayState::Connected || relay.failures == 0)
+        {
+            return None;
+        }
+        let errors = status
+            .iter()
+            .map(|relay| {
+                format!(
+                    "{} ({})",
+                    relay.url,
+                    relay.last_error.as_deref().unwrap_or("unknown error")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(format!(
+            "Unable to connect to any nostr relay, messages from the coordinator won't arrive: {}",
+            errors
+        ))
+    }
+
+    /// Try every relay once
+    pub async fn connect_all<C: RelayConnector + ?Sized>(&self, connector: &C) {
+        for url in self.urls() {
+            self.attempt(connector, &url).await;
+        }
+    }
+
+    /// Keep retrying the relays that failed, waiting out each one's backoff with `sleep`,
+    /// until every relay is connected
+    pub async fn retry_failed<C, S, F>(&self, connector: &C, sleep: S)
+    where
+        C: RelayConnector + ?Sized,
+        S: Fn(u64) -> F,
+        F: Future<Output = ()>,
+    {
+        loop {
+            let failed: Vec<RelayHealth> = self
+                .status()
+                .into_iter()
+                .filter(|relay| relay.state == RelayState::Failed)
+                .collect();
+            let Some(delay_ms) = failed.iter().map(RelayHealth::retry_delay_ms).min() else {
+                return;
+            };
+            sleep(delay_ms).await;
+            for relay in failed
+                .iter()
+                .filter(|relay| relay.retry_delay_ms() <= delay_ms)
+            {
+                self.attempt(connector, &relay.url).await;
+            }
+        }
+    }
+
+    async fn attempt<C: RelayConnector + ?Sized>(&self, connector: &C, url: &str) {
+        self.update(url, |relay| relay.state = RelayState::Connecting);
+        let result = connector.connect(url).await;
+        self.update(url, |relay| match result {
+            Ok(()) => {
+                relay.state = RelayState::Connected;
+                relay.failures = 0;
+            }
+            Err(e) => {
+                log::warn!("Failed to connect to relay {}: {}", relay.url, e);
+                relay.state = RelayState::Failed;
+                relay.last_error = Some(e);
+                relay.failures += 1;
+            }
+        });
+    }
+
+    fn update(&self, url: &str, f: impl FnOnce(&mut RelayHealth)) {
+        if let Ok(mut relays) = self.relays.lock() {
+            if let Some(relay) = relays.iter_mut().find(|relay| relay.url == url) {
+                f(relay);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relays_default_when_none_are_configured() {
+        let relays = RelaySet::new(vec![" ".to_string()]);
+        assert_eq!(relays.urls(), DEFAULT_RELAYS);
+
+        let relays = RelaySet::new(vec![
+            "wss://relay.example".to_string(),
+            "wss://relay.example".to_string(),
+        ]);
+        assert_eq!(relays.urls(), vec!["wss://relay.example"]);
+        assert!(!relays.is_healthy());
+        assert_eq!(relays.warning(), None);
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_up_to_the_cap() {
+        let mut relay = RelayHealth::new("wss://relay.example".to_string());
+        relay.failures = 1;
+        assert_eq!(relay.retry_delay_ms(), 2_000);
+        relay.failures = 3;
+        assert_eq!(relay.retry_delay_ms(), 8_000);
+        relay.failures = 40;
+        assert_eq!(relay.retry_delay_ms(), MAX_RETRY_DELAY_MS);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    mod wasm {
+        use super::super::*;
+        use std::cell::{Cell, RefCell};
+        use wasm_bindgen_test::wasm_bindgen_test;
+
+        const GOOD: &str = "wss://good.relay.example";
+        const BLOCKED: &str = "wss://blocked.relay.example";
+
+        /// Refuses connections to `BLOCKED` until it is unblocked
+        struct MockRelays {
+            blocked: Cell<bool>,
+        }
+
+        #[async_trait(?Send)]
+        impl RelayConnector for MockRelays {
+            async fn connect(&self, url: &str) -> Result<(), String> {
+                if url == BLOCKED && self.blocked.get() {
+                    return Err("connection refused".to_string());
+                }
+                Ok(())
+            }
+        }
+
+        #[wasm_bindgen_test]
+        async fn test_blocked_relay_is_reported_and_retried() {
+            let mock = MockRelays {
+                blocked: Cell::new(true),
+            };
+            let relays = RelaySet::new(vec![GOOD.to_string(), BLOCKED.to_string()]);
+            relays.connect_all(&mock).await;
+
+            let status = relays.status();
+            assert_eq!(status[0].state, RelayState::Connected);
+            assert_eq!(status[1].state, RelayState::Failed);
+            assert_eq!(status[1].last_error.as_deref(), Some("connection refused"));
+            // One healthy relay is enough to carry on
+            assert!(relays.is_healthy());
+            assert_eq!(relays.warning(), None);
+
+            mock.blocked.set(false);
+            let waits = RefCell::new(Vec::new());
+            relays
+                .retry_failed(&mock, |ms| {
+                    waits.borrow_mut().push(ms);
+                    async {}
+                })
+                .await;
+            assert_eq!(*waits.borrow(), vec![2_000]);
+            assert!(relays
+                .status()
+                .iter()
+                .all(|relay| relay.state == RelayState::Connected));
+        }
+
+        #[wasm_bindgen_test]
+        async fn test_warning_when_every_relay_failed() {
+            let mock = MockRelays {
+                blocked: Cell::new(true),
+            };
+            let relays = RelaySet::new(vec![BLOCKED.to_string()]);
+            relays.connect_all(&mock).await;
+
+            assert!(!relays.is_healthy());
+            let warning = relays.warning().expect("no relay is connected");
+            assert!(warning.contains(BLOCKED));
+            assert!(warning.contains("connection refused"));
+        }
+    }
+}
@@ -30,14 +30,17 @@ impl NostrClientWrapper {
         self.inner.signer.is_some()
     }
 
+    /// `relays` usually come from the coordinator's `/api/v1/info` or the player's own choice,
+    /// the default relays are used when it is empty
     #[wasm_bindgen]
     pub async fn initialize(
         &mut self,
         signer_type: SignerType,
         private_key: Option<String>,
+        relays: Option<Vec<String>>,
     ) -> Result<(), JsValue> {
         self.inner
-            .initialize(signer_type, private_key)
+            .initialize(signer_type, private_key, relays)
             .await
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
@@ -80,6 +83,18 @@ impl NostrClientWrapper {
             .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
     }
 
+    /// `[{ url, state, last_error, failures }]` with `state` one of connecting, connected or failed
+    #[wasm_bindgen(js_name = "getRelayStatus")]
+    pub fn get_relay_status(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.inner.get_relay_status())
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    }
+
+    #[wasm_bindgen(js_name = "relayWarning")]
+    pub fn relay_warning(&self) -> Option<String> {
+        self.inner.relay_warning()
+    }
+
     #[wasm_bindgen(js_name = "signEvent")]
     pub async fn sign_event(&self, event_json: &str) -> Result<String, JsValue> {
         let unsigned: UnsignedEvent = serde_json::from_str(event_json)
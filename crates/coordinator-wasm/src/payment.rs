@@ -0,0 +1,114 @@
+//! Checks on the ticket invoice the coordinator hands out, before the player is asked to pay it
+
+use coordinator_core::{InvoiceMemo, Sats};
+use serde::Deserialize;
+use thiserror::Error;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+#[derive(Error, Debug)]
+pub enum PaymentError {
+    #[error("Invalid ticket json: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("Invalid invoice memo: {0}")]
+    InvalidMemo(String),
+    #[error("Invoice memo is for ticket {0} in competition {1}, not this ticket")]
+    WrongTicket(String, String),
+    #[error("Entry fee {0} plus coordinator fee {1} doesn't add up to the invoice's {2} sats")]
+    AmountMismatch(u64, u64, u64),
+}
+
+/// The parts of the coordinator's `TicketResponse` the checks need
+#[derive(Debug, Deserialize)]
+struct TicketInvoice {
+    ticket_id: String,
+    amount_sats: Sats,
+    entry_fee: Sats,
+    coordinator_fee: Sats,
+    invoice_memo: String,
+}
+
+/// Check a ticket response from `/competitions/{competition_id}/ticket` or
+/// `/tickets/{ticket_id}/invoice`: its memo names this competition and ticket, and the fee
+/// breakdown adds up to the invoiced amount. Returns the parsed memo.
+pub fn check_ticket_invoice_json(
+    ticket_json: &str,
+    competition_id: &str,
+) -> Result<InvoiceMemo, PaymentError> {
+    let ticket: TicketInvoice = serde_json::from_str(ticket_json)?;
+    let memo = ticket
+        .invoice_memo
+        .parse::<InvoiceMemo>()
+        .map_err(|e| PaymentError::InvalidMemo(e.to_string()))?;
+    if !memo.matches(competition_id, &ticket.ticket_id) {
+        return Err(PaymentError::WrongTicket(memo.ticket_id, memo.competition));
+    }
+    if ticket.entry_fee.checked_add(ticket.coordinator_fee) != Some(ticket.amount_sats) {
+        return Err(PaymentError::AmountMismatch(
+            ticket.entry_fee.to_sat(),
+            ticket.coordinator_fee.to_sat(),
+            ticket.amount_sats.to_sat(),
+        ));
+    }
+    Ok(memo)
+}
+
+/// Check the ticket json the coordinator returned for `competition_id` before showing its
+/// invoice, returns the memo `{ coordinator, competition, ticket_id, escrow_txid }`
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(js_name = "checkTicketInvoice")]
+pub fn check_ticket_invoice(ticket_json: &str, competition_id: &str) -> Result<JsValue, JsValue> {
+    let memo = check_ticket_invoice_json(ticket_json, competition_id)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_wasm_bindgen::to_value(&memo).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPETITION_ID: &str = "0191d2a4-7c3b-7e21-9a3f-5b8c2d1e0f47";
+    const TICKET_ID: &str = "0191d2a5-0a11-7b42-8c9d-1e2f3a4b5c6d";
+
+    fn ticket_json(ticket_id: &str, coordinator_fee: u64) -> String {
+        let memo = InvoiceMemo::new("coordinator", COMPETITION_ID, TICKET_ID, None);
+        serde_json::json!({
+            "ticket_id": ticket_id,
+            "payment_request": "lnbcrt11u1...",
+            "payment_hash": "ab".repeat(32),
+            "amount_sats": 1100,
+            "entry_fee": 1000,
+            "coordinator_fee": coordinator_fee,
+            "expires_at": "2026-10-17T12:10:00Z",
+            "invoice_memo": memo.to_string(),
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_ticket_invoice_must_be_for_this_ticket() {
+        let memo = check_ticket_invoice_json(&ticket_json(TICKET_ID, 100), COMPETITION_ID).unwrap();
+        assert_eq!(memo.ticket_id, TICKET_ID);
+        assert_eq!(memo.coordinator, "coordinator");
+
+        assert!(matches!(
+            check_ticket_invoice_json(
+                &ticket_json(TICKET_ID, 100),
+                "0191d2a5-7c3b-7e21-9a3f-5b8c2d1e0f47"
+            ),
+            Err(PaymentError::WrongTicket(_, _))
+        ));
+        assert!(matches!(
+            check_ticket_invoice_json(
+                &ticket_json("0191d2a5-0a11-7b42-8c9d-000000000000", 100),
+                COMPETITION_ID
+            ),
+            Err(PaymentError::WrongTicket(_, _))
+        ));
+        assert!(matches!(
+            check_ticket_invoice_json(&ticket_json(TICKET_ID, 200), COMPETITION_ID),
+            Err(PaymentError::AmountMismatch(1000, 200, 1100))
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+//! Verification of the receipts the coordinator signs for ticket payments and accepted entries
+
+use coordinator_core::{verify_receipt_signature, ReceiptError, SignedReceipt};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Check `receipt_json` was signed by one of `coordinator_pubkeys`, the hex pubkeys served at
+/// the coordinator's `/api/v1/info`. Returns the signed payload json.
+pub fn verify_receipt_json(
+    receipt_json: &str,
+    coordinator_pubkeys: &[String],
+) -> Result<String, ReceiptError> {
+    let receipt: SignedReceipt = serde_json::from_str(receipt_json)?;
+    let payload = verify_receipt_signature(&receipt, coordinator_pubkeys)?;
+    serde_json::from_str::<serde_json::Value>(payload)?;
+    Ok(payload.to_string())
+}
+
+/// Verify a receipt from the coordinator against its `/api/v1/info` `receipt_pubkeys`,
+/// returning the payload json on success
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(js_name = "verifyReceipt")]
+pub fn verify_receipt(
+    receipt_json: &str,
+    coordinator_pubkeys: Vec<String>,
+) -> Result<String, JsValue> {
+    verify_receipt_json(receipt_json, &coordinator_pubkeys)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::bitcoin::secp256k1::{Keypair, Secp256k1, SecretKey};
+    use coordinator_core::receipt_message;
+
+    fn signed_receipt(payload: &str) -> SignedReceipt {
+        let secp = Secp256k1::new();
+        let keypair = Keypair::from_secret_key(&secp, &SecretKey::from_slice(&[7u8; 32]).unwrap());
+        SignedReceipt {
+            payload: payload.to_string(),
+            pubkey: keypair.x_only_public_key().0.to_string(),
+            signature: secp
+                .sign_schnorr_no_aux_rand(&receipt_message(payload), &keypair)
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn test_verify_receipt_rejects_tampered_payload() {
+        let receipt = signed_receipt(r#"{"kind":"ticket_payment","amount_sats":1100}"#);
+        let coordinator_pubkeys = vec![receipt.pubkey.clone()];
+
+        let json = serde_json::to_string(&receipt).unwrap();
+        assert_eq!(
+            verify_receipt_json(&json, &coordinator_pubkeys).unwrap(),
+            receipt.payload
+        );
+
+        let mut tampered = receipt.clone();
+        tampered.payload = tampered.payload.replace("1100", "9100");
+        let json = serde_json::to_string(&tampered).unwrap();
+        assert!(matches!(
+            verify_receipt_json(&json, &coordinator_pubkeys),
+            Err(ReceiptError::Signature(_))
+        ));
+
+        // A receipt is only as good as the keys it is checked against
+        let json = serde_json::to_string(&receipt).unwrap();
+        assert!(matches!(
+            verify_receipt_json(&json, &["02".repeat(32)]),
+            Err(ReceiptError::WrongSigner(_))
+        ));
+    }
+}
@@ -1,17 +1,22 @@
-use super::{DlcEntryData, KeymeldRegistrationData, WalletError};
+use super::{
+    DlcEntryData, DlcEntrySummary, KeymeldRegistrationData, KeymeldRegistrationRequest,
+    KeymeldRegistrationResult, WalletError,
+};
 use crate::NostrClientCore;
 use bdk_wallet::{
     bitcoin::{
         bip32::{ChainCode, ChildNumber, DerivationPath},
         ecdsa,
         hashes::{sha256, Hash},
-        secp256k1::{Message, Secp256k1 as BdkSecp256k1, SecretKey},
+        psbt::Input as PsbtInput,
+        secp256k1::{All, Message, Secp256k1 as BdkSecp256k1, SecretKey},
         sighash::{EcdsaSighashType, SighashCache},
-        Network, NetworkKind as BDKNetworkKind, Psbt, PublicKey,
+        Network, NetworkKind as BDKNetworkKind, PrivateKey, Psbt, PublicKey, Transaction,
     },
     descriptor::calc_checksum,
 };
 use blake2::{Blake2b512, Digest};
+use coordinator_core::BitcoinNetwork;
 use dlctix::{
     bitcoin::{bip32::Xpriv, OutPoint},
     musig2::{AggNonce, PartialSignature},
@@ -19,6 +24,7 @@ use dlctix::{
     ContractParameters, EventLockingConditions, NonceSharingRound, Outcome, SigMap, SigningSession,
     TicketedDLC,
 };
+use futures::future::join_all;
 use log::debug;
 use nostr_sdk::{FromBech32, NostrSigner};
 use rand::{rng, RngCore, SeedableRng};
@@ -56,6 +62,12 @@ impl From<Network> for NetworkKind {
     }
 }
 
+impl From<BitcoinNetwork> for NetworkKind {
+    fn from(n: BitcoinNetwork) -> Self {
+        NetworkKind::from(Network::from(n))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct KeyPair {
     pub descriptor: String,
@@ -118,8 +130,9 @@ impl TaprootWalletCoreBuilder {
             .nostr_client
             .ok_or_else(|| WalletError::SignerError("NostrClient is required".into()))?;
 
-        let network =
-            Network::from_str(&network).map_err(|e| WalletError::NetworkError(e.to_string()))?;
+        let network = BitcoinNetwork::from_str(&network)
+            .map(Network::from)
+            .map_err(|e| WalletError::NetworkError(e.to_string()))?;
 
         match self.encrypted_key {
             Some(encrypted) => {
@@ -332,6 +345,32 @@ impl TaprootWalletCore {
         })
     }
 
+    /// Prepare keymeld registration data for several entries at once, ie. a user entering a
+    /// number of competitions. The sessions are prepared concurrently and each request gets its
+    /// own result in the same order, a failing entry reports its error without affecting the rest.
+    pub async fn prepare_keymeld_registrations(
+        &self,
+        requests: &[KeymeldRegistrationRequest],
+    ) -> Vec<KeymeldRegistrationResult> {
+        join_all(requests.iter().map(|request| async move {
+            let (registration, error) = match self.prepare_keymeld_registration(
+                request.entry_index,
+                &request.enclave_pubkey_hex,
+                &request.session_id,
+            ) {
+                Ok(registration) => (Some(registration), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            KeymeldRegistrationResult {
+                entry_index: request.entry_index,
+                session_id: request.session_id.clone(),
+                registration,
+                error,
+            }
+        }))
+        .await
+    }
+
     pub async fn get_encrypted_dlc_payout_preimage(
         &self,
         entry_index: u32,
@@ -419,6 +458,36 @@ impl TaprootWalletCore {
         Some(entry.data.clone())
     }
 
+    /// Indices of all entries this wallet has signing material for, in ascending order
+    pub fn list_dlc_entry_indices(&self) -> Vec<u32> {
+        let mut indices: Vec<u32> = self.dlc_contracts.keys().copied().collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    pub fn get_dlc_entry_summary(&self, entry_index: u32) -> Result<DlcEntrySummary, WalletError> {
+        let entry = self
+            .dlc_contracts
+            .get(&entry_index)
+            .ok_or(WalletError::DlcEntryNotFound(entry_index))?;
+
+        Ok(DlcEntrySummary {
+            entry_index,
+            funding_outpoint: entry.data.funding_outpoint,
+            contract_loaded: entry.contract.is_some(),
+            has_payout_preimage: !entry.data.payout_preimage.expose_secret().is_empty(),
+            has_ticket_preimage: entry.data.ticket_preimage.is_some(),
+        })
+    }
+
+    /// Drop a stale entry (ie. competition completed or cancelled) and its signing material
+    pub fn remove_dlc_entry(&mut self, entry_index: u32) -> Result<(), WalletError> {
+        self.dlc_contracts
+            .remove(&entry_index)
+            .map(|_| ())
+            .ok_or(WalletError::DlcEntryNotFound(entry_index))
+    }
+
     pub fn add_contract(
         &mut self,
         entry_index: u32,
@@ -548,100 +617,9 @@ impl TaprootWalletCore {
 
         // Process each input in the PSBT
         for (input_index, input) in psbt.inputs.iter_mut().enumerate() {
-            // Check if this is an escrow input by looking for witness_script
-            let witness_script = match &input.witness_script {
-                Some(ws) => ws,
-                None => {
-                    debug!(
-                        "Input {} is not an escrow input (no witness_script), skipping",
-                        input_index
-                    );
-                    continue;
-                }
-            };
-
-            // Ensure we have the witness UTXO for signing
-            let witness_utxo = input.witness_utxo.as_ref().ok_or_else(|| {
-                WalletError::KeyError(format!(
-                    "Missing witness_utxo for escrow input {}",
-                    input_index
-                ))
-            })?;
-
-            // Check if we already have a signature from this key
-            if input.partial_sigs.contains_key(&child_pubkey) {
-                debug!("Input {} already has our signature, skipping", input_index);
-                continue;
-            }
-
-            // For miniscript escrow, verify our key is actually needed
-            // Simple verification: check if our public key bytes appear in the witness script
-            let script_bytes = witness_script.as_bytes();
-            let pubkey_bytes = child_pubkey.to_bytes();
-
-            let key_found = script_bytes
-                .windows(pubkey_bytes.len())
-                .any(|window| window == pubkey_bytes);
-
-            if !key_found {
-                debug!(
-                    "Our pubkey {} not found in witness script for input {}, skipping",
-                    child_pubkey, input_index
-                );
-                continue;
+            if sign_escrow_input(&secp, &tx, input_index, input, &child_privkey)? {
+                signed_count += 1;
             }
-
-            debug!(
-                "Found our key in escrow input {}, creating signature",
-                input_index
-            );
-
-            // Get the sighash type from PSBT or default to ALL
-            let sighash_type = match input.sighash_type {
-                Some(psbt_type) => {
-                    EcdsaSighashType::from_standard(psbt_type.to_u32()).map_err(|_| {
-                        WalletError::KeyError("Invalid sighash type in PSBT".to_string())
-                    })?
-                }
-                None => EcdsaSighashType::All,
-            };
-
-            // Create a sighash cache for the transaction
-            let mut sighash_cache = SighashCache::new(&tx);
-
-            // Compute the signature hash
-            let sighash = sighash_cache
-                .p2wsh_signature_hash(
-                    input_index,
-                    witness_script,
-                    witness_utxo.value,
-                    sighash_type,
-                )
-                .map_err(|e| {
-                    WalletError::KeyError(format!(
-                        "Failed to compute sighash for input {}: {}",
-                        input_index, e
-                    ))
-                })?;
-
-            // Create the ECDSA signature
-            let message = Message::from_digest(sighash.to_byte_array());
-            let signature = secp.sign_ecdsa(&message, &child_privkey.inner);
-
-            // Create ecdsa::Signature with the signature and sighash type
-            let ecdsa_sig = ecdsa::Signature {
-                signature,
-                sighash_type,
-            };
-
-            input.partial_sigs.insert(child_pubkey, ecdsa_sig);
-            signed_count += 1;
-
-            debug!(
-                "Added signature for escrow input {} (witness script: {} bytes)",
-                input_index,
-                witness_script.len()
-            );
         }
 
         match signed_count {
@@ -699,3 +677,403 @@ impl TaprootWalletCore {
         Ok(outcome)
     }
 }
+
+/// Sign the funding PSBT with an entry's ephemeral key, restricted to the input spending
+/// `funding_outpoint`. Any other input is left untouched even if it's locked to the same key,
+/// so a PSBT built by someone else can't get the player to sign away unrelated coins.
+pub fn sign_funding_psbt_for_outpoint(
+    mut psbt: Psbt,
+    ephemeral_private_key_hex: &str,
+    funding_outpoint: OutPoint,
+) -> Result<Psbt, WalletError> {
+    let secret_key = SecretKey::from_str(ephemeral_private_key_hex)
+        .map_err(|e| WalletError::KeyError(format!("Invalid ephemeral private key: {}", e)))?;
+    // The network only matters for WIF encoding, which this key never goes through
+    let privkey = PrivateKey::new(secret_key, BDKNetworkKind::Main);
+    let secp = BdkSecp256k1::new();
+
+    let mut matching = psbt
+        .unsigned_tx
+        .input
+        .iter()
+        .enumerate()
+        .filter(|(_, txin)| txin.previous_output == funding_outpoint)
+        .map(|(index, _)| index);
+    let input_index = matching
+        .next()
+        .ok_or(WalletError::FundingOutpointNotFound(funding_outpoint))?;
+    if matching.next().is_some() {
+        return Err(WalletError::InvalidPsbt(format!(
+            "Outpoint {} is spent by more than one input",
+            funding_outpoint
+        )));
+    }
+
+    let tx = psbt.unsigned_tx.clone();
+    let input = psbt
+        .inputs
+        .get_mut(input_index)
+        .ok_or_else(|| WalletError::InvalidPsbt(format!("Missing PSBT input {}", input_index)))?;
+    if input.witness_script.is_none() {
+        return Err(WalletError::InvalidPsbt(format!(
+            "Input spending {} is not an escrow input",
+            funding_outpoint
+        )));
+    }
+
+    let already_signed = input
+        .partial_sigs
+        .contains_key(&PublicKey::from_private_key(&secp, &privkey));
+    if !sign_escrow_input(&secp, &tx, input_index, input, &privkey)? && !already_signed {
+        return Err(WalletError::InvalidPsbt(format!(
+            "Ephemeral key is not part of the escrow spent by {}",
+            funding_outpoint
+        )));
+    }
+
+    debug!(
+        "Signed funding PSBT input {} spending {}",
+        input_index, funding_outpoint
+    );
+    Ok(psbt)
+}
+
+/// Add an ECDSA signature to a P2WSH escrow input when `privkey` appears in its witness script,
+/// returning whether a new signature was added
+fn sign_escrow_input(
+    secp: &BdkSecp256k1<All>,
+    tx: &Transaction,
+    input_index: usize,
+    input: &mut PsbtInput,
+    privkey: &PrivateKey,
+) -> Result<bool, WalletError> {
+    let pubkey = PublicKey::from_private_key(secp, privkey);
+
+    // Check if this is an escrow input by looking for witness_script
+    let witness_script = match &input.witness_script {
+        Some(ws) => ws,
+        None => {
+            debug!(
+                "Input {} is not an escrow input (no witness_script), skipping",
+                input_index
+            );
+            return Ok(false);
+        }
+    };
+
+    // Ensure we have the witness UTXO for signing
+    let witness_utxo = input.witness_utxo.as_ref().ok_or_else(|| {
+        WalletError::KeyError(format!(
+            "Missing witness_utxo for escrow input {}",
+            input_index
+        ))
+    })?;
+
+    // Check if we already have a signature from this key
+    if input.partial_sigs.contains_key(&pubkey) {
+        debug!("Input {} already has our signature, skipping", input_index);
+        return Ok(false);
+    }
+
+    // For miniscript escrow, verify our key is actually needed
+    // Simple verification: check if our public key bytes appear in the witness script
+    let script_bytes = witness_script.as_bytes();
+    let pubkey_bytes = pubkey.to_bytes();
+
+    let key_found = script_bytes
+        .windows(pubkey_bytes.len())
+        .any(|window| window == pubkey_bytes);
+
+    if !key_found {
+        debug!(
+            "Our pubkey {} not found in witness script for input {}, skipping",
+            pubkey, input_index
+        );
+        return Ok(false);
+    }
+
+    debug!(
+        "Found our key in escrow input {}, creating signature",
+        input_index
+    );
+
+    // Only SIGHASH_ALL commits to every input and output, anything else would let whoever built
+    // the PSBT change the rest of the transaction after the player signed
+    if let Some(psbt_type) = input.sighash_type {
+        if !matches!(
+            EcdsaSighashType::from_standard(psbt_type.to_u32()),
+            Ok(EcdsaSighashType::All)
+        ) {
+            return Err(WalletError::InvalidPsbt(format!(
+                "Escrow input {} asks for sighash type {}, only SIGHASH_ALL is signed",
+                input_index, psbt_type
+            )));
+        }
+    }
+    let sighash_type = EcdsaSighashType::All;
+
+    // Create a sighash cache for the transaction
+    let mut sighash_cache = SighashCache::new(tx);
+
+    // Compute the signature hash
+    let sighash = sighash_cache
+        .p2wsh_signature_hash(
+            input_index,
+            witness_script,
+            witness_utxo.value,
+            sighash_type,
+        )
+        .map_err(|e| {
+            WalletError::KeyError(format!(
+                "Failed to compute sighash for input {}: {}",
+                input_index, e
+            ))
+        })?;
+
+    // Create the ECDSA signature
+    let message = Message::from_digest(sighash.to_byte_array());
+    let signature = secp.sign_ecdsa(&message, &privkey.inner);
+
+    // Create ecdsa::Signature with the signature and sighash type
+    let ecdsa_sig = ecdsa::Signature {
+        signature,
+        sighash_type,
+    };
+
+    debug!(
+        "Added signature for escrow input {} (witness script: {} bytes)",
+        input_index,
+        witness_script.len()
+    );
+    input.partial_sigs.insert(pubkey, ecdsa_sig);
+
+    Ok(true)
+}
+
+/// Split an entry's partial signatures into chunks of at most `outcomes_per_chunk` outcomes for
+/// the coordinator's `/partial_signatures` endpoint, each outcome's win conditions travel with it
+pub fn chunk_partial_signatures(
+    partial_signatures: SigMap<PartialSignature>,
+    outcomes_per_chunk: usize,
+) -> Vec<SigMap<PartialSignature>> {
+    let outcomes: Vec<Outcome> = partial_signatures.by_outcome.keys().copied().collect();
+    outcomes
+        .chunks(outcomes_per_chunk.max(1))
+        .map(|outcomes| SigMap {
+            by_outcome: outcomes
+                .iter()
+                .filter_map(|outcome| {
+                    partial_signatures
+                        .by_outcome
+                        .get(outcome)
+                        .map(|signature| (*outcome, *signature))
+                })
+                .collect(),
+            by_win_condition: partial_signatures
+                .by_win_condition
+                .iter()
+                .filter(|(win_condition, _)| outcomes.contains(&win_condition.outcome))
+                .map(|(win_condition, signature)| (*win_condition, *signature))
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::bitcoin::{
+        absolute::LockTime, opcodes::all::OP_CHECKSIG, transaction::Version, Amount, ScriptBuf,
+        Sequence, TxIn, TxOut, Txid, Witness,
+    };
+
+    const EPHEMERAL_KEY: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+    fn outpoint(byte: u8) -> OutPoint {
+        OutPoint {
+            txid: Txid::from_byte_array([byte; 32]),
+            vout: 0,
+        }
+    }
+
+    /// Funding PSBT spending two escrows that are both locked to `pubkey`
+    fn funding_psbt(pubkey: &PublicKey) -> Psbt {
+        let witness_script = ScriptBuf::builder()
+            .push_key(pubkey)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: [outpoint(1), outpoint(2)]
+                .into_iter()
+                .map(|previous_output| TxIn {
+                    previous_output,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(19_000),
+                script_pubkey: ScriptBuf::new_p2wsh(&witness_script.wscript_hash()),
+            }],
+        };
+
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for input in psbt.inputs.iter_mut() {
+            input.witness_script = Some(witness_script.clone());
+            input.witness_utxo = Some(TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::new_p2wsh(&witness_script.wscript_hash()),
+            });
+        }
+        psbt
+    }
+
+    fn ephemeral_pubkey() -> PublicKey {
+        let secret_key = SecretKey::from_str(EPHEMERAL_KEY).unwrap();
+        PublicKey::from_private_key(
+            &BdkSecp256k1::new(),
+            &PrivateKey::new(secret_key, BDKNetworkKind::Main),
+        )
+    }
+
+    #[test]
+    fn test_keymeld_registrations_report_each_session() {
+        // The secp256k1 generator, any valid compressed point works as an enclave key
+        const ENCLAVE_KEY: &str =
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let wallet =
+            TaprootWalletCore::create_new(&NostrClientCore::new(), Network::Regtest).unwrap();
+        let request = |entry_index: u32, enclave_pubkey_hex: &str| KeymeldRegistrationRequest {
+            entry_index,
+            session_id: format!("session-{}", entry_index),
+            enclave_pubkey_hex: enclave_pubkey_hex.to_string(),
+        };
+        let requests = vec![
+            request(0, ENCLAVE_KEY),
+            request(1, "not an enclave key"),
+            request(2, ENCLAVE_KEY),
+        ];
+
+        let results = futures::executor::block_on(wallet.prepare_keymeld_registrations(&requests));
+        assert_eq!(results.len(), 3);
+        for (result, request) in results.iter().zip(&requests) {
+            assert_eq!(result.entry_index, request.entry_index);
+            assert_eq!(result.session_id, request.session_id);
+        }
+
+        // The bad enclave key only fails its own session
+        assert!(results[1].registration.is_none());
+        assert!(results[1].error.is_some());
+        for result in [&results[0], &results[2]] {
+            assert_eq!(result.error, None);
+            let registration = result.registration.as_ref().unwrap();
+            let expected = wallet
+                .prepare_keymeld_registration(result.entry_index, ENCLAVE_KEY, &result.session_id)
+                .unwrap();
+            assert_eq!(registration.auth_pubkey, expected.auth_pubkey);
+            assert!(!registration.encrypted_private_key.is_empty());
+        }
+        assert_ne!(
+            results[0].registration.as_ref().unwrap().auth_pubkey,
+            results[2].registration.as_ref().unwrap().auth_pubkey
+        );
+    }
+
+    #[test]
+    fn test_dlc_entries_are_listed_summarized_and_removed() {
+        let mut wallet =
+            TaprootWalletCore::create_new(&NostrClientCore::new(), Network::Regtest).unwrap();
+        assert!(wallet.list_dlc_entry_indices().is_empty());
+
+        for entry_index in [7, 2, 4] {
+            wallet.add_entry_index(entry_index).unwrap();
+        }
+        assert_eq!(wallet.list_dlc_entry_indices(), vec![2, 4, 7]);
+
+        // A fresh entry only has its payout preimage, nothing to sign with yet
+        let summary = wallet.get_dlc_entry_summary(4).unwrap();
+        assert_eq!(summary.entry_index, 4);
+        assert_eq!(summary.funding_outpoint, None);
+        assert!(!summary.contract_loaded);
+        assert!(summary.has_payout_preimage);
+        assert!(!summary.has_ticket_preimage);
+
+        wallet.remove_dlc_entry(4).unwrap();
+        assert_eq!(wallet.list_dlc_entry_indices(), vec![2, 7]);
+        assert!(matches!(
+            wallet.get_dlc_entry_summary(4),
+            Err(WalletError::DlcEntryNotFound(4))
+        ));
+        assert!(matches!(
+            wallet.remove_dlc_entry(4),
+            Err(WalletError::DlcEntryNotFound(4))
+        ));
+    }
+
+    #[test]
+    fn test_sign_funding_psbt_only_signs_the_funding_outpoint() {
+        let pubkey = ephemeral_pubkey();
+        let psbt = funding_psbt(&pubkey);
+
+        let signed = sign_funding_psbt_for_outpoint(psbt, EPHEMERAL_KEY, outpoint(2)).unwrap();
+        assert!(signed.inputs[0].partial_sigs.is_empty());
+        assert!(signed.inputs[1].partial_sigs.contains_key(&pubkey));
+
+        // Signing again is a no-op rather than an error
+        let resigned =
+            sign_funding_psbt_for_outpoint(signed.clone(), EPHEMERAL_KEY, outpoint(2)).unwrap();
+        assert_eq!(resigned, signed);
+    }
+
+    #[test]
+    fn test_sign_funding_psbt_rejects_unexpected_psbts() {
+        let pubkey = ephemeral_pubkey();
+
+        assert!(matches!(
+            sign_funding_psbt_for_outpoint(funding_psbt(&pubkey), EPHEMERAL_KEY, outpoint(3)),
+            Err(WalletError::FundingOutpointNotFound(missing)) if missing == outpoint(3)
+        ));
+
+        // Escrow locked to someone else's key
+        let other_key = PublicKey::from_private_key(
+            &BdkSecp256k1::new(),
+            &PrivateKey::new(
+                SecretKey::from_slice(&[2; 32]).unwrap(),
+                BDKNetworkKind::Main,
+            ),
+        );
+        assert!(matches!(
+            sign_funding_psbt_for_outpoint(funding_psbt(&other_key), EPHEMERAL_KEY, outpoint(1)),
+            Err(WalletError::InvalidPsbt(_))
+        ));
+
+        assert!(matches!(
+            sign_funding_psbt_for_outpoint(funding_psbt(&pubkey), "not hex", outpoint(1)),
+            Err(WalletError::KeyError(_))
+        ));
+
+        // Signatures that don't commit to the whole transaction
+        for sighash_type in [
+            EcdsaSighashType::Single,
+            EcdsaSighashType::None,
+            EcdsaSighashType::AllPlusAnyoneCanPay,
+        ] {
+            let mut psbt = funding_psbt(&pubkey);
+            psbt.inputs[0].sighash_type = Some(sighash_type.into());
+            assert!(matches!(
+                sign_funding_psbt_for_outpoint(psbt, EPHEMERAL_KEY, outpoint(1)),
+                Err(WalletError::InvalidPsbt(_))
+            ));
+        }
+        let mut psbt = funding_psbt(&pubkey);
+        psbt.inputs[0].sighash_type = Some(EcdsaSighashType::All.into());
+        let signed = sign_funding_psbt_for_outpoint(psbt, EPHEMERAL_KEY, outpoint(1)).unwrap();
+        assert_eq!(
+            signed.inputs[0].partial_sigs[&pubkey].sighash_type,
+            EcdsaSighashType::All
+        );
+    }
+}
@@ -12,10 +12,10 @@ use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-pub use core::{TaprootWalletCore, TaprootWalletCoreBuilder};
+pub use core::{sign_funding_psbt_for_outpoint, TaprootWalletCore, TaprootWalletCoreBuilder};
 
 #[cfg(target_arch = "wasm32")]
-pub use wasm::{TaprootWallet, TaprootWalletBuilder};
+pub use wasm::{sign_funding_psbt, TaprootWallet, TaprootWalletBuilder};
 
 #[derive(Error, Debug)]
 pub enum WalletError {
@@ -55,6 +55,10 @@ pub enum WalletError {
     DlcEntryNotFound(u32),
     #[error("Invalid escrow descriptor: {0}")]
     InvalidEscrow(String),
+    #[error("Funding outpoint {0} is not spent by the PSBT")]
+    FundingOutpointNotFound(OutPoint),
+    #[error("Invalid PSBT: {0}")]
+    InvalidPsbt(String),
     #[error("Psbt error: {0}")]
     PsbtError(#[from] PsbtParseError),
     #[error("Invalid BOLT11 invoice: {0}")]
@@ -75,6 +79,25 @@ pub struct KeymeldRegistrationData {
     pub auth_pubkey: String,
 }
 
+/// One entry of a batch of keymeld registrations, the entry's key is derived from the wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeymeldRegistrationRequest {
+    pub entry_index: u32,
+    /// The keymeld keygen session the entry's competition uses
+    pub session_id: String,
+    pub enclave_pubkey_hex: String,
+}
+
+/// Outcome for one entry of a batch, `registration` on success and `error` otherwise, so one
+/// bad entry doesn't fail the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeymeldRegistrationResult {
+    pub entry_index: u32,
+    pub session_id: String,
+    pub registration: Option<KeymeldRegistrationData>,
+    pub error: Option<String>,
+}
+
 #[cfg(target_arch = "wasm32")]
 impl From<WalletError> for wasm_bindgen::JsValue {
     fn from(error: WalletError) -> Self {
@@ -113,6 +136,17 @@ pub struct SigningState {
     pub public_nonces: SigMap<PubNonce>,
 }
 
+/// What the wallet holds for a stored entry, without any of the secret material
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DlcEntrySummary {
+    pub entry_index: u32,
+    pub funding_outpoint: Option<OutPoint>,
+    /// Contract parameters and funding outpoint have been added, so the entry can sign
+    pub contract_loaded: bool,
+    pub has_payout_preimage: bool,
+    pub has_ticket_preimage: bool,
+}
+
 #[derive(Clone)]
 pub struct DlcEntryData {
     pub payout_preimage: SecretString,
@@ -1,9 +1,13 @@
-use super::core::{TaprootWalletCore, TaprootWalletCoreBuilder};
+use super::core::{
+    chunk_partial_signatures, sign_funding_psbt_for_outpoint, TaprootWalletCore,
+    TaprootWalletCoreBuilder,
+};
+use super::KeymeldRegistrationRequest;
 use crate::nostr::NostrClientWrapper;
 use bdk_wallet::bitcoin::Psbt;
 use dlctix::{
     bitcoin::OutPoint,
-    musig2::AggNonce,
+    musig2::{AggNonce, PartialSignature},
     secp::{MaybeScalar, Scalar},
     ContractParameters, EventLockingConditions, SigMap,
 };
@@ -120,6 +124,22 @@ impl TaprootWallet {
         serde_wasm_bindgen::to_value(&data).map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Prepare keymeld registration data for several entries.
+    /// Takes an array of `{ entry_index, session_id, enclave_pubkey_hex }` and resolves to one
+    /// `{ entry_index, session_id, registration, error }` per request, in the same order.
+    /// Only a malformed array rejects, a failing entry is reported in its own result.
+    #[wasm_bindgen(js_name = "prepareKeymeldRegistrations")]
+    pub async fn prepare_keymeld_registrations(
+        &self,
+        requests: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let requests: Vec<KeymeldRegistrationRequest> = serde_wasm_bindgen::from_value(requests)
+            .map_err(|e| JsValue::from_str(&format!("Invalid registration requests: {}", e)))?;
+
+        let results = self.inner.prepare_keymeld_registrations(&requests).await;
+        serde_wasm_bindgen::to_value(&results).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     #[wasm_bindgen(js_name = "getEncryptedDlcPayoutPreimage")]
     pub async fn get_encrypted_dlc_payout_preimage(
         &self,
@@ -147,6 +167,30 @@ impl TaprootWallet {
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    #[wasm_bindgen(js_name = "listDlcEntries")]
+    pub fn list_dlc_entries(&self) -> Vec<u32> {
+        self.inner.list_dlc_entry_indices()
+    }
+
+    /// Summary of a stored entry (funding outpoint, contract loaded, preimage presence).
+    /// Secrets like the payout preimage are never included.
+    #[wasm_bindgen(js_name = "getDlcEntrySummary")]
+    pub fn get_dlc_entry_summary(&self, entry_index: u32) -> Result<JsValue, JsValue> {
+        let summary = self
+            .inner
+            .get_dlc_entry_summary(entry_index)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        serde_wasm_bindgen::to_value(&summary).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = "removeDlcEntry")]
+    pub fn remove_dlc_entry(&mut self, entry_index: u32) -> Result<(), JsValue> {
+        self.inner
+            .remove_dlc_entry(entry_index)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     #[wasm_bindgen(js_name = "addContract")]
     pub fn add_contract(
         &mut self,
@@ -257,3 +301,37 @@ impl TaprootWallet {
         Ok(outcome.to_string())
     }
 }
+
+/// Sign the funding PSBT with an entry's ephemeral private key (hex), only ever touching the
+/// input that spends `funding_outpoint` (`txid:vout`). Returns the partially signed PSBT as base64.
+#[wasm_bindgen(js_name = "signFundingPsbtWithKey")]
+pub fn sign_funding_psbt(
+    psbt_base64: &str,
+    ephemeral_private_key_hex: &str,
+    funding_outpoint: &str,
+) -> Result<String, JsValue> {
+    let psbt = Psbt::from_str(psbt_base64)
+        .map_err(|e| JsValue::from_str(&format!("Invalid PSBT base64: {}", e)))?;
+    let funding_outpoint = OutPoint::from_str(funding_outpoint)
+        .map_err(|e| JsValue::from_str(&format!("Invalid funding outpoint: {}", e)))?;
+
+    let signed_psbt =
+        sign_funding_psbt_for_outpoint(psbt, ephemeral_private_key_hex, funding_outpoint)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    Ok(signed_psbt.to_string())
+}
+
+/// Split partial signatures from `signAggregateNonces` into chunks of at most
+/// `outcomes_per_chunk` outcomes, to post one by one to `/partial_signatures`
+#[wasm_bindgen(js_name = "chunkPartialSignatures")]
+pub fn chunk_partial_signatures_js(
+    partial_signatures: JsValue,
+    outcomes_per_chunk: usize,
+) -> Result<JsValue, JsValue> {
+    let partial_signatures: SigMap<PartialSignature> =
+        serde_wasm_bindgen::from_value(partial_signatures)
+            .map_err(|e| JsValue::from_str(&format!("Invalid partial signatures: {}", e)))?;
+    let chunks = chunk_partial_signatures(partial_signatures, outcomes_per_chunk);
+    serde_wasm_bindgen::to_value(&chunks).map_err(|e| JsValue::from_str(&e.to_string()))
+}
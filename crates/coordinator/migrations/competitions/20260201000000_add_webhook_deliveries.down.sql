@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_webhook_deliveries_created_at;
+DROP INDEX IF EXISTS idx_webhook_deliveries_competition_id;
+DROP TABLE IF EXISTS webhook_deliveries;
@@ -0,0 +1,17 @@
+-- Outbound webhook deliveries, one row per (event, endpoint) so failed deliveries can be inspected and replayed
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id TEXT PRIMARY KEY,
+    competition_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    endpoint_url TEXT NOT NULL,
+    payload TEXT NOT NULL,                  -- Signed JSON body sent to the endpoint
+    attempts INTEGER NOT NULL DEFAULT 0,    -- Total attempts, including replays
+    last_status_code INTEGER,               -- HTTP status of the most recent attempt
+    last_error TEXT,                        -- Error of the most recent failed attempt
+    created_at DATETIME NOT NULL,
+    last_attempt_at DATETIME,
+    delivered_at DATETIME                   -- When the endpoint responded with a 2xx
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_competition_id ON webhook_deliveries(competition_id);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_created_at ON webhook_deliveries(created_at);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_entries_event_player_index;
+ALTER TABLE entries DROP COLUMN player_index;
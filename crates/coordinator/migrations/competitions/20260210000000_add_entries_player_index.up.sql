@@ -0,0 +1,17 @@
+-- Position of the entry's player in the DLC contract, fixed once entries are finalized
+ALTER TABLE entries ADD COLUMN player_index INTEGER;
+
+-- Competitions that already built their contract ordered players by ticket_id
+UPDATE entries
+SET player_index = (
+    SELECT COUNT(*)
+    FROM entries AS earlier
+    JOIN tickets ON earlier.ticket_id = tickets.id
+    WHERE earlier.event_id = entries.event_id
+    AND tickets.paid_at IS NOT NULL
+    AND earlier.ticket_id < entries.ticket_id
+)
+WHERE entries.ticket_id IN (SELECT id FROM tickets WHERE paid_at IS NOT NULL)
+AND entries.event_id IN (SELECT id FROM competitions WHERE contract_parameters IS NOT NULL);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_entries_event_player_index ON entries(event_id, player_index);
@@ -0,0 +1 @@
+ALTER TABLE tickets DROP COLUMN ln_backend_id;
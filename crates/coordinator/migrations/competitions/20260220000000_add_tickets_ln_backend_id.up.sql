@@ -0,0 +1,2 @@
+-- Lightning backend that created the ticket's hold invoice, settlement must go back to it
+ALTER TABLE tickets ADD COLUMN ln_backend_id TEXT;
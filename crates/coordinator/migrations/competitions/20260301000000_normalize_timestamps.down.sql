@@ -0,0 +1,14 @@
+UPDATE tickets SET reserved_at = strftime('%Y-%m-%d %H:%M:%S', reserved_at)
+WHERE reserved_at LIKE '____-__-__T__:__:__Z';
+
+UPDATE tickets SET paid_at = strftime('%Y-%m-%d %H:%M:%S', paid_at)
+WHERE paid_at LIKE '____-__-__T__:__:__Z';
+
+UPDATE tickets SET settled_at = strftime('%Y-%m-%d %H:%M:%S', settled_at)
+WHERE settled_at LIKE '____-__-__T__:__:__Z';
+
+UPDATE tickets SET invoice_expires_at = strftime('%Y-%m-%d %H:%M:%S', invoice_expires_at)
+WHERE invoice_expires_at LIKE '____-__-__T__:__:__Z';
+
+UPDATE entries SET signed_at = strftime('%Y-%m-%d %H:%M:%S', signed_at)
+WHERE signed_at LIKE '____-__-__T__:__:__Z';
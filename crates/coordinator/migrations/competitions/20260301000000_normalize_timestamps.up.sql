@@ -0,0 +1,16 @@
+-- Timestamps SQLite's datetime() stamped as 'YYYY-MM-DD HH:MM:SS' (UTC) move to the RFC3339
+-- UTC form every other timestamp is stored in, so they compare as strings against each other
+UPDATE tickets SET reserved_at = strftime('%Y-%m-%dT%H:%M:%SZ', reserved_at)
+WHERE reserved_at LIKE '____-__-__ __:__:__';
+
+UPDATE tickets SET paid_at = strftime('%Y-%m-%dT%H:%M:%SZ', paid_at)
+WHERE paid_at LIKE '____-__-__ __:__:__';
+
+UPDATE tickets SET settled_at = strftime('%Y-%m-%dT%H:%M:%SZ', settled_at)
+WHERE settled_at LIKE '____-__-__ __:__:__';
+
+UPDATE tickets SET invoice_expires_at = strftime('%Y-%m-%dT%H:%M:%SZ', invoice_expires_at)
+WHERE invoice_expires_at LIKE '____-__-__ __:__:__';
+
+UPDATE entries SET signed_at = strftime('%Y-%m-%dT%H:%M:%SZ', signed_at)
+WHERE signed_at LIKE '____-__-__ __:__:__';
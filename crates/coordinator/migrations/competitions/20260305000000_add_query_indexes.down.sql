@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_payouts_entry_failed_at;
+DROP INDEX IF EXISTS idx_tickets_hash;
+DROP INDEX IF EXISTS idx_tickets_event_reserved_at;
+DROP INDEX IF EXISTS idx_competitions_active;
@@ -0,0 +1,12 @@
+-- Watcher ticks only load competitions that haven't reached a terminal state
+CREATE INDEX IF NOT EXISTS idx_competitions_active ON competitions(id)
+WHERE expiry_broadcasted_at IS NULL AND completed_at IS NULL AND cancelled_at IS NULL;
+
+-- Ticket reservation looks up a competition's tickets ordered by reserved_at
+CREATE INDEX IF NOT EXISTS idx_tickets_event_reserved_at ON tickets(event_id, reserved_at);
+
+-- Invoice updates find their ticket by payment hash
+CREATE INDEX IF NOT EXISTS idx_tickets_hash ON tickets(hash);
+
+-- Payout attempts per entry, split by whether they failed
+CREATE INDEX IF NOT EXISTS idx_payouts_entry_failed_at ON payouts(entry_id, failed_at);
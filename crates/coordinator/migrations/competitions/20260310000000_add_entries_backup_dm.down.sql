@@ -0,0 +1,2 @@
+ALTER TABLE entries DROP COLUMN backup_dm_error;
+ALTER TABLE entries DROP COLUMN backup_dm_sent_at;
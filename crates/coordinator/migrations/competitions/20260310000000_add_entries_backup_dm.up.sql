@@ -0,0 +1,3 @@
+-- Whether the entry's recovery material reached the player as a nostr DM
+ALTER TABLE entries ADD COLUMN backup_dm_sent_at TEXT;
+ALTER TABLE entries ADD COLUMN backup_dm_error TEXT;
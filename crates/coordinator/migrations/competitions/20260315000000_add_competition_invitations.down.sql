@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_competition_invitations_pubkey;
+DROP TABLE IF EXISTS competition_invitations;
@@ -0,0 +1,9 @@
+-- Pubkeys invited to private competitions, only they can reserve tickets and enter
+CREATE TABLE IF NOT EXISTS competition_invitations (
+    competition_id TEXT NOT NULL REFERENCES competitions (id),
+    pubkey TEXT NOT NULL,                   -- Hex nostr pubkey of the invited player
+    created_at DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    PRIMARY KEY (competition_id, pubkey)
+);
+
+CREATE INDEX IF NOT EXISTS idx_competition_invitations_pubkey ON competition_invitations(pubkey);
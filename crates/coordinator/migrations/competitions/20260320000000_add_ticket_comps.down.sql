@@ -0,0 +1,3 @@
+ALTER TABLE tickets DROP COLUMN comp_note;
+ALTER TABLE tickets DROP COLUMN comped_at;
+ALTER TABLE tickets DROP COLUMN comped_by;
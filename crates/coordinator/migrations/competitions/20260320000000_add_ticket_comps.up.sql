@@ -0,0 +1,4 @@
+-- Tickets an admin marked as prepaid instead of the player paying the hold invoice
+ALTER TABLE tickets ADD COLUMN comped_by TEXT;
+ALTER TABLE tickets ADD COLUMN comped_at TEXT;
+ALTER TABLE tickets ADD COLUMN comp_note TEXT;
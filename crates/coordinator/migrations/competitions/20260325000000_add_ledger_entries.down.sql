@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_ledger_entries_competition_id;
+DROP TABLE IF EXISTS ledger_entries;
@@ -0,0 +1,13 @@
+-- Every movement of sats the coordinator is party to, appended where it happens
+CREATE TABLE IF NOT EXISTS ledger_entries (
+    id TEXT PRIMARY KEY,
+    competition_id TEXT NOT NULL REFERENCES competitions (id),
+    entry_id TEXT                         REFERENCES entries (id),
+    kind TEXT NOT NULL,                     -- invoice_settled, miner_fee, payout_sent, ...
+    amount_sats INTEGER NOT NULL,
+    reference TEXT NOT NULL,                -- Ticket, outpoint, txid or payout the sats moved with
+    recorded_at DATETIME NOT NULL,
+    UNIQUE (kind, reference)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_competition_id ON ledger_entries(competition_id);
@@ -0,0 +1 @@
+ALTER TABLE entries DROP COLUMN escrow_reclaim_address;
@@ -0,0 +1,2 @@
+-- Where a no-show player wants their escrow output sent once the competition is cancelled
+ALTER TABLE entries ADD COLUMN escrow_reclaim_address TEXT;
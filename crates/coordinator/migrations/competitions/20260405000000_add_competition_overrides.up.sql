@@ -0,0 +1,13 @@
+-- Manual state changes an operator forced on a competition, kept next to the state timestamps
+CREATE TABLE IF NOT EXISTS competition_overrides (
+    id TEXT PRIMARY KEY,
+    competition_id TEXT NOT NULL REFERENCES competitions (id),
+    action TEXT NOT NULL,                   -- force_complete
+    from_state TEXT NOT NULL,
+    to_state TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    admin TEXT NOT NULL,                    -- Admin pubkey, or anonymous without an allow-list
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_competition_overrides_competition_id ON competition_overrides(competition_id);
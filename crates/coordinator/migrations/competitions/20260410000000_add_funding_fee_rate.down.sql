@@ -0,0 +1 @@
+ALTER TABLE competitions DROP COLUMN funding_fee_rate;
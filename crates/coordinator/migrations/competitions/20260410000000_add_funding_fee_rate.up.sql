@@ -0,0 +1,2 @@
+-- Fee rate and its source the funding psbt was built with, kept next to funding_psbt_base64
+ALTER TABLE competitions ADD COLUMN funding_fee_rate BLOB;
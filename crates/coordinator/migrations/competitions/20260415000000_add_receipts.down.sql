@@ -0,0 +1,2 @@
+ALTER TABLE entries DROP COLUMN entry_receipt;
+ALTER TABLE tickets DROP COLUMN payment_receipt;
@@ -0,0 +1,3 @@
+-- Coordinator signed receipts players keep as proof of payment and entry
+ALTER TABLE tickets ADD COLUMN payment_receipt BLOB;
+ALTER TABLE entries ADD COLUMN entry_receipt BLOB;
@@ -0,0 +1 @@
+ALTER TABLE competitions DROP COLUMN player_order;
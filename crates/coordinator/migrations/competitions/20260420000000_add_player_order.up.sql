@@ -0,0 +1,2 @@
+-- Ticket order the keymeld subset definitions were built with, checked at contract creation
+ALTER TABLE competitions ADD COLUMN player_order BLOB;
@@ -0,0 +1,2 @@
+ALTER TABLE entries DROP COLUMN cancelled_reason;
+ALTER TABLE entries DROP COLUMN cancelled_at;
@@ -0,0 +1,3 @@
+-- Entries dropped from their competition, e.g. because their escrow was double spent
+ALTER TABLE entries ADD COLUMN cancelled_at DATETIME;
+ALTER TABLE entries ADD COLUMN cancelled_reason TEXT;
@@ -0,0 +1 @@
+ALTER TABLE competitions DROP COLUMN nonce_seed_outpoint;
@@ -0,0 +1,3 @@
+-- Funding outpoint the coordinator's musig nonces were derived from, a rebuilt funding psbt
+-- moves the outpoint and invalidates them
+ALTER TABLE competitions ADD COLUMN nonce_seed_outpoint BLOB;
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_competitions_slug;
+DROP INDEX IF EXISTS idx_competitions_name;
+ALTER TABLE competitions DROP COLUMN slug;
+ALTER TABLE competitions DROP COLUMN name;
@@ -0,0 +1,6 @@
+-- Human readable name and url slug an operator can give a competition, both unique
+ALTER TABLE competitions ADD COLUMN name TEXT;
+ALTER TABLE competitions ADD COLUMN slug TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_competitions_name ON competitions(name COLLATE NOCASE) WHERE name IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_competitions_slug ON competitions(slug) WHERE slug IS NOT NULL;
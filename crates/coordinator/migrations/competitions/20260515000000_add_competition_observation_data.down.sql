@@ -0,0 +1 @@
+DROP TABLE IF EXISTS competition_observation_data;
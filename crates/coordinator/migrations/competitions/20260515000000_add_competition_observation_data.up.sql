@@ -0,0 +1,8 @@
+-- Oracle forecasts, observations and entry scores fetched once a competition is attested, scoring
+-- is served from here instead of the oracle. Refetched only if the attestation changes.
+CREATE TABLE IF NOT EXISTS competition_observation_data (
+    competition_id TEXT PRIMARY KEY REFERENCES competitions (id),
+    attestation TEXT NOT NULL,              -- Hex of the attestation the data was fetched after
+    data TEXT NOT NULL,                     -- JSON encoded ObservationData
+    fetched_at DATETIME NOT NULL
+);
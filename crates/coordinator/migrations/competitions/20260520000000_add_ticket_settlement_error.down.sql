@@ -0,0 +1,2 @@
+ALTER TABLE tickets DROP COLUMN settlement_failed_at;
+ALTER TABLE tickets DROP COLUMN settlement_error;
@@ -0,0 +1,4 @@
+-- Last failure settling the ticket's hold invoice, cleared once it settles so one bad invoice
+-- is visible without holding up the rest
+ALTER TABLE tickets ADD COLUMN settlement_error TEXT;
+ALTER TABLE tickets ADD COLUMN settlement_failed_at DATETIME;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS ln_sync_cursors;
@@ -0,0 +1,7 @@
+-- Where the invoice and payment subscribers resume their catch-up sweeps after a dropped
+-- connection to lnd, one row per stream
+CREATE TABLE IF NOT EXISTS ln_sync_cursors (
+    stream TEXT PRIMARY KEY,                -- 'invoices' (add_index) or 'payments' (payment_index)
+    last_index INTEGER NOT NULL,            -- Everything at or below it has been processed
+    updated_at DATETIME NOT NULL
+);
@@ -0,0 +1,3 @@
+-- When the coordinator broadcast the ticket's escrow transaction, only set when the coordinator is
+-- the one broadcasting escrows (coordinator_settings.escrow_broadcast_by_coordinator)
+ALTER TABLE tickets ADD COLUMN escrow_broadcasted_at DATETIME;
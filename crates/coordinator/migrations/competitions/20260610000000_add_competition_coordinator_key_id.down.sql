@@ -0,0 +1 @@
+ALTER TABLE competitions DROP COLUMN coordinator_key_id;
@@ -0,0 +1,3 @@
+-- Id of the coordinator key (coordinator_settings.signing_keys) the competition's contract is
+-- signed with, NULL for competitions on the wallet seed key
+ALTER TABLE competitions ADD COLUMN coordinator_key_id TEXT;
@@ -0,0 +1 @@
+ALTER TABLE competitions DROP COLUMN oracle_submitted_entry_ids;
@@ -0,0 +1,3 @@
+-- JSON array of the entry ids the oracle has confirmed receiving, entries are sent in chunks and
+-- a retry only sends the ones missing from it
+ALTER TABLE competitions ADD COLUMN oracle_submitted_entry_ids TEXT;
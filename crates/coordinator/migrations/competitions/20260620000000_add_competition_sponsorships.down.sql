@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_competition_sponsorships_competition_id;
+DROP TABLE IF EXISTS competition_sponsorships;
@@ -0,0 +1,14 @@
+-- Sats sponsors added to a competition's pool on top of its entry fees
+CREATE TABLE IF NOT EXISTS competition_sponsorships (
+    id TEXT PRIMARY KEY,
+    competition_id TEXT NOT NULL REFERENCES competitions (id),
+    amount_sats INTEGER NOT NULL,
+    funds_kind TEXT NOT NULL,               -- utxo or lightning
+    funds_reference TEXT NOT NULL,          -- Outpoint or payment hash the sats arrived with
+    sponsor TEXT,
+    recorded_by TEXT NOT NULL,              -- Admin pubkey, or anonymous without an allow-list
+    created_at DATETIME NOT NULL,
+    UNIQUE (funds_kind, funds_reference)
+);
+
+CREATE INDEX IF NOT EXISTS idx_competition_sponsorships_competition_id ON competition_sponsorships(competition_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_competition_changes_competition_id;
+DROP TABLE IF EXISTS competition_changes;
+ALTER TABLE competitions DROP COLUMN version;
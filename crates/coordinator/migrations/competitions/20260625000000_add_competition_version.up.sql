@@ -0,0 +1,14 @@
+-- Bumped on every save of a competition's state, a save made from an older version is refused
+-- instead of overwriting the newer one
+ALTER TABLE competitions ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+
+-- Columns each save changed, for tracing how a competition got to its state
+CREATE TABLE IF NOT EXISTS competition_changes (
+    id TEXT PRIMARY KEY,
+    competition_id TEXT NOT NULL REFERENCES competitions (id),
+    version INTEGER NOT NULL,               -- Version the save produced
+    changed_fields TEXT NOT NULL,           -- JSON array of column names
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_competition_changes_competition_id ON competition_changes(competition_id);
@@ -0,0 +1 @@
+ALTER TABLE competitions DROP COLUMN unissued_tickets;
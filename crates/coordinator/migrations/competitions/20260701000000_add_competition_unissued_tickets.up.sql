@@ -0,0 +1,3 @@
+-- Tickets of the competition's total_allowed_entries not created yet, each reservation that
+-- finds no free ticket takes one. Competitions created before this have all their tickets.
+ALTER TABLE competitions ADD COLUMN unissued_tickets INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Nothing to undo, the store reads both timestamp forms
+SELECT 1;
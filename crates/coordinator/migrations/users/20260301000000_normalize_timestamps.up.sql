@@ -0,0 +1,7 @@
+-- Rows stamped by the CURRENT_TIMESTAMP defaults ('YYYY-MM-DD HH:MM:SS', UTC) move to the
+-- RFC3339 UTC form the store writes
+UPDATE user SET created_at = strftime('%Y-%m-%dT%H:%M:%SZ', created_at)
+WHERE created_at LIKE '____-__-__ __:__:__';
+
+UPDATE user SET updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', updated_at)
+WHERE updated_at LIKE '____-__-__ __:__:__';
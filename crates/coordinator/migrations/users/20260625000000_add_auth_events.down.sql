@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_auth_events_created_at;
+DROP INDEX IF EXISTS idx_auth_events_pubkey;
+DROP TABLE IF EXISTS auth_events;
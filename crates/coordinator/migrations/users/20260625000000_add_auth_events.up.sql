@@ -0,0 +1,16 @@
+-- Successful NIP-98 authentications, so users can see where their account was used
+CREATE TABLE IF NOT EXISTS auth_events (
+    id TEXT NOT NULL PRIMARY KEY,
+    nostr_pubkey TEXT NOT NULL,
+    event_id TEXT NOT NULL UNIQUE,  -- The NIP-98 event, replays of it are recorded once
+    ip TEXT,                        -- Truncated or hashed per api_settings.auth_event_ip_privacy
+    user_agent TEXT,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    expires_at DATETIME NOT NULL,   -- When the event's freshness window closes
+    revoked_at DATETIME,
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_auth_events_pubkey ON auth_events(nostr_pubkey, created_at);
+CREATE INDEX idx_auth_events_created_at ON auth_events(created_at);
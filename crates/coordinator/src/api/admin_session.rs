@@ -0,0 +1,128 @@
+use axum::http::{header::COOKIE, HeaderMap};
+use hmac::{Hmac, Mac};
+use nostr_sdk::PublicKey;
+use rand::RngCore;
+use sha2::Sha256;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+pub const ADMIN_SESSION_COOKIE: &str = "coordinator_admin_session";
+
+/// Issues and checks the cookie an operator gets after signing in to the admin dashboard with a
+/// NIP-98 header. The cookie holds `<pubkey hex>.<expiry unix secs>.<hmac>`, signed with a key
+/// generated at startup, so sessions end when they expire or the coordinator restarts.
+#[derive(Clone)]
+pub struct AdminSessions {
+    key: [u8; 32],
+    ttl: Duration,
+}
+
+impl AdminSessions {
+    pub fn new(ttl: Duration) -> Self {
+        let mut key = [0u8; 32];
+        rand::rng().fill_bytes(&mut key);
+        Self { key, ttl }
+    }
+
+    pub fn issue(&self, pubkey: &PublicKey) -> String {
+        let expires_at = OffsetDateTime::now_utc().unix_timestamp() + self.ttl.as_secs() as i64;
+        let claims = format!("{}.{}", pubkey.to_hex(), expires_at);
+        let signature = hex::encode(self.mac(&claims).finalize().into_bytes());
+        format!("{}.{}", claims, signature)
+    }
+
+    /// The pubkey a session token was issued to, if it was signed by this coordinator and
+    /// hasn't expired
+    pub fn verify(&self, token: &str) -> Option<PublicKey> {
+        let (claims, signature) = token.rsplit_once('.')?;
+        let signature = hex::decode(signature).ok()?;
+        self.mac(claims).verify_slice(&signature).ok()?;
+
+        let (pubkey, expires_at) = claims.split_once('.')?;
+        let expires_at: i64 = expires_at.parse().ok()?;
+        if expires_at <= OffsetDateTime::now_utc().unix_timestamp() {
+            return None;
+        }
+        PublicKey::from_hex(pubkey).ok()
+    }
+
+    /// `Set-Cookie` value for a new session, `secure` when the dashboard is served over https
+    pub fn cookie(&self, token: &str, secure: bool) -> String {
+        format!(
+            "{}={}; Path=/admin; Max-Age={}; HttpOnly; SameSite=Strict{}",
+            ADMIN_SESSION_COOKIE,
+            token,
+            self.ttl.as_secs(),
+            if secure { "; Secure" } else { "" }
+        )
+    }
+
+    pub fn cleared_cookie() -> String {
+        format!(
+            "{}=; Path=/admin; Max-Age=0; HttpOnly; SameSite=Strict",
+            ADMIN_SESSION_COOKIE
+        )
+    }
+
+    fn mac(&self, claims: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any size");
+        mac.update(claims.as_bytes());
+        mac
+    }
+}
+
+/// The admin session token from the request's `Cookie` headers, if there is one
+pub fn session_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|cookie| {
+            let (name, value) = cookie.trim().split_once('=')?;
+            (name == ADMIN_SESSION_COOKIE && !value.is_empty()).then_some(value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use nostr_sdk::Keys;
+
+    #[test]
+    fn test_session_round_trips_and_rejects_tampering() {
+        let sessions = AdminSessions::new(Duration::from_secs(600));
+        let keys = Keys::generate();
+        let token = sessions.issue(&keys.public_key());
+        assert_eq!(sessions.verify(&token), Some(keys.public_key()));
+
+        // Swapping in another pubkey breaks the signature
+        let other = Keys::generate().public_key().to_hex();
+        let forged = token.replacen(&keys.public_key().to_hex(), &other, 1);
+        assert_eq!(sessions.verify(&forged), None);
+
+        // Tokens from another coordinator (or before a restart) aren't accepted
+        let restarted = AdminSessions::new(Duration::from_secs(600));
+        assert_eq!(restarted.verify(&token), None);
+    }
+
+    #[test]
+    fn test_expired_session_is_rejected() {
+        let sessions = AdminSessions::new(Duration::ZERO);
+        let token = sessions.issue(&Keys::generate().public_key());
+        assert_eq!(sessions.verify(&token), None);
+    }
+
+    #[test]
+    fn test_session_token_is_read_from_cookie_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            COOKIE,
+            HeaderValue::from_static("theme=dark; coordinator_admin_session=abc.123.def"),
+        );
+        assert_eq!(session_token(&headers), Some("abc.123.def"));
+        assert_eq!(session_token(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,184 @@
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE, TRANSFER_ENCODING},
+        HeaderMap, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use log::warn;
+use serde_json::json;
+
+/// Largest request body each kind of route takes, in bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BodyLimits {
+    /// Nonce and signature submissions, their SigMaps grow with the contract's outcomes
+    pub signatures: usize,
+    pub entries: usize,
+    /// Every other route
+    pub default: usize,
+}
+
+impl BodyLimits {
+    /// The limit for `path`, matching the `DefaultBodyLimit` layers of the routes in `app`
+    pub fn for_path(&self, path: &str) -> usize {
+        let path = path.trim_end_matches('/');
+        if path.ends_with("/public_nonces")
+            || path.ends_with("/partial_signatures")
+            || path.ends_with("/final_signatures")
+        {
+            self.signatures
+        } else if path == "/api/v1/entries" {
+            self.entries
+        } else {
+            self.default
+        }
+    }
+}
+
+/// Turns away request bodies before a handler reads them. The API only takes JSON and forms,
+/// anything else such as multipart uploads gets 415. A declared `Content-Length` over the
+/// route's limit gets 413; bodies without one are cut off at the same limit by the route's
+/// `DefaultBodyLimit` as they're read.
+pub async fn reject_unexpected_bodies(
+    State(limits): State<BodyLimits>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limit = limits.for_path(request.uri().path());
+    if let Err(status) = check_request_body(request.headers(), limit) {
+        warn!(
+            "Rejected {} {} body with {}",
+            request.method(),
+            request.uri().path(),
+            status
+        );
+        let message = if status == StatusCode::PAYLOAD_TOO_LARGE {
+            format!("request body is larger than {} bytes", limit)
+        } else {
+            "request body must be application/json or application/x-www-form-urlencoded".to_string()
+        };
+        return (status, Json(json!({ "error": message }))).into_response();
+    }
+    next.run(request).await
+}
+
+/// Checks a request's body headers against `limit`, requests without a body always pass
+pub fn check_request_body(headers: &HeaderMap, limit: usize) -> Result<(), StatusCode> {
+    let content_length = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok())
+        .and_then(|length| length.trim().parse::<u64>().ok());
+    let has_body =
+        content_length.is_some_and(|length| length > 0) || headers.contains_key(TRANSFER_ENCODING);
+    if !has_body {
+        return Ok(());
+    }
+
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase());
+    if !content_type.as_deref().is_some_and(is_accepted_mime) {
+        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    if content_length.is_some_and(|length| length > limit as u64) {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    Ok(())
+}
+
+fn is_accepted_mime(mime: &str) -> bool {
+    mime == "application/json"
+        || mime == "application/x-www-form-urlencoded"
+        || (mime.starts_with("application/") && mime.ends_with("+json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(content_type: Option<&str>, content_length: Option<usize>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(content_type) = content_type {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_str(content_type).unwrap());
+        }
+        if let Some(length) = content_length {
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(length));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_bodies_are_checked_against_the_route_limit() {
+        let limits = BodyLimits {
+            signatures: 2_000_000,
+            entries: 64_000,
+            default: 16_000,
+        };
+        assert_eq!(
+            limits.for_path("/api/v1/competitions/c/entries/e/final_signatures"),
+            2_000_000
+        );
+        assert_eq!(
+            limits.for_path("/api/v1/competitions/c/entries/e/public_nonces/"),
+            2_000_000
+        );
+        assert_eq!(limits.for_path("/api/v1/entries"), 64_000);
+        assert_eq!(limits.for_path("/api/v1/users/login"), 16_000);
+
+        let json = Some("application/json; charset=utf-8");
+        assert_eq!(
+            check_request_body(&headers(json, Some(16_000)), 16_000),
+            Ok(())
+        );
+        assert_eq!(
+            check_request_body(&headers(json, Some(16_001)), 16_000),
+            Err(StatusCode::PAYLOAD_TOO_LARGE)
+        );
+        assert_eq!(
+            check_request_body(
+                &headers(Some("application/x-www-form-urlencoded"), Some(10)),
+                16_000
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_unexpected_content_types_are_rejected() {
+        for content_type in [
+            Some("multipart/form-data; boundary=xyz"),
+            Some("text/plain"),
+            Some("application/octet-stream"),
+            None,
+        ] {
+            assert_eq!(
+                check_request_body(&headers(content_type, Some(10)), 16_000),
+                Err(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+                "{:?}",
+                content_type
+            );
+        }
+
+        // Chunked bodies don't declare a length, their type is still checked
+        let mut chunked = headers(Some("multipart/form-data"), None);
+        chunked.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        assert_eq!(
+            check_request_body(&chunked, 16_000),
+            Err(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+        );
+
+        // Requests without a body, like an empty POST, pass whatever they declare
+        assert_eq!(check_request_body(&headers(None, None), 16_000), Ok(()));
+        assert_eq!(
+            check_request_body(&headers(Some("text/plain"), Some(0)), 16_000),
+            Ok(())
+        );
+    }
+}
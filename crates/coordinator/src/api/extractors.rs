@@ -17,6 +17,9 @@ use serde_json::json;
 use std::str::FromStr;
 use time::OffsetDateTime;
 
+/// How far a NIP-98 event's timestamp may be from the server's clock
+pub const NIP98_MAX_AGE_SECS: i64 = 60;
+
 pub async fn create_auth_event(
     method: &str,
     url: &str,
@@ -78,7 +81,7 @@ where
         }
 
         let now = OffsetDateTime::now_utc().unix_timestamp();
-        if (now - (event.created_at.as_u64() as i64)).abs() > 60 {
+        if (now - (event.created_at.as_u64() as i64)).abs() > NIP98_MAX_AGE_SECS {
             return Err(AuthError::ExpiredTimestamp);
         }
 
@@ -161,6 +164,10 @@ pub enum AuthError {
     InvalidSignature(String),
     #[error("Event content must be empty")]
     NonEmptyContent,
+    #[error("Pubkey {0} is not allowed to use admin routes")]
+    NotAdmin(String),
+    #[error("Auth event {0} was revoked")]
+    Revoked(String),
 }
 
 impl From<nostr_sdk::types::ParseError> for AuthError {
@@ -190,6 +197,8 @@ impl Serialize for AuthError {
             Self::UrlMethodMismatch => "url_method_mismatch",
             Self::InvalidSignature(_) => "invalid_signature",
             Self::NonEmptyContent => "non_empty_content",
+            Self::NotAdmin(_) => "not_admin",
+            Self::Revoked(_) => "revoked",
         };
 
         state.serialize_field("type", type_str)?;
@@ -201,7 +210,7 @@ impl Serialize for AuthError {
 impl IntoResponse for AuthError {
     fn into_response(self) -> axum::response::Response {
         let (body, code) = match &self {
-            Self::InvalidSignature(_) => {
+            Self::InvalidSignature(_) | Self::NotAdmin(_) => {
                 warn!("{}", self);
                 (json!({ "error": self }), StatusCode::FORBIDDEN)
             }
@@ -211,7 +220,8 @@ impl IntoResponse for AuthError {
             | Self::UrlMethodMismatch
             | Self::InvalidUrl(_)
             | Self::InvalidLogin
-            | Self::InvalidMethod(_) => {
+            | Self::InvalidMethod(_)
+            | Self::Revoked(_) => {
                 warn!("{}", self);
                 (json!({ "error": self }), StatusCode::UNAUTHORIZED)
             }
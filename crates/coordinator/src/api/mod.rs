@@ -1,2 +1,4 @@
+pub mod admin_session;
+pub mod body_limits;
 pub mod extractors;
 pub mod routes;
@@ -5,6 +5,7 @@ use axum::{
     Json,
 };
 use bdk_wallet::bitcoin::PublicKey;
+use coordinator_core::SignedReceipt;
 use dlctix::{
     musig2::{AggNonce, PartialSignature, PubNonce},
     SigMap,
@@ -16,15 +17,21 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    api::extractors::NostrAuth,
+    api::{
+        extractors::{AuthError, NostrAuth},
+        routes::fetch_leaderboard,
+    },
     domain::{
-        AddEntry, Competition, CreateEvent, FundedContract, PayoutInfo, SearchBy, TicketResponse,
-        TicketStatus, UserEntry,
+        parse_sig_map, scoring::Leaderboard, sig_map_len, AddEntry, Competition,
+        CompetitionTimeline, ContractDisclosure, CreateEvent, EligiblePayout, EntryPreview, Error,
+        EscrowReclaimInfo, FundedContract, KeymeldRegistration, OracleEventInfo, PayoutInfo,
+        SearchBy, SignatureChunkProgress, TicketResponse, TicketStatusResponse, UserEntry,
     },
+    infra::oracle::WeatherChoices,
     startup::AppState,
 };
 
-// Private route not exposed publically so NostrAuth is not needed
+// Operator route, gated by `api_settings.admin_pubkeys` in `require_admin` when configured
 pub async fn create_competition(
     State(state): State<Arc<AppState>>,
     Json(body): Json<CreateEvent>,
@@ -49,7 +56,8 @@ pub struct SettleEscrowRequest {
 }
 
 /// Request to obtain a ticket, including the user's Bitcoin public key
-/// needed for the escrow transaction refund path
+/// needed for the escrow transaction refund path. The ticket is bound to this key,
+/// the entry submitted for it must use it as its `ephemeral_pubkey`.
 #[derive(Debug, Deserialize)]
 pub struct TicketRequest {
     pub btc_pubkey: String, // Bitcoin public key for escrow refund path
@@ -100,7 +108,7 @@ pub async fn get_ticket_status(
     NostrAuth { pubkey, .. }: NostrAuth,
     State(state): State<Arc<AppState>>,
     Path((competition_id, ticket_id)): Path<(Uuid, Uuid)>,
-) -> Result<Json<TicketStatus>, ErrorResponse> {
+) -> Result<Json<TicketStatusResponse>, ErrorResponse> {
     state
         .coordinator
         .get_ticket_status(pubkey.to_hex(), competition_id, ticket_id)
@@ -112,6 +120,41 @@ pub async fn get_ticket_status(
         })
 }
 
+/// New invoice for a reserved ticket whose invoice expired while the reservation still holds.
+/// It keeps the ticket's payment hash and reservation, served on both GET and POST.
+pub async fn regenerate_ticket_invoice(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path((competition_id, ticket_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<TicketResponse>, ErrorResponse> {
+    state
+        .coordinator
+        .regenerate_ticket_invoice(pubkey.to_hex(), competition_id, ticket_id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error regenerating ticket invoice: {:?}", e);
+            e.into()
+        })
+}
+
+/// Coordinator signed proof of payment, available once the ticket's hold invoice settled
+pub async fn get_ticket_receipt(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path((competition_id, ticket_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<SignedReceipt>, ErrorResponse> {
+    state
+        .coordinator
+        .get_ticket_receipt(pubkey.to_hex(), competition_id, ticket_id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error getting ticket receipt: {:?}", e);
+            e.into()
+        })
+}
+
 /* Two steps
 1) submit entry with ticket_id for the hold invoice
 2) pay the hold invoice (server watching invoice state to become accepted)
@@ -152,16 +195,59 @@ pub async fn get_entries(
         })
 }
 
+/// Payouts the user's entries are owed in attested competitions, with whether a payout
+/// invoice was already submitted
+pub async fn get_eligible_payouts(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<EligiblePayout>>, ErrorResponse> {
+    state
+        .coordinator
+        .get_eligible_payouts(pubkey.to_hex())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error getting eligible payouts: {:?}", e);
+            e.into()
+        })
+}
+
+/// Coordinator signed proof the entry was accepted
+pub async fn get_entry_receipt(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path(entry_id): Path<Uuid>,
+) -> Result<Json<SignedReceipt>, ErrorResponse> {
+    state
+        .coordinator
+        .get_entry_receipt(pubkey.to_hex(), entry_id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error getting entry receipt: {:?}", e);
+            e.into()
+        })
+}
+
 //TODO: add the ability to filter competition list
+/// Private competitions are only listed for invited or admin pubkeys signing the request
 pub async fn get_competitions(
     State(state): State<Arc<AppState>>,
+    auth: Result<NostrAuth, AuthError>,
+    Query(filter): Query<SearchBy>,
 ) -> Result<Json<Vec<Competition>>, ErrorResponse> {
-    let competitions = state.coordinator.get_competitions().await.map_err(|e| {
-        error!("error getting competitions: {:?}", e);
-        e
-    })?;
+    let viewer = auth.ok().map(|auth| auth.pubkey);
+    let competitions = state
+        .coordinator
+        .get_visible_competitions(viewer.as_ref())
+        .await
+        .map_err(|e| {
+            error!("error getting competitions: {:?}", e);
+            e
+        })?;
     let competitions = competitions
         .into_iter()
+        .filter(|comp| filter.matches_tags(&comp.event_submission.tags))
         .map(|mut comp| {
             if !comp.is_funding_broadcasted() {
                 comp.funding_transaction = None;
@@ -193,6 +279,101 @@ pub async fn get_competition(
     Ok(Json(competition))
 }
 
+/// A competition looked up by the slug it was created with, as `get_competition` returns it
+pub async fn get_competition_by_slug(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> Result<Json<Competition>, ErrorResponse> {
+    let mut competition = state
+        .coordinator
+        .get_competition_by_slug(&slug)
+        .await
+        .map_err(|e| {
+            error!("error getting competition by slug {}: {:?}", slug, e);
+            e
+        })?;
+
+    if !competition.is_funding_broadcasted() {
+        competition.funding_transaction = None;
+    }
+
+    Ok(Json(competition))
+}
+
+/// Ranked entries with scores. Scores are projected from the oracle's observations during the
+/// observation window and final once the oracle has attested.
+pub async fn get_competition_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+) -> Result<Json<Leaderboard>, ErrorResponse> {
+    fetch_leaderboard(&state, competition_id)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            Error::DbError(sqlx::Error::RowNotFound) => {
+                Error::NotFound(format!("competition {} not found", competition_id)).into()
+            }
+            e => {
+                error!("error getting leaderboard: {:?}", e);
+                e.into()
+            }
+        })
+}
+
+/// Oracle announcement and attestation the contract was built and settled with, public so
+/// anyone can verify the outcome against the oracle's own data
+pub async fn get_competition_oracle_event(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+) -> Result<Json<OracleEventInfo>, ErrorResponse> {
+    state
+        .coordinator
+        .get_oracle_event_info(competition_id)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            Error::DbError(sqlx::Error::RowNotFound) => {
+                Error::NotFound(format!("competition {} not found", competition_id)).into()
+            }
+            e => {
+                error!("error getting oracle event: {:?}", e);
+                e.into()
+            }
+        })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntryPreviewQuery {
+    /// JSON encoded `expected_observations`, the same shape an entry submits
+    pub observations: String,
+}
+
+/// Payouts a hypothetical entry would receive under each outcome, nothing is stored so users
+/// can weigh up entering before requesting a ticket
+pub async fn get_entry_preview(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+    Query(query): Query<EntryPreviewQuery>,
+) -> Result<Json<EntryPreview>, ErrorResponse> {
+    let expected_observations: Vec<WeatherChoices> = serde_json::from_str(&query.observations)
+        .map_err(|e| Error::BadRequest(format!("Invalid observations: {}", e)))?;
+
+    state
+        .coordinator
+        .preview_entry(competition_id, expected_observations)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            Error::DbError(sqlx::Error::RowNotFound) => {
+                Error::NotFound(format!("competition {} not found", competition_id)).into()
+            }
+            e => {
+                error!("error previewing entry: {:?}", e);
+                e.into()
+            }
+        })
+}
+
 pub async fn get_contract_parameters(
     NostrAuth { pubkey, .. }: NostrAuth,
     State(state): State<Arc<AppState>>,
@@ -210,14 +391,54 @@ pub async fn get_contract_parameters(
         })
 }
 
+/// Contract terms for auditors with player pubkeys replaced by salted hashes, available once the
+/// contract is created. Entrants signing the request also get their own player indices.
+pub async fn get_contract_disclosure(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+    auth: Result<NostrAuth, AuthError>,
+) -> Result<Json<ContractDisclosure>, ErrorResponse> {
+    let viewer = auth.ok().map(|auth| auth.pubkey.to_hex());
+    state
+        .coordinator
+        .get_contract_disclosure(competition_id, viewer)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error getting contract disclosure: {:?}", e);
+            e.into()
+        })
+}
+
+/// Estimated block heights and times for the outcome and delta timelocks so clients can show
+/// countdowns. Entrants signing the request also get the exit windows of their own entries.
+pub async fn get_competition_timeline(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+    auth: Result<NostrAuth, AuthError>,
+) -> Result<Json<CompetitionTimeline>, ErrorResponse> {
+    let viewer = auth.ok().map(|auth| auth.pubkey.to_hex());
+    state
+        .coordinator
+        .get_competition_timeline(competition_id, viewer)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error getting competition timeline: {:?}", e);
+            e.into()
+        })
+}
+
 pub async fn submit_public_nonces(
     NostrAuth { pubkey, .. }: NostrAuth,
     State(state): State<Arc<AppState>>,
     Path((competition_id, entry_id)): Path<(Uuid, Uuid)>,
-    Json(public_nonces): Json<SigMap<PubNonce>>,
+    Json(public_nonces): Json<SigMap<serde_json::Value>>,
 ) -> Result<StatusCode, ErrorResponse> {
     let pubkey = pubkey.to_hex();
     debug!("submitted nonce by: {} {:?}", pubkey, public_nonces);
+    let public_nonces = parse_sig_map::<PubNonce>("public_nonces", public_nonces)
+        .map_err(|e| ErrorResponse::from(Error::from(e)))?;
 
     state
         .coordinator
@@ -253,17 +474,27 @@ pub struct FinalSignatures {
     pub partial_signatures: SigMap<PartialSignature>,
 }
 
+/// Final signatures as they arrive over the wire, the partial signatures are parsed one by one
+/// so a bad value is reported against its outcome or win condition
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmittedFinalSignatures {
+    pub funding_psbt_base64: String,
+    pub partial_signatures: SigMap<serde_json::Value>,
+}
+
 pub async fn submit_final_signatures(
     NostrAuth { pubkey, .. }: NostrAuth,
     State(state): State<Arc<AppState>>,
     Path((competition_id, entry_id)): Path<(Uuid, Uuid)>,
-    Json(final_signatures): Json<FinalSignatures>,
+    Json(submitted): Json<SubmittedFinalSignatures>,
 ) -> Result<StatusCode, ErrorResponse> {
     let pubkey = pubkey.to_hex();
-    debug!(
-        "submitted final signatures by: {} {:?}",
-        pubkey, final_signatures
-    );
+    debug!("submitted final signatures by: {} {:?}", pubkey, submitted);
+    let final_signatures = FinalSignatures {
+        funding_psbt_base64: submitted.funding_psbt_base64,
+        partial_signatures: parse_sig_map("partial_signatures", submitted.partial_signatures)
+            .map_err(|e| ErrorResponse::from(Error::from(e)))?,
+    };
 
     state
         .coordinator
@@ -276,6 +507,77 @@ pub async fn submit_final_signatures(
         })
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct FundingPsbtCheck {
+    pub funding_psbt_base64: String,
+}
+
+/// Check a funding psbt the way submitting signatures would, without storing anything
+pub async fn verify_funding_psbt(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path((competition_id, entry_id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<FundingPsbtCheck>,
+) -> Result<StatusCode, ErrorResponse> {
+    let pubkey = pubkey.to_hex();
+    state
+        .coordinator
+        .verify_entry_funding_psbt(pubkey, competition_id, entry_id, &body.funding_psbt_base64)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| {
+            error!("error verifying funding psbt: {:?}", e);
+            e.into()
+        })
+}
+
+/// Part of an entry's partial signatures, for SigMaps too large to send in one request. The
+/// funding psbt may come with any chunk, it's required by the time the last one lands when
+/// escrow is enabled.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PartialSignatureChunk {
+    #[serde(default)]
+    pub funding_psbt_base64: Option<String>,
+    pub partial_signatures: SigMap<PartialSignature>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmittedSignatureChunk {
+    #[serde(default)]
+    pub funding_psbt_base64: Option<String>,
+    pub partial_signatures: SigMap<serde_json::Value>,
+}
+
+pub async fn submit_partial_signature_chunk(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path((competition_id, entry_id)): Path<(Uuid, Uuid)>,
+    Json(submitted): Json<SubmittedSignatureChunk>,
+) -> Result<Json<SignatureChunkProgress>, ErrorResponse> {
+    let pubkey = pubkey.to_hex();
+    let chunk = PartialSignatureChunk {
+        funding_psbt_base64: submitted.funding_psbt_base64,
+        partial_signatures: parse_sig_map("partial_signatures", submitted.partial_signatures)
+            .map_err(|e| ErrorResponse::from(Error::from(e)))?,
+    };
+    debug!(
+        "submitted {} partial signatures for entry {} by: {}",
+        sig_map_len(&chunk.partial_signatures),
+        entry_id,
+        pubkey
+    );
+
+    state
+        .coordinator
+        .submit_partial_signature_chunk(pubkey, competition_id, entry_id, chunk)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error submitting partial signature chunk: {:?}", e);
+            e.into()
+        })
+}
+
 pub async fn submit_ticket_payout(
     NostrAuth { pubkey, .. }: NostrAuth,
     State(state): State<Arc<AppState>>,
@@ -295,3 +597,45 @@ pub async fn submit_ticket_payout(
             e.into()
         })
 }
+
+/// Register an entry with its competition's keygen session again, once an operator removed
+/// a bad registration
+pub async fn reregister_keymeld_participant(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path((competition_id, entry_id)): Path<(Uuid, Uuid)>,
+    Json(registration): Json<KeymeldRegistration>,
+) -> Result<StatusCode, ErrorResponse> {
+    state
+        .coordinator
+        .reregister_keymeld_participant(pubkey.to_hex(), competition_id, entry_id, registration)
+        .await
+        .map(|_| StatusCode::OK)
+        .map_err(|e| {
+            error!("error re-registering keymeld participant: {:?}", e);
+            e.into()
+        })
+}
+
+pub async fn register_escrow_reclaim(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path((competition_id, entry_id)): Path<(Uuid, Uuid)>,
+    Json(reclaim_info): Json<EscrowReclaimInfo>,
+) -> Result<StatusCode, ErrorResponse> {
+    let pubkey = pubkey.to_hex();
+    debug!(
+        "escrow reclaim registered by: {} for entry {}",
+        pubkey, entry_id
+    );
+
+    state
+        .coordinator
+        .register_escrow_reclaim(pubkey, competition_id, entry_id, reclaim_info)
+        .await
+        .map(|_| StatusCode::OK)
+        .map_err(|e| {
+            error!("error registering escrow reclaim: {:?}", e);
+            e.into()
+        })
+}
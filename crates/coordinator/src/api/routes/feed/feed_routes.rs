@@ -0,0 +1,51 @@
+use axum::{
+    extract::State,
+    http::header::{CACHE_CONTROL, CONTENT_TYPE},
+    response::{ErrorResponse, IntoResponse},
+    Json,
+};
+use log::error;
+use std::sync::Arc;
+
+use crate::startup::AppState;
+
+/// Feeds are public and polled by aggregators, let them and any CDN cache briefly
+const FEED_CACHE_CONTROL: &str = "public, max-age=60";
+
+/// Open competitions as JSON, no authentication required
+pub async fn open_competitions_json_feed(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    let feed = state
+        .coordinator
+        .get_open_competition_feed(&state.remote_url)
+        .await
+        .map_err(|e| {
+            error!("error building open competitions feed: {:?}", e);
+            e
+        })?;
+
+    Ok(([(CACHE_CONTROL, FEED_CACHE_CONTROL)], Json(feed)))
+}
+
+/// Open competitions as an Atom feed, generated from the same view as the JSON feed
+pub async fn open_competitions_atom_feed(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    let feed = state
+        .coordinator
+        .get_open_competition_feed(&state.remote_url)
+        .await
+        .map_err(|e| {
+            error!("error building open competitions feed: {:?}", e);
+            e
+        })?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, "application/atom+xml; charset=utf-8"),
+            (CACHE_CONTROL, FEED_CACHE_CONTROL),
+        ],
+        feed.to_atom(&state.remote_url),
+    ))
+}
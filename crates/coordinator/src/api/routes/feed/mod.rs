@@ -0,0 +1,3 @@
+mod feed_routes;
+
+pub use feed_routes::*;
@@ -1,4 +1,5 @@
 mod coordinator;
+mod feed;
 mod home;
 mod pages;
 mod system;
@@ -14,18 +15,25 @@ use std::borrow::Borrow;
 use crate::domain::Error;
 
 pub use coordinator::*;
+pub use feed::*;
 pub use pages::*;
 pub use system::*;
 
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
+        if let Error::Validation(errors) = &self {
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(errors)).into_response();
+        }
         let (status, error_message) = match self.borrow() {
             Error::NoAvailableTickets => (StatusCode::BAD_REQUEST, self.to_string()),
             Error::CompetitionFull => (StatusCode::BAD_REQUEST, self.to_string()),
             Error::BadRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             Error::PaymentFailed(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             Error::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            Error::Conflict(_) => (StatusCode::CONFLICT, self.to_string()),
             Error::InvalidSignature(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            Error::NotInvited(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            Error::InvalidFundingPsbt(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 String::from("internal server error"),
@@ -1,12 +1,13 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
-    response::{Html, IntoResponse},
+    extract::{rejection::JsonRejection, Path, Query, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
     Json,
 };
 use axum_extra::extract::Form;
+use coordinator_core::Sats;
 use log::{error, info};
 use maud::Markup;
 use serde::Deserialize;
@@ -14,19 +15,34 @@ use time::{format_description::well_known::Rfc3339, OffsetDateTime, UtcOffset};
 use uuid::Uuid;
 
 use crate::{
+    api::{
+        admin_session::AdminSessions,
+        extractors::{AuthError, NostrAuth},
+    },
+    domain::{
+        AddSponsorship, AuthActivity, CloneCompetition, CompetitionChange, CompetitionSummary,
+        ConsistencyReport, ContractSizeEstimate, Error, ExpiryTxStatus, PlayerOrderReport,
+        PnlReport, SolvencyReport, Sponsorship, StuckThresholds, Ticket, TiePolicy,
+        UndecodableBlob, UserEntry,
+    },
     infra::bitcoin::SendOptions,
     startup::AppState,
     templates::{
         admin::{
+            alerts::{stuck_competitions_error, stuck_competitions_panel},
             dashboard::{
-                admin_dashboard, competition_error, competition_success, CompetitionDefaults,
+                admin_dashboard, competition_error, competition_success,
+                competition_success_message, contract_estimate_panel, CompetitionDefaults,
                 Forecast, Observation, Station, StationWithWeather,
             },
             is_allowed_station,
+            login::admin_login_page,
+            pnl::{pnl_report_error, pnl_report_panel},
             wallet::{
                 fee_estimates_rows, send_error, send_success, wallet_balance_section,
                 wallet_outputs_rows, wallet_page, WalletBalance, WalletOutput,
             },
+            webhooks::{webhook_deliveries_error, webhook_deliveries_panel},
         },
         layouts::admin::{admin_base, AdminPageConfig},
     },
@@ -49,12 +65,49 @@ fn render_admin_fragment(
             api_base: &state.private_url,
             oracle_base: &state.oracle_url,
             esplora_url: &state.esplora_url,
-            network: &state.network,
+            network: state.network.as_str(),
         };
         Html(admin_base(&config, content).into_string())
     }
 }
 
+/// Sign-in page for the admin dashboard
+pub async fn admin_login_page_handler() -> Html<String> {
+    Html(admin_login_page().into_string())
+}
+
+/// Starts a dashboard session for an operator on the admin allow-list, proven by the request's
+/// NIP-98 header, and hands it back as a cookie so the admin pages can be browsed normally
+pub async fn admin_login_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    NostrAuth { pubkey, .. }: NostrAuth,
+) -> Result<impl IntoResponse, AuthError> {
+    if !state.settings.is_admin(&pubkey) {
+        return Err(AuthError::NotAdmin(pubkey.to_hex()));
+    }
+
+    let token = state.admin_sessions.issue(&pubkey);
+    // Same check NostrAuth uses to decide the request came in over https
+    let secure = headers.contains_key("x-forwarded-proto");
+    info!("Admin {} signed in to the dashboard", pubkey.to_hex());
+    Ok((
+        StatusCode::NO_CONTENT,
+        [(SET_COOKIE, state.admin_sessions.cookie(&token, secure))],
+    ))
+}
+
+/// Ends the dashboard session by clearing its cookie
+pub async fn admin_logout_handler() -> impl IntoResponse {
+    (
+        StatusCode::NO_CONTENT,
+        [
+            (SET_COOKIE.as_str(), AdminSessions::cleared_cookie()),
+            ("HX-Redirect", String::from("/admin/login")),
+        ],
+    )
+}
+
 /// Admin dashboard page (competition tab)
 pub async fn admin_page_handler(State(state): State<Arc<AppState>>) -> Html<String> {
     let config = AdminPageConfig {
@@ -62,7 +115,7 @@ pub async fn admin_page_handler(State(state): State<Arc<AppState>>) -> Html<Stri
         api_base: &state.private_url,
         oracle_base: &state.oracle_url,
         esplora_url: &state.esplora_url,
-        network: &state.network,
+        network: state.network.as_str(),
     };
 
     // Fetch stations from oracle and filter to top 200 cities
@@ -125,6 +178,84 @@ pub async fn admin_competition_fragment(
     render_admin_fragment(&headers, &state, "5day4cast Admin - Competition", content)
 }
 
+/// Stuck competitions panel fragment (for HTMX load/refresh)
+pub async fn admin_alerts_fragment(State(state): State<Arc<AppState>>) -> Html<String> {
+    // Thresholds can be changed by a settings reload, so read them per request
+    let thresholds = state
+        .settings
+        .read(|settings| StuckThresholds::from(&settings.alert_settings));
+    match state
+        .coordinator
+        .get_stuck_competition_report(&thresholds)
+        .await
+    {
+        Ok(report) => Html(stuck_competitions_panel(&report).into_string()),
+        Err(e) => {
+            error!("Failed to build stuck competition report: {e}");
+            Html(stuck_competitions_error(&e.to_string()).into_string())
+        }
+    }
+}
+
+/// Profit and loss panel fragment (for HTMX load/refresh)
+pub async fn admin_pnl_fragment(State(state): State<Arc<AppState>>) -> Html<String> {
+    match state.coordinator.get_pnl_report().await {
+        Ok(report) => Html(pnl_report_panel(&report).into_string()),
+        Err(e) => {
+            error!("Failed to build profit and loss report: {e}");
+            Html(pnl_report_error(&e.to_string()).into_string())
+        }
+    }
+}
+
+/// Number of deliveries shown in the webhook panel
+const RECENT_WEBHOOK_DELIVERIES: u32 = 25;
+
+/// Webhook deliveries panel fragment (for HTMX load/refresh)
+pub async fn admin_webhooks_fragment(State(state): State<Arc<AppState>>) -> Html<String> {
+    render_webhook_deliveries(&state, None).await
+}
+
+/// Queue a stored webhook delivery to be sent again
+pub async fn admin_replay_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    Path(delivery_id): Path<Uuid>,
+) -> Html<String> {
+    let notice = match state.webhook_store.get_delivery(delivery_id).await {
+        Ok(Some(_)) => match state.webhooks.replay(delivery_id) {
+            Ok(()) => {
+                info!("Queued replay of webhook delivery {}", delivery_id);
+                format!("Replay of delivery {} queued", delivery_id)
+            }
+            Err(e) => {
+                error!("Failed to queue replay of webhook delivery {delivery_id}: {e}");
+                return Html(webhook_deliveries_error(&e.to_string()).into_string());
+            }
+        },
+        Ok(None) => format!("Delivery {} not found", delivery_id),
+        Err(e) => {
+            error!("Failed to load webhook delivery {delivery_id}: {e}");
+            return Html(webhook_deliveries_error(&e.to_string()).into_string());
+        }
+    };
+
+    render_webhook_deliveries(&state, Some(&notice)).await
+}
+
+async fn render_webhook_deliveries(state: &AppState, notice: Option<&str>) -> Html<String> {
+    match state
+        .webhook_store
+        .get_recent_deliveries(RECENT_WEBHOOK_DELIVERIES)
+        .await
+    {
+        Ok(deliveries) => Html(webhook_deliveries_panel(&deliveries, notice).into_string()),
+        Err(e) => {
+            error!("Failed to load webhook deliveries: {e}");
+            Html(webhook_deliveries_error(&e.to_string()).into_string())
+        }
+    }
+}
+
 /// Admin wallet page (full page for direct navigation, fragment for HTMX)
 pub async fn admin_wallet_fragment(
     State(state): State<Arc<AppState>>,
@@ -193,15 +324,76 @@ pub struct CreateCompetitionForm {
     pub signing_date: String,
     pub start_observation_date: String,
     pub end_observation_date: String,
+    #[serde(default)]
+    pub signing_deadline: Option<String>,
     pub number_of_values_per_entry: usize,
     pub total_allowed_entries: usize,
-    pub entry_fee: usize,
+    pub entry_fee: Sats,
     pub coordinator_fee_percentage: usize,
     pub number_of_places_win: usize,
     #[serde(default)]
     pub locations: Vec<String>,
     #[serde(default)]
     pub relative_locktime_block_delta: Option<u16>,
+    /// Comma or whitespace separated relay urls
+    #[serde(default)]
+    pub backup_relays: Option<String>,
+    /// Checkbox, only sent when checked
+    #[serde(default)]
+    pub private: Option<String>,
+    /// Comma or whitespace separated pubkeys invited when the competition is private
+    #[serde(default)]
+    pub invited_pubkeys: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Left empty to derive the slug from the name
+    #[serde(default)]
+    pub slug: Option<String>,
+    /// Comma or whitespace separated tags
+    #[serde(default)]
+    pub tags: Option<String>,
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+    /// Left empty to use the coordinator's setting
+    #[serde(default)]
+    pub invoice_settlement_confirmations: Option<u32>,
+}
+
+/// Parameters a competition's contract size depends on, the rest of the create form is ignored
+#[derive(Debug, Deserialize)]
+pub struct ContractEstimateQuery {
+    pub total_allowed_entries: usize,
+    pub number_of_places_win: usize,
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+}
+
+impl ContractEstimateQuery {
+    fn estimate(&self) -> ContractSizeEstimate {
+        ContractSizeEstimate::new(
+            self.total_allowed_entries,
+            self.number_of_places_win,
+            self.tie_policy,
+        )
+    }
+}
+
+/// Dry run of the contract size checks a new competition goes through, without creating it
+pub async fn admin_contract_estimate_handler(
+    Query(query): Query<ContractEstimateQuery>,
+) -> Json<ContractSizeEstimate> {
+    Json(query.estimate())
+}
+
+/// Contract size panel of the create form, posted the form's values whenever they change
+pub async fn admin_contract_estimate_fragment(
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<ContractEstimateQuery>,
+) -> Html<String> {
+    Html(
+        contract_estimate_panel(&form.estimate(), &state.coordinator.contract_limits())
+            .into_string(),
+    )
 }
 
 /// Handle competition creation from HTMX form
@@ -240,6 +432,18 @@ pub async fn admin_create_competition_handler(
         }
     };
 
+    let signing_deadline = match form.signing_deadline.as_deref().filter(|s| !s.is_empty()) {
+        Some(deadline) => match OffsetDateTime::parse(deadline, &Rfc3339) {
+            Ok(dt) => Some(dt.to_offset(UtcOffset::UTC)),
+            Err(e) => {
+                return Html(
+                    competition_error(&format!("Invalid signing deadline: {}", e)).into_string(),
+                )
+            }
+        },
+        None => None,
+    };
+
     // Validate at least 1 location is selected
     if form.locations.is_empty() {
         return Html(competition_error("At least 1 location must be selected").into_string());
@@ -253,7 +457,14 @@ pub async fn admin_create_competition_handler(
     }
 
     // Calculate total pool
-    let total_competition_pool = form.entry_fee * form.total_allowed_entries;
+    let Some(total_competition_pool) = form
+        .entry_fee
+        .checked_mul(form.total_allowed_entries as u64)
+    else {
+        return Html(
+            competition_error("Entry fee times total allowed entries is too large").into_string(),
+        );
+    };
 
     // Create the competition via the coordinator
     let create_event = crate::domain::CreateEvent {
@@ -269,10 +480,84 @@ pub async fn admin_create_competition_handler(
         coordinator_fee_percentage: form.coordinator_fee_percentage,
         total_competition_pool,
         relative_locktime_block_delta: form.relative_locktime_block_delta,
+        signing_deadline,
+        backup_relays: split_list(form.backup_relays.as_deref()),
+        private: form.private.is_some(),
+        name: form.name.filter(|name| !name.trim().is_empty()),
+        slug: form.slug.filter(|slug| !slug.trim().is_empty()),
+        tie_policy: form.tie_policy,
+        invoice_settlement_confirmations: form.invoice_settlement_confirmations,
+        tags: split_list(form.tags.as_deref()),
+    };
+
+    let competition = match state.coordinator.create_competition(create_event).await {
+        Ok(competition) => competition,
+        Err(e) => return Html(competition_error(&e.to_string()).into_string()),
     };
 
-    match state.coordinator.create_competition(create_event).await {
-        Ok(competition) => Html(competition_success(&competition.id).into_string()),
+    let invited = split_list(form.invited_pubkeys.as_deref());
+    if competition.event_submission.private && !invited.is_empty() {
+        if let Err(e) = state
+            .coordinator
+            .invite_to_competition(competition.id, invited)
+            .await
+        {
+            return Html(
+                competition_error(&format!(
+                    "Competition {} created, but inviting failed: {}",
+                    competition.id, e
+                ))
+                .into_string(),
+            );
+        }
+    }
+
+    Html(competition_success(&competition.id).into_string())
+}
+
+/// Split a comma or whitespace separated form field
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Form data for inviting pubkeys to a private competition
+#[derive(Debug, Deserialize)]
+pub struct InviteForm {
+    pub competition_id: String,
+    /// Comma or whitespace separated pubkeys
+    pub pubkeys: String,
+}
+
+/// Invite pubkeys to a private competition, allowed until its entries close
+pub async fn admin_invite_handler(
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<InviteForm>,
+) -> Html<String> {
+    let competition_id = match Uuid::parse_str(form.competition_id.trim()) {
+        Ok(id) => id,
+        Err(e) => {
+            return Html(competition_error(&format!("Invalid competition ID: {}", e)).into_string())
+        }
+    };
+
+    match state
+        .coordinator
+        .invite_to_competition(competition_id, split_list(Some(&form.pubkeys)))
+        .await
+    {
+        Ok(invitations) => Html(
+            competition_success_message(&format!(
+                "Competition {} has {} invited pubkeys",
+                competition_id,
+                invitations.len()
+            ))
+            .into_string(),
+        ),
         Err(e) => Html(competition_error(&e.to_string()).into_string()),
     }
 }
@@ -539,3 +824,342 @@ pub async fn admin_settle_test_invoice_handler(
         }
     }
 }
+
+/// Rebroadcast a competition's expiry transaction and report how its fee compares to the current
+/// estimate. The expiry transaction is pre-signed by every player, so this can't raise its fee.
+pub async fn admin_rebroadcast_expiry_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+) -> Result<Json<ExpiryTxStatus>, Error> {
+    let status = state
+        .coordinator
+        .rebroadcast_expiry_transaction(competition_id)
+        .await?;
+    info!(
+        "Admin rebroadcast of expiry tx {} for competition {} ({} confirmations)",
+        status.txid, competition_id, status.confirmations
+    );
+    Ok(Json(status))
+}
+
+/// Read-only audit of stored competition state against the chain
+pub async fn admin_consistency_report_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ConsistencyReport>, Error> {
+    let report = state.coordinator.get_consistency_report().await?;
+    if !report.is_consistent() {
+        info!(
+            "Consistency check found mismatches in {} competition(s)",
+            report
+                .competitions
+                .iter()
+                .filter(|competition| !competition.mismatches.is_empty())
+                .count()
+        );
+    }
+    Ok(Json(report))
+}
+
+/// Player order the keymeld subset definitions were built with next to the contract's, for
+/// debugging signatures made for the wrong player indices
+pub async fn admin_player_order_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+) -> Result<Json<PlayerOrderReport>, Error> {
+    let report = state
+        .coordinator
+        .get_player_order_report(competition_id)
+        .await?;
+    if !report.mismatches.is_empty() {
+        info!(
+            "Player order of competition {} has {} mismatch(es)",
+            competition_id,
+            report.mismatches.len()
+        );
+    }
+    Ok(Json(report))
+}
+
+/// Revenue, costs and net per competition and in aggregate, from the accounting ledger
+pub async fn admin_pnl_report_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PnlReport>, Error> {
+    Ok(Json(state.coordinator.get_pnl_report().await?))
+}
+
+/// Funding active competitions still need from the wallet against its confirmed balance, with
+/// the competitions it can't cover flagged, worth checking before creating another one
+pub async fn admin_solvency_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SolvencyReport>, Error> {
+    Ok(Json(state.coordinator.get_solvency_report().await?))
+}
+
+/// Read-only scan for stored JSON blobs that no longer decode, so a corrupted column shows up
+/// here instead of as a failed load in a watcher
+pub async fn admin_blob_integrity_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<UndecodableBlob>>, Error> {
+    let undecodable = state.coordinator.find_undecodable_blobs().await?;
+    if !undecodable.is_empty() {
+        error!(
+            "Integrity check found {} undecodable blob(s)",
+            undecodable.len()
+        );
+    }
+    Ok(Json(undecodable))
+}
+
+/// Cancel a competition that hasn't broadcast its funding transaction yet
+pub async fn admin_cancel_competition_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+) -> Result<Json<CompetitionSummary>, Error> {
+    let competition = state.coordinator.cancel_competition(competition_id).await?;
+    Ok(Json(CompetitionSummary::from(&competition)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForceCompleteRequest {
+    /// Why the competition was completed by hand, kept with the override
+    pub reason: String,
+}
+
+/// Mark a competition completed after its winners were paid out of band. Only allowed once the
+/// outcome is on chain and every reclaim transaction has been broadcast.
+pub async fn admin_force_complete_competition_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+    auth: Result<NostrAuth, AuthError>,
+    Json(request): Json<ForceCompleteRequest>,
+) -> Result<Json<CompetitionSummary>, Error> {
+    let admin = auth
+        .map(|auth| auth.pubkey.to_hex())
+        .unwrap_or_else(|_| String::from("anonymous"));
+    let competition = state
+        .coordinator
+        .force_complete_competition(competition_id, &request.reason, &admin)
+        .await?;
+    Ok(Json(CompetitionSummary::from(&competition)))
+}
+
+/// Clear the failure of a failed competition so it's processed again
+pub async fn admin_retry_competition_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+) -> Result<Json<CompetitionSummary>, Error> {
+    let competition = state.coordinator.retry_competition(competition_id).await?;
+    Ok(Json(CompetitionSummary::from(&competition)))
+}
+
+/// Release a stuck, unpaid ticket reservation
+pub async fn admin_release_ticket_handler(
+    State(state): State<Arc<AppState>>,
+    Path(ticket_id): Path<Uuid>,
+) -> Result<Json<Ticket>, Error> {
+    let ticket = state.coordinator.release_ticket(ticket_id).await?;
+    Ok(Json(ticket))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompTicketQuery {
+    /// Why the ticket was comped, kept with the ticket for accounting
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Remove an entry's registration from its competition's keymeld keygen session so the player
+/// can register again, for a registration that keeps keygen from completing
+pub async fn admin_reset_keymeld_registration_handler(
+    State(state): State<Arc<AppState>>,
+    Path((competition_id, entry_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<UserEntry>, Error> {
+    let entry = state
+        .coordinator
+        .reset_keymeld_registration(competition_id, entry_id)
+        .await?;
+    Ok(Json(entry))
+}
+
+/// Mark a reserved ticket as prepaid, recording the admin's pubkey against it. When no admin
+/// allow-list is configured the request may be unsigned and the comp is recorded as anonymous.
+pub async fn admin_comp_ticket_handler(
+    State(state): State<Arc<AppState>>,
+    Path(ticket_id): Path<Uuid>,
+    Query(query): Query<CompTicketQuery>,
+    auth: Result<NostrAuth, AuthError>,
+) -> Result<Json<Ticket>, Error> {
+    let comped_by = auth
+        .map(|auth| auth.pubkey.to_hex())
+        .unwrap_or_else(|_| String::from("anonymous"));
+    let note = query.note.filter(|note| !note.trim().is_empty());
+    let ticket = state
+        .coordinator
+        .comp_ticket(ticket_id, &comped_by, note)
+        .await?;
+    Ok(Json(ticket))
+}
+
+/// Add a sponsor's sats, already received on chain or over lightning, to a competition's pool.
+/// When no admin allow-list is configured the request may be unsigned and the sponsorship is
+/// recorded as anonymous.
+pub async fn admin_sponsor_competition_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+    auth: Result<NostrAuth, AuthError>,
+    Json(request): Json<AddSponsorship>,
+) -> Result<Json<Sponsorship>, Error> {
+    let recorded_by = auth
+        .map(|auth| auth.pubkey.to_hex())
+        .unwrap_or_else(|_| String::from("anonymous"));
+    let sponsorship = state
+        .coordinator
+        .add_sponsorship(competition_id, request, &recorded_by)
+        .await?;
+    Ok(Json(sponsorship))
+}
+
+pub async fn admin_list_sponsorships_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+) -> Result<Json<Vec<Sponsorship>>, Error> {
+    let sponsorships = state.coordinator.get_sponsorships(competition_id).await?;
+    Ok(Json(sponsorships))
+}
+
+/// Create a competition from an existing one's parameters, with the fields in the body
+/// replaced. Returns the new competition and which fields differ from the source. The
+/// dashboard's clone button posts here as well and gets a notification fragment back.
+pub async fn admin_clone_competition_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+    headers: HeaderMap,
+    overrides: Result<Json<CloneCompetition>, JsonRejection>,
+) -> Response {
+    if headers.contains_key("HX-Request") {
+        return clone_competition_fragment(&state, competition_id, &headers)
+            .await
+            .into_response();
+    }
+    let Json(overrides) = match overrides {
+        Ok(overrides) => overrides,
+        Err(rejection) => return rejection.into_response(),
+    };
+    match state
+        .coordinator
+        .clone_competition(competition_id, overrides)
+        .await
+    {
+        Ok(cloned) => Json(cloned).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Dashboard clone button, the new start date comes from the button's prompt and the other
+/// dates keep their distance to it
+async fn clone_competition_fragment(
+    state: &AppState,
+    competition_id: Uuid,
+    headers: &HeaderMap,
+) -> Html<String> {
+    let start = headers
+        .get("HX-Prompt")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let start_observation_date = match start.map(|start| OffsetDateTime::parse(start, &Rfc3339)) {
+        None => None,
+        Some(Ok(start)) => Some(start.to_offset(UtcOffset::UTC)),
+        Some(Err(e)) => {
+            return Html(competition_error(&format!("Invalid start date: {}", e)).into_string())
+        }
+    };
+    let overrides = CloneCompetition {
+        start_observation_date,
+        ..Default::default()
+    };
+
+    match state
+        .coordinator
+        .clone_competition(competition_id, overrides)
+        .await
+    {
+        Ok(cloned) => Html(
+            competition_success_message(&format!(
+                "Competition {} cloned from {}, changed: {}",
+                cloned.competition.id,
+                competition_id,
+                cloned
+                    .changes
+                    .iter()
+                    .map(|change| change.field.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .into_string(),
+        ),
+        Err(e) => Html(competition_error(&e.to_string()).into_string()),
+    }
+}
+
+/// How a competition got to its current state, one row per save with the fields it changed
+pub async fn admin_competition_changes_handler(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<Uuid>,
+) -> Result<Json<Vec<CompetitionChange>>, Error> {
+    Ok(Json(
+        state
+            .coordinator
+            .get_competition_changes(competition_id)
+            .await?,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PayoutListQuery {
+    #[serde(default)]
+    pub failed: bool,
+}
+
+/// Pending payouts, or entries with failed payout attempts when `failed=true`
+pub async fn admin_list_payouts_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PayoutListQuery>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let payouts = if query.failed {
+        serde_json::to_value(state.coordinator.list_failed_payouts().await?)?
+    } else {
+        serde_json::to_value(state.coordinator.list_pending_payouts().await?)?
+    };
+    Ok(Json(payouts))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthActivityQuery {
+    /// How far back to look, a day by default
+    #[serde(default = "default_auth_activity_hours")]
+    pub hours: i64,
+}
+
+fn default_auth_activity_hours() -> i64 {
+    24
+}
+
+/// A year, the log is pruned well before that by default
+const MAX_AUTH_ACTIVITY_HOURS: i64 = 366 * 24;
+
+/// Sign-ins per pubkey over the last `hours`, busiest first, for investigating shared or abused
+/// accounts
+pub async fn admin_auth_activity_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuthActivityQuery>,
+) -> Result<Json<Vec<AuthActivity>>, Error> {
+    if !(1..=MAX_AUTH_ACTIVITY_HOURS).contains(&query.hours) {
+        return Err(Error::BadRequest(format!(
+            "hours must be between 1 and {}",
+            MAX_AUTH_ACTIVITY_HOURS
+        )));
+    }
+    let since = OffsetDateTime::now_utc() - time::Duration::hours(query.hours);
+    Ok(Json(state.users_info.get_auth_activity(since).await?))
+}
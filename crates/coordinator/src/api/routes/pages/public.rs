@@ -1,11 +1,10 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use dlctix::secp::Point;
 use log::{debug, error, warn};
 use nostr_sdk::ToBech32;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{Html, IntoResponse, Response},
 };
@@ -15,8 +14,11 @@ use uuid::Uuid;
 use crate::{
     api::extractors::{AuthError, NostrAuth},
     domain::{
-        scoring::{calculate_option_score, Forecast, Observation},
-        SearchBy,
+        scoring::{
+            calculate_option_score, score_entries, Forecast, Leaderboard, LeaderboardStatus,
+            Observation,
+        },
+        BlockEstimate, CompetitionTimeline, SearchBy,
     },
     infra::oracle::ValueOptions,
     startup::AppState,
@@ -134,8 +136,10 @@ pub async fn public_page_handler(State(state): State<Arc<AppState>>) -> Html<Str
         network: &state.bitcoin.get_network().to_string(),
     };
 
-    let competitions = fetch_competitions(&state).await;
-    let content = competitions_page(&competitions);
+    // Full page loads can't be signed, invited players see their private competitions once
+    // the list is refreshed through HTMX
+    let competitions = fetch_competitions(&state, None, &SearchBy::default()).await;
+    let content = competitions_page(&competitions, &[]);
     Html(base(&config, content).into_string())
 }
 
@@ -143,15 +147,23 @@ pub async fn public_page_handler(State(state): State<Arc<AppState>>) -> Html<Str
 pub async fn competitions_fragment(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
+    auth: Result<NostrAuth, AuthError>,
+    Query(filter): Query<SearchBy>,
 ) -> Html<String> {
-    let competitions = fetch_competitions(&state).await;
-    let content = competitions_page(&competitions);
+    let viewer = auth.ok().map(|auth| auth.pubkey);
+    let competitions = fetch_competitions(&state, viewer.as_ref(), &filter).await;
+    let content = competitions_page(&competitions, filter.tags.as_deref().unwrap_or_default());
     render_fragment(&headers, &state, "Competitions - Fantasy Weather", content)
 }
 
 /// Competition rows fragment (for HTMX auto-refresh)
-pub async fn competitions_rows_fragment(State(state): State<Arc<AppState>>) -> Html<String> {
-    let competitions = fetch_competitions(&state).await;
+pub async fn competitions_rows_fragment(
+    State(state): State<Arc<AppState>>,
+    auth: Result<NostrAuth, AuthError>,
+    Query(filter): Query<SearchBy>,
+) -> Html<String> {
+    let viewer = auth.ok().map(|auth| auth.pubkey);
+    let competitions = fetch_competitions(&state, viewer.as_ref(), &filter).await;
     Html(
         html! {
             @for comp in &competitions {
@@ -176,7 +188,7 @@ pub async fn entries_fragment(
     let content = if entries.is_empty() {
         no_entries()
     } else {
-        entries_page(&entries)
+        entries_page(&entries, &state.esplora_url)
     };
     render_fragment(&headers, &state, "My Entries - Fantasy Weather", content)
 }
@@ -198,9 +210,11 @@ pub async fn entry_form_fragment(
     State(state): State<Arc<AppState>>,
     Path(competition_id): Path<Uuid>,
     headers: HeaderMap,
+    auth: Result<NostrAuth, AuthError>,
 ) -> Html<String> {
     // Get competition details
-    let competitions = fetch_competitions(&state).await;
+    let viewer = auth.ok().map(|auth| auth.pubkey);
+    let competitions = fetch_competitions(&state, viewer.as_ref()).await;
     let competition = competitions
         .iter()
         .find(|c| c.id == competition_id.to_string());
@@ -324,6 +338,17 @@ pub async fn entry_detail_fragment(
         None
     };
 
+    // Timelock countdowns, only available once the contract is created
+    let timeline = if let Some(ref entry) = entry {
+        state
+            .coordinator
+            .get_competition_timeline(entry.event_id, Some(entry.pubkey.clone()))
+            .await
+            .ok()
+    } else {
+        None
+    };
+
     Html(
         html! {
             div {
@@ -388,6 +413,9 @@ pub async fn entry_detail_fragment(
                             }
                         }
                     }
+                    @if let Some(ref timeline) = timeline {
+                        (timeline_countdowns(timeline, entry.id))
+                    }
                 } @else {
                     p class="has-text-grey" { "Entry details not available" }
                 }
@@ -451,6 +479,80 @@ fn pick_card(data: &PickCardData) -> Markup {
     }
 }
 
+/// Countdowns to the contract's timelocks, with this entry's exit windows once known
+fn timeline_countdowns(timeline: &CompetitionTimeline, entry_id: Uuid) -> Markup {
+    let entry = timeline
+        .your_entries
+        .iter()
+        .find(|entry| entry.entry_id == entry_id);
+
+    html! {
+        div class="entry-timeline mt-4" {
+            h6 class="title is-6 mb-2" { "Timelocks" }
+            @if let (Some(outcome), Some(first_delta), Some(second_delta)) =
+                (timeline.outcome_confirmation, timeline.first_delta, timeline.second_delta)
+            {
+                (countdown_row(
+                    if timeline.outcome_confirmed { "Outcome confirmed" } else { "Outcome expected" },
+                    &outcome,
+                ))
+                (countdown_row("Split transactions valid", &first_delta))
+                (countdown_row("Unclaimed payouts reclaimed", &second_delta))
+                @if let Some(entry) = entry {
+                    @if entry.is_winner != Some(false) {
+                        (countdown_row("Lightning payout closes", &entry.sellback.closes))
+                        (countdown_row("On-chain claim opens", &entry.onchain_claim.opens))
+                    }
+                }
+                p class="is-size-7 has-text-grey mt-1" {
+                    "Estimated at 10 minutes per block from height " (timeline.tip_height)
+                }
+            } @else {
+                p class="is-size-7 has-text-grey" {
+                    "Estimates available once the funding transaction confirms"
+                }
+            }
+            script {
+                (maud::PreEscaped(r#"
+                (function() {
+                    function tick() {
+                        var els = document.querySelectorAll('.entry-timeline .countdown[data-until]');
+                        if (!els.length) return;
+                        els.forEach(function(el) {
+                            var mins = Math.round((new Date(el.dataset.until) - Date.now()) / 60000);
+                            if (mins <= 0) { el.textContent = 'any block now'; return; }
+                            var days = Math.floor(mins / 1440);
+                            var hours = Math.floor((mins % 1440) / 60);
+                            el.textContent = '~' + (days ? days + 'd ' : '') + hours + 'h ' + (mins % 60) + 'm';
+                            el.title = new Date(el.dataset.until).toLocaleString();
+                        });
+                        setTimeout(tick, 60000);
+                    }
+                    tick();
+                })();
+                "#))
+            }
+        }
+    }
+}
+
+fn countdown_row(label: &str, estimate: &BlockEstimate) -> Markup {
+    html! {
+        div class="entry-timeline-row is-size-7" {
+            strong { (label) }
+            " · block " (estimate.height) " · "
+            @if estimate.reached() {
+                span class="has-text-success" { "reached" }
+            } @else {
+                span class="countdown"
+                     data-until=(estimate.estimated_at.format(&time::format_description::well_known::Rfc3339).unwrap_or_default()) {
+                    (estimate.blocks_remaining) " blocks"
+                }
+            }
+        }
+    }
+}
+
 /// Format a numeric value with unit for display
 fn format_value(value: Option<f64>, unit: &str) -> String {
     match value {
@@ -501,54 +603,36 @@ async fn fetch_entry_weather_data(
     state: &AppState,
     entry: &crate::domain::UserEntry,
 ) -> Option<EntryWeatherData> {
-    use std::collections::HashMap;
-
-    // Get the competition to find observation dates
     let competition = state
         .coordinator
         .get_competition(entry.event_id)
         .await
         .ok()?;
 
-    // Get station IDs from the entry's picks
-    let station_ids: Vec<&str> = entry
-        .entry_submission
-        .expected_observations
-        .iter()
-        .map(|obs| obs.stations.as_str())
-        .collect();
-
-    if station_ids.is_empty() {
+    if entry.entry_submission.expected_observations.is_empty() {
         return None;
     }
 
-    // Fetch forecasts and observations for the competition period
-    let (forecasts, observations) = tokio::join!(
-        fetch_entry_forecasts(
-            &state.oracle_url,
-            &station_ids,
-            competition.event_submission.start_observation_date,
-            competition.event_submission.end_observation_date
-        ),
-        fetch_entry_observations(
-            &state.oracle_url,
-            &station_ids,
-            competition.event_submission.start_observation_date,
-            competition.event_submission.end_observation_date
-        )
-    );
-
-    let forecasts = forecasts.ok().unwrap_or_default();
-    let observations = observations.ok().unwrap_or_default();
+    // Scored from the competition's observation data, cached once it's attested
+    let data = match state.coordinator.get_observation_data(&competition).await {
+        Ok(data) => data,
+        Err(e) => {
+            warn!(
+                "Failed to get observation data for {}: {}",
+                competition.id, e
+            );
+            Default::default()
+        }
+    };
 
     // Index by station ID
     let mut forecast_map: HashMap<String, Forecast> = HashMap::new();
-    for f in forecasts {
+    for f in data.forecasts {
         forecast_map.insert(f.station_id.clone(), f);
     }
 
     let mut observation_map: HashMap<String, Observation> = HashMap::new();
-    for o in observations {
+    for o in data.observations {
         observation_map.insert(o.station_id.clone(), o);
     }
 
@@ -596,130 +680,34 @@ async fn fetch_entry_weather_data(
     })
 }
 
-/// Fetch forecasts for entry score calculation
-async fn fetch_entry_forecasts(
-    oracle_url: &str,
-    station_ids: &[&str],
-    start: time::OffsetDateTime,
-    end: time::OffsetDateTime,
-) -> Result<Vec<Forecast>, anyhow::Error> {
-    if station_ids.is_empty() {
-        return Ok(vec![]);
-    }
-
-    let client = reqwest_middleware::reqwest::Client::new();
-
-    let start_str = start
-        .format(&time::format_description::well_known::Rfc3339)
-        .unwrap_or_default();
-    let end_str = end
-        .format(&time::format_description::well_known::Rfc3339)
-        .unwrap_or_default();
-
-    let station_ids_param = station_ids.join(",");
-
-    let response = client
-        .get(format!(
-            "{}/stations/forecasts?station_ids={}&start={}&end={}",
-            oracle_url, station_ids_param, start_str, end_str
-        ))
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        // The oracle returns forecasts with temp_high/temp_low as integers
-        #[derive(serde::Deserialize)]
-        struct RawForecast {
-            station_id: String,
-            temp_high: i64,
-            temp_low: i64,
-            #[serde(default)]
-            wind_speed: Option<f64>,
-        }
-
-        let raw_forecasts: Vec<RawForecast> = response.json().await?;
-        Ok(raw_forecasts
-            .into_iter()
-            .map(|f| Forecast {
-                station_id: f.station_id,
-                temp_high: Some(f.temp_high as f64),
-                temp_low: Some(f.temp_low as f64),
-                wind_speed: f.wind_speed,
-            })
-            .collect())
-    } else {
-        Ok(vec![])
-    }
-}
-
-/// Fetch observations for entry score calculation
-async fn fetch_entry_observations(
-    oracle_url: &str,
-    station_ids: &[&str],
-    start: time::OffsetDateTime,
-    end: time::OffsetDateTime,
-) -> Result<Vec<Observation>, anyhow::Error> {
-    if station_ids.is_empty() {
-        return Ok(vec![]);
-    }
-
-    let client = reqwest_middleware::reqwest::Client::new();
-
-    let start_str = start
-        .format(&time::format_description::well_known::Rfc3339)
-        .unwrap_or_default();
-    let end_str = end
-        .format(&time::format_description::well_known::Rfc3339)
-        .unwrap_or_default();
-
-    let station_ids_param = station_ids.join(",");
-
-    let response = client
-        .get(format!(
-            "{}/stations/observations?station_ids={}&start={}&end={}",
-            oracle_url, station_ids_param, start_str, end_str
-        ))
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        #[derive(serde::Deserialize)]
-        struct RawObservation {
-            station_id: String,
-            temp_high: f64,
-            temp_low: f64,
-            #[serde(default)]
-            wind_speed: Option<f64>,
-        }
-
-        let raw_observations: Vec<RawObservation> = response.json().await?;
-        Ok(raw_observations
-            .into_iter()
-            .map(|o| Observation {
-                station_id: o.station_id,
-                temp_high: Some(o.temp_high),
-                temp_low: Some(o.temp_low),
-                wind_speed: o.wind_speed,
-            })
-            .collect())
-    } else {
-        Ok(vec![])
-    }
-}
-
 // Helper functions
 
-async fn fetch_competitions(state: &AppState) -> Vec<CompetitionView> {
-    match state.coordinator.get_competitions().await {
+async fn fetch_competitions(
+    state: &AppState,
+    viewer: Option<&nostr_sdk::PublicKey>,
+    filter: &SearchBy,
+) -> Vec<CompetitionView> {
+    match state.coordinator.get_visible_competitions(viewer).await {
         Ok(competitions) => competitions
             .into_iter()
+            .filter(|c| filter.matches_tags(&c.event_submission.tags))
             .map(|c| {
                 let status = determine_competition_status(&c);
                 let can_enter = status == "Registration"
                     && c.total_entries < c.event_submission.total_allowed_entries as u64;
+                let next_deadline =
+                    c.next_deadline(time::OffsetDateTime::now_utc())
+                        .map(|(kind, at)| {
+                            (
+                                kind.label().to_string(),
+                                at.format(&time::format_description::well_known::Rfc3339)
+                                    .unwrap_or_default(),
+                            )
+                        });
 
                 CompetitionView {
                     id: c.id.to_string(),
+                    name: c.name.clone(),
                     start_time: c
                         .event_submission
                         .start_observation_date
@@ -736,12 +724,16 @@ async fn fetch_competitions(state: &AppState) -> Vec<CompetitionView> {
                         .format(&time::format_description::well_known::Rfc3339)
                         .unwrap_or_default(),
                     status,
-                    entry_fee: c.event_submission.entry_fee as u64,
-                    total_pool: c.event_submission.total_competition_pool as u64,
+                    phase: c.user_facing_phase(),
+                    next_deadline,
+                    entry_fee: c.event_submission.entry_fee.to_sat(),
+                    total_pool: c.event_submission.total_competition_pool.to_sat(),
                     total_entries: c.total_entries,
                     num_winners: c.event_submission.number_of_places_win as u64,
                     can_enter,
                     number_of_values_per_entry: c.event_submission.number_of_values_per_entry,
+                    private: c.event_submission.private,
+                    tags: c.event_submission.tags.clone(),
                 }
             })
             .collect(),
@@ -783,79 +775,29 @@ fn determine_competition_status(competition: &crate::domain::Competition) -> Str
 async fn fetch_eligible_payouts(state: &AppState, pubkey: &str) -> Vec<PayoutView> {
     debug!("Fetching eligible payouts for pubkey: {}", pubkey);
 
-    let entries = match state
+    let payouts = match state
         .coordinator
-        .get_entries(pubkey.to_string(), SearchBy { event_ids: None })
+        .get_eligible_payouts(pubkey.to_string())
         .await
     {
-        Ok(e) => {
-            debug!("Found {} entries for pubkey {}", e.len(), pubkey);
-            e
-        }
-        Err(e) => {
-            error!("Failed to fetch entries for payouts: {:?}", e);
-            return vec![];
-        }
-    };
-
-    let competitions = match state.coordinator.get_competitions().await {
-        Ok(c) => {
-            debug!("Found {} competitions", c.len());
-            c
-        }
+        Ok(payouts) => payouts,
         Err(e) => {
-            error!("Failed to fetch competitions for payouts: {:?}", e);
+            error!("Failed to fetch eligible payouts: {:?}", e);
             return vec![];
         }
     };
 
-    let mut payouts = Vec::new();
-
-    for entry in &entries {
-        debug!(
-            "Checking entry {} (event_id: {}, paid_out_at: {:?})",
-            entry.id, entry.event_id, entry.paid_out_at
-        );
-
-        if entry.paid_out_at.is_some() {
-            debug!("Skipping entry {} - already paid out", entry.id);
-            continue;
-        }
-
-        if let Some(competition) = competitions.iter().find(|c| c.id == entry.event_id) {
-            debug!(
-                "Found competition {} for entry {} - attestation: {}, outcome_broadcasted_at: {:?}",
-                competition.id,
-                entry.id,
-                competition.attestation.is_some(),
-                competition.outcome_broadcasted_at
-            );
-
-            if competition.attestation.is_some() && competition.outcome_broadcasted_at.is_some() {
-                if let Some(payout_amount) =
-                    calculate_entry_payout(competition, &entry.ephemeral_pubkey)
-                {
-                    debug!(
-                        "Entry {} is eligible for payout of {} sats",
-                        entry.id, payout_amount
-                    );
-                    payouts.push(PayoutView {
-                        competition_id: competition.id.to_string(),
-                        entry_id: entry.id.to_string(),
-                        status: "Eligible".to_string(),
-                        payout_amount,
-                    });
-                } else {
-                    debug!("Entry {} is not eligible for payout", entry.id);
-                }
-            }
-        } else {
-            debug!(
-                "No matching competition found for entry {} (event_id: {})",
-                entry.id, entry.event_id
-            );
-        }
-    }
+    // Entries with a payout in flight or paid have nothing left to claim
+    let payouts: Vec<PayoutView> = payouts
+        .into_iter()
+        .filter(|payout| !payout.invoice_submitted)
+        .map(|payout| PayoutView {
+            competition_id: payout.competition_id.to_string(),
+            entry_id: payout.entry_id.to_string(),
+            status: "Eligible".to_string(),
+            payout_amount: payout.payout_amount_sats.to_sat(),
+        })
+        .collect();
 
     debug!(
         "Returning {} eligible payouts for pubkey {}",
@@ -865,30 +807,6 @@ async fn fetch_eligible_payouts(state: &AppState, pubkey: &str) -> Vec<PayoutVie
     payouts
 }
 
-/// Calculate the payout amount in sats for an entry based on the competition outcome.
-/// Returns None if the entry is not a winner or the calculation cannot be performed.
-fn calculate_entry_payout(
-    competition: &crate::domain::Competition,
-    ephemeral_pubkey_hex: &str,
-) -> Option<u64> {
-    let contract_params = competition.contract_parameters.as_ref()?;
-    let outcome = competition.get_current_outcome().ok()?;
-    let outcome_weights = contract_params.outcome_payouts.get(&outcome)?;
-    let ephemeral_pubkey = Point::from_hex(ephemeral_pubkey_hex).ok()?;
-
-    let player_weight = outcome_weights.iter().find_map(|(player_index, weight)| {
-        let player = contract_params.players.get(*player_index)?;
-        if player.pubkey == ephemeral_pubkey {
-            Some(*weight)
-        } else {
-            None
-        }
-    })?;
-
-    let total_pool_sats = contract_params.funding_value.to_sat();
-    Some((total_pool_sats * player_weight) / 100)
-}
-
 /// Fetch station locations from the Oracle and build map markers for a competition's stations
 async fn fetch_station_markers(state: &AppState, competition_id: Uuid) -> Vec<StationMarker> {
     let locations = match state.coordinator.get_competition(competition_id).await {
@@ -1143,176 +1061,99 @@ fn get_station_name(station_id: &str) -> String {
     }
 }
 
-/// Oracle event response containing entries with scores
-#[derive(Debug, Clone, serde::Deserialize)]
-struct OracleEvent {
-    entries: Vec<OracleEntry>,
-}
-
-/// Oracle entry with score
-#[derive(Debug, Clone, serde::Deserialize)]
-struct OracleEntry {
-    id: Uuid,
-    score: Option<i64>,
-}
-
 async fn fetch_leaderboard_scores(state: &AppState, competition_id: Uuid) -> Vec<EntryScore> {
-    // Fetch event from oracle to get entries with scores (used for sort order via final_score)
-    let oracle_entries = fetch_oracle_event_entries(&state.oracle_url, competition_id).await;
-
-    if oracle_entries.is_empty() {
-        return vec![];
+    match fetch_leaderboard(state, competition_id).await {
+        Ok(leaderboard) => leaderboard
+            .entries
+            .iter()
+            .map(EntryScore::from_leaderboard_entry)
+            .collect(),
+        Err(e) => {
+            warn!("Failed to build leaderboard for {}: {}", competition_id, e);
+            vec![]
+        }
     }
+}
 
-    // Fetch competition weather data once for raw score calculation
-    let competition = state.coordinator.get_competition(competition_id).await.ok();
-    let weather = if let Some(ref comp) = competition {
-        let station_ids: Vec<&str> = comp
-            .event_submission
-            .locations
-            .iter()
-            .map(|s| s.as_str())
-            .collect();
-        if !station_ids.is_empty() {
-            let (forecasts, observations) = tokio::join!(
-                fetch_entry_forecasts(
-                    &state.oracle_url,
-                    &station_ids,
-                    comp.event_submission.start_observation_date,
-                    comp.event_submission.end_observation_date
-                ),
-                fetch_entry_observations(
-                    &state.oracle_url,
-                    &station_ids,
-                    comp.event_submission.start_observation_date,
-                    comp.event_submission.end_observation_date
-                )
+/// Build the ranked leaderboard for a competition. Raw scores come from `score_entries` on each
+/// entry's picks against the oracle's forecasts/observations, ranking uses the oracle's entry
+/// scores.
+pub(crate) async fn fetch_leaderboard(
+    state: &AppState,
+    competition_id: Uuid,
+) -> Result<Leaderboard, crate::domain::Error> {
+    let competition = state.coordinator.get_competition(competition_id).await?;
+    let status = LeaderboardStatus::for_competition(
+        competition.is_attested(),
+        competition.event_submission.start_observation_date,
+        time::OffsetDateTime::now_utc(),
+    );
+
+    // Oracle entry scores (used for sort order via final_score) and the weather data raw scores
+    // are calculated from, served from the cache once the competition is attested
+    let data = match state.coordinator.get_observation_data(&competition).await {
+        Ok(data) => data,
+        Err(e) => {
+            warn!(
+                "Failed to get observation data for {}: {}",
+                competition_id, e
             );
-            let forecast_map: std::collections::HashMap<String, Forecast> = forecasts
-                .ok()
-                .unwrap_or_default()
-                .into_iter()
-                .map(|f| (f.station_id.clone(), f))
-                .collect();
-            let observation_map: std::collections::HashMap<String, Observation> = observations
-                .ok()
-                .unwrap_or_default()
-                .into_iter()
-                .map(|o| (o.station_id.clone(), o))
-                .collect();
-            Some((forecast_map, observation_map))
-        } else {
-            None
+            Default::default()
         }
-    } else {
-        None
     };
+    let oracle_entries = data.entry_scores.clone();
 
-    // Build index of oracle scores for sort order (final_score with tiebreaker)
-    let oracle_score_map: std::collections::HashMap<Uuid, i64> = oracle_entries
+    if oracle_entries.is_empty() {
+        return Ok(Leaderboard::build(
+            competition_id,
+            status,
+            vec![],
+            &HashMap::new(),
+            &HashMap::new(),
+        ));
+    }
+
+    let oracle_scores: HashMap<Uuid, i64> = oracle_entries
         .iter()
-        .map(|e| (e.id, e.score.unwrap_or(0)))
+        .filter_map(|e| e.score.map(|score| (e.id, score)))
         .collect();
 
-    // Convert to EntryScore, computing raw scores from picks + weather data
-    let mut scores: Vec<EntryScore> = Vec::with_capacity(oracle_entries.len());
+    // Fetch entry details for picks and usernames
+    let mut picks = Vec::with_capacity(oracle_entries.len());
+    let mut usernames = HashMap::new();
 
     for oracle_entry in &oracle_entries {
-        let mut entry_score = EntryScore {
-            rank: 0,
-            entry_id: oracle_entry.id.to_string(),
-            username: String::new(),
-            score: 0,
-        };
-
-        // Fetch entry details for username and raw score calculation
-        if let Ok(Some(entry)) = state.coordinator.get_entry_by_id(oracle_entry.id).await {
-            // Look up username
-            if let Ok(pubkey) = nostr_sdk::PublicKey::from_hex(&entry.pubkey) {
-                if let Ok(bech32) = pubkey.to_bech32() {
-                    if let Ok(Some(name)) = state.users_info.get_username_by_pubkey(&bech32).await {
-                        entry_score.username = name;
-                    } else {
-                        entry_score.username = entry.pubkey[..8].to_string();
-                    }
-                }
+        let entry = match state.coordinator.get_entry_by_id(oracle_entry.id).await {
+            Ok(Some(entry)) => entry,
+            _ => {
+                picks.push((oracle_entry.id, vec![]));
+                continue;
             }
+        };
 
-            // Compute raw score from picks + weather data
-            if let Some((ref forecast_map, ref observation_map)) = weather {
-                let mut raw_score = 0i32;
-                for obs in &entry.entry_submission.expected_observations {
-                    let forecast = forecast_map.get(&obs.stations);
-                    let observation = observation_map.get(&obs.stations);
-
-                    if let Some(pick) = &obs.temp_high {
-                        raw_score += calculate_option_score(
-                            forecast.and_then(|f| f.temp_high),
-                            observation.and_then(|o| o.temp_high),
-                            pick,
-                        );
-                    }
-                    if let Some(pick) = &obs.temp_low {
-                        raw_score += calculate_option_score(
-                            forecast.and_then(|f| f.temp_low),
-                            observation.and_then(|o| o.temp_low),
-                            pick,
-                        );
-                    }
-                    if let Some(pick) = &obs.wind_speed {
-                        raw_score += calculate_option_score(
-                            forecast.and_then(|f| f.wind_speed),
-                            observation.and_then(|o| o.wind_speed),
-                            pick,
-                        );
-                    }
-                }
-                entry_score.score = raw_score;
+        if let Ok(pubkey) = nostr_sdk::PublicKey::from_hex(&entry.pubkey) {
+            if let Ok(bech32) = pubkey.to_bech32() {
+                let username = match state.users_info.get_username_by_pubkey(&bech32).await {
+                    Ok(Some(name)) => name,
+                    _ => entry.pubkey[..8].to_string(),
+                };
+                usernames.insert(oracle_entry.id, username);
             }
         }
 
-        scores.push(entry_score);
-    }
-
-    // Sort by oracle final_score (handles tiebreaking), but display raw_score
-    scores.sort_by(|a, b| {
-        let a_oracle = oracle_score_map
-            .get(&Uuid::parse_str(&a.entry_id).unwrap_or_default())
-            .unwrap_or(&0);
-        let b_oracle = oracle_score_map
-            .get(&Uuid::parse_str(&b.entry_id).unwrap_or_default())
-            .unwrap_or(&0);
-        b_oracle
-            .cmp(a_oracle)
-            .then_with(|| a.entry_id.cmp(&b.entry_id))
-    });
-
-    // Assign ranks after sorting
-    for (idx, score) in scores.iter_mut().enumerate() {
-        score.rank = idx + 1;
+        picks.push((
+            oracle_entry.id,
+            entry.entry_submission.expected_observations,
+        ));
     }
 
-    scores
-}
+    let ranked_entries = score_entries(&picks, &data, &competition.event_submission);
 
-async fn fetch_oracle_event_entries(oracle_url: &str, event_id: Uuid) -> Vec<OracleEntry> {
-    let client = reqwest_middleware::reqwest::Client::new();
-
-    let response = match client
-        .get(format!("{}/oracle/events/{}", oracle_url, event_id))
-        .send()
-        .await
-    {
-        Ok(resp) => resp,
-        Err(_) => return vec![],
-    };
-
-    if response.status().is_success() {
-        match response.json::<OracleEvent>().await {
-            Ok(event) => event.entries,
-            Err(_) => vec![],
-        }
-    } else {
-        vec![]
-    }
+    Ok(Leaderboard::build(
+        competition_id,
+        status,
+        ranked_entries,
+        &oracle_scores,
+        &usernames,
+    ))
 }
@@ -0,0 +1,37 @@
+use axum::{extract::State, response::ErrorResponse, Json};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{api::extractors::NostrAuth, domain::Error, startup::AppState};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigReloadResponse {
+    /// Runtime settings that now have a new value
+    pub changed: Vec<String>,
+}
+
+/// Re-read the settings file and swap in the runtime adjustable settings (same as SIGHUP).
+/// Must be NIP-98 signed by the coordinator's own key.
+pub async fn reload_config(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ConfigReloadResponse>, ErrorResponse> {
+    if pubkey.to_hex() != state.coordinator.public_key() {
+        warn!("Rejected settings reload from {}", pubkey.to_hex());
+        return Err(Error::InvalidSignature(String::from(
+            "settings reload must be signed by the coordinator key",
+        ))
+        .into());
+    }
+
+    let changed = state.settings.reload().map_err(|e| {
+        error!("Failed to reload settings, keeping current settings: {}", e);
+        Error::BadRequest(e.to_string())
+    })?;
+    info!("Settings reloaded via admin api, changed: {:?}", changed);
+
+    Ok(Json(ConfigReloadResponse {
+        changed: changed.into_iter().map(String::from).collect(),
+    }))
+}
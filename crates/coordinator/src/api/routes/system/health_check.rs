@@ -1,9 +1,17 @@
-use axum::{extract::State, response::ErrorResponse};
+use axum::{
+    extract::State,
+    http::header,
+    response::{ErrorResponse, IntoResponse},
+    Json,
+};
 use hyper::StatusCode;
-use log::{debug, error};
+use log::{debug, error, warn};
+use serde::Serialize;
 use std::sync::Arc;
 
-use crate::{domain::Error, startup::AppState};
+use crate::{
+    domain::Error, infra::lightning::LnBackendHealth, startup::AppState, supervisor::TaskHealth,
+};
 
 pub async fn health(State(state): State<Arc<AppState>>) -> Result<StatusCode, ErrorResponse> {
     // Ping the database
@@ -18,18 +26,118 @@ pub async fn health(State(state): State<Arc<AppState>>) -> Result<StatusCode, Er
         e
     })?;
 
-    // Verify the background threads are still running
-    for (thread_name, thread) in state.background_threads.clone().iter() {
-        if thread.is_finished() {
-            let err = Error::Thread(format!(
-                "thread {} has died, we need to restart the service",
-                thread_name
-            ));
-            error!("{}", err);
-            return Err(err.into());
-        }
+    // Verify no background task has failed past its restart policy
+    for task in state.tasks.health().iter().filter(|task| !task.healthy()) {
+        let err = Error::Thread(format!(
+            "background task {} is {:?} after {} restarts, we need to restart the service",
+            task.name, task.state, task.restarts
+        ));
+        error!("{}", err);
+        return Err(err.into());
     }
 
     debug!("service, background threads, and db are up");
     Ok(StatusCode::OK)
 }
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub database: bool,
+    pub lightning: Vec<LnBackendStatus>,
+    pub tasks: Vec<TaskHealth>,
+}
+
+/// Whether a lightning backend answers. The error stays in the logs, it can carry node
+/// addresses this public endpoint shouldn't show.
+#[derive(Debug, Serialize)]
+pub struct LnBackendStatus {
+    pub id: String,
+    pub healthy: bool,
+}
+
+impl From<LnBackendHealth> for LnBackendStatus {
+    fn from(health: LnBackendHealth) -> Self {
+        LnBackendStatus {
+            id: health.id,
+            healthy: health.healthy,
+        }
+    }
+}
+
+/// Whether the coordinator can take entries: the database answers, at least one
+/// lightning backend is reachable and no background task is degraded. Reports each backend
+/// and task so a failover or a restarted watcher is visible.
+pub async fn ready(State(state): State<Arc<AppState>>) -> (StatusCode, Json<ReadinessReport>) {
+    let database = match state.coordinator.ping().await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Readiness check database ping failed: {}", e);
+            false
+        }
+    };
+    let lightning = state.coordinator.ln_health().await;
+    for backend in lightning.iter().filter(|backend| !backend.healthy) {
+        warn!(
+            "Lightning backend {} is unhealthy: {}",
+            backend.id,
+            backend.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+
+    let tasks = state.tasks.health();
+    for task in tasks.iter().filter(|task| !task.healthy()) {
+        warn!(
+            "Background task {} is {:?}: {}",
+            task.name,
+            task.state,
+            task.last_error.as_deref().unwrap_or("no error recorded")
+        );
+    }
+
+    let ready = database
+        && lightning.iter().any(|backend| backend.healthy)
+        && tasks.iter().all(TaskHealth::healthy);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(ReadinessReport {
+            ready,
+            database,
+            lightning: lightning.into_iter().map(LnBackendStatus::from).collect(),
+            tasks,
+        }),
+    )
+}
+
+/// Background task health as Prometheus gauges, 1 while a task is running or waiting out a
+/// restart and 0 once it is degraded or stopped, alongside how often each was restarted
+pub async fn task_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let tasks = state.tasks.health();
+    let mut body = String::from(
+        "# HELP coordinator_background_task_up Whether a supervised background task is running\n\
+         # TYPE coordinator_background_task_up gauge\n",
+    );
+    for task in &tasks {
+        body.push_str(&format!(
+            "coordinator_background_task_up{{task=\"{}\"}} {}\n",
+            task.name,
+            u8::from(task.healthy())
+        ));
+    }
+    body.push_str(
+        "# HELP coordinator_background_task_restarts Times a background task has been restarted\n\
+         # TYPE coordinator_background_task_restarts gauge\n",
+    );
+    for task in &tasks {
+        body.push_str(&format!(
+            "coordinator_background_task_restarts{{task=\"{}\"}} {}\n",
+            task.name, task.restarts
+        ));
+    }
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
@@ -0,0 +1,34 @@
+use axum::{extract::State, Json};
+use coordinator_core::BitcoinNetwork;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::startup::AppState;
+
+/// Public metadata clients use to check they are talking to the right coordinator and network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoordinatorMetadata {
+    pub name: String,
+    /// Hex encoded x-only pubkey the coordinator signs contracts with
+    pub pubkey: String,
+    pub network: BitcoinNetwork,
+    pub escrow_enabled: bool,
+    /// Every key the coordinator has signed receipts with, oldest first. `pubkey` is the newest,
+    /// the older ones keep receipts from before a key rotation verifiable.
+    #[serde(default)]
+    pub receipt_pubkeys: Vec<String>,
+    /// Nostr relays the browser client should connect to
+    #[serde(default)]
+    pub relays: Vec<String>,
+}
+
+pub async fn get_coordinator_info(State(state): State<Arc<AppState>>) -> Json<CoordinatorMetadata> {
+    Json(CoordinatorMetadata {
+        name: state.coordinator.name().to_string(),
+        pubkey: state.coordinator.public_key(),
+        network: state.network,
+        escrow_enabled: state.coordinator.is_escrow_enabled(),
+        receipt_pubkeys: state.coordinator.public_keys(),
+        relays: state.settings.client_relays(),
+    })
+}
@@ -1,7 +1,11 @@
 mod bitcoin_wallet;
+mod config_reload;
 mod health_check;
+mod info;
 mod user_info;
 
 pub use bitcoin_wallet::*;
+pub use config_reload::*;
 pub use health_check::*;
+pub use info::*;
 pub use user_info::*;
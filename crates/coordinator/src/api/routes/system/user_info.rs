@@ -1,23 +1,38 @@
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
     response::{ErrorResponse, IntoResponse},
     Json,
 };
+use coordinator_core::BitcoinNetwork;
 use log::{debug, error};
 use nostr_sdk::{Event, ToBech32};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
+use uuid::Uuid;
 
 use crate::{
     api::extractors::{AuthError, NostrAuth},
     domain::{
         self,
-        users::{hash_password, verify_password},
+        users::{hash_password, verify_password, UserSessions},
     },
     startup::AppState,
 };
 
+/// Wallets are created client side, make sure they are for the network the coordinator runs on
+fn validate_wallet_network(state: &AppState, network: &str) -> Result<(), domain::Error> {
+    let wallet_network =
+        BitcoinNetwork::from_str(network).map_err(|e| domain::Error::BadRequest(e.to_string()))?;
+    if wallet_network != state.network {
+        return Err(domain::Error::BadRequest(format!(
+            "Wallet network {} doesn't match coordinator network {}",
+            wallet_network, state.network
+        )));
+    }
+    Ok(())
+}
+
 fn validate_password_strength(password: &str) -> Result<(), String> {
     if password.len() < 10 {
         return Err("Password must be at least 10 characters".to_string());
@@ -37,6 +52,41 @@ fn validate_password_strength(password: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// The caller's recent sign-ins, and the ones whose auth header would still be accepted
+pub async fn get_sessions(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<UserSessions>, ErrorResponse> {
+    let pubkey = pubkey.to_bech32().expect("public bech32 format");
+    state
+        .users_info
+        .get_sessions(&pubkey)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to list sessions for {}: {}", pubkey, e);
+            ErrorResponse::from(e)
+        })
+}
+
+/// Stops one of the caller's sign-ins from being accepted again
+pub async fn revoke_session(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, ErrorResponse> {
+    let pubkey = pubkey.to_bech32().expect("public bech32 format");
+    state
+        .users_info
+        .revoke_session(&pubkey, session_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| {
+            error!("Failed to revoke session {}: {}", session_id, e);
+            ErrorResponse::from(e)
+        })
+}
+
 pub async fn login(
     NostrAuth { pubkey, .. }: NostrAuth,
     State(state): State<Arc<AppState>>,
@@ -71,6 +121,7 @@ pub async fn register(
     let pubkey = pubkey.to_bech32().expect("public bech32 format");
 
     debug!("registering user: {}", pubkey);
+    validate_wallet_network(&state, &body.network)?;
     match state.users_info.register(pubkey, body).await {
         Ok(user_info) => Ok((StatusCode::CREATED, Json(user_info))),
         Err(e) => {
@@ -135,6 +186,8 @@ pub async fn register_username(
         return Err(ErrorResponse::from(domain::Error::BadRequest(e)));
     }
 
+    validate_wallet_network(&state, &body.network)?;
+
     if state.users_info.username_exists(&body.username).await? {
         return Ok((
             StatusCode::CREATED,
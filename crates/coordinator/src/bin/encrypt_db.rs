@@ -0,0 +1,27 @@
+//! Encrypts the existing plaintext competitions and users databases in place with the key from
+//! `db_settings.encryption_key`. Stop the coordinator before running this.
+use anyhow::{anyhow, Result};
+use coordinator::{encrypt_database, get_settings, load_db_encryption_key, setup_logger};
+use log::info;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let settings = get_settings()?;
+    setup_logger(settings.level.clone(), vec![])?;
+
+    let Some(key) = load_db_encryption_key(&settings.db_settings)? else {
+        return Err(anyhow!("db_settings.encryption_key is not set"));
+    };
+
+    for db_name in ["competitions", "users"] {
+        let database_path = format!("{}/{}.db", settings.db_settings.data_folder, db_name);
+        info!("Encrypting {}", database_path);
+        let row_counts = encrypt_database(&database_path, &key).await?;
+        for (table, count) in row_counts {
+            info!("  {}: {} rows", table, count);
+        }
+        info!("{} is now encrypted", database_path);
+    }
+
+    Ok(())
+}
@@ -3,8 +3,8 @@ use bdk_wallet::bitcoin::{OutPoint, Txid};
 use clap::{Parser, Subcommand};
 use coordinator::SendOptions;
 use coordinator::{
-    get_settings_with_cli, setup_logger, Bitcoin, BitcoinClient, BitcoinSettings, CliSettings,
-    ConfigurableSettings,
+    get_settings_with_cli, secret_backend, setup_logger, Bitcoin, BitcoinClient, BitcoinSettings,
+    CliSettings, ConfigurableSettings, SecretsSettings,
 };
 use log::{debug, error, info};
 use serde::{Deserialize, Serialize};
@@ -89,6 +89,8 @@ impl From<Cli> for CliSettings {
 pub struct WalletSettings {
     pub level: Option<String>,
     pub bitcoin: BitcoinSettings,
+    #[serde(default)]
+    pub secrets_settings: SecretsSettings,
 }
 
 impl ConfigurableSettings for WalletSettings {
@@ -117,7 +119,11 @@ async fn main() -> Result<()> {
 
     debug!("Settings: {:?}", settings);
 
-    let client = BitcoinClient::new(&settings.bitcoin).await?;
+    let client = BitcoinClient::new(
+        &settings.bitcoin,
+        secret_backend(&settings.secrets_settings, &settings.bitcoin.seed_path),
+    )
+    .await?;
 
     match cli.command {
         Commands::Address => {
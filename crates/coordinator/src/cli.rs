@@ -0,0 +1,592 @@
+//! Operational subcommands of the coordinator binary. Everything except `serve` runs directly
+//! against the sqlite databases, so it works while the HTTP server is down.
+use anyhow::anyhow;
+use clap::Subcommand;
+use dlctix::{musig2::secp256k1::SecretKey, secp::Scalar};
+use serde::Serialize;
+use std::io::Write;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        cancel_competition, list_competitions, list_failed_payouts, list_pending_payouts,
+        reencrypt_keymeld_sessions, release_ticket, retry_competition, show_competition,
+        CompetitionStore, CompetitionSummary, CoordinatorKeyring, ExtendCompetition,
+    },
+    infra::{
+        db::{DBConnection, DatabasePoolConfig, DatabaseType},
+        db_encryption::load_db_encryption_key,
+        db_migrations::PendingMigration,
+        file_utils::create_folder,
+        secrets::{load_key, secret_backend},
+    },
+    Settings,
+};
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Run the coordinator server (default)
+    Serve,
+    /// Inspect and manage competitions
+    #[command(subcommand)]
+    Competition(CompetitionCommand),
+    /// Inspect payouts
+    #[command(subcommand)]
+    Payout(PayoutCommand),
+    /// Manage tickets
+    #[command(subcommand)]
+    Ticket(TicketCommand),
+    /// Database maintenance
+    #[command(subcommand)]
+    Db(DbCommand),
+    /// Coordinator key management
+    #[command(subcommand)]
+    Key(KeyCommand),
+    /// Replay a scripted competition against mock services and print its state trace
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    Simulate { scenario: std::path::PathBuf },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum CompetitionCommand {
+    /// List all competitions and their state
+    List,
+    /// Show a single competition
+    Show { id: Uuid },
+    /// Cancel a competition whose funding transaction hasn't been broadcast
+    Cancel { id: Uuid },
+    /// Clear a failed competition so it's processed again
+    Retry { id: Uuid },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum PayoutCommand {
+    /// List pending payouts
+    List {
+        /// List entries with failed payout attempts instead
+        #[arg(long)]
+        failed: bool,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum TicketCommand {
+    /// Release a stuck, unpaid ticket reservation
+    Release { id: Uuid },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum DbCommand {
+    /// Apply pending migrations to both databases
+    Migrate {
+        /// Print the statements of pending migrations without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Run sqlite's integrity check on both databases
+    Verify,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum KeyCommand {
+    /// Re-encrypt stored keymeld session secrets to the newest key in
+    /// `coordinator_settings.signing_keys`. Contracts stay on the key they were created under.
+    Rotate,
+}
+
+/// The coordinator keys the server loads, with the wallet seed key read through the secrets
+/// backend instead of the bitcoin client
+fn load_keyring(settings: &Settings) -> Result<CoordinatorKeyring, anyhow::Error> {
+    let wallet_secrets = secret_backend(
+        &settings.secrets_settings,
+        &settings.bitcoin_settings.seed_path,
+    );
+    let wallet_key = load_key::<SecretKey>(wallet_secrets.as_ref())?;
+    let default_key = Scalar::from_slice(&wallet_key.secret_bytes())
+        .map_err(|e| anyhow!("wallet seed key: {}", e))?;
+    CoordinatorKeyring::load(default_key, &settings.coordinator_settings.signing_keys)
+}
+
+fn pool_config(settings: &Settings) -> Result<DatabasePoolConfig, anyhow::Error> {
+    create_folder(&settings.db_settings.data_folder.clone());
+    let mut pool_config: DatabasePoolConfig = settings.db_settings.clone().into();
+    pool_config.encryption_key = load_db_encryption_key(&settings.db_settings)
+        .map_err(|e| anyhow!("Error loading database encryption key: {}", e))?;
+    Ok(pool_config)
+}
+
+async fn open_db(
+    settings: &Settings,
+    name: &str,
+    database_type: DatabaseType,
+) -> Result<DBConnection, anyhow::Error> {
+    DBConnection::new(
+        &settings.db_settings.data_folder,
+        name,
+        pool_config(settings)?,
+        database_type,
+    )
+    .await
+    .map_err(|e| anyhow!("Error opening {} db: {}", name, e))
+}
+
+/// Pending migrations of both databases, without applying them
+async fn pending_migrations(
+    settings: &Settings,
+) -> Result<Vec<(&'static str, Vec<PendingMigration>)>, anyhow::Error> {
+    let mut pending = Vec::new();
+    for (name, database_type) in [
+        ("competitions", DatabaseType::Competitions),
+        ("users", DatabaseType::Users),
+    ] {
+        let db = DBConnection::open(
+            &settings.db_settings.data_folder,
+            name,
+            pool_config(settings)?,
+        )
+        .await
+        .map_err(|e| anyhow!("Error opening {} db: {}", name, e))?;
+        let result = db.pending_migrations(&database_type).await;
+        db.close().await;
+        pending.push((
+            name,
+            result.map_err(|e| anyhow!("{} db can't be migrated: {}", name, e))?,
+        ));
+    }
+    Ok(pending)
+}
+
+fn print<T: Serialize>(
+    out: &mut impl Write,
+    json: bool,
+    value: &T,
+    text: impl FnOnce() -> String,
+) -> Result<(), anyhow::Error> {
+    if json {
+        writeln!(out, "{}", serde_json::to_string_pretty(value)?)?;
+    } else {
+        writeln!(out, "{}", text())?;
+    }
+    Ok(())
+}
+
+fn summary_line(competition: &CompetitionSummary) -> String {
+    let settlement = &competition.settlement_progress;
+    format!(
+        "{}  {:<24} {}/{} entries ({} paid{}{})  created {}{}",
+        competition.id,
+        competition.state,
+        competition.total_entries,
+        competition.total_allowed_entries,
+        competition.total_paid_entries,
+        if competition.total_comped_entries > 0 {
+            format!(", {} comped", competition.total_comped_entries)
+        } else {
+            String::new()
+        },
+        if settlement.settled_invoices > 0 || settlement.failed_invoices > 0 {
+            format!(
+                ", {}/{} settled{}",
+                settlement.settled_invoices,
+                settlement.total_invoices,
+                if settlement.failed_invoices > 0 {
+                    format!(" {} failing", settlement.failed_invoices)
+                } else {
+                    String::new()
+                }
+            )
+        } else {
+            String::new()
+        },
+        competition.created_at,
+        if competition.errors > 0 {
+            format!("  {} errors", competition.errors)
+        } else {
+            String::new()
+        }
+    )
+}
+
+/// Run a subcommand other than `serve`, writing its result to `out`
+pub async fn run_command(
+    settings: &Settings,
+    command: Command,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), anyhow::Error> {
+    match command {
+        Command::Serve => Err(anyhow!("serve is not an operational command")),
+        Command::Competition(command) => {
+            let db = open_db(settings, "competitions", DatabaseType::Competitions).await?;
+            let store = CompetitionStore::new(db.clone());
+            let result = run_competition_command(&store, command, json, out).await;
+            db.close().await;
+            result
+        }
+        Command::Payout(PayoutCommand::List { failed }) => {
+            let db = open_db(settings, "competitions", DatabaseType::Competitions).await?;
+            let store = CompetitionStore::new(db.clone());
+            let result = if failed {
+                let failures = list_failed_payouts(&store).await?;
+                print(out, json, &failures, || {
+                    failures
+                        .iter()
+                        .map(|failure| {
+                            format!(
+                                "{}  entry {}  {} failed attempts",
+                                failure.competition_id, failure.entry_id, failure.failures
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+            } else {
+                let payouts = list_pending_payouts(&store).await?;
+                print(out, json, &payouts, || {
+                    payouts
+                        .iter()
+                        .map(|payout| {
+                            format!(
+                                "{}  entry {}  {} sats  initiated {}",
+                                payout.id,
+                                payout.entry_id,
+                                payout.payout_amount_sats,
+                                payout.initiated_at
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+            };
+            db.close().await;
+            result
+        }
+        Command::Ticket(TicketCommand::Release { id }) => {
+            let db = open_db(settings, "competitions", DatabaseType::Competitions).await?;
+            let store = CompetitionStore::new(db.clone());
+            let result = match release_ticket(&store, id).await {
+                Ok(ticket) => print(out, json, &ticket, || {
+                    format!(
+                        "Released ticket {} of competition {}",
+                        ticket.id, ticket.competition_id
+                    )
+                }),
+                Err(e) => Err(e.into()),
+            };
+            db.close().await;
+            result
+        }
+        Command::Key(KeyCommand::Rotate) => {
+            let keys = load_keyring(settings)?;
+            let db = open_db(settings, "competitions", DatabaseType::Competitions).await?;
+            let store = CompetitionStore::new(db.clone());
+            let result = match reencrypt_keymeld_sessions(&store, &keys).await {
+                Ok(report) => print(out, json, &report, || {
+                    format!(
+                        "Re-encrypted {} keymeld session secrets to key {}, {} already on it",
+                        report.reencrypted_sessions.len(),
+                        report.key_id,
+                        report.unchanged_sessions
+                    )
+                }),
+                Err(e) => Err(e.into()),
+            };
+            db.close().await;
+            result
+        }
+        Command::Db(DbCommand::Migrate { dry_run: true }) => {
+            let pending = pending_migrations(settings).await?;
+            let statuses: Vec<_> = pending
+                .iter()
+                .map(|(name, migrations)| {
+                    serde_json::json!({ "database": name, "pending": migrations })
+                })
+                .collect();
+            print(out, json, &statuses, || {
+                let mut lines = Vec::new();
+                for (name, migrations) in &pending {
+                    lines.push(format!("{}: {} pending migrations", name, migrations.len()));
+                    for migration in migrations {
+                        lines.push(format!(
+                            "-- {} {}\n{}",
+                            migration.version,
+                            migration.description,
+                            migration.sql.trim_end()
+                        ));
+                    }
+                }
+                lines.join("\n")
+            })
+        }
+        Command::Db(command) => {
+            let mut statuses = Vec::new();
+            for (name, database_type) in [
+                ("competitions", DatabaseType::Competitions),
+                ("users", DatabaseType::Users),
+            ] {
+                // Opening a connection applies any pending migrations
+                let db = open_db(settings, name, database_type).await?;
+                if let DbCommand::Verify = command {
+                    db.quick_check()
+                        .await
+                        .map_err(|e| anyhow!("{} db failed its integrity check: {}", name, e))?;
+                }
+                db.close().await;
+                statuses.push(serde_json::json!({ "database": name, "status": "ok" }));
+            }
+            print(out, json, &statuses, || {
+                let action = match command {
+                    DbCommand::Migrate { .. } => "migrated",
+                    DbCommand::Verify => "verified",
+                };
+                format!("competitions and users databases {}", action)
+            })
+        }
+        #[cfg(any(feature = "e2e-testing", debug_assertions))]
+        Command::Simulate { scenario } => {
+            let scenario = crate::simulation::Scenario::load(&scenario)?;
+            let report = crate::simulation::run_scenario(&scenario).await?;
+            print(out, json, &report, || {
+                let mut lines = report.trace_lines();
+                lines.push(format!(
+                    "{} finished in {} after {} ticks",
+                    report.scenario, report.final_state, report.ticks_run
+                ));
+                lines.join("\n")
+            })?;
+            report.check(&scenario.expect)
+        }
+    }
+}
+
+async fn run_competition_command(
+    store: &CompetitionStore,
+    command: CompetitionCommand,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), anyhow::Error> {
+    match command {
+        CompetitionCommand::List => {
+            let competitions = list_competitions(store).await?;
+            print(out, json, &competitions, || {
+                competitions
+                    .iter()
+                    .map(summary_line)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+        }
+        CompetitionCommand::Show { id } => {
+            let competition = show_competition(store, id).await?;
+            let summary = CompetitionSummary::from(&competition);
+            let errors = competition
+                .errors
+                .iter()
+                .map(|error| format!("  {}", error))
+                .collect::<Vec<_>>();
+            print(out, json, &ExtendCompetition::from(competition), || {
+                let mut lines = vec![summary_line(&summary)];
+                if !errors.is_empty() {
+                    lines.push("errors:".to_string());
+                    lines.extend(errors);
+                }
+                lines.join("\n")
+            })
+        }
+        CompetitionCommand::Cancel { id } => {
+            let (competition, previous_state) = cancel_competition(store, id).await?;
+            let summary = CompetitionSummary::from(&competition);
+            print(out, json, &summary, || {
+                format!("Cancelled competition {} (was {})", id, previous_state)
+            })
+        }
+        CompetitionCommand::Retry { id } => {
+            let competition = retry_competition(store, id).await?;
+            let summary = CompetitionSummary::from(&competition);
+            print(out, json, &summary, || {
+                format!("Competition {} will resume in state {}", id, summary.state)
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{decrypt_session_secret, Competition, CreateEvent},
+        infra::keymeld::StoredDlcKeygenSession,
+        SigningKeySettings,
+    };
+    use std::fs;
+    use time::{Duration, OffsetDateTime};
+
+    fn test_settings() -> Settings {
+        let dir = std::env::temp_dir().join(format!("coordinator-cli-{}", Uuid::now_v7()));
+        let mut settings = Settings::default();
+        settings.db_settings.data_folder = dir.to_string_lossy().to_string();
+        settings.bitcoin_settings.seed_path = dir.join("seed.pem").to_string_lossy().to_string();
+        if cfg!(feature = "sqlcipher") {
+            settings.db_settings.encryption_key = Some(crate::SecretsSettings::Command {
+                command: "echo".to_string(),
+                args: vec![hex::encode([7u8; 32])],
+            });
+        }
+        settings
+    }
+
+    async fn add_competition(settings: &Settings) -> Competition {
+        let start = OffsetDateTime::now_utc() + Duration::hours(6);
+        let competition = Competition::new(&CreateEvent::fixture(start));
+        let db = open_db(settings, "competitions", DatabaseType::Competitions)
+            .await
+            .unwrap();
+        CompetitionStore::new(db.clone())
+            .add_competition_with_tickets(competition.clone(), vec![])
+            .await
+            .unwrap();
+        db.close().await;
+        competition
+    }
+
+    async fn run(settings: &Settings, command: Command) -> serde_json::Value {
+        let mut out = Vec::new();
+        run_command(settings, command, true, &mut out)
+            .await
+            .unwrap();
+        serde_json::from_slice(&out).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_competition_list_and_show() {
+        let settings = test_settings();
+        let competition = add_competition(&settings).await;
+
+        let listed = run(&settings, Command::Competition(CompetitionCommand::List)).await;
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["id"], competition.id.to_string());
+
+        let shown = run(
+            &settings,
+            Command::Competition(CompetitionCommand::Show { id: competition.id }),
+        )
+        .await;
+        assert_eq!(shown["id"], competition.id.to_string());
+
+        fs::remove_dir_all(&settings.db_settings.data_folder).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_db_migrate_dry_run_lists_pending_migrations() {
+        let settings = test_settings();
+
+        let pending = run(&settings, Command::Db(DbCommand::Migrate { dry_run: true })).await;
+        assert_eq!(pending[0]["database"], "competitions");
+        let statements = pending[0]["pending"].as_array().unwrap();
+        assert!(!statements.is_empty());
+        assert!(statements[0]["sql"]
+            .as_str()
+            .unwrap()
+            .contains("CREATE TABLE"));
+
+        run(
+            &settings,
+            Command::Db(DbCommand::Migrate { dry_run: false }),
+        )
+        .await;
+        let pending = run(&settings, Command::Db(DbCommand::Migrate { dry_run: true })).await;
+        assert!(pending[0]["pending"].as_array().unwrap().is_empty());
+        assert!(pending[1]["pending"].as_array().unwrap().is_empty());
+
+        fs::remove_dir_all(&settings.db_settings.data_folder).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_competition_cancel() {
+        let settings = test_settings();
+        let competition = add_competition(&settings).await;
+
+        let cancelled = run(
+            &settings,
+            Command::Competition(CompetitionCommand::Cancel { id: competition.id }),
+        )
+        .await;
+        assert_eq!(cancelled["state"], "cancelled");
+
+        // A second cancel is refused
+        let mut out = Vec::new();
+        let result = run_command(
+            &settings,
+            Command::Competition(CompetitionCommand::Cancel { id: competition.id }),
+            true,
+            &mut out,
+        )
+        .await;
+        assert!(result.is_err());
+
+        fs::remove_dir_all(&settings.db_settings.data_folder).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_key_rotate_reencrypts_keymeld_sessions() {
+        let mut settings = test_settings();
+        let competition = add_competition(&settings).await;
+
+        // Session stored while the wallet seed key was the only key
+        let old_keys = load_keyring(&settings).unwrap();
+        let (key_id, encrypted_session_secret) =
+            old_keys.encrypt_to_self(&hex::encode([5u8; 32])).unwrap();
+        assert_eq!(key_id, "default");
+        let db = open_db(&settings, "competitions", DatabaseType::Competitions)
+            .await
+            .unwrap();
+        let store = CompetitionStore::new(db.clone());
+        store
+            .store_keymeld_session(
+                competition.id,
+                &StoredDlcKeygenSession {
+                    session_id: Uuid::now_v7().to_string(),
+                    encrypted_session_secret,
+                    encryption_key_id: None,
+                    aggregate_key: vec![2; 33],
+                    outcome_subset_ids: Default::default(),
+                },
+            )
+            .await
+            .unwrap();
+
+        settings
+            .coordinator_settings
+            .signing_keys
+            .push(SigningKeySettings {
+                id: "2026-10".to_string(),
+                key_file: format!("{}/signing-2026-10.pem", settings.db_settings.data_folder),
+                secrets: Default::default(),
+            });
+        let rotated = run(&settings, Command::Key(KeyCommand::Rotate)).await;
+        assert_eq!(rotated["key_id"], "2026-10");
+        assert_eq!(
+            rotated["reencrypted_sessions"][0],
+            competition.id.to_string()
+        );
+
+        let session = store
+            .get_keymeld_session(competition.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.encryption_key_id.as_deref(), Some("2026-10"));
+        let new_keys = load_keyring(&settings).unwrap();
+        assert_eq!(
+            decrypt_session_secret(&new_keys, &session).unwrap(),
+            [5u8; 32]
+        );
+
+        // Running it again has nothing left to move
+        let again = run(&settings, Command::Key(KeyCommand::Rotate)).await;
+        assert_eq!(again["unchanged_sessions"], 1);
+
+        db.close().await;
+        fs::remove_dir_all(&settings.db_settings.data_folder).unwrap();
+    }
+}
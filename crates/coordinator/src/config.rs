@@ -1,17 +1,25 @@
 use anyhow::anyhow;
 use bdk_wallet::bitcoin::Network;
 use clap::Parser;
+use coordinator_core::BitcoinNetwork;
 use fern::colors::{Color, ColoredLevelConfig};
 use log::LevelFilter;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     env,
     fs::{self, File},
     io::{Read, Write},
+    net::IpAddr,
     path::PathBuf,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
 };
 use time::{format_description::well_known::Iso8601, OffsetDateTime};
 
+use crate::cli::Command;
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
@@ -22,6 +30,13 @@ pub struct Cli {
     /// Log level to run with the service (default: info)
     #[arg(short, long)]
     pub level: Option<String>,
+
+    /// Print command output as json
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -35,6 +50,12 @@ pub struct Settings {
     pub bitcoin_settings: BitcoinSettings,
     pub ln_settings: LnSettings,
     pub keymeld_settings: KeymeldSettings,
+    #[serde(default)]
+    pub alert_settings: AlertSettings,
+    #[serde(default)]
+    pub webhook_settings: WebhookSettings,
+    #[serde(default)]
+    pub secrets_settings: SecretsSettings,
 }
 
 impl ConfigurableSettings for Settings {
@@ -42,6 +63,10 @@ impl ConfigurableSettings for Settings {
         if let Some(level) = &cli_settings.level {
             self.level = Some(level.clone());
         }
+        // Remember where the settings came from so they can be re-read on reload
+        if let Some(config) = &cli_settings.config {
+            self.config = Some(config.clone());
+        }
     }
 
     fn default_config_path() -> PathBuf {
@@ -59,6 +84,27 @@ pub struct DBSettings {
     pub idle_timeout_secs: u64,
     pub acquire_timeout_secs: u64,
     pub sqlite_config: SqliteConfigSerde,
+    /// Source of the SQLCipher key used to encrypt the databases at rest. Required when built
+    /// with the `sqlcipher` feature and rejected without it. The `file` backend reads (or
+    /// generates) the PEM key at `encryption_key_file`.
+    #[serde(default)]
+    pub encryption_key: Option<SecretsSettings>,
+    #[serde(default)]
+    pub encryption_key_file: Option<String>,
+    /// Encoding of the large contract and signature blobs written from now on, blobs already
+    /// stored in the other format still read back
+    #[serde(default)]
+    pub blob_format: BlobFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlobFormat {
+    /// Readable with sqlite's JSON functions
+    #[default]
+    Json,
+    /// Smaller and quicker to decode, byte fields are stored raw instead of hex encoded
+    Cbor,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -87,6 +133,9 @@ impl Default for DBSettings {
             idle_timeout_secs: 600,   // 10 minutes
             acquire_timeout_secs: 15, // 15 seconds
             sqlite_config: SqliteConfigSerde::default(),
+            encryption_key: None,
+            encryption_key_file: None,
+            blob_format: BlobFormat::default(),
         }
     }
 }
@@ -156,6 +205,10 @@ pub struct LnSettings {
     pub invoice_watch_interval: u64,
     /// Interval in seconds to check for new payouts
     pub payout_watch_interval: u64,
+    /// Interval in seconds to release tickets whose reservation expired unpaid, cancelling
+    /// their invoices so the slot can be reserved again
+    #[serde(default = "default_reservation_sweep_interval")]
+    pub reservation_sweep_interval: u64,
     /// Enable mock LN client for E2E testing (no real LND required)
     #[serde(default)]
     pub mock_enabled: bool,
@@ -163,6 +216,33 @@ pub struct LnSettings {
     /// If not set, invoices must be manually accepted via test endpoints
     #[serde(default)]
     pub mock_auto_accept_secs: Option<u64>,
+    /// Additional lnd nodes, tried in order when the primary node fails to create an
+    /// invoice or send a payout
+    #[serde(default)]
+    pub failover: Vec<LnBackendSettings>,
+}
+
+impl LnSettings {
+    /// Settings for connecting to one of the failover nodes, sharing the watch intervals
+    pub fn for_backend(&self, backend: &LnBackendSettings) -> LnSettings {
+        LnSettings {
+            base_url: backend.base_url.clone(),
+            macaroon_file_path: backend.macaroon_file_path.clone(),
+            tls_cert_path: backend.tls_cert_path.clone(),
+            failover: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LnBackendSettings {
+    /// Stored on tickets to send settlement back to the node that created the invoice,
+    /// so it must stay stable across restarts
+    pub id: String,
+    pub base_url: String,
+    pub macaroon_file_path: String,
+    pub tls_cert_path: Option<String>,
 }
 
 impl Default for LnSettings {
@@ -173,8 +253,10 @@ impl Default for LnSettings {
             tls_cert_path: Some(String::from("./creds/tls.cert")),
             invoice_watch_interval: 5,
             payout_watch_interval: 5,
+            reservation_sweep_interval: default_reservation_sweep_interval(),
             mock_enabled: false,
             mock_auto_accept_secs: None,
+            failover: Vec::new(),
         }
     }
 }
@@ -214,10 +296,116 @@ impl Default for KeymeldSettings {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    /// Minutes a competition may sit in a state before the admin dashboard flags it as stuck,
+    /// keyed by state name (ie. `awaiting_signatures = 120`)
+    pub stuck_state_thresholds_mins: HashMap<String, u64>,
+    /// Threshold used for any state not listed in `stuck_state_thresholds_mins`
+    pub default_stuck_threshold_mins: u64,
+    /// Number of failed payout attempts for a single entry before it is flagged
+    pub max_payout_failures: u32,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        AlertSettings {
+            stuck_state_thresholds_mins: HashMap::from([
+                (String::from("created"), 60),
+                (String::from("contract_created"), 120),
+                (String::from("awaiting_signatures"), 120),
+                (String::from("funding_broadcasted"), 180),
+                (String::from("awaiting_attestation"), 720),
+            ]),
+            default_stuck_threshold_mins: 60,
+            max_payout_failures: 3,
+        }
+    }
+}
+
+/// Most attempts a webhook delivery may be configured with
+pub const MAX_WEBHOOK_ATTEMPTS: u32 = 20;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    /// Shared secret used to sign every webhook payload (HMAC-SHA256, sent in `X-Webhook-Signature`),
+    /// required when any endpoints are configured
+    pub secret: String,
+    /// Endpoints to notify about competition lifecycle events, no webhooks are sent when empty
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// Total number of attempts per delivery before giving up, at most `MAX_WEBHOOK_ATTEMPTS`
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after every failed attempt
+    pub initial_backoff_ms: u64,
+    /// Timeout for a single delivery request
+    pub request_timeout_secs: u64,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        WebhookSettings {
+            secret: String::new(),
+            endpoints: vec![],
+            max_attempts: 5,
+            initial_backoff_ms: 1000,
+            request_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// Event types to deliver to this endpoint (ie. `created`, `attested`, `completed`),
+    /// all events are delivered when empty
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+impl WebhookEndpoint {
+    pub fn accepts(&self, event_type: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|event| event == event_type)
+    }
+}
+
+/// Where the coordinator private key is loaded from. The same key backs the bitcoin wallet and
+/// nostr signing, so `env` and `command` ignore `seed_path`/`private_key_file`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum SecretsSettings {
+    /// PEM key file at `seed_path`/`private_key_file`, generated on first start when missing
+    #[default]
+    File,
+    /// Hex or WIF encoded key held in an environment variable
+    Env { var: String },
+    /// Hex or WIF encoded key printed to stdout by a command, ie. a secrets manager CLI or
+    /// `security find-generic-password -w -s coordinator` for the macOS keychain
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+/// A coordinator signing key and where to load it from
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningKeySettings {
+    /// Recorded on every competition created while this is the newest key, never reuse one
+    pub id: String,
+    /// PEM key file for the `file` backend, generated on first start when missing
+    #[serde(default)]
+    pub key_file: String,
+    #[serde(default)]
+    pub secrets: SecretsSettings,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BitcoinSettings {
-    /// On-chain network to use
-    pub network: Network,
+    /// On-chain network to use (bitcoin/mainnet, testnet, testnet4, signet, regtest).
+    /// Startup fails if the chain backend is serving a different network.
+    pub network: BitcoinNetwork,
     /// Url to grab on-chain data from as an esplora client
     pub esplora_url: String,
     /// Path to the raw seed to use for the wallet (can be the same as the nostr private key file)
@@ -229,6 +417,10 @@ pub struct BitcoinSettings {
     /// Frequency in seconds for how often to refresh block data with on-chain
     /// (usually want to set to half as often as a block on average will come in, 10min block time -> refresh every 5min)
     pub refresh_blocks_secs: u64,
+    /// Expected seconds between blocks, turns confirmation counts into the wall clock time a new
+    /// competition has to leave for them. Lower it on regtest where blocks are mined on demand.
+    #[serde(default = "default_expected_block_interval_secs")]
+    pub expected_block_interval_secs: u64,
     /// Enable mock Bitcoin client for E2E testing (no real Bitcoin infrastructure required)
     #[serde(default)]
     pub mock_enabled: bool,
@@ -237,24 +429,46 @@ pub struct BitcoinSettings {
 impl Default for BitcoinSettings {
     fn default() -> Self {
         BitcoinSettings {
-            network: Network::Regtest,
+            network: BitcoinNetwork::Regtest,
             esplora_url: String::from("http://localhost:9102"),
             storage_file: String::from("./data/bitcoin.db"),
             seed_path: String::from("./creds/coordinator_private_key.pem"),
             refresh_blocks_secs: 15,
+            expected_block_interval_secs: default_expected_block_interval_secs(),
             mock_enabled: false,
         }
     }
 }
 
+impl BitcoinSettings {
+    /// The configured network as used by the wallet and address/descriptor construction
+    pub fn chain(&self) -> Network {
+        Network::from(self.network)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CoordinatorSettings {
     pub name: String,
     pub oracle_url: String,
+    /// The oracle's public key (hex), published with every competition's oracle event so clients
+    /// can check its announcement and attestation against the oracle rather than this
+    /// coordinator. Read at startup.
+    #[serde(default)]
+    pub oracle_pubkey: Option<String>,
     /// Key to use to sign nostr notes and auth, may also be used for the bitcoin private key
     /// The service will generate one for the bitcoin wallet and use as the signing key for nostr by default
     pub private_key_file: String,
 
+    /// Extra keys the contracts are signed with, oldest first. New competitions use the last one
+    /// and keep it for their whole life, so adding a key rotates without touching competitions in
+    /// flight. The wallet seed key is always available as `default`, for competitions created
+    /// before any key was added. The wallet, escrow inputs and keymeld credentials stay on the
+    /// wallet seed key. Run `coordinator key rotate` after adding a key to move stored keymeld
+    /// session secrets over to it.
+    #[serde(default)]
+    pub signing_keys: Vec<SigningKeySettings>,
+
     /// A reasonable number of blocks within which a transaction can confirm.
     /// Used for enforcing relative locktime timeout spending conditions.
     /// We keep this the same for all competitions so it is a known behavior to the users/players
@@ -270,8 +484,111 @@ pub struct CoordinatorSettings {
     /// The number of confirmations required for a transaction to be considered confirmed
     /// by the coordinator system
     pub required_confirmations: u32,
+    /// Confirmations player escrow transactions need before the competition moves on to
+    /// funding. Defaults to `required_confirmations`
+    #[serde(default)]
+    pub escrow_required_confirmations: Option<u32>,
+    /// Confirmations the funding transaction needs before the competition is considered funded.
+    /// It locks the whole pool, so operators may want more here than for the small escrow
+    /// inputs. Defaults to `required_confirmations`
+    #[serde(default)]
+    pub funding_required_confirmations: Option<u32>,
     pub sync_interval_secs: u64,
 
+    /// Largest `total_allowed_entries` a new competition may ask for. Every entry is a player
+    /// in the DLC and the outcomes grow with the permutations of the winning places, so the
+    /// contract parameters, the `TicketedDLC` built for funding and the signatures players and
+    /// keymeld have to produce all grow with it. Competitions above the cap are rejected when
+    /// they are created instead of blowing up memory and signing time at funding.
+    /// Default is 25
+    #[serde(default = "default_max_total_allowed_entries")]
+    pub max_total_allowed_entries: usize,
+
+    /// Most outcomes a new competition's contract may have with every entry sold. Each outcome
+    /// is a transaction every player and keymeld sign, plus one per paid entry of it, and they
+    /// grow with the permutations of the winning places. Default is 100000.
+    #[serde(default = "default_max_contract_outcomes")]
+    pub max_contract_outcomes: u64,
+
+    /// Approximate megabytes the coordinator may need to build a new competition's
+    /// `TicketedDLC` and hold its nonces and partial signatures, estimated when it is created.
+    /// Default is 1024.
+    #[serde(default = "default_max_contract_memory_mb")]
+    pub max_contract_memory_mb: u64,
+
+    /// Most sats the coordinator will put into a competition's pool on top of the entry fees
+    /// when every ticket sells. With escrow disabled the coordinator wallet funds the whole
+    /// `total_competition_pool`, so a pool above `entry_fee * total_allowed_entries` is money
+    /// the coordinator underwrites. Competitions needing a bigger subsidy are rejected when
+    /// they are created, and a competition funded with fewer entries has its pool cut back to
+    /// stay within it. Default is 0, the pool has to be fully covered by entry fees.
+    #[serde(default)]
+    pub max_pool_subsidy_sats: u64,
+
+    /// Minutes a new competition's `start_observation_date` has to be ahead of its creation on
+    /// top of what the coordinator needs to get it funded: the keymeld keygen and signing
+    /// sessions, plus the escrow and funding confirmations at one block every ten minutes.
+    /// Competitions starting sooner are rejected when they are created. Default is 0, only the
+    /// time needed to fund the contract is required.
+    #[serde(default)]
+    pub min_creation_lead_time_mins: u64,
+
+    /// Tags competitions may carry, new competitions with any other tag are rejected. Empty (the
+    /// default) accepts any tag of lowercase letters, digits and dashes.
+    #[serde(default)]
+    pub allowed_competition_tags: Vec<String>,
+
+    /// Most entries sent to the oracle in one request. Entries go out in chunks of this size so
+    /// large competitions stay under the oracle's body limit, and a failed chunk is retried
+    /// without sending the ones that already landed. Default is 50.
+    #[serde(default = "default_oracle_entries_chunk_size")]
+    pub oracle_entries_chunk_size: usize,
+
+    /// Most contracts built at once. Building a competition's `TicketedDLC` and the
+    /// coordinator's nonces for it runs on the blocking thread pool and can take hundreds of
+    /// megabytes for a large competition, further builds wait for a slot. Read at startup.
+    /// Default is 2.
+    #[serde(default = "default_max_concurrent_contract_builds")]
+    pub max_concurrent_contract_builds: usize,
+
+    /// Coordinator signing sessions kept in memory between aggregating the entries' nonces and
+    /// signing the contract, so the contract isn't built twice. Each holds a whole contract,
+    /// 0 keeps none and signing rebuilds it. Read at startup. Default is 2.
+    #[serde(default = "default_signing_session_cache_size")]
+    pub signing_session_cache_size: usize,
+
+    /// Tickets created along with a competition for a burst of reservations, the rest are
+    /// issued as players reserve them. Competitions signed through keymeld get all of their
+    /// tickets up front. Default is 5.
+    #[serde(default = "default_ticket_buffer_size")]
+    pub ticket_buffer_size: usize,
+
+    /// Seconds before a competition's `signing_date` the oracle starts being asked for its
+    /// attestation. No attestation is expected earlier, so competitions waiting days for their
+    /// signing date don't poll the oracle every tick. Default is 600.
+    #[serde(default = "default_attestation_pre_window_secs")]
+    pub attestation_pre_window_secs: u64,
+
+    /// Seconds between oracle attestation polls for a competition once its pre-window opened.
+    /// Polled by a separate loop that only asks the oracle and kicks the competition watcher when
+    /// the attestation is in, so it can be shorter than `sync_interval_secs`. 0 leaves polling to
+    /// the competition watcher runs. Default is 5.
+    #[serde(default = "default_attestation_poll_interval_secs")]
+    pub attestation_poll_interval_secs: u64,
+
+    /// Randomly stretch or shrink each watcher sleep by up to this percentage (max 50) so the
+    /// competition, invoice and payout watchers don't hit the chain and lightning backends in the
+    /// same instant. 0 disables jitter.
+    #[serde(default)]
+    pub watcher_jitter_percent: u8,
+
+    /// Hours after `created_at` a competition that still hasn't completed, failed or been
+    /// cancelled is failed by the watcher, and refunding its players is started. A backstop for
+    /// competitions stuck in a state none of the stage timeouts cover. 0 disables it. Default is
+    /// 1440 (60 days).
+    #[serde(default = "default_max_competition_lifetime_hours")]
+    pub max_competition_lifetime_hours: u64,
+
     /// Enable on-chain escrow transactions (default: false)
     /// When disabled, only HODL invoices protect against non-completion.
     /// With keymeld signing, escrow is typically not needed since signing is fast.
@@ -279,6 +596,21 @@ pub struct CoordinatorSettings {
     #[serde(default)]
     pub escrow_enabled: bool,
 
+    /// With escrow enabled, have the coordinator broadcast each ticket's escrow transaction once
+    /// every entry of the competition is paid, instead of leaving it to the player. Players then
+    /// can't broadcast early and pay fees for a competition that never fills. Default is false,
+    /// players broadcast the escrow transaction they get with their ticket.
+    #[serde(default)]
+    pub escrow_broadcast_by_coordinator: bool,
+
+    /// With escrow disabled, what happens when the coordinator wallet's confirmed balance can't
+    /// cover a competition's pool plus the estimated funding fee once all its entries are in.
+    /// `fail` fails the competition with `InsufficientFunds` so its players are refunded. `wait`
+    /// leaves it in `entries_submitted` and checks again on every run until the wallet is topped
+    /// up. Default is `fail`.
+    #[serde(default)]
+    pub insufficient_funding_policy: InsufficientFundingPolicy,
+
     /// Enable mock oracle for E2E testing (no real oracle server required)
     #[serde(default)]
     pub mock_oracle: bool,
@@ -289,6 +621,144 @@ pub struct CoordinatorSettings {
     /// Default is 0 (settle immediately at broadcast).
     #[serde(default)]
     pub invoice_settlement_confirmations: u32,
+
+    /// Most `invoice_settlement_confirmations` a competition can ask for in place of the
+    /// coordinator's, competitions over it are rejected when they are created. Default is 144.
+    #[serde(default = "default_max_invoice_settlement_confirmations")]
+    pub max_invoice_settlement_confirmations: u32,
+
+    /// How strictly the funding transaction is checked before settling hold invoices.
+    /// `safe` waits for `invoice_settlement_confirmations` (at least 1) in the best chain and
+    /// holds settlement again if a reorg drops the funding transaction.
+    #[serde(default)]
+    pub invoice_settlement_mode: InvoiceSettlementMode,
+
+    /// Nostr relays (`wss://` urls) used to DM players the backup of their entry's encrypted
+    /// private key and payout preimage, for competitions that don't set their own relays.
+    /// No backup DMs are sent when this and the competition's relays are both empty.
+    #[serde(default)]
+    pub backup_relays: Vec<String>,
+
+    /// Nostr relays the browser client connects to, sent to it in `/api/v1/info`
+    #[serde(default = "default_client_relays")]
+    pub client_relays: Vec<String>,
+
+    /// Seconds shutdown waits for the competition watcher to finish the competition it is
+    /// processing, so a broadcast isn't cut off before its state is saved. Competitions still in
+    /// flight when it runs out are logged. Default is 30.
+    #[serde(default = "default_shutdown_drain_timeout_secs")]
+    pub shutdown_drain_timeout_secs: u64,
+
+    /// Times in a row a background task (watchers, subscribers, webhook worker) is restarted
+    /// after it panics or exits before it is reported degraded and left stopped. Default is 5.
+    #[serde(default = "default_task_max_restarts")]
+    pub task_max_restarts: u32,
+
+    /// Seconds before a failed background task's first restart, doubling on each failure in a
+    /// row up to `task_restart_max_backoff_secs`. Defaults are 1 and 60.
+    #[serde(default = "default_task_restart_backoff_secs")]
+    pub task_restart_backoff_secs: u64,
+
+    #[serde(default = "default_task_restart_max_backoff_secs")]
+    pub task_restart_max_backoff_secs: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceSettlementMode {
+    /// Settle once the funding transaction reports `invoice_settlement_confirmations`,
+    /// or right at broadcast when that is 0
+    #[default]
+    Standard,
+    /// Only settle against a funding transaction confirmed in the current best chain
+    Safe,
+}
+
+/// What to do with a competition the coordinator wallet can't fund yet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsufficientFundingPolicy {
+    /// Fail the competition with `InsufficientFunds`
+    #[default]
+    Fail,
+    /// Keep the competition waiting for the wallet to be topped up, it is still failed once it
+    /// runs past `max_competition_lifetime_hours`
+    Wait,
+}
+
+fn default_expected_block_interval_secs() -> u64 {
+    600
+}
+
+fn default_max_total_allowed_entries() -> usize {
+    25
+}
+
+fn default_max_contract_outcomes() -> u64 {
+    100_000
+}
+
+fn default_max_contract_memory_mb() -> u64 {
+    1024
+}
+
+fn default_max_concurrent_contract_builds() -> usize {
+    2
+}
+
+fn default_signing_session_cache_size() -> usize {
+    2
+}
+
+fn default_ticket_buffer_size() -> usize {
+    5
+}
+
+fn default_oracle_entries_chunk_size() -> usize {
+    50
+}
+
+fn default_client_relays() -> Vec<String> {
+    vec![
+        "wss://relay.damus.io".to_string(),
+        "wss://relay.primal.net".to_string(),
+    ]
+}
+
+fn default_attestation_pre_window_secs() -> u64 {
+    600
+}
+
+fn default_attestation_poll_interval_secs() -> u64 {
+    5
+}
+
+fn default_max_competition_lifetime_hours() -> u64 {
+    1440
+}
+
+fn default_max_invoice_settlement_confirmations() -> u32 {
+    144
+}
+
+fn default_shutdown_drain_timeout_secs() -> u64 {
+    30
+}
+
+fn default_task_max_restarts() -> u32 {
+    5
+}
+
+fn default_task_restart_backoff_secs() -> u64 {
+    1
+}
+
+fn default_task_restart_max_backoff_secs() -> u64 {
+    60
+}
+
+fn default_reservation_sweep_interval() -> u64 {
+    60
 }
 
 impl Default for CoordinatorSettings {
@@ -296,17 +766,57 @@ impl Default for CoordinatorSettings {
         CoordinatorSettings {
             name: String::from("coordinator"),
             oracle_url: String::from("http://127.0.0.1:9800"),
+            oracle_pubkey: None,
             private_key_file: String::from("./creds/coordinator_private_key.pem"),
+            signing_keys: Vec::new(),
             relative_locktime_block_delta: 144,
             required_confirmations: 1,
+            escrow_required_confirmations: None,
+            funding_required_confirmations: None,
             sync_interval_secs: 15,
+            max_total_allowed_entries: default_max_total_allowed_entries(),
+            max_contract_outcomes: default_max_contract_outcomes(),
+            max_contract_memory_mb: default_max_contract_memory_mb(),
+            max_pool_subsidy_sats: 0,
+            min_creation_lead_time_mins: 0,
+            allowed_competition_tags: Vec::new(),
+            oracle_entries_chunk_size: default_oracle_entries_chunk_size(),
+            max_concurrent_contract_builds: default_max_concurrent_contract_builds(),
+            signing_session_cache_size: default_signing_session_cache_size(),
+            ticket_buffer_size: default_ticket_buffer_size(),
+            attestation_pre_window_secs: default_attestation_pre_window_secs(),
+            attestation_poll_interval_secs: default_attestation_poll_interval_secs(),
+            watcher_jitter_percent: 0,
+            max_competition_lifetime_hours: default_max_competition_lifetime_hours(),
             escrow_enabled: false,
+            escrow_broadcast_by_coordinator: false,
+            insufficient_funding_policy: InsufficientFundingPolicy::Fail,
             mock_oracle: false,
             invoice_settlement_confirmations: 0,
+            max_invoice_settlement_confirmations: default_max_invoice_settlement_confirmations(),
+            invoice_settlement_mode: InvoiceSettlementMode::Standard,
+            backup_relays: Vec::new(),
+            client_relays: default_client_relays(),
+            shutdown_drain_timeout_secs: default_shutdown_drain_timeout_secs(),
+            task_max_restarts: default_task_max_restarts(),
+            task_restart_backoff_secs: default_task_restart_backoff_secs(),
+            task_restart_max_backoff_secs: default_task_restart_max_backoff_secs(),
         }
     }
 }
 
+impl CoordinatorSettings {
+    pub fn escrow_required_confirmations(&self) -> u32 {
+        self.escrow_required_confirmations
+            .unwrap_or(self.required_confirmations)
+    }
+
+    pub fn funding_required_confirmations(&self) -> u32 {
+        self.funding_required_confirmations
+            .unwrap_or(self.required_confirmations)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UISettings {
     pub private_url: String,
@@ -329,6 +839,73 @@ pub struct APISettings {
     pub domain: String,
     pub port: String,
     pub origins: Vec<String>,
+    /// Nostr pubkeys (hex or npub) of the operators allowed to use the `/admin` routes and to
+    /// create competitions, requests there must carry a NIP-98 auth header signed by one of
+    /// them. Empty leaves those routes open, for deployments that only expose them on a
+    /// private network.
+    #[serde(default)]
+    pub admin_pubkeys: Vec<String>,
+    /// Largest request body, in bytes after gzip decompression, accepted on the signature
+    /// submission endpoints. Clients with bigger SigMaps send them in chunks. Read at startup.
+    #[serde(default = "default_max_signature_body_bytes")]
+    pub max_signature_body_bytes: usize,
+    /// Largest request body, in bytes, accepted when submitting an entry. Entries are a few
+    /// kilobytes, the limit stops oversized secrets before they are parsed. Read at startup.
+    #[serde(default = "default_max_entry_body_bytes")]
+    pub max_entry_body_bytes: usize,
+    /// Largest request body, in bytes, accepted on every other route. Read at startup.
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Seconds an admin dashboard session lasts after signing in at `/admin/login`, sessions
+    /// also end when the coordinator restarts. Read at startup.
+    #[serde(default = "default_admin_session_ttl_secs")]
+    pub admin_session_ttl_secs: u64,
+    /// How client addresses are kept in the sign-in log users see at `/api/v1/users/me/sessions`.
+    /// Read at startup.
+    #[serde(default)]
+    pub auth_event_ip_privacy: IpPrivacy,
+    /// Days sign-in log rows are kept before they are pruned. Read at startup.
+    #[serde(default = "default_auth_event_retention_days")]
+    pub auth_event_retention_days: u64,
+    /// Addresses of the reverse proxies in front of the coordinator. Only requests coming from
+    /// one of them have their `X-Forwarded-For` header trusted for the client address in the
+    /// sign-in log, anyone else could put any address there. Read at startup.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+/// How a client's address is stored with each sign-in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpPrivacy {
+    /// Keep the /24 of IPv4 and the /48 of IPv6 addresses
+    #[default]
+    Truncate,
+    /// Keep an HMAC-SHA256 of the address under a key derived from the coordinator's private key,
+    /// enough to tell addresses apart but not to recover them without that key
+    Hash,
+    /// Don't keep the address
+    Omit,
+}
+
+fn default_max_signature_body_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_max_entry_body_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_max_body_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_admin_session_ttl_secs() -> u64 {
+    30 * 60
+}
+
+fn default_auth_event_retention_days() -> u64 {
+    30
 }
 
 impl Default for APISettings {
@@ -337,10 +914,579 @@ impl Default for APISettings {
             domain: String::from("127.0.0.1"),
             port: String::from("9990"),
             origins: vec![String::from("http://localhost:9990")],
+            admin_pubkeys: Vec::new(),
+            max_signature_body_bytes: default_max_signature_body_bytes(),
+            max_entry_body_bytes: default_max_entry_body_bytes(),
+            max_body_bytes: default_max_body_bytes(),
+            admin_session_ttl_secs: default_admin_session_ttl_secs(),
+            auth_event_ip_privacy: IpPrivacy::default(),
+            auth_event_retention_days: default_auth_event_retention_days(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+/// Settings shared with the running watchers and handlers so operational knobs can be changed
+/// without a restart (SIGHUP or `POST /admin/config/reload`). Only the fields copied in
+/// `Settings::with_runtime_settings` may change, anything else (network, DB path, keys, ...)
+/// is rejected on reload.
+#[derive(Clone, Debug)]
+pub struct SharedConfig {
+    inner: Arc<RwLock<Settings>>,
+}
+
+impl SharedConfig {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(settings)),
+        }
+    }
+
+    /// Read from the current settings, callers should not hold on to values across ticks
+    pub fn read<T>(&self, f: impl FnOnce(&Settings) -> T) -> T {
+        let settings = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        f(&settings)
+    }
+
+    pub fn current(&self) -> Settings {
+        self.read(Settings::clone)
+    }
+
+    pub fn sync_interval(&self) -> Duration {
+        self.read(|s| Duration::from_secs(s.coordinator_settings.sync_interval_secs))
+    }
+
+    pub fn bitcoin_refresh_interval(&self) -> Duration {
+        self.read(|s| Duration::from_secs(s.bitcoin_settings.refresh_blocks_secs))
+    }
+
+    pub fn invoice_watch_interval(&self) -> Duration {
+        self.read(|s| Duration::from_secs(s.ln_settings.invoice_watch_interval))
+    }
+
+    pub fn payout_watch_interval(&self) -> Duration {
+        self.read(|s| Duration::from_secs(s.ln_settings.payout_watch_interval))
+    }
+
+    pub fn reservation_sweep_interval(&self) -> Duration {
+        self.read(|s| Duration::from_secs(s.ln_settings.reservation_sweep_interval))
+    }
+
+    /// How long before `signing_date` competitions start polling the oracle for an attestation
+    pub fn attestation_pre_window(&self) -> Duration {
+        self.read(|s| Duration::from_secs(s.coordinator_settings.attestation_pre_window_secs))
+    }
+
+    pub fn attestation_poll_interval(&self) -> Duration {
+        self.read(|s| Duration::from_secs(s.coordinator_settings.attestation_poll_interval_secs))
+    }
+
+    pub fn watcher_jitter_percent(&self) -> u8 {
+        self.read(|s| s.coordinator_settings.watcher_jitter_percent)
+    }
+
+    pub fn max_total_allowed_entries(&self) -> usize {
+        self.read(|s| s.coordinator_settings.max_total_allowed_entries)
+    }
+
+    pub fn max_contract_outcomes(&self) -> u64 {
+        self.read(|s| s.coordinator_settings.max_contract_outcomes)
+    }
+
+    pub fn max_contract_memory_bytes(&self) -> u64 {
+        self.read(|s| {
+            s.coordinator_settings
+                .max_contract_memory_mb
+                .saturating_mul(1024 * 1024)
+        })
+    }
+
+    pub fn max_pool_subsidy_sats(&self) -> u64 {
+        self.read(|s| s.coordinator_settings.max_pool_subsidy_sats)
+    }
+
+    /// Shortest time allowed between creating a competition and its `start_observation_date`
+    pub fn min_creation_lead_time(&self) -> Duration {
+        self.min_creation_lead_time_for(None)
+    }
+
+    /// `min_creation_lead_time` for a competition settling its hold invoices after
+    /// `invoice_settlement_confirmations` in place of the coordinator's setting
+    pub fn min_creation_lead_time_for(
+        &self,
+        invoice_settlement_confirmations: Option<u32>,
+    ) -> Duration {
+        self.read(|s| {
+            let settings = &s.coordinator_settings;
+            let signing_window_secs = s.keymeld_settings.keygen_session_expiry_secs
+                + s.keymeld_settings.signing_session_expiry_secs;
+            let escrow_confirmations = if settings.escrow_enabled {
+                settings.escrow_required_confirmations()
+            } else {
+                0
+            };
+            let funding_confirmations = settings.funding_required_confirmations().max(
+                invoice_settlement_confirmations
+                    .unwrap_or(settings.invoice_settlement_confirmations),
+            );
+            let confirmations_secs = u64::from(escrow_confirmations + funding_confirmations)
+                * s.bitcoin_settings.expected_block_interval_secs;
+
+            Duration::from_secs(
+                settings.min_creation_lead_time_mins * 60
+                    + signing_window_secs
+                    + confirmations_secs,
+            )
+        })
+    }
+
+    /// Entries per oracle submission request, never less than one
+    pub fn oracle_entries_chunk_size(&self) -> usize {
+        self.read(|s| s.coordinator_settings.oracle_entries_chunk_size.max(1))
+    }
+
+    pub fn max_concurrent_contract_builds(&self) -> usize {
+        self.read(|s| s.coordinator_settings.max_concurrent_contract_builds.max(1))
+    }
+
+    pub fn signing_session_cache_size(&self) -> usize {
+        self.read(|s| s.coordinator_settings.signing_session_cache_size)
+    }
+
+    pub fn ticket_buffer_size(&self) -> usize {
+        self.read(|s| s.coordinator_settings.ticket_buffer_size)
+    }
+
+    pub fn insufficient_funding_policy(&self) -> InsufficientFundingPolicy {
+        self.read(|s| s.coordinator_settings.insufficient_funding_policy)
+    }
+
+    pub fn allowed_competition_tags(&self) -> Vec<String> {
+        self.read(|s| s.coordinator_settings.allowed_competition_tags.clone())
+    }
+
+    pub fn signing_keys(&self) -> Vec<SigningKeySettings> {
+        self.read(|s| s.coordinator_settings.signing_keys.clone())
+    }
+
+    pub fn backup_relays(&self) -> Vec<String> {
+        self.read(|s| s.coordinator_settings.backup_relays.clone())
+    }
+
+    pub fn client_relays(&self) -> Vec<String> {
+        self.read(|s| s.coordinator_settings.client_relays.clone())
+    }
+
+    pub fn max_signature_body_bytes(&self) -> usize {
+        self.read(|s| s.api_settings.max_signature_body_bytes)
+    }
+
+    pub fn max_entry_body_bytes(&self) -> usize {
+        self.read(|s| s.api_settings.max_entry_body_bytes)
+    }
+
+    pub fn max_body_bytes(&self) -> usize {
+        self.read(|s| s.api_settings.max_body_bytes)
+    }
+
+    pub fn trusted_proxies(&self) -> Vec<IpAddr> {
+        self.read(|s| s.api_settings.trusted_proxies.clone())
+    }
+
+    pub fn admin_pubkeys(&self) -> Vec<String> {
+        self.read(|s| s.api_settings.admin_pubkeys.clone())
+    }
+
+    /// Whether `pubkey` is on the admin allow-list, unparseable entries match nobody
+    pub fn is_admin(&self, pubkey: &nostr_sdk::PublicKey) -> bool {
+        self.read(|s| {
+            s.api_settings
+                .admin_pubkeys
+                .iter()
+                .any(|admin| nostr_sdk::PublicKey::parse(admin).is_ok_and(|admin| admin == *pubkey))
+        })
+    }
+
+    pub fn required_confirmations(&self) -> u32 {
+        self.read(|s| s.coordinator_settings.required_confirmations)
+    }
+
+    pub fn escrow_required_confirmations(&self) -> u32 {
+        self.read(|s| s.coordinator_settings.escrow_required_confirmations())
+    }
+
+    pub fn funding_required_confirmations(&self) -> u32 {
+        self.read(|s| s.coordinator_settings.funding_required_confirmations())
+    }
+
+    /// Longest a competition may run before the watcher fails it, `None` when disabled
+    pub fn max_competition_lifetime(&self) -> Option<time::Duration> {
+        self.read(
+            |s| match s.coordinator_settings.max_competition_lifetime_hours {
+                0 => None,
+                hours => Some(time::Duration::hours(hours as i64)),
+            },
+        )
+    }
+
+    pub fn escrow_broadcast_by_coordinator(&self) -> bool {
+        self.read(|s| s.coordinator_settings.escrow_broadcast_by_coordinator)
+    }
+
+    pub fn invoice_settlement_confirmations(&self) -> u32 {
+        self.read(|s| s.coordinator_settings.invoice_settlement_confirmations)
+    }
+
+    pub fn max_invoice_settlement_confirmations(&self) -> u32 {
+        self.read(|s| s.coordinator_settings.max_invoice_settlement_confirmations)
+    }
+
+    pub fn oracle_pubkey(&self) -> Option<String> {
+        self.read(|s| s.coordinator_settings.oracle_pubkey.clone())
+    }
+
+    /// Re-read the settings file the service was started with and swap in the new values
+    pub fn reload(&self) -> Result<Vec<&'static str>, anyhow::Error> {
+        let current = self.current();
+        let settings = get_settings_with_cli::<Settings>(CliSettings {
+            config: current.config.clone(),
+            level: current.level.clone(),
+        })?;
+        self.apply(settings)
+    }
+
+    /// Validate and swap in new settings, returning the runtime settings that changed
+    pub fn apply(&self, settings: Settings) -> Result<Vec<&'static str>, anyhow::Error> {
+        let mut current = self.inner.write().unwrap_or_else(|e| e.into_inner());
+
+        let immutable_changes =
+            changed_fields(&current, &settings.clone().with_runtime_settings(&current))?;
+        if !immutable_changes.is_empty() {
+            return Err(anyhow!(
+                "Settings can't be changed without a restart: {}",
+                immutable_changes.join(", ")
+            ));
+        }
+
+        settings.validate_runtime_settings()?;
+
+        let changed = current.runtime_settings_changed(&settings);
+        *current = settings;
+        Ok(changed)
+    }
+}
+
+impl Settings {
+    /// Copy the runtime adjustable settings from `other` onto these settings
+    fn with_runtime_settings(mut self, other: &Settings) -> Self {
+        self.coordinator_settings.sync_interval_secs =
+            other.coordinator_settings.sync_interval_secs;
+        self.coordinator_settings.watcher_jitter_percent =
+            other.coordinator_settings.watcher_jitter_percent;
+        self.coordinator_settings.max_competition_lifetime_hours =
+            other.coordinator_settings.max_competition_lifetime_hours;
+        self.coordinator_settings.attestation_pre_window_secs =
+            other.coordinator_settings.attestation_pre_window_secs;
+        self.coordinator_settings.attestation_poll_interval_secs =
+            other.coordinator_settings.attestation_poll_interval_secs;
+        self.coordinator_settings.max_total_allowed_entries =
+            other.coordinator_settings.max_total_allowed_entries;
+        self.coordinator_settings.max_pool_subsidy_sats =
+            other.coordinator_settings.max_pool_subsidy_sats;
+        self.coordinator_settings.min_creation_lead_time_mins =
+            other.coordinator_settings.min_creation_lead_time_mins;
+        self.coordinator_settings.allowed_competition_tags =
+            other.coordinator_settings.allowed_competition_tags.clone();
+        self.coordinator_settings.insufficient_funding_policy =
+            other.coordinator_settings.insufficient_funding_policy;
+        self.coordinator_settings.oracle_entries_chunk_size =
+            other.coordinator_settings.oracle_entries_chunk_size;
+        self.coordinator_settings.client_relays = other.coordinator_settings.client_relays.clone();
+        self.coordinator_settings.required_confirmations =
+            other.coordinator_settings.required_confirmations;
+        self.coordinator_settings.escrow_required_confirmations =
+            other.coordinator_settings.escrow_required_confirmations;
+        self.coordinator_settings.funding_required_confirmations =
+            other.coordinator_settings.funding_required_confirmations;
+        self.coordinator_settings.invoice_settlement_confirmations =
+            other.coordinator_settings.invoice_settlement_confirmations;
+        self.coordinator_settings
+            .max_invoice_settlement_confirmations = other
+            .coordinator_settings
+            .max_invoice_settlement_confirmations;
+        self.bitcoin_settings.refresh_blocks_secs = other.bitcoin_settings.refresh_blocks_secs;
+        self.ln_settings.invoice_watch_interval = other.ln_settings.invoice_watch_interval;
+        self.ln_settings.payout_watch_interval = other.ln_settings.payout_watch_interval;
+        self.ln_settings.reservation_sweep_interval = other.ln_settings.reservation_sweep_interval;
+        self.alert_settings = other.alert_settings.clone();
+        self.api_settings.admin_pubkeys = other.api_settings.admin_pubkeys.clone();
+        self
+    }
+
+    fn runtime_settings_changed(&self, other: &Settings) -> Vec<&'static str> {
+        let checks = [
+            (
+                "coordinator_settings.sync_interval_secs",
+                self.coordinator_settings.sync_interval_secs
+                    != other.coordinator_settings.sync_interval_secs,
+            ),
+            (
+                "coordinator_settings.watcher_jitter_percent",
+                self.coordinator_settings.watcher_jitter_percent
+                    != other.coordinator_settings.watcher_jitter_percent,
+            ),
+            (
+                "coordinator_settings.max_competition_lifetime_hours",
+                self.coordinator_settings.max_competition_lifetime_hours
+                    != other.coordinator_settings.max_competition_lifetime_hours,
+            ),
+            (
+                "coordinator_settings.attestation_pre_window_secs",
+                self.coordinator_settings.attestation_pre_window_secs
+                    != other.coordinator_settings.attestation_pre_window_secs,
+            ),
+            (
+                "coordinator_settings.attestation_poll_interval_secs",
+                self.coordinator_settings.attestation_poll_interval_secs
+                    != other.coordinator_settings.attestation_poll_interval_secs,
+            ),
+            (
+                "coordinator_settings.max_total_allowed_entries",
+                self.coordinator_settings.max_total_allowed_entries
+                    != other.coordinator_settings.max_total_allowed_entries,
+            ),
+            (
+                "coordinator_settings.max_pool_subsidy_sats",
+                self.coordinator_settings.max_pool_subsidy_sats
+                    != other.coordinator_settings.max_pool_subsidy_sats,
+            ),
+            (
+                "coordinator_settings.min_creation_lead_time_mins",
+                self.coordinator_settings.min_creation_lead_time_mins
+                    != other.coordinator_settings.min_creation_lead_time_mins,
+            ),
+            (
+                "coordinator_settings.allowed_competition_tags",
+                self.coordinator_settings.allowed_competition_tags
+                    != other.coordinator_settings.allowed_competition_tags,
+            ),
+            (
+                "coordinator_settings.oracle_entries_chunk_size",
+                self.coordinator_settings.oracle_entries_chunk_size
+                    != other.coordinator_settings.oracle_entries_chunk_size,
+            ),
+            (
+                "coordinator_settings.client_relays",
+                self.coordinator_settings.client_relays != other.coordinator_settings.client_relays,
+            ),
+            (
+                "coordinator_settings.insufficient_funding_policy",
+                self.coordinator_settings.insufficient_funding_policy
+                    != other.coordinator_settings.insufficient_funding_policy,
+            ),
+            (
+                "coordinator_settings.required_confirmations",
+                self.coordinator_settings.required_confirmations
+                    != other.coordinator_settings.required_confirmations,
+            ),
+            (
+                "coordinator_settings.escrow_required_confirmations",
+                self.coordinator_settings.escrow_required_confirmations
+                    != other.coordinator_settings.escrow_required_confirmations,
+            ),
+            (
+                "coordinator_settings.funding_required_confirmations",
+                self.coordinator_settings.funding_required_confirmations
+                    != other.coordinator_settings.funding_required_confirmations,
+            ),
+            (
+                "coordinator_settings.invoice_settlement_confirmations",
+                self.coordinator_settings.invoice_settlement_confirmations
+                    != other.coordinator_settings.invoice_settlement_confirmations,
+            ),
+            (
+                "coordinator_settings.max_invoice_settlement_confirmations",
+                self.coordinator_settings
+                    .max_invoice_settlement_confirmations
+                    != other
+                        .coordinator_settings
+                        .max_invoice_settlement_confirmations,
+            ),
+            (
+                "bitcoin_settings.refresh_blocks_secs",
+                self.bitcoin_settings.refresh_blocks_secs
+                    != other.bitcoin_settings.refresh_blocks_secs,
+            ),
+            (
+                "ln_settings.invoice_watch_interval",
+                self.ln_settings.invoice_watch_interval != other.ln_settings.invoice_watch_interval,
+            ),
+            (
+                "ln_settings.payout_watch_interval",
+                self.ln_settings.payout_watch_interval != other.ln_settings.payout_watch_interval,
+            ),
+            (
+                "ln_settings.reservation_sweep_interval",
+                self.ln_settings.reservation_sweep_interval
+                    != other.ln_settings.reservation_sweep_interval,
+            ),
+            (
+                "alert_settings",
+                self.alert_settings.stuck_state_thresholds_mins
+                    != other.alert_settings.stuck_state_thresholds_mins
+                    || self.alert_settings.default_stuck_threshold_mins
+                        != other.alert_settings.default_stuck_threshold_mins
+                    || self.alert_settings.max_payout_failures
+                        != other.alert_settings.max_payout_failures,
+            ),
+            (
+                "api_settings.admin_pubkeys",
+                self.api_settings.admin_pubkeys != other.api_settings.admin_pubkeys,
+            ),
+        ];
+
+        checks
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Check the settings the service starts with, the runtime settings included
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        self.validate_runtime_settings()?;
+        if let Some(pubkey) = &self.coordinator_settings.oracle_pubkey {
+            if nostr_sdk::secp256k1::PublicKey::from_str(pubkey).is_err()
+                && nostr_sdk::secp256k1::XOnlyPublicKey::from_str(pubkey).is_err()
+            {
+                return Err(anyhow!(
+                    "coordinator_settings.oracle_pubkey is not a hex public key: {}",
+                    pubkey
+                ));
+            }
+        }
+        if !self.webhook_settings.endpoints.is_empty()
+            && self.webhook_settings.secret.trim().is_empty()
+        {
+            return Err(anyhow!(
+                "webhook_settings.secret is required when webhook endpoints are configured"
+            ));
+        }
+        if self.webhook_settings.max_attempts > MAX_WEBHOOK_ATTEMPTS {
+            return Err(anyhow!(
+                "webhook_settings.max_attempts must be at most {}, got {}",
+                MAX_WEBHOOK_ATTEMPTS,
+                self.webhook_settings.max_attempts
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_runtime_settings(&self) -> Result<(), anyhow::Error> {
+        let intervals = [
+            (
+                "coordinator_settings.sync_interval_secs",
+                self.coordinator_settings.sync_interval_secs,
+            ),
+            (
+                "bitcoin_settings.refresh_blocks_secs",
+                self.bitcoin_settings.refresh_blocks_secs,
+            ),
+            (
+                "ln_settings.invoice_watch_interval",
+                self.ln_settings.invoice_watch_interval,
+            ),
+            (
+                "ln_settings.payout_watch_interval",
+                self.ln_settings.payout_watch_interval,
+            ),
+            (
+                "ln_settings.reservation_sweep_interval",
+                self.ln_settings.reservation_sweep_interval,
+            ),
+        ];
+        if let Some((name, _)) = intervals.iter().find(|(_, secs)| *secs == 0) {
+            return Err(anyhow!("{} must be greater than 0", name));
         }
+        if self.coordinator_settings.watcher_jitter_percent > 50 {
+            return Err(anyhow!(
+                "coordinator_settings.watcher_jitter_percent must be at most 50"
+            ));
+        }
+        if self.coordinator_settings.max_total_allowed_entries == 0 {
+            return Err(anyhow!(
+                "coordinator_settings.max_total_allowed_entries must be greater than 0"
+            ));
+        }
+        if self.coordinator_settings.required_confirmations == 0 {
+            return Err(anyhow!(
+                "coordinator_settings.required_confirmations must be greater than 0"
+            ));
+        }
+        if self.coordinator_settings.escrow_required_confirmations == Some(0) {
+            return Err(anyhow!(
+                "coordinator_settings.escrow_required_confirmations must be greater than 0"
+            ));
+        }
+        if self.coordinator_settings.funding_required_confirmations == Some(0) {
+            return Err(anyhow!(
+                "coordinator_settings.funding_required_confirmations must be greater than 0"
+            ));
+        }
+        if let Some(tag) = self
+            .coordinator_settings
+            .allowed_competition_tags
+            .iter()
+            .find(|tag| !crate::domain::is_valid_tag(tag))
+        {
+            return Err(anyhow!(
+                "coordinator_settings.allowed_competition_tags contains an invalid tag: {}",
+                tag
+            ));
+        }
+        if let Some(pubkey) = self
+            .api_settings
+            .admin_pubkeys
+            .iter()
+            .find(|pubkey| nostr_sdk::PublicKey::parse(pubkey).is_err())
+        {
+            return Err(anyhow!(
+                "api_settings.admin_pubkeys contains an invalid nostr pubkey: {}",
+                pubkey
+            ));
+        }
+        Ok(())
     }
 }
 
+/// Names (`section.field`) of every setting that differs between `a` and `b`
+fn changed_fields(a: &Settings, b: &Settings) -> Result<Vec<String>, anyhow::Error> {
+    let (serde_json::Value::Object(a), serde_json::Value::Object(b)) =
+        (serde_json::to_value(a)?, serde_json::to_value(b)?)
+    else {
+        return Err(anyhow!("Settings did not serialize to an object"));
+    };
+
+    let mut changed = vec![];
+    for (section, a_value) in &a {
+        let b_value = b.get(section).unwrap_or(&serde_json::Value::Null);
+        if a_value == b_value {
+            continue;
+        }
+        match (a_value, b_value) {
+            (serde_json::Value::Object(a_fields), serde_json::Value::Object(b_fields)) => {
+                for (field, a_field) in a_fields {
+                    if Some(a_field) != b_fields.get(field) {
+                        changed.push(format!("{}.{}", section, field));
+                    }
+                }
+            }
+            _ => changed.push(section.clone()),
+        }
+    }
+    Ok(changed)
+}
+
 pub fn get_settings() -> Result<Settings, anyhow::Error> {
     get_settings_with_cli(Cli::parse().into())
 }
@@ -487,3 +1633,168 @@ pub fn get_log_level(level: Option<String>) -> LevelFilter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_changes_competition_watcher_interval() {
+        let shared = SharedConfig::new(Settings::default());
+        // Watchers hold a clone and read the interval every tick
+        let watcher_view = shared.clone();
+        assert_eq!(watcher_view.sync_interval(), Duration::from_secs(15));
+
+        let mut settings = Settings::default();
+        settings.coordinator_settings.sync_interval_secs = 60;
+        let changed = shared.apply(settings).unwrap();
+
+        assert_eq!(changed, vec!["coordinator_settings.sync_interval_secs"]);
+        assert_eq!(watcher_view.sync_interval(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_reload_reads_settings_file() {
+        let path =
+            env::temp_dir().join(format!("coordinator-reload-{}.toml", uuid::Uuid::now_v7()));
+        let mut settings = Settings {
+            config: Some(path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let shared = SharedConfig::new(settings.clone());
+
+        settings.coordinator_settings.sync_interval_secs = 5;
+        fs::write(&path, toml::to_string(&settings).unwrap()).unwrap();
+        let reloaded = shared.reload();
+        fs::remove_file(&path).unwrap();
+
+        assert!(reloaded.is_ok(), "{:?}", reloaded);
+        assert_eq!(shared.sync_interval(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_apply_rejects_immutable_changes() {
+        let shared = SharedConfig::new(Settings::default());
+
+        let mut settings = Settings::default();
+        settings.bitcoin_settings.network = BitcoinNetwork::Bitcoin;
+        settings.db_settings.data_folder = String::from("/somewhere/else");
+        settings.coordinator_settings.sync_interval_secs = 60;
+        let err = shared.apply(settings).unwrap_err().to_string();
+
+        assert!(err.contains("bitcoin_settings.network"), "{}", err);
+        assert!(err.contains("db_settings.data_folder"), "{}", err);
+        // Nothing is swapped when the reload is rejected
+        assert_eq!(shared.sync_interval(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn test_apply_rejects_zero_interval() {
+        let shared = SharedConfig::new(Settings::default());
+
+        let mut settings = Settings::default();
+        settings.ln_settings.invoice_watch_interval = 0;
+
+        assert!(shared.apply(settings).is_err());
+        assert_eq!(shared.invoice_watch_interval(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_webhook_endpoints_need_a_secret() {
+        let mut settings = Settings::default();
+        assert!(settings.validate().is_ok());
+
+        settings.webhook_settings.endpoints = vec![WebhookEndpoint {
+            url: String::from("http://localhost:9999/hook"),
+            events: vec![],
+        }];
+        let err = settings.validate().unwrap_err().to_string();
+        assert!(err.contains("webhook_settings.secret"), "{}", err);
+
+        settings.webhook_settings.secret = String::from("shared-secret");
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_webhook_attempts_are_capped() {
+        let mut settings = Settings::default();
+        settings.webhook_settings.max_attempts = MAX_WEBHOOK_ATTEMPTS;
+        assert!(settings.validate().is_ok());
+
+        settings.webhook_settings.max_attempts = 33;
+        let err = settings.validate().unwrap_err().to_string();
+        assert!(err.contains("webhook_settings.max_attempts"), "{}", err);
+    }
+
+    #[test]
+    fn test_apply_watcher_jitter() {
+        let shared = SharedConfig::new(Settings::default());
+
+        let mut settings = Settings::default();
+        settings.coordinator_settings.watcher_jitter_percent = 80;
+        assert!(shared.apply(settings.clone()).is_err());
+
+        settings.coordinator_settings.watcher_jitter_percent = 25;
+        let changed = shared.apply(settings).unwrap();
+        assert_eq!(changed, vec!["coordinator_settings.watcher_jitter_percent"]);
+        assert_eq!(shared.watcher_jitter_percent(), 25);
+    }
+
+    #[test]
+    fn test_min_creation_lead_time_covers_funding() {
+        let mut settings = Settings::default();
+        // keygen and signing sessions plus one funding confirmation
+        assert_eq!(
+            SharedConfig::new(settings.clone()).min_creation_lead_time(),
+            Duration::from_secs(3600 + 300 + 600)
+        );
+
+        settings.coordinator_settings.min_creation_lead_time_mins = 120;
+        settings.coordinator_settings.escrow_enabled = true;
+        settings.coordinator_settings.required_confirmations = 2;
+        settings
+            .coordinator_settings
+            .invoice_settlement_confirmations = 3;
+        assert_eq!(
+            SharedConfig::new(settings.clone()).min_creation_lead_time(),
+            Duration::from_secs(7200 + 3600 + 300 + (2 + 3) * 600)
+        );
+
+        // Escrow and funding can each override the shared requirement
+        settings.coordinator_settings.escrow_required_confirmations = Some(1);
+        settings.coordinator_settings.funding_required_confirmations = Some(6);
+        let shared = SharedConfig::new(settings);
+        assert_eq!(shared.escrow_required_confirmations(), 1);
+        assert_eq!(shared.funding_required_confirmations(), 6);
+        assert_eq!(
+            shared.min_creation_lead_time(),
+            Duration::from_secs(7200 + 3600 + 300 + (1 + 6) * 600)
+        );
+
+        // Regtest blocks come as fast as they are mined
+        let mut settings = Settings::default();
+        settings.keymeld_settings.keygen_session_expiry_secs = 60;
+        settings.keymeld_settings.signing_session_expiry_secs = 30;
+        settings.bitcoin_settings.expected_block_interval_secs = 10;
+        assert_eq!(
+            SharedConfig::new(settings).min_creation_lead_time(),
+            Duration::from_secs(60 + 30 + 10)
+        );
+    }
+
+    #[test]
+    fn test_apply_admin_pubkeys() {
+        let shared = SharedConfig::new(Settings::default());
+
+        let mut settings = Settings::default();
+        settings.api_settings.admin_pubkeys = vec![String::from("not a pubkey")];
+        assert!(shared.apply(settings.clone()).is_err());
+        assert!(shared.admin_pubkeys().is_empty());
+
+        let admin = nostr_sdk::Keys::generate().public_key().to_hex();
+        settings.api_settings.admin_pubkeys = vec![admin.clone()];
+        let changed = shared.apply(settings).unwrap();
+        assert_eq!(changed, vec!["api_settings.admin_pubkeys"]);
+        assert_eq!(shared.admin_pubkeys(), vec![admin]);
+    }
+}
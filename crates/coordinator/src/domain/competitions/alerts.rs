@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::AlertSettings;
+
+use super::{Competition, CompetitionState};
+
+/// Limits used to decide when a competition needs an operator's attention
+#[derive(Debug, Clone)]
+pub struct StuckThresholds {
+    /// Keyed by the `CompetitionState` display name (ie. `awaiting_signatures`)
+    pub per_state: HashMap<String, Duration>,
+    pub default: Duration,
+    pub max_payout_failures: u32,
+}
+
+impl From<&AlertSettings> for StuckThresholds {
+    fn from(settings: &AlertSettings) -> Self {
+        Self {
+            per_state: settings
+                .stuck_state_thresholds_mins
+                .iter()
+                .map(|(state, mins)| (state.clone(), Duration::minutes(*mins as i64)))
+                .collect(),
+            default: Duration::minutes(settings.default_stuck_threshold_mins as i64),
+            max_payout_failures: settings.max_payout_failures,
+        }
+    }
+}
+
+impl StuckThresholds {
+    pub fn for_state(&self, state: &CompetitionState) -> Duration {
+        self.per_state
+            .get(&state.to_string())
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Number of failed payout attempts for an entry that never received a successful payout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PayoutFailureCount {
+    pub competition_id: Uuid,
+    pub entry_id: Uuid,
+    pub failures: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum StuckReason {
+    /// Competition has been in its current state longer than the configured threshold
+    StateTimeout {
+        state: String,
+        minutes_in_state: i64,
+        threshold_mins: i64,
+    },
+    /// Competition has recorded errors while processing
+    HasErrors { count: usize },
+    /// An entry's payout has failed repeatedly
+    PayoutFailures { entry_id: Uuid, failures: u32 },
+    /// Tickets were paid but their hold invoices are still unsettled after the funding window closed
+    UnsettledTickets { count: u64 },
+}
+
+impl StuckReason {
+    pub fn description(&self) -> String {
+        match self {
+            StuckReason::StateTimeout {
+                state,
+                minutes_in_state,
+                threshold_mins,
+            } => format!(
+                "In {} for {}m (threshold {}m)",
+                state, minutes_in_state, threshold_mins
+            ),
+            StuckReason::HasErrors { count } => format!("{} processing error(s)", count),
+            StuckReason::PayoutFailures { entry_id, failures } => {
+                format!("Payout for entry {} failed {} times", entry_id, failures)
+            }
+            StuckReason::UnsettledTickets { count } => {
+                format!("{} paid ticket(s) unsettled past funding window", count)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StuckCompetition {
+    pub competition_id: Uuid,
+    pub state: String,
+    pub reasons: Vec<StuckReason>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StuckCompetitionReport {
+    pub competitions: Vec<StuckCompetition>,
+}
+
+impl StuckCompetitionReport {
+    pub fn is_empty(&self) -> bool {
+        self.competitions.is_empty()
+    }
+
+    /// Build the report from active competitions and the payout/ticket aggregates from the store.
+    /// Kept free of any IO so the same data can feed the dashboard and alerting hooks.
+    pub fn build(
+        competitions: &[Competition],
+        payout_failures: &[PayoutFailureCount],
+        unsettled_tickets: &HashMap<Uuid, u64>,
+        thresholds: &StuckThresholds,
+        now: OffsetDateTime,
+    ) -> Self {
+        let mut stuck = Vec::new();
+
+        for competition in competitions {
+            let state = competition.get_state();
+            let mut reasons = Vec::new();
+
+            if let Some(since) = stuck_clock_start(competition, &state) {
+                let threshold = thresholds.for_state(&state);
+                let in_state = now - since;
+                if in_state > threshold {
+                    reasons.push(StuckReason::StateTimeout {
+                        state: state.to_string(),
+                        minutes_in_state: in_state.whole_minutes(),
+                        threshold_mins: threshold.whole_minutes(),
+                    });
+                }
+            }
+
+            if !competition.errors.is_empty() {
+                reasons.push(StuckReason::HasErrors {
+                    count: competition.errors.len(),
+                });
+            }
+
+            for failure in payout_failures.iter().filter(|failure| {
+                failure.competition_id == competition.id
+                    && failure.failures >= thresholds.max_payout_failures
+            }) {
+                reasons.push(StuckReason::PayoutFailures {
+                    entry_id: failure.entry_id,
+                    failures: failure.failures,
+                });
+            }
+
+            let funding_window_closed = now >= competition.event_submission.start_observation_date;
+            if let Some(count) = unsettled_tickets.get(&competition.id) {
+                if funding_window_closed && *count > 0 {
+                    reasons.push(StuckReason::UnsettledTickets { count: *count });
+                }
+            }
+
+            if !reasons.is_empty() {
+                stuck.push(StuckCompetition {
+                    competition_id: competition.id,
+                    state: state.to_string(),
+                    reasons,
+                    last_error: competition.errors.last().map(|e| e.error.to_string()),
+                });
+            }
+        }
+
+        Self {
+            competitions: stuck,
+        }
+    }
+}
+
+/// When the clock for "time in state" starts. Waiting for entries and waiting for the oracle are
+/// expected to take a while, so those are measured from when they should have finished instead.
+fn stuck_clock_start(
+    competition: &Competition,
+    state: &CompetitionState,
+) -> Option<OffsetDateTime> {
+    match state {
+        CompetitionState::Created | CompetitionState::EntriesCollected => {
+            Some(competition.event_submission.start_observation_date)
+        }
+        CompetitionState::EscrowFundsConfirmed => competition.escrow_funds_confirmed_at,
+        CompetitionState::EventCreated => competition.event_created_at,
+        CompetitionState::EntriesSubmitted => competition.entries_submitted_at,
+        CompetitionState::ContractCreated => competition.contracted_at,
+        CompetitionState::AwaitingSignatures => competition.keymeld_keygen_completed_at,
+        CompetitionState::SigningComplete => competition.signed_at,
+        CompetitionState::FundingBroadcasted => competition.funding_broadcasted_at,
+        CompetitionState::FundingConfirmed => competition.funding_confirmed_at,
+        CompetitionState::FundingSettled => competition.funding_settled_at,
+        CompetitionState::AwaitingAttestation | CompetitionState::Attested => {
+            Some(competition.event_submission.signing_date)
+        }
+        CompetitionState::OutcomeBroadcasted => competition.outcome_broadcasted_at,
+        CompetitionState::DeltaBroadcasted => competition.delta_broadcasted_at,
+        CompetitionState::ExpiryBroadcasted
+        | CompetitionState::Completed
+        | CompetitionState::Failed
+        | CompetitionState::Cancelled => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{CompetitionError, CreateEvent};
+
+    fn test_competition(start: OffsetDateTime) -> Competition {
+        Competition::new(&CreateEvent::fixture(start))
+    }
+
+    fn thresholds() -> StuckThresholds {
+        StuckThresholds::from(&AlertSettings::default())
+    }
+
+    #[test]
+    fn test_healthy_competition_not_reported() {
+        let now = OffsetDateTime::now_utc();
+        let competition = test_competition(now + Duration::hours(6));
+
+        let report =
+            StuckCompetitionReport::build(&[competition], &[], &HashMap::new(), &thresholds(), now);
+
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_state_timeout_uses_per_state_threshold() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now + Duration::hours(6));
+        competition.funding_broadcasted_at = Some(now - Duration::minutes(150));
+
+        // funding_broadcasted threshold is 180m by default
+        let report = StuckCompetitionReport::build(
+            &[competition.clone()],
+            &[],
+            &HashMap::new(),
+            &thresholds(),
+            now,
+        );
+        assert!(report.is_empty());
+
+        competition.funding_broadcasted_at = Some(now - Duration::minutes(200));
+        let report =
+            StuckCompetitionReport::build(&[competition], &[], &HashMap::new(), &thresholds(), now);
+        assert_eq!(report.competitions.len(), 1);
+        assert_eq!(
+            report.competitions[0].reasons,
+            vec![StuckReason::StateTimeout {
+                state: "funding_broadcasted".to_string(),
+                minutes_in_state: 200,
+                threshold_mins: 180,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_errors_reported_with_last_error() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now + Duration::hours(6));
+        competition.record_error(CompetitionError::FailedCreateEvent("first".to_string()));
+        competition.record_error(CompetitionError::FailedSubmitEntries("second".to_string()));
+
+        let report =
+            StuckCompetitionReport::build(&[competition], &[], &HashMap::new(), &thresholds(), now);
+
+        assert_eq!(report.competitions.len(), 1);
+        assert_eq!(
+            report.competitions[0].reasons,
+            vec![StuckReason::HasErrors { count: 2 }]
+        );
+        assert!(report.competitions[0]
+            .last_error
+            .as_ref()
+            .unwrap()
+            .contains("second"));
+    }
+
+    #[test]
+    fn test_payout_failures_over_limit_reported() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now - Duration::hours(30));
+        competition.outcome_broadcasted_at = Some(now);
+        let entry_id = Uuid::now_v7();
+        let failures = vec![
+            PayoutFailureCount {
+                competition_id: competition.id,
+                entry_id,
+                failures: 3,
+            },
+            PayoutFailureCount {
+                competition_id: competition.id,
+                entry_id: Uuid::now_v7(),
+                failures: 1,
+            },
+        ];
+
+        let report = StuckCompetitionReport::build(
+            &[competition],
+            &failures,
+            &HashMap::new(),
+            &thresholds(),
+            now,
+        );
+
+        assert_eq!(
+            report.competitions[0].reasons,
+            vec![StuckReason::PayoutFailures {
+                entry_id,
+                failures: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unsettled_tickets_only_after_funding_window() {
+        let now = OffsetDateTime::now_utc();
+        let competition = test_competition(now + Duration::hours(1));
+        let unsettled = HashMap::from([(competition.id, 2)]);
+
+        let report = StuckCompetitionReport::build(
+            &[competition.clone()],
+            &[],
+            &unsettled,
+            &thresholds(),
+            now,
+        );
+        assert!(report.is_empty());
+
+        let mut late = competition;
+        late.funding_broadcasted_at = Some(now - Duration::minutes(5));
+        let report = StuckCompetitionReport::build(
+            &[late],
+            &[],
+            &unsettled,
+            &thresholds(),
+            now + Duration::hours(2),
+        );
+        assert!(report.competitions[0]
+            .reasons
+            .contains(&StuckReason::UnsettledTickets { count: 2 }));
+    }
+}
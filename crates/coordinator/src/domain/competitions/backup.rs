@@ -0,0 +1,142 @@
+use nostr_sdk::{nips::nip04, Event, EventBuilder, Keys, Kind, PublicKey, Tag};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::UserEntry;
+
+/// Recovery material DMed to a player after their entry is stored. The key and preimage are
+/// still encrypted to the player's nostr key by their client, the DM only means they don't
+/// depend on the coordinator's database to get them back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryBackup {
+    pub competition_id: Uuid,
+    pub entry_id: Uuid,
+    pub ticket_id: Uuid,
+    pub ephemeral_pubkey: String,
+    pub ephemeral_privatekey_encrypted: String,
+    pub payout_hash: String,
+    pub payout_preimage_encrypted: String,
+}
+
+impl From<&UserEntry> for EntryBackup {
+    fn from(entry: &UserEntry) -> Self {
+        Self {
+            competition_id: entry.event_id,
+            entry_id: entry.id,
+            ticket_id: entry.ticket_id,
+            ephemeral_pubkey: entry.ephemeral_pubkey.clone(),
+            ephemeral_privatekey_encrypted: entry.ephemeral_privatekey_encrypted.clone(),
+            payout_hash: entry.payout_hash.clone(),
+            payout_preimage_encrypted: entry.payout_preimage_encrypted.clone(),
+        }
+    }
+}
+
+impl EntryBackup {
+    /// NIP-04 direct message from the coordinator carrying this backup as JSON
+    pub fn to_dm(&self, coordinator_keys: &Keys, recipient: &str) -> Result<Event, anyhow::Error> {
+        json_dm(self, coordinator_keys, recipient)
+    }
+}
+
+/// Tells an entrant the nonces and partial signatures they submitted were cleared because the
+/// funding transaction was rebuilt, so their client has to submit nonces again
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceRoundRestarted {
+    pub competition_id: Uuid,
+    pub entry_id: Uuid,
+    /// Funding outpoint the new coordinator nonces were derived from
+    pub funding_outpoint: String,
+}
+
+impl NonceRoundRestarted {
+    /// NIP-04 direct message from the coordinator carrying this notice as JSON
+    pub fn to_dm(&self, coordinator_keys: &Keys, recipient: &str) -> Result<Event, anyhow::Error> {
+        json_dm(self, coordinator_keys, recipient)
+    }
+}
+
+fn json_dm<T: Serialize>(
+    payload: &T,
+    coordinator_keys: &Keys,
+    recipient: &str,
+) -> Result<Event, anyhow::Error> {
+    let recipient = PublicKey::from_hex(recipient)?;
+    let content = nip04::encrypt(
+        coordinator_keys.secret_key(),
+        &recipient,
+        serde_json::to_string(payload)?,
+    )?;
+
+    Ok(EventBuilder::new(Kind::EncryptedDirectMessage, content)
+        .tags([Tag::public_key(recipient)])
+        .sign_with_keys(coordinator_keys)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_dm_decrypts_for_the_player() {
+        let coordinator_keys = Keys::generate();
+        let player_keys = Keys::generate();
+        let backup = EntryBackup {
+            competition_id: Uuid::now_v7(),
+            entry_id: Uuid::now_v7(),
+            ticket_id: Uuid::now_v7(),
+            ephemeral_pubkey: "02ab".to_string(),
+            ephemeral_privatekey_encrypted: "encrypted-key".to_string(),
+            payout_hash: "cd".to_string(),
+            payout_preimage_encrypted: "encrypted-preimage".to_string(),
+        };
+
+        let event = backup
+            .to_dm(&coordinator_keys, &player_keys.public_key().to_hex())
+            .unwrap();
+        assert_eq!(event.kind, Kind::EncryptedDirectMessage);
+        assert_eq!(event.pubkey, coordinator_keys.public_key());
+        event.verify().unwrap();
+
+        let decrypted = nip04::decrypt(
+            player_keys.secret_key(),
+            &coordinator_keys.public_key(),
+            &event.content,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::from_str::<EntryBackup>(&decrypted).unwrap(),
+            backup
+        );
+
+        assert!(backup.to_dm(&coordinator_keys, "not a pubkey").is_err());
+    }
+
+    #[test]
+    fn test_nonce_round_notice_decrypts_for_the_player() {
+        let coordinator_keys = Keys::generate();
+        let player_keys = Keys::generate();
+        let notice = NonceRoundRestarted {
+            competition_id: Uuid::now_v7(),
+            entry_id: Uuid::now_v7(),
+            funding_outpoint: format!("{}:1", "ab".repeat(32)),
+        };
+
+        let event = notice
+            .to_dm(&coordinator_keys, &player_keys.public_key().to_hex())
+            .unwrap();
+        assert_eq!(event.kind, Kind::EncryptedDirectMessage);
+        event.verify().unwrap();
+
+        let decrypted = nip04::decrypt(
+            player_keys.secret_key(),
+            &coordinator_keys.public_key(),
+            &event.content,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::from_str::<NonceRoundRestarted>(&decrypted).unwrap(),
+            notice
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Local verification of a contract transaction the mempool refused, to tell whether the
+//! aggregate signature or the control block of a spend is at fault.
+use dlctix::bitcoin::{
+    hashes::Hash,
+    key::XOnlyPublicKey,
+    script::Instruction,
+    secp256k1::{Message, Secp256k1, Verification},
+    sighash::{Prevouts, SighashCache},
+    taproot::{ControlBlock, Signature, TapLeafHash},
+    Script, Transaction, TxOut, Txid,
+};
+use std::fmt;
+
+use super::WatchedOutputKind;
+
+/// The backend's mempool refused a contract transaction, nothing was broadcast
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BroadcastRejected {
+    /// Output the transaction spends
+    pub kind: WatchedOutputKind,
+    pub txid: Txid,
+    /// bitcoind's reject-reason
+    pub reason: String,
+    /// What the local verification of the witnesses found
+    pub diagnosis: Vec<String>,
+}
+
+impl fmt::Display for BroadcastRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} spend {} rejected by the mempool: {}",
+            self.kind, self.txid, self.reason
+        )?;
+        if !self.diagnosis.is_empty() {
+            write!(f, " ({})", self.diagnosis.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BroadcastRejected {}
+
+/// Verify each taproot input of `transaction` against its prevout, `prevouts` holds one per
+/// input in order. A key-path signature is checked against the output key. A script-path spend
+/// has its control block checked against the output key and its signatures against the keys in
+/// the leaf script. Returns a line for every input that doesn't verify.
+pub fn diagnose_witnesses(transaction: &Transaction, prevouts: &[TxOut]) -> Vec<String> {
+    if prevouts.len() != transaction.input.len() {
+        return vec![format!(
+            "got {} prevouts for {} inputs, witnesses not verified",
+            prevouts.len(),
+            transaction.input.len()
+        )];
+    }
+
+    let secp = Secp256k1::verification_only();
+    let all_prevouts = Prevouts::All(prevouts);
+    let mut cache = SighashCache::new(transaction);
+    let mut faults = Vec::new();
+
+    for (index, (input, prevout)) in transaction.input.iter().zip(prevouts).enumerate() {
+        if !prevout.script_pubkey.is_p2tr() {
+            continue;
+        }
+        let Ok(output_key) = XOnlyPublicKey::from_slice(&prevout.script_pubkey.as_bytes()[2..])
+        else {
+            faults.push(format!("input {}: prevout has no valid taproot key", index));
+            continue;
+        };
+
+        let mut items: Vec<&[u8]> = input.witness.iter().collect();
+        if items.len() > 1 && items.last().is_some_and(|item| item.first() == Some(&0x50)) {
+            // Annex
+            items.pop();
+        }
+
+        match items.as_slice() {
+            [] => faults.push(format!("input {}: empty witness", index)),
+            [signature] => {
+                let verified = Signature::from_slice(signature)
+                    .map_err(|e| e.to_string())
+                    .and_then(|signature| {
+                        let sighash = cache
+                            .taproot_key_spend_signature_hash(
+                                index,
+                                &all_prevouts,
+                                signature.sighash_type,
+                            )
+                            .map_err(|e| e.to_string())?;
+                        verify(&secp, &signature, sighash.to_byte_array(), &output_key)
+                    });
+                if let Err(e) = verified {
+                    faults.push(format!(
+                        "input {}: aggregate key-path signature doesn't verify against output key {}: {}",
+                        index, output_key, e
+                    ));
+                }
+            }
+            [stack @ .., script, control_block] => {
+                let script = Script::from_bytes(script);
+                let control_block = match ControlBlock::decode(control_block) {
+                    Ok(control_block) => control_block,
+                    Err(e) => {
+                        faults.push(format!("input {}: malformed control block: {}", index, e));
+                        continue;
+                    }
+                };
+                if !control_block.verify_taproot_commitment(&secp, output_key, script) {
+                    faults.push(format!(
+                        "input {}: control block doesn't commit its leaf script to output key {}",
+                        index, output_key
+                    ));
+                    continue;
+                }
+
+                let leaf_hash = TapLeafHash::from_script(script, control_block.leaf_version);
+                let keys: Vec<XOnlyPublicKey> = script
+                    .instructions()
+                    .filter_map(|instruction| match instruction {
+                        Ok(Instruction::PushBytes(bytes)) if bytes.len() == 32 => {
+                            XOnlyPublicKey::from_slice(bytes.as_bytes()).ok()
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                for item in stack.iter().filter(|item| matches!(item.len(), 64 | 65)) {
+                    let Ok(signature) = Signature::from_slice(item) else {
+                        continue;
+                    };
+                    let Ok(sighash) = cache.taproot_script_spend_signature_hash(
+                        index,
+                        &all_prevouts,
+                        leaf_hash,
+                        signature.sighash_type,
+                    ) else {
+                        continue;
+                    };
+                    let digest = sighash.to_byte_array();
+                    if !keys
+                        .iter()
+                        .any(|key| verify(&secp, &signature, digest, key).is_ok())
+                    {
+                        faults.push(format!(
+                            "input {}: script-path signature doesn't verify against any key of leaf {}",
+                            index, leaf_hash
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    faults
+}
+
+fn verify<C: Verification>(
+    secp: &Secp256k1<C>,
+    signature: &Signature,
+    digest: [u8; 32],
+    key: &XOnlyPublicKey,
+) -> Result<(), String> {
+    secp.verify_schnorr(&signature.signature, &Message::from_digest(digest), key)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dlctix::bitcoin::{
+        absolute::LockTime,
+        key::{Keypair, TapTweak},
+        sighash::TapSighashType,
+        transaction::Version,
+        Amount, OutPoint, ScriptBuf, TxIn, Witness,
+    };
+
+    fn spend(witness: Witness) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_byte_array([4; 32]),
+                    vout: 0,
+                },
+                witness,
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(9_000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_diagnose_witnesses_pinpoints_key_path_and_control_block_faults() {
+        let secp = Secp256k1::new();
+        let keypair = Keypair::from_seckey_slice(&secp, &[3; 32]).unwrap();
+        let (internal_key, _) = keypair.x_only_public_key();
+        let prevout = TxOut {
+            value: Amount::from_sat(10_000),
+            script_pubkey: ScriptBuf::new_p2tr(&secp, internal_key, None),
+        };
+
+        let mut transaction = spend(Witness::new());
+        let sighash = SighashCache::new(&transaction)
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&[prevout.clone()]),
+                TapSighashType::Default,
+            )
+            .unwrap();
+        let signature = secp.sign_schnorr_no_aux_rand(
+            &Message::from_digest(sighash.to_byte_array()),
+            &keypair.tap_tweak(&secp, None).to_inner(),
+        );
+        let signature = signature.serialize().to_vec();
+        transaction.input[0].witness = Witness::from_slice(&[&signature]);
+        assert!(diagnose_witnesses(&transaction, &[prevout.clone()]).is_empty());
+
+        let mut forged = signature.clone();
+        forged[10] ^= 1;
+        transaction.input[0].witness = Witness::from_slice(&[&forged]);
+        let faults = diagnose_witnesses(&transaction, &[prevout.clone()]);
+        assert_eq!(faults.len(), 1);
+        assert!(faults[0].contains("aggregate key-path signature"));
+
+        // A control block for another internal key never commits to the output key
+        let other_key = Keypair::from_seckey_slice(&secp, &[5; 32]).unwrap();
+        let mut control_block = vec![0xc0];
+        control_block.extend_from_slice(&other_key.x_only_public_key().0.serialize());
+        transaction.input[0].witness =
+            Witness::from_slice(&[signature.clone(), vec![0x51], control_block]);
+        let faults = diagnose_witnesses(&transaction, &[prevout]);
+        assert_eq!(faults.len(), 1);
+        assert!(faults[0].contains("control block"));
+    }
+}
@@ -0,0 +1,211 @@
+use coordinator_core::Sats;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{Competition, CreateEvent};
+
+/// What to change when creating a competition from an earlier one, anything left out is copied
+/// from the source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloneCompetition {
+    /// The other dates keep their distance to it unless they are given too
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub start_observation_date: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub end_observation_date: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub signing_date: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub signing_deadline: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub locations: Option<Vec<String>>,
+    #[serde(default)]
+    pub number_of_values_per_entry: Option<usize>,
+    #[serde(default)]
+    pub number_of_places_win: Option<usize>,
+    #[serde(default)]
+    pub total_allowed_entries: Option<usize>,
+    #[serde(default)]
+    pub entry_fee: Option<Sats>,
+    #[serde(default)]
+    pub coordinator_fee_percentage: Option<usize>,
+    /// Without it the pool is the new entry fees plus whatever the source's pool added on top
+    /// of its entry fees
+    #[serde(default)]
+    pub total_competition_pool: Option<Sats>,
+    /// Names are unique, the clone has none unless one is given
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub slug: Option<String>,
+}
+
+/// A field of the clone's `CreateEvent` that differs from its source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClonedCompetition {
+    pub competition: Competition,
+    pub source_id: Uuid,
+    pub changes: Vec<FieldChange>,
+}
+
+/// The source's `CreateEvent` with `overrides` applied and a fresh id. `sponsored` is what
+/// sponsors added to the source's pool, it isn't carried over to the clone.
+pub fn cloned_create_event(
+    source: &CreateEvent,
+    sponsored: Sats,
+    overrides: &CloneCompetition,
+) -> CreateEvent {
+    let mut create_event = source.clone();
+    create_event.id = Uuid::now_v7();
+    create_event.name = overrides.name.clone();
+    create_event.slug = overrides.slug.clone();
+
+    let shift = overrides
+        .start_observation_date
+        .map(|start| start - source.start_observation_date)
+        .unwrap_or_default();
+    create_event.start_observation_date = source.start_observation_date + shift;
+    create_event.end_observation_date = overrides
+        .end_observation_date
+        .unwrap_or(source.end_observation_date + shift);
+    create_event.signing_date = overrides
+        .signing_date
+        .unwrap_or(source.signing_date + shift);
+    create_event.signing_deadline = overrides
+        .signing_deadline
+        .or(source.signing_deadline.map(|deadline| deadline + shift));
+
+    if let Some(locations) = &overrides.locations {
+        create_event.locations = locations.clone();
+    }
+    if let Some(values) = overrides.number_of_values_per_entry {
+        create_event.number_of_values_per_entry = values;
+    }
+    if let Some(places) = overrides.number_of_places_win {
+        create_event.number_of_places_win = places;
+    }
+    if let Some(entries) = overrides.total_allowed_entries {
+        create_event.total_allowed_entries = entries;
+    }
+    if let Some(entry_fee) = overrides.entry_fee {
+        create_event.entry_fee = entry_fee;
+    }
+    if let Some(percentage) = overrides.coordinator_fee_percentage {
+        create_event.coordinator_fee_percentage = percentage;
+    }
+
+    // Saturating so an oversized clone fails validation like any other new competition
+    create_event.total_competition_pool = overrides.total_competition_pool.unwrap_or_else(|| {
+        let mut unsponsored = source.clone();
+        unsponsored.total_competition_pool =
+            source.total_competition_pool.saturating_sub(sponsored);
+        create_event
+            .entry_fee
+            .saturating_mul(create_event.total_allowed_entries as u64)
+            .saturating_add(unsponsored.pool_subsidy(source.total_allowed_entries))
+    });
+
+    create_event
+}
+
+/// Fields of `after` that differ from `before`, ignoring the id
+pub fn create_event_changes(before: &CreateEvent, after: &CreateEvent) -> Vec<FieldChange> {
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+    after
+        .into_iter()
+        .filter(|(field, _)| field != "id")
+        .filter_map(|(field, to)| {
+            let from = before.get(&field).cloned().unwrap_or(Value::Null);
+            (from != to).then_some(FieldChange { field, from, to })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    fn source() -> CreateEvent {
+        let start = OffsetDateTime::now_utc() - Duration::days(1);
+        CreateEvent {
+            total_allowed_entries: 10,
+            // 5k subsidy from the coordinator and 2k from a sponsor
+            total_competition_pool: Sats(17_000),
+            signing_deadline: Some(start - Duration::hours(1)),
+            name: Some("Daily Chicago".to_string()),
+            tags: vec!["daily".to_string()],
+            ..CreateEvent::fixture(start)
+        }
+    }
+
+    #[test]
+    fn test_clone_shifts_dates_and_rescales_the_pool() {
+        let source = source();
+        let start = source.start_observation_date + Duration::days(1);
+        let overrides = CloneCompetition {
+            start_observation_date: Some(start),
+            total_allowed_entries: Some(50),
+            entry_fee: Some(Sats(2_000)),
+            ..Default::default()
+        };
+
+        let clone = cloned_create_event(&source, Sats(2_000), &overrides);
+        assert_ne!(clone.id, source.id);
+        assert_eq!(clone.start_observation_date, start);
+        assert_eq!(clone.end_observation_date, start + Duration::hours(18));
+        assert_eq!(clone.signing_date, start + Duration::hours(27));
+        assert_eq!(clone.signing_deadline, Some(start - Duration::hours(1)));
+        assert_eq!(clone.total_competition_pool, Sats(2_000 * 50 + 5_000));
+        assert_eq!(clone.name, None);
+        assert_eq!(clone.tags, source.tags);
+
+        let changes = create_event_changes(&source, &clone);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        for field in [
+            "start_observation_date",
+            "end_observation_date",
+            "signing_date",
+            "signing_deadline",
+            "total_allowed_entries",
+            "entry_fee",
+            "total_competition_pool",
+            "name",
+        ] {
+            assert!(fields.contains(&field), "{} should have changed", field);
+        }
+        assert!(!fields.contains(&"id"));
+        assert!(!fields.contains(&"locations"));
+        let entries = changes
+            .iter()
+            .find(|c| c.field == "total_allowed_entries")
+            .unwrap();
+        assert_eq!(entries.from, Value::from(10));
+        assert_eq!(entries.to, Value::from(50));
+    }
+
+    #[test]
+    fn test_clone_pool_override_is_kept() {
+        let overrides = CloneCompetition {
+            total_competition_pool: Some(Sats(42_000)),
+            name: Some("Daily Chicago 2".to_string()),
+            ..Default::default()
+        };
+        let clone = cloned_create_event(&source(), Sats::ZERO, &overrides);
+        assert_eq!(clone.total_competition_pool, Sats(42_000));
+        assert_eq!(clone.name.as_deref(), Some("Daily Chicago 2"));
+    }
+}
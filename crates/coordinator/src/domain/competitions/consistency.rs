@@ -0,0 +1,291 @@
+use dlctix::bitcoin::Txid;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::bitcoin::TxChainStatus;
+
+use super::Competition;
+
+/// Read-only audit of stored competition timestamps against what the chain backend reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    pub competitions: Vec<CompetitionConsistency>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitionConsistency {
+    pub competition_id: Uuid,
+    pub state: String,
+    /// Empty when the stored state agrees with the chain
+    pub mismatches: Vec<ConsistencyMismatch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ConsistencyMismatch {
+    /// A broadcast timestamp is set but the transaction itself was never stored
+    MissingTransaction { field: String, transaction: String },
+    /// A broadcast timestamp is set but the transaction is in neither the mempool nor the chain
+    NotOnChain {
+        field: String,
+        transaction: String,
+        txid: String,
+    },
+    /// The stored state requires confirmations the transaction doesn't have
+    NotConfirmed {
+        field: String,
+        transaction: String,
+        txid: String,
+    },
+    /// The transaction is in the mempool or chain but its broadcast timestamp is empty
+    UnrecordedBroadcast {
+        field: String,
+        transaction: String,
+        txid: String,
+    },
+    /// The chain backend couldn't be asked about the transaction
+    LookupFailed { transaction: String, txid: String },
+}
+
+/// A transaction of a competition whose chain status backs one of its stored timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditedTransaction {
+    pub transaction: &'static str,
+    pub field: &'static str,
+    pub txid: Option<Txid>,
+    pub recorded: bool,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.competitions
+            .iter()
+            .all(|competition| competition.mismatches.is_empty())
+    }
+
+    /// Transactions whose chain status the report needs, so the caller can look them up up front
+    pub fn audited_transactions(competition: &Competition) -> Vec<AuditedTransaction> {
+        let mut audited = vec![
+            AuditedTransaction {
+                transaction: "funding",
+                field: "funding_broadcasted_at",
+                txid: competition
+                    .funding_transaction
+                    .as_ref()
+                    .map(|tx| tx.compute_txid())
+                    .or(competition.funding_outpoint.map(|outpoint| outpoint.txid)),
+                recorded: competition.funding_broadcasted_at.is_some(),
+            },
+            AuditedTransaction {
+                transaction: "outcome",
+                field: "outcome_broadcasted_at",
+                txid: competition
+                    .outcome_transaction
+                    .as_ref()
+                    .map(|tx| tx.compute_txid()),
+                recorded: competition.outcome_broadcasted_at.is_some(),
+            },
+        ];
+        // Only a signed contract has an expiry transaction to look for
+        if let Some(signed_contract) = competition.signed_contract.as_ref() {
+            audited.push(AuditedTransaction {
+                transaction: "expiry",
+                field: "expiry_broadcasted_at",
+                txid: signed_contract.expiry_tx().map(|tx| tx.compute_txid()),
+                recorded: competition.expiry_broadcasted_at.is_some(),
+            });
+        }
+        audited
+    }
+
+    /// Build the report from non-terminal competitions and the chain status of their transactions.
+    /// Transactions missing from `chain_statuses` are reported as failed lookups.
+    pub fn build(
+        competitions: &[Competition],
+        chain_statuses: &HashMap<Txid, TxChainStatus>,
+        now: OffsetDateTime,
+    ) -> Self {
+        let competitions = competitions
+            .iter()
+            .map(|competition| CompetitionConsistency {
+                competition_id: competition.id,
+                state: competition.get_state().to_string(),
+                mismatches: audit_competition(competition, chain_statuses),
+            })
+            .collect();
+
+        Self {
+            generated_at: now,
+            competitions,
+        }
+    }
+}
+
+fn audit_competition(
+    competition: &Competition,
+    chain_statuses: &HashMap<Txid, TxChainStatus>,
+) -> Vec<ConsistencyMismatch> {
+    let mut mismatches = Vec::new();
+
+    for audited in ConsistencyReport::audited_transactions(competition) {
+        let Some(txid) = audited.txid else {
+            if audited.recorded {
+                mismatches.push(ConsistencyMismatch::MissingTransaction {
+                    field: audited.field.to_string(),
+                    transaction: audited.transaction.to_string(),
+                });
+            }
+            continue;
+        };
+        let Some(status) = chain_statuses.get(&txid).copied() else {
+            mismatches.push(ConsistencyMismatch::LookupFailed {
+                transaction: audited.transaction.to_string(),
+                txid: txid.to_string(),
+            });
+            continue;
+        };
+
+        match (audited.recorded, status) {
+            (true, TxChainStatus::NotFound) => mismatches.push(ConsistencyMismatch::NotOnChain {
+                field: audited.field.to_string(),
+                transaction: audited.transaction.to_string(),
+                txid: txid.to_string(),
+            }),
+            (false, TxChainStatus::Unconfirmed | TxChainStatus::Confirmed { .. }) => mismatches
+                .push(ConsistencyMismatch::UnrecordedBroadcast {
+                    field: audited.field.to_string(),
+                    transaction: audited.transaction.to_string(),
+                    txid: txid.to_string(),
+                }),
+            _ => {}
+        }
+
+        // Later timestamps only make sense once these transactions have confirmed
+        let requires_confirmation = match audited.transaction {
+            "funding" => competition
+                .funding_confirmed_at
+                .map(|_| "funding_confirmed_at"),
+            "outcome" => competition
+                .delta_broadcasted_at
+                .map(|_| "delta_broadcasted_at"),
+            _ => None,
+        };
+        if let Some(field) = requires_confirmation {
+            if status == TxChainStatus::Unconfirmed {
+                mismatches.push(ConsistencyMismatch::NotConfirmed {
+                    field: field.to_string(),
+                    transaction: audited.transaction.to_string(),
+                    txid: txid.to_string(),
+                });
+            }
+        }
+    }
+
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::CreateEvent;
+    use dlctix::bitcoin::{absolute::LockTime, transaction::Version, Transaction};
+    use time::Duration;
+
+    fn test_competition() -> Competition {
+        let start = OffsetDateTime::now_utc() - Duration::hours(1);
+        Competition::new(&CreateEvent::fixture(start))
+    }
+
+    fn test_tx(lock_time: u32) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::from_consensus(lock_time),
+            input: vec![],
+            output: vec![],
+        }
+    }
+
+    #[test]
+    fn test_consistent_competition_has_no_mismatches() {
+        let mut competition = test_competition();
+        let funding_tx = test_tx(1);
+        competition.funding_transaction = Some(funding_tx.clone());
+        competition.funding_broadcasted_at = Some(OffsetDateTime::now_utc());
+        competition.funding_confirmed_at = Some(OffsetDateTime::now_utc());
+        let statuses = HashMap::from([(
+            funding_tx.compute_txid(),
+            TxChainStatus::Confirmed {
+                height: 100,
+                confirmations: 3,
+            },
+        )]);
+
+        let report = ConsistencyReport::build(&[competition], &statuses, OffsetDateTime::now_utc());
+
+        assert!(report.is_consistent());
+    }
+
+    #[test]
+    fn test_recorded_broadcasts_missing_from_chain() {
+        let mut competition = test_competition();
+        let funding_tx = test_tx(1);
+        let outcome_tx = test_tx(2);
+        competition.funding_transaction = Some(funding_tx.clone());
+        competition.funding_broadcasted_at = Some(OffsetDateTime::now_utc());
+        competition.funding_confirmed_at = Some(OffsetDateTime::now_utc());
+        competition.outcome_transaction = Some(outcome_tx.clone());
+        competition.outcome_broadcasted_at = Some(OffsetDateTime::now_utc());
+        let statuses = HashMap::from([
+            (funding_tx.compute_txid(), TxChainStatus::Unconfirmed),
+            (outcome_tx.compute_txid(), TxChainStatus::NotFound),
+        ]);
+
+        let report = ConsistencyReport::build(&[competition], &statuses, OffsetDateTime::now_utc());
+
+        assert_eq!(
+            report.competitions[0].mismatches,
+            vec![
+                ConsistencyMismatch::NotConfirmed {
+                    field: "funding_confirmed_at".to_string(),
+                    transaction: "funding".to_string(),
+                    txid: funding_tx.compute_txid().to_string(),
+                },
+                ConsistencyMismatch::NotOnChain {
+                    field: "outcome_broadcasted_at".to_string(),
+                    transaction: "outcome".to_string(),
+                    txid: outcome_tx.compute_txid().to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unrecorded_and_missing_transactions() {
+        let mut competition = test_competition();
+        let funding_tx = test_tx(1);
+        competition.funding_transaction = Some(funding_tx.clone());
+        competition.outcome_broadcasted_at = Some(OffsetDateTime::now_utc());
+        let statuses = HashMap::from([(funding_tx.compute_txid(), TxChainStatus::Unconfirmed)]);
+
+        let report = ConsistencyReport::build(&[competition], &statuses, OffsetDateTime::now_utc());
+
+        assert_eq!(
+            report.competitions[0].mismatches,
+            vec![
+                ConsistencyMismatch::UnrecordedBroadcast {
+                    field: "funding_broadcasted_at".to_string(),
+                    transaction: "funding".to_string(),
+                    txid: funding_tx.compute_txid().to_string(),
+                },
+                ConsistencyMismatch::MissingTransaction {
+                    field: "outcome_broadcasted_at".to_string(),
+                    transaction: "outcome".to_string(),
+                },
+            ]
+        );
+    }
+}
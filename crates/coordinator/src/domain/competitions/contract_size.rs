@@ -0,0 +1,285 @@
+use coordinator_core::ValidationErrors;
+use serde::{Deserialize, Serialize};
+
+use super::{CreateEvent, TiePolicy};
+
+/// Weight above which bitcoin core won't relay a transaction
+pub const MAX_STANDARD_TX_WEIGHT: u64 = 400_000;
+
+pub const MIB: u64 = 1024 * 1024;
+
+// Rough in-memory sizes of the pieces of a `TicketedDLC` and its signing state. They only need
+// to be in the right ballpark to tell a competition that fits from one that doesn't.
+const OUTCOME_TX_BYTES: u64 = 200;
+const SPLIT_TX_BYTES: u64 = 150;
+const SPLIT_OUTPUT_BYTES: u64 = 50;
+/// Tapscript leaf and control block for a win condition, plus the split output's own tree
+const WIN_CONDITION_BYTES: u64 = 400;
+/// Public nonce and partial signature a signer gives for every outcome and win condition
+const SIGNER_BYTES_PER_SIGNATURE: u64 = 66 + 32;
+
+/// Outcome transactions spend the funding output by the key path, one input and one output
+const OUTCOME_TX_WEIGHT: u64 = 444;
+/// Split transaction without outputs: version, locktime, counts and the outcome output's input
+const SPLIT_TX_BASE_WEIGHT: u64 = 4 * (4 + 4 + 1 + 3 + 41);
+/// Signature, ticket preimage and win condition script revealed when spending the outcome output
+const SPLIT_TX_WITNESS_WEIGHT: u64 = 2 + 1 + 65 + 33 + 76 + 34;
+const P2TR_OUTPUT_WEIGHT: u64 = 4 * 43;
+const TAPROOT_BRANCH_WEIGHT: u64 = 32;
+
+/// How big the contract of a competition gets once every entry is sold, before any of it is
+/// built. Only entries, winning places and the tie policy change its size, the locations just
+/// change what the oracle scores.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractSizeEstimate {
+    pub total_allowed_entries: usize,
+    pub number_of_places_win: usize,
+    pub tie_policy: TiePolicy,
+    /// Attestation outcomes plus the expiry outcome, one outcome transaction each
+    pub outcomes: u64,
+    /// Paid entries summed over the outcomes, one split transaction output each
+    pub win_conditions: u64,
+    /// MuSig2 signatures the players, the coordinator and keymeld have to produce
+    pub signatures: u64,
+    /// Approximate bytes of the `TicketedDLC`, the nonces and the partial signatures
+    pub approx_memory_bytes: u64,
+    pub outcome_tx_weight: u64,
+    /// The widest split transaction, the refund and expiry outcomes pay every entry
+    pub max_split_tx_weight: u64,
+}
+
+/// Thresholds new competitions' contracts are checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractLimits {
+    pub max_outcomes: u64,
+    pub max_memory_bytes: u64,
+}
+
+impl ContractSizeEstimate {
+    pub fn new(
+        total_allowed_entries: usize,
+        number_of_places_win: usize,
+        tie_policy: TiePolicy,
+    ) -> Self {
+        let entries = total_allowed_entries as u64;
+        let places = number_of_places_win.min(total_allowed_entries);
+        // Rankings include the refund outcome that pays every entry
+        let (rankings, ranking_winners) = match tie_policy {
+            TiePolicy::Tiebreak => {
+                let strict = permutation_count(total_allowed_entries, places);
+                (
+                    strict.saturating_add(1),
+                    strict.saturating_mul(places as u64).saturating_add(entries),
+                )
+            }
+            TiePolicy::Split => weak_ranking_winners(total_allowed_entries, places),
+        };
+        // The expiry outcome refunds every entry too
+        let outcomes = rankings.saturating_add(1);
+        let win_conditions = ranking_winners.saturating_add(entries);
+        let signatures = outcomes.saturating_add(win_conditions);
+
+        let transactions = outcomes
+            .saturating_mul(OUTCOME_TX_BYTES + SPLIT_TX_BYTES)
+            .saturating_add(
+                win_conditions.saturating_mul(SPLIT_OUTPUT_BYTES + WIN_CONDITION_BYTES),
+            );
+        let signing_state = signatures
+            .saturating_mul(entries + 1)
+            .saturating_mul(SIGNER_BYTES_PER_SIGNATURE);
+
+        let tree_depth = u64::from(entries.max(1).next_power_of_two().trailing_zeros());
+        let max_split_tx_weight = SPLIT_TX_BASE_WEIGHT
+            + SPLIT_TX_WITNESS_WEIGHT
+            + tree_depth * TAPROOT_BRANCH_WEIGHT
+            + entries.saturating_mul(P2TR_OUTPUT_WEIGHT);
+
+        ContractSizeEstimate {
+            total_allowed_entries,
+            number_of_places_win,
+            tie_policy,
+            outcomes,
+            win_conditions,
+            signatures,
+            approx_memory_bytes: transactions.saturating_add(signing_state),
+            outcome_tx_weight: OUTCOME_TX_WEIGHT,
+            max_split_tx_weight,
+        }
+    }
+
+    /// Outcomes the oracle attests to, one locking point each. The expiry outcome needs none.
+    pub fn attested_outcomes(&self) -> u64 {
+        self.outcomes.saturating_sub(1)
+    }
+
+    pub fn for_event(create_event: &CreateEvent) -> Self {
+        Self::new(
+            create_event.total_allowed_entries,
+            create_event.number_of_places_win,
+            create_event.tie_policy,
+        )
+    }
+
+    /// A validation error per limit the contract goes over, with the estimated numbers
+    pub fn check(&self, limits: &ContractLimits) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.outcomes > limits.max_outcomes {
+            errors.push(
+                "total_allowed_entries",
+                "too_many_outcomes",
+                format!(
+                    "{} entries over {} places need {} outcomes and {} signatures, at most {} outcomes are allowed",
+                    self.total_allowed_entries,
+                    self.number_of_places_win,
+                    self.outcomes,
+                    self.signatures,
+                    limits.max_outcomes
+                ),
+            );
+        }
+        if self.approx_memory_bytes > limits.max_memory_bytes {
+            errors.push(
+                "total_allowed_entries",
+                "contract_too_large",
+                format!(
+                    "the contract for {} entries over {} places takes about {} MiB to build and sign, at most {} MiB is allowed",
+                    self.total_allowed_entries,
+                    self.number_of_places_win,
+                    self.approx_memory_bytes.div_ceil(MIB),
+                    limits.max_memory_bytes / MIB
+                ),
+            );
+        }
+        if self.max_split_tx_weight > MAX_STANDARD_TX_WEIGHT {
+            errors.push(
+                "total_allowed_entries",
+                "split_tx_too_heavy",
+                format!(
+                    "paying all {} entries takes a {} WU split transaction, over the {} WU standardness limit",
+                    self.total_allowed_entries, self.max_split_tx_weight, MAX_STANDARD_TX_WEIGHT
+                ),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+/// Orderings of `places` entries out of `entries`, the strict rankings of `TiePolicy::Tiebreak`
+fn permutation_count(entries: usize, places: usize) -> u64 {
+    (entries - places + 1..=entries).fold(1u64, |count, n| count.saturating_mul(n as u64))
+}
+
+/// Rankings `TiePolicy::Split` gives and the paid entries summed over them, counted the same
+/// way as `split_ranking_count`. Every entry of a group spanning a paid place is paid.
+fn weak_ranking_winners(remaining: usize, places_left: usize) -> (u64, u64) {
+    let mut count: u64 = 0;
+    let mut winners: u64 = 0;
+    let mut choose: u128 = 1;
+    for size in 1..=remaining {
+        // remaining choose size
+        choose = choose.saturating_mul((remaining - size + 1) as u128) / size as u128;
+        let choose = u64::try_from(choose).unwrap_or(u64::MAX);
+        let (rest, rest_winners) = if size >= places_left {
+            (1, 0)
+        } else {
+            weak_ranking_winners(remaining - size, places_left - size)
+        };
+        count = count.saturating_add(choose.saturating_mul(rest));
+        winners = winners.saturating_add(
+            choose.saturating_mul(
+                rest.saturating_mul(size as u64)
+                    .saturating_add(rest_winners),
+            ),
+        );
+    }
+    (count, winners)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{generate_rankings, split_ranking_count};
+
+    #[test]
+    fn test_estimate_matches_known_contracts() {
+        // 3 entries, 1 place: 3 rankings, refund and expiry
+        let small = ContractSizeEstimate::new(3, 1, TiePolicy::Tiebreak);
+        assert_eq!(small.outcomes, 5);
+        assert_eq!(small.win_conditions, 3 + 3 + 3);
+        assert_eq!(small.signatures, 14);
+
+        // The default cap of 25 entries over 3 places
+        let default_cap = ContractSizeEstimate::new(25, 3, TiePolicy::Tiebreak);
+        assert_eq!(default_cap.outcomes, 25 * 24 * 23 + 2);
+        assert_eq!(default_cap.win_conditions, 25 * 24 * 23 * 3 + 50);
+        assert_eq!(default_cap.signatures, 55_252);
+        assert!((128 * MIB..256 * MIB).contains(&default_cap.approx_memory_bytes));
+        assert!(default_cap.max_split_tx_weight < 10_000);
+
+        // Five places over 25 entries is millions of outcomes
+        let huge = ContractSizeEstimate::new(25, 5, TiePolicy::Tiebreak);
+        assert_eq!(huge.outcomes, 25 * 24 * 23 * 22 * 21 + 2);
+    }
+
+    #[test]
+    fn test_estimate_counts_the_rankings_the_contract_is_built_from() {
+        for (entries, places) in [(3, 1), (4, 2), (5, 3), (6, 2)] {
+            for policy in [TiePolicy::Tiebreak, TiePolicy::Split] {
+                let rankings = generate_rankings(entries, places, policy);
+                let winners: usize = rankings
+                    .iter()
+                    .map(|ranking| {
+                        let mut covered = 0;
+                        ranking
+                            .iter()
+                            .take_while(|group| {
+                                let paid = covered < places;
+                                covered += group.len();
+                                paid
+                            })
+                            .map(Vec::len)
+                            .sum::<usize>()
+                    })
+                    .sum();
+
+                let estimate = ContractSizeEstimate::new(entries, places, policy);
+                assert_eq!(estimate.outcomes, rankings.len() as u64 + 1);
+                assert_eq!(estimate.win_conditions, (winners + entries) as u64);
+            }
+            assert_eq!(
+                ContractSizeEstimate::new(entries, places, TiePolicy::Split).outcomes,
+                split_ranking_count(entries, places) as u64 + 1
+            );
+        }
+    }
+
+    #[test]
+    fn test_contracts_over_the_limits_are_rejected_with_the_numbers() {
+        let limits = ContractLimits {
+            max_outcomes: 100_000,
+            max_memory_bytes: 1024 * MIB,
+        };
+        assert!(ContractSizeEstimate::new(25, 3, TiePolicy::Tiebreak)
+            .check(&limits)
+            .is_ok());
+
+        let errors = ContractSizeEstimate::new(25, 4, TiePolicy::Tiebreak)
+            .check(&limits)
+            .unwrap_err();
+        let codes: Vec<&str> = errors.errors.iter().map(|e| e.code.as_str()).collect();
+        assert_eq!(codes, vec!["too_many_outcomes", "contract_too_large"]);
+        assert!(
+            errors.errors[0].message.contains("303602 outcomes"),
+            "{}",
+            errors.errors[0].message
+        );
+
+        let errors = ContractSizeEstimate::new(3_000, 1, TiePolicy::Tiebreak)
+            .check(&ContractLimits {
+                max_outcomes: u64::MAX,
+                max_memory_bytes: u64::MAX,
+            })
+            .unwrap_err();
+        assert_eq!(errors.errors[0].code, "split_tx_too_heavy");
+    }
+}
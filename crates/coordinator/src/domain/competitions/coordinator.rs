@@ -1,20 +1,55 @@
 #![allow(deprecated)]
 use super::{
-    states::CompetitionStatus, AddEntry, CompetitionError, CompetitionStore, FundedContract,
-    KeymeldSigningInfo, PayoutInfo, SearchBy, Ticket, TicketStatus, UserEntry, UserEntryView,
+    build_timeline, check_chunk_keys, check_entrant_psbt, check_sig_map_keys, cloned_create_event,
+    combine_entrant_psbt, create_event_changes, decrypt_session_secret, diagnose_witnesses,
+    disclose_contract, disclosure_salt, entrant_funding_psbt, equal_weights, escrow_double_spend,
+    escrow_input_index, generate_rankings, get_percentage_weights, is_valid_slug, is_valid_tag,
+    player_order_from_entries, player_order_from_tickets, ranking_entry_ids, ranking_weights,
+    resolve_spend, sig_map_covers, sig_map_digest, sig_map_len, sign_receipt, split_payout,
+    split_ranking_count, states::CompetitionStatus, verify_player_order, watched_outputs, AddEntry,
+    AddSponsorship, BroadcastRejected, CloneCompetition, ClonedCompetition, CompetitionChange,
+    CompetitionError, CompetitionSolvency, CompetitionState, CompetitionStore, CompetitionTimeline,
+    ConsistencyReport, ContractDisclosure, ContractLimits, ContractSizeEstimate,
+    CoordinatorKeyring, EligiblePayout, EntryBackup, EntryPayout, EntryPreview, EscrowBroadcaster,
+    EscrowChainStatus, EscrowDoubleSpent, EscrowReclaimInfo, EscrowStatus, FundedContract,
+    FundingFeeRate, FundingShortfall, KeymeldRegistration, KeymeldSigningInfo, KeyringError,
+    LedgerEntry, LedgerEntryKind, NonceRoundRestarted, OpenCompetitionFeed, OracleEventInfo,
+    PayoutFailureCount, PayoutInfo, PayoutStatus, PendingEscrowReclaim, PlayerOrderReport,
+    PnlReport, ReceiptKind, ReceiptPayload, SearchBy, SettlementProgress, SignatureChunkProgress,
+    SolvencyReport, SpendResolution, SponsorFunds, Sponsorship, StuckCompetitionReport,
+    StuckThresholds, Ticket, TicketStatus, TicketStatusResponse, TiePolicy, TimelineAnchors,
+    TimelinePlayer, UndecodableBlob, UnexpectedSpend, UserEntry, UserEntryView, WatchedOutputKind,
+    MAX_COMPETITION_TAGS, MAX_SLUG_LEN, MAX_SPLIT_OUTCOMES, MAX_TAG_LEN, TICKET_RESERVATION_WINDOW,
 };
 use crate::{
-    api::routes::FinalSignatures,
-    domain::{Competition, CreateEvent, EntryStatus, Error},
+    api::routes::{FinalSignatures, PartialSignatureChunk},
+    config::{InsufficientFundingPolicy, InvoiceSettlementMode, SharedConfig},
+    domain::{
+        attestation_window_open, jittered_interval,
+        scoring::{score_entries, winning_groups, CachedObservationData, ObservationData},
+        AttestationPolls, Competition, CreateEvent, EntryStatus, Error, InFlightCompetitions,
+        WatcherKicks, WebhookNotifier,
+    },
     infra::{
-        bitcoin::{Bitcoin, ForeignUtxo, REQUIRED_CONFIRMATIONS_FOR_TIME},
-        escrow::{create_escrow_descriptor, generate_escrow_tx, get_escrow_outpoint},
+        bitcoin::{
+            broadcast_transaction, classify_broadcast_error, validate_psbt_network, Bitcoin,
+            BroadcastError, BroadcastErrorKind, ForeignUtxo, MempoolAcceptance, OutpointStatus,
+            TxChainStatus, REQUIRED_CONFIRMATIONS_FOR_TIME,
+        },
+        escrow::{
+            build_escrow_reclaim_tx, create_escrow_descriptor, find_escrow_output,
+            generate_escrow_tx, get_escrow_outpoint, EscrowReclaim, EscrowReclaimPath,
+            ESCROW_CSV_BLOCKS,
+        },
         keymeld::{
             DlcKeygenSession, DlcSubsetInfo, Keymeld, ParticipantRegistrationData,
             StoredDlcKeygenSession, SubsetDefinition,
         },
-        lightning::Ln,
-        oracle::{AddEventEntries, AddEventEntry, Error as OracleError, Event, Oracle},
+        lightning::{InvoiceState, Ln, LnBackendHealth},
+        nostr_relays::NostrRelays,
+        oracle::{
+            AddEventEntries, AddEventEntry, Error as OracleError, Event, Oracle, WeatherChoices,
+        },
     },
 };
 use anyhow::anyhow;
@@ -23,12 +58,17 @@ use bdk_wallet::{
         absolute::LockTime,
         consensus::encode::deserialize,
         hashes::{sha256, Hash},
+        secp256k1::SecretKey,
         transaction::Version,
-        Amount, FeeRate, OutPoint, Psbt, PublicKey as BitcoinPublicKey, ScriptBuf, Transaction,
-        TxIn, TxOut,
+        Address, Amount, FeeRate, OutPoint, Psbt, PublicKey as BitcoinPublicKey, ScriptBuf,
+        Transaction, TxIn, TxOut,
     },
     SignOptions,
 };
+use coordinator_core::{
+    validate_entry_secrets, validate_value_count, InvoiceMemo, Sats, SignedReceipt,
+    ValidationErrors,
+};
 use dlctix::{
     bitcoin::{
         consensus,
@@ -42,8 +82,9 @@ use dlctix::{
     convert_xonly_key,
     musig2::{AggNonce, PartialSignature, PubNonce},
     secp::{Point, Scalar},
-    ContractParameters, ContractSignatures, NonceSharingRound, Outcome, PayoutWeights, Player,
-    PlayerIndex, SigMap, SigningSession, TicketedDLC, WinCondition,
+    ContractParameters, ContractSignatures, CoordinatorPartialSignatureRound,
+    EventLockingConditions, NonceSharingRound, Outcome, PayoutWeights, Player, PlayerIndex, SigMap,
+    SigningSession, TicketedDLC, WinCondition,
 };
 use futures::TryFutureExt;
 use itertools::Itertools;
@@ -52,14 +93,13 @@ use log::{debug, error, info, warn};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use serde::Serialize;
-use std::time::Duration;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     str::FromStr,
 };
 use std::{io::Write, sync::Arc};
 use time::OffsetDateTime;
-use tokio::time::sleep;
+use tokio::{sync::Semaphore, time::sleep};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
@@ -68,9 +108,21 @@ use uuid::Uuid;
 pub struct TicketResponse {
     pub ticket_id: uuid::Uuid,
     pub payment_request: String, // Lightning HODL invoice to pay for entry
-    pub escrow_tx: Option<String>, // escrow transaction the coordinator broadcasts prior to settling the HODL invoice
-    pub payment_hash: String,      // Hex-encoded payment hash for verification
-    pub amount_sats: u64,
+    /// Escrow transaction the player broadcasts before the HODL invoice is settled. Withheld
+    /// when `escrow_broadcast_by_coordinator` is set, the invoice memo still carries its txid.
+    pub escrow_tx: Option<String>,
+    pub payment_hash: String, // Hex-encoded payment hash for verification
+    pub amount_sats: Sats,
+    /// The part of `amount_sats` that goes into the prize pool
+    pub entry_fee: Sats,
+    /// The coordinator's cut on top of the entry fee
+    pub coordinator_fee: Sats,
+    /// When `payment_request` can no longer be paid. While the ticket is still reserved a new
+    /// invoice for the same payment hash is issued at `/tickets/{ticket_id}/invoice`.
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    /// Memo on the invoice, the `InvoiceMemo` naming this coordinator, competition and ticket
+    pub invoice_memo: String,
     /// The user's keymeld user_id (same as ticket_id) - used for keymeld registration
     pub keymeld_user_id: uuid::Uuid,
     /// Keymeld gateway URL for client registration
@@ -82,9 +134,28 @@ pub struct TicketResponse {
     pub keymeld_enclave_public_key: Option<String>,
 }
 
+/// Where the expiry (refund) transaction of a competition stands on chain
+#[derive(Debug, Serialize)]
+pub struct ExpiryTxStatus {
+    pub txid: String,
+    pub confirmations: u32,
+    /// Fee rate the pre-signed expiry transaction pays, in sat/vB
+    pub fee_rate_sat_vb: u64,
+    /// Current estimate to confirm within the next block, in sat/vB
+    pub estimated_fee_rate_sat_vb: u64,
+    /// Whether the transaction was handed to the backend again during this check
+    pub rebroadcast: bool,
+}
+
+impl ExpiryTxStatus {
+    pub fn is_underpaying(&self) -> bool {
+        self.confirmations == 0 && self.fee_rate_sat_vb < self.estimated_fee_rate_sat_vb
+    }
+}
+
 pub struct CompetitionWatcher {
     coordinator: Arc<Coordinator>,
-    sync_interval: Duration,
+    settings: SharedConfig,
     cancel_token: CancellationToken,
 }
 
@@ -92,11 +163,11 @@ impl CompetitionWatcher {
     pub fn new(
         coordinator: Arc<Coordinator>,
         cancel_token: CancellationToken,
-        sync_interval: Duration,
+        settings: SharedConfig,
     ) -> Self {
         Self {
             coordinator,
-            sync_interval,
+            settings,
             cancel_token,
         }
     }
@@ -110,7 +181,11 @@ impl CompetitionWatcher {
                 break;
             }
 
-            match self.coordinator.competition_handler().await {
+            match self
+                .coordinator
+                .competition_handler_until(&self.cancel_token)
+                .await
+            {
                 Ok(_) => {
                     info!("Competition sync completed successfully");
                 }
@@ -119,8 +194,17 @@ impl CompetitionWatcher {
                 }
             }
 
+            let interval = jittered_interval(
+                self.settings.sync_interval(),
+                self.settings.watcher_jitter_percent(),
+                &mut rand::rng(),
+            );
             tokio::select! {
-                _ = sleep(self.sync_interval) => continue,
+                _ = sleep(interval) => continue,
+                _ = self.coordinator.watcher_kicks().competitions_kicked() => {
+                    debug!("Competition sync watcher kicked");
+                    continue;
+                }
                 _ = self.cancel_token.cancelled() => {
                     info!("Competition sync watcher cancelled during sleep");
                     break;
@@ -132,20 +216,79 @@ impl CompetitionWatcher {
     }
 }
 
+/// Asks the oracle for the attestation of competitions inside their attestation window between
+/// competition watcher runs, and kicks the watcher as soon as one is available
+pub struct AttestationPoller {
+    coordinator: Arc<Coordinator>,
+    settings: SharedConfig,
+    cancel_token: CancellationToken,
+}
+
+impl AttestationPoller {
+    pub fn new(
+        coordinator: Arc<Coordinator>,
+        cancel_token: CancellationToken,
+        settings: SharedConfig,
+    ) -> Self {
+        Self {
+            coordinator,
+            settings,
+            cancel_token,
+        }
+    }
+
+    pub async fn watch(&self) -> Result<(), anyhow::Error> {
+        info!("Starting attestation poller");
+
+        loop {
+            let poll_interval = self.settings.attestation_poll_interval();
+            // 0 leaves polling to the competition watcher's own runs
+            let interval = if poll_interval.is_zero() {
+                self.settings.sync_interval()
+            } else {
+                self.coordinator.poll_attestations(poll_interval).await;
+                poll_interval
+            };
+            let interval = jittered_interval(
+                interval,
+                self.settings.watcher_jitter_percent(),
+                &mut rand::rng(),
+            );
+            tokio::select! {
+                _ = sleep(interval) => continue,
+                _ = self.cancel_token.cancelled() => {
+                    info!("Attestation poller cancelled during sleep");
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub struct Coordinator {
     oracle_client: Arc<dyn Oracle>,
     pub competition_store: Arc<CompetitionStore>,
     pub bitcoin: Arc<dyn Bitcoin>,
     ln: Arc<dyn Ln>,
+    relays: Arc<dyn NostrRelays>,
     keymeld: Arc<dyn Keymeld>,
     keymeld_gateway_url: Option<String>,
-    private_key: Scalar,
-    public_key: Point,
+    keys: CoordinatorKeyring,
     relative_locktime_block_delta: u32,
-    required_confirmations: u32,
     name: String,
     escrow_enabled: bool,
-    invoice_settlement_confirmations: u32,
+    invoice_settlement_mode: InvoiceSettlementMode,
+    settings: SharedConfig,
+    webhooks: WebhookNotifier,
+    kicks: WatcherKicks,
+    in_flight: InFlightCompetitions,
+    attestation_polls: AttestationPolls,
+    /// Slots for building contracts, see `build_coordinator_nonces`
+    contract_builds: Arc<Semaphore>,
+    /// Kept from aggregating nonces for signing the contract, see `coordinator_signing_session`
+    signing_sessions: SigningSessionCache,
 }
 
 impl Coordinator {
@@ -155,36 +298,97 @@ impl Coordinator {
         competition_store: CompetitionStore,
         bitcoin: Arc<dyn Bitcoin>,
         ln: Arc<dyn Ln>,
+        relays: Arc<dyn NostrRelays>,
         keymeld: Arc<dyn Keymeld>,
         keymeld_gateway_url: Option<String>,
         relative_locktime_block_delta: u32,
-        required_confirmations: u32,
         name: String,
         escrow_enabled: bool,
-        invoice_settlement_confirmations: u32,
+        invoice_settlement_mode: InvoiceSettlementMode,
+        settings: SharedConfig,
+        webhooks: WebhookNotifier,
     ) -> Result<Self, anyhow::Error> {
-        let private_key = bitcoin.get_derived_private_key().await?;
-        let public_key = private_key.base_point_mul();
+        if invoice_settlement_mode == InvoiceSettlementMode::Standard
+            && settings.invoice_settlement_confirmations() == 0
+        {
+            warn!(
+                "!!! invoice_settlement_confirmations is 0: hold invoices will be settled as soon as \
+                 the funding transaction is broadcast. If it never confirms or is dropped by a \
+                 reorg, players have already paid for a contract that is not funded. Set \
+                 invoice_settlement_mode = \"safe\" to wait for best-chain confirmations !!!"
+            );
+        }
+
+        let keys = CoordinatorKeyring::load(
+            bitcoin.get_derived_private_key().await?,
+            &settings.signing_keys(),
+        )?;
+        info!(
+            "Coordinator keys {:?}, new competitions use {}",
+            keys.ids(),
+            keys.newest_id()
+        );
 
         let coordinator = Self {
             oracle_client,
             competition_store: Arc::new(competition_store),
             bitcoin,
             ln,
+            relays,
             keymeld,
             keymeld_gateway_url,
-            private_key,
-            public_key,
+            keys,
             relative_locktime_block_delta,
-            required_confirmations,
             name,
             escrow_enabled,
-            invoice_settlement_confirmations,
+            invoice_settlement_mode,
+            settings,
+            webhooks,
+            kicks: WatcherKicks::default(),
+            in_flight: InFlightCompetitions::default(),
+            attestation_polls: AttestationPolls::default(),
+            contract_builds: Arc::new(Semaphore::new(settings.max_concurrent_contract_builds())),
+            signing_sessions: SigningSessionCache::new(settings.signing_session_cache_size()),
         };
         coordinator.validate_coordinator_metadata().await?;
         Ok(coordinator)
     }
 
+    /// Handles for waking the polling watchers after a mutation
+    pub fn watcher_kicks(&self) -> &WatcherKicks {
+        &self.kicks
+    }
+
+    /// Competitions the handler is processing right now
+    pub fn in_flight(&self) -> &InFlightCompetitions {
+        &self.in_flight
+    }
+
+    /// Ask the oracle for the attestation of every competition inside its attestation window that
+    /// is due a poll, and kick the competition watcher to pick up any that are attested
+    pub async fn poll_attestations(&self, interval: std::time::Duration) {
+        let due = self
+            .attestation_polls
+            .due(OffsetDateTime::now_utc(), interval);
+        let mut attested = false;
+        for competition_id in due {
+            match self.oracle_client.get_event(&competition_id).await {
+                Ok(event) if event.attestation.is_some() => {
+                    info!("Oracle attested competition {}", competition_id);
+                    attested = true;
+                }
+                Ok(_) => debug!("Competition {} not attested yet", competition_id),
+                Err(e) => warn!(
+                    "Failed to poll the oracle for competition {}: {}",
+                    competition_id, e
+                ),
+            }
+        }
+        if attested {
+            self.kicks.kick_competitions();
+        }
+    }
+
     /// Check if escrow transactions are enabled
     pub fn is_escrow_enabled(&self) -> bool {
         self.escrow_enabled
@@ -196,12 +400,18 @@ impl Coordinator {
     }
 
     /// Settle all hold invoices for a competition.
-    /// This releases the held funds to the coordinator.
+    /// This releases the held funds to the coordinator. A ticket failing to settle doesn't stop
+    /// the others, its error is stored on the ticket and it's retried on the next call.
     pub async fn settle_competition_invoices(
         &self,
         competition_id: Uuid,
-    ) -> Result<(), anyhow::Error> {
+    ) -> Result<SettlementProgress, anyhow::Error> {
         let tickets = self.competition_store.get_tickets(competition_id).await?;
+        let invoice_amount = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?
+            .calculate_invoice_amount();
 
         info!(
             "Settling {} hold invoices for competition {}",
@@ -209,6 +419,7 @@ impl Coordinator {
             competition_id
         );
 
+        let mut progress = SettlementProgress::default();
         for (_ticket_id, ticket) in tickets {
             // Only settle tickets that have been paid (invoice accepted)
             if ticket.paid_at.is_none() {
@@ -216,22 +427,39 @@ impl Coordinator {
                 continue;
             }
 
+            // Comped tickets never had a hold invoice to settle
+            if ticket.comped_at.is_some() {
+                continue;
+            }
+            progress.total_invoices += 1;
+
             // Skip already settled tickets
             if ticket.settled_at.is_some() {
                 debug!("Skipping ticket {} - already settled", ticket.id);
+                progress.settled_invoices += 1;
                 continue;
             }
 
             match self
                 .ln
-                .settle_hold_invoice(ticket.encrypted_preimage.clone())
+                .settle_hold_invoice(
+                    ticket.encrypted_preimage.clone(),
+                    ticket.ln_backend_id.as_deref(),
+                )
                 .await
             {
                 Ok(_) => {
                     info!("Settled hold invoice for ticket {}", ticket.id);
+                    progress.settled_invoices += 1;
                     // Mark ticket as settled
-                    if let Err(e) = self.competition_store.mark_ticket_settled(ticket.id).await {
-                        error!("Failed to mark ticket {} as settled: {}", ticket.id, e);
+                    match self
+                        .competition_store
+                        .mark_ticket_settled(ticket.id, invoice_amount.to_sat())
+                        .await
+                    {
+                        Ok(true) => self.issue_payment_receipt(&ticket, invoice_amount).await,
+                        Ok(false) => {}
+                        Err(e) => error!("Failed to mark ticket {} as settled: {}", ticket.id, e),
                     }
                 }
                 Err(e) => {
@@ -239,11 +467,31 @@ impl Coordinator {
                         "Failed to settle hold invoice for ticket {}: {}",
                         ticket.id, e
                     );
+                    progress.failed_invoices += 1;
+                    if let Err(e) = self
+                        .competition_store
+                        .record_ticket_settlement_failure(ticket.id, &e.to_string())
+                        .await
+                    {
+                        error!(
+                            "Failed to record settlement failure for ticket {}: {}",
+                            ticket.id, e
+                        );
+                    }
                 }
             }
         }
 
-        Ok(())
+        Ok(progress)
+    }
+
+    /// Confirmations the funding transaction needs before the competition's hold invoices are
+    /// settled, the competition's own or the coordinator's
+    fn invoice_settlement_confirmations(&self, competition: &Competition) -> u32 {
+        competition
+            .event_submission
+            .invoice_settlement_confirmations
+            .unwrap_or_else(|| self.settings.invoice_settlement_confirmations())
     }
 
     /// Check if all participants have registered with keymeld for a competition
@@ -262,8 +510,7 @@ impl Coordinator {
             })?;
 
         // Decrypt the session secret to restore the session
-        let session_secret =
-            self.decrypt_session_secret(&stored_session.encrypted_session_secret)?;
+        let session_secret = self.decrypt_session_secret(&stored_session)?;
         let session = stored_session.to_session(session_secret);
 
         let status = self
@@ -299,8 +546,7 @@ impl Coordinator {
             })?;
 
         // Decrypt session secret to restore session
-        let session_secret =
-            self.decrypt_session_secret(&stored_session.encrypted_session_secret)?;
+        let session_secret = self.decrypt_session_secret(&stored_session)?;
         let session = stored_session.to_session(session_secret);
 
         // Wait for keygen to complete and get aggregate key
@@ -330,70 +576,202 @@ impl Coordinator {
         Ok(())
     }
 
-    /// Store a Keymeld session for a competition (for use after keygen completes)
-    /// The session secret is encrypted to the coordinator's own nostr pubkey before storage
-    pub async fn store_keymeld_session(
+    /// Remove an entry's registration from its competition's keygen session and clear the
+    /// registration data it submitted, so one bad registration (e.g. a key encrypted to the
+    /// wrong enclave) doesn't keep keygen from ever completing. The player can then register
+    /// again with `reregister_keymeld_participant`.
+    pub async fn reset_keymeld_registration(
         &self,
         competition_id: Uuid,
-        session: DlcKeygenSession,
-    ) -> Result<(), Error> {
-        use nostr_sdk::nips::nip44;
-        use nostr_sdk::prelude::{Keys, SecretKey};
+        entry_id: Uuid,
+    ) -> Result<UserEntry, Error> {
+        let (entry, session) = self
+            .keymeld_registration_target(competition_id, entry_id)
+            .await?;
+        let user_id = UserId::from(entry.ticket_id);
 
-        // Encrypt session secret to our own pubkey for secure storage
-        let coordinator_secret_key = SecretKey::from_slice(&self.private_key.serialize())
-            .map_err(|e| Error::BadRequest(format!("Failed to create secret key: {}", e)))?;
+        self.keymeld
+            .remove_participant(&session, user_id.clone())
+            .await
+            .map_err(|e| {
+                Error::BadRequest(format!(
+                    "Failed to remove keymeld registration for entry {}: {}",
+                    entry_id, e
+                ))
+            })?;
 
-        let coordinator_keys = Keys::new(coordinator_secret_key);
+        self.competition_store
+            .set_entry_keymeld_registration(entry_id, None, None)
+            .await
+            .map_err(Error::DbError)?;
 
-        let encrypted_session_secret = nip44::encrypt(
-            coordinator_keys.secret_key(),
-            &coordinator_keys.public_key(),
-            hex::encode(session.session_secret),
-            nip44::Version::V2,
-        )
-        .map_err(|e| Error::BadRequest(format!("NIP-44 encryption failed: {}", e)))?;
+        info!(
+            "Removed keymeld registration of entry {} (user {}) from keygen session {} for competition {}, waiting for it to register again",
+            entry_id, user_id, session.session_id, competition_id
+        );
 
-        let stored_session =
-            StoredDlcKeygenSession::from_session(session, encrypted_session_secret);
+        Ok(UserEntry {
+            encrypted_keymeld_private_key: None,
+            keymeld_auth_pubkey: None,
+            ..entry
+        })
+    }
+
+    /// Register a player's entry with the competition's keygen session again, after an operator
+    /// removed its previous registration
+    pub async fn reregister_keymeld_participant(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+        entry_id: Uuid,
+        registration: KeymeldRegistration,
+    ) -> Result<(), Error> {
+        let (entry, session) = self
+            .keymeld_registration_target(competition_id, entry_id)
+            .await?;
+        if entry.pubkey != pubkey {
+            return Err(Error::NotFound(format!("Entry {} not found", entry_id)));
+        }
+        if entry.encrypted_keymeld_private_key.is_some() {
+            return Err(Error::BadRequest(format!(
+                "Entry {} is still registered with keymeld, an operator has to remove its registration first",
+                entry_id
+            )));
+        }
+
+        let user_id = UserId::from(entry.ticket_id);
+        let registration_data = ParticipantRegistrationData {
+            encrypted_private_key: registration.encrypted_keymeld_private_key.clone(),
+            public_key: entry.ephemeral_pubkey.clone(),
+            auth_pubkey: registration.keymeld_auth_pubkey.clone(),
+        };
+        self.keymeld
+            .register_participant(&session, user_id.clone(), &registration_data)
+            .await
+            .map_err(|e| {
+                Error::BadRequest(format!(
+                    "Failed to register entry {} with keymeld: {}",
+                    entry_id, e
+                ))
+            })?;
 
         self.competition_store
-            .store_keymeld_session(competition_id, &stored_session)
+            .set_entry_keymeld_registration(
+                entry_id,
+                Some(registration.encrypted_keymeld_private_key),
+                Some(registration.keymeld_auth_pubkey),
+            )
             .await
             .map_err(Error::DbError)?;
+
+        info!(
+            "Entry {} (user {}) registered again for keygen session {} of competition {}",
+            entry_id, user_id, session.session_id, competition_id
+        );
+
         Ok(())
     }
 
-    /// Decrypt a stored keymeld session secret
-    fn decrypt_session_secret(&self, encrypted: &str) -> Result<[u8; 32], Error> {
-        use nostr_sdk::nips::nip44;
-        use nostr_sdk::prelude::{Keys, SecretKey};
+    /// The entry and keygen session a registration change applies to, only while the
+    /// competition's keygen hasn't completed
+    async fn keymeld_registration_target(
+        &self,
+        competition_id: Uuid,
+        entry_id: Uuid,
+    ) -> Result<(UserEntry, DlcKeygenSession), Error> {
+        if !self.is_keymeld_enabled() {
+            return Err(Error::BadRequest("Keymeld is not enabled".into()));
+        }
 
-        let coordinator_secret_key = SecretKey::from_slice(&self.private_key.serialize())
-            .map_err(|e| Error::BadRequest(format!("Failed to create secret key: {}", e)))?;
+        let entry = self
+            .get_entry_by_id(entry_id)
+            .await?
+            .filter(|entry| entry.event_id == competition_id)
+            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
 
-        let coordinator_keys = Keys::new(coordinator_secret_key);
+        let competition = self.get_competition(competition_id).await?;
+        if competition.keymeld_keygen_completed_at.is_some() {
+            return Err(Error::BadRequest(format!(
+                "Keygen for competition {} has already completed",
+                competition_id
+            )));
+        }
 
-        let decrypted_hex = nip44::decrypt(
-            coordinator_keys.secret_key(),
-            &coordinator_keys.public_key(),
-            encrypted,
-        )
-        .map_err(|e| Error::BadRequest(format!("NIP-44 decryption failed: {}", e)))?;
+        let stored_session = self
+            .competition_store
+            .get_keymeld_session(competition_id)
+            .await
+            .map_err(Error::DbError)?
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "Keymeld session not found for competition {}",
+                    competition_id
+                ))
+            })?;
+        let session_secret = self.decrypt_session_secret(&stored_session)?;
 
-        let secret_bytes = hex::decode(&decrypted_hex)
-            .map_err(|e| Error::BadRequest(format!("Invalid session secret hex: {}", e)))?;
+        Ok((entry, stored_session.to_session(session_secret)))
+    }
 
-        secret_bytes
-            .try_into()
-            .map_err(|_| Error::BadRequest("Session secret must be 32 bytes".to_string()))
+    /// Store a Keymeld session for a competition (for use after keygen completes)
+    /// The session secret is encrypted to the coordinator's newest key before storage
+    pub async fn store_keymeld_session(
+        &self,
+        competition_id: Uuid,
+        session: DlcKeygenSession,
+    ) -> Result<(), Error> {
+        let (key_id, encrypted_session_secret) = self
+            .keys
+            .encrypt_to_self(&hex::encode(session.session_secret))?;
+
+        let stored_session =
+            StoredDlcKeygenSession::from_session(session, encrypted_session_secret)
+                .with_encryption_key_id(key_id);
+
+        self.competition_store
+            .store_keymeld_session(competition_id, &stored_session)
+            .await
+            .map_err(Error::DbError)?;
+        Ok(())
+    }
+
+    /// Decrypt a stored keymeld session secret with the key it was encrypted to
+    fn decrypt_session_secret(
+        &self,
+        stored_session: &StoredDlcKeygenSession,
+    ) -> Result<[u8; 32], Error> {
+        decrypt_session_secret(&self.keys, stored_session)
+    }
+
+    /// The key a competition's contract is signed with, for its whole life
+    fn competition_key(&self, competition: &Competition) -> Result<Scalar, KeyringError> {
+        self.keys.get(competition.coordinator_key_id.as_deref())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
+    /// The newest key, receipts and backup DMs are signed with it
     pub fn public_key(&self) -> String {
-        let (xonly, _) = self.public_key.into();
+        let (xonly, _) = self.keys.newest().base_point_mul().into();
         hex::encode(xonly.serialize())
     }
 
+    /// Hex x-only pubkeys of every coordinator key, oldest first. Receipts are signed with the
+    /// newest, the older ones keep receipts issued before a key rotation verifiable.
+    pub fn public_keys(&self) -> Vec<String> {
+        self.keys
+            .ids()
+            .into_iter()
+            .filter_map(|id| self.keys.public_key(Some(id)).ok())
+            .map(|point| {
+                let (xonly, _) = point.into();
+                hex::encode(xonly.serialize())
+            })
+            .collect()
+    }
+
     pub async fn ping(&self) -> Result<(), Error> {
         self.competition_store.ping().await.map_err(Error::DbError)
     }
@@ -405,67 +783,134 @@ impl Coordinator {
             .map_err(Error::DbError)
     }
 
+    /// Ping every configured lightning backend
+    pub async fn ln_health(&self) -> Vec<LnBackendHealth> {
+        self.ln.backend_health().await
+    }
+
     pub async fn competition_handler(&self) -> Result<(), anyhow::Error> {
+        self.competition_handler_until(&CancellationToken::new())
+            .await
+    }
+
+    /// Process competitions until `shutdown` is cancelled. A competition already picked up is
+    /// saved before the pass stops, the rest wait for the next start.
+    pub async fn competition_handler_until(
+        &self,
+        shutdown: &CancellationToken,
+    ) -> Result<(), anyhow::Error> {
+        // Auto-expire failed competitions after 1 hour so they stop showing up as active
+        const FAILED_EXPIRY_HOURS: i64 = 1;
+        match self
+            .competition_store
+            .cancel_failed_competitions(time::Duration::hours(FAILED_EXPIRY_HOURS))
+            .await
+        {
+            Ok(cancelled) => {
+                for competition_id in cancelled {
+                    info!(
+                        "Auto-cancelled failed competition {} (failed over {}h ago)",
+                        competition_id, FAILED_EXPIRY_HOURS
+                    );
+                    self.notify_transition(competition_id, "failed", "cancelled");
+                }
+            }
+            Err(e) => error!("Failed to cancel expired-failed competitions: {}", e),
+        }
+
+        // Cancelled competitions aren't processed below, their no-show escrows are swept here
+        if let Err(e) = self.reclaim_no_show_escrows().await {
+            error!("Failed to reclaim no-show escrows: {}", e);
+        }
+
+        // Failed, completed and cancelled competitions are filtered out in SQL
         let competitions: Vec<Competition> =
-            self.competition_store.get_competitions(true, true).await?;
+            self.competition_store.get_competitions_to_process().await?;
+        let awaiting_attestation: Vec<Uuid> = competitions
+            .iter()
+            .filter(|competition| competition.get_state() == CompetitionState::AwaitingAttestation)
+            .map(|competition| competition.id)
+            .collect();
+        self.attestation_polls.retain(&awaiting_attestation);
+
+        let max_lifetime = self.settings.max_competition_lifetime();
+        let total = competitions.len();
+        for (processed, mut competition) in competitions.into_iter().enumerate() {
+            if shutdown.is_cancelled() {
+                info!(
+                    "Shutting down, leaving {} of {} competitions for the next start",
+                    total - processed,
+                    total
+                );
+                break;
+            }
+            let _in_flight = self
+                .in_flight
+                .start(competition.id, competition.get_state().to_string());
 
-        for mut competition in competitions {
             let mut processed_states = 0;
             const MAX_CONSECUTIVE_STATES: usize = 10;
 
-            if competition.skip_competition() {
-                // Auto-expire failed competitions after 1 hour so they stop
-                // polluting every tick's log output and DB query results.
-                const FAILED_EXPIRY_HOURS: i64 = 1;
-                if competition.is_failed() && competition.cancelled_at.is_none() {
-                    if let Some(failed_at) = competition.failed_at {
-                        let age = OffsetDateTime::now_utc() - failed_at;
-                        if age.whole_hours() >= FAILED_EXPIRY_HOURS {
-                            competition.cancelled_at = Some(OffsetDateTime::now_utc());
-                            if let Err(e) = self
-                                .competition_store
-                                .update_competitions(vec![competition.clone()])
-                                .await
-                            {
-                                error!(
-                                    "Failed to cancel expired-failed competition {}: {}",
-                                    competition.id, e
-                                );
-                            } else {
-                                info!(
-                                    "Auto-cancelled failed competition {} (failed {}h ago)",
-                                    competition.id,
-                                    age.whole_hours()
-                                );
-                            }
-                            continue;
-                        }
-                    }
+            if let Some(max_lifetime) = max_lifetime {
+                if competition.exceeds_max_lifetime(max_lifetime, OffsetDateTime::now_utc()) {
+                    self.fail_runaway_competition(competition, max_lifetime)
+                        .await;
+                    continue;
                 }
-                debug!(
-                    "Skipping competition {} in state {}",
-                    competition.id,
-                    competition.get_state()
-                );
-                continue;
             }
 
             if competition.is_expired() && competition.cancelled_at.is_none() {
                 competition.cancelled_at = Some(OffsetDateTime::now_utc());
                 if let Err(e) = self
                     .competition_store
-                    .update_competitions(vec![competition.clone()])
+                    .update_competitions(std::slice::from_mut(&mut competition))
                     .await
                 {
                     error!(
                         "Failed to save competition {} after cancellation: {}",
                         competition.id, e
                     );
+                } else {
+                    self.webhooks.notify(
+                        competition.id,
+                        "cancelled",
+                        format!("Competition {} expired and was cancelled", competition.id),
+                    );
                 }
                 info!("Cancelled expired competition {}", competition.id);
                 continue;
             }
 
+            // Resolve outputs spent from under the coordinator before broadcasting anything else
+            let state_before_spends = competition.get_state().to_string();
+            match self.check_unexpected_spends(&mut competition).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    if let Err(e) = self
+                        .competition_store
+                        .update_competitions(std::slice::from_mut(&mut competition))
+                        .await
+                    {
+                        error!(
+                            "Failed to save competition {} after an unexpected spend: {}",
+                            competition.id, e
+                        );
+                        continue;
+                    }
+                    let state = competition.get_state().to_string();
+                    if state != state_before_spends {
+                        self.notify_transition(competition.id, &state_before_spends, &state);
+                    }
+                    if competition.is_failed() {
+                        continue;
+                    }
+                }
+                Err(e) => warn!(
+                    "Failed to check competition {} outputs for unexpected spends: {}",
+                    competition.id, e
+                ),
+            }
+
             loop {
                 let status: CompetitionStatus = competition.clone().into();
                 let current_state_name = status.state_name();
@@ -474,7 +919,7 @@ impl Coordinator {
                 let new_state_name = new_status.state_name();
                 let is_immediate = new_status.is_immediate_transition();
 
-                let updated_competition = new_status.into_competition();
+                let mut updated_competition = new_status.into_competition();
 
                 info!(
                     "Competition {} transitioned {} -> {}",
@@ -484,16 +929,30 @@ impl Coordinator {
                 if new_state_name != current_state_name {
                     processed_states += 1;
 
-                    if is_immediate && processed_states < MAX_CONSECUTIVE_STATES {
+                    // Each transition is saved before the next, so shutdown can stop the chain
+                    if is_immediate
+                        && processed_states < MAX_CONSECUTIVE_STATES
+                        && !shutdown.is_cancelled()
+                    {
                         if let Err(e) = self
                             .competition_store
-                            .update_competitions(vec![updated_competition.clone()])
+                            .update_competitions(std::slice::from_mut(&mut updated_competition))
                             .await
                         {
                             error!(
                                 "Failed to save competition {} in state {}: {}",
                                 competition.id, new_state_name, e
                             );
+                            // Saved elsewhere meanwhile, the next pass picks up the newer state
+                            if e.is_conflict() {
+                                break;
+                            }
+                        } else {
+                            self.notify_transition(
+                                competition.id,
+                                current_state_name,
+                                new_state_name,
+                            );
                         }
                         competition = updated_competition;
                         continue;
@@ -502,13 +961,15 @@ impl Coordinator {
 
                 if let Err(e) = self
                     .competition_store
-                    .update_competitions(vec![updated_competition])
+                    .update_competitions(std::slice::from_mut(&mut updated_competition))
                     .await
                 {
                     error!(
                         "Failed to save competition {} in state {}: {}",
                         competition.id, new_state_name, e
                     );
+                } else if new_state_name != current_state_name {
+                    self.notify_transition(competition.id, current_state_name, new_state_name);
                 }
                 break;
             }
@@ -517,10 +978,131 @@ impl Coordinator {
         Ok(())
     }
 
-    pub async fn process_status(&self, status: CompetitionStatus) -> CompetitionStatus {
-        use super::states::*;
+    /// Paid entries of a competition in contract player order, assigning and persisting the
+    /// order the first time the contract is built
+    async fn get_contract_entries(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<Vec<UserEntry>, anyhow::Error> {
+        let mut entries = self
+            .competition_store
+            .get_competition_entries(competition_id, vec![EntryStatus::Paid])
+            .await?;
+        let assigned = order_contract_entries(&mut entries)
+            .map_err(|e| anyhow!("competition {}: {}", competition_id, e))?;
 
-        let competition_id = status.competition_id();
+        // Signing with keymeld relies on the contract order matching its subset definitions
+        if let Some(player_order) = self
+            .competition_store
+            .get_player_order(competition_id)
+            .await?
+        {
+            verify_player_order(&player_order, &entries)
+                .map_err(|e| anyhow!("competition {}: {}", competition_id, e))?;
+            if player_order
+                .iter()
+                .any(|slot| slot.ephemeral_pubkey.is_none())
+            {
+                self.competition_store
+                    .set_player_order(competition_id, &player_order_from_entries(&entries))
+                    .await?;
+            }
+        }
+        if !assigned.is_empty() {
+            info!(
+                "Assigned player indices to {} entries of competition {}",
+                assigned.len(),
+                competition_id
+            );
+            self.competition_store.set_player_indices(assigned).await?;
+        }
+        Ok(entries)
+    }
+
+    /// Backstop for unfunded competitions stuck where none of the stage timeouts apply: fail it
+    /// and release its players' hold invoices
+    async fn fail_runaway_competition(
+        &self,
+        mut competition: Competition,
+        max_lifetime: time::Duration,
+    ) {
+        let previous_state = competition.get_state().to_string();
+        let reason = format!(
+            "still {} {} hours after it was created at {}, the limit is {} hours",
+            previous_state,
+            (OffsetDateTime::now_utc() - competition.created_at).whole_hours(),
+            competition.created_at,
+            max_lifetime.whole_hours()
+        );
+        error!(
+            "!!! Competition {} exceeded its max lifetime and is being failed: {} !!!",
+            competition.id, reason
+        );
+
+        if let Err(e) = self.recover_runaway_funds(&competition).await {
+            error!(
+                "!!! Competition {} couldn't start refunding its players, check them by hand: {} !!!",
+                competition.id, e
+            );
+        }
+
+        competition.record_error(CompetitionError::MaxLifetimeExceeded(reason));
+        competition.failed_at = Some(OffsetDateTime::now_utc());
+        if let Err(e) = self
+            .competition_store
+            .update_competitions(std::slice::from_mut(&mut competition))
+            .await
+        {
+            error!(
+                "Failed to save competition {} after it exceeded its max lifetime: {}",
+                competition.id, e
+            );
+            return;
+        }
+        self.notify_transition(competition.id, &previous_state, "failed");
+    }
+
+    /// Before funding the players' money is still held by their hold invoices, which are
+    /// cancelled
+    async fn recover_runaway_funds(&self, competition: &Competition) -> Result<(), anyhow::Error> {
+        let tickets = self.competition_store.get_tickets(competition.id).await?;
+        for ticket in tickets.values().filter(|ticket| {
+            ticket.paid_at.is_some() && ticket.settled_at.is_none() && ticket.comped_at.is_none()
+        }) {
+            match self
+                .ln
+                .cancel_hold_invoice(ticket.hash.clone(), ticket.ln_backend_id.as_deref())
+                .await
+            {
+                Ok(_) => warn!(
+                    "Competition {} cancelled the hold invoice of ticket {} to refund its player",
+                    competition.id, ticket.id
+                ),
+                Err(e) => error!(
+                    "Competition {} failed to cancel the hold invoice of ticket {}: {}",
+                    competition.id, ticket.id, e
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit a webhook for a state change that has been persisted
+    fn notify_transition(&self, competition_id: Uuid, from: &str, to: &str) {
+        self.webhooks.notify(
+            competition_id,
+            to,
+            format!(
+                "Competition {} moved from {} to {}",
+                competition_id, from, to
+            ),
+        );
+    }
+
+    pub async fn process_status(&self, status: CompetitionStatus) -> CompetitionStatus {
+        use super::states::*;
+
+        let competition_id = status.competition_id();
         let state_name = status.state_name();
         info!(
             "Processing competition {} in state {}",
@@ -569,15 +1151,20 @@ impl Coordinator {
                             CompetitionStatus::AwaitingEscrow(state)
                         }
                     }
+                    Err(e) if e.downcast_ref::<EscrowDoubleSpent>().is_some() => {
+                        // Only returned when the remaining entries can't make a contract
+                        error!("Competition {} escrow double spent: {}", competition_id, e);
+                        CompetitionStatus::AwaitingEscrow(state)
+                            .fail(CompetitionError::EscrowDoubleSpent(e.to_string()))
+                    }
                     Err(e) => {
                         error!(
                             "Competition {} failed to check escrow: {}",
                             competition_id, e
                         );
-                        state
-                            .competition_mut()
-                            .errors
-                            .push(CompetitionError::FailedEscrowConfirmation(e.to_string()));
+                        state.competition_mut().record_error(
+                            CompetitionError::FailedEscrowConfirmation(e.to_string()),
+                        );
                         if state.competition().should_abort() {
                             CompetitionStatus::AwaitingEscrow(state)
                                 .fail(CompetitionError::FailedEscrowConfirmation(e.to_string()))
@@ -615,9 +1202,28 @@ impl Coordinator {
             CompetitionStatus::EventCreated(mut state) => {
                 match self.submit_entries_to_oracle(state.competition_mut()).await {
                     Ok(_) => state.entries_submitted(),
+                    // The entries that landed are kept, the next run sends the rest
+                    Err(e)
+                        if e.downcast_ref::<OracleError>()
+                            .is_some_and(OracleError::is_transient) =>
+                    {
+                        warn!(
+                            "Competition {} failed to submit entries, will retry: {:#}",
+                            competition_id, e
+                        );
+                        state.competition_mut().record_error(
+                            CompetitionError::FailedSubmitEntries(format!("{:#}", e)),
+                        );
+                        if state.competition().should_abort() {
+                            CompetitionStatus::EventCreated(state)
+                                .fail(CompetitionError::FailedSubmitEntries(format!("{:#}", e)))
+                        } else {
+                            CompetitionStatus::EventCreated(state)
+                        }
+                    }
                     Err(e) => {
                         error!(
-                            "Competition {} failed to submit entries: {}",
+                            "Competition {} failed to submit entries: {:#}",
                             competition_id, e
                         );
                         CompetitionStatus::EventCreated(state)
@@ -640,6 +1246,22 @@ impl Coordinator {
                             CompetitionStatus::EntriesSubmitted(state)
                         }
                     }
+                    Err(e) if e.downcast_ref::<FundingShortfall>().is_some() => {
+                        match self.settings.insufficient_funding_policy() {
+                            InsufficientFundingPolicy::Fail => {
+                                error!("Competition {} can't be funded: {}", competition_id, e);
+                                CompetitionStatus::EntriesSubmitted(state)
+                                    .fail(CompetitionError::InsufficientFunds(e.to_string()))
+                            }
+                            InsufficientFundingPolicy::Wait => {
+                                warn!(
+                                    "Competition {} waits for the wallet to be topped up: {}",
+                                    competition_id, e
+                                );
+                                CompetitionStatus::EntriesSubmitted(state)
+                            }
+                        }
+                    }
                     Err(e) => {
                         error!(
                             "Competition {} failed to create contract: {}",
@@ -666,18 +1288,11 @@ impl Coordinator {
                             );
                             match self.complete_keymeld_keygen(state.competition_mut()).await {
                                 Ok(_) => {
-                                    // Chain immediately to AwaitingSignatures processing
-                                    let awaiting_sigs = CompetitionStatus::AwaitingSignatures(
-                                        AwaitingSignatures::from_competition(
-                                            state.into_competition(),
-                                        ),
-                                    );
                                     // Persist state before chaining to ensure we don't lose progress
+                                    let mut competition = state.into_competition();
                                     if let Err(e) = self
                                         .competition_store
-                                        .update_competitions(vec![awaiting_sigs
-                                            .clone()
-                                            .into_competition()])
+                                        .update_competitions(std::slice::from_mut(&mut competition))
                                         .await
                                     {
                                         error!(
@@ -685,6 +1300,10 @@ impl Coordinator {
                                             competition_id, e
                                         );
                                     }
+                                    // Chain immediately to AwaitingSignatures processing
+                                    let awaiting_sigs = CompetitionStatus::AwaitingSignatures(
+                                        AwaitingSignatures::from_competition(competition),
+                                    );
                                     // Use Box::pin to allow recursive async call
                                     return Box::pin(self.process_status(awaiting_sigs)).await;
                                 }
@@ -745,6 +1364,20 @@ impl Coordinator {
                     }
                 } else {
                     // Legacy MuSig2 flow
+                    if state.competition().nonce_seed_moved() {
+                        // Signing with nonces seeded by an outpoint the funding psbt no longer
+                        // spends to can only fail, start the round over instead
+                        if let Err(e) = self.restart_nonce_round(state.competition_mut()).await {
+                            error!(
+                                "Competition {} failed to restart its nonce round: {}",
+                                competition_id, e
+                            );
+                            return CompetitionStatus::AwaitingSignatures(state)
+                                .fail(CompetitionError::FailedNonceAggregation(e.to_string()));
+                        }
+                        return CompetitionStatus::AwaitingSignatures(state);
+                    }
+
                     if !state.has_all_nonces() {
                         return CompetitionStatus::AwaitingSignatures(state);
                     }
@@ -799,6 +1432,13 @@ impl Coordinator {
                             CompetitionStatus::SigningComplete(state)
                         }
                     }
+                    Err(e) if is_transient_broadcast_error(&e) => {
+                        warn!(
+                            "Competition {} funding broadcast hit transient error, will retry: {}",
+                            competition_id, e
+                        );
+                        CompetitionStatus::SigningComplete(state)
+                    }
                     Err(e) => {
                         error!(
                             "Competition {} funding broadcast failed: {}",
@@ -810,11 +1450,50 @@ impl Coordinator {
                 }
             }
 
+            CompetitionStatus::FundingBroadcasted(mut state)
+                if self.invoice_settlement_mode == InvoiceSettlementMode::Safe =>
+            {
+                if let Err(e) = self
+                    .settle_invoices_in_best_chain(state.competition_mut())
+                    .await
+                {
+                    warn!(
+                        "Competition {} failed to check funding in best chain, will retry: {}",
+                        competition_id, e
+                    );
+                }
+
+                // Never move past funding until the invoices are settled against a funding tx
+                // that is still in the best chain
+                if state.competition().invoices_settled_at.is_none() {
+                    CompetitionStatus::FundingBroadcasted(state)
+                } else {
+                    match self
+                        .check_funding_confirmation(state.competition_mut())
+                        .await
+                    {
+                        Ok(_) if state.competition().funding_confirmed_at.is_some() => {
+                            state.funding_confirmed()
+                        }
+                        Ok(_) => CompetitionStatus::FundingBroadcasted(state),
+                        Err(e) => {
+                            warn!(
+                                "Competition {} funding confirmation check failed, will retry: {}",
+                                competition_id, e
+                            );
+                            CompetitionStatus::FundingBroadcasted(state)
+                        }
+                    }
+                }
+            }
+
             CompetitionStatus::FundingBroadcasted(mut state) => {
                 // Settle hold invoices based on configured confirmation requirement
                 // If invoice_settlement_confirmations is 0, settle immediately at broadcast
                 // Otherwise, wait for the required confirmations before settling
-                let should_settle = if self.invoice_settlement_confirmations == 0 {
+                let settlement_confirmations =
+                    self.invoice_settlement_confirmations(state.competition());
+                let should_settle = if settlement_confirmations == 0 {
                     // Settle immediately at broadcast time
                     state.competition().invoices_settled_at.is_none()
                 } else {
@@ -823,7 +1502,7 @@ impl Coordinator {
                         let txid = funding_tx.compute_txid();
                         match self.bitcoin.get_tx_confirmation_height(&txid).await {
                             Ok(Some(confirmations)) => {
-                                confirmations >= self.invoice_settlement_confirmations
+                                confirmations >= settlement_confirmations
                                     && state.competition().invoices_settled_at.is_none()
                             }
                             _ => false,
@@ -834,18 +1513,32 @@ impl Coordinator {
                 };
 
                 if should_settle {
+                    if settlement_confirmations == 0 {
+                        warn!(
+                            "Competition {} settling hold invoices before the funding transaction has confirmed",
+                            competition_id
+                        );
+                    }
                     info!(
                         "Settling hold invoices for competition {} (required confirmations: {})",
-                        competition_id, self.invoice_settlement_confirmations
+                        competition_id, settlement_confirmations
                     );
-                    if let Err(e) = self.settle_competition_invoices(competition_id).await {
-                        error!(
+                    match self.settle_competition_invoices(competition_id).await {
+                        Ok(progress) if progress.is_complete() => {
+                            state.competition_mut().invoices_settled_at =
+                                Some(OffsetDateTime::now_utc());
+                        }
+                        Ok(progress) => warn!(
+                            "Competition {} settled {} of {} hold invoices, {} failed, will retry",
+                            competition_id,
+                            progress.settled_invoices,
+                            progress.total_invoices,
+                            progress.failed_invoices
+                        ),
+                        Err(e) => error!(
                             "Competition {} failed to settle invoices: {}",
                             competition_id, e
-                        );
-                    } else {
-                        state.competition_mut().invoices_settled_at =
-                            Some(OffsetDateTime::now_utc());
+                        ),
                     }
                 }
 
@@ -866,10 +1559,9 @@ impl Coordinator {
                             "Competition {} funding confirmation failed: {}",
                             competition_id, e
                         );
-                        state
-                            .competition_mut()
-                            .errors
-                            .push(CompetitionError::FailedFundingConfirmation(e.to_string()));
+                        state.competition_mut().record_error(
+                            CompetitionError::FailedFundingConfirmation(e.to_string()),
+                        );
                         if state.competition().should_abort() {
                             CompetitionStatus::FundingBroadcasted(state)
                                 .fail(CompetitionError::FailedFundingConfirmation(e.to_string()))
@@ -892,6 +1584,21 @@ impl Coordinator {
             CompetitionStatus::FundingSettled(state) => state.await_attestation(),
 
             CompetitionStatus::AwaitingAttestation(mut state) => {
+                let now = OffsetDateTime::now_utc();
+                let signing_date = state.competition().event_submission.signing_date;
+                if !attestation_window_open(
+                    signing_date,
+                    now,
+                    self.settings.attestation_pre_window(),
+                ) {
+                    debug!(
+                        "Competition {} signs at {}, not polling the oracle yet",
+                        competition_id, signing_date
+                    );
+                    return CompetitionStatus::AwaitingAttestation(state);
+                }
+                self.attestation_polls.track(competition_id, now);
+
                 match self.check_oracle_attestation(state.competition_mut()).await {
                     Ok(_) => {
                         if let Some(attestation) = state.competition().attestation {
@@ -903,6 +1610,7 @@ impl Coordinator {
                     Err(e) => {
                         if e.downcast_ref::<OracleError>()
                             .is_some_and(|oe| oe.is_transient())
+                            || is_transient_broadcast_error(&e)
                         {
                             warn!(
                                 "Competition {} attestation check hit transient error, will retry: {}",
@@ -913,10 +1621,9 @@ impl Coordinator {
                                 "Competition {} attestation check failed: {}",
                                 competition_id, e
                             );
-                            state
-                                .competition_mut()
-                                .errors
-                                .push(CompetitionError::FailedCheckingAttestation(e.to_string()));
+                            state.competition_mut().record_error(
+                                CompetitionError::FailedCheckingAttestation(e.to_string()),
+                            );
                             if state.competition().should_abort() {
                                 return CompetitionStatus::AwaitingAttestation(state).fail(
                                     CompetitionError::FailedCheckingAttestation(e.to_string()),
@@ -940,6 +1647,31 @@ impl Coordinator {
                             CompetitionStatus::Attested(state)
                         }
                     }
+                    Err(e) if is_transient_broadcast_error(&e) => {
+                        warn!(
+                            "Competition {} outcome broadcast hit transient error, will retry: {}",
+                            competition_id, e
+                        );
+                        CompetitionStatus::Attested(state)
+                    }
+                    Err(e) if e.downcast_ref::<UnexpectedSpend>().is_some() => {
+                        warn!(
+                            "Competition {} outcome broadcast skipped, its input is gone: {}",
+                            competition_id, e
+                        );
+                        CompetitionStatus::Attested(state)
+                    }
+                    Err(e) if e.downcast_ref::<BroadcastRejected>().is_some() => {
+                        // Left in place so the broadcast is retried once the signatures are fixed
+                        error!(
+                            "Competition {} outcome broadcast rejected: {}",
+                            competition_id, e
+                        );
+                        state.competition_mut().record_repeated_error(
+                            CompetitionError::BroadcastRejected(e.to_string()),
+                        );
+                        CompetitionStatus::Attested(state)
+                    }
                     Err(e) => {
                         error!(
                             "Competition {} outcome broadcast failed: {}",
@@ -951,7 +1683,40 @@ impl Coordinator {
                 }
             }
 
-            CompetitionStatus::ExpiryBroadcasted(state) => state.completed(),
+            CompetitionStatus::ExpiryBroadcasted(mut state) => {
+                match self
+                    .check_expiry_transaction(state.competition(), false)
+                    .await
+                {
+                    Ok(status) if status.confirmations > 0 => state.completed(),
+                    Ok(status) => {
+                        info!(
+                            "Competition {} expiry tx {} not confirmed yet",
+                            competition_id, status.txid
+                        );
+                        CompetitionStatus::ExpiryBroadcasted(state)
+                    }
+                    Err(e) if is_transient_broadcast_error(&e) => {
+                        warn!(
+                            "Competition {} expiry rebroadcast hit transient error, will retry: {}",
+                            competition_id, e
+                        );
+                        CompetitionStatus::ExpiryBroadcasted(state)
+                    }
+                    Err(e) => {
+                        // The expiry transaction is the players' refund, failing the competition
+                        // would stop following it. Record the error and check again next tick.
+                        error!(
+                            "Competition {} expiry tx check failed, will retry: {}",
+                            competition_id, e
+                        );
+                        state.competition_mut().record_repeated_error(
+                            CompetitionError::FailedBroadcast(e.to_string()),
+                        );
+                        CompetitionStatus::ExpiryBroadcasted(state)
+                    }
+                }
+            }
 
             CompetitionStatus::OutcomeBroadcasted(mut state) => {
                 match self
@@ -965,6 +1730,30 @@ impl Coordinator {
                             CompetitionStatus::OutcomeBroadcasted(state)
                         }
                     }
+                    Err(e) if is_transient_broadcast_error(&e) => {
+                        warn!(
+                            "Competition {} delta broadcast hit transient error, will retry: {}",
+                            competition_id, e
+                        );
+                        CompetitionStatus::OutcomeBroadcasted(state)
+                    }
+                    Err(e) if e.downcast_ref::<UnexpectedSpend>().is_some() => {
+                        warn!(
+                            "Competition {} delta broadcast skipped, its input is gone: {}",
+                            competition_id, e
+                        );
+                        CompetitionStatus::OutcomeBroadcasted(state)
+                    }
+                    Err(e) if e.downcast_ref::<BroadcastRejected>().is_some() => {
+                        error!(
+                            "Competition {} delta broadcast rejected: {}",
+                            competition_id, e
+                        );
+                        state.competition_mut().record_repeated_error(
+                            CompetitionError::BroadcastRejected(e.to_string()),
+                        );
+                        CompetitionStatus::OutcomeBroadcasted(state)
+                    }
                     Err(e) => {
                         error!(
                             "Competition {} delta broadcast failed: {}",
@@ -988,6 +1777,30 @@ impl Coordinator {
                             CompetitionStatus::DeltaBroadcasted(state)
                         }
                     }
+                    Err(e) if is_transient_broadcast_error(&e) => {
+                        warn!(
+                            "Competition {} delta2 broadcast hit transient error, will retry: {}",
+                            competition_id, e
+                        );
+                        CompetitionStatus::DeltaBroadcasted(state)
+                    }
+                    Err(e) if e.downcast_ref::<UnexpectedSpend>().is_some() => {
+                        warn!(
+                            "Competition {} delta2 broadcast skipped, its input is gone: {}",
+                            competition_id, e
+                        );
+                        CompetitionStatus::DeltaBroadcasted(state)
+                    }
+                    Err(e) if e.downcast_ref::<BroadcastRejected>().is_some() => {
+                        error!(
+                            "Competition {} delta2 broadcast rejected: {}",
+                            competition_id, e
+                        );
+                        state.competition_mut().record_repeated_error(
+                            CompetitionError::BroadcastRejected(e.to_string()),
+                        );
+                        CompetitionStatus::DeltaBroadcasted(state)
+                    }
                     Err(e) => {
                         error!(
                             "Competition {} delta2 broadcast failed: {}",
@@ -1013,9 +1826,15 @@ impl Coordinator {
         debug!("Checking escrow confirmations: {:?}", tickets);
 
         let mut all_confirmed = true;
-        let mut pending_txids = Vec::new();
-
-        for (_, ticket) in tickets {
+        let mut not_broadcast = Vec::new();
+        let mut unconfirmed = Vec::new();
+        let mut double_spent = Vec::new();
+        let required_confirmations = self.settings.escrow_required_confirmations();
+        let broadcast_by_coordinator = self.settings.escrow_broadcast_by_coordinator();
+        let escrow_amount = Amount::from_sat(competition.event_submission.entry_fee.to_sat());
+
+        for (entry_id, ticket) in &tickets {
+            let entry_id = *entry_id;
             if let Some(escrow_tx_hex) = &ticket.escrow_transaction {
                 let bytes = hex::decode(escrow_tx_hex)
                     .map_err(|e| anyhow!("Failed to decode escrow transaction: {}", e))?;
@@ -1025,8 +1844,44 @@ impl Coordinator {
                 let txid = escrow_tx.compute_txid();
 
                 // Check if transaction has required confirmations
-                match self.bitcoin.get_tx_confirmation_height(&txid).await? {
-                    Some(confirmations) if confirmations >= self.required_confirmations => {
+                let mut confirmation_height =
+                    self.bitcoin.get_tx_confirmation_height(&txid).await?;
+                let mut broadcast = ticket.escrow_broadcasted_at.is_some();
+                // Only reached once every entry is paid, so the escrows can go out now without
+                // players paying fees for a competition that never fills
+                if broadcast_by_coordinator && confirmation_height.is_none() && !broadcast {
+                    match broadcast_transaction(self.bitcoin.as_ref(), &escrow_tx).await {
+                        Ok(()) => {
+                            info!(
+                                "Competition {} broadcast escrow transaction {} for ticket {}",
+                                competition.id, txid, ticket.id
+                            );
+                            self.competition_store
+                                .mark_ticket_escrow_broadcasted(ticket.id)
+                                .await?;
+                            broadcast = true;
+                            confirmation_height =
+                                self.bitcoin.get_tx_confirmation_height(&txid).await?;
+                        }
+                        Err(e) if e.is_transient() => {
+                            warn!(
+                                "Competition {} couldn't broadcast escrow transaction {} for ticket {} yet: {}",
+                                competition.id, txid, ticket.id, e
+                            );
+                        }
+                        Err(e) => {
+                            return Err(anyhow!(
+                                "Escrow transaction {} for ticket {} was rejected when the coordinator broadcast it: {}",
+                                txid,
+                                ticket.id,
+                                e
+                            ));
+                        }
+                    }
+                }
+
+                match confirmation_height {
+                    Some(confirmations) if confirmations >= required_confirmations => {
                         debug!(
                             "Escrow transaction {} has {} confirmations for ticket {}",
                             txid, confirmations, ticket.id
@@ -1035,21 +1890,120 @@ impl Coordinator {
                     Some(confirmations) => {
                         debug!(
                                 "Escrow transaction {} has {} of {} required confirmations for ticket {}",
-                                txid, confirmations, self.required_confirmations, ticket.id
+                                txid, confirmations, required_confirmations, ticket.id
                             );
                         all_confirmed = false;
-                        pending_txids.push(txid);
+                        unconfirmed.push(txid);
+                    }
+                    None if broadcast => {
+                        debug!(
+                            "Escrow transaction {} for ticket {} was broadcast by the coordinator but isn't confirmed yet",
+                            txid, ticket.id
+                        );
+                        all_confirmed = false;
+                        unconfirmed.push(txid);
+                    }
+                    None if broadcast_by_coordinator => {
+                        debug!(
+                            "Escrow transaction {} for ticket {} hasn't been broadcast by the coordinator yet",
+                            txid, ticket.id
+                        );
+                        all_confirmed = false;
+                        not_broadcast.push(txid);
                     }
                     None => {
                         debug!(
-                            "Escrow transaction {} not found on-chain for ticket {}",
+                            "Escrow transaction {} not found on-chain for ticket {}, the player is responsible for broadcasting it",
                             txid, ticket.id
                         );
                         all_confirmed = false;
-                        pending_txids.push(txid);
+                        not_broadcast.push(txid);
+                    }
+                }
+
+                // The escrow or, while it's unconfirmed, its inputs may have been spent elsewhere
+                let escrow_outpoint = get_escrow_outpoint(&escrow_tx, escrow_amount)?;
+                let escrow_status = self.bitcoin.get_outpoint_status(&escrow_outpoint).await?;
+                let mut input_statuses = Vec::new();
+                if confirmation_height.is_none() {
+                    for input in &escrow_tx.input {
+                        let status = self
+                            .bitcoin
+                            .get_outpoint_status(&input.previous_output)
+                            .await?;
+                        input_statuses.push((input.previous_output, status));
                     }
                 }
+                if let Some(reason) =
+                    escrow_double_spend(&escrow_tx, escrow_outpoint, escrow_status, &input_statuses)
+                {
+                    double_spent.push((entry_id, reason));
+                }
+            }
+        }
+
+        if !double_spent.is_empty() {
+            let reasons: Vec<String> = double_spent
+                .iter()
+                .map(|(entry_id, reason)| format!("entry {}: {}", entry_id, reason))
+                .collect();
+            // Keymeld sessions were set up for every ticket, the contract can't drop a player
+            if self.is_keymeld_enabled() {
+                for (entry_id, reason) in &double_spent {
+                    self.competition_store
+                        .cancel_entry(*entry_id, reason)
+                        .await?;
+                }
+                return Err(EscrowDoubleSpent { reasons }.into());
+            }
+
+            let Some((event_submission, version)) = self
+                .competition_store
+                .cancel_double_spent_entries(competition.id, double_spent.clone())
+                .await?
+            else {
+                return Err(EscrowDoubleSpent { reasons }.into());
+            };
+            for (entry_id, reason) in &double_spent {
+                warn!(
+                    "Competition {} cancelled entry {}: {}",
+                    competition.id, entry_id, reason
+                );
+                // The player is out of the competition, their lightning payment goes back
+                let Some(ticket) = tickets.get(entry_id) else {
+                    continue;
+                };
+                if ticket.comped_at.is_some() || ticket.settled_at.is_some() {
+                    continue;
+                }
+                if let Err(e) = self
+                    .ln
+                    .cancel_hold_invoice(ticket.hash.clone(), ticket.ln_backend_id.as_deref())
+                    .await
+                {
+                    error!(
+                        "Competition {} failed to cancel the hold invoice of ticket {}: {}",
+                        competition.id, ticket.id, e
+                    );
+                }
+            }
+
+            let remaining = event_submission.total_allowed_entries;
+            competition.event_submission = event_submission;
+            // Only this copy's own write moved the version on, anything else in between still
+            // makes its save conflict
+            if version == competition.version + 1 {
+                competition.version = version;
+            }
+            if remaining < competition.event_submission.number_of_places_win.max(1) {
+                return Err(EscrowDoubleSpent { reasons }.into());
             }
+            info!(
+                "Competition {} continues with its {} remaining entries",
+                competition.id, remaining
+            );
+            competition.record_error(CompetitionError::EscrowDoubleSpent(reasons.join("; ")));
+            return Ok(competition);
         }
 
         if all_confirmed {
@@ -1057,9 +2011,10 @@ impl Coordinator {
             debug!("All escrow funds confirmed");
         } else {
             debug!(
-                "Competition {} waiting for {} escrow transactions to confirm",
+                "Competition {} waiting on escrow transactions: {} not broadcast, {} broadcast but unconfirmed",
                 competition.id,
-                pending_txids.len()
+                not_broadcast.len(),
+                unconfirmed.len()
             );
         }
         competition.errors = vec![];
@@ -1078,6 +2033,19 @@ impl Coordinator {
                 .await
             {
                 Ok(event) => Ok(event),
+                // Created on an earlier try that didn't get to store it, adopt it if it's the
+                // event this competition would have created
+                Err(OracleError::Conflict(conflict)) => {
+                    self.existing_oracle_event(competition, &conflict).await
+                }
+                // Not every oracle answers a duplicate with a conflict, check whether the event
+                // is there before failing
+                Err(OracleError::Request(e)) => {
+                    match self.oracle_client.get_event(&competition.id).await {
+                        Ok(existing) => self.adopt_oracle_event(competition, existing, &e),
+                        Err(_) => Err(Error::OracleFailed(OracleError::Request(e))),
+                    }
+                }
                 Err(OracleError::NotFound(e)) => Err(Error::NotFound(e)),
                 Err(OracleError::BadRequest(e)) => Err(Error::BadRequest(e)),
                 Err(e) => Err(Error::OracleFailed(e)),
@@ -1086,6 +2054,11 @@ impl Coordinator {
                 "Created competition's {} oracle event: {:?}",
                 competition.id, event
             );
+            check_event_outcomes(
+                &ContractSizeEstimate::for_event(&competition.event_submission),
+                &event.event_announcement,
+            )
+            .map_err(|e| anyhow!("oracle event {}: {}", competition.id, e))?;
 
             competition.event_announcement = Some(event.event_announcement);
             competition.event_created_at = Some(OffsetDateTime::now_utc());
@@ -1094,27 +2067,98 @@ impl Coordinator {
         Ok(competition)
     }
 
-    async fn submit_entries_to_oracle<'a>(
+    /// The oracle event already created under the competition's id, as long as it matches the
+    /// competition's event submission
+    async fn existing_oracle_event(
         &self,
-        competition: &'a mut Competition,
-    ) -> Result<&'a mut Competition, anyhow::Error> {
-        let entries = self
-            .competition_store
-            .get_competition_entries(competition.id, vec![EntryStatus::Paid])
-            .await
-            .map_err(|e| {
-                anyhow!(
-                    "Failed to get entries for competition {}: {}",
-                    competition.id,
-                    e
-                )
-            })?;
+        competition: &Competition,
+        conflict: &str,
+    ) -> Result<Event, Error> {
+        let event = self.oracle_client.get_event(&competition.id).await?;
+        self.adopt_oracle_event(competition, event, conflict)
+    }
 
-        if entries.is_empty() {
-            return Err(anyhow!(
-                "No paid entries found for competition {}",
-                competition.id
-            ));
+    /// `event` if it's the one the competition's event submission would have created
+    fn adopt_oracle_event(
+        &self,
+        competition: &Competition,
+        event: Event,
+        reason: &str,
+    ) -> Result<Event, Error> {
+        info!(
+            "Oracle event for competition {} already exists ({}), checking it matches",
+            competition.id, reason
+        );
+        let differences = event.differences(&competition.event_submission);
+        if !differences.is_empty() {
+            return Err(Error::BadRequest(format!(
+                "oracle already has a different event {}: {}",
+                competition.id,
+                differences.join(", ")
+            )));
+        }
+        info!(
+            "Adopting the existing oracle event for competition {}",
+            competition.id
+        );
+        Ok(event)
+    }
+
+    /// Send one chunk of entries. A conflict means some of them landed on an earlier try whose
+    /// response was lost, so each entry is sent on its own and the ones the oracle already has
+    /// count as submitted.
+    async fn submit_entries_chunk(
+        &self,
+        event_id: Uuid,
+        chunk: &[AddEventEntry],
+    ) -> Result<(), anyhow::Error> {
+        let submit = move |entries: Vec<AddEventEntry>| {
+            self.oracle_client
+                .submit_entries(AddEventEntries { event_id, entries })
+        };
+        match submit(chunk.to_vec()).await {
+            Ok(()) => return Ok(()),
+            Err(OracleError::Conflict(conflict)) => info!(
+                "Oracle already has entries of competition {} ({}), sending them one by one",
+                event_id, conflict
+            ),
+            Err(e) => {
+                return Err(anyhow::Error::new(e).context("Failed to submit entries to oracle"))
+            }
+        }
+        for entry in chunk {
+            match submit(vec![entry.clone()]).await {
+                Ok(()) | Err(OracleError::Conflict(_)) => {}
+                Err(e) => {
+                    return Err(anyhow::Error::new(e)
+                        .context(format!("Failed to submit entry {} to oracle", entry.id)))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn submit_entries_to_oracle<'a>(
+        &self,
+        competition: &'a mut Competition,
+    ) -> Result<&'a mut Competition, anyhow::Error> {
+        let entries = self
+            .competition_store
+            .get_competition_entries(competition.id, vec![EntryStatus::Paid])
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to get entries for competition {}: {}",
+                    competition.id,
+                    e
+                )
+            })?;
+
+        if entries.is_empty() {
+            return Err(anyhow!(
+                "No paid entries found for competition {}",
+                competition.id
+            ));
         }
 
         let mut oracle_entries: Vec<AddEventEntry> = Vec::new();
@@ -1138,22 +2182,32 @@ impl Coordinator {
             ));
         }
 
-        info!(
-            "Submitting {} entries to oracle for competition {}",
-            oracle_entries.len(),
-            competition.id,
-        );
-
-        let event_entries = AddEventEntries {
-            event_id: competition.id,
-            entries: oracle_entries,
-        };
-
         if competition.entries_submitted_at.is_none() {
-            self.oracle_client
-                .submit_entries(event_entries)
-                .await
-                .map_err(|e| anyhow!("Failed to submit entries to oracle: {:?}", e))?;
+            let pending: Vec<AddEventEntry> = oracle_entries
+                .into_iter()
+                .filter(|entry| !competition.oracle_submitted_entry_ids.contains(&entry.id))
+                .collect();
+            let chunk_size = self.settings.oracle_entries_chunk_size();
+            info!(
+                "Submitting {} entries to oracle for competition {} in chunks of {}, {} already submitted",
+                pending.len(),
+                competition.id,
+                chunk_size,
+                competition.oracle_submitted_entry_ids.len(),
+            );
+
+            for chunk in pending.chunks(chunk_size) {
+                self.submit_entries_chunk(competition.id, chunk).await?;
+                competition
+                    .oracle_submitted_entry_ids
+                    .extend(chunk.iter().map(|entry| entry.id));
+                self.competition_store
+                    .update_oracle_submitted_entry_ids(
+                        competition.id,
+                        &competition.oracle_submitted_entry_ids,
+                    )
+                    .await?;
+            }
 
             competition.entries_submitted_at = Some(OffsetDateTime::now_utc());
         }
@@ -1166,6 +2220,24 @@ impl Coordinator {
         Ok(competition)
     }
 
+    /// With escrow disabled the wallet funds the whole pool, so its confirmed balance has to
+    /// cover the pool and the funding fee before the funding psbt is built
+    async fn check_funding_balance(
+        &self,
+        pool: Sats,
+        funding_fee_rate: &FundingFeeRate,
+    ) -> Result<(), anyhow::Error> {
+        let balance = self.bitcoin.get_balance().await?;
+        if let Some(shortfall) = FundingShortfall::check(
+            Sats(balance.confirmed.to_sat()),
+            pool,
+            funding_fee_rate.sat_per_vb,
+        ) {
+            return Err(shortfall.into());
+        }
+        Ok(())
+    }
+
     pub async fn create_funding_psbt<'a>(
         &self,
         competition: &'a mut Competition,
@@ -1181,13 +2253,7 @@ impl Coordinator {
         if competition.public_nonces.is_some() {
             return Ok(competition);
         }
-        let mut entries = self
-            .competition_store
-            .get_competition_entries(competition.id, vec![EntryStatus::Paid])
-            .await?;
-        // Sort entries by ticket_id to ensure player indices match the ticket order
-        // used when creating keymeld subset definitions at competition creation time
-        entries.sort_by_key(|entry| entry.ticket_id);
+        let entries = self.get_contract_entries(competition.id).await?;
         debug!("Competition entries {:?}", entries);
         let tickets = self.competition_store.get_tickets(competition.id).await?;
 
@@ -1201,34 +2267,93 @@ impl Coordinator {
             );
         }
 
-        let outcome_payouts = generate_payouts(competition, &mut entries, &players)?;
+        check_event_outcomes(
+            &ContractSizeEstimate::new(
+                entries.len(),
+                competition.event_submission.number_of_places_win,
+                competition.event_submission.tie_policy,
+            ),
+            &event_announcement,
+        )
+        .map_err(|e| anyhow!("competition {}: {}", competition.id, e))?;
+        let outcome_payouts = generate_payouts(competition, &entries, &players)?;
         debug!("Generated outcome payouts:");
         for (outcome, weights) in &outcome_payouts {
             debug!("Outcome {:?}: weights={:?}", outcome, weights);
         }
 
-        let contract_amount_sats = competition.event_submission.total_competition_pool;
+        let sponsored = self
+            .competition_store
+            .get_sponsorships(competition.id)
+            .await?
+            .iter()
+            .fold(Sats::ZERO, |total, sponsorship| {
+                total.saturating_add(sponsorship.amount_sats)
+            });
+        // The subsidy cap was checked against a sold-out competition at creation, with fewer
+        // entries the pool shrinks so the coordinator never covers more than the cap
+        let pool = &competition.event_submission;
+        let contract_amount = pool.funded_pool(
+            entries.len(),
+            sponsored,
+            Sats(self.settings.max_pool_subsidy_sats()),
+        );
+        if contract_amount < pool.total_competition_pool {
+            warn!(
+                "Competition {} only has {} of {} entries, funding {} of its {} sat pool to stay within the {} sat subsidy cap",
+                competition.id,
+                entries.len(),
+                pool.total_allowed_entries,
+                contract_amount,
+                pool.total_competition_pool,
+                self.settings.max_pool_subsidy_sats()
+            );
+        }
+        let contract_amount_sats = contract_amount.to_sat();
+        // Comped entries didn't pay an entry fee, the coordinator's wallet covers theirs
+        let comped_entries = tickets
+            .values()
+            .filter(|ticket| ticket.comped_at.is_some())
+            .count();
+        info!(
+            "Competition {} funds a pool of {} sats from {} paid entries ({} comped) and {} sponsored sats, coordinator exposure is {} sats",
+            competition.id,
+            contract_amount_sats,
+            entries.len(),
+            comped_entries,
+            sponsored,
+            contract_amount
+                .saturating_sub(sponsored)
+                .saturating_sub(pool.entry_fee.saturating_mul(
+                    entries.len().saturating_sub(comped_entries) as u64
+                ))
+        );
         let fee_rates = self.bitcoin.get_estimated_fee_rates().await?;
         info!("Fee rates: {:?}", fee_rates);
 
         // TODO (@tee8z): make this configurable from the admin screen
-        let rate_confirm_within_2_blocks = if fee_rates.is_empty() {
-            1_u64
-        } else {
-            fee_rates[&1_u16].ceil() as u64
-        };
+        let funding_fee_rate = FundingFeeRate::from_estimates(&fee_rates, 1);
+        info!(
+            "Funding fee rate for competition {}: {} sat/vb ({:?})",
+            competition.id, funding_fee_rate.sat_per_vb, funding_fee_rate.source
+        );
 
-        let fee_rate = FeeRate::from_sat_per_vb_unchecked(rate_confirm_within_2_blocks);
+        if !self.escrow_enabled && competition.funding_psbt_base64.is_none() {
+            self.check_funding_balance(contract_amount, &funding_fee_rate)
+                .await?;
+        }
+
+        let fee_rate = funding_fee_rate.fee_rate();
 
         let contract_params = ContractParameters {
             market_maker: dlctix::MarketMaker {
-                pubkey: self.public_key,
+                pubkey: self.competition_key(competition)?.base_point_mul(),
             },
             players,
             event: event_announcement.clone(),
             outcome_payouts,
             fee_rate,
-            funding_value: Amount::from_sat(contract_amount_sats as u64),
+            funding_value: Amount::from_sat(contract_amount_sats),
             relative_locktime_block_delta: competition
                 .event_submission
                 .relative_locktime_block_delta
@@ -1265,7 +2390,7 @@ impl Coordinator {
                     debug!("Escrow transaction: {:?}", transaction);
                     let outpoint = get_escrow_outpoint(
                         &transaction,
-                        Amount::from_sat(competition.event_submission.entry_fee as u64),
+                        Amount::from_sat(competition.event_submission.entry_fee.to_sat()),
                     )?;
 
                     let escrow_output = transaction
@@ -1355,7 +2480,7 @@ impl Coordinator {
             .bitcoin
             .build_psbt(
                 funding_script.clone(),
-                Amount::from_sat(contract_amount_sats as u64),
+                Amount::from_sat(contract_amount_sats),
                 fee_rate,
                 vec![],
                 escrow_inputs,
@@ -1371,7 +2496,7 @@ impl Coordinator {
             .iter()
             .position(|output| {
                 output.script_pubkey == funding_script
-                    && output.value == Amount::from_sat(contract_amount_sats as u64)
+                    && output.value == Amount::from_sat(contract_amount_sats)
             })
             .ok_or_else(|| anyhow!("Funding output not found in PSBT"))?;
 
@@ -1382,14 +2507,15 @@ impl Coordinator {
 
         if competition.funding_psbt_base64.is_none() {
             competition.funding_psbt_base64 = Some(psbt.to_string());
+            competition.funding_fee_rate = Some(funding_fee_rate);
             competition.funding_outpoint = Some(funding_outpoint);
         }
 
-        // Note: word of warning, the ticketed_dlc may be very large in memory depending on the contract params
-        let ticketed_dlc = TicketedDLC::new(contract_params.clone(), funding_outpoint)?;
-        debug!("Built ticketed dlc");
-
         if self.is_keymeld_enabled() {
+            // The contract is only built when keymeld signs it, don't hold on to the parameters
+            // across the registrations
+            drop(contract_params);
+
             // Keymeld flow: Retrieve keygen session (created at competition creation) and register participants
             info!(
                 "Using Keymeld for MuSig2 signing for competition {}",
@@ -1410,7 +2536,7 @@ impl Coordinator {
                 })?;
 
             let session_secret = self
-                .decrypt_session_secret(&stored_session.encrypted_session_secret)
+                .decrypt_session_secret(&stored_session)
                 .map_err(|e| anyhow!("Failed to decrypt session secret: {}", e))?;
 
             let keygen_session = stored_session.to_session(session_secret);
@@ -1474,13 +2600,17 @@ impl Coordinator {
             }
         } else {
             // Traditional MuSig2 flow: Generate local nonces
-            let signing_session = {
-                let mut rng = create_deterministic_rng(&funding_outpoint, self.private_key);
-                SigningSession::<NonceSharingRound>::new(ticketed_dlc, &mut rng, self.private_key)?
-            };
+            let public_nonces = build_coordinator_nonces(
+                &self.contract_builds,
+                contract_params,
+                funding_outpoint,
+                self.competition_key(competition)?,
+            )
+            .await?;
             debug!("Started musig nonce sharing round");
             if competition.public_nonces.is_none() {
-                competition.public_nonces = Some(signing_session.our_public_nonces().to_owned());
+                competition.public_nonces = Some(public_nonces);
+                competition.nonce_seed_outpoint = Some(funding_outpoint);
             }
         }
 
@@ -1490,6 +2620,75 @@ impl Coordinator {
         Ok(competition)
     }
 
+    /// Derive fresh coordinator nonces from the current funding outpoint and have every entry
+    /// submit nonces again. The funding psbt was rebuilt after the first round, so the stored
+    /// nonces, and everything aggregated from them, can't produce valid signatures anymore.
+    async fn restart_nonce_round(
+        &self,
+        competition: &mut Competition,
+    ) -> Result<(), anyhow::Error> {
+        let Some(contract_parameters) = &competition.contract_parameters else {
+            return Err(anyhow!(
+                "contract parameters don't exists, failed restarting competition {} nonce round",
+                competition.id
+            ));
+        };
+        let Some(funding_outpoint) = competition.funding_outpoint else {
+            return Err(anyhow!(
+                "funding outpoint doesn't exists, failed restarting competition {} nonce round",
+                competition.id
+            ));
+        };
+        warn!(
+            "Competition {} funding outpoint moved from {:?} to {} after nonces were shared, restarting the nonce round",
+            competition.id, competition.nonce_seed_outpoint, funding_outpoint
+        );
+
+        let public_nonces = build_coordinator_nonces(
+            &self.contract_builds,
+            contract_parameters.to_owned(),
+            funding_outpoint,
+            self.competition_key(competition)?,
+        )
+        .await?;
+
+        let cleared = self
+            .competition_store
+            .clear_entry_signing_round(competition.id)
+            .await?;
+        competition.reset_nonce_round();
+        competition.public_nonces = Some(public_nonces);
+        competition.nonce_seed_outpoint = Some(funding_outpoint);
+
+        self.webhooks.notify(
+            competition.id,
+            "nonce_round_restarted",
+            format!(
+                "Competition {} funding transaction was rebuilt, {} entries need to submit nonces again",
+                competition.id,
+                cleared.len()
+            ),
+        );
+        let relays = self.entrant_relays(competition);
+        if relays.is_empty() && !cleared.is_empty() {
+            warn!(
+                "Competition {} has no relays to tell {} entrants to submit nonces again",
+                competition.id,
+                cleared.len()
+            );
+            return Ok(());
+        }
+        for (entry_id, pubkey) in cleared {
+            let notice = NonceRoundRestarted {
+                competition_id: competition.id,
+                entry_id,
+                funding_outpoint: funding_outpoint.to_string(),
+            };
+            self.send_entrant_dm(relays.clone(), entry_id, |keys| notice.to_dm(keys, &pubkey));
+        }
+        Ok(())
+    }
+
     /// Aggregate nonces from all participants and generate coordinator's partial signatures
     ///
     /// When keymeld is enabled, this step is skipped - keymeld handles nonce aggregation
@@ -1532,24 +2731,16 @@ impl Coordinator {
             return Err(anyhow!("coordinator nonces missing"));
         };
 
-        let ticketed_dlc =
-            TicketedDLC::new(contract_parameters.to_owned(), funding_outpoint.to_owned())?;
-
-        let signing_session = {
-            let mut rng = create_deterministic_rng(funding_outpoint, self.private_key);
-            SigningSession::<NonceSharingRound>::new(ticketed_dlc, &mut rng, self.private_key)?
-        };
-
-        // Verify our stored nonces match what would be generated
-        if signing_session.our_public_nonces() != our_nonces {
-            return Err(anyhow!("coordinator nonce mismatch"));
-        }
         debug!("our_nonces: {:?}", our_nonces);
-
         let received_nonces = self.get_received_nonces(competition.id).await?;
         debug!("received_nonces: {:?}", received_nonces);
-        let coordinator_sessions =
-            signing_session.aggregate_nonces_and_compute_partial_signatures(received_nonces)?;
+        let coordinator_sessions = build_coordinator_session(
+            contract_parameters.to_owned(),
+            *funding_outpoint,
+            self.competition_key(competition)?,
+            our_nonces,
+            received_nonces,
+        )?;
         debug!(
             "Received_nonces aggregated nonces 1: {:?}",
             coordinator_sessions.aggregated_nonces()
@@ -1562,6 +2753,12 @@ impl Coordinator {
             competition.partial_signatures =
                 Some(coordinator_sessions.our_partial_signatures().to_owned());
         }
+        self.signing_sessions.insert(
+            competition.id,
+            *funding_outpoint,
+            contract_parameters,
+            coordinator_sessions,
+        );
         competition.errors = vec![];
 
         Ok(competition)
@@ -1588,9 +2785,6 @@ impl Coordinator {
             ));
         };
 
-        let ticketed_dlc =
-            TicketedDLC::new(contract_parameters.to_owned(), funding_outpoint.to_owned())?;
-
         if self.is_keymeld_enabled() {
             // Keymeld flow: Use sign_dlc_batch to get all signatures
             info!(
@@ -1612,21 +2806,18 @@ impl Coordinator {
                 })?;
 
             let session_secret = self
-                .decrypt_session_secret(&stored_session.encrypted_session_secret)
+                .decrypt_session_secret(&stored_session)
                 .map_err(|e| anyhow!("Failed to decrypt session secret: {}", e))?;
 
             let keygen_session = stored_session.to_session(session_secret);
 
+            let ticketed_dlc =
+                new_ticketed_dlc(contract_parameters.to_owned(), funding_outpoint.to_owned())?;
             // Get signing data from ticketed DLC
             let signing_data = ticketed_dlc.signing_data()?;
 
-            // Get entries to build player_user_ids for subset definitions
-            // Sort by ticket_id to match the order used in keymeld subset definitions
-            let mut entries = self
-                .competition_store
-                .get_competition_entries(competition.id, vec![EntryStatus::Paid])
-                .await?;
-            entries.sort_by_key(|entry| entry.ticket_id);
+            // Entries in contract player order, matching the keymeld subset definitions
+            let entries = self.get_contract_entries(competition.id).await?;
             let player_user_ids: Vec<UserId> = entries
                 .iter()
                 .map(|entry| UserId::from(entry.ticket_id))
@@ -1678,20 +2869,18 @@ impl Coordinator {
                 return Err(anyhow!("coordinator nonces missing"));
             };
 
-            let signing_session = {
-                let mut rng = create_deterministic_rng(funding_outpoint, self.private_key);
-                SigningSession::<NonceSharingRound>::new(ticketed_dlc, &mut rng, self.private_key)?
-            };
-
-            if signing_session.our_public_nonces() != coordinator_nonces {
-                return Err(anyhow!("coordinator nonce mismatch"));
-            }
-
             let received_nonces = self.get_received_nonces(competition.id).await?;
             debug!("Received all aggregate nonces from entries");
 
-            let coordinator_session =
-                signing_session.aggregate_nonces_and_compute_partial_signatures(received_nonces)?;
+            let coordinator_session = coordinator_signing_session(
+                &self.signing_sessions,
+                competition.id,
+                contract_parameters,
+                *funding_outpoint,
+                self.competition_key(competition)?,
+                coordinator_nonces,
+                received_nonces,
+            )?;
             debug!("Built coordinator session before publishing");
 
             if *coordinator_session.our_partial_signatures() != coordinator_partial_sigantures {
@@ -1746,6 +2935,44 @@ impl Coordinator {
         Ok(competition)
     }
 
+    /// Escrow outpoints each player funds the contract from, by their ephemeral pubkey
+    async fn escrow_outpoints_by_player(
+        &self,
+        competition_id: Uuid,
+        entry_fee: Sats,
+    ) -> Result<BTreeMap<Point, Vec<OutPoint>>, anyhow::Error> {
+        let mut outpoints: BTreeMap<Point, Vec<OutPoint>> = BTreeMap::new();
+        for ticket in self
+            .competition_store
+            .get_tickets(competition_id)
+            .await?
+            .values()
+        {
+            let (Some(_), Some(pubkey), Some(escrow_hex)) = (
+                ticket.entry_id,
+                &ticket.ephemeral_pubkey,
+                &ticket.escrow_transaction,
+            ) else {
+                continue;
+            };
+            let player = Point::from_hex(pubkey)
+                .map_err(|e| anyhow!("Invalid pubkey on ticket {}: {}", ticket.id, e))?;
+            let escrow_tx: Transaction = deserialize(
+                &hex::decode(escrow_hex)
+                    .map_err(|e| anyhow!("Failed to decode escrow transaction: {}", e))?,
+            )
+            .map_err(|e| anyhow!("Failed to deserialize escrow transaction: {}", e))?;
+            outpoints
+                .entry(player)
+                .or_default()
+                .push(get_escrow_outpoint(
+                    &escrow_tx,
+                    Amount::from_sat(entry_fee.to_sat()),
+                )?);
+        }
+        Ok(outpoints)
+    }
+
     async fn sign_and_broadcast_funding_tx<'a>(
         &self,
         competition: &'a mut Competition,
@@ -1773,15 +3000,29 @@ impl Coordinator {
             }
 
             debug!("Merging all funding psbts");
+            let escrow_outpoints = self
+                .escrow_outpoints_by_player(competition.id, competition.event_submission.entry_fee)
+                .await?;
 
+            let mut signed_inputs = BTreeSet::new();
             for (sender_pubkey, final_signature) in &final_signatures_by_sender {
                 let sender_funding_psbt = Psbt::from_str(&final_signature.funding_psbt_base64)?;
-                match funding_psbt.combine(sender_funding_psbt) {
-                    Ok(_) => {
+                validate_psbt_network(&sender_funding_psbt, self.bitcoin.get_network())?;
+                let sender_outpoints = escrow_outpoints
+                    .get(sender_pubkey)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                match combine_entrant_psbt(
+                    &mut funding_psbt,
+                    &sender_funding_psbt,
+                    sender_outpoints,
+                ) {
+                    Ok(inputs) => {
                         debug!(
-                            "✓ Funding PSBT combination succeeded for player {}",
-                            sender_pubkey
+                            "✓ Funding inputs {:?} signed by player {}",
+                            inputs, sender_pubkey
                         );
+                        signed_inputs.extend(inputs);
                     }
                     Err(err) => {
                         error!(
@@ -1792,6 +3033,23 @@ impl Coordinator {
                 }
             }
 
+            let unsigned: Vec<OutPoint> = escrow_outpoints
+                .values()
+                .flatten()
+                .filter(|outpoint| {
+                    escrow_input_index(&funding_psbt, outpoint)
+                        .map_or(true, |index| !signed_inputs.contains(&index))
+                })
+                .copied()
+                .collect();
+            if !unsigned.is_empty() {
+                return Err(anyhow!(
+                    "Escrow inputs {:?} of competition {} funding aren't signed",
+                    unsigned,
+                    competition.id
+                ));
+            }
+
             debug!("Combined all psbts");
         } else {
             debug!(
@@ -1799,6 +3057,15 @@ impl Coordinator {
             );
         }
 
+        // Never hold up the broadcast on bookkeeping
+        let ledger_entries =
+            funding_ledger_entries(competition, &funding_psbt).unwrap_or_else(|e| {
+                error!(
+                    "Competition {} funding won't be on the ledger: {}",
+                    competition.id, e
+                );
+                vec![]
+            });
         let funding_transaction = signed_funding_tx(self.bitcoin.clone(), funding_psbt).await?;
 
         debug!(
@@ -1806,7 +3073,7 @@ impl Coordinator {
             funding_transaction
         );
 
-        self.bitcoin.broadcast(&funding_transaction).await?;
+        broadcast_transaction(self.bitcoin.as_ref(), &funding_transaction).await?;
         info!(
             "Competition {} funding tx broadcast: txid={}",
             competition.id,
@@ -1816,11 +3083,89 @@ impl Coordinator {
         if competition.funding_broadcasted_at.is_none() {
             competition.funding_broadcasted_at = Some(OffsetDateTime::now_utc());
             competition.funding_transaction = Some(funding_transaction);
+            self.record_ledger_entries(competition.id, ledger_entries)
+                .await;
         }
 
         Ok(competition)
     }
 
+    /// Safe settlement mode: settle the hold invoices only once the funding transaction has the
+    /// configured confirmations in the best chain, and put the competition back to waiting if a
+    /// reorg drops the funding transaction again.
+    async fn settle_invoices_in_best_chain(
+        &self,
+        competition: &mut Competition,
+    ) -> Result<(), anyhow::Error> {
+        let funding_tx = competition.funding_transaction.clone().ok_or_else(|| {
+            anyhow!(
+                "No funding transaction found for competition {}",
+                competition.id
+            )
+        })?;
+        let txid = funding_tx.compute_txid();
+        let status = self.bitcoin.get_tx_chain_status(&txid).await?;
+        let settlement_confirmations = self.invoice_settlement_confirmations(competition);
+
+        match safe_settlement_action(settlement_confirmations, status) {
+            SafeSettlementAction::Settle => {
+                if competition.invoices_settled_at.is_none() {
+                    info!(
+                        "Funding transaction {} has {} confirmations in the best chain, settling hold invoices for competition {}",
+                        txid,
+                        status.confirmations(),
+                        competition.id
+                    );
+                    let progress = self.settle_competition_invoices(competition.id).await?;
+                    if progress.is_complete() {
+                        competition.invoices_settled_at = Some(OffsetDateTime::now_utc());
+                    } else {
+                        warn!(
+                            "Competition {} settled {} of {} hold invoices, {} failed, will retry",
+                            competition.id,
+                            progress.settled_invoices,
+                            progress.total_invoices,
+                            progress.failed_invoices
+                        );
+                    }
+                }
+            }
+            SafeSettlementAction::Wait => {
+                if competition.funding_confirmed_at.take().is_some() {
+                    warn!(
+                        "Funding transaction {} for competition {} lost confirmations ({:?}), holding settlement",
+                        txid, competition.id, status
+                    );
+                } else {
+                    debug!(
+                        "Funding transaction {} for competition {} has {} of {} confirmations required to settle",
+                        txid,
+                        competition.id,
+                        status.confirmations(),
+                        settlement_confirmations.max(1)
+                    );
+                }
+            }
+            SafeSettlementAction::FundingDropped => {
+                competition.funding_confirmed_at = None;
+                if competition.invoices_settled_at.is_some() {
+                    error!(
+                        "Funding transaction {} for competition {} is no longer in the best chain or mempool after invoices were settled, rebroadcasting",
+                        txid, competition.id
+                    );
+                } else {
+                    warn!(
+                        "Funding transaction {} for competition {} is no longer in the best chain or mempool, holding settlement and rebroadcasting",
+                        txid, competition.id
+                    );
+                }
+                broadcast_transaction(self.bitcoin.as_ref(), &funding_tx).await?;
+            }
+        }
+
+        Ok(())
+    }
+
     async fn check_funding_confirmation<'a>(
         &self,
         competition: &'a mut Competition,
@@ -1833,8 +3178,9 @@ impl Coordinator {
         })?;
 
         let txid = funding_tx.compute_txid();
+        let required_confirmations = self.settings.funding_required_confirmations();
         match self.bitcoin.get_tx_confirmation_height(&txid).await? {
-            Some(confirmations) if confirmations >= self.required_confirmations => {
+            Some(confirmations) if confirmations >= required_confirmations => {
                 info!(
                     "Funding transaction {} confirmed with {} confirmations for competition {}",
                     txid, confirmations, competition.id
@@ -1846,7 +3192,7 @@ impl Coordinator {
             Some(confirmations) => {
                 debug!(
                     "Funding transaction {} has {} of {} required confirmations for competition {}",
-                    txid, confirmations, self.required_confirmations, competition.id
+                    txid, confirmations, required_confirmations, competition.id
                 );
             }
             None => {
@@ -1904,7 +3250,7 @@ impl Coordinator {
 
                     if competition.expiry_broadcasted_at.is_none() {
                         debug!("expiry_tx: {:?}", expiry_tx);
-                        self.bitcoin.broadcast(&expiry_tx).await?;
+                        broadcast_transaction(self.bitcoin.as_ref(), &expiry_tx).await?;
                         competition.expiry_broadcasted_at = Some(OffsetDateTime::now_utc())
                     };
 
@@ -1938,6 +3284,22 @@ impl Coordinator {
             "Oracle attestation added for competition {}",
             competition.id
         );
+        // Scores are final now, cache what they're computed from so the leaderboard stops
+        // hitting the oracle. A failed fetch is retried by the next leaderboard request.
+        match self.get_observation_data(competition).await {
+            Ok(data) => {
+                if let Err(e) = self.check_attested_winners(competition, &data).await {
+                    warn!(
+                        "Failed to check the attested winners of competition {}: {}",
+                        competition.id, e
+                    );
+                }
+            }
+            Err(e) => warn!(
+                "Failed to cache observation data for competition {}: {}",
+                competition.id, e
+            ),
+        }
         competition.errors = vec![];
 
         Ok(competition)
@@ -1998,7 +3360,8 @@ impl Coordinator {
         debug!("Transaction ID: {}", outcome_tx.compute_txid());
         competition.outcome_transaction = Some(outcome_tx.clone());
         if competition.outcome_broadcasted_at.is_none() {
-            self.bitcoin.broadcast(&outcome_tx).await?;
+            self.broadcast_unless_spent(WatchedOutputKind::Funding, &outcome_tx)
+                .await?;
             info!(
                 "Competition {} outcome tx broadcast: txid={}",
                 competition.id,
@@ -2032,7 +3395,8 @@ impl Coordinator {
 
                 if competition.expiry_broadcasted_at.is_none() {
                     debug!("expiry_tx: {:?}", expiry_tx);
-                    self.bitcoin.broadcast(&expiry_tx).await?;
+                    self.broadcast_unless_spent(WatchedOutputKind::Funding, &expiry_tx)
+                        .await?;
                     info!(
                         "Competition {} expiry tx broadcast: txid={}",
                         competition.id,
@@ -2049,44 +3413,134 @@ impl Coordinator {
         Ok(competition)
     }
 
-    pub async fn publish_delta_transactions<'a>(
+    /// Follow the expiry transaction until it confirms. Its fee was fixed when every player signed
+    /// the contract and its only output is behind the relative timelock, so the coordinator can
+    /// neither replace it nor bump it with a child. What it can do is keep it in mempools and flag
+    /// when it pays less than the current estimate.
+    pub async fn check_expiry_transaction(
         &self,
-        competition: &'a mut Competition,
-    ) -> Result<&'a mut Competition, anyhow::Error> {
+        competition: &Competition,
+        force_rebroadcast: bool,
+    ) -> Result<ExpiryTxStatus, anyhow::Error> {
         let Some(signed_contract) = competition.signed_contract.as_ref() else {
             return Err(anyhow!(
                 "No signed contract found for competition {}",
                 competition.id
             ));
         };
-
-        if competition.outcome_broadcasted_at.is_none() {
+        let Some(expiry_tx) = signed_contract.expiry_tx() else {
             return Err(anyhow!(
-                "No outcome broadcast timestamp found for competition {}",
+                "No expiry transaction found for competition {}",
                 competition.id
             ));
         };
-        let Some(outcome_transaction) = competition.outcome_transaction.as_ref() else {
-            return Err(anyhow!(
-                "No outcome transaction found for competition {}",
-                competition.id
-            ));
+        let txid = expiry_tx.compute_txid();
+
+        let chain_status = self.bitcoin.get_tx_chain_status(&txid).await?;
+        let fee_rate = expiry_tx_fee_rate(&expiry_tx, signed_contract.params().funding_value);
+        let fee_rates = self.bitcoin.get_estimated_fee_rates().await?;
+        let estimated_fee_rate = fee_rates.get(&1_u16).map_or(1, |rate| rate.ceil() as u64);
+
+        let rebroadcast = match chain_status {
+            TxChainStatus::NotFound => true,
+            TxChainStatus::Unconfirmed => force_rebroadcast,
+            TxChainStatus::Confirmed { .. } => false,
         };
-        let current_height = self.bitcoin.get_current_height().await?;
-        let Some(outcome_height) = self
-            .bitcoin
-            .get_tx_confirmation_height(&outcome_transaction.compute_txid())
-            .await?
-        else {
+        if rebroadcast {
+            if chain_status == TxChainStatus::NotFound {
+                warn!(
+                    "Competition {} expiry tx {} not known to the backend, rebroadcasting",
+                    competition.id, txid
+                );
+            }
+            broadcast_transaction(self.bitcoin.as_ref(), &expiry_tx).await?;
             info!(
-                "Outcome transaction not confirmed yet for competition {}",
-                competition.id
+                "Competition {} expiry tx rebroadcast: txid={}",
+                competition.id, txid
             );
-            return Ok(competition);
+        }
+
+        let status = ExpiryTxStatus {
+            txid: txid.to_string(),
+            confirmations: chain_status.confirmations(),
+            fee_rate_sat_vb: fee_rate.to_sat_per_vb_floor(),
+            estimated_fee_rate_sat_vb: estimated_fee_rate,
+            rebroadcast,
         };
+        if status.is_underpaying() {
+            warn!(
+                "Competition {} expiry tx {} pays {} sat/vB, below the current estimate of {} sat/vB; \
+                 player refunds may be delayed until mempool fees drop",
+                competition.id, txid, status.fee_rate_sat_vb, status.estimated_fee_rate_sat_vb
+            );
+        }
 
-        let blocks_since_outcome = current_height - outcome_height;
-        let required_delta = signed_contract.params().relative_locktime_block_delta as u32;
+        Ok(status)
+    }
+
+    /// Admin path to push a stuck expiry transaction back out and report its fee position
+    pub async fn rebroadcast_expiry_transaction(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<ExpiryTxStatus, Error> {
+        let competition = self.get_competition(competition_id).await?;
+        if !competition.is_expiry_broadcasted() {
+            return Err(Error::BadRequest(format!(
+                "Competition {} has not broadcast its expiry transaction",
+                competition_id
+            )));
+        }
+
+        self.check_expiry_transaction(&competition, true)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to rebroadcast expiry tx for competition {}: {}",
+                    competition_id, e
+                );
+                Error::BadRequest(e.to_string())
+            })
+    }
+
+    pub async fn publish_delta_transactions<'a>(
+        &self,
+        competition: &'a mut Competition,
+    ) -> Result<&'a mut Competition, anyhow::Error> {
+        let coordinator_key = self.competition_key(competition)?;
+        let Some(signed_contract) = competition.signed_contract.as_ref() else {
+            return Err(anyhow!(
+                "No signed contract found for competition {}",
+                competition.id
+            ));
+        };
+
+        if competition.outcome_broadcasted_at.is_none() {
+            return Err(anyhow!(
+                "No outcome broadcast timestamp found for competition {}",
+                competition.id
+            ));
+        };
+        let Some(outcome_transaction) = competition.outcome_transaction.as_ref() else {
+            return Err(anyhow!(
+                "No outcome transaction found for competition {}",
+                competition.id
+            ));
+        };
+        let current_height = self.bitcoin.get_current_height().await?;
+        let Some(outcome_height) = self
+            .bitcoin
+            .get_tx_confirmation_height(&outcome_transaction.compute_txid())
+            .await?
+        else {
+            info!(
+                "Outcome transaction not confirmed yet for competition {}",
+                competition.id
+            );
+            return Ok(competition);
+        };
+
+        let blocks_since_outcome = current_height - outcome_height;
+        let required_delta = signed_contract.params().relative_locktime_block_delta as u32;
 
         if blocks_since_outcome < required_delta {
             info!(
@@ -2258,7 +3712,7 @@ impl Coordinator {
                 &mut close_tx,
                 input_index,
                 &Prevouts::All(&[close_tx_prevout]),
-                self.private_key,
+                coordinator_key,
                 &winner_seckeys,
             )?;
 
@@ -2267,12 +3721,15 @@ impl Coordinator {
                     "Competition {} broadcasting unified close tx",
                     competition.id
                 );
-                self.bitcoin.broadcast(&close_tx).await?;
+                self.broadcast_unless_spent(WatchedOutputKind::Outcome, &close_tx)
+                    .await?;
                 info!(
                     "Competition {} unified close tx broadcast: txid={}",
                     competition.id,
                     close_tx.compute_txid()
                 );
+                self.record_contract_sweep(competition.id, None, &close_tx)
+                    .await;
                 competition.delta_broadcasted_at = Some(OffsetDateTime::now_utc());
             } else {
                 info!(
@@ -2378,7 +3835,8 @@ impl Coordinator {
                     .signed_split_tx(&win_cond, ticket_preimage)
                     .map_err(|e| anyhow!("Failed to build signed split TX: {}", e))?;
 
-                self.bitcoin.broadcast(&split_tx).await?;
+                self.broadcast_unless_spent(WatchedOutputKind::Outcome, &split_tx)
+                    .await?;
                 info!(
                     "Competition {} split tx broadcast: txid={}",
                     competition.id,
@@ -2434,17 +3892,33 @@ impl Coordinator {
                     &mut close_tx,
                     input_index,
                     &Prevouts::All(&[close_tx_prevout]),
-                    self.private_key,
+                    coordinator_key,
                     winner_seckey,
                 )?;
 
-                self.bitcoin.broadcast(&close_tx).await?;
+                // The split tx is already out, one winner taking their output on chain
+                // mustn't keep the others from being closed
+                match self
+                    .broadcast_unless_spent(WatchedOutputKind::Split, &close_tx)
+                    .await
+                {
+                    Err(e) if e.downcast_ref::<UnexpectedSpend>().is_some() => {
+                        warn!(
+                            "Competition {} split-close for player {} skipped: {}",
+                            competition.id, player_index, e
+                        );
+                        continue;
+                    }
+                    result => result?,
+                }
                 info!(
                     "Competition {} split-close tx broadcast for player {}: txid={}",
                     competition.id,
                     player_index,
                     close_tx.compute_txid()
                 );
+                self.record_contract_sweep(competition.id, Some(entry.id), &close_tx)
+                    .await;
 
                 // Mark entry as closed
                 self.competition_store
@@ -2461,6 +3935,7 @@ impl Coordinator {
         &self,
         competition: &'a mut Competition,
     ) -> Result<&'a mut Competition, anyhow::Error> {
+        let coordinator_key = self.competition_key(competition)?;
         let Some(signed_contract) = competition.signed_contract.as_ref() else {
             return Err(anyhow!(
                 "No signed contract found for competition {}",
@@ -2573,16 +4048,19 @@ impl Coordinator {
                     &mut reclaim_tx,
                     input_index,
                     &Prevouts::All(&[reclaim_tx_prevout]),
-                    self.private_key,
+                    coordinator_key,
                 )?;
 
-                self.bitcoin.broadcast(&reclaim_tx).await?;
+                self.broadcast_unless_spent(WatchedOutputKind::Split, &reclaim_tx)
+                    .await?;
                 info!(
                     "Competition {} split-reclaim tx broadcast for player {}: txid={}",
                     competition.id,
                     player_index,
                     reclaim_tx.compute_txid()
                 );
+                self.record_contract_sweep(competition.id, Some(entry.id), &reclaim_tx)
+                    .await;
 
                 self.competition_store
                     .mark_entry_reclaim_broadcast(entry.id, OffsetDateTime::now_utc())
@@ -2596,6 +4074,333 @@ impl Coordinator {
         Ok(competition)
     }
 
+    /// Append money movements to the ledger. The movement already happened on chain, so a
+    /// failed write is logged rather than failing the step that made it.
+    async fn record_ledger_entries(&self, competition_id: Uuid, entries: Vec<LedgerEntry>) {
+        if let Err(e) = self.competition_store.record_ledger_entries(entries).await {
+            error!(
+                "Failed to record ledger entries for competition {}: {}",
+                competition_id, e
+            );
+        }
+    }
+
+    /// Record the value a sweep brings back to the coordinator's wallet from the contract
+    async fn record_contract_sweep(
+        &self,
+        competition_id: Uuid,
+        entry_id: Option<Uuid>,
+        sweep_tx: &Transaction,
+    ) {
+        let swept = sweep_tx
+            .output
+            .iter()
+            .map(|output| output.value.to_sat())
+            .sum();
+        self.record_ledger_entries(
+            competition_id,
+            vec![LedgerEntry::new(
+                competition_id,
+                entry_id,
+                LedgerEntryKind::ContractSweep,
+                swept,
+                sweep_tx.compute_txid().to_string(),
+            )],
+        )
+        .await;
+    }
+
+    /// Profit and loss of every competition with recorded money movements
+    pub async fn get_pnl_report(&self) -> Result<PnlReport, Error> {
+        self.competition_store
+            .get_pnl_report()
+            .await
+            .map_err(Error::DbError)
+    }
+
+    /// Send the escrow outputs of no-show entries in cancelled competitions to the addresses
+    /// their players registered. Escrows of settled tickets go through the preimage branch once
+    /// its timelock matures, the rest through the cooperative branch right away.
+    async fn reclaim_no_show_escrows(&self) -> Result<(), anyhow::Error> {
+        let pending = self.competition_store.get_pending_escrow_reclaims().await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let coordinator_pubkey = self.bitcoin.get_public_key().await?;
+        let coordinator_key =
+            SecretKey::from_slice(&self.bitcoin.get_derived_private_key().await?.serialize())?;
+        // Reclaims aren't urgent, aim for confirmation within the hour
+        let fee_rates = self.bitcoin.get_estimated_fee_rates().await?;
+        let fee_rate = FeeRate::from_sat_per_vb_unchecked(
+            fee_rates.get(&6u16).cloned().unwrap_or(1.0).ceil() as u64,
+        );
+
+        for reclaim in pending {
+            if let Err(e) = self
+                .reclaim_escrow(&reclaim, &coordinator_pubkey, &coordinator_key, fee_rate)
+                .await
+            {
+                error!(
+                    "Failed to reclaim escrow of entry {} in competition {}: {}",
+                    reclaim.entry_id, reclaim.competition_id, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn reclaim_escrow(
+        &self,
+        reclaim: &PendingEscrowReclaim,
+        coordinator_pubkey: &BitcoinPublicKey,
+        coordinator_key: &SecretKey,
+        fee_rate: FeeRate,
+    ) -> Result<(), anyhow::Error> {
+        let ticket = self.competition_store.get_ticket(reclaim.ticket_id).await?;
+        let escrow_hex = ticket
+            .escrow_transaction
+            .as_ref()
+            .ok_or_else(|| anyhow!("Ticket {} has no escrow transaction", ticket.id))?;
+        let escrow_tx: Transaction = deserialize(&hex::decode(escrow_hex)?)?;
+        let user_pubkey = BitcoinPublicKey::from_str(
+            ticket
+                .ephemeral_pubkey
+                .as_deref()
+                .ok_or_else(|| anyhow!("Ticket {} has no ephemeral pubkey", ticket.id))?,
+        )?;
+        let preimage: [u8; 32] = hex::decode(&ticket.encrypted_preimage)?
+            .try_into()
+            .map_err(|_| anyhow!("Ticket {} preimage isn't 32 bytes", ticket.id))?;
+        let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
+
+        let descriptor = create_escrow_descriptor(coordinator_pubkey, &user_pubkey, &payment_hash)?;
+        let (outpoint, prevout) = find_escrow_output(&escrow_tx, &descriptor)?;
+        if self
+            .bitcoin
+            .get_tx_chain_status(&outpoint.txid)
+            .await?
+            .confirmations()
+            == 0
+        {
+            debug!(
+                "Escrow {} of entry {} isn't confirmed yet, waiting to reclaim it",
+                outpoint, reclaim.entry_id
+            );
+            return Ok(());
+        }
+
+        let destination = Address::from_str(&reclaim.address)?
+            .require_network(self.bitcoin.get_network())?
+            .script_pubkey();
+        let user_key = SecretKey::from_str(&reclaim.ephemeral_private_key)?;
+        // Settling the hold invoice revealed the preimage to the player, so its escrow is
+        // spent through the branch the player could use on their own
+        let path = if ticket.settled_at.is_some() {
+            EscrowReclaimPath::Preimage
+        } else {
+            EscrowReclaimPath::Cooperative
+        };
+        let reclaim_tx = build_escrow_reclaim_tx(
+            &EscrowReclaim {
+                descriptor,
+                outpoint,
+                prevout,
+                destination,
+                fee_rate,
+            },
+            path,
+            coordinator_key,
+            &user_key,
+            &preimage,
+        )?;
+
+        let broadcast = match path {
+            EscrowReclaimPath::Cooperative => {
+                broadcast_transaction(self.bitcoin.as_ref(), &reclaim_tx).await?;
+                true
+            }
+            EscrowReclaimPath::Preimage => {
+                self.bitcoin
+                    .broadcast_when_mature(&reclaim_tx, &outpoint.txid, ESCROW_CSV_BLOCKS)
+                    .await?
+            }
+        };
+        if !broadcast {
+            return Ok(());
+        }
+
+        info!(
+            "Competition {} escrow reclaim tx broadcast for entry {} through the {:?} branch: txid={}",
+            reclaim.competition_id,
+            reclaim.entry_id,
+            path,
+            reclaim_tx.compute_txid()
+        );
+        self.competition_store
+            .mark_entry_reclaim_broadcast(reclaim.entry_id, OffsetDateTime::now_utc())
+            .await?;
+        Ok(())
+    }
+
+    /// Look up every output the competition still expects to spend and resolve the ones spent by
+    /// someone else. Returns whether the competition or one of its entries changed.
+    async fn check_unexpected_spends(
+        &self,
+        competition: &mut Competition,
+    ) -> Result<bool, anyhow::Error> {
+        let entries = self
+            .competition_store
+            .get_competition_entries(competition.id, vec![EntryStatus::Paid])
+            .await?;
+        let tickets = self.competition_store.get_tickets(competition.id).await?;
+
+        let mut changed = false;
+        for watched in watched_outputs(competition, &entries, &tickets) {
+            let OutpointStatus::Spent { txid } =
+                self.bitcoin.get_outpoint_status(&watched.outpoint).await?
+            else {
+                continue;
+            };
+            let spending_tx = match watched.kind {
+                WatchedOutputKind::Outcome => Some(self.bitcoin.get_raw_transaction(&txid).await?),
+                _ => None,
+            };
+            let resolution = resolve_spend(
+                competition,
+                &watched,
+                txid,
+                spending_tx.as_ref(),
+                &entries,
+                &tickets,
+            );
+            if resolution == SpendResolution::Expected {
+                continue;
+            }
+
+            let spend = UnexpectedSpend {
+                kind: watched.kind,
+                outpoint: watched.outpoint,
+                spent_by: txid,
+            };
+            warn!(
+                "Competition {} {}, resolving as {:?}",
+                competition.id, spend, resolution
+            );
+            competition.record_error(CompetitionError::UnexpectedSpend(spend.to_string()));
+            changed = true;
+
+            let now = OffsetDateTime::now_utc();
+            match resolution {
+                SpendResolution::Expected => {}
+                SpendResolution::ExpiryBroadcast => competition.expiry_broadcasted_at = Some(now),
+                SpendResolution::UnilateralSplit { entry_id } => {
+                    self.competition_store
+                        .mark_entry_sellback_broadcast(entry_id, now)
+                        .await?;
+                }
+                SpendResolution::Foreign => {
+                    if let (WatchedOutputKind::Escrow, Some(entry_id)) =
+                        (watched.kind, watched.entry_id)
+                    {
+                        self.competition_store
+                            .cancel_entry(entry_id, &spend.to_string())
+                            .await?;
+                    }
+                    competition.failed_at = Some(now);
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Broadcast a contract transaction unless one of its inputs was already spent by another
+    /// transaction. That surfaces as `UnexpectedSpend`, which leaves the competition where it is
+    /// for the spend check to resolve on the next run. A transaction the mempool would refuse
+    /// isn't broadcast either, it surfaces as `BroadcastRejected`.
+    async fn broadcast_unless_spent(
+        &self,
+        kind: WatchedOutputKind,
+        transaction: &Transaction,
+    ) -> Result<(), anyhow::Error> {
+        let txid = transaction.compute_txid();
+        for input in &transaction.input {
+            match self
+                .bitcoin
+                .get_outpoint_status(&input.previous_output)
+                .await
+            {
+                Ok(OutpointStatus::Spent { txid: spent_by }) if spent_by != txid => {
+                    return Err(UnexpectedSpend {
+                        kind,
+                        outpoint: input.previous_output,
+                        spent_by,
+                    }
+                    .into());
+                }
+                Ok(_) => {}
+                // The broadcast itself reports what's wrong with the inputs
+                Err(e) => warn!(
+                    "Failed to look up input {} of {}: {}",
+                    input.previous_output, txid, e
+                ),
+            }
+        }
+
+        let tx_hex = consensus::encode::serialize_hex(transaction);
+        match self.bitcoin.test_mempool_accept(&tx_hex).await {
+            // Already known or not final yet, the broadcast reports those the usual way
+            Ok(MempoolAcceptance::Rejected { reason })
+                if classify_broadcast_error(&reason) == BroadcastErrorKind::Rejected =>
+            {
+                error!("Mempool rejected {} spend {}: {}", kind, txid, reason);
+                let diagnosis = self.diagnose_rejected(transaction).await;
+                return Err(BroadcastRejected {
+                    kind,
+                    txid,
+                    reason,
+                    diagnosis,
+                }
+                .into());
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to test mempool acceptance of {}: {}", txid, e),
+        }
+
+        broadcast_transaction(self.bitcoin.as_ref(), transaction).await?;
+        Ok(())
+    }
+
+    /// Verify the witnesses of a transaction the mempool refused against its prevouts
+    async fn diagnose_rejected(&self, transaction: &Transaction) -> Vec<String> {
+        let mut prevouts = Vec::new();
+        for input in &transaction.input {
+            let outpoint = input.previous_output;
+            let prevout = match self.bitcoin.get_raw_transaction(&outpoint.txid).await {
+                Ok(previous) => previous.output.get(outpoint.vout as usize).cloned(),
+                Err(e) => {
+                    warn!("Failed to look up prevout {}: {}", outpoint, e);
+                    None
+                }
+            };
+            let Some(prevout) = prevout else {
+                return vec![format!(
+                    "prevout {} unavailable, witnesses not verified",
+                    outpoint
+                )];
+            };
+            prevouts.push(prevout);
+        }
+
+        let faults = diagnose_witnesses(transaction, &prevouts);
+        if faults.is_empty() {
+            vec![String::from("witnesses verify locally")]
+        } else {
+            faults
+        }
+    }
+
     //Nonces from every entry into competition
     pub async fn get_received_nonces(
         &self,
@@ -2699,7 +4504,8 @@ impl Coordinator {
             }
             Err(e) => return Err(anyhow!("error getting stored public key: {}", e)),
         };
-        let dlc_pubkey = self.public_key;
+        // The metadata pins the wallet seed key, added signing keys don't change it
+        let dlc_pubkey = self.keys.public_key(None)?;
         let (xonly, _) = dlc_pubkey.into();
         let bitcoin_key = convert_xonly_key(xonly);
 
@@ -2707,14 +4513,14 @@ impl Coordinator {
             return Err(anyhow!(
                 "stored_pubkey: {:?} pem_pubkey: {:?}",
                 stored_public_key,
-                self.public_key()
+                bitcoin_key
             ));
         }
         Ok(())
     }
 
     async fn add_metadata(&self) -> Result<(), anyhow::Error> {
-        let dlc_pubkey = self.public_key;
+        let dlc_pubkey = self.keys.public_key(None)?;
         let (xonly, _) = dlc_pubkey.into();
         let bitcoin_key = convert_xonly_key(xonly);
 
@@ -2728,19 +4534,65 @@ impl Coordinator {
         &self,
         create_event: CreateEvent,
     ) -> Result<Competition, Error> {
-        let competition = Competition::new(&create_event);
-
-        if competition.event_submission.number_of_places_win > 5 {
-            return Err(Error::BadRequest(format!(
-                "Number of winners exceeds maximum allowed 5 {}",
-                competition.event_submission.number_of_places_win
-            )));
+        let mut competition = Competition::new(&create_event);
+        competition.coordinator_key_id = Some(self.keys.newest_id().to_string());
+        validate_create_event(
+            &create_event,
+            self.settings.max_total_allowed_entries(),
+            self.settings.max_pool_subsidy_sats(),
+            self.settings.max_invoice_settlement_confirmations(),
+            self.settings
+                .min_creation_lead_time_for(create_event.invoice_settlement_confirmations),
+        )?;
+        validate_tags(
+            &create_event.tags,
+            &self.settings.allowed_competition_tags(),
+        )?;
+        ContractSizeEstimate::for_event(&create_event).check(&self.contract_limits())?;
+        let (name_taken, slug_taken) = self
+            .competition_store
+            .competition_name_or_slug_taken(
+                competition.name.as_deref(),
+                competition.slug.as_deref(),
+            )
+            .await?;
+        if name_taken || slug_taken {
+            let mut errors = ValidationErrors::new();
+            if name_taken {
+                errors.push(
+                    "name",
+                    "name_taken",
+                    "another competition already uses this name",
+                );
+            }
+            if slug_taken {
+                errors.push(
+                    "slug",
+                    "slug_taken",
+                    "another competition already uses this slug",
+                );
+            }
+            return Err(errors.into());
+        }
+        let subsidy = create_event.pool_subsidy(create_event.total_allowed_entries);
+        if subsidy > Sats::ZERO {
+            info!(
+                "Competition {} pool of {} sats is subsidized by the coordinator with {} sats when every ticket sells",
+                create_event.id, create_event.total_competition_pool, subsidy
+            );
         }
 
         debug!("created competition");
-        let tickets = competition
-            .generate_competition_tickets(create_event.total_allowed_entries)
-            .await?;
+        // Keymeld's keygen session needs every ticket id up front, otherwise only a buffer is
+        // created and the rest are issued as players reserve them
+        let pre_created = if self.is_keymeld_enabled() {
+            create_event.total_allowed_entries
+        } else {
+            self.settings
+                .ticket_buffer_size()
+                .min(create_event.total_allowed_entries)
+        };
+        let tickets = competition.generate_competition_tickets(pre_created)?;
         debug!("tickets: {:?}", tickets);
 
         // First insert the competition into the database
@@ -2766,12 +4618,26 @@ impl Coordinator {
                 .map(|ticket| UserId::from(ticket.id))
                 .collect();
 
+            // Recorded so contract creation can check entries land on the same player indices
+            let ticket_ids: Vec<Uuid> = tickets.iter().map(|ticket| ticket.id).collect();
+            if let Err(e) = self
+                .competition_store
+                .set_player_order(competition.id, &player_order_from_tickets(&ticket_ids))
+                .await
+            {
+                error!(
+                    "Failed to store player order for competition {}: {}",
+                    competition.id, e
+                );
+            }
+
             // Pre-compute subset definitions for all possible outcomes
             // This is required because keymeld needs to know all subsets at keygen time
             let subset_info = compute_dlc_subset_definitions(
                 self.keymeld.coordinator_user_id(),
                 &player_user_ids,
                 competition.event_submission.number_of_places_win,
+                competition.event_submission.tie_policy,
             );
 
             info!(
@@ -2814,6 +4680,17 @@ impl Coordinator {
             }
         }
 
+        self.webhooks.notify(
+            competition.id,
+            "created",
+            format!(
+                "Competition {} created with {} entries at {} sats each",
+                competition.id,
+                competition.event_submission.total_allowed_entries,
+                competition.event_submission.entry_fee
+            ),
+        );
+
         Ok(competition)
     }
 
@@ -2827,1403 +4704,6181 @@ impl Coordinator {
             .await
     }
 
-    pub async fn request_ticket(
+    /// Competitions `viewer` may see: public ones, plus private ones they were invited to.
+    /// Admins see every competition.
+    pub async fn get_visible_competitions(
+        &self,
+        viewer: Option<&nostr_sdk::PublicKey>,
+    ) -> Result<Vec<Competition>, Error> {
+        let competitions = self.get_competitions().await?;
+        let invited = match viewer {
+            Some(viewer) if self.settings.is_admin(viewer) => return Ok(competitions),
+            Some(viewer) => self
+                .competition_store
+                .get_invited_competition_ids(&viewer.to_hex())
+                .await
+                .map_err(|e| {
+                    error!("failed to get invited competitions: {:?}", e);
+                    Error::DbError(e)
+                })?,
+            None => vec![],
+        };
+
+        Ok(competitions
+            .into_iter()
+            .filter(|competition| {
+                !competition.event_submission.private || invited.contains(&competition.id)
+            })
+            .collect())
+    }
+
+    /// Invite `pubkeys` (hex or npub) to a private competition until its entries close,
+    /// returning everyone invited so far
+    pub async fn invite_to_competition(
         &self,
-        pubkey: String,
         competition_id: Uuid,
-        btc_pubkey: BitcoinPublicKey,
-    ) -> Result<TicketResponse, Error> {
-        let competition = self
+        pubkeys: Vec<String>,
+    ) -> Result<Vec<String>, Error> {
+        let competition = self.get_competition(competition_id).await?;
+        if !competition.event_submission.private {
+            return Err(Error::BadRequest(format!(
+                "Competition {} is not private",
+                competition_id
+            )));
+        }
+        let entries_close = competition.event_submission.signing_window_end();
+        if OffsetDateTime::now_utc() >= entries_close {
+            return Err(Error::BadRequest(format!(
+                "Entries to competition {} closed at {}",
+                competition_id, entries_close
+            )));
+        }
+
+        let mut errors = ValidationErrors::new();
+        let mut invited = Vec::with_capacity(pubkeys.len());
+        for pubkey in &pubkeys {
+            match nostr_sdk::PublicKey::parse(pubkey) {
+                Ok(pubkey) => invited.push(pubkey.to_hex()),
+                Err(e) => errors.push(
+                    "pubkeys",
+                    "invalid_pubkey",
+                    format!("Invalid pubkey {}: {}", pubkey, e),
+                ),
+            }
+        }
+        if pubkeys.is_empty() {
+            errors.push("pubkeys", "required", "At least one pubkey is required");
+        }
+        errors.into_result()?;
+
+        let added = self
             .competition_store
-            .get_competition(competition_id)
+            .add_invitations(competition_id, invited)
             .await?;
-        if competition.total_entries as usize >= competition.event_submission.total_allowed_entries
+        info!(
+            "Invited {} new pubkeys to competition {}",
+            added, competition_id
+        );
+
+        Ok(self
+            .competition_store
+            .get_invitations(competition_id)
+            .await?)
+    }
+
+    /// Private competitions only take tickets and entries from invited pubkeys
+    async fn ensure_invited(&self, competition: &Competition, pubkey: &str) -> Result<(), Error> {
+        if !competition.event_submission.private
+            || self
+                .competition_store
+                .is_invited(competition.id, pubkey)
+                .await?
         {
-            return Err(Error::CompetitionFull);
+            return Ok(());
         }
-        debug!("got competition: {:?}", competition);
+        Err(Error::NotInvited(competition.id))
+    }
 
-        // Get ticket
-        let ticket = self
+    /// Active competitions that need an operator's attention
+    pub async fn get_stuck_competition_report(
+        &self,
+        thresholds: &StuckThresholds,
+    ) -> Result<StuckCompetitionReport, Error> {
+        let competitions = self
             .competition_store
-            .get_and_reserve_ticket(competition_id, &pubkey)
+            .get_competitions(true, false)
             .await
-            .map_err(|e| match e {
-                sqlx::Error::RowNotFound => Error::NoAvailableTickets,
-                e => Error::DbError(e),
+            .map_err(|e| {
+                error!("failed to get active competitions: {:?}", e);
+                Error::DbError(e)
             })?;
-        match self
-            .create_ticket_response(ticket.clone(), btc_pubkey, competition)
+        let payout_failures = self
+            .competition_store
+            .get_payout_failure_counts()
             .await
-        {
-            Ok(response) => Ok(response),
-            Err(e) => {
-                if let Err(clear_err) = self
-                    .competition_store
-                    .clear_ticket_reservation(ticket.id)
-                    .await
-                {
-                    error!(
-                        "Failed to clear ticket reservation for ticket {}: {}",
-                        ticket.id, clear_err
-                    );
-                }
-                Err(e)
-            }
-        }
-    }
-
-    async fn create_ticket_response(
-        &self,
-        ticket: Ticket,
-        btc_pubkey: BitcoinPublicKey,
-        competition: Competition,
-    ) -> Result<TicketResponse, Error> {
-        // Decode preimage from encrypted_preimage
-        let preimage = hex::decode(&ticket.encrypted_preimage)
-            .map_err(|_| Error::BadRequest("Invalid preimage".into()))?;
-
-        // Calculate payment hash from preimage
-        let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
-
-        // Generate escrow transaction only if escrow is enabled
-        let escrow_tx_hex = if self.escrow_enabled {
-            let escrow_tx = generate_escrow_tx(
-                self.bitcoin.clone(),
-                ticket.id,
-                btc_pubkey,
-                payment_hash,
-                competition.event_submission.entry_fee as u64,
-            )
+            .map_err(|e| {
+                error!("failed to get payout failure counts: {:?}", e);
+                Error::DbError(e)
+            })?;
+        let unsettled_tickets = self
+            .competition_store
+            .get_unsettled_paid_ticket_counts()
             .await
             .map_err(|e| {
-                error!("Failed to generate escrow transaction: {}", e);
-                Error::BadRequest(format!("Failed to generate refund transaction: {}", e))
+                error!("failed to get unsettled ticket counts: {:?}", e);
+                Error::DbError(e)
             })?;
 
-            debug!("escrow_tx: {:?}", escrow_tx);
-
-            let escrow_hex = hex::encode(dlctix::bitcoin::consensus::encode::serialize(&escrow_tx));
-
-            // Store the escrow transaction in the database
-            self.competition_store
-                .update_ticket_escrow(ticket.id, btc_pubkey.to_string(), escrow_hex.clone())
-                .await
-                .map_err(|e| {
-                    error!("Failed to update ticket with escrow transaction: {}", e);
-                    Error::DbError(e)
-                })?;
+        Ok(StuckCompetitionReport::build(
+            &competitions,
+            &payout_failures,
+            &unsettled_tickets,
+            thresholds,
+            OffsetDateTime::now_utc(),
+        ))
+    }
 
-            debug!(
-                "Created ticket {} with escrow tx {}",
-                ticket.id,
-                escrow_tx.compute_txid()
-            );
+    /// The player order keymeld was given at creation next to the order the contract uses,
+    /// without assigning player indices
+    pub async fn get_player_order_report(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<PlayerOrderReport, Error> {
+        let keymeld_order = self
+            .competition_store
+            .get_player_order(competition_id)
+            .await?;
+        let mut entries = self
+            .competition_store
+            .get_competition_entries(competition_id, vec![EntryStatus::Paid])
+            .await?;
 
-            Some(escrow_hex)
-        } else {
-            debug!(
-                "Created ticket {} without escrow (escrow disabled)",
-                ticket.id
-            );
-            None
-        };
+        let mut mismatches = Vec::new();
+        if let Err(e) = order_contract_entries(&mut entries) {
+            mismatches.push(e.to_string());
+        }
+        if let Some(keymeld_order) = &keymeld_order {
+            if let Err(e) = verify_player_order(keymeld_order, &entries) {
+                mismatches.extend(e.differences);
+            }
+        }
 
-        let fee_multiplier = competition.event_submission.coordinator_fee_percentage as f64 / 100.0;
-        let coordinator_fee =
-            (competition.event_submission.entry_fee as f64 * fee_multiplier).round() as u64;
+        Ok(PlayerOrderReport {
+            competition_id,
+            keymeld_order,
+            contract_order: player_order_from_entries(&entries),
+            mismatches,
+        })
+    }
 
-        let full_fee = (competition.event_submission.entry_fee as u64) + coordinator_fee;
+    /// Every stored JSON blob that no longer decodes into its column's type, so an operator
+    /// can find rows a migration or manual edit broke before the watchers trip over them
+    pub async fn find_undecodable_blobs(&self) -> Result<Vec<UndecodableBlob>, Error> {
+        self.competition_store
+            .find_undecodable_blobs()
+            .await
+            .map_err(|e| {
+                error!("failed to scan stored blobs: {:?}", e);
+                Error::DbError(e)
+            })
+    }
 
-        // Check if ticket already has a payment request (reuse existing invoice if not expired)
-        // Invoice needs to stay active through:
-        // 1. Registration period (until start_observation_date when entries close)
-        // 2. Keymeld signing (~5 min buffer)
-        // 3. Funding transaction broadcast (~5 min buffer)
-        let now = time::OffsetDateTime::now_utc();
-        let time_until_entries_close =
-            (competition.event_submission.start_observation_date - now).whole_seconds();
-        let buffer_seconds = 600i64; // 10 minutes buffer for signing + broadcast
-        let invoice_expiry_seconds = (time_until_entries_close + buffer_seconds).max(900); // minimum 15 min
-        let payment_request = if let Some(existing_payment_request) = &ticket.payment_request {
-            // Check if the existing invoice has expired
-            let is_expired = ticket
-                .invoice_expires_at
-                .map(|expires_at| expires_at < time::OffsetDateTime::now_utc())
-                .unwrap_or(true); // If no expiry stored, treat as expired to be safe
+    /// Cross-check stored broadcast/confirmation timestamps of non-terminal competitions against
+    /// the chain backend. Nothing is written, failed lookups are listed in the report.
+    pub async fn get_consistency_report(&self) -> Result<ConsistencyReport, Error> {
+        let competitions: Vec<Competition> = self
+            .competition_store
+            .get_competitions(true, false)
+            .await
+            .map_err(|e| {
+                error!("failed to get active competitions: {:?}", e);
+                Error::DbError(e)
+            })?
+            .into_iter()
+            .filter(|competition| !competition.skip_competition())
+            .collect();
 
-            if is_expired {
-                debug!(
-                    "Existing invoice for ticket {} has expired, creating new one",
-                    ticket.id
-                );
-                // Cancel the old invoice before creating a new one
-                if let Err(e) = self.ln.cancel_hold_invoice(hex::encode(payment_hash)).await {
-                    // Log but don't fail - the invoice might already be cancelled or not exist
-                    debug!("Failed to cancel expired invoice: {}", e);
+        let mut chain_statuses = HashMap::new();
+        for competition in &competitions {
+            for audited in ConsistencyReport::audited_transactions(competition) {
+                let Some(txid) = audited.txid else {
+                    continue;
+                };
+                if chain_statuses.contains_key(&txid) {
+                    continue;
+                }
+                match self.bitcoin.get_tx_chain_status(&txid).await {
+                    Ok(status) => {
+                        chain_statuses.insert(txid, status);
+                    }
+                    Err(e) => warn!(
+                        "Consistency check failed to look up {} tx {} for competition {}: {}",
+                        audited.transaction, txid, competition.id, e
+                    ),
                 }
+            }
+        }
 
-                // Create new HODL invoice
-                let invoice = self
-                    .ln
-                    .add_hold_invoice(
-                        full_fee,
-                        invoice_expiry_seconds as u64,
-                        hex::encode(payment_hash),
-                        ticket.competition_id,
-                        escrow_tx_hex.clone().unwrap_or_default(),
-                    )
-                    .await
-                    .map_err(|e| {
-                        error!("Failed to create HODL invoice: {}", e);
-                        Error::BadRequest(format!("Failed to create invoice: {}", e))
-                    })?;
+        Ok(ConsistencyReport::build(
+            &competitions,
+            &chain_statuses,
+            OffsetDateTime::now_utc(),
+        ))
+    }
 
-                let expires_at = time::OffsetDateTime::now_utc()
-                    + time::Duration::seconds(invoice_expiry_seconds);
+    /// Funding active competitions still need from the wallet and the payouts still owed,
+    /// against the wallet balance
+    pub async fn get_solvency_report(&self) -> Result<SolvencyReport, Error> {
+        let competitions: Vec<Competition> = self
+            .competition_store
+            .get_competitions(true, false)
+            .await
+            .map_err(|e| {
+                error!("failed to get active competitions: {:?}", e);
+                Error::DbError(e)
+            })?
+            .into_iter()
+            .filter(|competition| !competition.skip_competition())
+            .collect();
 
-                // Update ticket with new payment request and expiry
+        let mut solvencies = Vec::with_capacity(competitions.len());
+        for competition in &competitions {
+            let escrow_funded = if self.escrow_enabled {
                 self.competition_store
-                    .update_ticket_payment_request(ticket.id, &invoice.payment_request, expires_at)
-                    .await
-                    .map_err(|e| {
-                        error!("Failed to update ticket with payment request: {}", e);
-                        Error::DbError(e)
-                    })?;
-
-                invoice.payment_request
+                    .get_tickets(competition.id)
+                    .await?
+                    .values()
+                    .filter(|ticket| ticket.escrow_transaction.is_some())
+                    .count()
             } else {
-                debug!("Reusing existing payment request for ticket {}", ticket.id);
-                existing_payment_request.clone()
+                0
+            };
+
+            let mut outstanding_payouts = Sats::ZERO;
+            if competition.is_attested() && competition.is_outcome_broadcasted() {
+                let entries = self
+                    .competition_store
+                    .get_competition_entries(competition.id, vec![])
+                    .await?;
+                for entry in entries {
+                    let entry_payouts = self
+                        .competition_store
+                        .get_entry_payouts(entry.id, None)
+                        .await?;
+                    if let Some(payout) = EligiblePayout::new(competition, &entry, &entry_payouts) {
+                        if !matches!(payout.payout_status, Some(PayoutStatus::Succeeded)) {
+                            outstanding_payouts =
+                                outstanding_payouts.saturating_add(payout.payout_amount_sats);
+                        }
+                    }
+                }
             }
-        } else {
-            // Create new HODL invoice
-            // Note: escrow_tx_hex is empty string when escrow disabled
-            let invoice = self
-                .ln
-                .add_hold_invoice(
-                    full_fee,
-                    invoice_expiry_seconds as u64,
-                    hex::encode(payment_hash),
-                    ticket.competition_id,
-                    escrow_tx_hex.clone().unwrap_or_default(),
-                )
-                .await
-                .map_err(|e| {
-                    error!("Failed to create HODL invoice: {}", e);
-                    Error::BadRequest(format!("Failed to create invoice: {}", e))
-                })?;
 
-            let expires_at =
-                time::OffsetDateTime::now_utc() + time::Duration::seconds(invoice_expiry_seconds);
+            solvencies.push(CompetitionSolvency::new(
+                competition,
+                self.escrow_enabled,
+                escrow_funded,
+                outstanding_payouts,
+            ));
+        }
 
-            // Update ticket with payment request and expiry
-            self.competition_store
-                .update_ticket_payment_request(ticket.id, &invoice.payment_request, expires_at)
-                .await
-                .map_err(|e| {
-                    error!("Failed to update ticket with payment request: {}", e);
-                    Error::DbError(e)
-                })?;
+        let balance = self.bitcoin.get_balance().await.map_err(Error::Bitcoin)?;
+        let pending = balance.trusted_pending + balance.untrusted_pending + balance.immature;
+        let report = SolvencyReport::build(
+            solvencies,
+            Sats(balance.confirmed.to_sat()),
+            Sats(pending.to_sat()),
+            OffsetDateTime::now_utc(),
+        );
+        if !report.is_solvent() {
+            warn!(
+                "Confirmed balance of {} sats is {} sats short of committed competition funding",
+                report.confirmed_balance_sats, report.shortfall_sats
+            );
+        }
 
-            invoice.payment_request
-        };
+        Ok(report)
+    }
 
-        // Get keymeld session info if available (created at competition creation)
-        let (keymeld_session_id, keymeld_enclave_public_key) = if self.is_keymeld_enabled() {
-            if let Some(stored_session) = self
-                .competition_store
-                .get_keymeld_session(competition.id)
-                .await
-                .ok()
-                .flatten()
-            {
-                // Decrypt session secret to get the full session
-                let session_secret =
-                    self.decrypt_session_secret(&stored_session.encrypted_session_secret)?;
-                let session = stored_session.to_session(session_secret);
+    /// Operator cancel, shared with the `competition cancel` CLI command
+    pub async fn cancel_competition(&self, competition_id: Uuid) -> Result<Competition, Error> {
+        let (competition, previous_state) =
+            super::cancel_competition(&self.competition_store, competition_id).await?;
+        info!(
+            "Competition {} cancelled by operator from state {}",
+            competition_id, previous_state
+        );
+        self.notify_transition(competition_id, &previous_state, "cancelled");
+        Ok(competition)
+    }
 
-                // Get the user's assigned enclave public key
-                // ticket_id is used as keymeld user_id
-                let user_id = UserId::from(ticket.id);
-                let enclave_pubkey = self
-                    .keymeld
-                    .get_user_enclave_pubkey(&session, user_id)
-                    .await
-                    .map_err(|e| {
-                        error!("Failed to get user enclave pubkey: {}", e);
-                        Error::BadRequest(format!("Failed to get enclave info: {}", e))
-                    })?;
+    /// Operator override marking a competition completed after its winners were paid by hand
+    pub async fn force_complete_competition(
+        &self,
+        competition_id: Uuid,
+        reason: &str,
+        admin: &str,
+    ) -> Result<Competition, Error> {
+        let (competition, previous_state) = super::force_complete_competition(
+            &self.competition_store,
+            competition_id,
+            reason,
+            admin,
+        )
+        .await?;
+        warn!(
+            "Competition {} force completed by {} from state {}: {}",
+            competition_id, admin, previous_state, reason
+        );
+        self.notify_transition(competition_id, &previous_state, "completed");
+        Ok(competition)
+    }
 
-                (Some(stored_session.session_id), Some(enclave_pubkey))
-            } else {
-                (None, None)
-            }
-        } else {
-            (None, None)
-        };
+    /// Operator retry of a failed competition, shared with the `competition retry` CLI command
+    pub async fn retry_competition(&self, competition_id: Uuid) -> Result<Competition, Error> {
+        let competition = super::retry_competition(&self.competition_store, competition_id).await?;
+        let new_state = competition.get_state().to_string();
+        info!(
+            "Competition {} retried by operator, resuming in state {}",
+            competition_id, new_state
+        );
+        self.notify_transition(competition_id, "failed", &new_state);
+        self.kicks.kick_competitions();
+        Ok(competition)
+    }
 
-        Ok(TicketResponse {
-            ticket_id: ticket.id,
-            payment_request,
-            escrow_tx: escrow_tx_hex,
-            payment_hash: hex::encode(payment_hash),
-            amount_sats: full_fee,
-            // ticket_id is used as the keymeld user_id for consistency
-            keymeld_user_id: ticket.id,
-            keymeld_gateway_url: self.keymeld_gateway_url.clone(),
-            keymeld_session_id,
-            keymeld_enclave_public_key,
-        })
+    pub async fn release_ticket(&self, ticket_id: Uuid) -> Result<Ticket, Error> {
+        let ticket = super::release_ticket(&self.competition_store, ticket_id).await?;
+        info!(
+            "Ticket {} for competition {} released by operator",
+            ticket_id, ticket.competition_id
+        );
+        Ok(ticket)
     }
 
-    pub async fn get_ticket_status(
+    /// Mark a player's reserved ticket as prepaid so they enter for free. The hold invoice is
+    /// cancelled instead of paid and the coordinator's wallet covers the entry fee when it
+    /// funds the contract. `comped_by` is the admin recorded against the ticket.
+    pub async fn comp_ticket(
         &self,
-        user_pubkey: String,
-        competition_id: Uuid,
         ticket_id: Uuid,
-    ) -> Result<TicketStatus, Error> {
+        comped_by: &str,
+        note: Option<String>,
+    ) -> Result<Ticket, Error> {
         let ticket = self
             .competition_store
             .get_ticket(ticket_id)
             .await
-            .map_err(|e| {
-                debug!("error: {:?}", e);
-                match e {
-                    sqlx::Error::RowNotFound => Error::NotFound("Ticket not found".into()),
-                    e => Error::DbError(e),
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => {
+                    Error::NotFound(format!("ticket {} not found", ticket_id))
                 }
+                e => Error::DbError(e),
             })?;
-
-        // Verify this ticket belongs to this competition
-        if ticket.competition_id != competition_id {
+        if self.escrow_enabled {
+            // Escrow tickets are funded by their escrow transaction, which only goes out once
+            // the hold invoice is paid
             return Err(Error::BadRequest(
-                "Ticket does not belong to this competition".into(),
+                "Tickets can't be comped while escrow is enabled".into(),
             ));
         }
+        if ticket.entry_id.is_some() || ticket.paid_at.is_some() || ticket.settled_at.is_some() {
+            return Err(Error::BadRequest(format!(
+                "Ticket {} has already been paid",
+                ticket_id
+            )));
+        }
+        if ticket.reserved_by.is_none() || ticket.ephemeral_pubkey.is_none() {
+            return Err(Error::BadRequest(format!(
+                "Ticket {} has to be reserved by the player before it can be comped",
+                ticket_id
+            )));
+        }
 
-        // Verify this ticket was reserved by this user
-        if ticket.reserved_by.as_deref() != Some(&user_pubkey) {
-            return Err(Error::BadRequest("Ticket not reserved by this user".into()));
+        let competition = self.get_competition(ticket.competition_id).await?;
+        let entries_close = competition.event_submission.signing_window_end();
+        if competition.get_state() != CompetitionState::Created
+            || OffsetDateTime::now_utc() >= entries_close
+        {
+            return Err(Error::BadRequest(format!(
+                "Entries to competition {} are closed",
+                competition.id
+            )));
         }
 
-        Ok(ticket.get_status())
+        if ticket.payment_request.is_some() {
+            self.cancel_comped_invoice(&ticket).await?;
+        }
+        if !self
+            .competition_store
+            .comp_ticket(ticket_id, comped_by, note)
+            .await?
+        {
+            return Err(Error::BadRequest(format!(
+                "Ticket {} was paid or released while it was being comped",
+                ticket_id
+            )));
+        }
+        info!(
+            "Ticket {} for competition {} comped by {}, coordinator covers its {} sat entry fee",
+            ticket_id, competition.id, comped_by, competition.event_submission.entry_fee
+        );
+        self.watcher_kicks().kick_competitions();
+
+        Ok(self.competition_store.get_ticket(ticket_id).await?)
     }
 
-    pub async fn get_competition(&self, competition_id: Uuid) -> Result<Competition, Error> {
-        self.competition_store
-            .get_competition(competition_id)
-            .map_err(|e| {
-                error!(
-                    "failed to get competition: competition_id {} {:?}",
-                    competition_id, e
-                );
-                Error::DbError(e)
-            })
+    /// Cancel a ticket's hold invoice before comping it, so the player can't also pay it
+    async fn cancel_comped_invoice(&self, ticket: &Ticket) -> Result<(), Error> {
+        match self
+            .ln
+            .lookup_invoice(&ticket.hash, ticket.ln_backend_id.as_deref())
+            .await
+        {
+            Ok(invoice) if invoice.state == InvoiceState::Canceled => return Ok(()),
+            Ok(invoice)
+                if matches!(
+                    invoice.state,
+                    InvoiceState::Accepted | InvoiceState::Settled
+                ) =>
+            {
+                return Err(Error::BadRequest(format!(
+                    "Ticket {} invoice is already {:?}, it can't be comped",
+                    ticket.id, invoice.state
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => debug!("Failed to lookup invoice for ticket {}: {}", ticket.id, e),
+        }
+
+        self.ln
+            .cancel_hold_invoice(ticket.hash.clone(), ticket.ln_backend_id.as_deref())
             .await
+            .map_err(Error::LnError)
     }
 
-    /// Delete a competition by ID. Only allowed if no entries have been paid.
-    pub async fn delete_competition(&self, competition_id: Uuid) -> Result<(), Error> {
-        // First check if competition exists and has no paid entries
+    /// Add a sponsor's sats to a competition's pool. The funds have to be an unspent output of
+    /// the coordinator's wallet or a settled invoice of its lightning node worth at least the
+    /// amount, and the pool can only grow until the contract is built. `recorded_by` is the
+    /// admin recorded against the sponsorship.
+    pub async fn add_sponsorship(
+        &self,
+        competition_id: Uuid,
+        request: AddSponsorship,
+        recorded_by: &str,
+    ) -> Result<Sponsorship, Error> {
+        if request.amount_sats == Sats::ZERO {
+            return Err(Error::BadRequest(
+                "Sponsorship amount must be more than 0 sats".into(),
+            ));
+        }
         let competition = self.get_competition(competition_id).await?;
-
-        if competition.total_paid_entries > 0 {
+        if competition.contract_parameters.is_some()
+            || competition.funding_psbt_base64.is_some()
+            || competition.cancelled_at.is_some()
+            || competition.failed_at.is_some()
+        {
             return Err(Error::BadRequest(format!(
-                "Cannot delete competition {} - it has {} paid entries",
-                competition_id, competition.total_paid_entries
+                "Competition {} is {}, its pool can no longer change",
+                competition.id,
+                competition.get_state()
+            )));
+        }
+        if self.in_flight.contains(competition.id) {
+            return Err(Error::BadRequest(format!(
+                "Competition {} is being processed, try again shortly",
+                competition.id
             )));
         }
 
-        self.competition_store
-            .delete_competition(competition_id)
-            .await
-            .map_err(|e| {
-                error!(
-                    "failed to delete competition: competition_id {} {:?}",
-                    competition_id, e
-                );
-                Error::DbError(e)
-            })?;
-
-        info!("Deleted competition: {}", competition_id);
-        Ok(())
-    }
+        let received = self.received_sponsor_funds(&request.funds).await?;
+        if received < request.amount_sats {
+            return Err(Error::BadRequest(format!(
+                "Sponsor funds {} only hold {} sats, less than the {} sat sponsorship",
+                request.funds.reference(),
+                received,
+                request.amount_sats
+            )));
+        }
 
-    pub async fn handle_invoice_accepted(
-        &self,
-        competition_id: Uuid,
-        ticket_hash: &str,
-    ) -> Result<(), Error> {
-        self.competition_store
-            .mark_ticket_paid(ticket_hash, competition_id)
+        let sponsorship = Sponsorship {
+            id: Uuid::now_v7(),
+            competition_id: competition.id,
+            amount_sats: request.amount_sats,
+            funds: request.funds,
+            sponsor: request
+                .sponsor
+                .map(|sponsor| sponsor.trim().to_string())
+                .filter(|sponsor| !sponsor.is_empty()),
+            recorded_by: recorded_by.to_string(),
+            created_at: OffsetDateTime::now_utc(),
+        };
+        match self
+            .competition_store
+            .add_sponsorship(sponsorship.clone())
             .await
-            .map_err(|e| match e {
-                sqlx::Error::RowNotFound => {
-                    Error::BadRequest("Invalid ticket or competition".into())
-                }
-                e => Error::DbError(e),
-            })?;
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(Error::BadRequest(format!(
+                    "Competition {} committed its pool before the sponsorship was recorded",
+                    competition.id
+                )))
+            }
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                return Err(Error::BadRequest(format!(
+                    "Sponsor funds {} already sponsor a competition",
+                    sponsorship.funds.reference()
+                )))
+            }
+            Err(e) => return Err(Error::DbError(e)),
+        }
+        self.record_ledger_entries(
+            competition.id,
+            vec![LedgerEntry::new(
+                competition.id,
+                None,
+                LedgerEntryKind::SponsorContribution,
+                sponsorship.amount_sats.to_sat(),
+                sponsorship.funds.reference(),
+            )],
+        )
+        .await;
+        info!(
+            "Competition {} pool sponsored with {} sats from {} by {}, recorded by {}",
+            competition.id,
+            sponsorship.amount_sats,
+            sponsorship.funds.reference(),
+            sponsorship
+                .sponsor
+                .as_deref()
+                .unwrap_or("an anonymous sponsor"),
+            recorded_by
+        );
 
-        Ok(())
+        Ok(sponsorship)
     }
 
-    pub async fn add_entry(&self, pubkey: String, entry: AddEntry) -> Result<UserEntry, Error> {
-        let competition = self
+    pub async fn get_sponsorships(&self, competition_id: Uuid) -> Result<Vec<Sponsorship>, Error> {
+        Ok(self
             .competition_store
-            .get_competition(entry.event_id)
-            .await
-            .map_err(|e| {
-                error!("error {:?}", e);
-                match e {
-                    sqlx::Error::RowNotFound => Error::BadRequest("Competition not found".into()),
-                    e => Error::DbError(e),
-                }
-            })?;
-
-        validate_entry(entry.clone().into(), competition).await?;
+            .get_sponsorships(competition_id)
+            .await?)
+    }
 
-        debug!("entry: {:?}", entry);
-        let ticket = self
+    /// Each save of the competition's state and the fields it changed, oldest first
+    pub async fn get_competition_changes(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<Vec<CompetitionChange>, Error> {
+        Ok(self
             .competition_store
-            .get_ticket(entry.ticket_id)
-            .await
-            .map_err(|e| {
-                error!("error {:?}", e);
-                match e {
-                    sqlx::Error::RowNotFound => Error::BadRequest("Ticket not found".into()),
-                    e => Error::DbError(e),
-                }
-            })?;
+            .get_competition_changes(competition_id)
+            .await?)
+    }
 
-        if ticket.reserved_by.as_deref() != Some(&pubkey) {
-            return Err(Error::BadRequest("Ticket not reserved by this user".into()));
+    pub fn contract_limits(&self) -> ContractLimits {
+        ContractLimits {
+            max_outcomes: self.settings.max_contract_outcomes(),
+            max_memory_bytes: self.settings.max_contract_memory_bytes(),
         }
+    }
 
-        if ticket.paid_at.is_none() {
-            return Err(Error::BadRequest("Ticket has not been paid".into()));
-        }
+    /// Creates a new competition from the parameters of `source_id` with `overrides` applied,
+    /// going through the same validation and setup as any other new competition
+    pub async fn clone_competition(
+        &self,
+        source_id: Uuid,
+        overrides: CloneCompetition,
+    ) -> Result<ClonedCompetition, Error> {
+        let source = self.get_competition(source_id).await?;
+        let sponsored = self
+            .get_sponsorships(source_id)
+            .await?
+            .iter()
+            .fold(Sats::ZERO, |total, sponsorship| {
+                total.saturating_add(sponsorship.amount_sats)
+            });
 
-        if ticket.entry_id.is_some() {
-            return Err(Error::BadRequest("Ticket has already been used".into()));
-        }
+        let create_event = cloned_create_event(&source.event_submission, sponsored, &overrides);
+        let changes = create_event_changes(&source.event_submission, &create_event);
+        let competition = self.create_competition(create_event).await?;
+        info!(
+            "competition {} cloned from {} with {} changed fields",
+            competition.id,
+            source_id,
+            changes.len()
+        );
 
-        if let Some(btc_pubkey) = &ticket.ephemeral_pubkey {
-            if btc_pubkey != &entry.ephemeral_pubkey {
-                return Err(Error::BadRequest(format!(
-                    "Entry public key {} must match ticket escrow public key {}",
-                    entry.ephemeral_pubkey, btc_pubkey
-                )));
-            }
-        }
+        Ok(ClonedCompetition {
+            competition,
+            source_id,
+            changes,
+        })
+    }
 
-        let user_entry = self
-            .competition_store
-            .add_entry(entry.clone().into_user_entry(pubkey), ticket.id)
-            .await
-            .map_err(|e| match e {
-                sqlx::Error::RowNotFound => {
-                    Error::BadRequest(
-                        "Failed to claim ticket - may have expired or been claimed by another entry"
-                            .into(),
-                    )
+    /// Sats the coordinator holds from sponsor funds, rejecting funds it doesn't control or
+    /// hasn't been paid
+    async fn received_sponsor_funds(&self, funds: &SponsorFunds) -> Result<Sats, Error> {
+        match funds {
+            SponsorFunds::Utxo { outpoint } => self
+                .bitcoin
+                .list_utxos()
+                .await
+                .into_iter()
+                .find(|output| output.outpoint == *outpoint)
+                .map(|output| Sats(output.txout.value.to_sat()))
+                .ok_or_else(|| {
+                    Error::BadRequest(format!(
+                        "{} is not an unspent output of the coordinator wallet",
+                        outpoint
+                    ))
+                }),
+            SponsorFunds::Lightning { payment_hash } => {
+                // Ticket invoices are already in the pool as entry fees
+                if self
+                    .competition_store
+                    .get_ticket_by_hash(payment_hash)
+                    .await?
+                    .is_some()
+                {
+                    return Err(Error::BadRequest(format!(
+                        "Invoice {} pays for a ticket, not a sponsorship",
+                        payment_hash
+                    )));
                 }
-                e => {
-                    error!(
-                        "entry added to oracle, but failed to be saved: entry_id {}, event_id {} {:?}",
-                        entry.id, entry.event_id, e
-                    );
-                    Error::DbError(e)
+                let invoice = self
+                    .ln
+                    .lookup_invoice(payment_hash, None)
+                    .await
+                    .map_err(|e| {
+                        Error::BadRequest(format!(
+                            "No invoice with payment hash {}: {}",
+                            payment_hash, e
+                        ))
+                    })?;
+                if invoice.state != InvoiceState::Settled {
+                    return Err(Error::BadRequest(format!(
+                        "Invoice {} is {:?}, it has to be settled to sponsor a pool",
+                        payment_hash, invoice.state
+                    )));
                 }
-            })?;
-
-        Ok(user_entry)
+                invoice.value.parse::<u64>().map(Sats).map_err(|e| {
+                    Error::LnError(anyhow!(
+                        "Invalid value {} of invoice {}: {}",
+                        invoice.value,
+                        payment_hash,
+                        e
+                    ))
+                })
+            }
+        }
     }
 
-    pub async fn get_entries(
-        &self,
-        pubkey: String,
-        filter: SearchBy,
-    ) -> Result<Vec<UserEntry>, Error> {
-        self.competition_store
-            .get_user_entries(pubkey, filter)
-            .map_err(Error::DbError)
-            .await
+    pub async fn list_pending_payouts(&self) -> Result<Vec<EntryPayout>, Error> {
+        super::list_pending_payouts(&self.competition_store).await
     }
 
-    /// Get lightweight entry views for the entries list page.
-    /// Single query that joins entries with competitions for dates and payout status.
-    pub async fn get_user_entry_views(&self, pubkey: String) -> Result<Vec<UserEntryView>, Error> {
-        self.competition_store
-            .get_user_entry_views(pubkey)
-            .map_err(Error::DbError)
-            .await
+    pub async fn list_failed_payouts(&self) -> Result<Vec<PayoutFailureCount>, Error> {
+        super::list_failed_payouts(&self.competition_store).await
     }
 
-    /// Get a single entry by ID (public, for leaderboard entry details)
-    pub async fn get_entry_by_id(&self, entry_id: Uuid) -> Result<Option<UserEntry>, Error> {
-        self.competition_store
-            .get_entry_by_id(entry_id)
-            .map_err(Error::DbError)
+    /// Competitions currently accepting entries, for the public aggregator feeds
+    pub async fn get_open_competition_feed(
+        &self,
+        base_url: &str,
+    ) -> Result<OpenCompetitionFeed, Error> {
+        let competitions: Vec<Competition> = self
+            .competition_store
+            .get_competitions(true, false)
+            .await
+            .map_err(|e| {
+                error!("failed to get active competitions: {:?}", e);
+                Error::DbError(e)
+            })?
+            .into_iter()
+            .filter(|competition| {
+                competition.get_state() == CompetitionState::Created
+                    && !competition.event_submission.private
+            })
+            .collect();
+        let competition_ids: Vec<Uuid> = competitions.iter().map(|c| c.id).collect();
+        let available_tickets = self
+            .competition_store
+            .get_available_ticket_counts(&competition_ids)
             .await
+            .map_err(|e| {
+                error!("failed to get available ticket counts: {:?}", e);
+                Error::DbError(e)
+            })?;
+
+        Ok(OpenCompetitionFeed::build(
+            &competitions,
+            &available_tickets,
+            base_url,
+            OffsetDateTime::now_utc(),
+        ))
     }
 
-    pub async fn get_contract_parameters(
+    pub async fn request_ticket(
         &self,
         pubkey: String,
         competition_id: Uuid,
-    ) -> Result<FundedContract, Error> {
+        btc_pubkey: BitcoinPublicKey,
+    ) -> Result<TicketResponse, Error> {
         let competition = self
             .competition_store
             .get_competition(competition_id)
             .await?;
+        self.ensure_invited(&competition, &pubkey).await?;
+        if competition.total_entries as usize >= competition.event_submission.total_allowed_entries
+        {
+            return Err(Error::CompetitionFull);
+        }
+        debug!("got competition: {:?}", competition);
 
-        // Verify user has an entry in this competition
-        let entries = self
+        // Get ticket, bound to the player's escrow pubkey so the entry can be checked against it
+        let ephemeral_pubkey = btc_pubkey.to_string();
+        let ticket = self
             .competition_store
-            .get_user_entries(
-                pubkey.clone(),
-                SearchBy {
-                    event_ids: Some(vec![competition_id]),
-                },
-            )
-            .await?;
-
-        if entries.is_empty() {
-            return Err(Error::NotFound(format!(
-                "No entry found for user in competition {}",
-                competition_id
+            .get_and_reserve_ticket(competition_id, &pubkey, &ephemeral_pubkey)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => Error::NoAvailableTickets,
+                e => Error::DbError(e),
+            })?;
+        if let Some(bound) = ticket
+            .ephemeral_pubkey
+            .as_ref()
+            .filter(|bound| **bound != ephemeral_pubkey)
+        {
+            return Err(Error::BadRequest(format!(
+                "Ticket {} was paid with escrow public key {}, not {}",
+                ticket.id, bound, ephemeral_pubkey
             )));
         }
+        match self
+            .create_ticket_response(ticket.clone(), btc_pubkey, competition)
+            .await
+        {
+            Ok(response) => Ok(response),
+            Err(e) => {
+                if let Err(clear_err) = self
+                    .competition_store
+                    .clear_ticket_reservation(ticket.id)
+                    .await
+                {
+                    error!(
+                        "Failed to clear ticket reservation for ticket {}: {}",
+                        ticket.id, clear_err
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
 
-        let contract = competition.contract_parameters.ok_or_else(|| {
-            Error::NotFound(format!(
-                "Contract parameters not yet available for competition {}",
-                competition_id
-            ))
-        })?;
+    async fn create_ticket_response(
+        &self,
+        ticket: Ticket,
+        btc_pubkey: BitcoinPublicKey,
+        competition: Competition,
+    ) -> Result<TicketResponse, Error> {
+        // Decode preimage from encrypted_preimage
+        let preimage = hex::decode(&ticket.encrypted_preimage)
+            .map_err(|_| Error::BadRequest("Invalid preimage".into()))?;
 
-        let funding_outpoint = competition.funding_outpoint.ok_or_else(|| {
-            Error::NotFound(format!(
-                "Funding transaction is not yet available for competition {}",
-                competition_id
-            ))
-        })?;
+        // Calculate payment hash from preimage
+        let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
 
-        let funding_psbt_base64 = competition.funding_psbt_base64.ok_or_else(|| {
-            Error::NotFound(format!(
-                "Funding psbt is not yet available for competition {}",
-                competition_id
-            ))
-        })?;
+        // Generate escrow transaction only if escrow is enabled
+        let escrow_tx_hex = if self.escrow_enabled {
+            let escrow_tx = generate_escrow_tx(
+                self.bitcoin.clone(),
+                ticket.id,
+                btc_pubkey,
+                payment_hash,
+                competition.event_submission.entry_fee.to_sat(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to generate escrow transaction: {}", e);
+                Error::BadRequest(format!("Failed to generate refund transaction: {}", e))
+            })?;
 
-        // Get keymeld signing info if enabled
-        let keymeld = if self.is_keymeld_enabled() {
-            self.get_keymeld_signing_info(competition_id, &pubkey, &entries[0])
+            debug!("escrow_tx: {:?}", escrow_tx);
+
+            let escrow_hex = hex::encode(dlctix::bitcoin::consensus::encode::serialize(&escrow_tx));
+
+            // Store the escrow transaction in the database
+            self.competition_store
+                .update_ticket_escrow_transaction(ticket.id, &escrow_hex)
                 .await
-                .ok()
+                .map_err(|e| {
+                    error!("Failed to update ticket with escrow transaction: {}", e);
+                    Error::DbError(e)
+                })?;
+
+            debug!(
+                "Created ticket {} with escrow tx {}",
+                ticket.id,
+                escrow_tx.compute_txid()
+            );
+
+            Some(escrow_hex)
         } else {
+            debug!(
+                "Created ticket {} without escrow (escrow disabled)",
+                ticket.id
+            );
             None
         };
 
-        Ok(FundedContract {
-            contract_params: contract,
-            funding_outpoint,
-            funding_psbt_base64,
-            keymeld,
+        let full_fee = competition.calculate_invoice_amount();
+
+        // The invoice only has to be paid while the reservation holds, an unpaid ticket goes to
+        // the next player after that. Once paid the hold invoice stays accepted until it's
+        // settled or cancelled, whatever its expiry.
+        let now = time::OffsetDateTime::now_utc();
+        let reserved_at = ticket.reserved_at.unwrap_or(now);
+        let invoice_expiry_seconds = (reserved_at + TICKET_RESERVATION_WINDOW - now)
+            .whole_seconds()
+            .max(60);
+        let (payment_request, expires_at) = if let Some(existing_payment_request) =
+            &ticket.payment_request
+        {
+            // Check if the existing invoice has expired, a paid one is never replaced
+            let is_expired = ticket.paid_at.is_none()
+                && ticket
+                    .invoice_expires_at
+                    .map(|expires_at| expires_at < time::OffsetDateTime::now_utc())
+                    .unwrap_or(true); // If no expiry stored, treat as expired to be safe
+
+            if is_expired {
+                debug!(
+                    "Existing invoice for ticket {} has expired, creating new one",
+                    ticket.id
+                );
+                // Cancel the old invoice before creating a new one
+                if let Err(e) = self
+                    .ln
+                    .cancel_hold_invoice(hex::encode(payment_hash), ticket.ln_backend_id.as_deref())
+                    .await
+                {
+                    // Log but don't fail - the invoice might already be cancelled or not exist
+                    debug!("Failed to cancel expired invoice: {}", e);
+                }
+
+                self.issue_hold_invoice(
+                    &ticket,
+                    full_fee,
+                    invoice_expiry_seconds,
+                    escrow_tx_hex.as_deref(),
+                )
+                .await?
+            } else {
+                debug!("Reusing existing payment request for ticket {}", ticket.id);
+                (
+                    existing_payment_request.clone(),
+                    ticket
+                        .invoice_expires_at
+                        .unwrap_or(reserved_at + TICKET_RESERVATION_WINDOW),
+                )
+            }
+        } else {
+            self.issue_hold_invoice(
+                &ticket,
+                full_fee,
+                invoice_expiry_seconds,
+                escrow_tx_hex.as_deref(),
+            )
+            .await?
+        };
+
+        let (keymeld_session_id, keymeld_enclave_public_key) =
+            self.keymeld_ticket_info(competition.id, ticket.id).await?;
+
+        Ok(TicketResponse {
+            ticket_id: ticket.id,
+            payment_request,
+            payment_hash: hex::encode(payment_hash),
+            amount_sats: full_fee,
+            entry_fee: competition.event_submission.entry_fee,
+            coordinator_fee: full_fee.saturating_sub(competition.event_submission.entry_fee),
+            expires_at,
+            invoice_memo: self
+                .invoice_memo(&ticket, escrow_tx_hex.as_deref())
+                .to_string(),
+            escrow_tx: self.escrow_tx_for_player(escrow_tx_hex),
+            // ticket_id is used as the keymeld user_id for consistency
+            keymeld_user_id: ticket.id,
+            keymeld_gateway_url: self.keymeld_gateway_url.clone(),
+            keymeld_session_id,
+            keymeld_enclave_public_key,
+        })
+    }
+
+    /// The escrow transaction to hand the player, withheld when the coordinator broadcasts it
+    /// so it can't go on-chain before every entry is paid
+    fn escrow_tx_for_player(&self, escrow_tx_hex: Option<String>) -> Option<String> {
+        escrow_tx_hex.filter(|_| !self.settings.escrow_broadcast_by_coordinator())
+    }
+
+    /// The memo on a ticket's hold invoices, the same for every invoice the ticket gets
+    fn invoice_memo(&self, ticket: &Ticket, escrow_tx_hex: Option<&str>) -> InvoiceMemo {
+        let escrow_txid = escrow_tx_hex
+            .and_then(|escrow_tx_hex| hex::decode(escrow_tx_hex).ok())
+            .and_then(|bytes| deserialize::<Transaction>(&bytes).ok())
+            .map(|escrow_tx| escrow_tx.compute_txid().to_string());
+        InvoiceMemo::new(
+            &self.name,
+            &ticket.competition_id.to_string(),
+            &ticket.id.to_string(),
+            escrow_txid,
+        )
+    }
+
+    /// A new hold invoice for the ticket's payment hash expiring in `expiry_seconds`, stored on
+    /// the ticket in place of its old one. Returns the invoice and when it expires.
+    async fn issue_hold_invoice(
+        &self,
+        ticket: &Ticket,
+        amount: Sats,
+        expiry_seconds: i64,
+        escrow_tx_hex: Option<&str>,
+    ) -> Result<(String, OffsetDateTime), Error> {
+        let invoice = self
+            .ln
+            .add_hold_invoice(
+                amount.to_sat(),
+                expiry_seconds as u64,
+                ticket.hash.clone(),
+                ticket.competition_id,
+                self.invoice_memo(ticket, escrow_tx_hex).to_string(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to create HODL invoice: {}", e);
+                Error::BadRequest(format!("Failed to create invoice: {}", e))
+            })?;
+
+        let expires_at = OffsetDateTime::now_utc() + time::Duration::seconds(expiry_seconds);
+        self.competition_store
+            .update_ticket_payment_request(
+                ticket.id,
+                &invoice.payment_request,
+                expires_at,
+                invoice.backend_id.as_deref(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to update ticket with payment request: {}", e);
+                Error::DbError(e)
+            })?;
+
+        Ok((invoice.payment_request, expires_at))
+    }
+
+    /// Keymeld session id and the ticket holder's enclave public key, when signing through
+    /// keymeld. The session is created along with the competition.
+    async fn keymeld_ticket_info(
+        &self,
+        competition_id: Uuid,
+        ticket_id: Uuid,
+    ) -> Result<(Option<String>, Option<String>), Error> {
+        if self.is_keymeld_enabled() {
+            if let Some(stored_session) = self
+                .competition_store
+                .get_keymeld_session(competition_id)
+                .await
+                .ok()
+                .flatten()
+            {
+                // Decrypt session secret to get the full session
+                let session_secret = self.decrypt_session_secret(&stored_session)?;
+                let session = stored_session.to_session(session_secret);
+
+                // Get the user's assigned enclave public key
+                // ticket_id is used as keymeld user_id
+                let user_id = UserId::from(ticket_id);
+                let enclave_pubkey = self
+                    .keymeld
+                    .get_user_enclave_pubkey(&session, user_id)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to get user enclave pubkey: {}", e);
+                        Error::BadRequest(format!("Failed to get enclave info: {}", e))
+                    })?;
+
+                return Ok((Some(stored_session.session_id), Some(enclave_pubkey)));
+            }
+        }
+        Ok((None, None))
+    }
+
+    /// A fresh invoice for a reserved ticket whose invoice expired before it was paid, good for
+    /// what is left of the reservation
+    pub async fn regenerate_ticket_invoice(
+        &self,
+        user_pubkey: String,
+        competition_id: Uuid,
+        ticket_id: Uuid,
+    ) -> Result<TicketResponse, Error> {
+        let ticket = self
+            .get_user_ticket(&user_pubkey, competition_id, ticket_id)
+            .await?;
+        match ticket.get_status() {
+            TicketStatus::Reserved => {}
+            TicketStatus::Expired => {
+                return Err(Error::BadRequest(format!(
+                    "Reservation of ticket {} has expired, request a new ticket",
+                    ticket.id
+                )))
+            }
+            status => {
+                return Err(Error::BadRequest(format!(
+                    "Ticket {} is {:?}, only reserved tickets get a new invoice",
+                    ticket.id, status
+                )))
+            }
+        }
+
+        let now = OffsetDateTime::now_utc();
+        if ticket
+            .invoice_expires_at
+            .is_some_and(|expires_at| expires_at > now)
+        {
+            return Err(Error::BadRequest(format!(
+                "Invoice for ticket {} has not expired yet",
+                ticket.id
+            )));
+        }
+        let Some(reserved_at) = ticket.reserved_at else {
+            return Err(Error::BadRequest(format!(
+                "Ticket {} is not reserved",
+                ticket.id
+            )));
+        };
+        let remaining_seconds = (reserved_at + TICKET_RESERVATION_WINDOW - now).whole_seconds();
+
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        if let Err(e) = self
+            .ln
+            .cancel_hold_invoice(ticket.hash.clone(), ticket.ln_backend_id.as_deref())
+            .await
+        {
+            debug!("Failed to cancel expired invoice: {}", e);
+        }
+
+        let amount = competition.calculate_invoice_amount();
+        let (payment_request, expires_at) = self
+            .issue_hold_invoice(
+                &ticket,
+                amount,
+                remaining_seconds,
+                ticket.escrow_transaction.as_deref(),
+            )
+            .await?;
+        info!(
+            "Regenerated invoice for ticket {} expiring in {}s",
+            ticket.id, remaining_seconds
+        );
+
+        let (keymeld_session_id, keymeld_enclave_public_key) =
+            self.keymeld_ticket_info(competition_id, ticket.id).await?;
+
+        Ok(TicketResponse {
+            ticket_id: ticket.id,
+            payment_request,
+            escrow_tx: self.escrow_tx_for_player(ticket.escrow_transaction.clone()),
+            payment_hash: ticket.hash.clone(),
+            amount_sats: amount,
+            entry_fee: competition.event_submission.entry_fee,
+            coordinator_fee: amount.saturating_sub(competition.event_submission.entry_fee),
+            expires_at,
+            invoice_memo: self
+                .invoice_memo(&ticket, ticket.escrow_transaction.as_deref())
+                .to_string(),
+            keymeld_user_id: ticket.id,
+            keymeld_gateway_url: self.keymeld_gateway_url.clone(),
+            keymeld_session_id,
+            keymeld_enclave_public_key,
+        })
+    }
+
+    pub async fn get_ticket_status(
+        &self,
+        user_pubkey: String,
+        competition_id: Uuid,
+        ticket_id: Uuid,
+    ) -> Result<TicketStatusResponse, Error> {
+        let ticket = self
+            .get_user_ticket(&user_pubkey, competition_id, ticket_id)
+            .await?;
+        let escrow = match &ticket.escrow_transaction {
+            Some(escrow_tx_hex) => Some(self.escrow_status(&ticket, escrow_tx_hex).await?),
+            None => None,
+        };
+        Ok(TicketStatusResponse {
+            status: ticket.get_status(),
+            escrow,
+        })
+    }
+
+    /// Where a ticket's escrow transaction stands and who is expected to broadcast it
+    async fn escrow_status(
+        &self,
+        ticket: &Ticket,
+        escrow_tx_hex: &str,
+    ) -> Result<EscrowStatus, Error> {
+        let bytes = hex::decode(escrow_tx_hex)
+            .map_err(|e| anyhow!("Failed to decode escrow transaction: {}", e))?;
+        let escrow_tx: Transaction = deserialize(&bytes)
+            .map_err(|e| anyhow!("Failed to deserialize escrow transaction: {}", e))?;
+        let txid = escrow_tx.compute_txid();
+        let chain_status: EscrowChainStatus = self.bitcoin.get_tx_chain_status(&txid).await?.into();
+
+        let broadcast_by_coordinator = self.settings.escrow_broadcast_by_coordinator();
+        let message = match (
+            broadcast_by_coordinator,
+            ticket.escrow_broadcasted_at,
+            chain_status,
+        ) {
+            (_, _, EscrowChainStatus::Confirmed { .. }) => "Escrow transaction is confirmed",
+            (true, Some(_), _) => {
+                "The coordinator broadcast the escrow transaction, waiting for it to confirm"
+            }
+            (true, None, _) => {
+                "The coordinator broadcasts the escrow transaction once every entry is paid, \
+                 don't broadcast it yourself"
+            }
+            (false, _, EscrowChainStatus::Unconfirmed) => {
+                "Escrow transaction is in the mempool, waiting for it to confirm"
+            }
+            (false, _, EscrowChainStatus::NotFound) => {
+                "You are responsible for broadcasting the escrow transaction, it hasn't been \
+                 seen on-chain yet"
+            }
+        };
+        let broadcast_by = if broadcast_by_coordinator {
+            EscrowBroadcaster::Coordinator
+        } else {
+            EscrowBroadcaster::Player
+        };
+
+        Ok(EscrowStatus {
+            txid: txid.to_string(),
+            broadcast_by,
+            broadcasted_at: ticket.escrow_broadcasted_at,
+            chain_status,
+            message: message.to_string(),
+        })
+    }
+
+    /// Signed proof of payment for a settled ticket. Tickets that settled before receipts were
+    /// issued, or whose receipt failed to store, get one on first request.
+    pub async fn get_ticket_receipt(
+        &self,
+        user_pubkey: String,
+        competition_id: Uuid,
+        ticket_id: Uuid,
+    ) -> Result<SignedReceipt, Error> {
+        let ticket = self
+            .get_user_ticket(&user_pubkey, competition_id, ticket_id)
+            .await?;
+        if let Some(receipt) = ticket.payment_receipt {
+            return Ok(receipt);
+        }
+        if ticket.settled_at.is_none() {
+            return Err(Error::NotFound(format!(
+                "Ticket {} has not settled yet",
+                ticket_id
+            )));
+        }
+
+        let competition = self.get_competition(competition_id).await?;
+        let payload = ReceiptPayload {
+            kind: ReceiptKind::TicketPayment,
+            competition_id,
+            ticket_id,
+            entry_id: ticket.entry_id,
+            amount_sats: ticket.paid_amount(competition.calculate_invoice_amount()),
+            payment_hash: ticket.hash.clone(),
+            issued_at: OffsetDateTime::now_utc(),
+        };
+        let receipt = self.issue_receipt(&payload)?;
+        self.competition_store
+            .set_ticket_receipt(ticket_id, &receipt)
+            .await?;
+        Ok(receipt)
+    }
+
+    /// Sign and store the receipt of a ticket whose hold invoice just settled. The settlement
+    /// stands either way, so a failure is only logged and retried when the receipt is requested.
+    pub async fn issue_payment_receipt(&self, ticket: &Ticket, amount: Sats) {
+        let payload = ReceiptPayload {
+            kind: ReceiptKind::TicketPayment,
+            competition_id: ticket.competition_id,
+            ticket_id: ticket.id,
+            entry_id: ticket.entry_id,
+            amount_sats: amount,
+            payment_hash: ticket.hash.clone(),
+            issued_at: OffsetDateTime::now_utc(),
+        };
+        let stored = match self.issue_receipt(&payload) {
+            Ok(receipt) => self
+                .competition_store
+                .set_ticket_receipt(ticket.id, &receipt)
+                .await
+                .map_err(Error::DbError),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = stored {
+            error!(
+                "Failed to issue payment receipt for ticket {}: {}",
+                ticket.id, e
+            );
+        }
+    }
+
+    /// Sign and store the receipt of an entry accepted with `ticket`
+    async fn issue_entry_receipt(
+        &self,
+        ticket: &Ticket,
+        entry_id: Uuid,
+        amount: Sats,
+    ) -> Result<SignedReceipt, Error> {
+        let payload = ReceiptPayload {
+            kind: ReceiptKind::EntryAccepted,
+            competition_id: ticket.competition_id,
+            ticket_id: ticket.id,
+            entry_id: Some(entry_id),
+            amount_sats: amount,
+            payment_hash: ticket.hash.clone(),
+            issued_at: OffsetDateTime::now_utc(),
+        };
+        let receipt = self.issue_receipt(&payload)?;
+        self.competition_store
+            .set_entry_receipt(entry_id, &receipt)
+            .await?;
+        Ok(receipt)
+    }
+
+    /// Sign a receipt with the newest coordinator key, the one published at `/api/v1/info`
+    fn issue_receipt(&self, payload: &ReceiptPayload) -> Result<SignedReceipt, Error> {
+        let secret_key = SecretKey::from_slice(&self.keys.newest().serialize())
+            .map_err(|e| Error::SigningError(e.to_string()))?;
+        sign_receipt(&secret_key, payload).map_err(|e| Error::SigningError(e.to_string()))
+    }
+
+    /// Ticket in `competition_id` reserved by `user_pubkey`
+    async fn get_user_ticket(
+        &self,
+        user_pubkey: &str,
+        competition_id: Uuid,
+        ticket_id: Uuid,
+    ) -> Result<Ticket, Error> {
+        let ticket = self
+            .competition_store
+            .get_ticket(ticket_id)
+            .await
+            .map_err(|e| {
+                debug!("error: {:?}", e);
+                match e {
+                    sqlx::Error::RowNotFound => Error::NotFound("Ticket not found".into()),
+                    e => Error::DbError(e),
+                }
+            })?;
+
+        // Verify this ticket belongs to this competition
+        if ticket.competition_id != competition_id {
+            return Err(Error::BadRequest(
+                "Ticket does not belong to this competition".into(),
+            ));
+        }
+
+        // Verify this ticket was reserved by this user
+        if ticket.reserved_by.as_deref() != Some(user_pubkey) {
+            return Err(Error::BadRequest("Ticket not reserved by this user".into()));
+        }
+
+        Ok(ticket)
+    }
+
+    pub async fn get_competition(&self, competition_id: Uuid) -> Result<Competition, Error> {
+        self.competition_store
+            .get_competition(competition_id)
+            .map_err(|e| {
+                error!(
+                    "failed to get competition: competition_id {} {:?}",
+                    competition_id, e
+                );
+                Error::DbError(e)
+            })
+            .await
+    }
+
+    pub async fn get_competition_by_slug(&self, slug: &str) -> Result<Competition, Error> {
+        let competition_id = self
+            .competition_store
+            .get_competition_id_by_slug(slug)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("No competition with slug {}", slug)))?;
+        self.get_competition(competition_id).await
+    }
+
+    /// Oracle announcement and attestation stored for a competition
+    pub async fn get_oracle_event_info(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<OracleEventInfo, Error> {
+        let competition = self.get_competition(competition_id).await?;
+        let oracle_url = self
+            .settings
+            .read(|s| s.coordinator_settings.oracle_url.clone());
+        Ok(OracleEventInfo::new(
+            &competition,
+            &oracle_url,
+            self.settings.oracle_pubkey(),
+        ))
+    }
+
+    /// Forecasts, observations and oracle entry scores to score a competition with. Once it's
+    /// attested they're fetched from the oracle once and served from the cache, until the
+    /// attestation changes.
+    pub async fn get_observation_data(
+        &self,
+        competition: &Competition,
+    ) -> Result<ObservationData, Error> {
+        let Some(attestation) = competition.attestation.as_ref() else {
+            return self.fetch_observation_data(competition).await;
+        };
+        let attestation = hex::encode(attestation.serialize());
+        if let Some(cached) = self
+            .competition_store
+            .get_observation_data(competition.id)
+            .await?
+        {
+            if cached.attestation == attestation {
+                return Ok(cached.data);
+            }
+            info!(
+                "Attestation for competition {} was corrected, refetching observation data",
+                competition.id
+            );
+        }
+
+        let data = self.fetch_observation_data(competition).await?;
+        self.competition_store
+            .save_observation_data(CachedObservationData {
+                competition_id: competition.id,
+                attestation,
+                data: data.clone(),
+                fetched_at: OffsetDateTime::now_utc(),
+            })
+            .await?;
+        Ok(data)
+    }
+
+    /// Compare who the attested outcome pays with who `score_entries` puts in the paid places.
+    /// The contract pays the attested outcome either way, a mismatch means the oracle scored
+    /// the entries differently from the data it published and is logged for operators.
+    async fn check_attested_winners(
+        &self,
+        competition: &Competition,
+        data: &ObservationData,
+    ) -> Result<(), Error> {
+        let Ok(Outcome::Attestation(outcome_index)) = competition.get_current_outcome() else {
+            return Ok(());
+        };
+        let mut entries: Vec<UserEntry> = self
+            .competition_store
+            .get_competition_entries(competition.id, vec![])
+            .await?
+            .into_iter()
+            .filter(|entry| entry.player_index.is_some())
+            .collect();
+        entries.sort_by_key(|entry| entry.player_index);
+
+        let event = &competition.event_submission;
+        let rankings =
+            generate_rankings(entries.len(), event.number_of_places_win, event.tie_policy);
+        // The last outcome refunds everyone, there are no winners to check
+        if outcome_index + 1 >= rankings.len() {
+            return Ok(());
+        }
+        let entry_ids: Vec<Uuid> = entries.iter().map(|entry| entry.id).collect();
+        let Some(attested) = ranking_entry_ids(&rankings[outcome_index], &entry_ids) else {
+            return Err(Error::BadRequest(format!(
+                "outcome {} ranks entries the competition doesn't have",
+                outcome_index
+            )));
+        };
+
+        let picks: Vec<(Uuid, Vec<WeatherChoices>)> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.id,
+                    entry.entry_submission.expected_observations.clone(),
+                )
+            })
+            .collect();
+        let scored = winning_groups(
+            &score_entries(&picks, data, event),
+            event.number_of_places_win,
+        );
+        if attested == scored {
+            debug!(
+                "Attested winners of competition {} match the scored ones: {:?}",
+                competition.id, attested
+            );
+        } else {
+            error!(
+                "Attested winners of competition {} differ from the scored ones, attested {:?} scored {:?}",
+                competition.id, attested, scored
+            );
+        }
+        Ok(())
+    }
+
+    async fn fetch_observation_data(
+        &self,
+        competition: &Competition,
+    ) -> Result<ObservationData, Error> {
+        let event = &competition.event_submission;
+        Ok(self
+            .oracle_client
+            .get_observation_data(
+                &competition.id,
+                &event.locations,
+                event.start_observation_date,
+                event.end_observation_date,
+            )
+            .await?)
+    }
+
+    /// Project what an entry with these observations would be paid, nothing is stored
+    pub async fn preview_entry(
+        &self,
+        competition_id: Uuid,
+        expected_observations: Vec<WeatherChoices>,
+    ) -> Result<EntryPreview, Error> {
+        let competition = self.get_competition(competition_id).await?;
+        let entry = AddEventEntry {
+            id: Uuid::now_v7(),
+            event_id: competition_id,
+            expected_observations,
+        };
+        validate_entry(entry, competition.clone()).await?;
+
+        EntryPreview::new(&competition).map_err(|e| Error::BadRequest(e.to_string()))
+    }
+
+    /// Delete a competition by ID. Only allowed if no entries have been paid.
+    pub async fn delete_competition(&self, competition_id: Uuid) -> Result<(), Error> {
+        // First check if competition exists and has no paid entries
+        let competition = self.get_competition(competition_id).await?;
+
+        if competition.total_paid_entries > 0 {
+            return Err(Error::BadRequest(format!(
+                "Cannot delete competition {} - it has {} paid entries",
+                competition_id, competition.total_paid_entries
+            )));
+        }
+
+        self.competition_store
+            .delete_competition(competition_id)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to delete competition: competition_id {} {:?}",
+                    competition_id, e
+                );
+                Error::DbError(e)
+            })?;
+
+        info!("Deleted competition: {}", competition_id);
+        Ok(())
+    }
+
+    pub async fn handle_invoice_accepted(
+        &self,
+        competition_id: Uuid,
+        ticket_hash: &str,
+    ) -> Result<(), Error> {
+        self.competition_store
+            .mark_ticket_paid(ticket_hash, competition_id)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => {
+                    Error::BadRequest("Invalid ticket or competition".into())
+                }
+                e => Error::DbError(e),
+            })?;
+        self.kicks.kick_competitions();
+
+        Ok(())
+    }
+
+    pub async fn add_entry(&self, pubkey: String, entry: AddEntry) -> Result<UserEntry, Error> {
+        let competition = self
+            .competition_store
+            .get_competition(entry.event_id)
+            .await
+            .map_err(|e| {
+                error!("error {:?}", e);
+                match e {
+                    sqlx::Error::RowNotFound => Error::BadRequest("Competition not found".into()),
+                    e => Error::DbError(e),
+                }
+            })?;
+        self.ensure_invited(&competition, &pubkey).await?;
+
+        let backup_relays = self.entrant_relays(&competition);
+        let invoice_amount = competition.calculate_invoice_amount();
+        validate_entry_keys(&entry)?;
+        validate_entry(entry.clone().into(), competition).await?;
+
+        debug!("entry: {:?}", entry);
+        let ticket = self
+            .competition_store
+            .get_ticket(entry.ticket_id)
+            .await
+            .map_err(|e| {
+                error!("error {:?}", e);
+                match e {
+                    sqlx::Error::RowNotFound => Error::BadRequest("Ticket not found".into()),
+                    e => Error::DbError(e),
+                }
+            })?;
+
+        if ticket.reserved_by.as_deref() != Some(&pubkey) {
+            return Err(Error::BadRequest("Ticket not reserved by this user".into()));
+        }
+
+        if ticket.paid_at.is_none() {
+            return Err(Error::BadRequest("Ticket has not been paid".into()));
+        }
+
+        if ticket.entry_id.is_some() {
+            return Err(Error::BadRequest("Ticket has already been used".into()));
+        }
+
+        // Bound when the ticket was reserved, only tickets reserved before that have none
+        if let Some(btc_pubkey) = &ticket.ephemeral_pubkey {
+            if btc_pubkey != &entry.ephemeral_pubkey {
+                return Err(Error::BadRequest(format!(
+                    "Entry public key {} must match ticket escrow public key {}",
+                    entry.ephemeral_pubkey, btc_pubkey
+                )));
+            }
+        }
+
+        let user_entry = self
+            .competition_store
+            .add_entry(entry.clone().into_user_entry(pubkey), ticket.id)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => {
+                    Error::BadRequest(
+                        "Failed to claim ticket - may have expired or been claimed by another entry"
+                            .into(),
+                    )
+                }
+                e => {
+                    error!(
+                        "entry added to oracle, but failed to be saved: entry_id {}, event_id {} {:?}",
+                        entry.id, entry.event_id, e
+                    );
+                    Error::DbError(e)
+                }
+            })?;
+        self.kicks.kick_competitions();
+
+        let mut user_entry = user_entry;
+        match self
+            .issue_entry_receipt(&ticket, user_entry.id, ticket.paid_amount(invoice_amount))
+            .await
+        {
+            Ok(receipt) => user_entry.receipt = Some(receipt),
+            Err(e) => error!("Failed to issue receipt for entry {}: {}", user_entry.id, e),
+        }
+        if !backup_relays.is_empty() {
+            self.send_backup_dm(backup_relays, &user_entry);
+        }
+
+        Ok(user_entry)
+    }
+
+    /// DM the player their entry's encrypted private key and payout preimage, signed by the
+    /// coordinator's nostr key, so they can recover them without the coordinator. Relays can take
+    /// up to their publish timeout, so the DM goes out in the background and its outcome is
+    /// recorded on the entry.
+    fn send_backup_dm(&self, relays: Vec<String>, entry: &UserEntry) {
+        let entry_id = entry.id;
+        let event = self.sign_dm(|keys| EntryBackup::from(entry).to_dm(keys, &entry.pubkey));
+        let publisher = self.relays.clone();
+        let store = self.competition_store.clone();
+        tokio::spawn(async move {
+            let published = match event {
+                Ok(event) => publisher.publish(&relays, event).await,
+                Err(e) => Err(e),
+            };
+            let (sent_at, error) = match published {
+                Ok(_) => (Some(OffsetDateTime::now_utc()), None),
+                Err(e) => {
+                    warn!("Failed to send backup DM for entry {}: {}", entry_id, e);
+                    (None, Some(e.to_string()))
+                }
+            };
+            if let Err(e) = store.record_backup_dm(entry_id, sent_at, error).await {
+                error!(
+                    "Failed to record backup DM status for entry {}: {}",
+                    entry_id, e
+                );
+            }
+        });
+    }
+
+    /// Build a DM signed with the newest coordinator key's nostr identity
+    fn sign_dm(
+        &self,
+        build: impl FnOnce(&nostr_sdk::prelude::Keys) -> Result<nostr_sdk::Event, anyhow::Error>,
+    ) -> Result<nostr_sdk::Event, anyhow::Error> {
+        use nostr_sdk::prelude::{Keys, SecretKey};
+
+        let secret_key = SecretKey::from_slice(&self.keys.newest().serialize())?;
+        build(&Keys::new(secret_key))
+    }
+
+    /// Relays entrants are DMed on, the competition's own or the coordinator's backup relays
+    fn entrant_relays(&self, competition: &Competition) -> Vec<String> {
+        if competition.event_submission.backup_relays.is_empty() {
+            self.settings.backup_relays()
+        } else {
+            competition.event_submission.backup_relays.clone()
+        }
+    }
+
+    /// Publish a DM to an entrant in the background, signed by the newest coordinator key.
+    /// Failures are only logged, the entrant's client also sees the cleared nonces when it polls.
+    fn send_entrant_dm(
+        &self,
+        relays: Vec<String>,
+        entry_id: Uuid,
+        build: impl FnOnce(&nostr_sdk::prelude::Keys) -> Result<nostr_sdk::Event, anyhow::Error>,
+    ) {
+        let event = self.sign_dm(build);
+        let publisher = self.relays.clone();
+        tokio::spawn(async move {
+            let published = match event {
+                Ok(event) => publisher.publish(&relays, event).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = published {
+                warn!("Failed to send DM for entry {}: {}", entry_id, e);
+            }
+        });
+    }
+
+    /// The user's entries owed a share of an attested competition's pool, with the state of
+    /// their lightning payouts
+    pub async fn get_eligible_payouts(&self, pubkey: String) -> Result<Vec<EligiblePayout>, Error> {
+        let entries = self
+            .competition_store
+            .get_user_entries(
+                pubkey,
+                SearchBy {
+                    event_ids: None,
+                    tags: None,
+                },
+            )
+            .await?;
+
+        let mut competitions: HashMap<Uuid, Competition> = HashMap::new();
+        let mut payouts = Vec::new();
+        for entry in entries {
+            if !competitions.contains_key(&entry.event_id) {
+                let competition = self.get_competition(entry.event_id).await?;
+                competitions.insert(entry.event_id, competition);
+            }
+            // Skip the payouts lookup for competitions that can't owe anything yet
+            let competition = &competitions[&entry.event_id];
+            if !competition.is_attested() || !competition.is_outcome_broadcasted() {
+                continue;
+            }
+
+            let entry_payouts = self
+                .competition_store
+                .get_entry_payouts(entry.id, None)
+                .await?;
+            if let Some(payout) = EligiblePayout::new(competition, &entry, &entry_payouts) {
+                payouts.push(payout);
+            }
+        }
+
+        Ok(payouts)
+    }
+
+    pub async fn get_entries(
+        &self,
+        pubkey: String,
+        filter: SearchBy,
+    ) -> Result<Vec<UserEntry>, Error> {
+        self.competition_store
+            .get_user_entries(pubkey, filter)
+            .map_err(Error::DbError)
+            .await
+    }
+
+    /// Get lightweight entry views for the entries list page.
+    /// Single query that joins entries with competitions for dates and payout status.
+    pub async fn get_user_entry_views(&self, pubkey: String) -> Result<Vec<UserEntryView>, Error> {
+        self.competition_store
+            .get_user_entry_views(pubkey)
+            .map_err(Error::DbError)
+            .await
+    }
+
+    /// Signed proof the user's entry was accepted, issued now for entries accepted before
+    /// receipts existed
+    pub async fn get_entry_receipt(
+        &self,
+        pubkey: String,
+        entry_id: Uuid,
+    ) -> Result<SignedReceipt, Error> {
+        let entry = self
+            .competition_store
+            .get_entry_by_id(entry_id)
+            .await?
+            .filter(|entry| entry.pubkey == pubkey)
+            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+        if let Some(receipt) = entry.receipt {
+            return Ok(receipt);
+        }
+
+        let ticket = self.competition_store.get_ticket(entry.ticket_id).await?;
+        let competition = self.get_competition(entry.event_id).await?;
+        self.issue_entry_receipt(
+            &ticket,
+            entry.id,
+            ticket.paid_amount(competition.calculate_invoice_amount()),
+        )
+        .await
+    }
+
+    /// Get a single entry by ID (public, for leaderboard entry details)
+    pub async fn get_entry_by_id(&self, entry_id: Uuid) -> Result<Option<UserEntry>, Error> {
+        self.competition_store
+            .get_entry_by_id(entry_id)
+            .map_err(Error::DbError)
+            .await
+    }
+
+    pub async fn get_contract_parameters(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+    ) -> Result<FundedContract, Error> {
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        // Verify user has an entry in this competition
+        let entries = self
+            .competition_store
+            .get_user_entries(
+                pubkey.clone(),
+                SearchBy {
+                    event_ids: Some(vec![competition_id]),
+                    tags: None,
+                },
+            )
+            .await?;
+
+        if entries.is_empty() {
+            return Err(Error::NotFound(format!(
+                "No entry found for user in competition {}",
+                competition_id
+            )));
+        }
+
+        let contract = competition.contract_parameters.ok_or_else(|| {
+            Error::NotFound(format!(
+                "Contract parameters not yet available for competition {}",
+                competition_id
+            ))
+        })?;
+
+        let funding_outpoint = competition.funding_outpoint.ok_or_else(|| {
+            Error::NotFound(format!(
+                "Funding transaction is not yet available for competition {}",
+                competition_id
+            ))
+        })?;
+
+        let funding_psbt_base64 = competition.funding_psbt_base64.ok_or_else(|| {
+            Error::NotFound(format!(
+                "Funding psbt is not yet available for competition {}",
+                competition_id
+            ))
+        })?;
+        // Entrants only see and sign their own escrow inputs
+        let funding_psbt_base64 = if self.is_escrow_enabled() {
+            let escrow_outpoints = self
+                .escrow_outpoints_by_player(competition_id, competition.event_submission.entry_fee)
+                .await?;
+            let mut entrant_outpoints = vec![];
+            for entry in &entries {
+                let player = Point::from_hex(&entry.ephemeral_pubkey).map_err(|e| {
+                    Error::BadRequest(format!(
+                        "Invalid ephemeral pubkey for entry {}: {}",
+                        entry.id, e
+                    ))
+                })?;
+                if let Some(outpoints) = escrow_outpoints.get(&player) {
+                    entrant_outpoints.extend(outpoints.iter().copied());
+                }
+            }
+            entrant_outpoints.sort();
+            entrant_outpoints.dedup();
+            let funding_psbt = Psbt::from_str(&funding_psbt_base64)
+                .map_err(|e| Error::Bitcoin(anyhow!("Invalid funding psbt: {}", e)))?;
+            entrant_funding_psbt(&funding_psbt, &entrant_outpoints)
+                .map_err(|e| Error::Bitcoin(e.into()))?
+                .to_string()
+        } else {
+            funding_psbt_base64
+        };
+
+        // Get keymeld signing info if enabled
+        let keymeld = if self.is_keymeld_enabled() {
+            self.get_keymeld_signing_info(competition_id, &pubkey, &entries[0])
+                .await
+                .ok()
+        } else {
+            None
+        };
+
+        let aggregated_nonces_digest = competition
+            .aggregated_nonces
+            .as_ref()
+            .map(sig_map_digest)
+            .transpose()?;
+
+        Ok(FundedContract {
+            contract_params: contract,
+            funding_outpoint,
+            funding_psbt_base64,
+            aggregated_nonces_digest,
+            keymeld,
+        })
+    }
+
+    /// Contract terms of a competition for auditors, with player pubkeys replaced by salted
+    /// hashes. An entrant passing their pubkey also gets the player slots of their entries.
+    pub async fn get_contract_disclosure(
+        &self,
+        competition_id: Uuid,
+        viewer: Option<String>,
+    ) -> Result<ContractDisclosure, Error> {
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        let params = competition
+            .contract_parameters
+            .as_ref()
+            .filter(|_| competition.is_contract_created())
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "Contract for competition {} has not been created yet",
+                    competition_id
+                ))
+            })?;
+
+        let your_player_indices = match viewer {
+            Some(pubkey) => self
+                .competition_store
+                .get_user_entries(
+                    pubkey,
+                    SearchBy {
+                        event_ids: Some(vec![competition_id]),
+                        tags: None,
+                    },
+                )
+                .await?
+                .into_iter()
+                .filter_map(|entry| entry.player_index)
+                .sorted()
+                .collect(),
+            None => vec![],
+        };
+
+        let salt = disclosure_salt(
+            &self.competition_key(&competition)?.serialize(),
+            competition_id,
+        );
+        Ok(disclose_contract(
+            competition_id,
+            params,
+            competition.funding_outpoint,
+            &salt,
+            your_player_indices,
+        ))
+    }
+
+    /// Estimated heights and times for a competition's outcome and delta timelocks, anchored on
+    /// the funding and outcome confirmations and refreshed from the current tip. An entrant
+    /// passing their pubkey also gets the sellback and on-chain claim windows of their entries.
+    pub async fn get_competition_timeline(
+        &self,
+        competition_id: Uuid,
+        viewer: Option<String>,
+    ) -> Result<CompetitionTimeline, Error> {
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        let params = competition
+            .contract_parameters
+            .as_ref()
+            .filter(|_| competition.is_contract_created())
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "Contract for competition {} has not been created yet",
+                    competition_id
+                ))
+            })?;
+
+        let tip_height = self.bitcoin.get_current_height().await?;
+        let funding_confirmation_height = match competition.funding_transaction.as_ref() {
+            Some(tx) => {
+                self.bitcoin
+                    .get_tx_confirmation_height(&tx.compute_txid())
+                    .await?
+            }
+            None => None,
+        };
+        let outcome_confirmation_height = match competition.outcome_transaction.as_ref() {
+            Some(tx) => {
+                self.bitcoin
+                    .get_tx_confirmation_height(&tx.compute_txid())
+                    .await?
+            }
+            None => None,
+        };
+
+        let winners = competition
+            .get_current_outcome()
+            .ok()
+            .map(|outcome| params.outcome_payouts.get(&outcome));
+        let players: Vec<TimelinePlayer> = match viewer {
+            Some(pubkey) => self
+                .competition_store
+                .get_user_entries(
+                    pubkey,
+                    SearchBy {
+                        event_ids: Some(vec![competition_id]),
+                        tags: None,
+                    },
+                )
+                .await?
+                .into_iter()
+                .filter_map(|entry| {
+                    let player_index = entry.player_index?;
+                    Some(TimelinePlayer {
+                        entry_id: entry.id,
+                        player_index,
+                        is_winner: winners.map(|weights| {
+                            weights.is_some_and(|weights| weights.contains_key(&player_index))
+                        }),
+                    })
+                })
+                .sorted_by_key(|player| player.player_index)
+                .collect(),
+            None => vec![],
+        };
+
+        let expiry_at = params
+            .event
+            .expiry
+            .and_then(|expiry| OffsetDateTime::from_unix_timestamp(i64::from(expiry)).ok());
+
+        Ok(build_timeline(
+            competition_id,
+            params.relative_locktime_block_delta,
+            competition.event_submission.signing_date,
+            expiry_at,
+            TimelineAnchors {
+                tip_height,
+                now: OffsetDateTime::now_utc(),
+                funding_confirmation_height,
+                outcome_confirmation_height,
+            },
+            &players,
+        ))
+    }
+
+    /// Get keymeld signing info for a user's entry
+    /// Only returns info if the user's ticket has been paid (HODL invoice accepted)
+    /// Decrypts the stored session secret and re-encrypts it to the user's nostr pubkey
+    async fn get_keymeld_signing_info(
+        &self,
+        competition_id: Uuid,
+        user_pubkey: &str,
+        entry: &UserEntry,
+    ) -> Result<KeymeldSigningInfo, Error> {
+        use nostr_sdk::nips::nip44;
+        use nostr_sdk::prelude::{PublicKey, SecretKey};
+
+        // Check that the user's ticket has been paid before revealing keymeld info
+        let ticket = self
+            .competition_store
+            .get_ticket(entry.ticket_id)
+            .await
+            .map_err(Error::DbError)?;
+
+        if ticket.paid_at.is_none() {
+            return Err(Error::BadRequest(
+                "Keymeld info not available until payment is received".to_string(),
+            ));
+        }
+
+        let stored_session = self
+            .competition_store
+            .get_keymeld_session(competition_id)
+            .await
+            .map_err(Error::DbError)?
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "Keymeld session not found for competition {}",
+                    competition_id
+                ))
+            })?;
+
+        let gateway_url = self
+            .keymeld_gateway_url
+            .clone()
+            .ok_or_else(|| Error::BadRequest("Keymeld gateway URL not configured".to_string()))?;
+
+        // Decrypt the session secret from storage
+        let session_secret = self.decrypt_session_secret(&stored_session)?;
+
+        // Re-encrypt to the user's pubkey
+        let nostr_pubkey = PublicKey::from_hex(user_pubkey)
+            .map_err(|e| Error::BadRequest(format!("Invalid user pubkey: {}", e)))?;
+
+        // Sent from the key at `/api/v1/info`, whichever key the secret is stored under
+        let coordinator_secret_key = SecretKey::from_slice(&self.keys.newest().serialize())
+            .map_err(|e| Error::BadRequest(format!("Failed to create secret key: {}", e)))?;
+
+        let encrypted_session_secret = nip44::encrypt(
+            &coordinator_secret_key,
+            &nostr_pubkey,
+            hex::encode(session_secret),
+            nip44::Version::V2,
+        )
+        .map_err(|e| Error::BadRequest(format!("NIP-44 encryption failed: {}", e)))?;
+
+        Ok(KeymeldSigningInfo {
+            enabled: true,
+            gateway_url,
+            session_id: stored_session.session_id,
+            encrypted_session_secret,
+            // Use ticket_id as user_id for consistency with keygen session creation
+            user_id: entry.ticket_id.to_string(),
+        })
+    }
+
+    /// Verify an entry's partial signatures against the aggregated nonces the way signing the
+    /// contract does, so a bad signature is turned away before it's stored
+    async fn verify_entry_partial_signatures(
+        &self,
+        competition: &Competition,
+        player: Point,
+        partial_signatures: &SigMap<PartialSignature>,
+    ) -> Result<(), Error> {
+        let (Some(contract_parameters), Some(funding_outpoint)) = (
+            competition.contract_parameters.clone(),
+            competition.funding_outpoint,
+        ) else {
+            return Err(Error::BadRequest("Contract not yet available".to_string()));
+        };
+        let ticketed_dlc = TicketedDLC::new(contract_parameters, funding_outpoint)
+            .map_err(|e| Error::SigningError(e.to_string()))?;
+        let signing_session = {
+            let coordinator_key = self.competition_key(competition)?;
+            let mut rng = create_deterministic_rng(&funding_outpoint, coordinator_key);
+            SigningSession::<NonceSharingRound>::new(ticketed_dlc, &mut rng, coordinator_key)
+                .map_err(|e| Error::SigningError(e.to_string()))?
+        };
+        let received_nonces = self
+            .get_received_nonces(competition.id)
+            .await
+            .map_err(|e| Error::SigningError(e.to_string()))?;
+        let coordinator_session = signing_session
+            .aggregate_nonces_and_compute_partial_signatures(received_nonces)
+            .map_err(|e| Error::SigningError(e.to_string()))?;
+
+        if let Err(e) = coordinator_session.verify_partial_signatures(player, partial_signatures) {
+            let mut errors = ValidationErrors::new();
+            errors.push(
+                "partial_signatures",
+                "invalid_signature",
+                format!(
+                    "partial signatures don't verify against the aggregated nonces: {}",
+                    e
+                ),
+            );
+            return Err(errors.into());
+        }
+        Ok(())
+    }
+
+    pub async fn submit_public_nonces(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+        entry_id: Uuid,
+        public_nonces: SigMap<PubNonce>,
+    ) -> Result<(), Error> {
+        debug!(
+            "Submitting nonces for entry {}: {:?}",
+            entry_id, public_nonces
+        );
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        let Some(contract_parameters) = &competition.contract_parameters else {
+            return Err(Error::BadRequest(
+                "Contract parameters not yet available".to_string(),
+            ));
+        };
+
+        let entries = self
+            .competition_store
+            .get_user_entries(
+                pubkey,
+                SearchBy {
+                    event_ids: Some(vec![competition_id]),
+                    tags: None,
+                },
+            )
+            .await?;
+
+        let entry = entries
+            .iter()
+            .find(|e| e.id == entry_id)
+            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+
+        if entry.public_nonces.is_some() {
+            return Err(Error::BadRequest(
+                "Public nonces already submitted for this entry".to_string(),
+            ));
+        }
+
+        let (_, expected) = player_sig_map(contract_parameters, entry)?;
+        check_sig_map_keys("public_nonces", &public_nonces, &expected).into_result()?;
+
+        self.competition_store
+            .add_public_nonces(entry_id, public_nonces)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed save entry public nonces: entry_id {}, event_id {} {:?}",
+                    entry_id, competition_id, e
+                );
+                Error::DbError(e)
+            })?;
+
+        Ok(())
+    }
+
+    pub async fn get_aggregate_nonces(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+    ) -> Result<SigMap<AggNonce>, Error> {
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        let entries = self
+            .competition_store
+            .get_user_entries(
+                pubkey,
+                SearchBy {
+                    event_ids: Some(vec![competition_id]),
+                    tags: None,
+                },
+            )
+            .await?;
+
+        if entries.is_empty() {
+            return Err(Error::NotFound(format!(
+                "No entry found for user in competition {}",
+                competition_id
+            )));
+        }
+
+        competition.aggregated_nonces.ok_or_else(|| {
+            Error::NotFound(format!(
+                "Aggregate nonces not yet available for competition {}",
+                competition_id
+            ))
+        })
+    }
+
+    pub async fn submit_final_signatures(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+        entry_id: Uuid,
+        final_signatures: FinalSignatures,
+    ) -> Result<(), Error> {
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        let Some(contract_parameters) = &competition.contract_parameters else {
+            return Err(Error::BadRequest(
+                "Contract parameters not yet available".to_string(),
+            ));
+        };
+
+        if competition.partial_signatures.is_none() {
+            return Err(Error::BadRequest(
+                "Contract partial_signatures not yet available".to_string(),
+            ));
+        }
+
+        debug!("adding signatures on entry {} for {}", entry_id, pubkey);
+        let entries = self
+            .competition_store
+            .get_user_entries(
+                pubkey,
+                SearchBy {
+                    event_ids: Some(vec![competition_id]),
+                    tags: None,
+                },
+            )
+            .await?;
+
+        let entry = entries
+            .iter()
+            .find(|e| e.id == entry_id)
+            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+
+        // Player funding PSBTs are only merged into the funding transaction with escrow enabled
+        if self.is_escrow_enabled() {
+            self.check_entry_funding_psbt(
+                &competition,
+                entry,
+                &final_signatures.funding_psbt_base64,
+            )
+            .await?;
+        }
+
+        let (player, expected) = player_sig_map(contract_parameters, entry)?;
+        check_sig_map_keys(
+            "partial_signatures",
+            &final_signatures.partial_signatures,
+            &expected,
+        )
+        .into_result()?;
+        if !self.is_keymeld_enabled() {
+            self.verify_entry_partial_signatures(
+                &competition,
+                player,
+                &final_signatures.partial_signatures,
+            )
+            .await?;
+        }
+
+        self.competition_store
+            .add_final_signatures(entry_id, final_signatures)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed save entry signatures: entry_id {}, event_id {} {:?}",
+                    entry_id, competition_id, e
+                );
+                Error::DbError(e)
+            })?;
+
+        Ok(())
+    }
+
+    /// Dry run of the funding psbt check done when an entry's signatures are submitted, so a
+    /// client can find out its psbt would be rejected before signing the contract
+    pub async fn verify_entry_funding_psbt(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+        entry_id: Uuid,
+        funding_psbt_base64: &str,
+    ) -> Result<(), Error> {
+        if !self.is_escrow_enabled() {
+            return Err(Error::BadRequest(
+                "Entrants only sign the funding psbt with escrow enabled".to_string(),
+            ));
+        }
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+        let entry = self
+            .competition_store
+            .get_entry_by_id(entry_id)
+            .await?
+            .filter(|entry| entry.event_id == competition_id && entry.pubkey == pubkey)
+            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+
+        self.check_entry_funding_psbt(&competition, &entry, funding_psbt_base64)
+            .await
+    }
+
+    /// An entrant's funding psbt has to be the competition's funding transaction with its
+    /// outputs untouched, signing the entry's escrow input and nothing else
+    async fn check_entry_funding_psbt(
+        &self,
+        competition: &Competition,
+        entry: &UserEntry,
+        funding_psbt_base64: &str,
+    ) -> Result<(), Error> {
+        let entrant_psbt = Psbt::from_str(funding_psbt_base64)
+            .map_err(|e| Error::BadRequest(format!("Invalid funding psbt: {}", e)))?;
+        validate_psbt_network(&entrant_psbt, self.bitcoin.get_network())
+            .map_err(|e| Error::BadRequest(e.to_string()))?;
+
+        let funding_psbt = competition
+            .funding_psbt_base64
+            .as_deref()
+            .ok_or_else(|| Error::BadRequest("Funding psbt not yet available".to_string()))?;
+        let funding_psbt = Psbt::from_str(funding_psbt)
+            .map_err(|e| anyhow!("Stored funding psbt doesn't parse: {}", e))?;
+
+        // Comped entries have no escrow, their psbt shouldn't sign anything
+        let ticket = self.competition_store.get_ticket(entry.ticket_id).await?;
+        let escrow_outpoints = match ticket.escrow_transaction {
+            Some(escrow_hex) => {
+                let escrow_tx: Transaction = deserialize(
+                    &hex::decode(escrow_hex)
+                        .map_err(|e| anyhow!("Failed to decode escrow transaction: {}", e))?,
+                )
+                .map_err(|e| anyhow!("Failed to deserialize escrow transaction: {}", e))?;
+                vec![get_escrow_outpoint(
+                    &escrow_tx,
+                    Amount::from_sat(competition.event_submission.entry_fee.to_sat()),
+                )?]
+            }
+            None => vec![],
+        };
+
+        check_entrant_psbt(&funding_psbt, &entrant_psbt, &escrow_outpoints).map_err(|e| {
+            warn!(
+                "Rejected funding psbt for entry {} of competition {}: {}",
+                entry.id, competition.id, e
+            );
+            Error::from(e)
+        })
+    }
+
+    /// Merge a chunk of an entry's partial signatures, the entry counts as signed once every
+    /// outcome and win condition its player signs is covered and the signatures verify
+    pub async fn submit_partial_signature_chunk(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+        entry_id: Uuid,
+        chunk: PartialSignatureChunk,
+    ) -> Result<SignatureChunkProgress, Error> {
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        let Some(contract_parameters) = &competition.contract_parameters else {
+            return Err(Error::BadRequest(
+                "Contract parameters not yet available".to_string(),
+            ));
+        };
+
+        if competition.partial_signatures.is_none() {
+            return Err(Error::BadRequest(
+                "Contract partial_signatures not yet available".to_string(),
+            ));
+        }
+
+        let entries = self
+            .competition_store
+            .get_user_entries(
+                pubkey,
+                SearchBy {
+                    event_ids: Some(vec![competition_id]),
+                    tags: None,
+                },
+            )
+            .await?;
+        let entry = entries
+            .iter()
+            .find(|e| e.id == entry_id)
+            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+        if self.is_escrow_enabled() {
+            if let Some(funding_psbt_base64) = &chunk.funding_psbt_base64 {
+                self.check_entry_funding_psbt(&competition, entry, funding_psbt_base64)
+                    .await?;
+            }
+        }
+        let (player, expected) = player_sig_map(contract_parameters, entry)?;
+        check_chunk_keys(&chunk.partial_signatures, &expected)
+            .map_err(|e| Error::BadRequest(e.to_string()))?;
+
+        let funding_psbt_base64 = chunk
+            .funding_psbt_base64
+            .clone()
+            .or_else(|| entry.funding_psbt_base64.clone());
+        let merged = self
+            .competition_store
+            .merge_entry_partial_signatures(
+                entry_id,
+                chunk.partial_signatures,
+                chunk.funding_psbt_base64,
+            )
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to merge entry signatures: entry_id {}, event_id {} {:?}",
+                    entry_id, competition_id, e
+                );
+                Error::DbError(e)
+            })?
+            .map_err(|e| Error::BadRequest(e.to_string()))?;
+
+        let complete = sig_map_covers(&merged, &expected);
+        let progress = SignatureChunkProgress {
+            received: sig_map_len(&merged),
+            expected: sig_map_len(&expected),
+            complete,
+        };
+        if !complete || entry.signed_at.is_some() {
+            return Ok(progress);
+        }
+
+        if !self.is_keymeld_enabled() {
+            if let Err(e) = self
+                .verify_entry_partial_signatures(&competition, player, &merged)
+                .await
+            {
+                // A bad signature from an earlier chunk can't be replaced by resending it, so
+                // the entry starts over
+                self.competition_store
+                    .clear_entry_partial_signatures(entry_id)
+                    .await?;
+                return Err(e);
+            }
+        }
+
+        let funding_psbt_base64 = match funding_psbt_base64 {
+            Some(funding_psbt_base64) => funding_psbt_base64,
+            None if self.is_escrow_enabled() => {
+                return Err(Error::BadRequest(format!(
+                    "All partial signatures received for entry {} but no funding psbt",
+                    entry_id
+                )));
+            }
+            None => String::new(),
+        };
+        debug!(
+            "chunked signatures complete on entry {} ({} signatures)",
+            entry_id, progress.received
+        );
+        self.competition_store
+            .add_final_signatures(
+                entry_id,
+                FinalSignatures {
+                    funding_psbt_base64,
+                    partial_signatures: merged,
+                },
+            )
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed save entry signatures: entry_id {}, event_id {} {:?}",
+                    entry_id, competition_id, e
+                );
+                Error::DbError(e)
+            })?;
+
+        Ok(progress)
+    }
+
+    /// Register where the escrow of an entry that never signed goes once its competition is
+    /// cancelled, along with the ephemeral key the escrow is locked to
+    pub async fn register_escrow_reclaim(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+        entry_id: Uuid,
+        reclaim_info: EscrowReclaimInfo,
+    ) -> Result<(), Error> {
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+        if competition.cancelled_at.is_none() {
+            return Err(Error::BadRequest(
+                "Escrow can only be reclaimed from a cancelled competition".into(),
+            ));
+        }
+
+        let entries = self
+            .competition_store
+            .get_user_entries(
+                pubkey,
+                SearchBy {
+                    event_ids: Some(vec![competition_id]),
+                    tags: None,
+                },
+            )
+            .await?;
+        let entry = entries
+            .iter()
+            .find(|e| e.id == entry_id)
+            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+        if entry.ticket_id != reclaim_info.ticket_id {
+            return Err(Error::BadRequest("Invalid ticket for this entry".into()));
+        }
+        if entry.signed_at.is_some() {
+            return Err(Error::BadRequest(format!(
+                "Entry {} signed the contract, its escrow funded the competition",
+                entry.id
+            )));
+        }
+
+        let ticket = self.competition_store.get_ticket(entry.ticket_id).await?;
+        if ticket.escrow_transaction.is_none() {
+            return Err(Error::BadRequest(format!(
+                "Ticket {} has no escrow to reclaim",
+                ticket.id
+            )));
+        }
+
+        let ephemeral_pubkey = Point::from_hex(&entry.ephemeral_pubkey)
+            .map_err(|e| Error::BadRequest(format!("Invalid ephemeral pubkey: {}", e)))?;
+        let provided_private_key = Scalar::from_hex(&reclaim_info.ephemeral_private_key)
+            .map_err(|e| Error::BadRequest(format!("Invalid private key: {}", e)))?;
+        if provided_private_key.base_point_mul() != ephemeral_pubkey {
+            return Err(Error::BadRequest(
+                "Invalid private key for this entry".into(),
+            ));
+        }
+
+        let address = Address::from_str(&reclaim_info.address)
+            .and_then(|address| address.require_network(self.bitcoin.get_network()))
+            .map_err(|e| Error::BadRequest(format!("Invalid reclaim address: {}", e)))?;
+
+        if !self
+            .competition_store
+            .register_escrow_reclaim(
+                entry.id,
+                address.to_string(),
+                reclaim_info.ephemeral_private_key,
+            )
+            .await?
+        {
+            return Err(Error::BadRequest(format!(
+                "Escrow of entry {} was already reclaimed",
+                entry.id
+            )));
+        }
+        info!(
+            "Entry {} of cancelled competition {} registered escrow reclaim to {}",
+            entry.id, competition_id, address
+        );
+        self.kicks.kick_competitions();
+
+        Ok(())
+    }
+
+    pub async fn submit_ticket_payout(
+        &self,
+        pubkey: String,
+        competition_id: Uuid,
+        entry_id: Uuid,
+        payout_info: PayoutInfo,
+    ) -> Result<(), Error> {
+        if payout_info.ln_invoice.is_empty() {
+            return Err(Error::BadRequest("Invalid lightning invoice".into()));
+        }
+
+        // Get the competition and verify it's in a valid state for payouts
+        let competition = self
+            .competition_store
+            .get_competition(competition_id)
+            .await?;
+
+        if !competition.is_attested() {
+            return Err(Error::BadRequest(
+                "Competition results not yet attested".into(),
+            ));
+        }
+
+        if let Some(ref event_announcement) = competition.event_announcement {
+            debug!("Locking points: {:?}", event_announcement.locking_points);
+        }
+
+        if competition.is_delta_broadcasted()
+            || competition.is_expiry_broadcasted()
+            || competition.is_completed()
+        {
+            return Err(Error::BadRequest(
+                "Funds already received to user's on-chain key".into(),
+            ));
+        }
+
+        // Get the entry and verify ownership
+        let entries = self
+            .competition_store
+            .get_user_entries(
+                pubkey.clone(),
+                SearchBy {
+                    event_ids: Some(vec![competition_id]),
+                    tags: None,
+                },
+            )
+            .await?;
+
+        let entry = entries
+            .iter()
+            .find(|e| e.id == entry_id)
+            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+
+        // Verify the ticket matches
+        if entry.ticket_id != payout_info.ticket_id {
+            return Err(Error::BadRequest("Invalid ticket for this entry".into()));
+        }
+
+        // Get competition tickets to verify against stored data
+        let tickets = self.competition_store.get_tickets(competition_id).await?;
+        _ = tickets
+            .get(&entry.id)
+            .ok_or_else(|| Error::NotFound("Ticket not found".into()))?;
+
+        // Verify the entry hasn't already been paid out or pending a payout
+        if let Some(paid_out_at) = entry.paid_out_at {
+            return Err(Error::BadRequest(format!(
+                "Entry {} already paid out at {}",
+                entry.id, paid_out_at
+            )));
+        }
+
+        // Get the current outcome
+        let outcome = competition.get_current_outcome()?;
+        debug!("Current outcome: {:?}", outcome);
+
+        // Get the signed contract to verify winner
+        let signed_contract = competition
+            .signed_contract
+            .as_ref()
+            .ok_or_else(|| Error::BadRequest("Competition contract not yet signed".into()))?;
+
+        // Verify this entry is a winner
+        let winner_weights = signed_contract
+            .params()
+            .outcome_payouts
+            .get(&outcome)
+            .ok_or_else(|| Error::BadRequest("No payout mapping for outcome".into()))?;
+
+        let ephemeral_pubkey = Point::from_hex(&entry.ephemeral_pubkey)
+            .map_err(|e| Error::BadRequest(format!("Invalid ephemeral pubkey: {}", e)))?;
+
+        debug!("Ephemeral pubkey: {:?}", ephemeral_pubkey);
+        debug!("Winner weights: {:?}", winner_weights);
+        debug!("Players: {:?}", signed_contract.params().players);
+
+        let is_winner = winner_weights.iter().any(|(player_index, _)| {
+            if let Some(player) = signed_contract.params().players.get(*player_index) {
+                player.pubkey == ephemeral_pubkey
+            } else {
+                false
+            }
+        });
+
+        if !is_winner {
+            return Err(Error::BadRequest("Entry is not a winner".into()));
+        }
+
+        // Verify the private key matches the public key
+        let provided_private_key = Scalar::from_hex(&payout_info.ephemeral_private_key)
+            .map_err(|e| Error::BadRequest(format!("Invalid private key: {}", e)))?;
+
+        let derived_public_key = provided_private_key.base_point_mul();
+        if derived_public_key != ephemeral_pubkey {
+            return Err(Error::BadRequest(
+                "Invalid private key for this entry".into(),
+            ));
+        }
+
+        // Verify the payout preimage matches the hash
+        let provided_preimage =
+            dlctix::hashlock::preimage_from_hex(&payout_info.payout_preimage)
+                .map_err(|e| Error::BadRequest(format!("Invalid payout preimage: {}", e)))?;
+
+        let derived_hash = dlctix::hashlock::sha256(&provided_preimage);
+        if derived_hash.to_hex_string(dlctix::bitcoin::hex::Case::Lower) != entry.payout_hash {
+            return Err(Error::BadRequest(
+                "Invalid payout preimage for this entry".into(),
+            ));
+        }
+
+        if payout_info.ln_invoice.is_empty() {
+            return Err(Error::BadRequest("Invalid lightning invoice".into()));
+        }
+
+        // Calculate the payout amount based on winner's share of the pool
+        let total_pool = Sats(signed_contract.params().funding_value.to_sat());
+        let (winner_index, winner_weight) = winner_weights
+            .iter()
+            .find_map(|(player_index, weight)| {
+                let player = signed_contract.params().players.get(*player_index)?;
+                if player.pubkey == ephemeral_pubkey {
+                    Some((*player_index, *weight))
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| Error::BadRequest("Unable to determine winner weight".into()))?;
+
+        let payout_amount = split_payout(total_pool, winner_weights)
+            .get(&winner_index)
+            .copied()
+            .ok_or_else(|| Error::BadRequest("Unable to determine winner weight".into()))?;
+
+        debug!(
+            "Total pool: {} sats, Winner weight: {}, Payout amount: {} sats",
+            total_pool, winner_weight, payout_amount
+        );
+
+        let invoice_amount_sats =
+            crate::infra::lightning::extract_amount_from_invoice(&payout_info.ln_invoice)
+                .map_err(|e| Error::BadRequest(format!("Invalid lightning invoice: {}", e)))?;
+
+        if let Some(invoice_amount_sats) = invoice_amount_sats {
+            if Sats(invoice_amount_sats) != payout_amount {
+                return Err(Error::BadRequest(format!(
+                    "Invoice amount {} sats does not match expected payout {} sats",
+                    invoice_amount_sats, payout_amount
+                )));
+            }
+        }
+
+        match self
+            .ln
+            .send_payment(
+                payout_info.ln_invoice.clone(),
+                payout_amount.to_sat(),
+                60,   // TODO(@tee8z): make this timeout configurable, 60 second timeout
+                1000, // TODO(@tee8z): make this fee configurable, 1000 sat fee limit
+            )
+            .await
+        {
+            Ok(_) => {
+                // Store payout info but DON'T mark as paid out yet
+                // The PayoutWatcher will monitor the payment and mark as paid when it settles
+                self.competition_store
+                    .store_payout_info_pending(
+                        entry_id,
+                        payout_info.payout_preimage,
+                        payout_info.ephemeral_private_key,
+                        payout_info.ln_invoice,
+                        payout_amount,
+                    )
+                    .await
+                    .map_err(Error::DbError)
+                    .inspect(|pay_out_id| {
+                        info!("Payout initiated with ID: {}", pay_out_id);
+                        self.kicks.kick_payouts();
+                    })
+                    .map(|_| ())
+            }
+            Err(e) => Err(Error::PaymentFailed(format!(
+                "Failed to initiate lightning payment: {}",
+                e
+            ))),
+        }
+    }
+}
+
+/// Put paid entries in contract player order. The first time, indices are assigned by ticket_id
+/// to match the keymeld subset definitions built from the ticket order at competition creation,
+/// and the new assignments are returned so the caller can persist them.
+fn order_contract_entries(entries: &mut [UserEntry]) -> Result<Vec<(Uuid, usize)>, anyhow::Error> {
+    let assigned = entries
+        .iter()
+        .filter(|entry| entry.player_index.is_some())
+        .count();
+
+    if assigned == 0 {
+        entries.sort_by_key(|entry| entry.ticket_id);
+        return Ok(entries
+            .iter_mut()
+            .enumerate()
+            .map(|(index, entry)| {
+                entry.player_index = Some(index);
+                (entry.id, index)
+            })
+            .collect());
+    }
+    if assigned != entries.len() {
+        return Err(anyhow!(
+            "only {} of {} paid entries have a player index",
+            assigned,
+            entries.len()
+        ));
+    }
+
+    entries.sort_by_key(|entry| entry.player_index);
+    for (expected, entry) in entries.iter().enumerate() {
+        if entry.player_index != Some(expected) {
+            return Err(anyhow!(
+                "entry {} has player index {:?}, expected {}",
+                entry.id,
+                entry.player_index,
+                expected
+            ));
+        }
+    }
+    Ok(vec![])
+}
+
+fn generate_players(
+    entries: &Vec<UserEntry>,
+    tickets: &HashMap<Uuid, Ticket>,
+) -> Result<Vec<Player>, anyhow::Error> {
+    let mut players = vec![];
+    for entry in entries {
+        let Some(ticket) = tickets.get(&entry.id) else {
+            error!("ticket missing for entry {} , skipping", entry.id);
+            continue;
+        };
+
+        let payout_hash = entry.payout_hash.as_str();
+        debug!("player_pubkey: {}", entry.ephemeral_pubkey);
+
+        let pubkey = Point::from_hex(&entry.ephemeral_pubkey)
+            .map_err(|e| anyhow!("failed to get point: {}", e))?;
+        let player = Player {
+            pubkey,
+            // coordinator generates this preimage and only shares after player has paid for a ticket
+            ticket_hash: string_to_byte_array(&ticket.hash),
+            // players generate this preimage and only share after winning with the coordinator
+            payout_hash: string_to_byte_array(payout_hash),
+        };
+        players.push(player);
+    }
+
+    Ok(players)
+}
+
+/// Transient broadcast errors (node unreachable, timelock not matured yet) shouldn't fail the competition
+fn is_transient_broadcast_error(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<BroadcastError>()
+        .is_some_and(|be| be.is_transient())
+}
+
+/// The expiry transaction spends the whole funding output, whatever its outputs don't claim is fee
+fn expiry_tx_fee_rate(expiry_tx: &Transaction, funding_value: Amount) -> FeeRate {
+    let output_value: Amount = expiry_tx.output.iter().map(|output| output.value).sum();
+    let fee = funding_value
+        .checked_sub(output_value)
+        .unwrap_or(Amount::ZERO);
+    let weight = expiry_tx.weight().to_wu().max(1);
+    FeeRate::from_sat_per_kwu(fee.to_sat() * 1000 / weight)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SafeSettlementAction {
+    Settle,
+    Wait,
+    FundingDropped,
+}
+
+/// Safe mode always wants at least one best-chain confirmation, even if settlement at broadcast is configured
+fn safe_settlement_action(
+    required_confirmations: u32,
+    status: TxChainStatus,
+) -> SafeSettlementAction {
+    match status {
+        TxChainStatus::NotFound => SafeSettlementAction::FundingDropped,
+        status if status.confirmations() >= required_confirmations.max(1) => {
+            SafeSettlementAction::Settle
+        }
+        _ => SafeSettlementAction::Wait,
+    }
+}
+
+fn string_to_byte_array(hex_str: &str) -> [u8; 32] {
+    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+    let bytes = hex::decode(hex_str).expect("valid hex string");
+    bytes.try_into().expect("32 bytes")
+}
+
+/// The oracle has to attest to exactly the rankings the contract pays out on, index for index.
+/// With `TiePolicy::Split` that's the tied rankings too, which an oracle unaware of the policy
+/// won't have created locking points for.
+fn check_event_outcomes(
+    estimate: &ContractSizeEstimate,
+    event_announcement: &EventLockingConditions,
+) -> Result<(), anyhow::Error> {
+    let locking_points = event_announcement.locking_points.len() as u64;
+    if locking_points != estimate.attested_outcomes() {
+        return Err(anyhow!(
+            "oracle event has {} outcomes, {} entries over {} places with {:?} ties need {}",
+            locking_points,
+            estimate.total_allowed_entries,
+            estimate.number_of_places_win,
+            estimate.tie_policy,
+            estimate.attested_outcomes()
+        ));
+    }
+    Ok(())
+}
+
+fn generate_payouts(
+    competition: &Competition,
+    entries: &[UserEntry],
+    players: &[Player],
+) -> Result<BTreeMap<Outcome, PayoutWeights>, anyhow::Error> {
+    debug!("Generating payouts for {} players", players.len());
+
+    // Entries are in contract player order, see `order_contract_entries`
+    let mut payouts: BTreeMap<Outcome, PayoutWeights> = BTreeMap::new();
+
+    let possible_rankings = generate_rankings(
+        entries.len(),
+        competition.event_submission.number_of_places_win,
+        competition.event_submission.tie_policy,
+    );
+    debug!("Generated {} possible rankings", possible_rankings.len());
+
+    let percentage_weights =
+        get_percentage_weights(competition.event_submission.number_of_places_win);
+    if percentage_weights.len() != competition.event_submission.number_of_places_win {
+        return Err(anyhow!(
+            "No payout weights for {} winning places",
+            competition.event_submission.number_of_places_win
+        ));
+    }
+
+    // The "refund all" outcome is always the last one, a ranking can also cover every
+    // player when the number of places matches the number of entries
+    let refund_outcome_index = possible_rankings.len() - 1;
+    for (outcome_index, ranking) in possible_rankings.iter().enumerate() {
+        debug!(
+            "Processing outcome {} with ranking: {:?}",
+            outcome_index, ranking
+        );
+
+        // Special handling for "all players" outcome
+        if outcome_index == refund_outcome_index {
+            debug!("Processing special 'all players' outcome for equal refunds");
+
+            // Create equal weights for all players (everyone gets their entry fee back)
+            let equal_weights = equal_weights(players.len());
+
+            debug!(
+                "Final weights for refund outcome {}: {:?}",
+                outcome_index, equal_weights
+            );
+
+            payouts.insert(Outcome::Attestation(outcome_index), equal_weights);
+            continue;
+        }
+
+        // Tied entries share the places they span, see `ranking_weights`
+        let entry_weights = ranking_weights(ranking, &percentage_weights)
+            .map_err(|e| anyhow!("Invalid weights for outcome {}: {}", outcome_index, e))?;
+        let (winner_indices, weights): (Vec<usize>, Vec<u64>) = entry_weights.into_iter().unzip();
+
+        let entry_pubkeys = find_winning_entries_pubkeys(entries, winner_indices);
+        debug!("Winner pubkeys: {:?}", entry_pubkeys);
+
+        let player_indices = find_player_indices(players, entry_pubkeys)?;
+        debug!("Mapped to player indices: {:?}", player_indices);
+
+        if player_indices.len() < competition.event_submission.number_of_places_win {
+            return Err(anyhow!(
+                "Incorrect number of winners for outcome {}",
+                outcome_index
+            ));
+        }
+
+        let mut payout_weights: BTreeMap<PlayerIndex, u64> = BTreeMap::new();
+
+        for (&player_index, weight) in player_indices.iter().zip(weights) {
+            debug!(
+                "Assigning weight {} to player index {}",
+                weight, player_index
+            );
+            payout_weights.insert(player_index, weight);
+        }
+
+        debug!(
+            "Final weights for outcome {}: {:?}",
+            outcome_index, payout_weights
+        );
+
+        payouts.insert(Outcome::Attestation(outcome_index), payout_weights);
+    }
+
+    // Add expiry outcome with equal distribution
+    let expiry_weights = equal_weights(players.len());
+    payouts.insert(Outcome::Expiry, expiry_weights);
+
+    debug!("Generated {} total outcomes", payouts.len());
+    Ok(payouts)
+}
+
+pub fn generate_ranking_permutations(num_players: usize, rankings: usize) -> Vec<Vec<usize>> {
+    let mut permutations = (0..num_players)
+        .permutations(rankings)
+        .collect::<Vec<Vec<usize>>>();
+
+    // Always add the special "refund all" outcome
+    permutations.push((0..num_players).collect());
+
+    permutations
+}
+
+/// Pre-compute DLC subset definitions for keygen session creation.
+///
+/// This generates subset definitions based on the competition parameters (number of entries
+/// and number of winners). Each outcome has a subset containing the coordinator plus all
+/// winning players for that outcome.
+///
+/// The player_user_ids must be ordered to match the player indices (0, 1, 2, ...).
+/// At competition creation time, tickets are generated in order, so ticket_ids map
+/// directly to player indices.
+///
+/// Returns DlcSubsetInfo containing:
+/// - definitions: SubsetDefinition list for keygen session creation
+/// - outcome_subset_ids: Mapping from outcome index to subset UUID for signing
+pub fn compute_dlc_subset_definitions(
+    coordinator_user_id: UserId,
+    player_user_ids: &[UserId],
+    number_of_places_win: usize,
+    tie_policy: TiePolicy,
+) -> DlcSubsetInfo {
+    let num_players = player_user_ids.len();
+    let possible_rankings = generate_rankings(num_players, number_of_places_win, tie_policy);
+
+    let mut definitions = Vec::new();
+    let mut outcome_subset_ids = BTreeMap::new();
+
+    for (outcome_index, ranking) in possible_rankings.iter().enumerate() {
+        let winner_indices: Vec<usize> = ranking.iter().flatten().copied().collect();
+        let subset_id = Uuid::now_v7();
+        outcome_subset_ids.insert(outcome_index, subset_id);
+
+        // Subset includes: coordinator + all winners for this outcome
+        let mut participants = vec![coordinator_user_id.clone()];
+        for &winner_idx in &winner_indices {
+            if winner_idx < player_user_ids.len() {
+                participants.push(player_user_ids[winner_idx].clone());
+            }
+        }
+
+        debug!(
+            "Outcome {}: subset {} with {} participants (winners: {:?})",
+            outcome_index,
+            subset_id,
+            participants.len(),
+            winner_indices
+        );
+
+        definitions.push(SubsetDefinition::with_id(subset_id, participants));
+    }
+
+    info!(
+        "Pre-computed {} subset definitions for {} players with {} winners per outcome",
+        definitions.len(),
+        num_players,
+        number_of_places_win
+    );
+
+    DlcSubsetInfo {
+        definitions,
+        outcome_subset_ids,
+    }
+}
+
+fn find_player_indices(
+    players: &[Player],
+    entry_ticket_pubkeys: Vec<String>,
+) -> Result<Vec<usize>, anyhow::Error> {
+    entry_ticket_pubkeys
+        .into_iter()
+        .map(|pubkey| {
+            let pubkey_point = Point::from_hex(&pubkey)?;
+            players
+                .iter()
+                .position(|player| player.pubkey == pubkey_point)
+                .ok_or_else(|| anyhow!("No player found for pubkey: {}", pubkey))
+        })
+        .collect()
+}
+
+fn find_winning_entries_pubkeys(
+    entries: &[UserEntry],
+    winning_entry_indices: Vec<usize>,
+) -> Vec<String> {
+    winning_entry_indices
+        .into_iter()
+        .map(|idx| entries[idx].ephemeral_pubkey.clone())
+        .collect()
+}
+
+async fn signed_funding_tx(
+    bitcoin_client: Arc<dyn Bitcoin>,
+    mut funding_tx: Psbt,
+) -> Result<Transaction, anyhow::Error> {
+    debug!("Funding Psbt before coordinator signing: {:?}", funding_tx);
+
+    // Sign the PSBT (including escrow inputs)
+    let fully_signed = bitcoin_client
+        .sign_psbt_with_escrow_support(
+            &mut funding_tx,
+            SignOptions {
+                trust_witness_utxo: true,
+                sign_with_tap_internal_key: true,
+                allow_all_sighashes: true,
+                allow_grinding: true,
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    debug!(
+        "Coordinator signing complete. Fully signed: {}",
+        fully_signed
+    );
+
+    // Log signature state after signing
+    for (i, input) in funding_tx.inputs.iter().enumerate() {
+        if input.witness_script.is_some() {
+            debug!("Input {} signature count: {}", i, input.partial_sigs.len());
+            for pk in input.partial_sigs.keys() {
+                debug!("  - Signed by: {}", pk);
+            }
+        }
+    }
+
+    // Verify we have all required signatures
+    let mut validation_errors = Vec::new();
+    for (i, input) in funding_tx.inputs.iter().enumerate() {
+        // Skip already finalized inputs
+        if input.final_script_witness.is_some() {
+            debug!("Input {} is already finalized", i);
+            continue;
+        }
+
+        // Check escrow inputs
+        if input.witness_script.is_some() {
+            // For 2-of-2 multisig escrow, we need exactly 2 signatures
+            if input.partial_sigs.len() < 2 {
+                validation_errors.push(format!(
+                    "Input {}: Escrow input has {} signatures, needs 2",
+                    i,
+                    input.partial_sigs.len()
+                ));
+            }
+        } else if input.partial_sigs.is_empty() {
+            validation_errors.push(format!("Input {}: No signatures present", i));
+        }
+    }
+
+    if !validation_errors.is_empty() {
+        return Err(anyhow!(
+            "PSBT not ready for finalization. Issues: {}",
+            validation_errors.join(", ")
+        ));
+    }
+
+    // Now finalize the PSBT
+    let finalized = bitcoin_client
+        .finalize_psbt_with_escrow_support(&mut funding_tx)
+        .await?;
+
+    if !finalized {
+        return Err(anyhow!("Failed to finalize PSBT after signing"));
+    }
+
+    // Extract the final transaction
+    match funding_tx.clone().extract_tx() {
+        Ok(final_tx) => {
+            debug!(
+                "Successfully extracted and finalized funding transaction: {}",
+                final_tx.compute_txid()
+            );
+            Ok(final_tx)
+        }
+        Err(e) => {
+            // Detailed debugging if extraction fails
+            debug!("Failed to extract transaction: {:?}", e);
+
+            for (i, input) in funding_tx.inputs.iter().enumerate() {
+                if input.final_script_witness.is_none() && input.witness_script.is_some() {
+                    debug!("Input {} not finalized:", i);
+                    debug!("  - Signatures: {}", input.partial_sigs.len());
+                    if let Some(ws) = &input.witness_script {
+                        debug!("  - Witness script: {}", ws.to_hex_string());
+                    }
+                }
+            }
+
+            Err(anyhow!("Failed to extract transaction: {}", e))
+        }
+    }
+}
+
+fn p2tr_script_pubkey(pubkey: Point) -> ScriptBuf {
+    let (xonly, _) = pubkey.into();
+    let tweaked = TweakedPublicKey::dangerous_assume_tweaked(convert_xonly_key(xonly));
+    ScriptBuf::new_p2tr_tweaked(tweaked)
+}
+
+/// What the funding transaction moves: the escrow outputs it consumes, the contract's funding
+/// output and the miner fee, all from the coordinator's point of view
+fn funding_ledger_entries(
+    competition: &Competition,
+    funding_psbt: &Psbt,
+) -> Result<Vec<LedgerEntry>, anyhow::Error> {
+    let funding_outpoint = competition
+        .funding_outpoint
+        .ok_or_else(|| anyhow!("No funding outpoint for competition {}", competition.id))?;
+    let funding_output = funding_psbt
+        .unsigned_tx
+        .output
+        .get(funding_outpoint.vout as usize)
+        .ok_or_else(|| anyhow!("Funding output missing from funding psbt"))?;
+    let fee = funding_psbt
+        .fee()
+        .map_err(|e| anyhow!("Failed to compute funding transaction fee: {}", e))?;
+
+    let mut entries: Vec<LedgerEntry> = funding_psbt
+        .inputs
+        .iter()
+        .zip(&funding_psbt.unsigned_tx.input)
+        .filter(|(input, _)| input.witness_script.is_some())
+        .filter_map(|(input, txin)| {
+            let escrow_output = input.witness_utxo.as_ref()?;
+            Some(LedgerEntry::new(
+                competition.id,
+                None,
+                LedgerEntryKind::EscrowInputConsumed,
+                escrow_output.value.to_sat(),
+                txin.previous_output.to_string(),
+            ))
+        })
+        .collect();
+    entries.push(LedgerEntry::new(
+        competition.id,
+        None,
+        LedgerEntryKind::FundingOutputCreated,
+        funding_output.value.to_sat(),
+        funding_outpoint.to_string(),
+    ));
+    entries.push(LedgerEntry::new(
+        competition.id,
+        None,
+        LedgerEntryKind::MinerFee,
+        fee.to_sat(),
+        funding_outpoint.txid.to_string(),
+    ));
+    Ok(entries)
+}
+
+fn simple_sweep_tx(
+    destination_pubkey: Point,
+    input: TxIn,
+    input_weight: InputWeightPrediction,
+    prevout_value: Amount,
+    fee_rate: FeeRate,
+) -> Transaction {
+    let script_pubkey = p2tr_script_pubkey(destination_pubkey);
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![input],
+        output: vec![TxOut {
+            value: {
+                let tx_weight = predict_weight([input_weight], [script_pubkey.len()]);
+                let fee = tx_weight * fee_rate;
+                prevout_value - fee
+            },
+            script_pubkey,
+        }],
+    }
+}
+
+/// The entry's player and the outcomes and win conditions it signs in the contract
+fn player_sig_map(
+    contract_parameters: &ContractParameters,
+    entry: &UserEntry,
+) -> Result<(Point, SigMap<()>), Error> {
+    let player = Point::from_hex(&entry.ephemeral_pubkey).map_err(|e| {
+        Error::BadRequest(format!(
+            "Invalid ephemeral pubkey for entry {}: {}",
+            entry.id, e
+        ))
+    })?;
+    let expected = contract_parameters
+        .sigmap_for_pubkey(player)
+        .ok_or_else(|| {
+            Error::BadRequest(format!(
+                "Entry {} is not a player in the contract",
+                entry.id
+            ))
+        })?;
+    Ok((player, expected))
+}
+
+/// The coordinator's public nonces for a contract. The `TicketedDLC` and signing session are
+/// built on the blocking thread pool once `permits` has a slot, and dropped before this returns
+/// so only the nonces outlive the build.
+pub async fn build_coordinator_nonces(
+    permits: &Semaphore,
+    contract_params: ContractParameters,
+    funding_outpoint: OutPoint,
+    coordinator_key: Scalar,
+) -> Result<SigMap<PubNonce>, anyhow::Error> {
+    let _permit = permits
+        .acquire()
+        .await
+        .map_err(|e| anyhow!("contract builds closed: {}", e))?;
+    tokio::task::spawn_blocking(move || {
+        let ticketed_dlc = TicketedDLC::new(contract_params, funding_outpoint)?;
+        debug!("Built ticketed dlc");
+        let mut rng = create_deterministic_rng(&funding_outpoint, coordinator_key);
+        let signing_session =
+            SigningSession::<NonceSharingRound>::new(ticketed_dlc, &mut rng, coordinator_key)?;
+        Ok::<_, anyhow::Error>(signing_session.our_public_nonces().to_owned())
+    })
+    .await
+    .map_err(|e| anyhow!("contract build task failed: {}", e))?
+}
+
+#[cfg(test)]
+thread_local! {
+    /// `TicketedDLC`s built through `new_ticketed_dlc` on this thread
+    static TICKETED_DLC_BUILDS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Builds every transaction of the contract, the most expensive step of signing it
+fn new_ticketed_dlc(
+    contract_parameters: ContractParameters,
+    funding_outpoint: OutPoint,
+) -> Result<TicketedDLC, anyhow::Error> {
+    #[cfg(test)]
+    TICKETED_DLC_BUILDS.with(|builds| builds.set(builds.get() + 1));
+    Ok(TicketedDLC::new(contract_parameters, funding_outpoint)?)
+}
+
+/// The coordinator's signing session with every entry's nonces aggregated and its own partial
+/// signatures computed, checking the nonces it derives match the ones it gave out
+fn build_coordinator_session(
+    contract_parameters: ContractParameters,
+    funding_outpoint: OutPoint,
+    coordinator_key: Scalar,
+    our_nonces: &SigMap<PubNonce>,
+    received_nonces: BTreeMap<Point, SigMap<PubNonce>>,
+) -> Result<SigningSession<CoordinatorPartialSignatureRound>, anyhow::Error> {
+    let ticketed_dlc = new_ticketed_dlc(contract_parameters, funding_outpoint)?;
+    let mut rng = create_deterministic_rng(&funding_outpoint, coordinator_key);
+    let signing_session =
+        SigningSession::<NonceSharingRound>::new(ticketed_dlc, &mut rng, coordinator_key)?;
+    if signing_session.our_public_nonces() != our_nonces {
+        return Err(anyhow!("coordinator nonce mismatch"));
+    }
+    Ok(signing_session.aggregate_nonces_and_compute_partial_signatures(received_nonces)?)
+}
+
+/// The session the aggregation step cached for this contract and funding outpoint, or after a
+/// restart or a change to either, one rebuilt from the stored contract
+fn coordinator_signing_session(
+    cache: &SigningSessionCache,
+    competition_id: Uuid,
+    contract_parameters: &ContractParameters,
+    funding_outpoint: OutPoint,
+    coordinator_key: Scalar,
+    our_nonces: &SigMap<PubNonce>,
+    received_nonces: BTreeMap<Point, SigMap<PubNonce>>,
+) -> Result<SigningSession<CoordinatorPartialSignatureRound>, anyhow::Error> {
+    if let Some(session) = cache.take(competition_id, funding_outpoint, contract_parameters) {
+        debug!(
+            "Reusing signing session for competition {} from nonce aggregation",
+            competition_id
+        );
+        return Ok(session);
+    }
+    build_coordinator_session(
+        contract_parameters.to_owned(),
+        funding_outpoint,
+        coordinator_key,
+        our_nonces,
+        received_nonces,
+    )
+}
+
+fn create_deterministic_rng(funding_outpoint: &OutPoint, private_key: Scalar) -> ChaCha20Rng {
+    let mut hasher = sha256::Hash::engine();
+
+    hasher.write_all(&funding_outpoint.txid[..]).unwrap();
+    hasher
+        .write_all(&funding_outpoint.vout.to_le_bytes())
+        .unwrap();
+    hasher.write_all(&private_key.serialize()).unwrap();
+
+    let hash = sha256::Hash::from_engine(hasher);
+    let seed: [u8; 32] = hash.to_byte_array();
+    ChaCha20Rng::from_seed(seed)
+}
+
+fn validate_create_event(
+    create_event: &CreateEvent,
+    max_total_allowed_entries: usize,
+    max_pool_subsidy_sats: u64,
+    max_invoice_settlement_confirmations: u32,
+    min_lead_time: std::time::Duration,
+) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    if create_event.total_allowed_entries > max_total_allowed_entries {
+        errors.push(
+            "total_allowed_entries",
+            "too_many_entries",
+            format!(
+                "total allowed entries exceeds maximum allowed {}, got {}",
+                max_total_allowed_entries, create_event.total_allowed_entries
+            ),
+        );
+    }
+
+    if create_event.checked_invoice_amount().is_none()
+        || create_event
+            .entry_fee
+            .checked_mul(create_event.total_allowed_entries as u64)
+            .is_none()
+    {
+        errors.push(
+            "entry_fee",
+            "overflow",
+            format!(
+                "entry fee of {} sats with a {}% coordinator fee over {} entries overflows",
+                create_event.entry_fee,
+                create_event.coordinator_fee_percentage,
+                create_event.total_allowed_entries
+            ),
+        );
+    }
+
+    let subsidy = create_event.pool_subsidy(create_event.total_allowed_entries);
+    if subsidy.to_sat() > max_pool_subsidy_sats {
+        errors.push(
+            "total_competition_pool",
+            "exceeds_entry_fees",
+            format!(
+                "total competition pool needs {} sats beyond the entry fees, the coordinator subsidizes at most {}",
+                subsidy, max_pool_subsidy_sats
+            ),
+        );
+    }
+
+    if let Some(confirmations) = create_event.invoice_settlement_confirmations {
+        if confirmations > max_invoice_settlement_confirmations {
+            errors.push(
+                "invoice_settlement_confirmations",
+                "too_many_confirmations",
+                format!(
+                    "invoice settlement confirmations exceeds maximum allowed {}, got {}",
+                    max_invoice_settlement_confirmations, confirmations
+                ),
+            );
+        }
+    }
+
+    if create_event.tie_policy == TiePolicy::Split {
+        let outcomes = split_ranking_count(
+            create_event.total_allowed_entries,
+            create_event.number_of_places_win,
+        );
+        if outcomes > MAX_SPLIT_OUTCOMES {
+            errors.push(
+                "tie_policy",
+                "too_many_outcomes",
+                format!(
+                    "splitting ties over {} entries and {} places needs {} outcomes, at most {} are supported",
+                    create_event.total_allowed_entries,
+                    create_event.number_of_places_win,
+                    outcomes,
+                    MAX_SPLIT_OUTCOMES
+                ),
+            );
+        }
+    }
+
+    if create_event.number_of_places_win > 5 {
+        errors.push(
+            "number_of_places_win",
+            "too_many_winners",
+            format!(
+                "number of winners exceeds maximum allowed 5, got {}",
+                create_event.number_of_places_win
+            ),
+        );
+    }
+
+    for relay in &create_event.backup_relays {
+        let is_websocket = nostr_sdk::Url::parse(relay)
+            .map(|url| matches!(url.scheme(), "ws" | "wss"))
+            .unwrap_or(false);
+        if !is_websocket {
+            errors.push(
+                "backup_relays",
+                "invalid_relay_url",
+                format!("backup relay must be a ws:// or wss:// url, got {}", relay),
+            );
+        }
+    }
+
+    let lead_time = create_event.start_observation_date - OffsetDateTime::now_utc();
+    if lead_time < min_lead_time {
+        errors.push(
+            "start_observation_date",
+            "insufficient_lead_time",
+            format!(
+                "start observation date must be at least {} after creation to allow for signing and funding, got {}",
+                format_lead_time(min_lead_time.as_secs() as i64),
+                format_lead_time(lead_time.whole_seconds()),
+            ),
+        );
+    }
+
+    if let Some(name) = &create_event.name {
+        if name.trim().is_empty() || name.trim() != name || name.chars().count() > 100 {
+            errors.push(
+                "name",
+                "invalid_name",
+                "name must be 1 to 100 characters without leading or trailing whitespace",
+            );
+        }
+    }
+
+    if let Some(slug) = create_event
+        .competition_slug()
+        .filter(|slug| !is_valid_slug(slug))
+    {
+        errors.push(
+            "slug",
+            "invalid_slug",
+            format!(
+                "slug must be 3 to {} lowercase letters, digits and dashes and not a uuid, got {}",
+                MAX_SLUG_LEN, slug
+            ),
+        );
+    }
+
+    if let Some(signing_deadline) = create_event.signing_deadline {
+        if signing_deadline >= create_event.start_observation_date {
+            errors.push(
+                "signing_deadline",
+                "after_observation_start",
+                format!(
+                    "signing deadline {} must be before the start observation date {}",
+                    signing_deadline, create_event.start_observation_date
+                ),
+            );
+        }
+    }
+
+    errors.into_result()
+}
+
+/// Tags have to be well formed, unique and, when the coordinator restricts them, allowed
+fn validate_tags(tags: &[String], allowed_tags: &[String]) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    if tags.len() > MAX_COMPETITION_TAGS {
+        errors.push(
+            "tags",
+            "too_many_tags",
+            format!(
+                "a competition can have at most {} tags, got {}",
+                MAX_COMPETITION_TAGS,
+                tags.len()
+            ),
+        );
+    }
+    for (index, tag) in tags.iter().enumerate() {
+        if !is_valid_tag(tag) {
+            errors.push(
+                "tags",
+                "invalid_tag",
+                format!(
+                    "tags must be 1 to {} lowercase letters, digits and dashes, got {}",
+                    MAX_TAG_LEN, tag
+                ),
+            );
+        } else if !allowed_tags.is_empty() && !allowed_tags.contains(tag) {
+            errors.push(
+                "tags",
+                "unknown_tag",
+                format!("tag {} is not one of {}", tag, allowed_tags.join(", ")),
+            );
+        } else if tags[..index].contains(tag) {
+            errors.push("tags", "duplicate_tag", format!("tag {} is repeated", tag));
+        }
+    }
+    errors.into_result()
+}
+
+fn format_lead_time(seconds: i64) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    let minutes = seconds.unsigned_abs().div_ceil(60);
+    format!("{}{}h {}m", sign, minutes / 60, minutes % 60)
+}
+
+/// The shape checks the wasm client runs too, plus that the ephemeral pubkey is on the curve
+fn validate_entry_keys(entry: &AddEntry) -> Result<(), ValidationErrors> {
+    let mut errors = validate_entry_secrets(
+        &entry.ephemeral_pubkey,
+        &entry.ephemeral_privatekey_encrypted,
+        &entry.payout_hash,
+        &entry.payout_preimage_encrypted,
+    )
+    .err()
+    .unwrap_or_default();
+    if !errors
+        .errors
+        .iter()
+        .any(|error| error.field == "ephemeral_pubkey")
+        && Point::from_hex(&entry.ephemeral_pubkey).is_err()
+    {
+        errors.push(
+            "ephemeral_pubkey",
+            "invalid_pubkey",
+            "must be a point on secp256k1",
+        );
+    }
+    errors.into_result()
+}
+
+async fn validate_entry(
+    entry: AddEventEntry,
+    competition: Competition,
+) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    if entry.id.get_version_num() != 7 {
+        errors.push("id", "invalid_uuid_version", "id must be a UUIDv7");
+    }
+
+    let choice_count: usize = entry
+        .expected_observations
+        .iter()
+        .map(|weather_choice| {
+            [
+                weather_choice.temp_high.is_some(),
+                weather_choice.temp_low.is_some(),
+                weather_choice.wind_speed.is_some(),
+            ]
+            .into_iter()
+            .filter(|picked| *picked)
+            .count()
         })
+        .sum();
+    if let Err(count_errors) = validate_value_count(
+        "expected_observations",
+        choice_count,
+        competition.event_submission.number_of_values_per_entry,
+    ) {
+        errors.errors.extend(count_errors.errors);
     }
 
-    /// Get keymeld signing info for a user's entry
-    /// Only returns info if the user's ticket has been paid (HODL invoice accepted)
-    /// Decrypts the stored session secret and re-encrypts it to the user's nostr pubkey
-    async fn get_keymeld_signing_info(
-        &self,
-        competition_id: Uuid,
-        user_pubkey: &str,
-        entry: &UserEntry,
-    ) -> Result<KeymeldSigningInfo, Error> {
-        use nostr_sdk::nips::nip44;
-        use nostr_sdk::prelude::{PublicKey, SecretKey};
+    for (index, weather_choice) in entry.expected_observations.iter().enumerate() {
+        if !competition
+            .event_submission
+            .locations
+            .contains(&weather_choice.stations)
+        {
+            errors.push(
+                format!("expected_observations[{}].stations", index),
+                "unknown_location",
+                format!(
+                    "location {} is not part of this competition",
+                    weather_choice.stations
+                ),
+            );
+        }
+    }
 
-        // Check that the user's ticket has been paid before revealing keymeld info
-        let ticket = self
-            .competition_store
-            .get_ticket(entry.ticket_id)
-            .await
-            .map_err(Error::DbError)?;
+    errors.into_result()
+}
 
-        if ticket.paid_at.is_none() {
-            return Err(Error::BadRequest(
-                "Keymeld info not available until payment is received".to_string(),
-            ));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn test_entry(ticket_id: Uuid, player_index: Option<usize>) -> UserEntry {
+        let mut entry = AddEntry {
+            id: Uuid::now_v7(),
+            ticket_id,
+            ephemeral_pubkey: String::new(),
+            ephemeral_privatekey_encrypted: String::new(),
+            payout_hash: String::new(),
+            payout_preimage_encrypted: String::new(),
+            event_id: Uuid::now_v7(),
+            expected_observations: vec![],
+            encrypted_keymeld_private_key: None,
+            keymeld_auth_pubkey: None,
         }
+        .into_user_entry(String::new());
+        entry.player_index = player_index;
+        entry
+    }
 
-        let stored_session = self
-            .competition_store
-            .get_keymeld_session(competition_id)
-            .await
-            .map_err(Error::DbError)?
-            .ok_or_else(|| {
-                Error::NotFound(format!(
-                    "Keymeld session not found for competition {}",
-                    competition_id
-                ))
-            })?;
+    #[tokio::test]
+    async fn test_validate_entry_reports_every_invalid_field() {
+        use crate::infra::oracle::{ValueOptions, WeatherChoices};
 
-        let gateway_url = self
-            .keymeld_gateway_url
-            .clone()
-            .ok_or_else(|| Error::BadRequest("Keymeld gateway URL not configured".to_string()))?;
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(6);
+        let competition = Competition::new(&CreateEvent {
+            number_of_values_per_entry: 2,
+            ..CreateEvent::fixture(start)
+        });
+        let choice = |station: &str| WeatherChoices {
+            stations: station.to_string(),
+            wind_speed: Some(ValueOptions::Over),
+            temp_high: None,
+            temp_low: Some(ValueOptions::Under),
+        };
+        let entry = AddEntry {
+            id: Uuid::nil(),
+            ticket_id: Uuid::now_v7(),
+            ephemeral_pubkey: String::new(),
+            ephemeral_privatekey_encrypted: String::new(),
+            payout_hash: String::new(),
+            payout_preimage_encrypted: String::new(),
+            event_id: competition.id,
+            expected_observations: vec![choice("KORD"), choice("KSEA")],
+            encrypted_keymeld_private_key: None,
+            keymeld_auth_pubkey: None,
+        };
 
-        // Decrypt the session secret from storage
-        let session_secret =
-            self.decrypt_session_secret(&stored_session.encrypted_session_secret)?;
+        let errors = validate_entry(entry.into(), competition).await.unwrap_err();
+        let codes: Vec<(&str, &str)> = errors
+            .errors
+            .iter()
+            .map(|e| (e.field.as_str(), e.code.as_str()))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("id", "invalid_uuid_version"),
+                ("expected_observations", "too_many_values"),
+                ("expected_observations[1].stations", "unknown_location"),
+            ]
+        );
+    }
 
-        // Re-encrypt to the user's pubkey
-        let nostr_pubkey = PublicKey::from_hex(user_pubkey)
-            .map_err(|e| Error::BadRequest(format!("Invalid user pubkey: {}", e)))?;
+    #[test]
+    fn test_order_contract_entries_assigns_by_ticket_then_keeps_order() {
+        let tickets: Vec<Uuid> = (0..3).map(|_| Uuid::now_v7()).collect();
+        let mut entries = vec![
+            test_entry(tickets[2], None),
+            test_entry(tickets[0], None),
+            test_entry(tickets[1], None),
+        ];
+
+        let assigned = order_contract_entries(&mut entries).unwrap();
+        assert_eq!(assigned.len(), 3);
+        let ordered: Vec<Uuid> = entries.iter().map(|entry| entry.ticket_id).collect();
+        assert_eq!(ordered, tickets);
+
+        // Persisted indices win over ticket order
+        let mut entries = vec![
+            test_entry(tickets[0], Some(2)),
+            test_entry(tickets[1], Some(0)),
+            test_entry(tickets[2], Some(1)),
+        ];
+        assert!(order_contract_entries(&mut entries).unwrap().is_empty());
+        let ordered: Vec<Uuid> = entries.iter().map(|entry| entry.ticket_id).collect();
+        assert_eq!(ordered, vec![tickets[1], tickets[2], tickets[0]]);
+    }
 
-        let coordinator_secret_key = SecretKey::from_slice(&self.private_key.serialize())
-            .map_err(|e| Error::BadRequest(format!("Failed to create secret key: {}", e)))?;
+    #[test]
+    fn test_order_contract_entries_rejects_partial_or_gapped_indices() {
+        let mut entries = vec![
+            test_entry(Uuid::now_v7(), Some(0)),
+            test_entry(Uuid::now_v7(), None),
+        ];
+        assert!(order_contract_entries(&mut entries).is_err());
+
+        let mut entries = vec![
+            test_entry(Uuid::now_v7(), Some(0)),
+            test_entry(Uuid::now_v7(), Some(2)),
+        ];
+        assert!(order_contract_entries(&mut entries).is_err());
+    }
 
-        let encrypted_session_secret = nip44::encrypt(
-            &coordinator_secret_key,
-            &nostr_pubkey,
-            hex::encode(session_secret),
-            nip44::Version::V2,
-        )
-        .map_err(|e| Error::BadRequest(format!("NIP-44 encryption failed: {}", e)))?;
+    #[test]
+    fn test_player_order_verifies_whatever_order_entries_arrive_in() {
+        let tickets: Vec<Uuid> = (0..4).map(|_| Uuid::now_v7()).collect();
+        let player_order = player_order_from_tickets(&tickets);
 
-        Ok(KeymeldSigningInfo {
-            enabled: true,
-            gateway_url,
-            session_id: stored_session.session_id,
-            encrypted_session_secret,
-            // Use ticket_id as user_id for consistency with keygen session creation
-            user_id: entry.ticket_id.to_string(),
-        })
+        for insertion_order in [[3, 1, 0, 2], [2, 3, 1, 0], [0, 1, 2, 3]] {
+            let mut entries: Vec<UserEntry> = insertion_order
+                .iter()
+                .map(|&index| {
+                    let mut entry = test_entry(tickets[index], None);
+                    entry.ephemeral_pubkey = format!("pubkey-{}", index);
+                    entry
+                })
+                .collect();
+            order_contract_entries(&mut entries).unwrap();
+            verify_player_order(&player_order, &entries).unwrap();
+
+            // Once pubkeys are recorded they have to keep matching too
+            let recorded = player_order_from_entries(&entries);
+            assert_eq!(recorded[3].ephemeral_pubkey.as_deref(), Some("pubkey-3"));
+            verify_player_order(&recorded, &entries).unwrap();
+            entries[3].ephemeral_pubkey = String::from("swapped");
+            assert!(verify_player_order(&recorded, &entries).is_err());
+        }
     }
 
-    pub async fn submit_public_nonces(
-        &self,
-        pubkey: String,
-        competition_id: Uuid,
-        entry_id: Uuid,
-        public_nonces: SigMap<PubNonce>,
-    ) -> Result<(), Error> {
-        debug!(
-            "Submitting nonces for entry {}: {:?}",
-            entry_id, public_nonces
+    #[test]
+    fn test_player_order_reports_dropped_entry() {
+        let tickets: Vec<Uuid> = (0..3).map(|_| Uuid::now_v7()).collect();
+        let player_order = player_order_from_tickets(&tickets);
+
+        // Without ticket 1 every later entry shifts down a player index
+        let mut entries = vec![test_entry(tickets[2], None), test_entry(tickets[0], None)];
+        order_contract_entries(&mut entries).unwrap();
+
+        let mismatch = verify_player_order(&player_order, &entries).unwrap_err();
+        assert_eq!(
+            mismatch.differences,
+            vec![
+                format!(
+                    "index 1: keymeld ticket {}, contract ticket {}",
+                    tickets[1], tickets[2]
+                ),
+                format!(
+                    "index 2: keymeld ticket {} has no contract entry",
+                    tickets[2]
+                ),
+            ]
         );
-        let competition = self
-            .competition_store
-            .get_competition(competition_id)
-            .await?;
-
-        if competition.contract_parameters.is_none() {
-            return Err(Error::BadRequest(
-                "Contract parameters not yet available".to_string(),
-            ));
-        }
+        assert!(mismatch.to_string().contains(&tickets[1].to_string()));
+    }
 
-        if public_nonces.is_mirror(&competition.public_nonces.unwrap()) {
-            return Err(Error::BadRequest(format!(
-                "public nonces do not match competitions: {}",
-                entry_id
-            )));
-        }
+    #[test]
+    fn test_event_outcomes_must_match_the_rankings() {
+        use dlctix::EventLockingConditions;
+
+        let event = |outcomes: u64| EventLockingConditions {
+            locking_points: (1..=outcomes)
+                .map(|i| {
+                    let hash = sha256::Hash::hash(format!("outcome_{}", i).as_bytes());
+                    Scalar::from_slice(&hash.to_byte_array())
+                        .unwrap()
+                        .base_point_mul()
+                })
+                .collect(),
+            expiry: Some(2_000_000_000),
+        };
+        let tiebreak = ContractSizeEstimate::new(3, 1, TiePolicy::Tiebreak);
+        let split = ContractSizeEstimate::new(3, 1, TiePolicy::Split);
+        assert_eq!(tiebreak.attested_outcomes(), 4);
+        assert_eq!(split.attested_outcomes(), 7);
+
+        check_event_outcomes(&tiebreak, &event(4)).unwrap();
+        check_event_outcomes(&split, &event(7)).unwrap();
+        // An oracle that only knows strict rankings leaves the tied ones without a locking point
+        let err = check_event_outcomes(&split, &event(4)).unwrap_err();
+        assert!(err.to_string().contains("has 4 outcomes"), "{}", err);
+        assert!(check_event_outcomes(&tiebreak, &event(7)).is_err());
+    }
 
-        let entries = self
-            .competition_store
-            .get_user_entries(
-                pubkey,
-                SearchBy {
-                    event_ids: Some(vec![competition_id]),
-                },
-            )
-            .await?;
+    #[test]
+    fn test_expiry_tx_fee_rate_from_funding_value() {
+        let expiry_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![TxOut {
+                value: Amount::from_sat(99_000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        let weight = expiry_tx.weight().to_wu();
 
-        let entry = entries
-            .iter()
-            .find(|e| e.id == entry_id)
-            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+        let fee_rate = expiry_tx_fee_rate(&expiry_tx, Amount::from_sat(100_000));
+        assert_eq!(fee_rate.to_sat_per_kwu(), 1_000_000 / weight);
 
-        if entry.public_nonces.is_some() {
-            return Err(Error::BadRequest(
-                "Public nonces already submitted for this entry".to_string(),
-            ));
-        }
+        // Outputs worth more than the funding value can't produce a negative fee
+        let fee_rate = expiry_tx_fee_rate(&expiry_tx, Amount::from_sat(50_000));
+        assert_eq!(fee_rate, FeeRate::ZERO);
+    }
 
-        self.competition_store
-            .add_public_nonces(entry_id, public_nonces)
-            .await
-            .map_err(|e| {
-                error!(
-                    "failed save entry public nonces: entry_id {}, event_id {} {:?}",
-                    entry_id, competition_id, e
-                );
-                Error::DbError(e)
-            })?;
+    #[test]
+    fn test_safe_settlement_waits_for_best_chain_confirmations() {
+        assert_eq!(
+            safe_settlement_action(0, TxChainStatus::Unconfirmed),
+            SafeSettlementAction::Wait
+        );
+        assert_eq!(
+            safe_settlement_action(
+                0,
+                TxChainStatus::Confirmed {
+                    height: 100,
+                    confirmations: 1
+                }
+            ),
+            SafeSettlementAction::Settle
+        );
+        assert_eq!(
+            safe_settlement_action(
+                3,
+                TxChainStatus::Confirmed {
+                    height: 100,
+                    confirmations: 2
+                }
+            ),
+            SafeSettlementAction::Wait
+        );
+        assert_eq!(
+            safe_settlement_action(
+                3,
+                TxChainStatus::Confirmed {
+                    height: 100,
+                    confirmations: 3
+                }
+            ),
+            SafeSettlementAction::Settle
+        );
+    }
 
-        Ok(())
+    #[test]
+    fn test_safe_settlement_detects_dropped_funding() {
+        assert_eq!(
+            safe_settlement_action(1, TxChainStatus::NotFound),
+            SafeSettlementAction::FundingDropped
+        );
     }
 
-    pub async fn get_aggregate_nonces(
-        &self,
-        pubkey: String,
-        competition_id: Uuid,
-    ) -> Result<SigMap<AggNonce>, Error> {
-        let competition = self
-            .competition_store
-            .get_competition(competition_id)
-            .await?;
+    #[test]
+    fn test_validate_create_event_caps_total_allowed_entries() {
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(6);
+        let mut create_event = CreateEvent {
+            number_of_values_per_entry: 1,
+            total_allowed_entries: 25,
+            total_competition_pool: Sats(25_000),
+            ..CreateEvent::fixture(start)
+        };
+        assert!(
+            validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO).is_ok()
+        );
 
-        let entries = self
-            .competition_store
-            .get_user_entries(
-                pubkey,
-                SearchBy {
-                    event_ids: Some(vec![competition_id]),
-                },
-            )
-            .await?;
+        create_event.total_allowed_entries = 26;
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "total_allowed_entries");
+        assert_eq!(errors.errors[0].code, "too_many_entries");
+    }
 
-        if entries.is_empty() {
-            return Err(Error::NotFound(format!(
-                "No entry found for user in competition {}",
-                competition_id
-            )));
-        }
+    #[test]
+    fn test_validate_tags() {
+        let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        let codes = |result: Result<(), ValidationErrors>| -> Vec<String> {
+            result
+                .err()
+                .map(|errors| errors.errors.into_iter().map(|error| error.code).collect())
+                .unwrap_or_default()
+        };
 
-        competition.aggregated_nonces.ok_or_else(|| {
-            Error::NotFound(format!(
-                "Aggregate nonces not yet available for competition {}",
-                competition_id
-            ))
-        })
-    }
+        assert!(validate_tags(&tags(&["northeast", "daily"]), &[]).is_ok());
+        assert_eq!(
+            codes(validate_tags(&tags(&["North East", "-daily"]), &[])),
+            vec!["invalid_tag", "invalid_tag"]
+        );
+        assert_eq!(
+            codes(validate_tags(&tags(&["daily", "daily"]), &[])),
+            vec!["duplicate_tag"]
+        );
 
-    pub async fn submit_final_signatures(
-        &self,
-        pubkey: String,
-        competition_id: Uuid,
-        entry_id: Uuid,
-        final_signatures: FinalSignatures,
-    ) -> Result<(), Error> {
-        let competition = self
-            .competition_store
-            .get_competition(competition_id)
-            .await?;
+        let allowed = tags(&["northeast", "temperature", "daily"]);
+        assert!(validate_tags(&tags(&["temperature"]), &allowed).is_ok());
+        assert_eq!(
+            codes(validate_tags(&tags(&["weekly"]), &allowed)),
+            vec!["unknown_tag"]
+        );
 
-        if competition.contract_parameters.is_none() {
-            return Err(Error::BadRequest(
-                "Contract parameters not yet available".to_string(),
-            ));
-        }
+        let too_many: Vec<String> = (0..=MAX_COMPETITION_TAGS)
+            .map(|i| format!("tag-{}", i))
+            .collect();
+        assert_eq!(codes(validate_tags(&too_many, &[])), vec!["too_many_tags"]);
+    }
 
-        let Some(comp_partial_signatures) = competition.partial_signatures else {
-            return Err(Error::BadRequest(
-                "Contract partial_signatures not yet available".to_string(),
-            ));
+    #[test]
+    fn test_validate_create_event_rejects_non_websocket_relays() {
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(6);
+        let mut create_event = CreateEvent {
+            number_of_values_per_entry: 1,
+            total_allowed_entries: 5,
+            total_competition_pool: Sats(5_000),
+            backup_relays: vec!["wss://relay.example.com".to_string()],
+            ..CreateEvent::fixture(start)
         };
+        assert!(
+            validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO).is_ok()
+        );
 
-        if final_signatures
-            .partial_signatures
-            .is_mirror(&comp_partial_signatures)
-        {
-            return Err(Error::BadRequest(format!(
-                "partial signatures do not match competitions: {}",
-                entry_id
-            )));
-        }
-        debug!("adding signatures on entry {} for {}", entry_id, pubkey);
-        let entries = self
-            .competition_store
-            .get_user_entries(
-                pubkey,
-                SearchBy {
-                    event_ids: Some(vec![competition_id]),
-                },
-            )
-            .await?;
+        create_event
+            .backup_relays
+            .push("https://relay.example.com".to_string());
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "backup_relays");
+        assert_eq!(errors.errors[0].code, "invalid_relay_url");
+    }
 
-        entries
-            .iter()
-            .find(|e| e.id == entry_id)
-            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+    #[test]
+    fn test_validate_create_event_checks_name_and_slug() {
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(6);
+        let mut create_event = CreateEvent {
+            number_of_values_per_entry: 1,
+            total_allowed_entries: 5,
+            total_competition_pool: Sats(5_000),
+            name: Some("Chicago Heat Wave!".to_string()),
+            ..CreateEvent::fixture(start)
+        };
+        assert!(
+            validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO).is_ok()
+        );
+        assert_eq!(
+            create_event.competition_slug().as_deref(),
+            Some("chicago-heat-wave")
+        );
 
-        self.competition_store
-            .add_final_signatures(entry_id, final_signatures)
-            .await
-            .map_err(|e| {
-                error!(
-                    "failed save entry signatures: entry_id {}, event_id {} {:?}",
-                    entry_id, competition_id, e
-                );
-                Error::DbError(e)
-            })?;
+        // Too short for a slug of its own, the id stands in rather than failing validation
+        create_event.name = Some("Q!".to_string());
+        assert!(
+            validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO).is_ok()
+        );
+        assert_eq!(
+            create_event.competition_slug(),
+            Some(format!("competition-{}", create_event.id.simple()))
+        );
 
-        Ok(())
+        create_event.slug = Some(create_event.id.to_string());
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "slug");
+        assert_eq!(errors.errors[0].code, "invalid_slug");
+
+        create_event.name = Some(" padded ".to_string());
+        create_event.slug = Some("Not-Lowercase".to_string());
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors.len(), 2);
+        assert_eq!(errors.errors[0].code, "invalid_name");
+        assert_eq!(errors.errors[1].code, "invalid_slug");
     }
 
-    pub async fn submit_ticket_payout(
-        &self,
-        pubkey: String,
-        competition_id: Uuid,
-        entry_id: Uuid,
-        payout_info: PayoutInfo,
-    ) -> Result<(), Error> {
-        if payout_info.ln_invoice.is_empty() {
-            return Err(Error::BadRequest("Invalid lightning invoice".into()));
-        }
+    #[test]
+    fn test_validate_create_event_caps_pool_subsidy() {
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(6);
+        let create_event = CreateEvent {
+            number_of_values_per_entry: 1,
+            total_allowed_entries: 5,
+            total_competition_pool: Sats(6_000),
+            ..CreateEvent::fixture(start)
+        };
+        assert_eq!(create_event.pool_subsidy(5), Sats(1_000));
+        assert_eq!(create_event.pool_subsidy(2), Sats(4_000));
+        // With fewer entries the funded pool shrinks to the fees, sponsorships and the cap
+        assert_eq!(
+            create_event.funded_pool(5, Sats::ZERO, Sats(1_000)),
+            Sats(6_000)
+        );
+        assert_eq!(
+            create_event.funded_pool(2, Sats::ZERO, Sats(1_000)),
+            Sats(3_000)
+        );
+        assert_eq!(
+            create_event.funded_pool(2, Sats(500), Sats(1_000)),
+            Sats(3_500)
+        );
+
+        // Entry fees have to cover the pool unless the coordinator opts into a subsidy
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "total_competition_pool");
+        assert_eq!(errors.errors[0].code, "exceeds_entry_fees");
+        assert!(
+            validate_create_event(&create_event, 25, 999, 144, std::time::Duration::ZERO).is_err()
+        );
+        assert!(
+            validate_create_event(&create_event, 25, 1_000, 144, std::time::Duration::ZERO).is_ok()
+        );
+    }
 
-        // Get the competition and verify it's in a valid state for payouts
-        let competition = self
-            .competition_store
-            .get_competition(competition_id)
-            .await?;
+    #[test]
+    fn test_validate_create_event_rejects_overflowing_fees() {
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(6);
+        let mut create_event = CreateEvent {
+            number_of_values_per_entry: 1,
+            total_allowed_entries: 2,
+            entry_fee: Sats(u64::MAX / 2),
+            coordinator_fee_percentage: 0,
+            total_competition_pool: Sats(u64::MAX / 2),
+            ..CreateEvent::fixture(start)
+        };
+        assert!(
+            validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO).is_ok()
+        );
 
-        if !competition.is_attested() {
-            return Err(Error::BadRequest(
-                "Competition results not yet attested".into(),
-            ));
-        }
+        // The entry fee plus the coordinator's cut no longer fits in a u64
+        create_event.coordinator_fee_percentage = 10;
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "entry_fee");
+        assert_eq!(errors.errors[0].code, "overflow");
+
+        // Neither do the entry fees collected from every ticket
+        create_event.coordinator_fee_percentage = 0;
+        create_event.total_allowed_entries = 3;
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors[0].field, "entry_fee");
+        assert_eq!(errors.errors[0].code, "overflow");
+    }
 
-        if let Some(ref event_announcement) = competition.event_announcement {
-            debug!("Locking points: {:?}", event_announcement.locking_points);
-        }
+    #[test]
+    fn test_validate_create_event_requires_lead_time() {
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(2);
+        let create_event = CreateEvent {
+            number_of_values_per_entry: 1,
+            total_allowed_entries: 5,
+            total_competition_pool: Sats(5_000),
+            ..CreateEvent::fixture(start)
+        };
+        let one_hour = std::time::Duration::from_secs(3600);
+        assert!(validate_create_event(&create_event, 25, 0, 144, one_hour).is_ok());
+
+        let errors = validate_create_event(&create_event, 25, 0, 144, 3 * one_hour).unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "start_observation_date");
+        assert_eq!(errors.errors[0].code, "insufficient_lead_time");
+        assert!(
+            errors.errors[0].message.contains("at least 3h 0m"),
+            "{}",
+            errors.errors[0].message
+        );
+    }
 
-        if competition.is_delta_broadcasted()
-            || competition.is_expiry_broadcasted()
-            || competition.is_completed()
-        {
-            return Err(Error::BadRequest(
-                "Funds already received to user's on-chain key".into(),
-            ));
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_entries_are_resubmitted_to_the_oracle_without_duplicates() {
+        use crate::domain::invoices::test_support::{player_pubkey, test_coordinator_with};
+        use crate::simulation::{BitcoinScript, ScriptedBitcoin, SimClock};
+
+        let bitcoin = Arc::new(ScriptedBitcoin::new(
+            BitcoinScript::default(),
+            SimClock::default(),
+        ));
+        let test = test_coordinator_with(bitcoin, |settings| {
+            settings.coordinator_settings.oracle_entries_chunk_size = 2
+        })
+        .await;
+        let mut competition = test.create_competition(5).await;
+        for seed in 1..=5 {
+            let ticket = test.pay_for_ticket(competition.id, seed).await;
+            test.coordinator
+                .add_entry(
+                    player_pubkey(seed),
+                    test.entry(competition.id, ticket.ticket_id, seed),
+                )
+                .await
+                .unwrap();
         }
+        test.coordinator
+            .submit_event_to_oracle(&mut competition)
+            .await
+            .unwrap();
 
-        // Get the entry and verify ownership
-        let entries = self
+        // The second chunk lands on the oracle but its response is lost
+        test.oracle.fail_entries_chunk_once(1, true);
+        let err = test
+            .coordinator
+            .submit_entries_to_oracle(&mut competition)
+            .await
+            .unwrap_err();
+        assert!(err
+            .downcast_ref::<OracleError>()
+            .is_some_and(OracleError::is_transient));
+        assert!(competition.entries_submitted_at.is_none());
+        assert_eq!(competition.oracle_submitted_entry_ids.len(), 2);
+        let stored = test
+            .coordinator
             .competition_store
-            .get_user_entries(
-                pubkey.clone(),
-                SearchBy {
-                    event_ids: Some(vec![competition_id]),
-                },
-            )
-            .await?;
+            .get_competition(competition.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            stored.oracle_submitted_entry_ids,
+            competition.oracle_submitted_entry_ids
+        );
 
-        let entry = entries
+        // The retry skips the first chunk, the second conflicts and goes out entry by entry
+        test.coordinator
+            .submit_entries_to_oracle(&mut competition)
+            .await
+            .unwrap();
+        assert!(competition.entries_submitted_at.is_some());
+        let request_sizes: Vec<usize> = test
+            .oracle
+            .received_entries()
             .iter()
-            .find(|e| e.id == entry_id)
-            .ok_or_else(|| Error::NotFound(format!("Entry {} not found", entry_id)))?;
+            .map(|request| request.entries.len())
+            .collect();
+        assert_eq!(request_sizes, vec![2, 2, 2, 1, 1, 1]);
+
+        let mut on_oracle = test.oracle.stored_entry_ids(competition.id);
+        assert_eq!(on_oracle.len(), 5);
+        on_oracle.sort();
+        on_oracle.dedup();
+        assert_eq!(on_oracle.len(), 5);
+        let mut submitted = competition.oracle_submitted_entry_ids.clone();
+        submitted.sort();
+        assert_eq!(submitted, on_oracle);
+    }
 
-        // Verify the ticket matches
-        if entry.ticket_id != payout_info.ticket_id {
-            return Err(Error::BadRequest("Invalid ticket for this entry".into()));
-        }
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_submit_event_adopts_event_created_before_a_crash() {
+        use crate::domain::invoices::test_support::test_coordinator;
 
-        // Get competition tickets to verify against stored data
-        let tickets = self.competition_store.get_tickets(competition_id).await?;
-        _ = tickets
-            .get(&entry.id)
-            .ok_or_else(|| Error::NotFound("Ticket not found".into()))?;
+        let test = test_coordinator().await;
+        let mut competition = test.create_competition(2).await;
 
-        // Verify the entry hasn't already been paid out or pending a payout
-        if let Some(paid_out_at) = entry.paid_out_at {
-            return Err(Error::BadRequest(format!(
-                "Entry {} already paid out at {}",
-                entry.id, paid_out_at
-            )));
-        }
+        // Created on the oracle, but the coordinator went down before storing the announcement
+        let created = test
+            .coordinator
+            .oracle_client
+            .create_event(competition.event_submission.clone())
+            .await
+            .unwrap();
+        assert!(competition.event_created_at.is_none());
 
-        // Get the current outcome
-        let outcome = competition.get_current_outcome()?;
-        debug!("Current outcome: {:?}", outcome);
+        test.coordinator
+            .submit_event_to_oracle(&mut competition)
+            .await
+            .unwrap();
+        assert!(competition.event_created_at.is_some());
+        assert_eq!(
+            competition.event_announcement,
+            Some(created.event_announcement)
+        );
+    }
 
-        // Get the signed contract to verify winner
-        let signed_contract = competition
-            .signed_contract
-            .as_ref()
-            .ok_or_else(|| Error::BadRequest("Competition contract not yet signed".into()))?;
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_submit_event_rejects_a_different_event_with_the_same_id() {
+        use crate::domain::invoices::test_support::test_coordinator;
 
-        // Verify this entry is a winner
-        let winner_weights = signed_contract
-            .params()
-            .outcome_payouts
-            .get(&outcome)
-            .ok_or_else(|| Error::BadRequest("No payout mapping for outcome".into()))?;
+        let test = test_coordinator().await;
+        let mut competition = test.create_competition(2).await;
 
-        let ephemeral_pubkey = Point::from_hex(&entry.ephemeral_pubkey)
-            .map_err(|e| Error::BadRequest(format!("Invalid ephemeral pubkey: {}", e)))?;
+        let mut other = competition.event_submission.clone();
+        other.locations = vec!["KSEA".to_string()];
+        test.coordinator
+            .oracle_client
+            .create_event(other)
+            .await
+            .unwrap();
 
-        debug!("Ephemeral pubkey: {:?}", ephemeral_pubkey);
-        debug!("Winner weights: {:?}", winner_weights);
-        debug!("Players: {:?}", signed_contract.params().players);
+        let err = test
+            .coordinator
+            .submit_event_to_oracle(&mut competition)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("locations"), "{}", err);
+        assert!(competition.event_created_at.is_none());
+        assert!(competition.event_announcement.is_none());
+    }
 
-        let is_winner = winner_weights.iter().any(|(player_index, _)| {
-            if let Some(player) = signed_contract.params().players.get(*player_index) {
-                player.pubkey == ephemeral_pubkey
-            } else {
-                false
-            }
-        });
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_submit_event_checks_for_the_event_when_creation_fails_unexpectedly() {
+        use crate::domain::invoices::test_support::test_coordinator;
+        use crate::infra::oracle_mock::{OracleEndpoint, OracleFailure};
+
+        let test = test_coordinator().await;
+        let mut competition = test.create_competition(2).await;
+
+        // Nothing on the oracle yet, the failure stands and the next tick tries again
+        test.oracle
+            .set_failure_mode(OracleEndpoint::CreateEvent, OracleFailure::Unexpected, 1);
+        assert!(test
+            .coordinator
+            .submit_event_to_oracle(&mut competition)
+            .await
+            .is_err());
+        assert!(competition.event_created_at.is_none());
+
+        // Created on an earlier try, and the oracle answers the duplicate with an error
+        let created = test
+            .coordinator
+            .oracle_client
+            .create_event(competition.event_submission.clone())
+            .await
+            .unwrap();
+        test.oracle
+            .set_failure_mode(OracleEndpoint::CreateEvent, OracleFailure::Unexpected, 1);
+        test.coordinator
+            .submit_event_to_oracle(&mut competition)
+            .await
+            .unwrap();
+        assert!(competition.event_created_at.is_some());
+        assert_eq!(
+            competition.event_announcement,
+            Some(created.event_announcement)
+        );
+    }
 
-        if !is_winner {
-            return Err(Error::BadRequest("Entry is not a winner".into()));
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_add_entry_rejects_malformed_entry_secrets() {
+        use crate::domain::invoices::test_support::{player_pubkey, test_coordinator};
+
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.pay_for_ticket(competition.id, 1).await;
+
+        let mut entry = test.entry(competition.id, ticket.ticket_id, 1);
+        assert!(validate_entry_keys(&entry).is_ok());
+
+        // Right length and prefix, but x is past the field size so it's not on the curve
+        entry.ephemeral_pubkey = format!("02{}", "ff".repeat(32));
+        entry.payout_preimage_encrypted = "A".repeat(4 * 1024 * 1024);
+        match test.coordinator.add_entry(player_pubkey(1), entry).await {
+            Err(Error::Validation(errors)) => {
+                let codes: Vec<(&str, &str)> = errors
+                    .errors
+                    .iter()
+                    .map(|e| (e.field.as_str(), e.code.as_str()))
+                    .collect();
+                assert_eq!(
+                    codes,
+                    vec![
+                        ("payout_preimage_encrypted", "too_long"),
+                        ("ephemeral_pubkey", "invalid_pubkey"),
+                    ]
+                );
+            }
+            result => panic!("expected validation errors, got {:?}", result),
         }
+        assert!(test
+            .coordinator
+            .competition_store
+            .get_competition_entries(competition.id, vec![])
+            .await
+            .unwrap()
+            .is_empty());
+    }
 
-        // Verify the private key matches the public key
-        let provided_private_key = Scalar::from_hex(&payout_info.ephemeral_private_key)
-            .map_err(|e| Error::BadRequest(format!("Invalid private key: {}", e)))?;
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_add_entry_rejects_pubkey_not_bound_to_ticket() {
+        use crate::domain::invoices::test_support::{
+            player_bitcoin_pubkey, player_pubkey, test_coordinator,
+        };
 
-        let derived_public_key = provided_private_key.base_point_mul();
-        if derived_public_key != ephemeral_pubkey {
-            return Err(Error::BadRequest(
-                "Invalid private key for this entry".into(),
-            ));
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let bound = player_bitcoin_pubkey(1).to_string();
+        let other = player_bitcoin_pubkey(2).to_string();
+
+        let ticket = test.pay_for_ticket(competition.id, 1).await;
+        let stored = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.ephemeral_pubkey.as_deref(), Some(bound.as_str()));
+
+        // A paid ticket can't be moved to another key
+        let result = test
+            .coordinator
+            .request_ticket(player_pubkey(1), competition.id, player_bitcoin_pubkey(2))
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+
+        let mut entry = test.entry(competition.id, ticket.ticket_id, 1);
+        entry.ephemeral_pubkey = other.clone();
+        match test.coordinator.add_entry(player_pubkey(1), entry).await {
+            Err(Error::BadRequest(message)) => {
+                assert!(message.contains(&bound), "{}", message);
+                assert!(message.contains(&other), "{}", message);
+            }
+            result => panic!("expected a bad request, got {:?}", result),
         }
 
-        // Verify the payout preimage matches the hash
-        let provided_preimage =
-            dlctix::hashlock::preimage_from_hex(&payout_info.payout_preimage)
-                .map_err(|e| Error::BadRequest(format!("Invalid payout preimage: {}", e)))?;
+        // The ticket is still usable with the bound key
+        test.coordinator
+            .add_entry(
+                player_pubkey(1),
+                test.entry(competition.id, ticket.ticket_id, 1),
+            )
+            .await
+            .unwrap();
+    }
 
-        let derived_hash = dlctix::hashlock::sha256(&provided_preimage);
-        if derived_hash.to_hex_string(dlctix::bitcoin::hex::Case::Lower) != entry.payout_hash {
-            return Err(Error::BadRequest(
-                "Invalid payout preimage for this entry".into(),
-            ));
-        }
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_paid_ticket_can_be_claimed_after_reservation_window() {
+        use crate::domain::invoices::test_support::{player_pubkey, test_coordinator};
 
-        if payout_info.ln_invoice.is_empty() {
-            return Err(Error::BadRequest("Invalid lightning invoice".into()));
-        }
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.pay_for_ticket(competition.id, 1).await;
 
-        // Calculate the payout amount based on winner's weight
-        let total_pool_sats = signed_contract.params().funding_value.to_sat();
-        let winner_weight = winner_weights
-            .iter()
-            .find_map(|(player_index, weight)| {
-                if let Some(player) = signed_contract.params().players.get(*player_index) {
-                    if player.pubkey == ephemeral_pubkey {
-                        Some(*weight)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| Error::BadRequest("Unable to determine winner weight".into()))?;
+        // The entry arrives after the 10 minute reservation, read just before the cutoff
+        test.expire_reservation(ticket.ticket_id).await;
+        let mut stored = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        stored.expiry = OffsetDateTime::now_utc() - time::Duration::seconds(1);
+        assert_eq!(stored.get_status(), TicketStatus::Paid);
+        assert!(!stored.is_expired());
+
+        let entry = test
+            .coordinator
+            .add_entry(
+                player_pubkey(1),
+                test.entry(competition.id, ticket.ticket_id, 1),
+            )
+            .await
+            .unwrap();
+        let claimed = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(claimed.entry_id, Some(entry.id));
+
+        // The claim re-checks the ticket in its transaction, a second entry writes nothing
+        let second = test
+            .entry(competition.id, ticket.ticket_id, 1)
+            .into_user_entry(player_pubkey(1));
+        let result = test
+            .coordinator
+            .competition_store
+            .add_entry(second, ticket.ticket_id)
+            .await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+
+        // As does an entry for a ticket that was reserved but never paid
+        let unpaid = test.reserve_ticket(competition.id, 2).await;
+        let result = test
+            .coordinator
+            .competition_store
+            .add_entry(
+                test.entry(competition.id, unpaid.ticket_id, 2)
+                    .into_user_entry(player_pubkey(2)),
+                unpaid.ticket_id,
+            )
+            .await;
+        assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+
+        let entries = test
+            .coordinator
+            .competition_store
+            .get_competition_entries(competition.id, vec![])
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, entry.id);
+    }
 
-        let payout_amount_sats = (total_pool_sats * winner_weight) / 100;
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_expired_invoice_is_regenerated_within_the_reservation() {
+        use crate::{
+            domain::invoices::test_support::{player_pubkey, test_coordinator},
+            infra::lightning::InvoiceState,
+        };
 
-        debug!(
-            "Total pool: {} sats, Winner weight: {}%, Payout amount: {} sats",
-            total_pool_sats, winner_weight, payout_amount_sats
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.reserve_ticket(competition.id, 1).await;
+
+        // The first invoice is still good, there is nothing to regenerate
+        let result = test
+            .coordinator
+            .regenerate_ticket_invoice(player_pubkey(1), competition.id, ticket.ticket_id)
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+
+        test.expire_invoice(ticket.ticket_id).await;
+        let regenerated = test
+            .coordinator
+            .regenerate_ticket_invoice(player_pubkey(1), competition.id, ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(regenerated.ticket_id, ticket.ticket_id);
+        assert_eq!(regenerated.payment_hash, ticket.payment_hash);
+        assert_eq!(regenerated.amount_sats, ticket.amount_sats);
+        assert_eq!(
+            test.ln.get_invoice_state(&ticket.payment_hash),
+            Some(InvoiceState::Open)
         );
 
-        let invoice_amount_sats =
-            crate::infra::lightning::extract_amount_from_invoice(&payout_info.ln_invoice)
-                .map_err(|e| Error::BadRequest(format!("Invalid lightning invoice: {}", e)))?;
+        // Only good for what is left of the reservation
+        let stored = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        let expires_at = stored.invoice_expires_at.unwrap();
+        assert!(expires_at > OffsetDateTime::now_utc());
+        assert!(expires_at <= stored.reserved_at.unwrap() + TICKET_RESERVATION_WINDOW);
+
+        // Another player can't regenerate it for them
+        let result = test
+            .coordinator
+            .regenerate_ticket_invoice(player_pubkey(2), competition.id, ticket.ticket_id)
+            .await;
+        assert!(result.is_err());
+    }
 
-        if let Some(invoice_amount_sats) = invoice_amount_sats {
-            if invoice_amount_sats != payout_amount_sats {
-                return Err(Error::BadRequest(format!(
-                    "Invoice amount {} sats does not match expected payout {} sats",
-                    invoice_amount_sats, payout_amount_sats
-                )));
-            }
-        }
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_reissued_invoice_keeps_the_ticket_hash_binding() {
+        use crate::{
+            domain::invoices::test_support::{player_pubkey, test_coordinator},
+            infra::lightning::Ln,
+        };
 
-        match self
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.reserve_ticket(competition.id, 1).await;
+        let memo: InvoiceMemo = ticket.invoice_memo.parse().unwrap();
+        assert!(memo.matches(&competition.id.to_string(), &ticket.ticket_id.to_string()));
+        assert_eq!(memo.coordinator, test.coordinator.name());
+        assert_eq!(ticket.entry_fee, competition.event_submission.entry_fee);
+        assert_eq!(
+            ticket.entry_fee.saturating_add(ticket.coordinator_fee),
+            ticket.amount_sats
+        );
+        let reserved = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            reserved.invoice_expires_at.map(|at| at.unix_timestamp()),
+            Some(ticket.expires_at.unix_timestamp())
+        );
+
+        test.expire_invoice(ticket.ticket_id).await;
+        let reissued = test
+            .coordinator
+            .regenerate_ticket_invoice(player_pubkey(1), competition.id, ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(reissued.payment_hash, ticket.payment_hash);
+        assert_eq!(reissued.invoice_memo, ticket.invoice_memo);
+        assert_eq!(reissued.escrow_tx, ticket.escrow_tx);
+        assert!(reissued.expires_at > OffsetDateTime::now_utc());
+
+        let invoice = test
             .ln
-            .send_payment(
-                payout_info.ln_invoice.clone(),
-                payout_amount_sats,
-                60,   // TODO(@tee8z): make this timeout configurable, 60 second timeout
-                1000, // TODO(@tee8z): make this fee configurable, 1000 sat fee limit
-            )
+            .lookup_invoice(&ticket.payment_hash, None)
             .await
-        {
-            Ok(_) => {
-                // Store payout info but DON'T mark as paid out yet
-                // The PayoutWatcher will monitor the payment and mark as paid when it settles
-                self.competition_store
-                    .store_payout_info_pending(
-                        entry_id,
-                        payout_info.payout_preimage,
-                        payout_info.ephemeral_private_key,
-                        payout_info.ln_invoice,
-                        payout_amount_sats,
-                    )
-                    .await
-                    .map_err(Error::DbError)
-                    .inspect(|pay_out_id| info!("Payout initiated with ID: {}", pay_out_id))
-                    .map(|_| ())
-            }
-            Err(e) => Err(Error::PaymentFailed(format!(
-                "Failed to initiate lightning payment: {}",
-                e
-            ))),
-        }
+            .unwrap();
+        assert_eq!(invoice.payment_request, reissued.payment_request);
+        assert_eq!(invoice.memo.as_deref(), Some(ticket.invoice_memo.as_str()));
+
+        // Still the same reservation, paying the new invoice pays for the ticket
+        let stored = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.hash, reserved.hash);
+        assert_eq!(stored.encrypted_preimage, reserved.encrypted_preimage);
+        assert_eq!(stored.reserved_at, reserved.reserved_at);
+        assert_eq!(
+            stored.invoice_expires_at.map(|at| at.unix_timestamp()),
+            Some(reissued.expires_at.unix_timestamp())
+        );
+
+        test.ln.accept_invoice(&reissued.payment_hash).unwrap();
+        test.coordinator
+            .handle_invoice_accepted(competition.id, &reissued.payment_hash)
+            .await
+            .unwrap();
+        let paid = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(paid.get_status(), TicketStatus::Paid);
     }
-}
 
-fn generate_players(
-    entries: &Vec<UserEntry>,
-    tickets: &HashMap<Uuid, Ticket>,
-) -> Result<Vec<Player>, anyhow::Error> {
-    let mut players = vec![];
-    for entry in entries {
-        let Some(ticket) = tickets.get(&entry.id) else {
-            error!("ticket missing for entry {} , skipping", entry.id);
-            continue;
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_invoice_is_not_regenerated_once_the_reservation_expired() {
+        use crate::domain::invoices::test_support::{player_pubkey, test_coordinator};
+
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.reserve_ticket(competition.id, 1).await;
+        test.expire_invoice(ticket.ticket_id).await;
+        test.expire_reservation(ticket.ticket_id).await;
+
+        let result = test
+            .coordinator
+            .regenerate_ticket_invoice(player_pubkey(1), competition.id, ticket.ticket_id)
+            .await;
+        assert!(
+            matches!(&result, Err(Error::BadRequest(message)) if message.contains("request a new ticket"))
+        );
+
+        // Nor once it is paid
+        let paid = test.pay_for_ticket(competition.id, 2).await;
+        test.expire_invoice(paid.ticket_id).await;
+        let result = test
+            .coordinator
+            .regenerate_ticket_invoice(player_pubkey(2), competition.id, paid.ticket_id)
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+    }
+
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_settled_sponsor_invoice_grows_the_pool_once() {
+        use crate::{
+            domain::invoices::test_support::test_coordinator,
+            infra::lightning::extract_payment_hash_from_invoice,
         };
 
-        let payout_hash = entry.payout_hash.as_str();
-        debug!("player_pubkey: {}", entry.ephemeral_pubkey);
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let pool = competition.event_submission.total_competition_pool;
+        let invoice = test
+            .coordinator
+            .ln
+            .add_invoice(5_000, 3_600, "sponsor".to_string(), competition.id)
+            .await
+            .unwrap();
+        let payment_hash = extract_payment_hash_from_invoice(&invoice.payment_request).unwrap();
+        let request = AddSponsorship {
+            amount_sats: Sats(5_000),
+            funds: SponsorFunds::Lightning {
+                payment_hash: payment_hash.clone(),
+            },
+            sponsor: Some("Weather Co".to_string()),
+        };
 
-        let pubkey = Point::from_hex(&entry.ephemeral_pubkey)
-            .map_err(|e| anyhow!("failed to get point: {}", e))?;
-        let player = Player {
-            pubkey,
-            // coordinator generates this preimage and only shares after player has paid for a ticket
-            ticket_hash: string_to_byte_array(&ticket.hash),
-            // players generate this preimage and only share after winning with the coordinator
-            payout_hash: string_to_byte_array(payout_hash),
+        // Not until the sponsor paid
+        let result = test
+            .coordinator
+            .add_sponsorship(competition.id, request.clone(), "admin")
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+
+        test.ln.settle_invoice_by_hash(&payment_hash).unwrap();
+        let too_much = AddSponsorship {
+            amount_sats: Sats(5_001),
+            ..request.clone()
         };
-        players.push(player);
+        let result = test
+            .coordinator
+            .add_sponsorship(competition.id, too_much, "admin")
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+
+        let sponsorship = test
+            .coordinator
+            .add_sponsorship(competition.id, request.clone(), "admin")
+            .await
+            .unwrap();
+        assert_eq!(sponsorship.sponsor.as_deref(), Some("Weather Co"));
+        let sponsored = test
+            .coordinator
+            .get_competition(competition.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            sponsored.event_submission.total_competition_pool,
+            pool.saturating_add(Sats(5_000))
+        );
+        assert_eq!(
+            test.coordinator
+                .get_sponsorships(competition.id)
+                .await
+                .unwrap(),
+            vec![sponsorship]
+        );
+        let pnl = test.coordinator.get_pnl_report().await.unwrap();
+        assert_eq!(
+            pnl.total.amount(LedgerEntryKind::SponsorContribution),
+            5_000
+        );
+
+        // The same payment only sponsors once
+        let result = test
+            .coordinator
+            .add_sponsorship(competition.id, request, "admin")
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+        let sponsored = test
+            .coordinator
+            .get_competition(competition.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            sponsored.event_submission.total_competition_pool,
+            pool.saturating_add(Sats(5_000))
+        );
     }
 
-    Ok(players)
-}
-fn string_to_byte_array(hex_str: &str) -> [u8; 32] {
-    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
-    let bytes = hex::decode(hex_str).expect("valid hex string");
-    bytes.try_into().expect("32 bytes")
-}
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_sponsorships_need_funds_the_coordinator_holds() {
+        use crate::domain::invoices::test_support::test_coordinator;
+
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
 
-fn get_percentage_weights(num_winners: usize) -> Vec<u64> {
-    match num_winners {
-        1 => vec![100],
-        2 => vec![60, 40],
-        3 => vec![45, 35, 20],
-        4 => vec![42, 30, 18, 10],
-        5 => vec![40, 27, 16, 9, 8],
-        _ => vec![100], // fallback to winner takes all
+        // The mock wallet has no outputs
+        let result = test
+            .coordinator
+            .add_sponsorship(
+                competition.id,
+                AddSponsorship {
+                    amount_sats: Sats(1_000),
+                    funds: SponsorFunds::Utxo {
+                        outpoint: OutPoint::null(),
+                    },
+                    sponsor: None,
+                },
+                "admin",
+            )
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+
+        // Nor can a ticket's own invoice count twice
+        let ticket = test.pay_for_ticket(competition.id, 1).await;
+        test.ln
+            .settle_invoice_by_hash(&ticket.payment_hash)
+            .unwrap();
+        let result = test
+            .coordinator
+            .add_sponsorship(
+                competition.id,
+                AddSponsorship {
+                    amount_sats: Sats(1_000),
+                    funds: SponsorFunds::Lightning {
+                        payment_hash: ticket.payment_hash,
+                    },
+                    sponsor: None,
+                },
+                "admin",
+            )
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
     }
-}
 
-fn generate_payouts(
-    competition: &Competition,
-    entries: &mut [UserEntry],
-    players: &[Player],
-) -> Result<BTreeMap<Outcome, PayoutWeights>, anyhow::Error> {
-    debug!("Generating payouts for {} players", players.len());
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_clones_are_validated_like_new_competitions() {
+        use crate::domain::invoices::test_support::test_coordinator;
+
+        let test = test_coordinator().await;
+        let source = test.create_competition(2).await;
+
+        // Starting an hour ago leaves no lead time to sell tickets
+        let result = test
+            .coordinator
+            .clone_competition(
+                source.id,
+                CloneCompetition {
+                    start_observation_date: Some(
+                        OffsetDateTime::now_utc() - time::Duration::hours(1),
+                    ),
+                    ..Default::default()
+                },
+            )
+            .await;
+        match result {
+            Err(Error::Validation(errors)) => assert!(errors
+                .errors
+                .iter()
+                .any(|e| e.field == "start_observation_date")),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
 
-    // Sort entries by ticket_id for consistent indexing
-    // This ensures player indices match the ticket order used when creating
-    // keymeld subset definitions at competition creation time
-    entries.sort_by_key(|entry| entry.ticket_id);
-    let mut payouts: BTreeMap<Outcome, PayoutWeights> = BTreeMap::new();
+        let cloned = test
+            .coordinator
+            .clone_competition(
+                source.id,
+                CloneCompetition {
+                    total_allowed_entries: Some(3),
+                    locations: Some(vec!["KORD".to_string(), "KLGA".to_string()]),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_ne!(cloned.competition.id, source.id);
+        assert_eq!(cloned.source_id, source.id);
+        assert_eq!(cloned.competition.get_state(), CompetitionState::Created);
+        let clone_event = &cloned.competition.event_submission;
+        assert_eq!(clone_event.total_allowed_entries, 3);
+        assert_eq!(clone_event.total_competition_pool, Sats(3_000));
+        assert_eq!(
+            clone_event.start_observation_date,
+            source.event_submission.start_observation_date
+        );
 
-    let possible_rankings = generate_ranking_permutations(
-        entries.len(),
-        competition.event_submission.number_of_places_win,
-    );
-    debug!("Generated {} possible rankings", possible_rankings.len());
-    for (outcome_index, winner_indices) in possible_rankings.iter().enumerate() {
-        debug!(
-            "Processing outcome {} with winner indices: {:?}",
-            outcome_index, winner_indices
+        let fields: BTreeSet<&str> = cloned.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            BTreeSet::from([
+                "locations",
+                "total_allowed_entries",
+                "total_competition_pool"
+            ])
         );
+        let stored = test
+            .coordinator
+            .get_competition(cloned.competition.id)
+            .await
+            .unwrap();
+        assert_eq!(stored.get_state(), CompetitionState::Created);
+    }
 
-        // Special handling for "all players" outcome
-        if winner_indices.len() == entries.len() {
-            debug!("Processing special 'all players' outcome for equal refunds");
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_receipts_are_issued_for_entries_and_settled_tickets() {
+        use crate::domain::{
+            invoices::test_support::{player_pubkey, test_coordinator},
+            verify_receipt,
+        };
 
-            // Create equal weights for all players (everyone gets their entry fee back)
-            let mut equal_weights: BTreeMap<PlayerIndex, u64> = BTreeMap::new();
-            let weight_per_player = 100 / players.len() as u64;
-            let remainder = 100 % players.len() as u64;
-
-            for i in 0..players.len() {
-                // Distribute remainder to maintain total of 100
-                let player_weight = if (i as u64) < remainder {
-                    weight_per_player + 1
-                } else {
-                    weight_per_player
-                };
-                equal_weights.insert(i, player_weight);
-                debug!(
-                    "Assigning equal weight {} to player index {} for refund outcome",
-                    player_weight, i
-                );
-            }
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let coordinator_pubkeys = test.coordinator.public_keys();
+        let ticket = test.pay_for_ticket(competition.id, 1).await;
+
+        // Nothing to prove until the hold invoice settles
+        let result = test
+            .coordinator
+            .get_ticket_receipt(player_pubkey(1), competition.id, ticket.ticket_id)
+            .await;
+        assert!(matches!(result, Err(Error::NotFound(_))));
+
+        let entry = test
+            .coordinator
+            .add_entry(
+                player_pubkey(1),
+                test.entry(competition.id, ticket.ticket_id, 1),
+            )
+            .await
+            .unwrap();
+        let receipt = entry
+            .receipt
+            .clone()
+            .expect("entry receipt should be issued");
+        let payload = verify_receipt(&receipt, &coordinator_pubkeys).unwrap();
+        assert_eq!(payload.kind, ReceiptKind::EntryAccepted);
+        assert_eq!(payload.competition_id, competition.id);
+        assert_eq!(payload.ticket_id, ticket.ticket_id);
+        assert_eq!(payload.entry_id, Some(entry.id));
+        assert_eq!(payload.amount_sats, ticket.amount_sats);
+        assert_eq!(payload.payment_hash, ticket.payment_hash);
+
+        // The stored receipt is returned as is, and only to the entrant
+        assert_eq!(
+            test.coordinator
+                .get_entry_receipt(player_pubkey(1), entry.id)
+                .await
+                .unwrap(),
+            receipt
+        );
+        let result = test
+            .coordinator
+            .get_entry_receipt(player_pubkey(2), entry.id)
+            .await;
+        assert!(matches!(result, Err(Error::NotFound(_))));
+
+        test.coordinator
+            .settle_competition_invoices(competition.id)
+            .await
+            .unwrap();
+        let stored = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        let payment_receipt = stored
+            .payment_receipt
+            .expect("payment receipt should be issued on settle");
+        let payload = verify_receipt(&payment_receipt, &coordinator_pubkeys).unwrap();
+        assert_eq!(payload.kind, ReceiptKind::TicketPayment);
+        assert_eq!(payload.entry_id, Some(entry.id));
+        assert_eq!(payload.amount_sats, ticket.amount_sats);
+        assert_eq!(
+            test.coordinator
+                .get_ticket_receipt(player_pubkey(1), competition.id, ticket.ticket_id)
+                .await
+                .unwrap(),
+            payment_receipt
+        );
+    }
 
-            debug!(
-                "Final weights for refund outcome {}: {:?}",
-                outcome_index, equal_weights
-            );
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_failed_invoice_settlement_is_recorded_per_ticket() {
+        use crate::domain::{
+            invoices::test_support::{player_pubkey, test_coordinator},
+            ExtendCompetition,
+        };
 
-            payouts.insert(Outcome::Attestation(outcome_index), equal_weights);
-            continue;
+        let test = test_coordinator().await;
+        let competition = test.create_competition(3).await;
+        let settled = test.pay_for_ticket(competition.id, 1).await;
+        let failing = test.pay_for_ticket(competition.id, 2).await;
+        for (seed, ticket) in [(1, &settled), (2, &failing)] {
+            test.coordinator
+                .add_entry(
+                    player_pubkey(seed),
+                    test.entry(competition.id, ticket.ticket_id, seed),
+                )
+                .await
+                .unwrap();
         }
 
-        // Normal outcome processing
-        let entry_pubkeys = find_winning_entries_pubkeys(entries, winner_indices.to_owned());
-        debug!("Winner pubkeys: {:?}", entry_pubkeys);
+        // The invoice can no longer be settled, the other one still is
+        test.ln
+            .cancel_invoice_by_hash(&failing.payment_hash)
+            .unwrap();
+        let progress = test
+            .coordinator
+            .settle_competition_invoices(competition.id)
+            .await
+            .unwrap();
+        let expected = SettlementProgress {
+            settled_invoices: 1,
+            total_invoices: 2,
+            failed_invoices: 1,
+        };
+        assert_eq!(progress, expected);
+        assert!(!progress.is_complete());
+
+        let store = &test.coordinator.competition_store;
+        let ticket = store.get_ticket(settled.ticket_id).await.unwrap();
+        assert!(ticket.settled_at.is_some());
+        assert_eq!(ticket.settlement_error, None);
+        let ticket = store.get_ticket(failing.ticket_id).await.unwrap();
+        assert_eq!(ticket.settled_at, None);
+        assert!(ticket.settlement_error.is_some());
+        assert!(ticket.settlement_failed_at.is_some());
+
+        let stored = store.get_competition(competition.id).await.unwrap();
+        assert_eq!(stored.settlement_progress, expected);
+        assert_eq!(
+            ExtendCompetition::from(stored).settlement_progress,
+            expected
+        );
 
-        let player_indices = find_player_indices(players, entry_pubkeys)?;
-        debug!("Mapped to player indices: {:?}", player_indices);
+        // Settled tickets aren't settled again, the failing one is retried
+        assert_eq!(
+            test.coordinator
+                .settle_competition_invoices(competition.id)
+                .await
+                .unwrap(),
+            expected
+        );
+    }
 
-        if player_indices.len() != competition.event_submission.number_of_places_win {
-            return Err(anyhow!(
-                "Incorrect number of winners for outcome {}",
-                outcome_index
-            ));
-        }
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_competition_overrides_invoice_settlement_confirmations() {
+        use crate::domain::invoices::test_support::test_coordinator;
+
+        let test = test_coordinator().await;
+        let mut competition = test.create_competition(2).await;
+        assert_eq!(
+            test.coordinator
+                .invoice_settlement_confirmations(&competition),
+            test.settings.invoice_settlement_confirmations()
+        );
+        competition
+            .event_submission
+            .invoice_settlement_confirmations = Some(6);
+        assert_eq!(
+            test.coordinator
+                .invoice_settlement_confirmations(&competition),
+            6
+        );
 
-        let percentage_weights =
-            get_percentage_weights(competition.event_submission.number_of_places_win);
-        let mut payout_weights: BTreeMap<PlayerIndex, u64> = BTreeMap::new();
+        let mut create_event = competition.event_submission;
+        create_event.invoice_settlement_confirmations = Some(144);
+        assert!(
+            validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO).is_ok()
+        );
+        create_event.invoice_settlement_confirmations = Some(145);
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "invoice_settlement_confirmations");
+        assert_eq!(errors.errors[0].code, "too_many_confirmations");
+
+        // Waiting on more confirmations than the coordinator asks for needs more lead time
+        assert!(
+            test.settings.min_creation_lead_time_for(Some(6))
+                > test.settings.min_creation_lead_time()
+        );
+    }
 
-        for (rank, &player_index) in player_indices.iter().enumerate() {
-            let weight = percentage_weights[rank];
-            debug!(
-                "Assigning weight {} to player index {}",
-                weight, player_index
-            );
-            payout_weights.insert(player_index, weight);
-        }
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_private_competition_is_invite_only() {
+        use crate::domain::invoices::test_support::{
+            player_bitcoin_pubkey, player_pubkey, test_coordinator,
+        };
+        use axum::{http::StatusCode, response::IntoResponse};
 
-        debug!(
-            "Final weights for outcome {}: {:?}",
-            outcome_index, payout_weights
-        );
+        let test = test_coordinator().await;
+        let public = test.create_competition(2).await;
+        let private = test.create_private_competition(2).await;
+        let invited = nostr_sdk::PublicKey::from_hex(&player_pubkey(1)).unwrap();
 
-        // Verify total weight is 100
-        let total_weight: u64 = payout_weights.values().sum();
-        if total_weight != 100 {
-            return Err(anyhow!(
-                "Total weight for outcome {} should be 100, got {}",
-                outcome_index,
-                total_weight
-            ));
-        }
+        let err = test
+            .coordinator
+            .request_ticket(player_pubkey(1), private.id, player_bitcoin_pubkey(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NotInvited(id) if id == private.id));
+        assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
 
-        payouts.insert(Outcome::Attestation(outcome_index), payout_weights);
+        let listed: Vec<Uuid> = test
+            .coordinator
+            .get_visible_competitions(None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(listed, vec![public.id]);
+
+        // Public competitions can't take invitations
+        assert!(matches!(
+            test.coordinator
+                .invite_to_competition(public.id, vec![player_pubkey(1)])
+                .await,
+            Err(Error::BadRequest(_))
+        ));
+        assert!(matches!(
+            test.coordinator
+                .invite_to_competition(private.id, vec!["not a pubkey".to_string()])
+                .await,
+            Err(Error::Validation(_))
+        ));
+
+        let npub = nostr_sdk::ToBech32::to_bech32(&invited).unwrap();
+        let invitations = test
+            .coordinator
+            .invite_to_competition(private.id, vec![npub])
+            .await
+            .unwrap();
+        assert_eq!(invitations, vec![invited.to_hex()]);
+
+        let listed = test
+            .coordinator
+            .get_visible_competitions(Some(&invited))
+            .await
+            .unwrap();
+        assert!(listed.iter().any(|c| c.id == private.id));
+        assert!(matches!(
+            test.coordinator
+                .request_ticket(player_pubkey(2), private.id, player_bitcoin_pubkey(2))
+                .await,
+            Err(Error::NotInvited(_))
+        ));
+
+        let ticket = test.pay_for_ticket(private.id, 1).await;
+        test.coordinator
+            .add_entry(
+                player_pubkey(1),
+                test.entry(private.id, ticket.ticket_id, 1),
+            )
+            .await
+            .unwrap();
     }
 
-    // Add expiry outcome with equal distribution
-    let mut expiry_weights = BTreeMap::new();
-    let player_count = players.len() as u64;
-    let base_weight = 100 / player_count;
-    let remainder = 100 % player_count;
-
-    for i in 0..players.len() {
-        // Distribute remainder one point at a time to early indices
-        let weight = if (i as u64) < remainder {
-            base_weight + 1
-        } else {
-            base_weight
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_add_entry_sends_backup_dm_to_competition_relays() {
+        use crate::domain::invoices::test_support::test_coordinator;
+
+        let test = test_coordinator().await;
+        let relays = vec!["wss://relay.example.com".to_string()];
+        let competition = test.create_competition_with_relays(2, relays.clone()).await;
+
+        // The DM goes out in the background, the entry is returned before it is recorded
+        let backup_recorded = |entry_id: Uuid| {
+            let coordinator = test.coordinator.clone();
+            async move {
+                tokio::time::timeout(std::time::Duration::from_secs(5), async {
+                    loop {
+                        let entry = coordinator
+                            .get_entry_by_id(entry_id)
+                            .await
+                            .unwrap()
+                            .unwrap();
+                        if entry.backup_dm_sent_at.is_some() || entry.backup_dm_error.is_some() {
+                            return entry;
+                        }
+                        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    }
+                })
+                .await
+                .expect("backup DM status was never recorded")
+            }
         };
-        expiry_weights.insert(i, weight);
+
+        let entry_id = test.enter(competition.id, 1).await;
+        let entry = backup_recorded(entry_id).await;
+        let published = test.relays.published();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].0, relays);
+        assert_eq!(published[0].1.kind, nostr_sdk::Kind::EncryptedDirectMessage);
+        assert!(entry.backup_dm_sent_at.is_some());
+        assert_eq!(entry.backup_dm_error, None);
+
+        // An unreachable relay doesn't fail the entry, it's recorded for the operator
+        test.relays.set_unreachable(true);
+        let entry_id = test.enter(competition.id, 2).await;
+        let entry = backup_recorded(entry_id).await;
+        assert_eq!(entry.backup_dm_sent_at, None);
+        assert!(entry.backup_dm_error.is_some());
+        assert_eq!(test.relays.published().len(), 1);
     }
-    payouts.insert(Outcome::Expiry, expiry_weights);
 
-    debug!("Generated {} total outcomes", payouts.len());
-    Ok(payouts)
-}
+    fn payout_fixture(
+        num_players: usize,
+        places: usize,
+    ) -> (Competition, Vec<UserEntry>, Vec<Player>) {
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(6);
+        let competition = Competition::new(&CreateEvent {
+            number_of_values_per_entry: 1,
+            number_of_places_win: places,
+            total_allowed_entries: num_players,
+            total_competition_pool: Sats(1000 * num_players as u64),
+            ..CreateEvent::fixture(start)
+        });
 
-pub fn generate_ranking_permutations(num_players: usize, rankings: usize) -> Vec<Vec<usize>> {
-    let mut permutations = (0..num_players)
-        .permutations(rankings)
-        .collect::<Vec<Vec<usize>>>();
+        let mut entries = Vec::with_capacity(num_players);
+        let mut players = Vec::with_capacity(num_players);
+        for i in 0..num_players {
+            let mut secret = [0u8; 32];
+            secret[31] = i as u8 + 1;
+            let pubkey = Scalar::try_from(secret).unwrap().base_point_mul();
+
+            let mut entry = test_entry(Uuid::now_v7(), Some(i));
+            entry.ephemeral_pubkey = hex::encode(pubkey.serialize());
+            entries.push(entry);
+            players.push(Player {
+                pubkey,
+                ticket_hash: [i as u8; 32],
+                payout_hash: [i as u8; 32],
+            });
+        }
 
-    // Always add the special "refund all" outcome
-    permutations.push((0..num_players).collect());
+        (competition, entries, players)
+    }
 
-    permutations
-}
+    #[test]
+    fn test_generate_payouts_splits_tied_places() {
+        let (mut competition, entries, players) = payout_fixture(4, 2);
+        let tiebreak = generate_payouts(&competition, &entries, &players).unwrap();
+        competition.event_submission.tie_policy = TiePolicy::Split;
+        let split = generate_payouts(&competition, &entries, &players).unwrap();
+
+        let rankings = generate_rankings(4, 2, TiePolicy::Split);
+        assert_eq!(split.len(), rankings.len() + 1);
+        // Strict rankings pay the same under both policies
+        for (outcome, weights) in &tiebreak {
+            if matches!(outcome, Outcome::Attestation(i) if *i < tiebreak.len() - 2) {
+                assert_eq!(split.get(outcome), Some(weights));
+            }
+        }
 
-/// Pre-compute DLC subset definitions for keygen session creation.
-///
-/// This generates subset definitions based on the competition parameters (number of entries
-/// and number of winners). Each outcome has a subset containing the coordinator plus all
-/// winning players for that outcome.
-///
-/// The player_user_ids must be ordered to match the player indices (0, 1, 2, ...).
-/// At competition creation time, tickets are generated in order, so ticket_ids map
-/// directly to player indices.
-///
-/// Returns DlcSubsetInfo containing:
-/// - definitions: SubsetDefinition list for keygen session creation
-/// - outcome_subset_ids: Mapping from outcome index to subset UUID for signing
-pub fn compute_dlc_subset_definitions(
-    coordinator_user_id: UserId,
-    player_user_ids: &[UserId],
-    number_of_places_win: usize,
-) -> DlcSubsetInfo {
-    let num_players = player_user_ids.len();
-    let possible_rankings = generate_ranking_permutations(num_players, number_of_places_win);
+        let outcome_of = |ranking: Vec<Vec<usize>>| {
+            let index = rankings.iter().position(|r| *r == ranking).unwrap();
+            split[&Outcome::Attestation(index)].clone()
+        };
+        // Two way and three way ties for second share its 40%
+        assert_eq!(
+            outcome_of(vec![vec![2], vec![0, 3]]),
+            BTreeMap::from([(0, 40), (2, 120), (3, 40)])
+        );
+        assert_eq!(
+            outcome_of(vec![vec![1], vec![0, 2, 3]]),
+            BTreeMap::from([(0, 40), (1, 180), (2, 40), (3, 40)])
+        );
+    }
 
-    let mut definitions = Vec::new();
-    let mut outcome_subset_ids = BTreeMap::new();
+    #[test]
+    fn test_split_tie_policy_caps_the_outcome_count() {
+        let (competition, _, _) = payout_fixture(6, 2);
+        let mut create_event = competition.event_submission;
+        create_event.tie_policy = TiePolicy::Split;
+        assert!(
+            validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO).is_ok()
+        );
 
-    for (outcome_index, winner_indices) in possible_rankings.iter().enumerate() {
-        let subset_id = Uuid::now_v7();
-        outcome_subset_ids.insert(outcome_index, subset_id);
+        create_event.total_allowed_entries = 25;
+        create_event.total_competition_pool = Sats(25_000);
+        let errors = validate_create_event(&create_event, 25, 0, 144, std::time::Duration::ZERO)
+            .unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "tie_policy");
+        assert_eq!(errors.errors[0].code, "too_many_outcomes");
+    }
 
-        // Subset includes: coordinator + all winners for this outcome
-        let mut participants = vec![coordinator_user_id.clone()];
-        for &winner_idx in winner_indices {
-            if winner_idx < player_user_ids.len() {
-                participants.push(player_user_ids[winner_idx].clone());
-            }
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_observation_data_is_cached_once_attested() {
+        use crate::domain::{
+            invoices::test_support::test_coordinator,
+            scoring::{Observation, ObservationData, OracleEntryScore},
+        };
+        use dlctix::secp::MaybeScalar;
+
+        let test = test_coordinator().await;
+        let mut competition = test.create_competition(2).await;
+        let observed = |temp_high: f64| ObservationData {
+            forecasts: vec![],
+            observations: vec![Observation {
+                station_id: "KORD".to_string(),
+                wind_speed: None,
+                temp_high: Some(temp_high),
+                temp_low: None,
+            }],
+            entry_scores: vec![OracleEntryScore {
+                id: Uuid::now_v7(),
+                score: Some(10),
+            }],
+        };
+        let temp_high = |data: ObservationData| data.observations[0].temp_high;
+        test.oracle
+            .set_observation_data_for(competition.id, observed(80.0));
+
+        // Scores are still moving before the attestation, every request asks the oracle
+        for _ in 0..2 {
+            let data = test
+                .coordinator
+                .get_observation_data(&competition)
+                .await
+                .unwrap();
+            assert_eq!(temp_high(data), Some(80.0));
+        }
+        assert_eq!(test.oracle.observation_fetches(), 2);
+
+        competition.attestation = Some(MaybeScalar::Valid(Scalar::one()));
+        test.oracle
+            .set_observation_data_for(competition.id, observed(81.0));
+        for _ in 0..3 {
+            let data = test
+                .coordinator
+                .get_observation_data(&competition)
+                .await
+                .unwrap();
+            assert_eq!(temp_high(data), Some(81.0));
         }
+        assert_eq!(test.oracle.observation_fetches(), 3);
+
+        // The cache outlives the oracle's own data, only a corrected attestation refetches
+        test.oracle
+            .set_observation_data_for(competition.id, observed(82.0));
+        let data = test
+            .coordinator
+            .get_observation_data(&competition)
+            .await
+            .unwrap();
+        assert_eq!(temp_high(data), Some(81.0));
+        assert_eq!(test.oracle.observation_fetches(), 3);
+
+        competition.attestation = Some(MaybeScalar::Valid(Scalar::from_slice(&[2; 32]).unwrap()));
+        let data = test
+            .coordinator
+            .get_observation_data(&competition)
+            .await
+            .unwrap();
+        assert_eq!(temp_high(data), Some(82.0));
+        assert_eq!(test.oracle.observation_fetches(), 4);
+    }
 
-        debug!(
-            "Outcome {}: subset {} with {} participants (winners: {:?})",
-            outcome_index,
-            subset_id,
-            participants.len(),
-            winner_indices
-        );
+    #[test]
+    fn test_generate_payouts_when_every_player_places() {
+        // Shrunk case: with as many places as players every ranking used to be
+        // treated as the refund outcome and paid out equally
+        let (competition, entries, players) = payout_fixture(2, 2);
+        let payouts = generate_payouts(&competition, &entries, &players).unwrap();
 
-        definitions.push(SubsetDefinition::with_id(subset_id, participants));
+        assert_eq!(
+            payouts[&Outcome::Attestation(0)],
+            BTreeMap::from([(0, 60), (1, 40)])
+        );
+        assert_eq!(
+            payouts[&Outcome::Attestation(1)],
+            BTreeMap::from([(1, 60), (0, 40)])
+        );
+        assert_eq!(
+            payouts[&Outcome::Attestation(2)],
+            BTreeMap::from([(0, 50), (1, 50)])
+        );
     }
 
-    info!(
-        "Pre-computed {} subset definitions for {} players with {} winners per outcome",
-        definitions.len(),
-        num_players,
-        number_of_places_win
-    );
+    #[test]
+    fn test_generate_payouts_rejects_unsupported_places() {
+        // Shrunk case: more than 5 places used to index past the weight table and panic
+        let (competition, entries, players) = payout_fixture(6, 6);
+        assert!(generate_payouts(&competition, &entries, &players).is_err());
+    }
 
-    DlcSubsetInfo {
-        definitions,
-        outcome_subset_ids,
+    /// Enter both players of a two player competition and give each ticket an escrow
+    /// transaction locking the entry fee
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    async fn entered_with_escrows(
+        test: &crate::domain::invoices::test_support::TestCoordinator,
+    ) -> (Competition, Vec<(u8, Uuid, Transaction)>) {
+        use crate::domain::invoices::test_support::{player_bitcoin_pubkey, player_pubkey};
+
+        let competition = test.create_competition(2).await;
+        let coordinator_pubkey = test.coordinator.bitcoin.get_public_key().await.unwrap();
+        let mut escrows = vec![];
+        for seed in [1u8, 2] {
+            let ticket = test.pay_for_ticket(competition.id, seed).await;
+            let payment_hash: [u8; 32] = hex::decode(&ticket.payment_hash)
+                .unwrap()
+                .try_into()
+                .unwrap();
+            let descriptor = create_escrow_descriptor(
+                &coordinator_pubkey,
+                &player_bitcoin_pubkey(seed),
+                &payment_hash,
+            )
+            .unwrap();
+            let escrow_tx = Transaction {
+                version: Version::TWO,
+                lock_time: LockTime::ZERO,
+                input: vec![],
+                output: vec![TxOut {
+                    value: Amount::from_sat(competition.event_submission.entry_fee.to_sat()),
+                    script_pubkey: descriptor.script_pubkey(),
+                }],
+            };
+            test.coordinator
+                .competition_store
+                .update_ticket_escrow_transaction(
+                    ticket.ticket_id,
+                    &hex::encode(consensus::serialize(&escrow_tx)),
+                )
+                .await
+                .unwrap();
+            test.coordinator
+                .add_entry(
+                    player_pubkey(seed),
+                    test.entry(competition.id, ticket.ticket_id, seed),
+                )
+                .await
+                .unwrap();
+            escrows.push((seed, ticket.ticket_id, escrow_tx));
+        }
+        (competition, escrows)
     }
-}
 
-fn find_player_indices(
-    players: &[Player],
-    entry_ticket_pubkeys: Vec<String>,
-) -> Result<Vec<usize>, anyhow::Error> {
-    entry_ticket_pubkeys
-        .into_iter()
-        .map(|pubkey| {
-            let pubkey_point = Point::from_hex(&pubkey)?;
-            players
-                .iter()
-                .position(|player| player.pubkey == pubkey_point)
-                .ok_or_else(|| anyhow!("No player found for pubkey: {}", pubkey))
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_coordinator_broadcasts_escrows_once_entries_are_collected() {
+        use crate::domain::invoices::test_support::{player_pubkey, test_coordinator_with};
+        use crate::simulation::{BitcoinScript, ScriptedBitcoin, SimClock};
+
+        let bitcoin = Arc::new(ScriptedBitcoin::new(
+            BitcoinScript::default(),
+            SimClock::default(),
+        ));
+        let test = test_coordinator_with(bitcoin.clone(), |settings| {
+            settings
+                .coordinator_settings
+                .escrow_broadcast_by_coordinator = true
         })
-        .collect()
-}
+        .await;
+        let (mut competition, escrows) = entered_with_escrows(&test).await;
 
-fn find_winning_entries_pubkeys(
-    entries: &[UserEntry],
-    winning_entry_indices: Vec<usize>,
-) -> Vec<String> {
-    winning_entry_indices
-        .into_iter()
-        .map(|idx| entries[idx].ephemeral_pubkey.clone())
-        .collect()
-}
+        let (seed, ticket_id, _) = escrows[0];
+        let status = test
+            .coordinator
+            .get_ticket_status(player_pubkey(seed), competition.id, ticket_id)
+            .await
+            .unwrap();
+        let escrow = status.escrow.unwrap();
+        assert_eq!(escrow.broadcast_by, EscrowBroadcaster::Coordinator);
+        assert_eq!(escrow.chain_status, EscrowChainStatus::NotFound);
+        assert!(escrow.broadcasted_at.is_none());
+
+        // Every entry is in, the escrows go out but haven't been mined yet
+        test.coordinator
+            .check_escrow_confirmations(&mut competition)
+            .await
+            .unwrap();
+        assert!(competition.escrow_funds_confirmed_at.is_none());
+        let tickets = test
+            .coordinator
+            .competition_store
+            .get_tickets(competition.id)
+            .await
+            .unwrap();
+        assert!(tickets
+            .values()
+            .all(|ticket| ticket.escrow_broadcasted_at.is_some()));
+        let escrow = test
+            .coordinator
+            .get_ticket_status(player_pubkey(seed), competition.id, ticket_id)
+            .await
+            .unwrap()
+            .escrow
+            .unwrap();
+        assert_eq!(escrow.chain_status, EscrowChainStatus::Unconfirmed);
+        assert!(escrow.broadcasted_at.is_some());
+
+        bitcoin.advance(0);
+        test.coordinator
+            .check_escrow_confirmations(&mut competition)
+            .await
+            .unwrap();
+        assert!(competition.escrow_funds_confirmed_at.is_some());
+    }
+
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_escrow_tx_is_withheld_when_the_coordinator_broadcasts_it() {
+        use crate::domain::invoices::test_support::{player_pubkey, test_coordinator_with};
+        use crate::simulation::{BitcoinScript, ScriptedBitcoin, SimClock};
+
+        let bitcoin = Arc::new(ScriptedBitcoin::new(
+            BitcoinScript::default(),
+            SimClock::default(),
+        ));
+        let test = test_coordinator_with(bitcoin, |settings| {
+            settings
+                .coordinator_settings
+                .escrow_broadcast_by_coordinator = true
+        })
+        .await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.reserve_ticket(competition.id, 1).await;
+        let escrow_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: Amount::from_sat(competition.event_submission.entry_fee.to_sat()),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        test.coordinator
+            .competition_store
+            .update_ticket_escrow_transaction(
+                ticket.ticket_id,
+                &hex::encode(consensus::serialize(&escrow_tx)),
+            )
+            .await
+            .unwrap();
 
-async fn signed_funding_tx(
-    bitcoin_client: Arc<dyn Bitcoin>,
-    mut funding_tx: Psbt,
-) -> Result<Transaction, anyhow::Error> {
-    debug!("Funding Psbt before coordinator signing: {:?}", funding_tx);
+        test.expire_invoice(ticket.ticket_id).await;
+        let reissued = test
+            .coordinator
+            .regenerate_ticket_invoice(player_pubkey(1), competition.id, ticket.ticket_id)
+            .await
+            .unwrap();
+        assert!(reissued.escrow_tx.is_none());
+        // The player can still tell which escrow their payment is bound to
+        let memo: InvoiceMemo = reissued.invoice_memo.parse().unwrap();
+        assert_eq!(memo.escrow_txid, Some(escrow_tx.compute_txid().to_string()));
+    }
 
-    // Sign the PSBT (including escrow inputs)
-    let fully_signed = bitcoin_client
-        .sign_psbt_with_escrow_support(
-            &mut funding_tx,
-            SignOptions {
-                trust_witness_utxo: true,
-                sign_with_tap_internal_key: true,
-                allow_all_sighashes: true,
-                allow_grinding: true,
-                ..Default::default()
-            },
-        )
-        .await?;
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_players_broadcast_their_own_escrows_by_default() {
+        use crate::domain::invoices::test_support::{player_pubkey, test_coordinator_with};
+        use crate::simulation::{BitcoinScript, ScriptedBitcoin, SimClock};
 
-    debug!(
-        "Coordinator signing complete. Fully signed: {}",
-        fully_signed
-    );
+        let bitcoin = Arc::new(ScriptedBitcoin::new(
+            BitcoinScript::default(),
+            SimClock::default(),
+        ));
+        let test = test_coordinator_with(bitcoin.clone(), |_| {}).await;
+        let (mut competition, escrows) = entered_with_escrows(&test).await;
 
-    // Log signature state after signing
-    for (i, input) in funding_tx.inputs.iter().enumerate() {
-        if input.witness_script.is_some() {
-            debug!("Input {} signature count: {}", i, input.partial_sigs.len());
-            for pk in input.partial_sigs.keys() {
-                debug!("  - Signed by: {}", pk);
-            }
+        // The coordinator leaves the escrows alone and keeps waiting on the players
+        test.coordinator
+            .check_escrow_confirmations(&mut competition)
+            .await
+            .unwrap();
+        assert!(competition.escrow_funds_confirmed_at.is_none());
+        let (seed, ticket_id, _) = escrows[0];
+        let escrow = test
+            .coordinator
+            .get_ticket_status(player_pubkey(seed), competition.id, ticket_id)
+            .await
+            .unwrap()
+            .escrow
+            .unwrap();
+        assert_eq!(escrow.broadcast_by, EscrowBroadcaster::Player);
+        assert_eq!(escrow.chain_status, EscrowChainStatus::NotFound);
+        assert!(escrow.message.contains("responsible for broadcasting"));
+
+        for (_, _, escrow_tx) in &escrows {
+            bitcoin.record_broadcast(escrow_tx).unwrap();
         }
+        let escrow = test
+            .coordinator
+            .get_ticket_status(player_pubkey(seed), competition.id, ticket_id)
+            .await
+            .unwrap()
+            .escrow
+            .unwrap();
+        assert_eq!(escrow.chain_status, EscrowChainStatus::Unconfirmed);
+        assert!(escrow.broadcasted_at.is_none());
+
+        bitcoin.advance(0);
+        test.coordinator
+            .check_escrow_confirmations(&mut competition)
+            .await
+            .unwrap();
+        assert!(competition.escrow_funds_confirmed_at.is_some());
+        let tickets = test
+            .coordinator
+            .competition_store
+            .get_tickets(competition.id)
+            .await
+            .unwrap();
+        assert!(tickets
+            .values()
+            .all(|ticket| ticket.escrow_broadcasted_at.is_none()));
     }
 
-    // Verify we have all required signatures
-    let mut validation_errors = Vec::new();
-    for (i, input) in funding_tx.inputs.iter().enumerate() {
-        // Skip already finalized inputs
-        if input.final_script_witness.is_some() {
-            debug!("Input {} is already finalized", i);
-            continue;
-        }
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_double_spent_entries_leave_the_competition() {
+        use crate::domain::invoices::test_support::test_coordinator;
 
-        // Check escrow inputs
-        if input.witness_script.is_some() {
-            // For 2-of-2 multisig escrow, we need exactly 2 signatures
-            if input.partial_sigs.len() < 2 {
-                validation_errors.push(format!(
-                    "Input {}: Escrow input has {} signatures, needs 2",
-                    i,
-                    input.partial_sigs.len()
-                ));
-            }
-        } else if input.partial_sigs.is_empty() {
-            validation_errors.push(format!("Input {}: No signatures present", i));
+        let test = test_coordinator().await;
+        let competition = test.create_competition(3).await;
+        let mut entry_ids = Vec::new();
+        for seed in 1..=3 {
+            entry_ids.push(test.enter(competition.id, seed).await);
         }
+        let store = &test.coordinator.competition_store;
+
+        let (event_submission, version) = store
+            .cancel_double_spent_entries(
+                competition.id,
+                vec![(entry_ids[1], String::from("escrow spent elsewhere"))],
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event_submission.total_allowed_entries, 2);
+        assert_eq!(
+            event_submission.total_competition_pool,
+            competition
+                .event_submission
+                .total_competition_pool
+                .saturating_sub(competition.event_submission.entry_fee)
+        );
+
+        // The cancelled entry no longer counts toward the contract
+        let stored = store.get_competition(competition.id).await.unwrap();
+        assert_eq!(stored.total_entries, 2);
+        assert_eq!(stored.total_paid_entries, 2);
+        assert_eq!(stored.event_submission.total_allowed_entries, 2);
+        assert_eq!(stored.version, version);
+        let entries = store
+            .get_competition_entries(competition.id, vec![EntryStatus::Paid])
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.id != entry_ids[1]));
+        let tickets = store.get_tickets(competition.id).await.unwrap();
+        assert!(!tickets.contains_key(&entry_ids[1]));
+
+        // Cancelling it again changes nothing
+        let (again, _) = store
+            .cancel_double_spent_entries(
+                competition.id,
+                vec![(entry_ids[1], String::from("escrow spent elsewhere"))],
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.total_allowed_entries, 2);
+
+        // Once the oracle event exists the event can't shrink anymore
+        let mut created = store.get_competition(competition.id).await.unwrap();
+        created.event_created_at = Some(OffsetDateTime::now_utc());
+        store
+            .update_competitions(std::slice::from_mut(&mut created))
+            .await
+            .unwrap();
+        assert!(store
+            .cancel_double_spent_entries(
+                competition.id,
+                vec![(entry_ids[0], String::from("escrow spent elsewhere"))],
+            )
+            .await
+            .unwrap()
+            .is_none());
     }
 
-    if !validation_errors.is_empty() {
-        return Err(anyhow!(
-            "PSBT not ready for finalization. Issues: {}",
-            validation_errors.join(", ")
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_runaway_competition_is_failed_and_its_hold_invoices_cancelled() {
+        use crate::domain::invoices::test_support::test_coordinator;
+
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        test.enter(competition.id, 1).await;
+        let competition = test
+            .coordinator
+            .competition_store
+            .get_competition(competition.id)
+            .await
+            .unwrap();
+
+        let max_lifetime = time::Duration::hours(1);
+        let now = OffsetDateTime::now_utc();
+        assert!(!competition.exceeds_max_lifetime(max_lifetime, now));
+        assert!(competition.exceeds_max_lifetime(max_lifetime, now + time::Duration::hours(2)));
+
+        test.coordinator
+            .fail_runaway_competition(competition.clone(), max_lifetime)
+            .await;
+
+        let stored = test
+            .coordinator
+            .competition_store
+            .get_competition(competition.id)
+            .await
+            .unwrap();
+        assert!(stored.is_failed());
+        assert!(matches!(
+            stored.errors.last().map(|recorded| &recorded.error),
+            Some(CompetitionError::MaxLifetimeExceeded(_))
         ));
-    }
+        // Failed competitions aren't picked up again
+        assert!(!stored.exceeds_max_lifetime(max_lifetime, now + time::Duration::hours(2)));
 
-    // Now finalize the PSBT
-    let finalized = bitcoin_client
-        .finalize_psbt_with_escrow_support(&mut funding_tx)
-        .await?;
+        // Funded ones are left to their expiry transaction rather than failed
+        let mut funded = competition.clone();
+        funded.funding_broadcasted_at = Some(now);
+        assert!(!funded.exceeds_max_lifetime(max_lifetime, now + time::Duration::hours(2)));
 
-    if !finalized {
-        return Err(anyhow!("Failed to finalize PSBT after signing"));
+        // Nothing was funded, the entry's paid hold invoice is released back to the player
+        let tickets = test
+            .coordinator
+            .competition_store
+            .get_tickets(competition.id)
+            .await
+            .unwrap();
+        for ticket in tickets.values() {
+            let invoice = test.ln.lookup_invoice(&ticket.hash, None).await.unwrap();
+            assert_eq!(invoice.state, InvoiceState::Canceled);
+        }
     }
 
-    // Extract the final transaction
-    match funding_tx.clone().extract_tx() {
-        Ok(final_tx) => {
-            debug!(
-                "Successfully extracted and finalized funding transaction: {}",
-                final_tx.compute_txid()
-            );
-            Ok(final_tx)
+    #[cfg(any(feature = "e2e-testing", debug_assertions))]
+    #[tokio::test]
+    async fn test_reclaims_no_show_escrows_through_both_branches() {
+        use crate::domain::invoices::test_support::{
+            player_bitcoin_pubkey, player_pubkey, test_coordinator,
+        };
+
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let coordinator_pubkey = test.coordinator.bitcoin.get_public_key().await.unwrap();
+
+        let mut no_shows = vec![];
+        for seed in [1u8, 2] {
+            let ticket = test.pay_for_ticket(competition.id, seed).await;
+            let entry = test
+                .coordinator
+                .add_entry(
+                    player_pubkey(seed),
+                    test.entry(competition.id, ticket.ticket_id, seed),
+                )
+                .await
+                .unwrap();
+
+            let payment_hash: [u8; 32] = hex::decode(&ticket.payment_hash)
+                .unwrap()
+                .try_into()
+                .unwrap();
+            let descriptor = create_escrow_descriptor(
+                &coordinator_pubkey,
+                &player_bitcoin_pubkey(seed),
+                &payment_hash,
+            )
+            .unwrap();
+            let escrow_tx = Transaction {
+                version: Version::TWO,
+                lock_time: LockTime::ZERO,
+                input: vec![],
+                output: vec![TxOut {
+                    value: Amount::from_sat(10_000),
+                    script_pubkey: descriptor.script_pubkey(),
+                }],
+            };
+            test.coordinator
+                .competition_store
+                .update_ticket_escrow_transaction(
+                    ticket.ticket_id,
+                    &hex::encode(consensus::serialize(&escrow_tx)),
+                )
+                .await
+                .unwrap();
+            no_shows.push((seed, ticket.ticket_id, entry.id));
         }
-        Err(e) => {
-            // Detailed debugging if extraction fails
-            debug!("Failed to extract transaction: {:?}", e);
+        // The second player's invoice was settled, only the preimage branch is left for them
+        test.coordinator
+            .competition_store
+            .mark_ticket_settled(no_shows[1].1, 1_100)
+            .await
+            .unwrap();
 
-            for (i, input) in funding_tx.inputs.iter().enumerate() {
-                if input.final_script_witness.is_none() && input.witness_script.is_some() {
-                    debug!("Input {} not finalized:", i);
-                    debug!("  - Signatures: {}", input.partial_sigs.len());
-                    if let Some(ws) = &input.witness_script {
-                        debug!("  - Witness script: {}", ws.to_hex_string());
-                    }
-                }
-            }
+        let reclaim_info = |seed: u8, ticket_id: Uuid| EscrowReclaimInfo {
+            ticket_id,
+            ephemeral_private_key: hex::encode([seed; 32]),
+            address: String::from("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"),
+        };
+        let (seed, ticket_id, entry_id) = no_shows[0];
+        let result = test
+            .coordinator
+            .register_escrow_reclaim(
+                player_pubkey(seed),
+                competition.id,
+                entry_id,
+                reclaim_info(seed, ticket_id),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
 
-            Err(anyhow!("Failed to extract transaction: {}", e))
+        test.coordinator
+            .cancel_competition(competition.id)
+            .await
+            .unwrap();
+        let result = test
+            .coordinator
+            .register_escrow_reclaim(
+                player_pubkey(seed),
+                competition.id,
+                entry_id,
+                reclaim_info(2, ticket_id),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(_))));
+        for (seed, ticket_id, entry_id) in &no_shows {
+            test.coordinator
+                .register_escrow_reclaim(
+                    player_pubkey(*seed),
+                    competition.id,
+                    *entry_id,
+                    reclaim_info(*seed, *ticket_id),
+                )
+                .await
+                .unwrap();
         }
-    }
-}
 
-fn p2tr_script_pubkey(pubkey: Point) -> ScriptBuf {
-    let (xonly, _) = pubkey.into();
-    let tweaked = TweakedPublicKey::dangerous_assume_tweaked(convert_xonly_key(xonly));
-    ScriptBuf::new_p2tr_tweaked(tweaked)
-}
+        // The mock chain has the escrows 4 blocks deep, enough for the cooperative branch but
+        // short of the preimage branch's timelock
+        test.coordinator.reclaim_no_show_escrows().await.unwrap();
+        let entries = test
+            .coordinator
+            .competition_store
+            .get_competition_entries(competition.id, vec![])
+            .await
+            .unwrap();
+        let reclaimed = |entry_id: Uuid| {
+            entries
+                .iter()
+                .find(|entry| entry.id == entry_id)
+                .is_some_and(|entry| entry.reclaimed_broadcasted_at.is_some())
+        };
+        assert!(reclaimed(no_shows[0].2));
+        assert!(!reclaimed(no_shows[1].2));
 
-fn simple_sweep_tx(
-    destination_pubkey: Point,
-    input: TxIn,
-    input_weight: InputWeightPrediction,
-    prevout_value: Amount,
-    fee_rate: FeeRate,
-) -> Transaction {
-    let script_pubkey = p2tr_script_pubkey(destination_pubkey);
-    Transaction {
-        version: Version::TWO,
-        lock_time: LockTime::ZERO,
-        input: vec![input],
-        output: vec![TxOut {
-            value: {
-                let tx_weight = predict_weight([input_weight], [script_pubkey.len()]);
-                let fee = tx_weight * fee_rate;
-                prevout_value - fee
-            },
-            script_pubkey,
-        }],
+        let pending = test
+            .coordinator
+            .competition_store
+            .get_pending_escrow_reclaims()
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].entry_id, no_shows[1].2);
     }
-}
 
-fn create_deterministic_rng(funding_outpoint: &OutPoint, private_key: Scalar) -> ChaCha20Rng {
-    let mut hasher = sha256::Hash::engine();
-
-    hasher.write_all(&funding_outpoint.txid[..]).unwrap();
-    hasher
-        .write_all(&funding_outpoint.vout.to_le_bytes())
-        .unwrap();
-    hasher.write_all(&private_key.serialize()).unwrap();
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_generate_payouts_pays_out_the_pool(
+            (num_players, places) in (1usize..=6)
+                .prop_flat_map(|n| (Just(n), 1..=n.min(5))),
+            funding_value_sats in 1u64..=2_100_000_000_000_000,
+        ) {
+            let (competition, entries, players) = payout_fixture(num_players, places);
+            let payouts = generate_payouts(&competition, &entries, &players).unwrap();
+
+            let num_rankings = generate_ranking_permutations(num_players, places).len();
+            // Every ranking, the refund outcome (already counted) and expiry
+            prop_assert_eq!(payouts.len(), num_rankings + 1);
+
+            for (outcome, weights) in &payouts {
+                let expected_winners = match outcome {
+                    Outcome::Attestation(i) if *i < num_rankings - 1 => places,
+                    _ => num_players,
+                };
+                prop_assert_eq!(weights.len(), expected_winners);
+                prop_assert!(weights.keys().all(|index| *index < players.len()));
+                prop_assert_eq!(weights.values().sum::<u64>(), 100);
 
-    let hash = sha256::Hash::from_engine(hasher);
-    let seed: [u8; 32] = hash.to_byte_array();
-    ChaCha20Rng::from_seed(seed)
-}
+                let shares = split_payout(funding_value_sats, weights);
+                prop_assert_eq!(shares.values().sum::<u64>(), funding_value_sats);
+                prop_assert!(shares.values().all(|share| *share <= funding_value_sats));
+            }
 
-async fn validate_entry(entry: AddEventEntry, competition: Competition) -> Result<(), Error> {
-    if entry.id.get_version_num() != 7 {
-        return Err(Error::BadRequest(format!(
-            "Client needs to provide a valid Uuidv7 for entry id {}",
-            entry.id
-        )));
+            // Ties in rank or weight must not depend on anything but the inputs
+            prop_assert_eq!(
+                payouts,
+                generate_payouts(&competition, &entries, &players).unwrap()
+            );
+        }
     }
 
-    let mut choice_count = 0;
-    for weather_choice in &entry.expected_observations {
-        if weather_choice.temp_high.is_some() {
-            choice_count += 1;
-        }
-        if weather_choice.temp_low.is_some() {
-            choice_count += 1;
+    #[test]
+    fn test_signing_reuses_the_session_built_while_aggregating_nonces() {
+        use dlctix::{attestation_locking_point, EventLockingConditions};
+
+        fn scalar(context: &str, index: usize) -> Scalar {
+            let hash = sha256::Hash::hash(format!("{}{}", context, index).as_bytes());
+            Scalar::from_slice(&hash.to_byte_array()).unwrap()
         }
-        if weather_choice.wind_speed.is_some() {
-            choice_count += 1;
+        fn builds() -> usize {
+            TICKETED_DLC_BUILDS.with(|builds| builds.get())
         }
 
-        if choice_count > competition.event_submission.number_of_values_per_entry {
-            return Err(Error::BadRequest(format!(
-                "entry_id {0} not valid, too many value choices, max allowed {1} but got {2}",
-                entry.id, competition.event_submission.number_of_values_per_entry, choice_count
-            )));
+        const ENTRIES: usize = 2;
+        let player_keys: Vec<Scalar> = (0..ENTRIES).map(|i| scalar("player", i)).collect();
+        let oracle_pubkey = scalar("oracle", 0).base_point_mul();
+        let nonce_point = scalar("nonce", 0).base_point_mul();
+        let mut outcome_payouts: BTreeMap<Outcome, PayoutWeights> = (0..ENTRIES)
+            .map(|i| (Outcome::Attestation(i), PayoutWeights::from([(i, 1)])))
+            .collect();
+        outcome_payouts.insert(Outcome::Attestation(ENTRIES), equal_weights(ENTRIES));
+        outcome_payouts.insert(Outcome::Expiry, equal_weights(ENTRIES));
+        let coordinator_key = scalar("coordinator", 0);
+        let contract_params = ContractParameters {
+            market_maker: dlctix::MarketMaker {
+                pubkey: coordinator_key.base_point_mul(),
+            },
+            players: (0..ENTRIES)
+                .map(|i| Player {
+                    pubkey: player_keys[i].base_point_mul(),
+                    ticket_hash: sha256::Hash::hash(&scalar("ticket", i).serialize())
+                        .to_byte_array(),
+                    payout_hash: sha256::Hash::hash(&scalar("payout", i).serialize())
+                        .to_byte_array(),
+                })
+                .collect(),
+            event: EventLockingConditions {
+                locking_points: (0..=ENTRIES)
+                    .map(|i| {
+                        attestation_locking_point(
+                            oracle_pubkey,
+                            nonce_point,
+                            format!("outcome_{}", i).as_bytes(),
+                        )
+                    })
+                    .collect(),
+                expiry: Some(2_000_000_000),
+            },
+            outcome_payouts,
+            fee_rate: FeeRate::from_sat_per_vb_unchecked(2),
+            funding_value: Amount::from_sat(ENTRIES as u64 * 10_000),
+            relative_locktime_block_delta: 144,
+        };
+        let funding_outpoint = OutPoint::null();
+
+        let nonces_of = |key: Scalar, mut rng: ChaCha20Rng| {
+            let ticketed_dlc = TicketedDLC::new(contract_params.clone(), funding_outpoint).unwrap();
+            SigningSession::<NonceSharingRound>::new(ticketed_dlc, &mut rng, key)
+                .unwrap()
+                .our_public_nonces()
+                .to_owned()
+        };
+        let our_nonces = nonces_of(
+            coordinator_key,
+            create_deterministic_rng(&funding_outpoint, coordinator_key),
+        );
+        let received_nonces: BTreeMap<Point, SigMap<PubNonce>> = player_keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let rng = ChaCha20Rng::from_seed([i as u8; 32]);
+                (key.base_point_mul(), nonces_of(*key, rng))
+            })
+            .collect();
+
+        let cache = SigningSessionCache::new(2);
+        let competition_id = Uuid::now_v7();
+        let before = builds();
+
+        // Aggregating the nonces builds the contract once and keeps the session
+        let session = build_coordinator_session(
+            contract_params.clone(),
+            funding_outpoint,
+            coordinator_key,
+            &our_nonces,
+            received_nonces.clone(),
+        )
+        .unwrap();
+        assert_eq!(builds(), before + 1);
+        let partial_signatures = session.our_partial_signatures().to_owned();
+        cache.insert(competition_id, funding_outpoint, &contract_params, session);
+
+        // Signing on the next tick takes it instead of building the contract again
+        let session = coordinator_signing_session(
+            &cache,
+            competition_id,
+            &contract_params,
+            funding_outpoint,
+            coordinator_key,
+            &our_nonces,
+            received_nonces.clone(),
+        )
+        .unwrap();
+        assert_eq!(builds(), before + 1);
+        assert_eq!(*session.our_partial_signatures(), partial_signatures);
+        assert!(cache.is_empty());
+
+        // After a restart the session is rebuilt to the same signatures
+        let session = coordinator_signing_session(
+            &cache,
+            competition_id,
+            &contract_params,
+            funding_outpoint,
+            coordinator_key,
+            &our_nonces,
+            received_nonces.clone(),
+        )
+        .unwrap();
+        assert_eq!(builds(), before + 2);
+        assert_eq!(*session.our_partial_signatures(), partial_signatures);
+
+        // A session for another funding outpoint or contract is dropped, not used
+        cache.insert(competition_id, funding_outpoint, &contract_params, session);
+        let rebuilt_outpoint = OutPoint {
+            vout: 1,
+            ..funding_outpoint
+        };
+        assert!(cache
+            .take(competition_id, rebuilt_outpoint, &contract_params)
+            .is_none());
+        assert!(cache.is_empty());
+
+        let mut changed_params = contract_params.clone();
+        changed_params.fee_rate = FeeRate::from_sat_per_vb_unchecked(3);
+        for _ in 0..3 {
+            let session = build_coordinator_session(
+                contract_params.clone(),
+                funding_outpoint,
+                coordinator_key,
+                &our_nonces,
+                received_nonces.clone(),
+            )
+            .unwrap();
+            cache.insert(Uuid::now_v7(), funding_outpoint, &contract_params, session);
         }
+        // Only the two newest are kept
+        assert_eq!(cache.len(), 2);
+        let session = build_coordinator_session(
+            contract_params.clone(),
+            funding_outpoint,
+            coordinator_key,
+            &our_nonces,
+            received_nonces,
+        )
+        .unwrap();
+        cache.insert(competition_id, funding_outpoint, &contract_params, session);
+        assert!(cache
+            .take(competition_id, funding_outpoint, &changed_params)
+            .is_none());
+        assert_eq!(cache.len(), 1);
     }
 
-    let locations_choose: Vec<String> = entry
-        .expected_observations
-        .clone()
-        .iter()
-        .map(|weather_vals| weather_vals.stations.clone())
-        .collect();
-    let all_valid_locations = locations_choose
-        .iter()
-        .all(|choose| competition.event_submission.locations.contains(choose));
-    if !all_valid_locations {
-        return Err(Error::BadRequest(format!(
-            "entry_id {0} not valid, choose locations not in the even",
-            entry.id
-        )));
+    /// Peak memory and runtime responsiveness while building the coordinator's nonces for a 100
+    /// entry contract, run with `cargo test -p coordinator contract_build -- --ignored --nocapture`
+    #[tokio::test(flavor = "current_thread")]
+    #[ignore]
+    async fn test_contract_build_of_100_entries_leaves_the_runtime_responsive() {
+        use dlctix::{attestation_locking_point, EventLockingConditions};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        fn scalar(context: &str, index: usize) -> Scalar {
+            let hash = sha256::Hash::hash(format!("{}{}", context, index).as_bytes());
+            Scalar::from_slice(&hash.to_byte_array()).unwrap()
+        }
+        fn peak_rss_kb() -> Option<u64> {
+            std::fs::read_to_string("/proc/self/status")
+                .ok()?
+                .lines()
+                .find_map(|line| line.strip_prefix("VmHWM:"))?
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse()
+                .ok()
+        }
+
+        const ENTRIES: usize = 100;
+        let players: Vec<Player> = (0..ENTRIES)
+            .map(|i| Player {
+                pubkey: scalar("player", i).base_point_mul(),
+                ticket_hash: sha256::Hash::hash(&scalar("ticket", i).serialize()).to_byte_array(),
+                payout_hash: sha256::Hash::hash(&scalar("payout", i).serialize()).to_byte_array(),
+            })
+            .collect();
+        // One winning place, an outcome per entry plus the refund outcome
+        let oracle_pubkey = scalar("oracle", 0).base_point_mul();
+        let nonce_point = scalar("nonce", 0).base_point_mul();
+        let locking_points = (0..=ENTRIES)
+            .map(|i| {
+                attestation_locking_point(
+                    oracle_pubkey,
+                    nonce_point,
+                    format!("outcome_{}", i).as_bytes(),
+                )
+            })
+            .collect();
+        let mut outcome_payouts: BTreeMap<Outcome, PayoutWeights> = (0..ENTRIES)
+            .map(|i| (Outcome::Attestation(i), PayoutWeights::from([(i, 1)])))
+            .collect();
+        outcome_payouts.insert(Outcome::Attestation(ENTRIES), equal_weights(ENTRIES));
+        outcome_payouts.insert(Outcome::Expiry, equal_weights(ENTRIES));
+
+        let coordinator_key = scalar("coordinator", 0);
+        let contract_params = ContractParameters {
+            market_maker: dlctix::MarketMaker {
+                pubkey: coordinator_key.base_point_mul(),
+            },
+            players,
+            event: EventLockingConditions {
+                locking_points,
+                expiry: Some(2_000_000_000),
+            },
+            outcome_payouts,
+            fee_rate: FeeRate::from_sat_per_vb_unchecked(2),
+            funding_value: Amount::from_sat(ENTRIES as u64 * 10_000),
+            relative_locktime_block_delta: 144,
+        };
+
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let ticker = tokio::spawn({
+            let ticks = ticks.clone();
+            async move {
+                loop {
+                    sleep(std::time::Duration::from_millis(10)).await;
+                    ticks.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+
+        // Resets the peak so it only covers the build
+        let _ = std::fs::write("/proc/self/clear_refs", "5");
+        let rss_before = peak_rss_kb();
+        let started = std::time::Instant::now();
+        let nonces = build_coordinator_nonces(
+            &Semaphore::new(1),
+            contract_params,
+            OutPoint::null(),
+            coordinator_key,
+        )
+        .await
+        .unwrap();
+        let elapsed = started.elapsed();
+        let rss_after = peak_rss_kb();
+        ticker.abort();
+        let ticks = ticks.load(Ordering::Relaxed);
+
+        println!(
+            "built nonces for {} entries in {:?}, peak rss {:?} kB -> {:?} kB, {} ticks meanwhile",
+            ENTRIES, elapsed, rss_before, rss_after, ticks
+        );
+        assert!(nonces.by_outcome.len() > ENTRIES);
+        // On a single threaded runtime the ticker only gets to run when the build is off it
+        assert!(
+            ticks as u128 >= elapsed.as_millis() / 50,
+            "{} ticks in {:?}",
+            ticks,
+            elapsed
+        );
     }
-    Ok(())
 }
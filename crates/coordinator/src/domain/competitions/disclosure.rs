@@ -0,0 +1,146 @@
+//! Public view of a competition's contract, so the payout weights, fee rate and locktimes can be
+//! audited without revealing which nostr user holds which player slot.
+use dlctix::{
+    bitcoin::{
+        hashes::{sha256, Hash, HashEngine},
+        OutPoint,
+    },
+    secp::Point,
+    ContractParameters, EventLockingConditions, Outcome, PayoutWeights,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const DISCLOSURE_SALT_TAG: &[u8] = b"5day4cast/contract-disclosure";
+
+/// A contract player with their pubkey replaced by a salted hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnonymizedPlayer {
+    pub player_index: usize,
+    /// Hex encoded sha256 of the competition's salt and the player's pubkey
+    pub player_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisclosedPayout {
+    pub outcome: Outcome,
+    pub weights: PayoutWeights,
+}
+
+/// Contract terms of a competition with the player identities hidden
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractDisclosure {
+    pub competition_id: Uuid,
+    pub market_maker_pubkey: Point,
+    pub players: Vec<AnonymizedPlayer>,
+    pub event: EventLockingConditions,
+    pub outcome_payouts: Vec<DisclosedPayout>,
+    pub fee_rate_sat_vb: u64,
+    pub funding_value_sats: u64,
+    pub relative_locktime_block_delta: u16,
+    pub funding_outpoint: Option<OutPoint>,
+    /// Player slots of the requesting entrant's own entries, empty without NIP-98 auth
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub your_player_indices: Vec<usize>,
+}
+
+/// Salt for one competition's player hashes. It is keyed by the coordinator's secret so nobody
+/// else can test a known pubkey against the hashes, and differs per competition so the same
+/// player can't be followed from one competition to the next.
+pub fn disclosure_salt(coordinator_secret: &[u8], competition_id: Uuid) -> [u8; 32] {
+    let mut engine = sha256::Hash::engine();
+    engine.input(DISCLOSURE_SALT_TAG);
+    engine.input(coordinator_secret);
+    engine.input(competition_id.as_bytes());
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// The salted hash standing in for a player's serialized pubkey
+pub fn anonymize_player(salt: &[u8; 32], pubkey: &[u8]) -> String {
+    let mut engine = sha256::Hash::engine();
+    engine.input(salt);
+    engine.input(pubkey);
+    hex::encode(sha256::Hash::from_engine(engine).to_byte_array())
+}
+
+pub fn disclose_contract(
+    competition_id: Uuid,
+    params: &ContractParameters,
+    funding_outpoint: Option<OutPoint>,
+    salt: &[u8; 32],
+    your_player_indices: Vec<usize>,
+) -> ContractDisclosure {
+    ContractDisclosure {
+        competition_id,
+        market_maker_pubkey: params.market_maker.pubkey,
+        players: params
+            .players
+            .iter()
+            .enumerate()
+            .map(|(player_index, player)| AnonymizedPlayer {
+                player_index,
+                player_hash: anonymize_player(salt, &player.pubkey.serialize()),
+            })
+            .collect(),
+        event: params.event.clone(),
+        outcome_payouts: params
+            .outcome_payouts
+            .iter()
+            .map(|(outcome, weights)| DisclosedPayout {
+                outcome: *outcome,
+                weights: weights.clone(),
+            })
+            .collect(),
+        fee_rate_sat_vb: params.fee_rate.to_sat_per_vb_ceil(),
+        funding_value_sats: params.funding_value.to_sat(),
+        relative_locktime_block_delta: params.relative_locktime_block_delta,
+        funding_outpoint,
+        your_player_indices,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dlctix::secp::Scalar;
+
+    fn pubkey(byte: u8) -> Vec<u8> {
+        Scalar::from_slice(&[byte; 32])
+            .unwrap()
+            .base_point_mul()
+            .serialize()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_player_hash_is_stable_per_competition_but_unlinkable_across() {
+        let secret = [9u8; 32];
+        let competition = Uuid::now_v7();
+        let other_competition = Uuid::now_v7();
+        let alice = pubkey(1);
+        let bob = pubkey(2);
+
+        let salt = disclosure_salt(&secret, competition);
+        assert_eq!(salt, disclosure_salt(&secret, competition));
+        assert_eq!(
+            anonymize_player(&salt, &alice),
+            anonymize_player(&disclosure_salt(&secret, competition), &alice)
+        );
+        assert_ne!(
+            anonymize_player(&salt, &alice),
+            anonymize_player(&salt, &bob)
+        );
+
+        // The same player in another competition gets an unrelated hash
+        let other_salt = disclosure_salt(&secret, other_competition);
+        assert_ne!(salt, other_salt);
+        assert_ne!(
+            anonymize_player(&salt, &alice),
+            anonymize_player(&other_salt, &alice)
+        );
+
+        // Without the coordinator's secret the salt can't be recomputed
+        assert_ne!(salt, disclosure_salt(&[8u8; 32], competition));
+        assert_eq!(anonymize_player(&salt, &alice).len(), 64);
+    }
+}
@@ -0,0 +1,251 @@
+use coordinator_core::Sats;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use uuid::Uuid;
+
+use super::{Competition, CompetitionState};
+
+/// Public listing of a competition that is currently accepting entries, shared by the JSON and
+/// Atom feeds so aggregators see the same data in both formats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenCompetition {
+    pub id: Uuid,
+    pub entry_fee: Sats,
+    pub total_competition_pool: Sats,
+    pub total_allowed_entries: usize,
+    pub remaining_slots: u64,
+    pub locations: Vec<String>,
+    /// Last moment a ticket can be requested
+    #[serde(with = "time::serde::rfc3339")]
+    pub entries_close_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub start_observation_date: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub end_observation_date: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub links: OpenCompetitionLinks,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenCompetitionLinks {
+    /// Page where a player can enter the competition
+    pub enter: String,
+    pub leaderboard: String,
+    pub api: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenCompetitionFeed {
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    pub competitions: Vec<OpenCompetition>,
+}
+
+impl OpenCompetitionFeed {
+    /// Keep only competitions that are still waiting on entries, haven't closed entries and
+    /// have tickets left. `available_tickets` comes from the ticket table so in-flight
+    /// reservations are already accounted for; competitions missing from it are full.
+    pub fn build(
+        competitions: &[Competition],
+        available_tickets: &HashMap<Uuid, u64>,
+        base_url: &str,
+        now: OffsetDateTime,
+    ) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        let mut open: Vec<OpenCompetition> = competitions
+            .iter()
+            .filter(|competition| competition.get_state() == CompetitionState::Created)
+            .filter(|competition| now < competition.event_submission.signing_window_end())
+            .filter_map(|competition| {
+                let remaining_slots = available_tickets.get(&competition.id).copied()?;
+                if remaining_slots == 0 {
+                    return None;
+                }
+                let event = &competition.event_submission;
+                Some(OpenCompetition {
+                    id: competition.id,
+                    entry_fee: event.entry_fee,
+                    total_competition_pool: event.total_competition_pool,
+                    total_allowed_entries: event.total_allowed_entries,
+                    remaining_slots,
+                    locations: event.locations.clone(),
+                    entries_close_at: event.signing_window_end(),
+                    start_observation_date: event.start_observation_date,
+                    end_observation_date: event.end_observation_date,
+                    created_at: competition.created_at,
+                    links: OpenCompetitionLinks {
+                        enter: format!("{}/competitions/{}/entry-form", base_url, competition.id),
+                        leaderboard: format!(
+                            "{}/competitions/{}/leaderboard",
+                            base_url, competition.id
+                        ),
+                        api: format!("{}/api/v1/competitions/{}", base_url, competition.id),
+                    },
+                })
+            })
+            .collect();
+
+        // Soonest to close first, that's what aggregators want to surface
+        open.sort_by_key(|competition| competition.entries_close_at);
+
+        Self {
+            generated_at: now,
+            competitions: open,
+        }
+    }
+
+    /// Render the feed as an Atom document
+    pub fn to_atom(&self, base_url: &str) -> String {
+        let base_url = base_url.trim_end_matches('/');
+        let updated = format_rfc3339(self.generated_at);
+        let mut atom = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        atom.push('\n');
+        atom.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+        atom.push_str("<title>Fantasy Weather - Open Competitions</title>");
+        atom.push_str(&format!(
+            "<id>{}/feed/competitions.atom</id>",
+            escape_xml(base_url)
+        ));
+        atom.push_str(&format!(
+            r#"<link rel="self" href="{}/feed/competitions.atom"/>"#,
+            escape_xml(base_url)
+        ));
+        atom.push_str(&format!("<updated>{}</updated>", updated));
+
+        for competition in &self.competitions {
+            let summary = format!(
+                "Entry fee {} sats, pool {} sats, {} of {} slots left. Locations: {}. Entries close {}.",
+                competition.entry_fee,
+                competition.total_competition_pool,
+                competition.remaining_slots,
+                competition.total_allowed_entries,
+                competition.locations.join(", "),
+                format_rfc3339(competition.entries_close_at),
+            );
+            atom.push_str("<entry>");
+            atom.push_str(&format!(
+                "<title>Competition {} ({})</title>",
+                competition.id,
+                escape_xml(&competition.locations.join(", "))
+            ));
+            atom.push_str(&format!("<id>urn:uuid:{}</id>", competition.id));
+            atom.push_str(&format!(
+                r#"<link rel="alternate" href="{}"/>"#,
+                escape_xml(&competition.links.enter)
+            ));
+            atom.push_str(&format!("<updated>{}</updated>", updated));
+            atom.push_str(&format!(
+                "<published>{}</published>",
+                format_rfc3339(competition.created_at)
+            ));
+            atom.push_str(&format!("<summary>{}</summary>", escape_xml(&summary)));
+            atom.push_str("</entry>");
+        }
+
+        atom.push_str("</feed>");
+        atom
+    }
+}
+
+fn format_rfc3339(time: OffsetDateTime) -> String {
+    time.format(&Rfc3339).unwrap_or_default()
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::CreateEvent;
+    use time::Duration;
+
+    fn test_competition(start: OffsetDateTime) -> Competition {
+        Competition::new(&CreateEvent {
+            locations: vec!["KORD".to_string(), "KJFK".to_string()],
+            ..CreateEvent::fixture(start)
+        })
+    }
+
+    #[test]
+    fn test_open_competition_listed_with_counted_slots() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now + Duration::hours(6));
+        // One entry submitted and one ticket reserved but unpaid, only the ticket count sees both
+        competition.total_entries = 1;
+        let available = HashMap::from([(competition.id, 1)]);
+
+        let feed = OpenCompetitionFeed::build(
+            &[competition.clone()],
+            &available,
+            "https://example.com/",
+            now,
+        );
+
+        assert_eq!(feed.competitions.len(), 1);
+        let listed = &feed.competitions[0];
+        assert_eq!(listed.remaining_slots, 1);
+        assert_eq!(
+            listed.links.enter,
+            format!(
+                "https://example.com/competitions/{}/entry-form",
+                competition.id
+            )
+        );
+    }
+
+    #[test]
+    fn test_closed_competitions_excluded() {
+        let now = OffsetDateTime::now_utc();
+        let started = test_competition(now - Duration::hours(1));
+        let mut signing_closed = test_competition(now + Duration::hours(6));
+        signing_closed.event_submission.signing_deadline = Some(now - Duration::minutes(5));
+        let mut cancelled = test_competition(now + Duration::hours(6));
+        cancelled.cancelled_at = Some(now);
+        let mut funding = test_competition(now + Duration::hours(6));
+        funding.funding_broadcasted_at = Some(now);
+
+        let competitions = vec![started, signing_closed, cancelled, funding];
+        let available = competitions.iter().map(|c| (c.id, 2)).collect();
+
+        let feed = OpenCompetitionFeed::build(&competitions, &available, "", now);
+
+        assert!(feed.competitions.is_empty());
+    }
+
+    #[test]
+    fn test_full_competitions_excluded() {
+        let now = OffsetDateTime::now_utc();
+        let no_tickets = test_competition(now + Duration::hours(6));
+        let zero_tickets = test_competition(now + Duration::hours(6));
+        let available = HashMap::from([(zero_tickets.id, 0)]);
+
+        let feed = OpenCompetitionFeed::build(&[no_tickets, zero_tickets], &available, "", now);
+
+        assert!(feed.competitions.is_empty());
+    }
+
+    #[test]
+    fn test_atom_escapes_content() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now + Duration::hours(6));
+        competition.event_submission.locations = vec!["A&B".to_string()];
+        let available = HashMap::from([(competition.id, 3)]);
+        let feed = OpenCompetitionFeed::build(&[competition], &available, "https://x.io", now);
+
+        let atom = feed.to_atom("https://x.io");
+
+        assert!(atom.starts_with(r#"<?xml version="1.0" encoding="utf-8"?>"#));
+        assert!(atom.contains("A&amp;B"));
+        assert!(!atom.contains("A&B"));
+        assert_eq!(atom.matches("<entry>").count(), 1);
+    }
+}
@@ -0,0 +1,345 @@
+//! The funding PSBT as each entrant sees it. With escrow enabled every entrant signs the escrow
+//! inputs they fund the contract from. Rather than the whole funding PSBT, with every other
+//! entrant's escrow scripts and the coordinator's wallet data, they get a copy keeping only
+//! their own inputs' data. The coordinator merges the inputs they sign back into its PSBT.
+use bdk_wallet::bitcoin::{
+    psbt::{Input, Output},
+    OutPoint, Psbt, Txid,
+};
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EntrantPsbtError {
+    #[error("escrow outpoint {0} isn't an input of the funding transaction")]
+    MissingInput(OutPoint),
+    #[error("psbt spends funding transaction {0}, expected {1}")]
+    WrongTransaction(Txid, Txid),
+    #[error("escrow input {0} isn't signed")]
+    Unsigned(OutPoint),
+    #[error("psbt changes the funding transaction's inputs")]
+    InputsAltered,
+    #[error("psbt changes the funding transaction's outputs")]
+    OutputsAltered,
+    #[error("psbt signs input {0}, which isn't one of the entry's escrow inputs")]
+    UnexpectedSignature(OutPoint),
+}
+
+fn is_signed(input: &Input) -> bool {
+    !input.partial_sigs.is_empty()
+        || !input.tap_script_sigs.is_empty()
+        || input.tap_key_sig.is_some()
+        || input.final_script_witness.is_some()
+        || input.final_script_sig.is_some()
+}
+
+/// Index of the funding transaction input spending `escrow_outpoint`
+pub fn escrow_input_index(
+    psbt: &Psbt,
+    escrow_outpoint: &OutPoint,
+) -> Result<usize, EntrantPsbtError> {
+    psbt.unsigned_tx
+        .input
+        .iter()
+        .position(|input| input.previous_output == *escrow_outpoint)
+        .ok_or(EntrantPsbtError::MissingInput(*escrow_outpoint))
+}
+
+/// Copy of the funding PSBT for the entrant funding it from `escrow_outpoints`. The unsigned
+/// transaction is kept whole, the signature hash commits to every input and output, but only
+/// the entrant's own inputs keep their utxo and witness script.
+pub fn entrant_funding_psbt(
+    funding: &Psbt,
+    escrow_outpoints: &[OutPoint],
+) -> Result<Psbt, EntrantPsbtError> {
+    let indices = escrow_outpoints
+        .iter()
+        .map(|outpoint| escrow_input_index(funding, outpoint))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+
+    let mut psbt = funding.clone();
+    for (index, input) in psbt.inputs.iter_mut().enumerate() {
+        if !indices.contains(&index) {
+            *input = Input::default();
+        }
+    }
+    for output in psbt.outputs.iter_mut() {
+        *output = Output::default();
+    }
+    psbt.xpub.clear();
+    psbt.proprietary.clear();
+    psbt.unknown.clear();
+    Ok(psbt)
+}
+
+/// Check an entrant's PSBT before it's stored: it has to be the funding transaction unchanged,
+/// sign every one of the entrant's escrow inputs and carry no signature on any other input.
+pub fn check_entrant_psbt(
+    funding: &Psbt,
+    entrant: &Psbt,
+    escrow_outpoints: &[OutPoint],
+) -> Result<(), EntrantPsbtError> {
+    if entrant.unsigned_tx.output != funding.unsigned_tx.output {
+        return Err(EntrantPsbtError::OutputsAltered);
+    }
+    let spends = |psbt: &Psbt| -> Vec<OutPoint> {
+        psbt.unsigned_tx
+            .input
+            .iter()
+            .map(|input| input.previous_output)
+            .collect()
+    };
+    if spends(entrant) != spends(funding) {
+        return Err(EntrantPsbtError::InputsAltered);
+    }
+    let expected = funding.unsigned_tx.compute_txid();
+    let txid = entrant.unsigned_tx.compute_txid();
+    if txid != expected {
+        return Err(EntrantPsbtError::WrongTransaction(txid, expected));
+    }
+
+    let own = escrow_outpoints
+        .iter()
+        .map(|outpoint| escrow_input_index(funding, outpoint))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+    for (index, (input, txin)) in entrant
+        .inputs
+        .iter()
+        .zip(&entrant.unsigned_tx.input)
+        .enumerate()
+    {
+        match (own.contains(&index), is_signed(input)) {
+            (true, false) => return Err(EntrantPsbtError::Unsigned(txin.previous_output)),
+            (false, true) => {
+                return Err(EntrantPsbtError::UnexpectedSignature(txin.previous_output))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Merge the escrow inputs an entrant signed into the coordinator's funding PSBT. Nothing else
+/// is taken from the entrant's PSBT, so a full funding PSBT works as well as a scoped one.
+/// Returns the indices of the inputs merged, `funding` is untouched on error.
+pub fn combine_entrant_psbt(
+    funding: &mut Psbt,
+    entrant: &Psbt,
+    escrow_outpoints: &[OutPoint],
+) -> Result<Vec<usize>, EntrantPsbtError> {
+    let expected = funding.unsigned_tx.compute_txid();
+    let txid = entrant.unsigned_tx.compute_txid();
+    if txid != expected {
+        return Err(EntrantPsbtError::WrongTransaction(txid, expected));
+    }
+
+    let mut signed = Vec::with_capacity(escrow_outpoints.len());
+    for outpoint in escrow_outpoints {
+        let index = escrow_input_index(funding, outpoint)?;
+        if !entrant.inputs.get(index).is_some_and(is_signed) {
+            return Err(EntrantPsbtError::Unsigned(*outpoint));
+        }
+        signed.push(index);
+    }
+
+    for index in &signed {
+        funding.inputs[*index].combine(entrant.inputs[*index].clone());
+    }
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::bitcoin::{
+        absolute::LockTime,
+        ecdsa,
+        hashes::Hash,
+        opcodes::all::OP_CHECKSIG,
+        script::Builder,
+        secp256k1::{Message, Secp256k1, SecretKey},
+        sighash::{EcdsaSighashType, SighashCache},
+        transaction::Version,
+        Amount, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    };
+
+    fn secret(i: u8) -> SecretKey {
+        SecretKey::from_slice(&[i + 1; 32]).unwrap()
+    }
+
+    fn witness_script(i: u8) -> ScriptBuf {
+        let pubkey = PublicKey::new(secret(i).public_key(&Secp256k1::new()));
+        Builder::new()
+            .push_key(&pubkey)
+            .push_opcode(OP_CHECKSIG)
+            .into_script()
+    }
+
+    /// Funding PSBT spending one escrow output per entrant into the contract
+    fn funding_psbt(entrants: u8) -> (Psbt, Vec<OutPoint>) {
+        let outpoints: Vec<OutPoint> = (0..entrants)
+            .map(|i| OutPoint {
+                txid: Txid::from_byte_array([i + 1; 32]),
+                vout: i as u32,
+            })
+            .collect();
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: outpoints
+                .iter()
+                .map(|outpoint| TxIn {
+                    previous_output: *outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(9_000 * entrants as u64),
+                script_pubkey: ScriptBuf::new_p2wsh(&witness_script(100).wscript_hash()),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for (i, input) in psbt.inputs.iter_mut().enumerate() {
+            let script = witness_script(i as u8);
+            input.witness_utxo = Some(TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::new_p2wsh(&script.wscript_hash()),
+            });
+            input.witness_script = Some(script);
+        }
+        (psbt, outpoints)
+    }
+
+    fn sign_input(psbt: &mut Psbt, index: usize) {
+        let secp = Secp256k1::new();
+        let secret = secret(index as u8);
+        let input = &psbt.inputs[index];
+        let sighash = SighashCache::new(&psbt.unsigned_tx)
+            .p2wsh_signature_hash(
+                index,
+                input.witness_script.as_ref().unwrap(),
+                input.witness_utxo.as_ref().unwrap().value,
+                EcdsaSighashType::All,
+            )
+            .unwrap();
+        let signature = secp.sign_ecdsa(&Message::from_digest(sighash.to_byte_array()), &secret);
+        psbt.inputs[index].partial_sigs.insert(
+            PublicKey::new(secret.public_key(&secp)),
+            ecdsa::Signature {
+                signature,
+                sighash_type: EcdsaSighashType::All,
+            },
+        );
+    }
+
+    #[test]
+    fn test_entrant_psbts_combine_like_the_full_psbt() {
+        let (master, outpoints) = funding_psbt(3);
+
+        // Every entrant signing the full funding PSBT
+        let mut monolithic = master.clone();
+        for index in 0..outpoints.len() {
+            let mut signed = master.clone();
+            sign_input(&mut signed, index);
+            monolithic.combine(signed).unwrap();
+        }
+
+        // Every entrant signing only their scoped copy
+        let mut combined = master.clone();
+        for (index, outpoint) in outpoints.iter().enumerate() {
+            let mut entrant = entrant_funding_psbt(&master, &[*outpoint]).unwrap();
+            assert_eq!(entrant.unsigned_tx, master.unsigned_tx);
+            for (other, input) in entrant.inputs.iter().enumerate() {
+                assert_eq!(input.witness_script.is_some(), other == index);
+            }
+            sign_input(&mut entrant, index);
+            assert_eq!(
+                combine_entrant_psbt(&mut combined, &entrant, &[*outpoint]).unwrap(),
+                vec![index]
+            );
+        }
+
+        assert_eq!(combined, monolithic);
+        assert!(combined
+            .inputs
+            .iter()
+            .all(|input| input.partial_sigs.len() == 1));
+    }
+
+    #[test]
+    fn test_combine_rejects_unsigned_or_foreign_psbts() {
+        let (master, outpoints) = funding_psbt(2);
+        let mut funding = master.clone();
+
+        // Signing someone else's input doesn't count for your own
+        let mut entrant = master.clone();
+        sign_input(&mut entrant, 1);
+        assert_eq!(
+            combine_entrant_psbt(&mut funding, &entrant, &outpoints[..1]),
+            Err(EntrantPsbtError::Unsigned(outpoints[0]))
+        );
+
+        let (other, other_outpoints) = funding_psbt(3);
+        assert!(matches!(
+            combine_entrant_psbt(&mut funding, &other, &other_outpoints[..1]),
+            Err(EntrantPsbtError::WrongTransaction(..))
+        ));
+        assert_eq!(
+            entrant_funding_psbt(&master, &other_outpoints[2..]),
+            Err(EntrantPsbtError::MissingInput(other_outpoints[2]))
+        );
+        assert_eq!(funding, master);
+    }
+
+    #[test]
+    fn test_check_rejects_altered_or_over_signed_psbts() {
+        let (master, outpoints) = funding_psbt(3);
+
+        let mut entrant = entrant_funding_psbt(&master, &outpoints[1..2]).unwrap();
+        assert_eq!(
+            check_entrant_psbt(&master, &entrant, &outpoints[1..2]),
+            Err(EntrantPsbtError::Unsigned(outpoints[1]))
+        );
+        sign_input(&mut entrant, 1);
+        assert_eq!(
+            check_entrant_psbt(&master, &entrant, &outpoints[1..2]),
+            Ok(())
+        );
+
+        // A full funding PSBT is fine as long as only the entrant's input is signed
+        let mut full = master.clone();
+        sign_input(&mut full, 1);
+        assert_eq!(check_entrant_psbt(&master, &full, &outpoints[1..2]), Ok(()));
+        sign_input(&mut full, 2);
+        assert_eq!(
+            check_entrant_psbt(&master, &full, &outpoints[1..2]),
+            Err(EntrantPsbtError::UnexpectedSignature(outpoints[2]))
+        );
+
+        let mut redirected = entrant.clone();
+        redirected.unsigned_tx.output[0].value = Amount::from_sat(1);
+        assert_eq!(
+            check_entrant_psbt(&master, &redirected, &outpoints[1..2]),
+            Err(EntrantPsbtError::OutputsAltered)
+        );
+
+        let mut swapped = entrant.clone();
+        swapped.unsigned_tx.input[0].previous_output = OutPoint {
+            txid: Txid::from_byte_array([9; 32]),
+            vout: 0,
+        };
+        assert_eq!(
+            check_entrant_psbt(&master, &swapped, &outpoints[1..2]),
+            Err(EntrantPsbtError::InputsAltered)
+        );
+
+        let mut relocked = entrant.clone();
+        relocked.unsigned_tx.lock_time = LockTime::from_consensus(1);
+        assert!(matches!(
+            check_entrant_psbt(&master, &relocked, &outpoints[1..2]),
+            Err(EntrantPsbtError::WrongTransaction(..))
+        ));
+    }
+}
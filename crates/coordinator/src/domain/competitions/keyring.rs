@@ -0,0 +1,272 @@
+//! Versioned coordinator keys. Every competition signs with the key it was created under for its
+//! whole life, so adding a key only moves new competitions over while the ones in flight keep
+//! their MuSig material.
+use dlctix::secp::{Point, Scalar};
+use nostr_sdk::{
+    nips::nip44,
+    prelude::{Keys, SecretKey},
+};
+use std::fmt;
+
+use crate::{
+    domain::Error,
+    infra::{
+        keymeld::StoredDlcKeygenSession,
+        secrets::{load_key, secret_backend},
+    },
+    SigningKeySettings,
+};
+
+/// Id of the wallet seed key, also used for competitions created before keys had ids
+pub const DEFAULT_KEY_ID: &str = "default";
+
+#[derive(thiserror::Error, Debug)]
+pub enum KeyringError {
+    #[error("unknown coordinator key id {0}")]
+    UnknownKey(String),
+    #[error("coordinator key id {0} is configured more than once")]
+    DuplicateKey(String),
+    #[error("NIP-44 {0}")]
+    Encryption(String),
+}
+
+/// The coordinator's keys in the order they were added, the last one is used for new competitions
+#[derive(Clone)]
+pub struct CoordinatorKeyring {
+    keys: Vec<(String, Scalar)>,
+}
+
+impl fmt::Debug for CoordinatorKeyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoordinatorKeyring")
+            .field("ids", &self.ids())
+            .finish()
+    }
+}
+
+impl CoordinatorKeyring {
+    pub fn new(default_key: Scalar) -> Self {
+        Self {
+            keys: vec![(DEFAULT_KEY_ID.to_string(), default_key)],
+        }
+    }
+
+    /// The wallet seed key plus every configured signing key, loaded through their secrets backend
+    pub fn load(
+        default_key: Scalar,
+        signing_keys: &[SigningKeySettings],
+    ) -> Result<Self, anyhow::Error> {
+        let mut keyring = Self::new(default_key);
+        for signing_key in signing_keys {
+            let backend = secret_backend(&signing_key.secrets, &signing_key.key_file);
+            let secret_key = load_key::<dlctix::musig2::secp256k1::SecretKey>(backend.as_ref())?;
+            let key = Scalar::from_slice(&secret_key.secret_bytes())
+                .map_err(|e| anyhow::anyhow!("coordinator key {}: {}", signing_key.id, e))?;
+            keyring = keyring.with_key(&signing_key.id, key)?;
+        }
+        Ok(keyring)
+    }
+
+    pub fn with_key(mut self, id: &str, key: Scalar) -> Result<Self, KeyringError> {
+        if self.keys.iter().any(|(existing, _)| existing == id) {
+            return Err(KeyringError::DuplicateKey(id.to_string()));
+        }
+        self.keys.push((id.to_string(), key));
+        Ok(self)
+    }
+
+    pub fn newest_id(&self) -> &str {
+        &self.newest_entry().0
+    }
+
+    pub fn newest(&self) -> Scalar {
+        self.newest_entry().1
+    }
+
+    fn newest_entry(&self) -> &(String, Scalar) {
+        self.keys
+            .last()
+            .expect("keyring always holds the default key")
+    }
+
+    /// The key for an id, `None` is the default key
+    pub fn get(&self, id: Option<&str>) -> Result<Scalar, KeyringError> {
+        let id = id.unwrap_or(DEFAULT_KEY_ID);
+        self.keys
+            .iter()
+            .find(|(existing, _)| existing == id)
+            .map(|(_, key)| *key)
+            .ok_or_else(|| KeyringError::UnknownKey(id.to_string()))
+    }
+
+    pub fn public_key(&self, id: Option<&str>) -> Result<Point, KeyringError> {
+        Ok(self.get(id)?.base_point_mul())
+    }
+
+    pub fn ids(&self) -> Vec<&str> {
+        self.keys.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    /// NIP-44 encrypt to the newest key's own nostr pubkey, returning the key id used
+    pub fn encrypt_to_self(&self, plaintext: &str) -> Result<(String, String), KeyringError> {
+        let keys = nostr_keys(self.newest())?;
+        let encrypted = nip44::encrypt(
+            keys.secret_key(),
+            &keys.public_key(),
+            plaintext,
+            nip44::Version::V2,
+        )
+        .map_err(|e| KeyringError::Encryption(format!("encryption failed: {}", e)))?;
+        Ok((self.newest_id().to_string(), encrypted))
+    }
+
+    /// Decrypt what `encrypt_to_self` encrypted under the key `id`
+    pub fn decrypt_from_self(
+        &self,
+        id: Option<&str>,
+        encrypted: &str,
+    ) -> Result<String, KeyringError> {
+        let keys = nostr_keys(self.get(id)?)?;
+        nip44::decrypt(keys.secret_key(), &keys.public_key(), encrypted)
+            .map_err(|e| KeyringError::Encryption(format!("decryption failed: {}", e)))
+    }
+}
+
+/// Decrypt a stored keymeld session secret with the key it was encrypted to
+pub fn decrypt_session_secret(
+    keys: &CoordinatorKeyring,
+    stored_session: &StoredDlcKeygenSession,
+) -> Result<[u8; 32], Error> {
+    let decrypted_hex = keys.decrypt_from_self(
+        stored_session.encryption_key_id.as_deref(),
+        &stored_session.encrypted_session_secret,
+    )?;
+
+    let secret_bytes = hex::decode(&decrypted_hex)
+        .map_err(|e| Error::BadRequest(format!("Invalid session secret hex: {}", e)))?;
+
+    secret_bytes
+        .try_into()
+        .map_err(|_| Error::BadRequest("Session secret must be 32 bytes".to_string()))
+}
+
+/// The stored session with its secret encrypted to the newest key instead, `None` when it
+/// already is
+pub fn reencrypt_session_secret(
+    keys: &CoordinatorKeyring,
+    stored_session: &StoredDlcKeygenSession,
+) -> Result<Option<StoredDlcKeygenSession>, Error> {
+    if stored_session
+        .encryption_key_id
+        .as_deref()
+        .unwrap_or(DEFAULT_KEY_ID)
+        == keys.newest_id()
+    {
+        return Ok(None);
+    }
+    let session_secret = decrypt_session_secret(keys, stored_session)?;
+    let (key_id, encrypted_session_secret) = keys.encrypt_to_self(&hex::encode(session_secret))?;
+    Ok(Some(StoredDlcKeygenSession {
+        encrypted_session_secret,
+        encryption_key_id: Some(key_id),
+        ..stored_session.clone()
+    }))
+}
+
+fn nostr_keys(key: Scalar) -> Result<Keys, KeyringError> {
+    let secret_key = SecretKey::from_slice(&key.serialize())
+        .map_err(|e| KeyringError::Encryption(format!("invalid secret key: {}", e)))?;
+    Ok(Keys::new(secret_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> Scalar {
+        Scalar::from_slice(&[byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_newest_key_is_the_last_added() {
+        let keyring = CoordinatorKeyring::new(key(1));
+        assert_eq!(keyring.newest_id(), DEFAULT_KEY_ID);
+
+        let keyring = keyring.with_key("2026-10", key(2)).unwrap();
+        assert_eq!(keyring.newest_id(), "2026-10");
+        assert_eq!(keyring.newest(), key(2));
+        assert_eq!(keyring.get(None).unwrap(), key(1));
+        assert_eq!(keyring.get(Some("2026-10")).unwrap(), key(2));
+        assert!(matches!(
+            keyring.get(Some("2027-01")),
+            Err(KeyringError::UnknownKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_key_ids_are_rejected() {
+        let keyring = CoordinatorKeyring::new(key(1));
+        assert!(matches!(
+            keyring.with_key(DEFAULT_KEY_ID, key(2)),
+            Err(KeyringError::DuplicateKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_secrets_decrypt_under_the_key_they_were_encrypted_to() {
+        let old = CoordinatorKeyring::new(key(1));
+        let (old_id, encrypted) = old.encrypt_to_self("session secret").unwrap();
+
+        let rotated = old.with_key("next", key(2)).unwrap();
+        assert_eq!(
+            rotated
+                .decrypt_from_self(Some(&old_id), &encrypted)
+                .unwrap(),
+            "session secret"
+        );
+        assert!(rotated.decrypt_from_self(Some("next"), &encrypted).is_err());
+
+        let (new_id, reencrypted) = rotated.encrypt_to_self("session secret").unwrap();
+        assert_eq!(new_id, "next");
+        assert_eq!(
+            rotated
+                .decrypt_from_self(Some(&new_id), &reencrypted)
+                .unwrap(),
+            "session secret"
+        );
+    }
+
+    #[test]
+    fn test_session_secret_moves_to_the_newest_key() {
+        let old = CoordinatorKeyring::new(key(1));
+        let (_, encrypted_session_secret) = old.encrypt_to_self(&hex::encode([9u8; 32])).unwrap();
+        let stored = StoredDlcKeygenSession {
+            session_id: uuid::Uuid::now_v7().to_string(),
+            encrypted_session_secret,
+            encryption_key_id: None,
+            aggregate_key: vec![2; 33],
+            outcome_subset_ids: Default::default(),
+        };
+
+        let rotated = old.with_key("next", key(2)).unwrap();
+        let moved = reencrypt_session_secret(&rotated, &stored)
+            .unwrap()
+            .expect("session on the old key should be re-encrypted");
+        assert_eq!(moved.encryption_key_id.as_deref(), Some("next"));
+        assert_eq!(moved.session_id, stored.session_id);
+        assert_eq!(decrypt_session_secret(&rotated, &moved).unwrap(), [9u8; 32]);
+        assert!(reencrypt_session_secret(&rotated, &moved)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_debug_leaves_out_key_material() {
+        let keyring = CoordinatorKeyring::new(key(1))
+            .with_key("next", key(2))
+            .unwrap();
+        let debug = format!("{:?}", keyring);
+        assert!(debug.contains("next"));
+        assert!(!debug.contains(&hex::encode([2u8; 32])));
+    }
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// A movement of sats the coordinator is party to. Each kind is either revenue, a cost or a
+/// transfer between the coordinator's own outputs, which is reported but doesn't change the net.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerEntryKind {
+    /// A ticket's hold invoice was settled
+    InvoiceSettled,
+    /// An escrow output, funded from the coordinator's wallet, was spent by the funding tx
+    EscrowInputConsumed,
+    /// The contract's funding output was created
+    FundingOutputCreated,
+    /// Miner fee paid by a transaction the coordinator funded
+    MinerFee,
+    /// A winner was paid over lightning
+    PayoutSent,
+    /// Lightning routing fee paid on a payout
+    RoutingFee,
+    /// A contract output swept back to the coordinator's wallet
+    ContractSweep,
+    /// Sats a sponsor added to the pool, received on chain or over lightning
+    SponsorContribution,
+}
+
+impl LedgerEntryKind {
+    pub const ALL: [LedgerEntryKind; 8] = [
+        LedgerEntryKind::InvoiceSettled,
+        LedgerEntryKind::EscrowInputConsumed,
+        LedgerEntryKind::FundingOutputCreated,
+        LedgerEntryKind::MinerFee,
+        LedgerEntryKind::PayoutSent,
+        LedgerEntryKind::RoutingFee,
+        LedgerEntryKind::ContractSweep,
+        LedgerEntryKind::SponsorContribution,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerEntryKind::InvoiceSettled => "invoice_settled",
+            LedgerEntryKind::EscrowInputConsumed => "escrow_input_consumed",
+            LedgerEntryKind::FundingOutputCreated => "funding_output_created",
+            LedgerEntryKind::MinerFee => "miner_fee",
+            LedgerEntryKind::PayoutSent => "payout_sent",
+            LedgerEntryKind::RoutingFee => "routing_fee",
+            LedgerEntryKind::ContractSweep => "contract_sweep",
+            LedgerEntryKind::SponsorContribution => "sponsor_contribution",
+        }
+    }
+
+    pub fn is_revenue(&self) -> bool {
+        matches!(
+            self,
+            LedgerEntryKind::InvoiceSettled
+                | LedgerEntryKind::ContractSweep
+                | LedgerEntryKind::SponsorContribution
+        )
+    }
+
+    pub fn is_cost(&self) -> bool {
+        matches!(
+            self,
+            LedgerEntryKind::FundingOutputCreated
+                | LedgerEntryKind::MinerFee
+                | LedgerEntryKind::PayoutSent
+                | LedgerEntryKind::RoutingFee
+        )
+    }
+}
+
+impl fmt::Display for LedgerEntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LedgerEntryKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LedgerEntryKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown ledger entry kind: {}", s))
+    }
+}
+
+/// One money movement. `reference` identifies what moved (ticket, outpoint, txid or payout) so
+/// recording the same movement twice keeps a single row.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub competition_id: Uuid,
+    pub entry_id: Option<Uuid>,
+    pub kind: LedgerEntryKind,
+    pub amount_sats: u64,
+    pub reference: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub recorded_at: OffsetDateTime,
+}
+
+impl LedgerEntry {
+    pub fn new(
+        competition_id: Uuid,
+        entry_id: Option<Uuid>,
+        kind: LedgerEntryKind,
+        amount_sats: u64,
+        reference: impl Into<String>,
+    ) -> Self {
+        Self {
+            competition_id,
+            entry_id,
+            kind,
+            amount_sats,
+            reference: reference.into(),
+            recorded_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+/// Revenue, costs and net for a competition (or all of them) built from the ledger
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompetitionPnl {
+    /// None for the aggregate across competitions
+    pub competition_id: Option<Uuid>,
+    pub by_kind: BTreeMap<LedgerEntryKind, u64>,
+    pub revenue_sats: u64,
+    pub costs_sats: u64,
+    pub transfers_sats: u64,
+    pub net_sats: i64,
+}
+
+impl CompetitionPnl {
+    pub fn from_totals(
+        competition_id: Option<Uuid>,
+        by_kind: BTreeMap<LedgerEntryKind, u64>,
+    ) -> Self {
+        let sum = |filter: fn(&LedgerEntryKind) -> bool| -> u64 {
+            by_kind
+                .iter()
+                .filter(|(kind, _)| filter(kind))
+                .map(|(_, amount)| *amount)
+                .fold(0, u64::saturating_add)
+        };
+        let revenue_sats = sum(LedgerEntryKind::is_revenue);
+        let costs_sats = sum(LedgerEntryKind::is_cost);
+        let transfers_sats = sum(|kind| !kind.is_revenue() && !kind.is_cost());
+
+        Self {
+            competition_id,
+            net_sats: revenue_sats as i64 - costs_sats as i64,
+            by_kind,
+            revenue_sats,
+            costs_sats,
+            transfers_sats,
+        }
+    }
+
+    pub fn amount(&self, kind: LedgerEntryKind) -> u64 {
+        self.by_kind.get(&kind).copied().unwrap_or(0)
+    }
+}
+
+/// Profit and loss of every competition with ledger entries, plus their aggregate
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PnlReport {
+    pub competitions: Vec<CompetitionPnl>,
+    pub total: CompetitionPnl,
+}
+
+impl PnlReport {
+    pub fn new(competitions: Vec<CompetitionPnl>) -> Self {
+        let mut by_kind = BTreeMap::new();
+        for pnl in &competitions {
+            for (kind, amount) in &pnl.by_kind {
+                let total: &mut u64 = by_kind.entry(*kind).or_default();
+                *total = total.saturating_add(*amount);
+            }
+        }
+
+        Self {
+            total: CompetitionPnl::from_totals(None, by_kind),
+            competitions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pnl_separates_revenue_costs_and_transfers() {
+        let competition_id = Uuid::now_v7();
+        let pnl = CompetitionPnl::from_totals(
+            Some(competition_id),
+            BTreeMap::from([
+                (LedgerEntryKind::InvoiceSettled, 3_300),
+                (LedgerEntryKind::EscrowInputConsumed, 3_000),
+                (LedgerEntryKind::FundingOutputCreated, 3_000),
+                (LedgerEntryKind::MinerFee, 200),
+                (LedgerEntryKind::PayoutSent, 2_900),
+                (LedgerEntryKind::RoutingFee, 5),
+                (LedgerEntryKind::ContractSweep, 2_850),
+            ]),
+        );
+
+        assert_eq!(pnl.revenue_sats, 6_150);
+        assert_eq!(pnl.costs_sats, 6_105);
+        assert_eq!(pnl.transfers_sats, 3_000);
+        assert_eq!(pnl.net_sats, 45);
+
+        let report = PnlReport::new(vec![pnl.clone(), pnl]);
+        assert_eq!(report.total.competition_id, None);
+        assert_eq!(report.total.net_sats, 90);
+        assert_eq!(report.total.amount(LedgerEntryKind::MinerFee), 400);
+
+        for kind in LedgerEntryKind::ALL {
+            assert_eq!(kind.as_str().parse::<LedgerEntryKind>().unwrap(), kind);
+        }
+    }
+}
@@ -1,28 +1,73 @@
+mod alerts;
+mod backup;
+mod broadcast_check;
+mod cloning;
+mod consistency;
+mod contract_size;
 mod coordinator;
+mod disclosure;
+mod feed;
+mod funding_psbt;
+mod keyring;
+mod ledger;
+mod operations;
+mod payouts;
+mod player_order;
+mod receipts;
+mod signature_checks;
+mod signature_chunks;
+mod signing_sessions;
+mod solvency;
+mod spend_monitor;
+mod sponsorship;
 pub mod states;
 mod store;
+mod timeline;
 use crate::infra::{
-    db::{
-        parse_optional_blob_json, parse_optional_datetime, parse_optional_sqlite_datetime,
-        parse_required_blob_json, parse_required_datetime,
-    },
+    bitcoin::TxChainStatus,
+    db::{parse_optional_blob_json, parse_optional_count, parse_required_blob_json},
+    db_timestamps::{parse_optional_datetime, parse_required_datetime},
     oracle::{AddEventEntry, WeatherChoices},
 };
+pub use alerts::*;
 use anyhow::anyhow;
+pub use backup::*;
+pub use broadcast_check::*;
+pub use cloning::*;
+pub use consistency::*;
+pub use contract_size::*;
 pub use coordinator::*;
+use coordinator_core::{Sats, SignedReceipt};
+pub use disclosure::*;
 use dlctix::{
-    bitcoin::{hex::DisplayHex, OutPoint, Transaction},
+    bitcoin::{hex::DisplayHex, FeeRate, OutPoint, Transaction},
     hashlock,
     musig2::{AggNonce, PartialSignature, PubNonce},
     secp::MaybeScalar,
     ContractParameters, EventLockingConditions, Outcome, SigMap, SignedContract,
 };
+pub use feed::*;
+pub use funding_psbt::*;
+pub use keyring::*;
+pub use ledger::*;
+use lightning_invoice::Bolt11Invoice;
 use log::{debug, error};
+pub use operations::*;
+pub use payouts::*;
+pub use player_order::*;
+pub use receipts::*;
 use serde::{Deserialize, Serialize};
+pub use signature_checks::*;
+pub use signature_chunks::*;
+pub use signing_sessions::*;
+pub use solvency::*;
+pub use spend_monitor::*;
+pub use sponsorship::*;
 use sqlx::{sqlite::SqliteRow, FromRow, Row};
-use std::fmt;
+use std::{collections::HashMap, fmt, str::FromStr};
 pub use store::*;
-use time::{Duration, OffsetDateTime};
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+pub use timeline::*;
 use uuid::Uuid;
 
 use super::Error;
@@ -72,7 +117,7 @@ pub struct EntryPayout {
     // User provide invoice to pay out user via lightning
     pub payout_payment_request: String,
     //  Amount paid out to user in sats via lightning
-    pub payout_amount_sats: u64,
+    pub payout_amount_sats: Sats,
     #[serde(with = "time::serde::rfc3339")]
     /// Time at which the payout initiated to the user
     pub initiated_at: OffsetDateTime,
@@ -114,7 +159,9 @@ impl FromRow<'_, SqliteRow> for EntryPayout {
             })?,
             payout_status,
             payout_payment_request: row.try_get("payout_payment_request")?,
-            payout_amount_sats: row.try_get("payout_amount_sats").unwrap_or(0) as u64,
+            payout_amount_sats: Sats(
+                row.try_get::<i64, _>("payout_amount_sats").unwrap_or(0) as u64
+            ),
             initiated_at: parse_required_datetime(row, "initiated_at")?,
             succeed_at,
             failed_at,
@@ -187,52 +234,93 @@ pub struct UserEntry {
     #[serde(with = "time::serde::rfc3339::option")]
     pub paid_out_at: Option<OffsetDateTime>,
     pub payout_ln_invoice: Option<String>,
+    /// Index of this entry's player in the DLC contract, assigned once when the contract is built
+    pub player_index: Option<usize>,
+    /// When the backup DM with the encrypted private key and preimage was accepted by a relay
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub backup_dm_sent_at: Option<OffsetDateTime>,
+    /// Why the backup DM could not be sent, the user needs to save their own backup
+    #[serde(default)]
+    pub backup_dm_error: Option<String>,
+    /// Coordinator signed proof the entry was accepted
+    #[serde(default)]
+    pub receipt: Option<SignedReceipt>,
+    /// When the entry was dropped from its competition
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub cancelled_at: Option<OffsetDateTime>,
+    /// Why the entry was dropped, shown to the user
+    #[serde(default)]
+    pub cancelled_reason: Option<String>,
 }
 
 impl FromRow<'_, SqliteRow> for UserEntry {
     fn from_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
         Ok(UserEntry {
-            id: Uuid::parse_str(&row.get::<String, _>("id")).map_err(|e| {
+            id: Uuid::parse_str(&row.try_get::<String, _>("id")?).map_err(|e| {
                 sqlx::Error::ColumnDecode {
                     index: "id".to_string(),
                     source: Box::new(e),
                 }
             })?,
-            event_id: Uuid::parse_str(&row.get::<String, _>("event_id")).map_err(|e| {
+            event_id: Uuid::parse_str(&row.try_get::<String, _>("event_id")?).map_err(|e| {
                 sqlx::Error::ColumnDecode {
                     index: "event_id".to_string(),
                     source: Box::new(e),
                 }
             })?,
-            ticket_id: Uuid::parse_str(&row.get::<String, _>("ticket_id")).map_err(|e| {
+            ticket_id: Uuid::parse_str(&row.try_get::<String, _>("ticket_id")?).map_err(|e| {
                 sqlx::Error::ColumnDecode {
                     index: "ticket_id".to_string(),
                     source: Box::new(e),
                 }
             })?,
-            pubkey: row.get("pubkey"),
-            ephemeral_pubkey: row.get("ephemeral_pubkey"),
-            ephemeral_privatekey_encrypted: row.get("ephemeral_privatekey_encrypted"),
-            payout_hash: row.get("payout_hash"),
-            payout_preimage_encrypted: row.get("payout_preimage_encrypted"),
+            pubkey: row.try_get("pubkey")?,
+            ephemeral_pubkey: row.try_get("ephemeral_pubkey")?,
+            ephemeral_privatekey_encrypted: row.try_get("ephemeral_privatekey_encrypted")?,
+            payout_hash: row.try_get("payout_hash")?,
+            payout_preimage_encrypted: row.try_get("payout_preimage_encrypted")?,
             entry_submission: parse_required_blob_json(row, "entry_submission")?,
-            ephemeral_privatekey: row.get("ephemeral_privatekey"),
-            payout_preimage: row.get("payout_preimage"),
-            encrypted_keymeld_private_key: row.get("encrypted_keymeld_private_key"),
-            keymeld_auth_pubkey: row.get("keymeld_auth_pubkey"),
+            ephemeral_privatekey: row.try_get("ephemeral_privatekey")?,
+            payout_preimage: row.try_get("payout_preimage")?,
+            encrypted_keymeld_private_key: row.try_get("encrypted_keymeld_private_key")?,
+            keymeld_auth_pubkey: row.try_get("keymeld_auth_pubkey")?,
             public_nonces: parse_optional_blob_json(row, "public_nonces")?,
             funding_psbt_base64: row.get("funding_psbt_base64"),
             partial_signatures: parse_optional_blob_json(row, "partial_signatures")?,
-            signed_at: parse_optional_sqlite_datetime(row, "signed_at")?,
-            paid_at: parse_optional_sqlite_datetime(row, "paid_at")?,
+            signed_at: parse_optional_datetime(row, "signed_at")?,
+            paid_at: parse_optional_datetime(row, "paid_at")?,
             sellback_broadcasted_at: parse_optional_datetime(row, "sellback_broadcasted_at")?,
             reclaimed_broadcasted_at: parse_optional_datetime(row, "reclaimed_broadcasted_at")?,
             paid_out_at: parse_optional_datetime(row, "paid_out_at")?,
-            payout_ln_invoice: row.get("payout_ln_invoice"),
+            payout_ln_invoice: row.try_get("payout_ln_invoice")?,
+            player_index: row
+                .try_get::<Option<i64>, _>("player_index")?
+                .map(|index| index as usize),
+            backup_dm_sent_at: parse_optional_datetime(row, "backup_dm_sent_at")?,
+            backup_dm_error: row.try_get("backup_dm_error")?,
+            receipt: parse_optional_blob_json(row, "entry_receipt")?,
+            cancelled_at: parse_optional_datetime(row, "cancelled_at")?,
+            cancelled_reason: row.try_get("cancelled_reason")?,
         })
     }
 }
 
+impl UserEntry {
+    /// Decode each JSON blob column of an entries row on its own, so a broken column is
+    /// reported even when an earlier one is broken too
+    pub fn blob_decode_errors(row: &SqliteRow) -> Vec<sqlx::Error> {
+        [
+            parse_required_blob_json::<AddEventEntry>(row, "entry_submission").err(),
+            parse_optional_blob_json::<SigMap<PubNonce>>(row, "public_nonces").err(),
+            parse_optional_blob_json::<SigMap<PartialSignature>>(row, "partial_signatures").err(),
+            parse_optional_blob_json::<SignedReceipt>(row, "entry_receipt").err(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
 /// Lightweight view of a user's entry joined with competition dates.
 /// Used for the entries list page to avoid fetching full Competition objects.
 #[derive(Debug, Clone)]
@@ -242,6 +330,61 @@ pub struct UserEntryView {
     pub start_time: String,
     pub end_time: String,
     pub status: String,
+    pub payout_method: PayoutMethod,
+    /// Txid of the on-chain close or reclaim, or the payment hash of the lightning payout
+    pub payout_reference: Option<String>,
+    pub payout_amount_sats: Option<u64>,
+    /// Why the entry was dropped from its competition
+    pub cancelled_reason: Option<String>,
+}
+
+/// How a winning entry's share left the contract, as far as the coordinator knows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutMethod {
+    /// Paid over lightning, the coordinator closes the split output afterwards
+    LightningPaid,
+    /// The entry's split output was closed on chain without a lightning payout
+    OnChainSellback,
+    /// The winner never claimed, so the coordinator reclaimed their split output
+    OnChainReclaim,
+    Unpaid,
+}
+
+impl PayoutMethod {
+    /// A lightning payout is what the user actually received, so it wins over the on-chain
+    /// close that follows it
+    pub fn from_timestamps(
+        paid_out_at: Option<OffsetDateTime>,
+        sellback_broadcasted_at: Option<OffsetDateTime>,
+        reclaimed_broadcasted_at: Option<OffsetDateTime>,
+    ) -> Self {
+        if paid_out_at.is_some() {
+            PayoutMethod::LightningPaid
+        } else if sellback_broadcasted_at.is_some() {
+            PayoutMethod::OnChainSellback
+        } else if reclaimed_broadcasted_at.is_some() {
+            PayoutMethod::OnChainReclaim
+        } else {
+            PayoutMethod::Unpaid
+        }
+    }
+
+    pub fn is_on_chain(&self) -> bool {
+        matches!(
+            self,
+            PayoutMethod::OnChainSellback | PayoutMethod::OnChainReclaim
+        )
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PayoutMethod::LightningPaid => "Lightning",
+            PayoutMethod::OnChainSellback => "On-chain close",
+            PayoutMethod::OnChainReclaim => "On-chain reclaim",
+            PayoutMethod::Unpaid => "Unpaid",
+        }
+    }
 }
 
 impl FromRow<'_, SqliteRow> for UserEntryView {
@@ -249,8 +392,11 @@ impl FromRow<'_, SqliteRow> for UserEntryView {
         let signed_at: Option<String> = row.get("signed_at");
         let paid_at: Option<String> = row.get("paid_at");
         let paid_out_at: Option<String> = row.get("paid_out_at");
+        let cancelled_at: Option<String> = row.get("cancelled_at");
 
-        let status = if paid_out_at.is_some() {
+        let status = if cancelled_at.is_some() {
+            "Cancelled"
+        } else if paid_out_at.is_some() {
             "Paid Out"
         } else if paid_at.is_some() {
             "Entry Paid"
@@ -261,6 +407,25 @@ impl FromRow<'_, SqliteRow> for UserEntryView {
         }
         .to_string();
 
+        let payout_method = PayoutMethod::from_timestamps(
+            parse_optional_datetime(row, "paid_out_at")?,
+            parse_optional_datetime(row, "sellback_broadcasted_at")?,
+            parse_optional_datetime(row, "reclaimed_broadcasted_at")?,
+        );
+        let (payout_reference, payout_amount_sats) = match payout_method {
+            PayoutMethod::LightningPaid => (
+                row.try_get::<Option<String>, _>("payout_payment_request")?
+                    .and_then(|request| Bolt11Invoice::from_str(&request).ok())
+                    .map(|invoice| invoice.payment_hash().to_string()),
+                row.try_get::<Option<i64>, _>("payout_amount_sats")?,
+            ),
+            PayoutMethod::OnChainSellback | PayoutMethod::OnChainReclaim => (
+                row.try_get::<Option<String>, _>("sweep_txid")?,
+                row.try_get::<Option<i64>, _>("sweep_amount_sats")?,
+            ),
+            PayoutMethod::Unpaid => (None, None),
+        };
+
         Ok(UserEntryView {
             entry_id: row.get("entry_id"),
             competition_id: row.get("competition_id"),
@@ -269,6 +434,10 @@ impl FromRow<'_, SqliteRow> for UserEntryView {
                 .unwrap_or_default(),
             end_time: row.get::<Option<String>, _>("end_time").unwrap_or_default(),
             status,
+            payout_method,
+            payout_reference,
+            payout_amount_sats: payout_amount_sats.map(|amount| amount as u64),
+            cancelled_reason: row.try_get("cancelled_reason")?,
         })
     }
 }
@@ -299,6 +468,12 @@ impl AddEntry {
             reclaimed_broadcasted_at: None,
             paid_out_at: None,
             payout_ln_invoice: None,
+            player_index: None,
+            backup_dm_sent_at: None,
+            backup_dm_error: None,
+            receipt: None,
+            cancelled_at: None,
+            cancelled_reason: None,
         }
     }
 }
@@ -327,6 +502,35 @@ pub struct PayoutInfo {
     pub ln_invoice: String,
 }
 
+/// Sent by a player whose entry never signed, so the escrow output of their ticket can be
+/// returned once the competition is cancelled. Every escrow branch needs the player's ephemeral
+/// key, the competition is over so handing it to the coordinator risks nothing else.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowReclaimInfo {
+    pub ticket_id: Uuid,
+    pub ephemeral_private_key: String,
+    /// Bitcoin address the escrow output is sent to
+    pub address: String,
+}
+
+/// Fresh keymeld registration data for an entry whose previous registration was removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeymeldRegistration {
+    /// The entry's ephemeral private key encrypted to its assigned enclave's public key
+    pub encrypted_keymeld_private_key: String,
+    pub keymeld_auth_pubkey: String,
+}
+
+/// A no-show entry of a cancelled competition whose player registered where their escrow goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingEscrowReclaim {
+    pub competition_id: Uuid,
+    pub entry_id: Uuid,
+    pub ticket_id: Uuid,
+    pub address: String,
+    pub ephemeral_private_key: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ticket {
     pub id: Uuid,
@@ -344,52 +548,114 @@ pub struct Ticket {
     pub paid_at: Option<OffsetDateTime>,
     pub settled_at: Option<OffsetDateTime>,
     pub escrow_transaction: Option<String>, // Hex-encoded escrow transaction
+    /// Lightning backend that created the hold invoice, when running with failover
+    pub ln_backend_id: Option<String>,
+    /// Admin pubkey that marked the ticket as prepaid, the coordinator's wallet covers its
+    /// entry fee instead of a hold invoice
+    pub comped_by: Option<String>,
+    pub comped_at: Option<OffsetDateTime>,
+    pub comp_note: Option<String>,
+    /// Coordinator signed proof of payment, issued once the hold invoice settled
+    pub payment_receipt: Option<SignedReceipt>,
+    /// Why the last attempt to settle the hold invoice failed, cleared once it settles
+    pub settlement_error: Option<String>,
+    pub settlement_failed_at: Option<OffsetDateTime>,
+    /// When the coordinator broadcast the escrow transaction, unset while players broadcast
+    /// their own escrows
+    pub escrow_broadcasted_at: Option<OffsetDateTime>,
 }
 
 impl FromRow<'_, SqliteRow> for Ticket {
     fn from_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
         Ok(Ticket {
-            id: Uuid::parse_str(&row.get::<String, _>("id")).map_err(|e| {
+            id: Uuid::parse_str(&row.try_get::<String, _>("id")?).map_err(|e| {
                 sqlx::Error::ColumnDecode {
                     index: "id".to_string(),
                     source: Box::new(e),
                 }
             })?,
-            competition_id: Uuid::parse_str(&row.get::<String, _>("competition_id")).map_err(
+            competition_id: Uuid::parse_str(&row.try_get::<String, _>("competition_id")?).map_err(
                 |e| sqlx::Error::ColumnDecode {
                     index: "competition_id".to_string(),
                     source: Box::new(e),
                 },
             )?,
             entry_id: row
-                .get::<Option<String>, _>("entry_id")
+                .try_get::<Option<String>, _>("entry_id")?
                 .map(|s| Uuid::parse_str(&s))
                 .transpose()
                 .map_err(|e| sqlx::Error::ColumnDecode {
                     index: "entry_id".to_string(),
                     source: Box::new(e),
                 })?,
-            encrypted_preimage: row.get("encrypted_preimage"),
-            hash: row.get("hash"),
-            payment_request: row.get("payment_request"),
-            invoice_expires_at: parse_optional_sqlite_datetime(row, "invoice_expires_at")?,
-            expiry: row
-                .get::<Option<String>, _>("expiry")
-                .and_then(|s| {
-                    OffsetDateTime::parse(&s, &time::format_description::well_known::Rfc3339).ok()
-                })
-                .unwrap_or_else(|| OffsetDateTime::now_utc() + time::Duration::minutes(60)),
-            ephemeral_pubkey: row.get("ephemeral_pubkey"),
-            reserved_by: row.get("reserved_by"),
-            reserved_at: parse_optional_sqlite_datetime(row, "reserved_at")?,
-            paid_at: parse_optional_sqlite_datetime(row, "paid_at")?,
-            settled_at: parse_optional_sqlite_datetime(row, "settled_at")?,
-            escrow_transaction: row.get("escrow_transaction"),
+            encrypted_preimage: row.try_get("encrypted_preimage")?,
+            hash: row.try_get("hash")?,
+            payment_request: row.try_get("payment_request")?,
+            invoice_expires_at: parse_optional_datetime(row, "invoice_expires_at")?,
+            expiry: parse_required_datetime(row, "expiry")?,
+            ephemeral_pubkey: row.try_get("ephemeral_pubkey")?,
+            reserved_by: row.try_get("reserved_by")?,
+            reserved_at: parse_optional_datetime(row, "reserved_at")?,
+            paid_at: parse_optional_datetime(row, "paid_at")?,
+            settled_at: parse_optional_datetime(row, "settled_at")?,
+            escrow_transaction: row.try_get("escrow_transaction")?,
+            ln_backend_id: row.try_get("ln_backend_id")?,
+            comped_by: row.try_get("comped_by")?,
+            comped_at: parse_optional_datetime(row, "comped_at")?,
+            comp_note: row.try_get("comp_note")?,
+            payment_receipt: parse_optional_blob_json(row, "payment_receipt")?,
+            settlement_error: row.try_get("settlement_error")?,
+            settlement_failed_at: parse_optional_datetime(row, "settlement_failed_at")?,
+            escrow_broadcasted_at: parse_optional_datetime(row, "escrow_broadcasted_at")?,
         })
     }
 }
 
 impl Ticket {
+    /// A fresh, unreserved ticket with its own preimage. `Uuid::now_v7` is monotonic within the
+    /// process, so tickets sort in the order they were created.
+    pub fn generate(competition_id: Uuid, expiry: OffsetDateTime) -> Self {
+        let ticket_preimage = hashlock::preimage_random(&mut rand::rng());
+        let ticket_hash = hashlock::sha256(&ticket_preimage);
+
+        Ticket {
+            id: Uuid::now_v7(),
+            competition_id,
+            entry_id: None,
+            ephemeral_pubkey: None,
+            encrypted_preimage: ticket_preimage.to_lower_hex_string(), // TODO: encrypt this
+            hash: ticket_hash.to_lower_hex_string(),
+            payment_request: None,
+            invoice_expires_at: None,
+            expiry,
+            reserved_by: None,
+            reserved_at: None,
+            paid_at: None,
+            settled_at: None,
+            escrow_transaction: None,
+            ln_backend_id: None,
+            comped_by: None,
+            comped_at: None,
+            comp_note: None,
+            payment_receipt: None,
+            settlement_error: None,
+            settlement_failed_at: None,
+            escrow_broadcasted_at: None,
+        }
+    }
+
+    /// Sats the player paid for this ticket, the competition's invoice amount unless an admin
+    /// comped it
+    pub fn paid_amount(&self, invoice_amount: Sats) -> Sats {
+        if self.comped_at.is_some() {
+            Sats::ZERO
+        } else {
+            invoice_amount
+        }
+    }
+
+    /// Only unpaid reservations expire, once the hold invoice is paid the user's funds are
+    /// locked to the ticket so it stays usable however late the entry arrives
     pub fn get_status(&self) -> TicketStatus {
         let now = OffsetDateTime::now_utc();
 
@@ -397,10 +663,6 @@ impl Ticket {
             return TicketStatus::Used;
         }
 
-        if now > self.expiry {
-            return TicketStatus::Expired;
-        }
-
         if self.settled_at.is_some() {
             return TicketStatus::Settled;
         }
@@ -409,9 +671,13 @@ impl Ticket {
             return TicketStatus::Paid;
         }
 
+        if now > self.expiry {
+            return TicketStatus::Expired;
+        }
+
         if let Some(reserved_at) = self.reserved_at {
-            // If reservation is older than 10 minutes and not paid, consider it expired
-            if now - reserved_at > Duration::minutes(10) {
+            // If reservation is older than the window and not paid, consider it expired
+            if now - reserved_at > TICKET_RESERVATION_WINDOW {
                 return TicketStatus::Expired;
             }
             return TicketStatus::Reserved;
@@ -456,12 +722,109 @@ pub enum TicketStatus {
     Cancelled, // Competition cancelled
 }
 
+/// Ticket status along with where its escrow transaction stands, when it has one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TicketStatusResponse {
+    pub status: TicketStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escrow: Option<EscrowStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EscrowStatus {
+    pub txid: String,
+    pub broadcast_by: EscrowBroadcaster,
+    /// When the coordinator broadcast it, always unset while players broadcast their own
+    #[serde(
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub broadcasted_at: Option<OffsetDateTime>,
+    pub chain_status: EscrowChainStatus,
+    /// What, if anything, the player needs to do about the escrow
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowBroadcaster {
+    Coordinator,
+    Player,
+}
+
+/// Escrow transaction as seen by the coordinator's bitcoin backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum EscrowChainStatus {
+    NotFound,
+    Unconfirmed,
+    Confirmed { height: u32, confirmations: u32 },
+}
+
+impl From<TxChainStatus> for EscrowChainStatus {
+    fn from(status: TxChainStatus) -> Self {
+        match status {
+            TxChainStatus::NotFound => EscrowChainStatus::NotFound,
+            TxChainStatus::Unconfirmed => EscrowChainStatus::Unconfirmed,
+            TxChainStatus::Confirmed {
+                height,
+                confirmations,
+            } => EscrowChainStatus::Confirmed {
+                height,
+                confirmations,
+            },
+        }
+    }
+}
+
 //TODO: add pagination when it's needed
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchBy {
     /// Optionally add event_ids
     #[serde(skip_serializing_if = "Option::is_none")]
     pub event_ids: Option<Vec<Uuid>>,
+    /// Only competitions carrying every one of these tags, a list or a comma separated string
+    /// (`?tags=northeast,daily`)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_tags"
+    )]
+    pub tags: Option<Vec<String>>,
+}
+
+impl SearchBy {
+    /// Whether a competition with `tags` passes the tag filter
+    pub fn matches_tags(&self, tags: &[String]) -> bool {
+        self.tags
+            .as_ref()
+            .is_none_or(|wanted| wanted.iter().all(|tag| tags.contains(tag)))
+    }
+}
+
+fn deserialize_tags<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Tags {
+        List(Vec<String>),
+        Joined(String),
+    }
+
+    let tags = match Option::<Tags>::deserialize(deserializer)? {
+        Some(Tags::List(tags)) => tags,
+        Some(Tags::Joined(joined)) => joined.split(',').map(str::to_string).collect(),
+        None => return Ok(None),
+    };
+    let tags: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    Ok((!tags.is_empty()).then_some(tags))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -486,16 +849,175 @@ pub struct CreateEvent {
     /// Total number of allowed entries into the event
     pub total_allowed_entries: usize,
     /// Total sats required per entry for ticket
-    pub entry_fee: usize,
+    pub entry_fee: Sats,
     /// Percentage of entry fee that goes to the coordinator
     pub coordinator_fee_percentage: usize,
     /// Total sats in competition pool to be won
-    pub total_competition_pool: usize,
+    pub total_competition_pool: Sats,
     /// Relative locktime block delta for this competition.
     /// Controls how many blocks to wait between outcome and delta transactions.
     /// If not set, uses the coordinator-level default from config.
     #[serde(default)]
     pub relative_locktime_block_delta: Option<u16>,
+    /// Optional time at which ticket sales and signing close, must be before the start observation date.
+    /// Lets operators leave headroom for the funding transaction to confirm before observations start.
+    /// If not set, signing closes one minute before the start observation date.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub signing_deadline: Option<OffsetDateTime>,
+    /// Nostr relays (`wss://` urls) that entries' backup DMs are sent through.
+    /// If empty, the coordinator's configured backup relays are used.
+    #[serde(default)]
+    pub backup_relays: Vec<String>,
+    /// Only invited pubkeys can reserve tickets and enter, and the competition is left out of
+    /// public listings and the feed
+    #[serde(default)]
+    pub private: bool,
+    /// Human readable name shown in listings, unique across competitions
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Url handle for `/competitions/by-slug/{slug}`, lowercase letters, digits and dashes.
+    /// Derived from `name` when not given.
+    #[serde(default)]
+    pub slug: Option<String>,
+    /// How entries tied on score are paid, forwarded to the oracle so it announces matching
+    /// outcomes. Defaults to breaking ties by entry time, then entry id.
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+    /// Confirmations the funding transaction needs before the hold invoices are settled, in
+    /// place of the coordinator's `invoice_settlement_confirmations`. At most the configured
+    /// `max_invoice_settlement_confirmations`.
+    #[serde(default)]
+    pub invoice_settlement_confirmations: Option<u32>,
+    /// Labels to browse competitions by, ie. `northeast`, `temperature` or `daily`. Checked
+    /// against the coordinator's `allowed_competition_tags`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Longest slug a competition can have
+pub const MAX_SLUG_LEN: usize = 64;
+
+impl CreateEvent {
+    /// The slug the competition is stored under, the given one or one derived from the name.
+    /// Names that don't make a valid slug (ie. too short) get one derived from the id instead.
+    pub fn competition_slug(&self) -> Option<String> {
+        if let Some(slug) = &self.slug {
+            return Some(slug.clone());
+        }
+        let slug = slugify(self.name.as_deref()?);
+        if is_valid_slug(&slug) {
+            Some(slug)
+        } else {
+            Some(format!("competition-{}", self.id.simple()))
+        }
+    }
+
+    /// When ticket sales and signing close for this event
+    pub fn signing_window_end(&self) -> OffsetDateTime {
+        self.signing_deadline
+            .unwrap_or(self.start_observation_date - TICKET_EXPIRY_BUFFER)
+    }
+
+    /// Sats of the pool not covered by entry fees when `paid_entries` tickets were sold,
+    /// the amount the coordinator wallet underwrites when it funds the contract
+    pub fn pool_subsidy(&self, paid_entries: usize) -> Sats {
+        let collected = self.entry_fee.saturating_mul(paid_entries as u64);
+        self.total_competition_pool.saturating_sub(collected)
+    }
+
+    /// Sats the contract is funded with once `entries` entered: the pool, cut back so the
+    /// coordinator wallet never adds more than `max_subsidy` to the entry fees and `sponsored`
+    pub fn funded_pool(&self, entries: usize, sponsored: Sats, max_subsidy: Sats) -> Sats {
+        let covered = self
+            .entry_fee
+            .saturating_mul(entries as u64)
+            .saturating_add(sponsored)
+            .saturating_add(max_subsidy);
+        self.total_competition_pool.min(covered)
+    }
+
+    /// Entry fee plus the coordinator's percentage of it, `None` when that doesn't fit in a u64
+    pub fn checked_invoice_amount(&self) -> Option<Sats> {
+        let coordinator_fee = self
+            .entry_fee
+            .checked_percent(self.coordinator_fee_percentage as u64)?;
+        self.entry_fee.checked_add(coordinator_fee)
+    }
+}
+
+#[cfg(any(test, feature = "e2e-testing", debug_assertions))]
+impl CreateEvent {
+    /// Three entry, single winner competition on KORD observing from `start` for 18 hours, tests
+    /// and simulations override just the fields they exercise
+    pub fn fixture(start: OffsetDateTime) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            signing_date: start + Duration::hours(27),
+            start_observation_date: start,
+            end_observation_date: start + Duration::hours(18),
+            locations: vec!["KORD".to_string()],
+            number_of_values_per_entry: 3,
+            number_of_places_win: 1,
+            total_allowed_entries: 3,
+            entry_fee: Sats(1000),
+            coordinator_fee_percentage: 10,
+            total_competition_pool: Sats(3000),
+            relative_locktime_block_delta: None,
+            signing_deadline: None,
+            backup_relays: vec![],
+            private: false,
+            name: None,
+            slug: None,
+            tie_policy: TiePolicy::default(),
+            invoice_settlement_confirmations: None,
+            tags: vec![],
+        }
+    }
+}
+
+/// Lowercase `name`, with every run of other characters turned into a single dash
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let mut slug: String = slug.chars().take(MAX_SLUG_LEN).collect();
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+/// 3 to 64 lowercase letters, digits and dashes, not starting or ending with a dash. Slugs
+/// that parse as a UUID are refused so they can't be mistaken for a competition id.
+pub fn is_valid_slug(slug: &str) -> bool {
+    (3..=MAX_SLUG_LEN).contains(&slug.len())
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && Uuid::parse_str(slug).is_err()
+}
+
+/// Most tags a competition can carry
+pub const MAX_COMPETITION_TAGS: usize = 8;
+
+/// Longest tag a competition can carry
+pub const MAX_TAG_LEN: usize = 32;
+
+/// 1 to 32 lowercase letters, digits and dashes, not starting or ending with a dash
+pub fn is_valid_tag(tag: &str) -> bool {
+    (1..=MAX_TAG_LEN).contains(&tag.len())
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !tag.starts_with('-')
+        && !tag.ends_with('-')
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -506,9 +1028,79 @@ pub struct CoordinatorInfo {
     pub signature: String,
 }
 
+/// Where the funding transaction's fee rate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeRateSource {
+    /// The esplora estimate for the confirmation target
+    Estimated,
+    /// Esplora returned no estimates, so the 1 sat/vb floor was used
+    MinimumFallback,
+}
+
+/// Fee rate picked when the funding psbt was built, kept to compare against the mempool when a
+/// transaction is stuck
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingFeeRate {
+    pub sat_per_vb: u64,
+    pub source: FeeRateSource,
+    /// Confirmation target in blocks the estimate was requested for
+    pub confirmation_target: Option<u16>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub chosen_at: OffsetDateTime,
+}
+
+impl FundingFeeRate {
+    /// Pick the rate for `confirmation_target` out of esplora's estimates, falling back to the
+    /// 1 sat/vb floor when there are none
+    pub fn from_estimates(estimates: &HashMap<u16, f64>, confirmation_target: u16) -> Self {
+        let (sat_per_vb, source, confirmation_target) = match estimates.get(&confirmation_target) {
+            Some(rate) => (
+                (rate.ceil() as u64).max(1),
+                FeeRateSource::Estimated,
+                Some(confirmation_target),
+            ),
+            None => (1, FeeRateSource::MinimumFallback, None),
+        };
+        Self {
+            sat_per_vb,
+            source,
+            confirmation_target,
+            chosen_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    pub fn fee_rate(&self) -> FeeRate {
+        FeeRate::from_sat_per_vb_unchecked(self.sat_per_vb)
+    }
+}
+
+/// How far settling a competition's hold invoices has got
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementProgress {
+    pub settled_invoices: u64,
+    /// Paid hold invoices, settled or not
+    pub total_invoices: u64,
+    /// Unsettled invoices whose last settle attempt failed, see `Ticket::settlement_error`
+    pub failed_invoices: u64,
+}
+
+impl SettlementProgress {
+    pub fn is_complete(&self) -> bool {
+        self.settled_invoices >= self.total_invoices
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Competition {
     pub id: Uuid,
+    /// Human readable name, unique across competitions
+    pub name: Option<String>,
+    /// Unique url handle, `/competitions/by-slug/{slug}`
+    pub slug: Option<String>,
+    /// Id of the coordinator key the contract is signed with, `None` for the wallet seed key
+    #[serde(default)]
+    pub coordinator_key_id: Option<String>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
     pub event_submission: CreateEvent,
@@ -516,14 +1108,23 @@ pub struct Competition {
     pub total_entry_nonces: u64,
     pub total_signed_entries: u64,
     pub total_paid_entries: u64,
+    /// Paid entries whose ticket was comped by an admin rather than paid for
+    pub total_comped_entries: u64,
     pub total_paid_out_entries: u64,
+    /// Hold invoices settled so far, comped tickets have none
+    #[serde(default)]
+    pub settlement_progress: SettlementProgress,
     pub event_announcement: Option<EventLockingConditions>,
     pub funding_outpoint: Option<OutPoint>,
     pub funding_psbt_base64: Option<String>,
+    /// Fee rate the funding psbt was built with
+    pub funding_fee_rate: Option<FundingFeeRate>,
     pub funding_transaction: Option<Transaction>,
     pub outcome_transaction: Option<Transaction>,
     pub contract_parameters: Option<ContractParameters>,
     pub public_nonces: Option<SigMap<PubNonce>>,
+    /// Funding outpoint the coordinator's public nonces were derived from
+    pub nonce_seed_outpoint: Option<OutPoint>,
     pub aggregated_nonces: Option<SigMap<AggNonce>>,
     pub partial_signatures: Option<SigMap<PartialSignature>>,
     pub signed_contract: Option<SignedContract>,
@@ -544,6 +1145,9 @@ pub struct Competition {
     /// When the coordinator successfully batch sends all entries to the oracle
     #[serde(with = "time::serde::rfc3339::option")]
     pub entries_submitted_at: Option<OffsetDateTime>,
+    /// Entries the oracle confirmed receiving, so a retried submission only sends the rest
+    #[serde(default)]
+    pub oracle_submitted_entry_ids: Vec<Uuid>,
     #[serde(with = "time::serde::rfc3339::option")]
     pub funding_broadcasted_at: Option<OffsetDateTime>,
     /// Funding transaction is considered settled after 1 confirmation by default
@@ -575,12 +1179,31 @@ pub struct Competition {
     /// When keymeld keygen completed (aggregate key generated)
     #[serde(with = "time::serde::rfc3339::option")]
     pub keymeld_keygen_completed_at: Option<OffsetDateTime>,
-    pub errors: Vec<CompetitionError>,
+    pub errors: Vec<RecordedCompetitionError>,
+    /// Bumped each time the competition's state is saved, a save from an older version is
+    /// refused
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// A lifecycle timestamp the competition has reached, labelled for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub event: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtendCompetition {
     pub id: Uuid,
+    /// Human readable name, unique across competitions
+    pub name: Option<String>,
+    /// Unique url handle, `/competitions/by-slug/{slug}`
+    pub slug: Option<String>,
+    /// Id of the coordinator key the contract is signed with, `None` for the wallet seed key
+    #[serde(default)]
+    pub coordinator_key_id: Option<String>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
     pub event_submission: CreateEvent,
@@ -588,14 +1211,23 @@ pub struct ExtendCompetition {
     pub total_entry_nonces: u64,
     pub total_signed_entries: u64,
     pub total_paid_entries: u64,
+    /// Paid entries whose ticket was comped by an admin rather than paid for
+    pub total_comped_entries: u64,
     pub total_paid_out_entries: u64,
+    /// Hold invoices settled so far, comped tickets have none
+    #[serde(default)]
+    pub settlement_progress: SettlementProgress,
     pub event_announcement: Option<EventLockingConditions>,
     pub funding_transaction: Option<Transaction>,
     pub funding_outpoint: Option<OutPoint>,
     pub funding_psbt_base64: Option<String>,
+    /// Fee rate the funding psbt was built with
+    pub funding_fee_rate: Option<FundingFeeRate>,
     pub outcome_transaction: Option<Transaction>,
     pub contract_parameters: Option<ContractParameters>,
     pub public_nonces: Option<SigMap<PubNonce>>,
+    /// Funding outpoint the coordinator's public nonces were derived from
+    pub nonce_seed_outpoint: Option<OutPoint>,
     pub aggregated_nonces: Option<SigMap<AggNonce>>,
     pub partial_signatures: Option<SigMap<PartialSignature>>,
     pub signed_contract: Option<SignedContract>,
@@ -613,6 +1245,9 @@ pub struct ExtendCompetition {
     /// When the coordinator successfully batch sends all entries to the oracle
     #[serde(with = "time::serde::rfc3339::option")]
     pub entries_submitted_at: Option<OffsetDateTime>,
+    /// Entries the oracle confirmed receiving, so a retried submission only sends the rest
+    #[serde(default)]
+    pub oracle_submitted_entry_ids: Vec<Uuid>,
     #[serde(with = "time::serde::rfc3339::option")]
     pub escrow_funds_confirmed_at: Option<OffsetDateTime>,
     #[serde(with = "time::serde::rfc3339::option")]
@@ -642,15 +1277,29 @@ pub struct ExtendCompetition {
     pub failed_at: Option<OffsetDateTime>,
     #[serde(with = "time::serde::rfc3339::option")]
     pub keymeld_keygen_completed_at: Option<OffsetDateTime>,
-    pub errors: Vec<CompetitionError>,
+    pub errors: Vec<RecordedCompetitionError>,
+    /// Changes every time the coordinator saves the competition
+    #[serde(default)]
+    pub version: u64,
     pub state: String,
+    /// Simplified phase for clients that don't want to interpret `state`
+    pub phase: UserFacingPhase,
+    /// The lifecycle timestamps that are set, oldest first
+    #[serde(default)]
+    pub timeline: Vec<TimelineEvent>,
 }
 
 impl From<Competition> for ExtendCompetition {
     fn from(competition: Competition) -> Self {
-        let state = competition.get_state().to_string();
+        let competition_state = competition.get_state();
+        let state = competition_state.to_string();
+        let phase = competition_state.user_facing_phase();
+        let timeline = competition.progress_timeline();
         Self {
             id: competition.id,
+            name: competition.name,
+            slug: competition.slug,
+            coordinator_key_id: competition.coordinator_key_id,
             created_at: competition.created_at,
             event_submission: competition.event_submission,
             event_announcement: competition.event_announcement,
@@ -658,12 +1307,15 @@ impl From<Competition> for ExtendCompetition {
             total_entry_nonces: competition.total_entry_nonces,
             total_signed_entries: competition.total_signed_entries,
             total_paid_entries: competition.total_paid_entries,
+            total_comped_entries: competition.total_comped_entries,
             total_paid_out_entries: competition.total_paid_out_entries,
+            settlement_progress: competition.settlement_progress,
             funding_transaction: competition.funding_transaction,
             funding_outpoint: competition.funding_outpoint,
             outcome_transaction: competition.outcome_transaction,
             contract_parameters: competition.contract_parameters,
             public_nonces: competition.public_nonces,
+            nonce_seed_outpoint: competition.nonce_seed_outpoint,
             aggregated_nonces: competition.aggregated_nonces,
             partial_signatures: competition.partial_signatures,
             signed_contract: competition.signed_contract,
@@ -673,7 +1325,9 @@ impl From<Competition> for ExtendCompetition {
             signed_at: competition.signed_at,
             event_created_at: competition.event_created_at,
             entries_submitted_at: competition.entries_submitted_at,
+            oracle_submitted_entry_ids: competition.oracle_submitted_entry_ids,
             funding_psbt_base64: competition.funding_psbt_base64,
+            funding_fee_rate: competition.funding_fee_rate,
             escrow_funds_confirmed_at: competition.escrow_funds_confirmed_at,
             funding_broadcasted_at: competition.funding_broadcasted_at,
             funding_confirmed_at: competition.funding_confirmed_at,
@@ -686,7 +1340,10 @@ impl From<Competition> for ExtendCompetition {
             failed_at: competition.failed_at,
             keymeld_keygen_completed_at: competition.keymeld_keygen_completed_at,
             errors: competition.errors,
+            version: competition.version,
             state,
+            phase,
+            timeline,
         }
     }
 }
@@ -704,31 +1361,28 @@ impl Serialize for Competition {
 
 const TICKET_EXPIRY_BUFFER: Duration = Duration::minutes(1);
 
-impl Competition {
-    async fn generate_competition_tickets(
-        &self,
-        total_tickets: usize,
-    ) -> Result<Vec<Ticket>, Error> {
-        let expiry_time = self.calculate_ticket_expiry()?;
-        let mut tickets = Vec::with_capacity(total_tickets);
-        for i in 0..total_tickets {
-            let ticket = self.create_ticket(expiry_time).await?;
-
-            tickets.push(ticket);
+/// How long a reserved ticket stays held for its player before it has to be paid
+pub const TICKET_RESERVATION_WINDOW: Duration = Duration::minutes(10);
 
-            if i < total_tickets - 1 {
-                tokio::time::sleep(std::time::Duration::from_micros(1)).await;
-            }
-        }
+/// How far back `should_abort` counts a competition's errors
+const ABORT_ERROR_WINDOW: Duration = Duration::hours(1);
+/// More errors than this within `ABORT_ERROR_WINDOW` fail the competition
+const MAX_RECENT_ERRORS: usize = 5;
 
-        Ok(tickets)
+impl Competition {
+    /// Tickets created along with the competition, the rest of its `total_allowed_entries` are
+    /// issued as players reserve them
+    fn generate_competition_tickets(&self, total_tickets: usize) -> Result<Vec<Ticket>, Error> {
+        let expiry_time = self.calculate_ticket_expiry()?;
+        Ok((0..total_tickets)
+            .map(|_| Ticket::generate(self.id, expiry_time))
+            .collect())
     }
 
     fn calculate_ticket_expiry(&self) -> Result<OffsetDateTime, Error> {
         let now = OffsetDateTime::now_utc();
 
-        let latest_signing_end =
-            self.event_submission.start_observation_date - TICKET_EXPIRY_BUFFER;
+        let latest_signing_end = self.event_submission.signing_window_end();
 
         if now >= latest_signing_end {
             return Err(Error::TooLateToSign(latest_signing_end, now));
@@ -857,37 +1511,18 @@ impl Competition {
         Ok(outcome)
     }
 
-    pub fn calculate_invoice_amount(&self) -> u64 {
-        let fee_multiplier = self.event_submission.coordinator_fee_percentage as f64 / 100.0;
-        let coordinator_fee =
-            (self.event_submission.entry_fee as f64 * fee_multiplier).round() as u64;
-
-        (self.event_submission.entry_fee as u64) + coordinator_fee
+    /// Entry fee plus the coordinator's percentage of it, rounded half up. Integer math so
+    /// large fees neither lose precision nor overflow.
+    pub fn calculate_invoice_amount(&self) -> Sats {
+        // Competitions are validated at creation, so this only saturates for stored ones
+        // created before that check existed
+        self.event_submission
+            .checked_invoice_amount()
+            .unwrap_or(Sats::MAX)
     }
 
     // We add the fee for the coordinator's service at this point in the process,
     // A user can not enter into the competition without paying the fee
-    async fn create_ticket(&self, expiry_time: OffsetDateTime) -> Result<Ticket, Error> {
-        let ticket_preimage = hashlock::preimage_random(&mut rand::rng());
-        let ticket_hash = hashlock::sha256(&ticket_preimage);
-
-        Ok(Ticket {
-            id: Uuid::now_v7(),
-            competition_id: self.id,
-            entry_id: None,
-            ephemeral_pubkey: None,
-            encrypted_preimage: ticket_preimage.to_lower_hex_string(), // TODO: encrypt this
-            hash: ticket_hash.to_lower_hex_string(),
-            payment_request: None,
-            invoice_expires_at: None,
-            expiry: expiry_time,
-            reserved_by: None,
-            reserved_at: None,
-            paid_at: None,
-            settled_at: None,
-            escrow_transaction: None,
-        })
-    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -895,11 +1530,49 @@ pub struct FundedContract {
     pub contract_params: ContractParameters,
     pub funding_outpoint: OutPoint,
     pub funding_psbt_base64: String,
+    /// sha256 of the aggregated nonces' json once available, the SigMap itself is served from
+    /// `/aggregate_nonces` to keep this response small with many outcomes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregated_nonces_digest: Option<String>,
     /// Keymeld signing info (present when keymeld is enabled and user has entry)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub keymeld: Option<KeymeldSigningInfo>,
 }
 
+/// The oracle data the coordinator used for a competition, so a client can re-run the
+/// attestation check against the locking points on its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleEventInfo {
+    pub competition_id: Uuid,
+    /// Key the oracle announces and attests with, to check the locking points came from the
+    /// oracle. Left out when the operator hasn't configured it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oracle_pubkey: Option<String>,
+    /// Where the oracle publishes its own copy of the announcement and attestation, on the oracle
+    /// url the pages already hand browsers
+    pub oracle_event_url: String,
+    /// Locking points (and expiry) announced by the oracle when the event was created
+    pub event_announcement: Option<EventLockingConditions>,
+    /// Attestation secret, its base point multiple is the locking point of the winning outcome
+    pub attestation: Option<MaybeScalar>,
+}
+
+impl OracleEventInfo {
+    pub fn new(competition: &Competition, oracle_url: &str, oracle_pubkey: Option<String>) -> Self {
+        Self {
+            competition_id: competition.id,
+            oracle_pubkey,
+            oracle_event_url: format!(
+                "{}/oracle/events/{}",
+                oracle_url.trim_end_matches('/'),
+                competition.id
+            ),
+            event_announcement: competition.event_announcement.clone(),
+            attestation: competition.attestation,
+        }
+    }
+}
+
 /// Keymeld signing information included in contract response
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct KeymeldSigningInfo {
@@ -989,24 +1662,137 @@ impl fmt::Display for CompetitionState {
     }
 }
 
+impl CompetitionState {
+    /// Collapse the internal state machine into the handful of phases a player cares about
+    pub fn user_facing_phase(&self) -> UserFacingPhase {
+        match self {
+            CompetitionState::Created | CompetitionState::EntriesCollected => {
+                UserFacingPhase::Entries
+            }
+            CompetitionState::EscrowFundsConfirmed
+            | CompetitionState::EventCreated
+            | CompetitionState::EntriesSubmitted
+            | CompetitionState::ContractCreated
+            | CompetitionState::AwaitingSignatures
+            | CompetitionState::SigningComplete
+            | CompetitionState::FundingBroadcasted
+            | CompetitionState::FundingConfirmed => UserFacingPhase::Funding,
+            CompetitionState::FundingSettled | CompetitionState::AwaitingAttestation => {
+                UserFacingPhase::Live
+            }
+            CompetitionState::Attested
+            | CompetitionState::ExpiryBroadcasted
+            | CompetitionState::OutcomeBroadcasted
+            | CompetitionState::DeltaBroadcasted
+            | CompetitionState::Completed => UserFacingPhase::Results,
+            CompetitionState::Failed | CompetitionState::Cancelled => UserFacingPhase::Cancelled,
+        }
+    }
+}
+
+/// Simplified competition lifecycle shown to players: Entries -> Funding -> Live -> Results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserFacingPhase {
+    /// Accepting entries until tickets are sold out or the entry window closes
+    Entries,
+    /// Building, signing and funding the contract
+    Funding,
+    /// Contract is funded and the observation window is running
+    Live,
+    /// Oracle has attested and payouts are being made
+    Results,
+    /// Competition was cancelled or failed, entries are refunded
+    Cancelled,
+}
+
+impl UserFacingPhase {
+    /// Phases in the order they are displayed in the progress indicator
+    pub const STEPS: [UserFacingPhase; 4] = [
+        UserFacingPhase::Entries,
+        UserFacingPhase::Funding,
+        UserFacingPhase::Live,
+        UserFacingPhase::Results,
+    ];
+
+    /// Position of this phase in `STEPS`, `None` when the competition left the happy path
+    pub fn step_index(&self) -> Option<usize> {
+        Self::STEPS.iter().position(|step| step == self)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UserFacingPhase::Entries => "Entries",
+            UserFacingPhase::Funding => "Funding",
+            UserFacingPhase::Live => "Live",
+            UserFacingPhase::Results => "Results",
+            UserFacingPhase::Cancelled => "Cancelled",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            UserFacingPhase::Entries => "Open for entries until the competition is full",
+            UserFacingPhase::Funding => {
+                "Entries are closed, the contract is being signed and funded"
+            }
+            UserFacingPhase::Live => "Contract is funded, waiting on the weather observations",
+            UserFacingPhase::Results => "Results are in, winners can claim their payouts",
+            UserFacingPhase::Cancelled => "Competition did not run, entries can be refunded",
+        }
+    }
+}
+
+impl fmt::Display for UserFacingPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Which deadline a countdown is pointing at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadlineKind {
+    EntryClose,
+    SigningDeadline,
+    ObservationEnd,
+}
+
+impl DeadlineKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeadlineKind::EntryClose => "Entries close",
+            DeadlineKind::SigningDeadline => "Signing deadline",
+            DeadlineKind::ObservationEnd => "Observation ends",
+        }
+    }
+}
+
 impl Competition {
     pub fn new(create_event: &CreateEvent) -> Self {
         Self {
             id: create_event.id,
+            name: create_event.name.clone(),
+            slug: create_event.competition_slug(),
+            coordinator_key_id: None,
             created_at: OffsetDateTime::now_utc(),
             event_submission: create_event.clone(),
             total_entries: 0,
             total_entry_nonces: 0,
             total_signed_entries: 0,
             total_paid_entries: 0,
+            total_comped_entries: 0,
             total_paid_out_entries: 0,
+            settlement_progress: SettlementProgress::default(),
             event_announcement: None,
             funding_transaction: None,
             outcome_transaction: None,
             funding_outpoint: None,
             funding_psbt_base64: None,
+            funding_fee_rate: None,
             contract_parameters: None,
             public_nonces: None,
+            nonce_seed_outpoint: None,
             aggregated_nonces: None,
             attestation: None,
             cancelled_at: None,
@@ -1016,6 +1802,7 @@ impl Competition {
             partial_signatures: None,
             event_created_at: None,
             entries_submitted_at: None,
+            oracle_submitted_entry_ids: vec![],
             escrow_funds_confirmed_at: None,
             funding_broadcasted_at: None,
             funding_confirmed_at: None,
@@ -1029,6 +1816,7 @@ impl Competition {
             failed_at: None,
             keymeld_keygen_completed_at: None,
             errors: vec![],
+            version: 0,
         }
     }
     pub fn has_full_entries(&self) -> bool {
@@ -1044,6 +1832,27 @@ impl Competition {
         (self.total_entries > 0) && (self.total_entry_nonces >= self.total_entries)
     }
 
+    /// The funding psbt was rebuilt after the coordinator derived its nonces, so they no longer
+    /// match what the current funding outpoint seeds. Competitions that predate the recorded
+    /// seed are never reported.
+    pub fn nonce_seed_moved(&self) -> bool {
+        match (self.nonce_seed_outpoint, self.funding_outpoint) {
+            (Some(seed), Some(current)) => self.public_nonces.is_some() && seed != current,
+            _ => false,
+        }
+    }
+
+    /// Drop the coordinator's side of the nonce round and what was built on top of it, the
+    /// entries' nonces and signatures are cleared in the store
+    pub fn reset_nonce_round(&mut self) {
+        self.public_nonces = None;
+        self.nonce_seed_outpoint = None;
+        self.aggregated_nonces = None;
+        self.partial_signatures = None;
+        self.total_entry_nonces = 0;
+        self.total_signed_entries = 0;
+    }
+
     pub fn has_all_entry_partial_signatures(&self) -> bool {
         (self.total_entries > 0) && (self.total_signed_entries >= self.total_entries)
     }
@@ -1120,8 +1929,54 @@ impl Competition {
         self.failed_at.is_some()
     }
 
+    /// Record an error against the competition's current state
+    pub fn record_error(&mut self, error: CompetitionError) {
+        let state = self.get_state().to_string();
+        self.errors.push(RecordedCompetitionError {
+            error,
+            occurred_at: OffsetDateTime::now_utc(),
+            state,
+        });
+    }
+
+    /// Record an error that comes back every tick for as long as it lasts, ie. a rejected
+    /// broadcast being retried. Repeats of the last error only refresh when it last happened.
+    pub fn record_repeated_error(&mut self, error: CompetitionError) {
+        let state = self.get_state().to_string();
+        let occurred_at = OffsetDateTime::now_utc();
+        match self.errors.last_mut() {
+            Some(last) if last.state == state && last.error.to_string() == error.to_string() => {
+                last.occurred_at = occurred_at;
+            }
+            _ => self.errors.push(RecordedCompetitionError {
+                error,
+                occurred_at,
+                state,
+            }),
+        }
+    }
+
+    /// Errors recorded within `ABORT_ERROR_WINDOW` of `now`
+    pub fn recent_error_count(&self, now: OffsetDateTime) -> usize {
+        self.errors
+            .iter()
+            .filter(|recorded| recorded.occurred_at > now - ABORT_ERROR_WINDOW)
+            .count()
+    }
+
+    /// Whether errors are piling up fast enough to give up on the competition, old errors on a
+    /// long running competition don't count towards it
     pub fn should_abort(&self) -> bool {
-        self.errors.len() > 5
+        self.recent_error_count(OffsetDateTime::now_utc()) > MAX_RECENT_ERRORS
+    }
+
+    /// Still running `max_lifetime` after it was created, whatever state it is stuck in. Funded
+    /// competitions are left to their expiry transaction, which only becomes valid at the
+    /// contract's expiry.
+    pub fn exceeds_max_lifetime(&self, max_lifetime: Duration, now: OffsetDateTime) -> bool {
+        !self.skip_competition()
+            && !self.is_funding_broadcasted()
+            && now - self.created_at > max_lifetime
     }
 
     pub fn is_expired(&self) -> bool {
@@ -1207,31 +2062,111 @@ impl Competition {
         }
         CompetitionState::Created
     }
+
+    pub fn user_facing_phase(&self) -> UserFacingPhase {
+        self.get_state().user_facing_phase()
+    }
+
+    /// Every lifecycle timestamp that has been set, oldest first. Steps recorded at the same
+    /// instant keep the order they happen in.
+    pub fn progress_timeline(&self) -> Vec<TimelineEvent> {
+        let steps = [
+            ("created", Some(self.created_at)),
+            ("oracle event created", self.event_created_at),
+            ("keymeld keygen completed", self.keymeld_keygen_completed_at),
+            ("entries collected", self.entries_submitted_at),
+            ("escrow confirmed", self.escrow_funds_confirmed_at),
+            ("contract created", self.contracted_at),
+            ("contract signed", self.signed_at),
+            ("funding broadcast", self.funding_broadcasted_at),
+            ("funding confirmed", self.funding_confirmed_at),
+            ("invoices settled", self.invoices_settled_at),
+            ("funding settled", self.funding_settled_at),
+            ("awaiting attestation", self.awaiting_attestation_at),
+            ("outcome broadcast", self.outcome_broadcasted_at),
+            ("expiry broadcast", self.expiry_broadcasted_at),
+            ("payouts broadcast", self.delta_broadcasted_at),
+            ("completed", self.completed_at),
+            ("failed", self.failed_at),
+            ("cancelled", self.cancelled_at),
+        ];
+        let mut timeline: Vec<TimelineEvent> = steps
+            .into_iter()
+            .filter_map(|(event, at)| {
+                at.map(|at| TimelineEvent {
+                    event: event.to_string(),
+                    at,
+                })
+            })
+            .collect();
+        timeline.sort_by_key(|step| step.at);
+        timeline
+    }
+
+    /// The next deadline relevant to the competition's current phase, if it hasn't passed yet.
+    /// Players have to be signed up and signed before observation starts, so both the entry
+    /// close and signing deadline fall back to start_observation_date when no signing deadline is set.
+    pub fn next_deadline(&self, now: OffsetDateTime) -> Option<(DeadlineKind, OffsetDateTime)> {
+        let deadline = match self.user_facing_phase() {
+            UserFacingPhase::Entries => (
+                DeadlineKind::EntryClose,
+                self.event_submission.signing_window_end(),
+            ),
+            UserFacingPhase::Funding => (
+                DeadlineKind::SigningDeadline,
+                self.event_submission
+                    .signing_deadline
+                    .unwrap_or(self.event_submission.start_observation_date),
+            ),
+            UserFacingPhase::Live => (
+                DeadlineKind::ObservationEnd,
+                self.event_submission.end_observation_date,
+            ),
+            UserFacingPhase::Results | UserFacingPhase::Cancelled => return None,
+        };
+
+        if deadline.1 > now {
+            Some(deadline)
+        } else {
+            None
+        }
+    }
 }
 
 impl FromRow<'_, SqliteRow> for Competition {
     fn from_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
         Ok(Competition {
-            id: Uuid::parse_str(&row.get::<String, _>("id")).map_err(|e| {
+            id: Uuid::parse_str(&row.try_get::<String, _>("id")?).map_err(|e| {
                 sqlx::Error::ColumnDecode {
                     index: "id".to_string(),
                     source: Box::new(e),
                 }
             })?,
+            name: row.try_get("name")?,
+            slug: row.try_get("slug")?,
+            coordinator_key_id: row.try_get("coordinator_key_id")?,
             created_at: parse_required_datetime(row, "created_at")?,
             event_submission: parse_required_blob_json(row, "event_submission")?,
-            total_entries: row.try_get("total_entries").unwrap_or(0) as u64,
-            total_entry_nonces: row.try_get("total_entry_nonces").unwrap_or(0) as u64,
-            total_signed_entries: row.try_get("total_signed_entries").unwrap_or(0) as u64,
-            total_paid_entries: row.try_get("total_paid_entries").unwrap_or(0) as u64,
-            total_paid_out_entries: row.try_get("total_paid_out_entries").unwrap_or(0) as u64,
+            total_entries: parse_optional_count(row, "total_entries")?,
+            total_entry_nonces: parse_optional_count(row, "total_entry_nonces")?,
+            total_signed_entries: parse_optional_count(row, "total_signed_entries")?,
+            total_paid_entries: parse_optional_count(row, "total_paid_entries")?,
+            total_comped_entries: parse_optional_count(row, "total_comped_entries")?,
+            total_paid_out_entries: parse_optional_count(row, "total_paid_out_entries")?,
+            settlement_progress: SettlementProgress {
+                settled_invoices: parse_optional_count(row, "total_settled_invoices")?,
+                total_invoices: parse_optional_count(row, "total_invoices")?,
+                failed_invoices: parse_optional_count(row, "total_failed_settlements")?,
+            },
             event_announcement: parse_optional_blob_json(row, "event_announcement")?,
             funding_outpoint: parse_optional_blob_json(row, "funding_outpoint")?,
             funding_psbt_base64: row.get("funding_psbt_base64"),
+            funding_fee_rate: parse_optional_blob_json(row, "funding_fee_rate")?,
             funding_transaction: parse_optional_blob_json(row, "funding_transaction")?,
             outcome_transaction: parse_optional_blob_json(row, "outcome_transaction")?,
             contract_parameters: parse_optional_blob_json(row, "contract_parameters")?,
             public_nonces: parse_optional_blob_json(row, "public_nonces")?,
+            nonce_seed_outpoint: parse_optional_blob_json(row, "nonce_seed_outpoint")?,
             aggregated_nonces: parse_optional_blob_json(row, "aggregated_nonces")?,
             partial_signatures: parse_optional_blob_json(row, "partial_signatures")?,
             signed_contract: parse_optional_blob_json(row, "signed_contract")?,
@@ -1242,6 +2177,11 @@ impl FromRow<'_, SqliteRow> for Competition {
             escrow_funds_confirmed_at: parse_optional_datetime(row, "escrow_funds_confirmed_at")?,
             event_created_at: parse_optional_datetime(row, "event_created_at")?,
             entries_submitted_at: parse_optional_datetime(row, "entries_submitted_at")?,
+            oracle_submitted_entry_ids: parse_optional_blob_json(
+                row,
+                "oracle_submitted_entry_ids",
+            )?
+            .unwrap_or_default(),
             funding_broadcasted_at: parse_optional_datetime(row, "funding_broadcasted_at")?,
             funding_confirmed_at: parse_optional_datetime(row, "funding_confirmed_at")?,
             invoices_settled_at: parse_optional_datetime(row, "invoices_settled_at")?,
@@ -1257,10 +2197,38 @@ impl FromRow<'_, SqliteRow> for Competition {
                 "keymeld_keygen_completed_at",
             )?,
             errors: parse_optional_blob_json(row, "errors")?.unwrap_or_default(),
+            version: row.try_get::<i64, _>("version")? as u64,
         })
     }
 }
 
+impl Competition {
+    /// Decode each JSON blob column of a competitions row on its own, so a broken column is
+    /// reported even when an earlier one is broken too
+    pub fn blob_decode_errors(row: &SqliteRow) -> Vec<sqlx::Error> {
+        [
+            parse_required_blob_json::<CreateEvent>(row, "event_submission").err(),
+            parse_optional_blob_json::<EventLockingConditions>(row, "event_announcement").err(),
+            parse_optional_blob_json::<OutPoint>(row, "funding_outpoint").err(),
+            parse_optional_blob_json::<FundingFeeRate>(row, "funding_fee_rate").err(),
+            parse_optional_blob_json::<Transaction>(row, "funding_transaction").err(),
+            parse_optional_blob_json::<Transaction>(row, "outcome_transaction").err(),
+            parse_optional_blob_json::<ContractParameters>(row, "contract_parameters").err(),
+            parse_optional_blob_json::<SigMap<PubNonce>>(row, "public_nonces").err(),
+            parse_optional_blob_json::<OutPoint>(row, "nonce_seed_outpoint").err(),
+            parse_optional_blob_json::<SigMap<AggNonce>>(row, "aggregated_nonces").err(),
+            parse_optional_blob_json::<SigMap<PartialSignature>>(row, "partial_signatures").err(),
+            parse_optional_blob_json::<SignedContract>(row, "signed_contract").err(),
+            parse_optional_blob_json::<MaybeScalar>(row, "attestation").err(),
+            parse_optional_blob_json::<Vec<Uuid>>(row, "oracle_submitted_entry_ids").err(),
+            parse_optional_blob_json::<Vec<RecordedCompetitionError>>(row, "errors").err(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
 #[derive(thiserror::Error, Debug, Serialize, Clone, Deserialize)]
 pub enum CompetitionError {
     #[error("Failed to create transaction: {0}")]
@@ -1285,4 +2253,517 @@ pub enum CompetitionError {
     Expired(String),
     #[error("Invalid state transition: {0}")]
     InvalidStateTransition(String),
+    #[error("Unexpected spend: {0}")]
+    UnexpectedSpend(String),
+    #[error("Escrow double spent: {0}")]
+    EscrowDoubleSpent(String),
+    #[error("Broadcast rejected: {0}")]
+    BroadcastRejected(String),
+    #[error("Competition exceeded its max lifetime: {0}")]
+    MaxLifetimeExceeded(String),
+    #[error("Coordinator wallet can't fund the contract: {0}")]
+    InsufficientFunds(String),
+}
+
+/// An error a competition ran into, with when it happened and the state it was in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredCompetitionError")]
+pub struct RecordedCompetitionError {
+    pub error: CompetitionError,
+    #[serde(with = "time::serde::rfc3339")]
+    pub occurred_at: OffsetDateTime,
+    pub state: String,
+}
+
+impl fmt::Display for RecordedCompetitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let occurred_at = self
+            .occurred_at
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| self.occurred_at.to_string());
+        write!(f, "{} [{}] {}", occurred_at, self.state, self.error)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCompetitionError {
+    Recorded {
+        error: CompetitionError,
+        #[serde(with = "time::serde::rfc3339")]
+        occurred_at: OffsetDateTime,
+        state: String,
+    },
+    /// Stored before errors were timestamped
+    Legacy(CompetitionError),
+}
+
+impl From<StoredCompetitionError> for RecordedCompetitionError {
+    fn from(stored: StoredCompetitionError) -> Self {
+        match stored {
+            StoredCompetitionError::Recorded {
+                error,
+                occurred_at,
+                state,
+            } => Self {
+                error,
+                occurred_at,
+                state,
+            },
+            // When these happened is lost, dating them to the epoch keeps them out of
+            // `should_abort`'s window
+            StoredCompetitionError::Legacy(error) => Self {
+                error,
+                occurred_at: OffsetDateTime::UNIX_EPOCH,
+                state: "unknown".to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_competition(now: OffsetDateTime) -> Competition {
+        Competition::new(&CreateEvent::fixture(now + Duration::hours(6)))
+    }
+
+    #[test]
+    fn test_progress_timeline_is_chronological_and_skips_unset_steps() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now);
+        competition.created_at = now;
+        competition.event_created_at = Some(now + Duration::minutes(1));
+        competition.entries_submitted_at = Some(now + Duration::hours(2));
+        // Escrow confirmed before the last entries reached the oracle
+        competition.escrow_funds_confirmed_at = Some(now + Duration::hours(1));
+        competition.contracted_at = Some(now + Duration::hours(2));
+        competition.funding_confirmed_at = Some(now + Duration::hours(3));
+
+        let events: Vec<(String, OffsetDateTime)> = competition
+            .progress_timeline()
+            .into_iter()
+            .map(|step| (step.event, step.at))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("created".to_string(), now),
+                (
+                    "oracle event created".to_string(),
+                    now + Duration::minutes(1)
+                ),
+                ("escrow confirmed".to_string(), now + Duration::hours(1)),
+                ("entries collected".to_string(), now + Duration::hours(2)),
+                ("contract created".to_string(), now + Duration::hours(2)),
+                ("funding confirmed".to_string(), now + Duration::hours(3)),
+            ]
+        );
+
+        let json = serde_json::to_value(&competition).unwrap();
+        assert_eq!(json["timeline"].as_array().unwrap().len(), 6);
+        assert_eq!(json["timeline"][5]["event"], "funding confirmed");
+    }
+
+    #[test]
+    fn test_user_facing_phase_mapping() {
+        use CompetitionState::*;
+
+        for state in [Created, EntriesCollected] {
+            assert_eq!(state.user_facing_phase(), UserFacingPhase::Entries);
+        }
+        for state in [
+            EscrowFundsConfirmed,
+            EventCreated,
+            EntriesSubmitted,
+            ContractCreated,
+            AwaitingSignatures,
+            SigningComplete,
+            FundingBroadcasted,
+            FundingConfirmed,
+        ] {
+            assert_eq!(state.user_facing_phase(), UserFacingPhase::Funding);
+        }
+        for state in [FundingSettled, AwaitingAttestation] {
+            assert_eq!(state.user_facing_phase(), UserFacingPhase::Live);
+        }
+        for state in [
+            Attested,
+            ExpiryBroadcasted,
+            OutcomeBroadcasted,
+            DeltaBroadcasted,
+            Completed,
+        ] {
+            assert_eq!(state.user_facing_phase(), UserFacingPhase::Results);
+        }
+        for state in [Failed, Cancelled] {
+            assert_eq!(state.user_facing_phase(), UserFacingPhase::Cancelled);
+        }
+    }
+
+    #[test]
+    fn test_phase_step_index() {
+        assert_eq!(UserFacingPhase::Entries.step_index(), Some(0));
+        assert_eq!(UserFacingPhase::Results.step_index(), Some(3));
+        assert_eq!(UserFacingPhase::Cancelled.step_index(), None);
+    }
+
+    #[test]
+    fn test_search_by_tags_accept_list_or_comma_separated() {
+        let filter: SearchBy = serde_json::from_str(r#"{"tags": "Northeast, daily,"}"#).unwrap();
+        assert_eq!(
+            filter.tags,
+            Some(vec!["northeast".to_string(), "daily".to_string()])
+        );
+
+        let filter: SearchBy = serde_json::from_str(r#"{"tags": ["temperature"]}"#).unwrap();
+        assert_eq!(filter.tags, Some(vec!["temperature".to_string()]));
+
+        let filter: SearchBy = serde_json::from_str(r#"{"tags": " , "}"#).unwrap();
+        assert_eq!(filter.tags, None);
+        let filter: SearchBy = serde_json::from_str("{}").unwrap();
+        assert_eq!(filter.tags, None);
+    }
+
+    #[test]
+    fn test_search_by_requires_every_tag() {
+        let tags = vec!["northeast".to_string(), "daily".to_string()];
+        assert!(SearchBy::default().matches_tags(&tags));
+        assert!(SearchBy::default().matches_tags(&[]));
+
+        let filter = SearchBy {
+            tags: Some(vec!["daily".to_string()]),
+            ..Default::default()
+        };
+        assert!(filter.matches_tags(&tags));
+        assert!(!filter.matches_tags(&[]));
+
+        let filter = SearchBy {
+            tags: Some(vec!["daily".to_string(), "weekly".to_string()]),
+            ..Default::default()
+        };
+        assert!(!filter.matches_tags(&tags));
+    }
+
+    #[test]
+    fn test_valid_tags() {
+        assert!(is_valid_tag("northeast"));
+        assert!(is_valid_tag("heat-wave-2026"));
+        assert!(!is_valid_tag(""));
+        assert!(!is_valid_tag("Daily"));
+        assert!(!is_valid_tag("heat wave"));
+        assert!(!is_valid_tag("-daily"));
+        assert!(!is_valid_tag(&"a".repeat(MAX_TAG_LEN + 1)));
+    }
+
+    #[test]
+    fn test_phase_serializes_snake_case() {
+        let json = serde_json::to_string(&UserFacingPhase::Entries).unwrap();
+        assert_eq!(json, "\"entries\"");
+    }
+
+    #[test]
+    fn test_next_deadline_follows_phase() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now);
+
+        let (kind, at) = competition.next_deadline(now).unwrap();
+        assert_eq!(kind, DeadlineKind::EntryClose);
+        assert_eq!(
+            at,
+            competition.event_submission.start_observation_date - TICKET_EXPIRY_BUFFER
+        );
+
+        competition.event_created_at = Some(now);
+        let (kind, at) = competition.next_deadline(now).unwrap();
+        assert_eq!(kind, DeadlineKind::SigningDeadline);
+        assert_eq!(at, competition.event_submission.start_observation_date);
+
+        competition.funding_settled_at = Some(now);
+        let (kind, at) = competition.next_deadline(now).unwrap();
+        assert_eq!(kind, DeadlineKind::ObservationEnd);
+        assert_eq!(at, competition.event_submission.end_observation_date);
+
+        competition.completed_at = Some(now);
+        assert!(competition.next_deadline(now).is_none());
+    }
+
+    #[test]
+    fn test_signing_deadline_overrides_ticket_expiry() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now);
+        assert_eq!(
+            competition.calculate_ticket_expiry().unwrap(),
+            competition.event_submission.start_observation_date - TICKET_EXPIRY_BUFFER
+        );
+
+        let deadline = now + Duration::hours(2);
+        competition.event_submission.signing_deadline = Some(deadline);
+        assert_eq!(competition.calculate_ticket_expiry().unwrap(), deadline);
+
+        competition.event_created_at = Some(now);
+        let (kind, at) = competition.next_deadline(now).unwrap();
+        assert_eq!(kind, DeadlineKind::SigningDeadline);
+        assert_eq!(at, deadline);
+    }
+
+    #[test]
+    fn test_passed_signing_deadline_is_too_late() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now);
+        competition.event_submission.signing_deadline = Some(now - Duration::minutes(1));
+
+        assert!(matches!(
+            competition.calculate_ticket_expiry(),
+            Err(Error::TooLateToSign(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_next_deadline_skips_passed_deadlines() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now);
+        competition.funding_settled_at = Some(now);
+
+        assert!(competition
+            .next_deadline(now + Duration::hours(25))
+            .is_none());
+    }
+
+    #[test]
+    fn test_oracle_event_info_points_at_oracle_event() {
+        let competition = test_competition(OffsetDateTime::now_utc());
+        let pubkey = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let info = OracleEventInfo::new(
+            &competition,
+            "https://oracle.example.com/",
+            Some(pubkey.to_string()),
+        );
+
+        assert_eq!(
+            info.oracle_event_url,
+            format!(
+                "https://oracle.example.com/oracle/events/{}",
+                competition.id
+            )
+        );
+        assert_eq!(info.oracle_pubkey.as_deref(), Some(pubkey));
+        assert!(info.event_announcement.is_none());
+        assert!(info.attestation.is_none());
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert!(json.get("oracle_url").is_none());
+        let unconfigured = OracleEventInfo::new(&competition, "https://oracle.example.com/", None);
+        assert!(serde_json::to_value(&unconfigured)
+            .unwrap()
+            .get("oracle_pubkey")
+            .is_none());
+    }
+
+    #[test]
+    fn test_invoice_amount_does_not_overflow() {
+        let mut competition = test_competition(OffsetDateTime::now_utc());
+        assert_eq!(competition.calculate_invoice_amount(), Sats(1100));
+
+        competition.event_submission.entry_fee = Sats(5);
+        assert_eq!(competition.calculate_invoice_amount(), Sats(6));
+
+        // Fee-free promo competitions charge just the entry fee
+        competition.event_submission.coordinator_fee_percentage = 0;
+        assert_eq!(competition.calculate_invoice_amount(), Sats(5));
+        competition.event_submission.coordinator_fee_percentage = 10;
+
+        // Shrunk from the property below, `entry_fee as u64 + fee` used to overflow
+        competition.event_submission.entry_fee = Sats::MAX;
+        assert_eq!(competition.calculate_invoice_amount(), Sats::MAX);
+        assert_eq!(competition.event_submission.checked_invoice_amount(), None);
+    }
+
+    #[test]
+    fn test_should_abort_only_counts_recent_errors() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now);
+        let error = |minutes_ago: i64| RecordedCompetitionError {
+            error: CompetitionError::FailedBroadcast("mempool full".to_string()),
+            occurred_at: now - Duration::minutes(minutes_ago),
+            state: "funding_broadcasted".to_string(),
+        };
+
+        // Spread over days, none close enough together to give up
+        competition.errors = (1..=10).map(|day| error(day * 24 * 60)).collect();
+        assert_eq!(competition.recent_error_count(now), 0);
+        assert!(!competition.should_abort());
+
+        competition
+            .errors
+            .extend((0..MAX_RECENT_ERRORS as i64).map(error));
+        assert!(!competition.should_abort());
+
+        competition.record_error(CompetitionError::FailedBroadcast("again".to_string()));
+        assert_eq!(competition.recent_error_count(now), MAX_RECENT_ERRORS + 1);
+        assert!(competition.should_abort());
+        let recorded = competition.errors.last().unwrap();
+        assert_eq!(recorded.state, competition.get_state().to_string());
+        assert!(recorded.occurred_at >= now);
+    }
+
+    #[test]
+    fn test_repeated_errors_are_recorded_once() {
+        let now = OffsetDateTime::now_utc();
+        let mut competition = test_competition(now);
+        let rejected = || CompetitionError::BroadcastRejected("bad-txns".to_string());
+
+        for _ in 0..(MAX_RECENT_ERRORS * 2) {
+            competition.record_repeated_error(rejected());
+        }
+        assert_eq!(competition.errors.len(), 1);
+        assert!(!competition.should_abort());
+
+        // A different error in between is kept, and the rejection is recorded again after it
+        competition.record_repeated_error(CompetitionError::FailedBroadcast("timeout".into()));
+        competition.record_repeated_error(rejected());
+        assert_eq!(competition.errors.len(), 3);
+    }
+
+    #[test]
+    fn test_errors_stored_without_timestamps_still_load() {
+        let json = r#"[
+            {"FailedCreateEvent": "oracle down"},
+            {
+                "error": {"FailedBroadcast": "mempool full"},
+                "occurred_at": "2026-03-01T12:00:00Z",
+                "state": "funding_confirmed"
+            }
+        ]"#;
+        let errors: Vec<RecordedCompetitionError> = serde_json::from_str(json).unwrap();
+
+        assert!(matches!(
+            errors[0].error,
+            CompetitionError::FailedCreateEvent(_)
+        ));
+        assert_eq!(errors[0].occurred_at, OffsetDateTime::UNIX_EPOCH);
+        assert_eq!(errors[0].state, "unknown");
+        assert!(matches!(
+            errors[1].error,
+            CompetitionError::FailedBroadcast(_)
+        ));
+        assert_eq!(errors[1].state, "funding_confirmed");
+
+        let round_trip: Vec<RecordedCompetitionError> =
+            serde_json::from_str(&serde_json::to_string(&errors).unwrap()).unwrap();
+        assert_eq!(round_trip[1].occurred_at, errors[1].occurred_at);
+    }
+
+    #[test]
+    fn test_payout_method_from_timestamps() {
+        let at = Some(OffsetDateTime::now_utc());
+        let cases = [
+            ((None, None, None), PayoutMethod::Unpaid),
+            ((at, None, None), PayoutMethod::LightningPaid),
+            ((None, at, None), PayoutMethod::OnChainSellback),
+            ((None, None, at), PayoutMethod::OnChainReclaim),
+            ((at, at, None), PayoutMethod::LightningPaid),
+            ((at, None, at), PayoutMethod::LightningPaid),
+            ((None, at, at), PayoutMethod::OnChainSellback),
+            ((at, at, at), PayoutMethod::LightningPaid),
+        ];
+
+        for ((paid_out_at, sellback_broadcasted_at, reclaimed_broadcasted_at), expected) in cases {
+            assert_eq!(
+                PayoutMethod::from_timestamps(
+                    paid_out_at,
+                    sellback_broadcasted_at,
+                    reclaimed_broadcasted_at
+                ),
+                expected,
+                "paid_out_at={:?} sellback={:?} reclaimed={:?}",
+                paid_out_at.is_some(),
+                sellback_broadcasted_at.is_some(),
+                reclaimed_broadcasted_at.is_some()
+            );
+        }
+        assert!(PayoutMethod::OnChainSellback.is_on_chain());
+        assert!(PayoutMethod::OnChainReclaim.is_on_chain());
+        assert!(!PayoutMethod::LightningPaid.is_on_chain());
+        assert!(!PayoutMethod::Unpaid.is_on_chain());
+    }
+
+    #[test]
+    fn test_funding_fee_rate_records_its_source() {
+        let estimated = FundingFeeRate::from_estimates(&HashMap::from([(1, 12.3), (6, 4.0)]), 1);
+        assert_eq!(estimated.sat_per_vb, 13);
+        assert_eq!(estimated.source, FeeRateSource::Estimated);
+        assert_eq!(estimated.confirmation_target, Some(1));
+        assert_eq!(estimated.fee_rate(), FeeRate::from_sat_per_vb_unchecked(13));
+
+        let fallback = FundingFeeRate::from_estimates(&HashMap::new(), 1);
+        assert_eq!(fallback.sat_per_vb, 1);
+        assert_eq!(fallback.source, FeeRateSource::MinimumFallback);
+        assert_eq!(fallback.confirmation_target, None);
+
+        let json = serde_json::to_string(&estimated).unwrap();
+        assert!(json.contains(r#""source":"estimated""#));
+        let round_trip: FundingFeeRate = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_trip.sat_per_vb, estimated.sat_per_vb);
+        assert_eq!(round_trip.source, estimated.source);
+    }
+
+    #[test]
+    fn test_nonce_seed_moved_after_funding_psbt_rebuild() {
+        use dlctix::bitcoin::{hashes::Hash, Txid};
+
+        let outpoint = |byte: u8, vout: u32| OutPoint {
+            txid: Txid::from_byte_array([byte; 32]),
+            vout,
+        };
+        let mut competition = test_competition(OffsetDateTime::now_utc());
+        competition.funding_outpoint = Some(outpoint(1, 0));
+        competition.public_nonces = Some(SigMap {
+            by_outcome: Default::default(),
+            by_win_condition: Default::default(),
+        });
+        // Nonces from before the seed was recorded are left alone
+        assert!(!competition.nonce_seed_moved());
+
+        competition.nonce_seed_outpoint = competition.funding_outpoint;
+        assert!(!competition.nonce_seed_moved());
+
+        competition.funding_outpoint = Some(outpoint(1, 1));
+        assert!(competition.nonce_seed_moved());
+        competition.funding_outpoint = Some(outpoint(2, 0));
+        assert!(competition.nonce_seed_moved());
+
+        competition.total_entries = 3;
+        competition.total_entry_nonces = 3;
+        competition.total_signed_entries = 3;
+        competition.reset_nonce_round();
+        assert!(!competition.nonce_seed_moved());
+        assert!(competition.public_nonces.is_none());
+        assert!(competition.nonce_seed_outpoint.is_none());
+        assert!(!competition.has_all_entry_nonces());
+        assert!(!competition.has_all_entry_partial_signatures());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_invoice_amount_covers_entry_fee(
+            entry_fee in 0u64..=u64::MAX,
+            coordinator_fee_percentage in 0usize..=100,
+        ) {
+            let mut competition = test_competition(OffsetDateTime::now_utc());
+            competition.event_submission.entry_fee = Sats(entry_fee);
+            competition.event_submission.coordinator_fee_percentage = coordinator_fee_percentage;
+
+            let amount = competition.calculate_invoice_amount().to_sat();
+            let exact_fee = entry_fee as u128 * coordinator_fee_percentage as u128;
+            proptest::prop_assert!(amount >= entry_fee);
+            if amount < u64::MAX {
+                let fee = (amount - entry_fee) as u128;
+                // Within half a sat of the exact fee
+                proptest::prop_assert!(fee * 100 + 50 > exact_fee);
+                proptest::prop_assert!(fee * 100 <= exact_fee + 50);
+            }
+        }
+    }
 }
@@ -0,0 +1,400 @@
+//! Operator actions shared by the admin routes and the coordinator CLI. They only need the
+//! competition store, so the CLI can run them without starting the chain, lightning or oracle
+//! clients.
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use dlctix::secp::Point;
+
+use super::{
+    reencrypt_session_secret, Competition, CompetitionState, CompetitionStore, CoordinatorKeyring,
+    EntryPayout, EntryStatus, PayoutFailureCount, SettlementProgress, Ticket, UserEntry,
+};
+use crate::domain::Error;
+
+/// One line of `competition list`
+#[derive(Debug, Clone, Serialize)]
+pub struct CompetitionSummary {
+    pub id: Uuid,
+    pub state: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub total_entries: u64,
+    pub total_paid_entries: u64,
+    pub total_comped_entries: u64,
+    pub total_allowed_entries: u64,
+    pub settlement_progress: SettlementProgress,
+    pub errors: usize,
+}
+
+impl From<&Competition> for CompetitionSummary {
+    fn from(competition: &Competition) -> Self {
+        Self {
+            id: competition.id,
+            state: competition.get_state().to_string(),
+            created_at: competition.created_at,
+            total_entries: competition.total_entries,
+            total_paid_entries: competition.total_paid_entries,
+            total_comped_entries: competition.total_comped_entries,
+            total_allowed_entries: competition.event_submission.total_allowed_entries as u64,
+            settlement_progress: competition.settlement_progress,
+            errors: competition.errors.len(),
+        }
+    }
+}
+
+/// A state change an operator forced on a competition, outside the automated flow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompetitionOverride {
+    pub id: Uuid,
+    pub competition_id: Uuid,
+    pub action: String,
+    pub from_state: String,
+    pub to_state: String,
+    pub reason: String,
+    /// Admin pubkey, or `anonymous` when no admin allow-list is configured
+    pub admin: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+fn map_not_found(kind: &str, id: Uuid) -> impl FnOnce(sqlx::Error) -> Error + '_ {
+    move |e| match e {
+        sqlx::Error::RowNotFound => Error::NotFound(format!("{} {} not found", kind, id)),
+        e => Error::DbError(e),
+    }
+}
+
+pub async fn list_competitions(store: &CompetitionStore) -> Result<Vec<CompetitionSummary>, Error> {
+    let competitions = store.get_competitions(false, false).await?;
+    Ok(competitions.iter().map(CompetitionSummary::from).collect())
+}
+
+pub async fn show_competition(
+    store: &CompetitionStore,
+    competition_id: Uuid,
+) -> Result<Competition, Error> {
+    store
+        .get_competition(competition_id)
+        .await
+        .map_err(map_not_found("competition", competition_id))
+}
+
+/// Competitions can be cancelled until their funding transaction is broadcast, after that the
+/// funds are locked in the contract and only the chain can settle them
+pub fn check_cancellable(competition: &Competition) -> Result<(), Error> {
+    if competition.is_cancelled() {
+        return Err(Error::BadRequest(format!(
+            "competition {} is already cancelled",
+            competition.id
+        )));
+    }
+    if competition.is_completed() {
+        return Err(Error::BadRequest(format!(
+            "competition {} has already completed",
+            competition.id
+        )));
+    }
+    if competition.is_funding_broadcasted() {
+        return Err(Error::BadRequest(format!(
+            "competition {} funding transaction was already broadcast",
+            competition.id
+        )));
+    }
+    Ok(())
+}
+
+/// Only failed competitions that haven't been cancelled can be retried
+pub fn check_retryable(competition: &Competition) -> Result<(), Error> {
+    if competition.is_cancelled() {
+        return Err(Error::BadRequest(format!(
+            "competition {} is cancelled",
+            competition.id
+        )));
+    }
+    if !competition.is_failed() {
+        return Err(Error::BadRequest(format!(
+            "competition {} has not failed",
+            competition.id
+        )));
+    }
+    Ok(())
+}
+
+/// Only competitions whose outcome is on chain can be force completed, before that the contract
+/// still has to settle through the automated flow
+pub fn check_force_completable(competition: &Competition) -> Result<(), Error> {
+    match competition.get_state() {
+        CompetitionState::OutcomeBroadcasted | CompetitionState::DeltaBroadcasted => Ok(()),
+        state => Err(Error::BadRequest(format!(
+            "competition {} is {}, only competitions in outcome_broadcasted or delta_broadcasted can be force completed",
+            competition.id, state
+        ))),
+    }
+}
+
+/// Winning entries the coordinator still has to broadcast a reclaim transaction for: not paid
+/// out, not closed through a sellback and not reclaimed yet
+pub fn pending_reclaim_entries(
+    competition: &Competition,
+    entries: &[UserEntry],
+) -> Result<Vec<Uuid>, Error> {
+    let signed_contract = competition.signed_contract.as_ref().ok_or_else(|| {
+        Error::BadRequest(format!(
+            "competition {} has no signed contract",
+            competition.id
+        ))
+    })?;
+    let outcome = competition
+        .get_current_outcome()
+        .map_err(|e| Error::BadRequest(e.to_string()))?;
+    let Some(winners) = signed_contract.params().outcome_payouts.get(&outcome) else {
+        return Ok(vec![]);
+    };
+
+    Ok(winners
+        .keys()
+        .filter_map(|player_index| signed_contract.params().players.get(*player_index))
+        .filter_map(|player| {
+            entries.iter().find(|entry| {
+                Point::from_hex(&entry.ephemeral_pubkey).is_ok_and(|pubkey| pubkey == player.pubkey)
+            })
+        })
+        .filter(|entry| {
+            entry.paid_out_at.is_none()
+                && entry.sellback_broadcasted_at.is_none()
+                && entry.reclaimed_broadcasted_at.is_none()
+        })
+        .map(|entry| entry.id)
+        .collect())
+}
+
+/// Mark a competition cancelled, returns the state it was in beforehand
+pub async fn cancel_competition(
+    store: &CompetitionStore,
+    competition_id: Uuid,
+) -> Result<(Competition, String), Error> {
+    let mut competition = show_competition(store, competition_id).await?;
+    check_cancellable(&competition)?;
+    let previous_state = competition.get_state().to_string();
+
+    competition.cancelled_at = Some(OffsetDateTime::now_utc());
+    store
+        .update_competitions(std::slice::from_mut(&mut competition))
+        .await?;
+    Ok((competition, previous_state))
+}
+
+/// Mark a competition completed after the operator paid its winners by hand, recording the
+/// override. Returns the state the competition was in beforehand.
+pub async fn force_complete_competition(
+    store: &CompetitionStore,
+    competition_id: Uuid,
+    reason: &str,
+    admin: &str,
+) -> Result<(Competition, String), Error> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(Error::BadRequest(
+            "a reason is required to force complete a competition".into(),
+        ));
+    }
+
+    let mut competition = show_competition(store, competition_id).await?;
+    check_force_completable(&competition)?;
+    let entries = store
+        .get_competition_entries(competition_id, vec![EntryStatus::Paid])
+        .await?;
+    let pending = pending_reclaim_entries(&competition, &entries)?;
+    if !pending.is_empty() {
+        return Err(Error::BadRequest(format!(
+            "competition {} still has reclaim transactions to broadcast for entries {}",
+            competition_id,
+            pending
+                .iter()
+                .map(Uuid::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    let previous_state = competition.get_state().to_string();
+    let now = OffsetDateTime::now_utc();
+    competition.completed_at = Some(now);
+    let competition_override = CompetitionOverride {
+        id: Uuid::now_v7(),
+        competition_id,
+        action: String::from("force_complete"),
+        from_state: previous_state.clone(),
+        to_state: competition.get_state().to_string(),
+        reason: reason.to_string(),
+        admin: admin.to_string(),
+        created_at: now,
+    };
+    if !store
+        .force_complete_competition(competition_override)
+        .await?
+    {
+        return Err(Error::BadRequest(format!(
+            "competition {} has already completed",
+            competition_id
+        )));
+    }
+    Ok((competition, previous_state))
+}
+
+/// Clear a competition's failure so the background processor picks it up again from the state
+/// its timestamps describe
+pub async fn retry_competition(
+    store: &CompetitionStore,
+    competition_id: Uuid,
+) -> Result<Competition, Error> {
+    let mut competition = show_competition(store, competition_id).await?;
+    check_retryable(&competition)?;
+
+    competition.failed_at = None;
+    competition.errors.clear();
+    store
+        .update_competitions(std::slice::from_mut(&mut competition))
+        .await?;
+    Ok(competition)
+}
+
+/// Release a stuck reservation so the ticket can be sold again. Paid tickets are refused, their
+/// hold invoice has to be cancelled first or the player's payment would be left dangling.
+pub async fn release_ticket(store: &CompetitionStore, ticket_id: Uuid) -> Result<Ticket, Error> {
+    let ticket = store
+        .get_ticket(ticket_id)
+        .await
+        .map_err(map_not_found("ticket", ticket_id))?;
+
+    if ticket.entry_id.is_some() {
+        return Err(Error::BadRequest(format!(
+            "ticket {} is already used by an entry",
+            ticket_id
+        )));
+    }
+    if ticket.paid_at.is_some() || ticket.settled_at.is_some() {
+        return Err(Error::BadRequest(format!(
+            "ticket {} has been paid, cancel its invoice first",
+            ticket_id
+        )));
+    }
+    if ticket.reserved_at.is_none() {
+        return Err(Error::BadRequest(format!(
+            "ticket {} is not reserved",
+            ticket_id
+        )));
+    }
+
+    if !store.clear_ticket_reservation(ticket_id).await? {
+        return Err(Error::BadRequest(format!(
+            "ticket {} could not be released",
+            ticket_id
+        )));
+    }
+    Ok(store.get_ticket(ticket_id).await?)
+}
+
+pub async fn list_pending_payouts(store: &CompetitionStore) -> Result<Vec<EntryPayout>, Error> {
+    Ok(store.get_all_pending_payouts().await?)
+}
+
+pub async fn list_failed_payouts(
+    store: &CompetitionStore,
+) -> Result<Vec<PayoutFailureCount>, Error> {
+    Ok(store.get_payout_failure_counts().await?)
+}
+
+/// Result of `key rotate`
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyRotationReport {
+    pub key_id: String,
+    /// Competitions whose keymeld session secret was re-encrypted to `key_id`
+    pub reencrypted_sessions: Vec<Uuid>,
+    /// Competitions whose session secret already was on `key_id`
+    pub unchanged_sessions: usize,
+}
+
+/// Re-encrypt every stored keymeld session secret to the newest coordinator key. Contracts stay
+/// on the key their competition was created under, only the at-rest encryption moves.
+pub async fn reencrypt_keymeld_sessions(
+    store: &CompetitionStore,
+    keys: &CoordinatorKeyring,
+) -> Result<KeyRotationReport, Error> {
+    let mut report = KeyRotationReport {
+        key_id: keys.newest_id().to_string(),
+        reencrypted_sessions: vec![],
+        unchanged_sessions: 0,
+    };
+    for (competition_id, stored_session) in store.get_keymeld_sessions().await? {
+        match reencrypt_session_secret(keys, &stored_session)? {
+            Some(reencrypted) => {
+                store
+                    .store_keymeld_session(competition_id, &reencrypted)
+                    .await?;
+                report.reencrypted_sessions.push(competition_id);
+            }
+            None => report.unchanged_sessions += 1,
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::CreateEvent;
+    use time::Duration;
+
+    fn test_competition() -> Competition {
+        let start = OffsetDateTime::now_utc() + Duration::hours(6);
+        Competition::new(&CreateEvent::fixture(start))
+    }
+
+    #[test]
+    fn test_cancel_refused_once_funding_broadcast() {
+        let mut competition = test_competition();
+        assert!(check_cancellable(&competition).is_ok());
+
+        competition.funding_broadcasted_at = Some(OffsetDateTime::now_utc());
+        assert!(matches!(
+            check_cancellable(&competition),
+            Err(Error::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_retry_requires_failed_competition() {
+        let mut competition = test_competition();
+        assert!(check_retryable(&competition).is_err());
+
+        competition.failed_at = Some(OffsetDateTime::now_utc());
+        assert!(check_retryable(&competition).is_ok());
+
+        competition.cancelled_at = Some(OffsetDateTime::now_utc());
+        assert!(check_retryable(&competition).is_err());
+    }
+
+    #[test]
+    fn test_force_complete_requires_outcome_on_chain() {
+        let mut competition = test_competition();
+        assert!(check_force_completable(&competition).is_err());
+
+        competition.funding_broadcasted_at = Some(OffsetDateTime::now_utc());
+        assert!(check_force_completable(&competition).is_err());
+
+        competition.outcome_broadcasted_at = Some(OffsetDateTime::now_utc());
+        assert!(check_force_completable(&competition).is_ok());
+
+        competition.delta_broadcasted_at = Some(OffsetDateTime::now_utc());
+        assert!(check_force_completable(&competition).is_ok());
+
+        competition.completed_at = Some(OffsetDateTime::now_utc());
+        assert!(matches!(
+            check_force_completable(&competition),
+            Err(Error::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,611 @@
+use anyhow::anyhow;
+use coordinator_core::Sats;
+use dlctix::{secp::Point, PayoutWeights, PlayerIndex};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use super::{Competition, EntryPayout, PayoutStatus, UserEntry};
+
+/// Percentage of the pool paid to each winning place, empty when the number of places isn't
+/// supported
+pub fn get_percentage_weights(num_winners: usize) -> Vec<u64> {
+    match num_winners {
+        1 => vec![100],
+        2 => vec![60, 40],
+        3 => vec![45, 35, 20],
+        4 => vec![42, 30, 18, 10],
+        5 => vec![40, 27, 16, 9, 8],
+        _ => vec![],
+    }
+}
+
+/// How entries finishing on the same score are paid for the places they tie on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TiePolicy {
+    /// The oracle orders tied entries by when they were made, then by entry id, so every
+    /// outcome is a strict ranking and each place pays its own weight
+    #[default]
+    Tiebreak,
+    /// Tied entries share the weights of the places they span evenly. The oracle announces an
+    /// outcome for every way the entries can tie, many more than with `Tiebreak`.
+    Split,
+}
+
+/// Most attestation outcomes a `Split` competition can have, keygen needs a subset per outcome
+pub const MAX_SPLIT_OUTCOMES: u128 = 20_000;
+
+/// Entries in finishing order, grouped by score. Every group of a strict ranking has one entry.
+pub type Ranking = Vec<Vec<usize>>;
+
+/// Every ranking of `num_players` entries over `places` paid places the oracle can attest, in
+/// outcome order. Strict rankings come first in `generate_ranking_permutations` order so both
+/// policies agree on their indices, `Split` follows them with the rankings where entries tie.
+/// The last one is always "refund all", every entry in a single group.
+pub fn generate_rankings(num_players: usize, places: usize, tie_policy: TiePolicy) -> Vec<Ranking> {
+    let mut rankings: Vec<Ranking> = (0..num_players)
+        .permutations(places)
+        .map(|ranking| ranking.into_iter().map(|entry| vec![entry]).collect())
+        .collect();
+
+    if tie_policy == TiePolicy::Split {
+        let entries: Vec<usize> = (0..num_players).collect();
+        push_tied_rankings(&entries, places, &mut Vec::new(), &mut rankings);
+    }
+
+    rankings.push(vec![(0..num_players).collect()]);
+    rankings
+}
+
+/// Rankings of `remaining` filling `places_left` that start with `prefix` and tie somewhere,
+/// leaving out the single group of every entry since that's the refund outcome
+fn push_tied_rankings(
+    remaining: &[usize],
+    places_left: usize,
+    prefix: &mut Ranking,
+    rankings: &mut Vec<Ranking>,
+) {
+    for size in 1..=remaining.len() {
+        for group in remaining.iter().copied().combinations(size) {
+            let rest: Vec<usize> = remaining
+                .iter()
+                .copied()
+                .filter(|entry| !group.contains(entry))
+                .collect();
+            let refund = prefix.is_empty() && rest.is_empty();
+            prefix.push(group);
+            if size < places_left {
+                push_tied_rankings(&rest, places_left - size, prefix, rankings);
+            } else if !refund && prefix.iter().any(|group| group.len() > 1) {
+                rankings.push(prefix.clone());
+            }
+            prefix.pop();
+        }
+    }
+}
+
+/// Number of rankings `generate_rankings` gives under `TiePolicy::Split`, without building
+/// them. Counts ordered groupings of the entries covering the paid places, the refund outcome
+/// included as the grouping with a single group.
+pub fn split_ranking_count(num_players: usize, places: usize) -> u128 {
+    weak_ranking_count(num_players, places)
+}
+
+fn weak_ranking_count(remaining: usize, places_left: usize) -> u128 {
+    let mut count: u128 = 0;
+    let mut choose: u128 = 1;
+    for size in 1..=remaining {
+        // remaining choose size
+        choose = choose * (remaining - size + 1) as u128 / size as u128;
+        let rest = if size >= places_left {
+            1
+        } else {
+            weak_ranking_count(remaining - size, places_left - size)
+        };
+        count = count.saturating_add(choose.saturating_mul(rest));
+    }
+    count
+}
+
+/// Weights by entry index for a ranking. Each group shares the weights of the paid places it
+/// spans evenly, everything is scaled by the product of the group sizes so every share is
+/// whole. A strict ranking gets the percentage weights as they are.
+pub fn ranking_weights(
+    ranking: &Ranking,
+    percentage_weights: &[u64],
+) -> Result<Vec<(usize, u64)>, anyhow::Error> {
+    let places = percentage_weights.len();
+    if ranking.iter().map(Vec::len).sum::<usize>() < places {
+        return Err(anyhow!("ranking doesn't fill {} places", places));
+    }
+
+    let scale: u64 = ranking.iter().map(|group| group.len() as u64).product();
+    let mut weights = Vec::new();
+    let mut place = 0;
+    for group in ranking {
+        if place >= places {
+            break;
+        }
+        let spanned: u64 = percentage_weights[place..places.min(place + group.len())]
+            .iter()
+            .sum();
+        let share = spanned * scale / group.len() as u64;
+        weights.extend(group.iter().map(|entry| (*entry, share)));
+        place += group.len();
+    }
+
+    let total_weight: u64 = weights.iter().map(|(_, weight)| weight).sum();
+    if total_weight != 100 * scale {
+        return Err(anyhow!(
+            "total weight should be {}, got {}",
+            100 * scale,
+            total_weight
+        ));
+    }
+    Ok(weights)
+}
+
+/// The entry ids a ranking pays, with `entry_ids` in contract player order. Ids within a group
+/// are sorted to compare with `scoring::winning_groups`. `None` when the ranking names an entry
+/// that isn't there.
+pub fn ranking_entry_ids(ranking: &Ranking, entry_ids: &[Uuid]) -> Option<Vec<Vec<Uuid>>> {
+    ranking
+        .iter()
+        .map(|group| {
+            let mut ids = group
+                .iter()
+                .map(|entry| entry_ids.get(*entry).copied())
+                .collect::<Option<Vec<Uuid>>>()?;
+            ids.sort();
+            Some(ids)
+        })
+        .collect()
+}
+
+/// Weights splitting the pool equally between every player, used for the refund and expiry
+/// outcomes. The remainder goes one point at a time to early indices to keep the total at 100.
+pub fn equal_weights(num_players: usize) -> PayoutWeights {
+    let player_count = num_players as u64;
+    let base_weight = 100 / player_count;
+    let remainder = 100 % player_count;
+
+    (0..num_players)
+        .map(|i| {
+            let weight = if (i as u64) < remainder {
+                base_weight + 1
+            } else {
+                base_weight
+            };
+            (i, weight)
+        })
+        .collect()
+}
+
+/// Split `funding_value` between the players of an outcome in proportion to their
+/// weights. Shares are rounded down and the sats left over go one at a time to the largest
+/// remainders, ties going to the lower player index, so the shares always add up to the
+/// funding value exactly.
+pub fn split_payout(funding_value: Sats, weights: &PayoutWeights) -> BTreeMap<PlayerIndex, Sats> {
+    let total_weight: u128 = weights.values().map(|weight| *weight as u128).sum();
+    if total_weight == 0 {
+        return weights
+            .keys()
+            .map(|player_index| (*player_index, Sats::ZERO))
+            .collect();
+    }
+
+    let mut shares = BTreeMap::new();
+    let mut remainders = Vec::with_capacity(weights.len());
+    let mut allocated: u128 = 0;
+    for (player_index, weight) in weights {
+        let scaled = funding_value.to_sat() as u128 * *weight as u128;
+        let share = scaled / total_weight;
+        allocated += share;
+        // A share never exceeds the funding value, so it fits back in a u64
+        shares.insert(*player_index, Sats(share as u64));
+        remainders.push((scaled % total_weight, *player_index));
+    }
+
+    // Larger remainder first, lower player index first among equals
+    remainders.sort_by(|(a_rem, a_idx), (b_rem, b_idx)| b_rem.cmp(a_rem).then(a_idx.cmp(b_idx)));
+    let leftover = funding_value.to_sat() as u128 - allocated;
+    for (_, player_index) in remainders.into_iter().take(leftover as usize) {
+        if let Some(share) = shares.get_mut(&player_index) {
+            *share = share.saturating_add(Sats(1));
+        }
+    }
+
+    shares
+}
+
+/// What a hypothetical entry would be paid under each kind of outcome once the competition
+/// fills, nothing about the entry is stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryPreview {
+    pub competition_id: Uuid,
+    /// Entry fee plus the coordinator fee, what the ticket invoice will ask for
+    pub invoice_amount_sats: u64,
+    /// Funding value of the contract once every entry is in
+    pub total_pool_sats: u64,
+    pub total_allowed_entries: usize,
+    pub outcomes: Vec<PreviewOutcome>,
+    /// Average payout assuming every ranking of the players is equally likely, the coordinator
+    /// has no probabilities for the oracle's outcomes
+    pub expected_value_sats: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum PreviewOutcome {
+    /// The entry finishes in this paid place, 1 being first
+    Place { place: usize, payout_sats: u64 },
+    /// The entry finishes outside the paid places
+    Unplaced { payout_sats: u64 },
+    /// The oracle attests the "refund all" outcome
+    Refund { payout_sats: u64 },
+    /// The contract expires without an attestation
+    Expiry { payout_sats: u64 },
+}
+
+impl EntryPreview {
+    /// Project payouts with the same weights `generate_payouts` gives the contract, with the
+    /// previewed entry as the last player
+    pub fn new(competition: &Competition) -> Result<Self, anyhow::Error> {
+        let num_players = competition.event_submission.total_allowed_entries;
+        let places = competition.event_submission.number_of_places_win;
+        if num_players == 0 || places > num_players {
+            return Err(anyhow!(
+                "competition pays {} places with {} entries",
+                places,
+                num_players
+            ));
+        }
+        let percentage_weights = get_percentage_weights(places);
+        if percentage_weights.len() != places {
+            return Err(anyhow!("No payout weights for {} winning places", places));
+        }
+
+        let total_pool_sats = competition
+            .contract_parameters
+            .as_ref()
+            .map(|params| params.funding_value.to_sat())
+            .unwrap_or(competition.event_submission.total_competition_pool.to_sat());
+        let player = num_players - 1;
+        let payout_for = |weights: &PayoutWeights| {
+            split_payout(Sats(total_pool_sats), weights)
+                .get(&player)
+                .map(|share| share.to_sat())
+                .unwrap_or(0)
+        };
+
+        let mut outcomes = Vec::with_capacity(places + 3);
+        let mut placed_total: u64 = 0;
+        for place in 0..places {
+            // Other players fill the remaining places in index order
+            let mut others = 0..player;
+            let weights: PayoutWeights = percentage_weights
+                .iter()
+                .enumerate()
+                .filter_map(|(rank, weight)| {
+                    let index = if rank == place {
+                        player
+                    } else {
+                        others.next()?
+                    };
+                    Some((index, *weight))
+                })
+                .collect();
+            let payout_sats = payout_for(&weights);
+            placed_total += payout_sats;
+            outcomes.push(PreviewOutcome::Place {
+                place: place + 1,
+                payout_sats,
+            });
+        }
+        if places < num_players {
+            outcomes.push(PreviewOutcome::Unplaced { payout_sats: 0 });
+        }
+
+        let refund_sats = payout_for(&equal_weights(num_players));
+        outcomes.push(PreviewOutcome::Refund {
+            payout_sats: refund_sats,
+        });
+        outcomes.push(PreviewOutcome::Expiry {
+            payout_sats: refund_sats,
+        });
+
+        Ok(EntryPreview {
+            competition_id: competition.id,
+            invoice_amount_sats: competition.calculate_invoice_amount().to_sat(),
+            total_pool_sats,
+            total_allowed_entries: num_players,
+            outcomes,
+            // A uniformly random ranking puts the entry in each place with probability 1/n
+            expected_value_sats: placed_total / num_players as u64,
+        })
+    }
+}
+
+/// Player index and share of the pool owed to the entry with this ephemeral pubkey under the
+/// attested outcome, `None` before attestation or when the entry didn't win anything
+pub fn entry_payout(
+    competition: &Competition,
+    ephemeral_pubkey_hex: &str,
+) -> Option<(PlayerIndex, Sats)> {
+    let contract_params = competition.contract_parameters.as_ref()?;
+    let outcome = competition.get_current_outcome().ok()?;
+    let outcome_weights = contract_params.outcome_payouts.get(&outcome)?;
+    let ephemeral_pubkey = Point::from_hex(ephemeral_pubkey_hex).ok()?;
+
+    let player_index = *outcome_weights.keys().find(|player_index| {
+        contract_params
+            .players
+            .get(**player_index)
+            .is_some_and(|player| player.pubkey == ephemeral_pubkey)
+    })?;
+
+    let total_pool = Sats(contract_params.funding_value.to_sat());
+    let share = *split_payout(total_pool, outcome_weights).get(&player_index)?;
+    Some((player_index, share))
+}
+
+/// One of a user's entries that won a share of the pool, and how far its lightning payout got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EligiblePayout {
+    pub competition_id: Uuid,
+    pub entry_id: Uuid,
+    pub player_index: PlayerIndex,
+    pub payout_amount_sats: Sats,
+    /// A payout invoice is in flight or was paid, a new one is rejected
+    pub invoice_submitted: bool,
+    /// Status of the latest payout attempt, `None` until an invoice is submitted
+    pub payout_status: Option<PayoutStatus>,
+}
+
+impl EligiblePayout {
+    /// `None` when the entry isn't owed anything under the competition's attested outcome.
+    /// `payouts` are the entry's payout attempts in any order.
+    pub fn new(
+        competition: &Competition,
+        entry: &UserEntry,
+        payouts: &[EntryPayout],
+    ) -> Option<Self> {
+        if !competition.is_attested() || !competition.is_outcome_broadcasted() {
+            return None;
+        }
+        let (player_index, payout_amount_sats) =
+            entry_payout(competition, &entry.ephemeral_pubkey)?;
+        let (invoice_submitted, payout_status) = payout_progress(payouts);
+
+        Some(EligiblePayout {
+            competition_id: competition.id,
+            entry_id: entry.id,
+            player_index,
+            payout_amount_sats,
+            invoice_submitted,
+            payout_status,
+        })
+    }
+}
+
+/// Whether a payout is in flight or done, and the status of the latest attempt
+fn payout_progress(payouts: &[EntryPayout]) -> (bool, Option<PayoutStatus>) {
+    let invoice_submitted = payouts
+        .iter()
+        .any(|payout| !matches!(payout.payout_status, PayoutStatus::Failed));
+    let latest = payouts.iter().max_by_key(|payout| payout.initiated_at);
+    (
+        invoice_submitted,
+        latest.map(|payout| payout.payout_status.clone()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::CreateEvent;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_ranking_entry_ids_follow_player_order() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::now_v7()).collect();
+        let rankings = generate_rankings(3, 2, TiePolicy::Split);
+
+        assert_eq!(
+            ranking_entry_ids(&rankings[0], &ids),
+            Some(vec![vec![ids[0]], vec![ids[1]]])
+        );
+        let tied = rankings
+            .iter()
+            .find(|ranking| ranking == &&vec![vec![0], vec![1, 2]])
+            .expect("split rankings include a tie for second");
+        let mut tied_ids = vec![ids[1], ids[2]];
+        tied_ids.sort();
+        assert_eq!(
+            ranking_entry_ids(tied, &ids),
+            Some(vec![vec![ids[0]], tied_ids])
+        );
+        assert_eq!(ranking_entry_ids(&vec![vec![3]], &ids), None);
+    }
+
+    #[test]
+    fn test_split_payout_hands_out_rounding_dust() {
+        // Splitting by `pool * weight / 100` used to leave 1 sat of this pool unpaid
+        let weights = BTreeMap::from([(0, 45), (1, 35), (2, 20)]);
+        let shares = split_payout(Sats(1_001), &weights);
+        assert_eq!(
+            shares,
+            BTreeMap::from([(0, Sats(451)), (1, Sats(350)), (2, Sats(200))])
+        );
+
+        let equal = BTreeMap::from([(0, 34), (1, 33), (2, 33)]);
+        assert_eq!(
+            Sats::checked_sum(split_payout(Sats(100), &equal).into_values()),
+            Some(Sats(100))
+        );
+    }
+
+    #[test]
+    fn test_entry_preview_pays_each_place() {
+        let start = time::OffsetDateTime::now_utc() + time::Duration::hours(6);
+        let competition = Competition::new(&CreateEvent {
+            number_of_values_per_entry: 1,
+            number_of_places_win: 2,
+            ..CreateEvent::fixture(start)
+        });
+
+        let preview = EntryPreview::new(&competition).unwrap();
+        assert_eq!(preview.invoice_amount_sats, 1100);
+        assert_eq!(
+            preview.outcomes,
+            vec![
+                PreviewOutcome::Place {
+                    place: 1,
+                    payout_sats: 1800
+                },
+                PreviewOutcome::Place {
+                    place: 2,
+                    payout_sats: 1200
+                },
+                PreviewOutcome::Unplaced { payout_sats: 0 },
+                PreviewOutcome::Refund { payout_sats: 990 },
+                PreviewOutcome::Expiry { payout_sats: 990 },
+            ]
+        );
+        assert_eq!(preview.expected_value_sats, 1000);
+    }
+
+    #[test]
+    fn test_payout_progress_follows_latest_attempt() {
+        let attempt = |minutes: i64, status: PayoutStatus| EntryPayout {
+            id: Uuid::now_v7(),
+            entry_id: Uuid::nil(),
+            payout_status: status,
+            payout_payment_request: String::new(),
+            payout_amount_sats: Sats(1_000),
+            initiated_at: time::OffsetDateTime::UNIX_EPOCH + time::Duration::minutes(minutes),
+            succeed_at: None,
+            failed_at: None,
+            error: None,
+        };
+
+        assert!(matches!(payout_progress(&[]), (false, None)));
+        assert!(matches!(
+            payout_progress(&[attempt(1, PayoutStatus::Failed)]),
+            (false, Some(PayoutStatus::Failed))
+        ));
+        // A retry after a failure is what the user sees, in whatever order the rows come back
+        assert!(matches!(
+            payout_progress(&[
+                attempt(2, PayoutStatus::Pending),
+                attempt(1, PayoutStatus::Failed)
+            ]),
+            (true, Some(PayoutStatus::Pending))
+        ));
+        assert!(matches!(
+            payout_progress(&[
+                attempt(1, PayoutStatus::Failed),
+                attempt(2, PayoutStatus::Succeeded)
+            ]),
+            (true, Some(PayoutStatus::Succeeded))
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_split_payout_conserves_sats(
+            funding_value_sats in 0u64..=2_100_000_000_000_000,
+            weights in prop::collection::btree_map(0usize..16, 0u64..=100, 1..8),
+        ) {
+            let funding_value = Sats(funding_value_sats);
+            let shares = split_payout(funding_value, &weights);
+            let total_weight: u64 = weights.values().sum();
+
+            prop_assert_eq!(shares.len(), weights.len());
+            if total_weight > 0 {
+                prop_assert_eq!(Sats::checked_sum(shares.values().copied()), Some(funding_value));
+            }
+            for (player_index, share) in &shares {
+                prop_assert!(*share <= funding_value);
+                if weights[player_index] == 0 {
+                    prop_assert_eq!(*share, Sats::ZERO);
+                }
+            }
+            // Deterministic for the same input
+            prop_assert_eq!(shares, split_payout(funding_value, &weights));
+        }
+    }
+
+    fn tied_shares(ranking: Ranking, places: usize, pool: u64) -> BTreeMap<usize, Sats> {
+        let weights = ranking_weights(&ranking, &get_percentage_weights(places)).unwrap();
+        split_payout(Sats(pool), &weights.into_iter().collect())
+    }
+
+    #[test]
+    fn test_two_way_tie_at_the_payout_boundary_splits_the_place() {
+        // 60/40 over two places, entries 1 and 2 tie for second
+        assert_eq!(
+            tied_shares(vec![vec![0], vec![1, 2]], 2, 1_000),
+            BTreeMap::from([(0, Sats(600)), (1, Sats(200)), (2, Sats(200))])
+        );
+        // 45/35/20 over three places, entries 0 and 1 tie for second and third
+        assert_eq!(
+            tied_shares(vec![vec![2], vec![0, 1]], 3, 1_000),
+            BTreeMap::from([(0, Sats(275)), (1, Sats(275)), (2, Sats(450))])
+        );
+    }
+
+    #[test]
+    fn test_three_way_tie_at_the_payout_boundary_splits_the_place() {
+        assert_eq!(
+            tied_shares(vec![vec![0], vec![1, 2, 3]], 2, 900),
+            BTreeMap::from([
+                (0, Sats(540)),
+                (1, Sats(120)),
+                (2, Sats(120)),
+                (3, Sats(120))
+            ])
+        );
+        // Three entries tied for first of two places share both of them
+        assert_eq!(
+            tied_shares(vec![vec![1, 2, 3]], 2, 900),
+            BTreeMap::from([(1, Sats(300)), (2, Sats(300)), (3, Sats(300))])
+        );
+        // Rounding dust still goes out, lower index first
+        assert_eq!(
+            tied_shares(vec![vec![3], vec![0, 1, 2]], 2, 1_000),
+            BTreeMap::from([
+                (0, Sats(134)),
+                (1, Sats(133)),
+                (2, Sats(133)),
+                (3, Sats(600))
+            ])
+        );
+    }
+
+    #[test]
+    fn test_split_rankings_extend_the_tiebreak_ones() {
+        for (num_players, places) in [(3, 1), (4, 2), (5, 3), (5, 5)] {
+            let tiebreak = generate_rankings(num_players, places, TiePolicy::Tiebreak);
+            let split = generate_rankings(num_players, places, TiePolicy::Split);
+            assert_eq!(
+                split.len() as u128,
+                split_ranking_count(num_players, places)
+            );
+
+            // Strict rankings keep their outcome index, refund all stays last
+            let strict = tiebreak.len() - 1;
+            assert_eq!(split[..strict], tiebreak[..strict]);
+            assert_eq!(split.last(), tiebreak.last());
+            assert!(split[strict..split.len() - 1]
+                .iter()
+                .all(|ranking| ranking.iter().any(|group| group.len() > 1)));
+
+            let unique: std::collections::BTreeSet<_> = split.iter().collect();
+            assert_eq!(unique.len(), split.len());
+        }
+        assert_eq!(generate_rankings(3, 1, TiePolicy::Split).len(), 7);
+    }
+}
@@ -0,0 +1,122 @@
+//! The player order the keymeld subset definitions were built with at competition creation,
+//! checked against the order entries are given in the contract so signatures are never made
+//! for the wrong player indices.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+use super::UserEntry;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerSlot {
+    pub player_index: usize,
+    pub ticket_id: Uuid,
+    /// Unknown at competition creation, recorded once the contract order has been verified
+    pub ephemeral_pubkey: Option<String>,
+}
+
+/// Slots for the tickets in the order their user ids were given to keymeld
+pub fn player_order_from_tickets(ticket_ids: &[Uuid]) -> Vec<PlayerSlot> {
+    ticket_ids
+        .iter()
+        .enumerate()
+        .map(|(player_index, ticket_id)| PlayerSlot {
+            player_index,
+            ticket_id: *ticket_id,
+            ephemeral_pubkey: None,
+        })
+        .collect()
+}
+
+/// Slots for entries already in contract player order
+pub fn player_order_from_entries(entries: &[UserEntry]) -> Vec<PlayerSlot> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(player_index, entry)| PlayerSlot {
+            player_index,
+            ticket_id: entry.ticket_id,
+            ephemeral_pubkey: Some(entry.ephemeral_pubkey.clone()),
+        })
+        .collect()
+}
+
+/// Every slot where the contract order disagrees with the order keymeld was given
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerOrderMismatch {
+    pub differences: Vec<String>,
+}
+
+impl fmt::Display for PlayerOrderMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "contract player order differs from the keymeld subset order: {}",
+            self.differences.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for PlayerOrderMismatch {}
+
+/// Check entries in contract order sit at the same player index as their ticket did when the
+/// subset definitions were built. Pubkeys are only compared once `expected` has recorded them.
+pub fn verify_player_order(
+    expected: &[PlayerSlot],
+    entries: &[UserEntry],
+) -> Result<(), PlayerOrderMismatch> {
+    let actual = player_order_from_entries(entries);
+    let mut differences = Vec::new();
+
+    for player_index in 0..expected.len().max(actual.len()) {
+        match (expected.get(player_index), actual.get(player_index)) {
+            (Some(expected), Some(actual)) => {
+                if expected.ticket_id != actual.ticket_id {
+                    differences.push(format!(
+                        "index {}: keymeld ticket {}, contract ticket {}",
+                        player_index, expected.ticket_id, actual.ticket_id
+                    ));
+                } else if expected
+                    .ephemeral_pubkey
+                    .as_ref()
+                    .is_some_and(|pubkey| Some(pubkey) != actual.ephemeral_pubkey.as_ref())
+                {
+                    differences.push(format!(
+                        "index {}: ticket {} pubkey {:?}, contract pubkey {:?}",
+                        player_index,
+                        expected.ticket_id,
+                        expected.ephemeral_pubkey,
+                        actual.ephemeral_pubkey
+                    ));
+                }
+            }
+            (Some(expected), None) => differences.push(format!(
+                "index {}: keymeld ticket {} has no contract entry",
+                player_index, expected.ticket_id
+            )),
+            (None, Some(actual)) => differences.push(format!(
+                "index {}: contract ticket {} is not in the keymeld order",
+                player_index, actual.ticket_id
+            )),
+            (None, None) => {}
+        }
+    }
+
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        Err(PlayerOrderMismatch { differences })
+    }
+}
+
+/// Stored order next to the current contract order of a competition, for debugging signing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerOrderReport {
+    pub competition_id: Uuid,
+    /// Order the keymeld subset definitions were built with, `None` for competitions created
+    /// without keymeld or before the order was recorded
+    pub keymeld_order: Option<Vec<PlayerSlot>>,
+    /// Paid entries in the order the contract uses, or would use once it is built
+    pub contract_order: Vec<PlayerSlot>,
+    pub mismatches: Vec<String>,
+}
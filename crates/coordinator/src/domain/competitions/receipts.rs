@@ -0,0 +1,113 @@
+//! Receipts the coordinator signs so players hold portable proof that they paid for a ticket
+//! and that their entry was accepted, usable in a dispute without trusting the coordinator's db.
+use bdk_wallet::bitcoin::secp256k1::{Keypair, Secp256k1, SecretKey};
+use coordinator_core::{receipt_message, verify_receipt_signature, Sats, SignedReceipt};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptKind {
+    /// The ticket's hold invoice was settled
+    TicketPayment,
+    /// An entry was accepted into the competition with the ticket
+    EntryAccepted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptPayload {
+    pub kind: ReceiptKind,
+    pub competition_id: Uuid,
+    pub ticket_id: Uuid,
+    /// Always set on entry receipts, on payment receipts once the ticket was used for an entry
+    pub entry_id: Option<Uuid>,
+    /// Sats paid for the ticket, zero when an admin comped it
+    pub amount_sats: Sats,
+    /// Hex payment hash of the ticket's hold invoice
+    pub payment_hash: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub issued_at: OffsetDateTime,
+}
+
+pub use coordinator_core::ReceiptError;
+
+pub fn sign_receipt(
+    secret_key: &SecretKey,
+    payload: &ReceiptPayload,
+) -> Result<SignedReceipt, ReceiptError> {
+    let secp = Secp256k1::new();
+    let keypair = Keypair::from_secret_key(&secp, secret_key);
+    let (pubkey, _) = keypair.x_only_public_key();
+    let payload = serde_json::to_string(payload)?;
+    let signature = secp.sign_schnorr_no_aux_rand(&receipt_message(&payload), &keypair);
+
+    Ok(SignedReceipt {
+        payload,
+        pubkey: pubkey.to_string(),
+        signature: signature.to_string(),
+    })
+}
+
+/// Check a receipt was signed by one of `coordinator_pubkeys` (hex x-only, as served at
+/// `/api/v1/info`) and return what it attests to
+pub fn verify_receipt(
+    receipt: &SignedReceipt,
+    coordinator_pubkeys: &[String],
+) -> Result<ReceiptPayload, ReceiptError> {
+    let payload = verify_receipt_signature(receipt, coordinator_pubkeys)?;
+    Ok(serde_json::from_str(payload)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> ReceiptPayload {
+        ReceiptPayload {
+            kind: ReceiptKind::EntryAccepted,
+            competition_id: Uuid::now_v7(),
+            ticket_id: Uuid::now_v7(),
+            entry_id: Some(Uuid::now_v7()),
+            amount_sats: Sats(1_100),
+            payment_hash: "ab".repeat(32),
+            issued_at: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_receipt_verifies_against_the_coordinator_pubkey_only() {
+        let secret_key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let receipt = sign_receipt(&secret_key, &payload()).unwrap();
+        let coordinator_pubkeys = vec![receipt.pubkey.clone()];
+        assert_eq!(receipt.pubkey.len(), 64);
+
+        assert_eq!(
+            verify_receipt(&receipt, &coordinator_pubkeys).unwrap(),
+            serde_json::from_str::<ReceiptPayload>(&receipt.payload).unwrap()
+        );
+
+        // Receipts signed by anyone else are rejected even when they are internally consistent
+        let other = sign_receipt(&SecretKey::from_slice(&[8u8; 32]).unwrap(), &payload()).unwrap();
+        assert!(matches!(
+            verify_receipt(&other, &coordinator_pubkeys),
+            Err(ReceiptError::WrongSigner(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampered_receipt_fails_verification() {
+        let secret_key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let mut receipt = sign_receipt(&secret_key, &payload()).unwrap();
+        let coordinator_pubkeys = vec![receipt.pubkey.clone()];
+
+        receipt.payload = receipt
+            .payload
+            .replace("\"amount_sats\":1100", "\"amount_sats\":9100");
+        assert!(receipt.payload.contains("9100"));
+        assert!(matches!(
+            verify_receipt(&receipt, &coordinator_pubkeys),
+            Err(ReceiptError::Signature(_))
+        ));
+    }
+}
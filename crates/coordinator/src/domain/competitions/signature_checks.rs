@@ -0,0 +1,193 @@
+//! Checks on the nonces and partial signatures players submit. A SigMap with the wrong keys or
+//! an unparseable value is turned away with the keys at fault, instead of being stored and
+//! failing the whole competition when the coordinator aggregates.
+use coordinator_core::ValidationErrors;
+use dlctix::{Outcome, SigMap, WinCondition};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+
+pub fn outcome_label(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Attestation(index) => format!("attestation_{}", index),
+        Outcome::Expiry => "expiry".to_string(),
+    }
+}
+
+pub fn win_condition_label(win_condition: &WinCondition) -> String {
+    format!(
+        "{}.player_{}",
+        outcome_label(&win_condition.outcome),
+        win_condition.player_index
+    )
+}
+
+/// Errors for every key `submitted` is missing or has on top of `expected`, with fields like
+/// `{field}.by_outcome.attestation_3` and `{field}.by_win_condition.attestation_3.player_1`
+pub fn check_sig_map_keys<T, U>(
+    field: &str,
+    submitted: &SigMap<T>,
+    expected: &SigMap<U>,
+) -> ValidationErrors {
+    let mut errors = ValidationErrors::new();
+    push_key_errors(
+        &mut errors,
+        &format!("{}.by_outcome", field),
+        &submitted.by_outcome,
+        &expected.by_outcome,
+        outcome_label,
+    );
+    push_key_errors(
+        &mut errors,
+        &format!("{}.by_win_condition", field),
+        &submitted.by_win_condition,
+        &expected.by_win_condition,
+        win_condition_label,
+    );
+    errors
+}
+
+fn push_key_errors<K: Ord, T, U>(
+    errors: &mut ValidationErrors,
+    field: &str,
+    submitted: &BTreeMap<K, T>,
+    expected: &BTreeMap<K, U>,
+    label: fn(&K) -> String,
+) {
+    for key in expected.keys().filter(|key| !submitted.contains_key(key)) {
+        errors.push(
+            format!("{}.{}", field, label(key)),
+            "missing_key",
+            "the contract needs a value for this key",
+        );
+    }
+    for key in submitted.keys().filter(|key| !expected.contains_key(key)) {
+        errors.push(
+            format!("{}.{}", field, label(key)),
+            "unexpected_key",
+            "this player doesn't sign this key in the contract",
+        );
+    }
+}
+
+/// Parse every value of a submitted SigMap, reporting each key whose value doesn't parse
+pub fn parse_sig_map<T: DeserializeOwned>(
+    field: &str,
+    raw: SigMap<serde_json::Value>,
+) -> Result<SigMap<T>, ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    let by_outcome = parse_values(
+        &mut errors,
+        &format!("{}.by_outcome", field),
+        raw.by_outcome,
+        outcome_label,
+    );
+    let by_win_condition = parse_values(
+        &mut errors,
+        &format!("{}.by_win_condition", field),
+        raw.by_win_condition,
+        win_condition_label,
+    );
+    errors.into_result()?;
+    Ok(SigMap {
+        by_outcome,
+        by_win_condition,
+    })
+}
+
+fn parse_values<K: Ord, T: DeserializeOwned>(
+    errors: &mut ValidationErrors,
+    field: &str,
+    raw: BTreeMap<K, serde_json::Value>,
+    label: fn(&K) -> String,
+) -> BTreeMap<K, T> {
+    raw.into_iter()
+        .filter_map(|(key, value)| match serde_json::from_value(value) {
+            Ok(value) => Some((key, value)),
+            Err(e) => {
+                errors.push(
+                    format!("{}.{}", field, label(&key)),
+                    "invalid_value",
+                    e.to_string(),
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dlctix::{
+        musig2::PartialSignature,
+        secp::{MaybeScalar, Scalar},
+    };
+
+    fn expected() -> SigMap<()> {
+        SigMap {
+            by_outcome: BTreeMap::from([
+                (Outcome::Attestation(0), ()),
+                (Outcome::Attestation(1), ()),
+                (Outcome::Expiry, ()),
+            ]),
+            by_win_condition: BTreeMap::from([(
+                WinCondition {
+                    outcome: Outcome::Attestation(1),
+                    player_index: 1,
+                },
+                (),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_sig_map_key_and_value_errors_name_the_key() {
+        let signature = serde_json::to_value(MaybeScalar::Valid(Scalar::one())).unwrap();
+        let mut raw = SigMap {
+            by_outcome: expected()
+                .by_outcome
+                .into_keys()
+                .map(|outcome| (outcome, signature.clone()))
+                .collect(),
+            by_win_condition: expected()
+                .by_win_condition
+                .into_keys()
+                .map(|win_condition| (win_condition, signature.clone()))
+                .collect(),
+        };
+        assert!(check_sig_map_keys("partial_signatures", &raw, &expected()).is_empty());
+
+        raw.by_win_condition.clear();
+        raw.by_outcome
+            .insert(Outcome::Attestation(2), signature.clone());
+        let errors = check_sig_map_keys("partial_signatures", &raw, &expected());
+        let fields: Vec<(&str, &str)> = errors
+            .errors
+            .iter()
+            .map(|error| (error.field.as_str(), error.code.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    "partial_signatures.by_outcome.attestation_2",
+                    "unexpected_key"
+                ),
+                (
+                    "partial_signatures.by_win_condition.attestation_1.player_1",
+                    "missing_key"
+                ),
+            ]
+        );
+
+        raw.by_outcome
+            .insert(Outcome::Expiry, serde_json::Value::String("zz".to_string()));
+        let errors = parse_sig_map::<PartialSignature>("partial_signatures", raw).unwrap_err();
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(
+            errors.errors[0].field,
+            "partial_signatures.by_outcome.expiry"
+        );
+        assert_eq!(errors.errors[0].code, "invalid_value");
+    }
+}
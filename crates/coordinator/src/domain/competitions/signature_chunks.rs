@@ -0,0 +1,204 @@
+//! Partial signatures submitted a chunk at a time. With many outcomes an entry's
+//! `SigMap<PartialSignature>` runs to hundreds of KB, so clients may send a few outcomes per
+//! request and the coordinator merges them until every outcome and win condition it signed is
+//! covered.
+use dlctix::{
+    bitcoin::hashes::{sha256, Hash},
+    SigMap,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Debug};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SignatureChunkError {
+    #[error("chunk resubmits {0} with a different signature")]
+    Conflict(String),
+    #[error("chunk signs {0}, which the contract doesn't have")]
+    Unexpected(String),
+}
+
+/// How far an entry's chunked partial signatures have come
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureChunkProgress {
+    pub received: usize,
+    pub expected: usize,
+    /// Every signature has arrived and the entry counts as signed
+    pub complete: bool,
+}
+
+/// Hex sha256 of a SigMap's json, lets clients check a SigMap fetched on its own belongs to the
+/// contract they were served
+pub fn sig_map_digest<T: Serialize>(sig_map: &SigMap<T>) -> Result<String, serde_json::Error> {
+    let json = serde_json::to_vec(sig_map)?;
+    Ok(sha256::Hash::hash(&json).to_string())
+}
+
+pub fn sig_map_len<T>(sig_map: &SigMap<T>) -> usize {
+    sig_map.by_outcome.len() + sig_map.by_win_condition.len()
+}
+
+/// Whether `sig_map` has a value for every outcome and win condition in `expected`
+pub fn sig_map_covers<T, U>(sig_map: &SigMap<T>, expected: &SigMap<U>) -> bool {
+    expected
+        .by_outcome
+        .keys()
+        .all(|outcome| sig_map.by_outcome.contains_key(outcome))
+        && expected
+            .by_win_condition
+            .keys()
+            .all(|win_condition| sig_map.by_win_condition.contains_key(win_condition))
+}
+
+/// Check every key of `chunk` is one the coordinator signed
+pub fn check_chunk_keys<T, U>(
+    chunk: &SigMap<T>,
+    expected: &SigMap<U>,
+) -> Result<(), SignatureChunkError> {
+    if let Some(outcome) = chunk
+        .by_outcome
+        .keys()
+        .find(|outcome| !expected.by_outcome.contains_key(outcome))
+    {
+        return Err(SignatureChunkError::Unexpected(format!("{:?}", outcome)));
+    }
+    if let Some(win_condition) = chunk
+        .by_win_condition
+        .keys()
+        .find(|win_condition| !expected.by_win_condition.contains_key(win_condition))
+    {
+        return Err(SignatureChunkError::Unexpected(format!(
+            "{:?}",
+            win_condition
+        )));
+    }
+    Ok(())
+}
+
+/// Add `chunk` to `merged`. Resending a signature that's already there is fine, a different one
+/// for the same outcome or win condition is rejected and `merged` is left as it was.
+pub fn merge_sig_map<T: PartialEq>(
+    merged: &mut SigMap<T>,
+    chunk: SigMap<T>,
+) -> Result<(), SignatureChunkError> {
+    check_conflicts(&merged.by_outcome, &chunk.by_outcome)?;
+    check_conflicts(&merged.by_win_condition, &chunk.by_win_condition)?;
+    merged.by_outcome.extend(chunk.by_outcome);
+    merged.by_win_condition.extend(chunk.by_win_condition);
+    Ok(())
+}
+
+fn check_conflicts<K: Ord + Debug, T: PartialEq>(
+    merged: &BTreeMap<K, T>,
+    chunk: &BTreeMap<K, T>,
+) -> Result<(), SignatureChunkError> {
+    match chunk
+        .iter()
+        .find(|(key, value)| merged.get(key).is_some_and(|existing| existing != *value))
+    {
+        Some((key, _)) => Err(SignatureChunkError::Conflict(format!("{:?}", key))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dlctix::{
+        musig2::PartialSignature,
+        secp::{MaybeScalar, Scalar},
+        Outcome, WinCondition,
+    };
+
+    fn signature(i: u32) -> PartialSignature {
+        let mut bytes = [0u8; 32];
+        bytes[28..].copy_from_slice(&(i + 1).to_be_bytes());
+        MaybeScalar::Valid(Scalar::from_slice(&bytes).unwrap())
+    }
+
+    fn synthetic_sig_map(outcomes: usize) -> SigMap<PartialSignature> {
+        SigMap {
+            by_outcome: (0..outcomes)
+                .map(|i| (Outcome::Attestation(i), signature(i as u32)))
+                .chain([(Outcome::Expiry, signature(outcomes as u32))])
+                .collect(),
+            by_win_condition: (0..outcomes)
+                .map(|i| {
+                    (
+                        WinCondition {
+                            outcome: Outcome::Attestation(i),
+                            player_index: i % 7,
+                        },
+                        signature((outcomes + 1 + i) as u32),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_chunked_submission_reassembles_identically() {
+        let full = synthetic_sig_map(500);
+        assert_eq!(sig_map_len(&full), 1_001);
+
+        // Clients chunk by outcome, each chunk carrying that outcome's win conditions
+        let outcomes: Vec<Outcome> = full.by_outcome.keys().copied().collect();
+        let mut merged = SigMap {
+            by_outcome: BTreeMap::new(),
+            by_win_condition: BTreeMap::new(),
+        };
+        for outcomes in outcomes.chunks(64).rev() {
+            let chunk = SigMap {
+                by_outcome: outcomes
+                    .iter()
+                    .map(|outcome| (*outcome, full.by_outcome[outcome]))
+                    .collect(),
+                by_win_condition: full
+                    .by_win_condition
+                    .iter()
+                    .filter(|(win_condition, _)| outcomes.contains(&win_condition.outcome))
+                    .map(|(win_condition, signature)| (*win_condition, *signature))
+                    .collect(),
+            };
+            check_chunk_keys(&chunk, &full).unwrap();
+            assert!(!sig_map_covers(&merged, &full));
+            merge_sig_map(&mut merged, chunk).unwrap();
+        }
+
+        assert!(sig_map_covers(&merged, &full));
+        assert_eq!(merged, full);
+        assert_eq!(
+            sig_map_digest(&merged).unwrap(),
+            sig_map_digest(&full).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_string(&merged).unwrap(),
+            serde_json::to_string(&full).unwrap()
+        );
+
+        // Resending a chunk is harmless, changing a signature is not
+        let resent = SigMap {
+            by_outcome: BTreeMap::from([(Outcome::Attestation(3), signature(3))]),
+            by_win_condition: BTreeMap::new(),
+        };
+        merge_sig_map(&mut merged, resent).unwrap();
+        let forged = SigMap {
+            by_outcome: BTreeMap::from([(Outcome::Attestation(3), signature(9_999))]),
+            by_win_condition: BTreeMap::new(),
+        };
+        assert!(matches!(
+            merge_sig_map(&mut merged, forged),
+            Err(SignatureChunkError::Conflict(_))
+        ));
+        assert_eq!(merged, full);
+
+        let unknown = SigMap {
+            by_outcome: BTreeMap::from([(Outcome::Attestation(500), signature(1))]),
+            by_win_condition: BTreeMap::new(),
+        };
+        assert!(matches!(
+            check_chunk_keys(&unknown, &full),
+            Err(SignatureChunkError::Unexpected(_))
+        ));
+    }
+}
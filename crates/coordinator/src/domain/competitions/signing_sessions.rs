@@ -0,0 +1,109 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use dlctix::{
+    bitcoin::{
+        hashes::{sha256, Hash},
+        OutPoint,
+    },
+    ContractParameters, CoordinatorPartialSignatureRound, SigningSession,
+};
+use uuid::Uuid;
+
+/// Coordinator signing sessions with every entry's nonces aggregated, kept from the aggregation
+/// step so signing the contract on a later tick doesn't rebuild the `TicketedDLC`. Only the
+/// most recent `capacity` are kept and nothing survives a restart, a missing session is rebuilt
+/// from the stored contract.
+#[derive(Clone)]
+pub struct SigningSessionCache {
+    capacity: usize,
+    sessions: Arc<Mutex<VecDeque<CachedSession>>>,
+}
+
+struct CachedSession {
+    competition_id: Uuid,
+    funding_outpoint: OutPoint,
+    contract_fingerprint: sha256::Hash,
+    session: SigningSession<CoordinatorPartialSignatureRound>,
+}
+
+impl SigningSessionCache {
+    /// A `capacity` of 0 keeps nothing
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            sessions: Arc::default(),
+        }
+    }
+
+    /// Keep `session` for the competition's contract, replacing any it had and dropping the
+    /// oldest session once over capacity
+    pub fn insert(
+        &self,
+        competition_id: Uuid,
+        funding_outpoint: OutPoint,
+        contract_parameters: &ContractParameters,
+        session: SigningSession<CoordinatorPartialSignatureRound>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let Some(contract_fingerprint) = contract_fingerprint(contract_parameters) else {
+            return;
+        };
+        let mut sessions = self.lock();
+        sessions.retain(|cached| cached.competition_id != competition_id);
+        sessions.push_back(CachedSession {
+            competition_id,
+            funding_outpoint,
+            contract_fingerprint,
+            session,
+        });
+        while sessions.len() > self.capacity {
+            sessions.pop_front();
+        }
+    }
+
+    /// Remove the competition's session, returning it when it was built for this contract and
+    /// funding outpoint. A session for anything else is stale and just dropped.
+    pub fn take(
+        &self,
+        competition_id: Uuid,
+        funding_outpoint: OutPoint,
+        contract_parameters: &ContractParameters,
+    ) -> Option<SigningSession<CoordinatorPartialSignatureRound>> {
+        let cached = {
+            let mut sessions = self.lock();
+            let position = sessions
+                .iter()
+                .position(|cached| cached.competition_id == competition_id)?;
+            sessions.remove(position)?
+        };
+        (cached.funding_outpoint == funding_outpoint
+            && Some(cached.contract_fingerprint) == contract_fingerprint(contract_parameters))
+        .then_some(cached.session)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<CachedSession>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Identifies the contract a session was built for without keeping a copy of it
+fn contract_fingerprint(contract_parameters: &ContractParameters) -> Option<sha256::Hash> {
+    serde_json::to_vec(contract_parameters)
+        .ok()
+        .map(|bytes| sha256::Hash::hash(&bytes))
+}
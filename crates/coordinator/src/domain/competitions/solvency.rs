@@ -0,0 +1,213 @@
+use coordinator_core::Sats;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::Competition;
+
+/// The coordinator wallet against what active competitions still need from it, checked before
+/// creating competitions the coordinator underwrites
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolvencyReport {
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    pub confirmed_balance_sats: u64,
+    /// Unconfirmed and immature wallet sats, not counted toward the commitments
+    pub pending_balance_sats: u64,
+    /// Sats active competitions still need from the wallet to fund their contracts
+    pub committed_funding_sats: u64,
+    /// Lightning payouts owed to winners that haven't succeeded yet
+    pub outstanding_payouts_sats: u64,
+    /// Committed funding the confirmed balance doesn't cover
+    pub shortfall_sats: u64,
+    pub competitions: Vec<CompetitionSolvency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompetitionSolvency {
+    pub competition_id: Uuid,
+    pub name: Option<String>,
+    pub state: String,
+    pub committed_funding_sats: u64,
+    pub outstanding_payouts_sats: u64,
+    /// The confirmed balance covers this competition's funding once every competition created
+    /// before it is funded
+    pub covered: bool,
+}
+
+impl CompetitionSolvency {
+    /// `escrow_funded` is the number of entries whose escrow output the wallet already funded,
+    /// those sats left the wallet when the ticket was paid
+    pub fn new(
+        competition: &Competition,
+        escrow_enabled: bool,
+        escrow_funded: usize,
+        outstanding_payouts: Sats,
+    ) -> Self {
+        let pool = competition.event_submission.total_competition_pool;
+        let committed_funding = if competition.funding_broadcasted_at.is_some() {
+            Sats::ZERO
+        } else if escrow_enabled {
+            let escrowed = competition
+                .event_submission
+                .entry_fee
+                .saturating_mul(escrow_funded as u64);
+            pool.saturating_sub(escrowed)
+        } else {
+            pool
+        };
+
+        CompetitionSolvency {
+            competition_id: competition.id,
+            name: competition.name.clone(),
+            state: competition.get_state().to_string(),
+            committed_funding_sats: committed_funding.to_sat(),
+            outstanding_payouts_sats: outstanding_payouts.to_sat(),
+            covered: true,
+        }
+    }
+}
+
+impl SolvencyReport {
+    /// Competitions are funded in creation order, so each is covered when the confirmed balance
+    /// reaches its commitment plus those of the competitions created before it
+    pub fn build(
+        mut competitions: Vec<CompetitionSolvency>,
+        confirmed_balance: Sats,
+        pending_balance: Sats,
+        generated_at: OffsetDateTime,
+    ) -> Self {
+        // Competition ids are uuidv7, ordered by creation time
+        competitions.sort_by_key(|competition| competition.competition_id);
+
+        let mut committed_funding = Sats::ZERO;
+        let mut outstanding_payouts = Sats::ZERO;
+        for competition in &mut competitions {
+            committed_funding =
+                committed_funding.saturating_add(Sats(competition.committed_funding_sats));
+            outstanding_payouts =
+                outstanding_payouts.saturating_add(Sats(competition.outstanding_payouts_sats));
+            competition.covered =
+                competition.committed_funding_sats == 0 || committed_funding <= confirmed_balance;
+        }
+
+        SolvencyReport {
+            generated_at,
+            confirmed_balance_sats: confirmed_balance.to_sat(),
+            pending_balance_sats: pending_balance.to_sat(),
+            committed_funding_sats: committed_funding.to_sat(),
+            outstanding_payouts_sats: outstanding_payouts.to_sat(),
+            shortfall_sats: committed_funding.saturating_sub(confirmed_balance).to_sat(),
+            competitions,
+        }
+    }
+
+    pub fn is_solvent(&self) -> bool {
+        self.shortfall_sats == 0
+    }
+}
+
+/// Size in vbytes the funding transaction is estimated at before it's built with escrow
+/// disabled: a few wallet inputs, the contract's funding output and change
+pub const FUNDING_TX_ESTIMATED_VBYTES: u64 = 250;
+
+/// The coordinator wallet's confirmed balance doesn't cover a contract it funds on its own
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "coordinator wallet has {confirmed_sats} confirmed sats but needs {pool_sats} for the pool and about {fee_sats} for the funding fee, {shortfall_sats} sats short"
+)]
+pub struct FundingShortfall {
+    pub confirmed_sats: u64,
+    pub pool_sats: u64,
+    pub fee_sats: u64,
+    pub shortfall_sats: u64,
+}
+
+impl FundingShortfall {
+    /// `None` when the confirmed balance covers the pool and the funding transaction's fee at
+    /// `sat_per_vb`
+    pub fn check(confirmed_balance: Sats, pool: Sats, sat_per_vb: u64) -> Option<Self> {
+        let fee = Sats(sat_per_vb).saturating_mul(FUNDING_TX_ESTIMATED_VBYTES);
+        let shortfall = pool.saturating_add(fee).saturating_sub(confirmed_balance);
+        (shortfall > Sats::ZERO).then(|| FundingShortfall {
+            confirmed_sats: confirmed_balance.to_sat(),
+            pool_sats: pool.to_sat(),
+            fee_sats: fee.to_sat(),
+            shortfall_sats: shortfall.to_sat(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::CreateEvent;
+
+    fn competition(pool: u64) -> Competition {
+        let start = OffsetDateTime::now_utc() + time::Duration::hours(6);
+        Competition::new(&CreateEvent {
+            total_competition_pool: Sats(pool),
+            ..CreateEvent::fixture(start)
+        })
+    }
+
+    #[test]
+    fn test_later_competitions_are_flagged_once_the_balance_runs_out() {
+        let first = competition(3_000);
+        let second = competition(3_000);
+        let mut funded = competition(3_000);
+        funded.funding_broadcasted_at = Some(OffsetDateTime::now_utc());
+
+        // Escrow outputs already funded come off the commitment
+        let escrowed = CompetitionSolvency::new(&second, true, 2, Sats::ZERO);
+        assert_eq!(escrowed.committed_funding_sats, 1_000);
+
+        let report = SolvencyReport::build(
+            vec![
+                CompetitionSolvency::new(&funded, false, 0, Sats(2_500)),
+                CompetitionSolvency::new(&second, false, 0, Sats::ZERO),
+                CompetitionSolvency::new(&first, false, 0, Sats::ZERO),
+            ],
+            Sats(4_000),
+            Sats(10_000),
+            OffsetDateTime::now_utc(),
+        );
+        assert_eq!(report.committed_funding_sats, 6_000);
+        assert_eq!(report.outstanding_payouts_sats, 2_500);
+        assert_eq!(report.shortfall_sats, 2_000);
+        assert!(!report.is_solvent());
+
+        // Only the earlier of the two unfunded competitions fits in the balance
+        let covered: Vec<(Uuid, bool)> = report
+            .competitions
+            .iter()
+            .map(|competition| (competition.competition_id, competition.covered))
+            .filter(|(id, _)| *id != funded.id)
+            .collect();
+        let (earlier, later) = (first.id.min(second.id), first.id.max(second.id));
+        assert_eq!(covered, vec![(earlier, true), (later, false)]);
+        assert!(report
+            .competitions
+            .iter()
+            .any(|competition| competition.competition_id == funded.id && competition.covered));
+    }
+
+    #[test]
+    fn test_funding_shortfall_counts_the_fee() {
+        let fee = 2 * FUNDING_TX_ESTIMATED_VBYTES;
+        assert_eq!(
+            FundingShortfall::check(Sats(10_000 + fee), Sats(10_000), 2),
+            None
+        );
+
+        let shortfall = FundingShortfall::check(Sats(10_000), Sats(10_000), 2).unwrap();
+        assert_eq!(shortfall.fee_sats, fee);
+        assert_eq!(shortfall.shortfall_sats, fee);
+        assert!(shortfall
+            .to_string()
+            .contains(&format!("{} sats short", fee)));
+
+        let empty = FundingShortfall::check(Sats::ZERO, Sats(10_000), 1).unwrap();
+        assert_eq!(empty.shortfall_sats, 10_000 + FUNDING_TX_ESTIMATED_VBYTES);
+    }
+}
@@ -0,0 +1,498 @@
+use dlctix::{
+    bitcoin::{
+        consensus::encode::deserialize,
+        hashes::{sha256, Hash},
+        Amount, OutPoint, Psbt, Transaction, Txid,
+    },
+    secp::Point,
+    Outcome, PlayerIndex, SignedContract, WinCondition,
+};
+use serde::Serialize;
+use std::{collections::HashMap, fmt, str::FromStr};
+use uuid::Uuid;
+
+use super::{Competition, Ticket, UserEntry};
+use crate::infra::{bitcoin::OutpointStatus, escrow::get_escrow_outpoint};
+
+/// Which part of the contract flow an output on chain belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchedOutputKind {
+    /// A player's escrow, spent by the funding transaction
+    Escrow,
+    /// The contract's funding output, spent by the outcome or expiry transaction
+    Funding,
+    /// The outcome transaction's output, spent by the unified close or the split transaction
+    Outcome,
+    /// A winner's split output, spent by the split-close or split-reclaim transaction
+    Split,
+}
+
+impl fmt::Display for WatchedOutputKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchedOutputKind::Escrow => write!(f, "escrow"),
+            WatchedOutputKind::Funding => write!(f, "funding"),
+            WatchedOutputKind::Outcome => write!(f, "outcome"),
+            WatchedOutputKind::Split => write!(f, "split"),
+        }
+    }
+}
+
+/// An output of a competition the coordinator still expects to spend through its own flow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchedOutput {
+    pub kind: WatchedOutputKind,
+    pub outpoint: OutPoint,
+    /// Entry owning the output, set for escrow and split outputs
+    pub entry_id: Option<Uuid>,
+}
+
+/// A watched output was spent by a transaction the coordinator didn't expect
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{kind} output {outpoint} was spent by {spent_by}")]
+pub struct UnexpectedSpend {
+    pub kind: WatchedOutputKind,
+    pub outpoint: OutPoint,
+    pub spent_by: Txid,
+}
+
+/// Escrows spent outside the contract before it was funded. Their entries have been cancelled,
+/// the contract can't be funded without them.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{}", .reasons.join("; "))]
+pub struct EscrowDoubleSpent {
+    pub reasons: Vec<String>,
+}
+
+/// What the coordinator does about a spend of a watched output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpendResolution {
+    /// Spent by a transaction of the contract flow, nothing to do
+    Expected,
+    /// Someone else broadcast the expiry transaction before the outcome was published
+    ExpiryBroadcast,
+    /// A winner went on chain on their own, with the split transaction or their split output.
+    /// The coordinator no longer closes or reclaims that winner's share.
+    UnilateralSplit { entry_id: Uuid },
+    /// Spent outside the contract, nothing the coordinator would broadcast can confirm anymore
+    Foreign,
+}
+
+/// Outputs of the competition that can still be spent from under the coordinator, given how far
+/// it got. Escrows are only watched until the funding transaction confirms, split outputs only
+/// for winners the coordinator hasn't closed or reclaimed yet.
+pub fn watched_outputs(
+    competition: &Competition,
+    entries: &[UserEntry],
+    tickets: &HashMap<Uuid, Ticket>,
+) -> Vec<WatchedOutput> {
+    let mut watched = Vec::new();
+
+    if !competition.is_funding_confirmed() {
+        let escrow_amount = Amount::from_sat(competition.event_submission.entry_fee.to_sat());
+        for (entry_id, ticket) in tickets {
+            let Some(escrow_tx) = ticket
+                .escrow_transaction
+                .as_ref()
+                .and_then(|escrow_hex| hex::decode(escrow_hex).ok())
+                .and_then(|bytes| deserialize::<Transaction>(&bytes).ok())
+            else {
+                continue;
+            };
+            if let Ok(outpoint) = get_escrow_outpoint(&escrow_tx, escrow_amount) {
+                watched.push(WatchedOutput {
+                    kind: WatchedOutputKind::Escrow,
+                    outpoint,
+                    entry_id: Some(*entry_id),
+                });
+            }
+        }
+    }
+
+    if let (Some(outpoint), Some(_)) = (
+        competition.funding_outpoint,
+        competition.funding_broadcasted_at,
+    ) {
+        watched.push(WatchedOutput {
+            kind: WatchedOutputKind::Funding,
+            outpoint,
+            entry_id: None,
+        });
+    }
+
+    let (Some(signed_contract), Some(_)) = (
+        competition.signed_contract.as_ref(),
+        competition.outcome_broadcasted_at,
+    ) else {
+        return watched;
+    };
+    let Ok(outcome) = competition.get_current_outcome() else {
+        return watched;
+    };
+    if let Ok((input, _)) = signed_contract.outcome_close_tx_input_and_prevout(&outcome) {
+        watched.push(WatchedOutput {
+            kind: WatchedOutputKind::Outcome,
+            outpoint: input.previous_output,
+            entry_id: None,
+        });
+    }
+
+    if competition.delta_broadcasted_at.is_none() {
+        return watched;
+    }
+    let Some(winners) = signed_contract.params().outcome_payouts.get(&outcome) else {
+        return watched;
+    };
+    for &player_index in winners.keys() {
+        let Some(entry) = winner_entry(signed_contract, entries, player_index) else {
+            continue;
+        };
+        if entry.sellback_broadcasted_at.is_some() || entry.reclaimed_broadcasted_at.is_some() {
+            continue;
+        }
+        let win_condition = WinCondition {
+            outcome,
+            player_index,
+        };
+        if let Ok((input, _)) = signed_contract.split_reclaim_tx_input_and_prevout(&win_condition) {
+            watched.push(WatchedOutput {
+                kind: WatchedOutputKind::Split,
+                outpoint: input.previous_output,
+                entry_id: Some(entry.id),
+            });
+        }
+    }
+
+    watched
+}
+
+/// Decide what a spend of `watched` by `spent_by` means for the competition. `spending_tx` is
+/// only needed for outcome outputs, where it tells a winner's split transaction apart.
+pub fn resolve_spend(
+    competition: &Competition,
+    watched: &WatchedOutput,
+    spent_by: Txid,
+    spending_tx: Option<&Transaction>,
+    entries: &[UserEntry],
+    tickets: &HashMap<Uuid, Ticket>,
+) -> SpendResolution {
+    match watched.kind {
+        WatchedOutputKind::Escrow => {
+            // Witnesses aren't part of the txid, the unsigned funding psbt already has it
+            let funded_by = competition
+                .funding_transaction
+                .as_ref()
+                .map(Transaction::compute_txid)
+                .or_else(|| {
+                    let psbt = Psbt::from_str(competition.funding_psbt_base64.as_deref()?).ok()?;
+                    Some(psbt.unsigned_tx.compute_txid())
+                });
+            if funded_by == Some(spent_by) {
+                SpendResolution::Expected
+            } else {
+                SpendResolution::Foreign
+            }
+        }
+        WatchedOutputKind::Funding => {
+            let Some(signed_contract) = competition.signed_contract.as_ref() else {
+                return SpendResolution::Foreign;
+            };
+            let published_outcome = competition
+                .outcome_transaction
+                .as_ref()
+                .map(Transaction::compute_txid);
+            if published_outcome == Some(spent_by)
+                || attested_outcome_txid(competition, signed_contract) == Some(spent_by)
+            {
+                return SpendResolution::Expected;
+            }
+            let expiry = signed_contract
+                .expiry_tx()
+                .map(|expiry_tx| expiry_tx.compute_txid());
+            if expiry != Some(spent_by) {
+                return SpendResolution::Foreign;
+            }
+            if competition.expiry_broadcasted_at.is_some() {
+                SpendResolution::Expected
+            } else if competition.outcome_broadcasted_at.is_none() {
+                SpendResolution::ExpiryBroadcast
+            } else {
+                // The expiry transaction beat the outcome transaction the coordinator published
+                SpendResolution::Foreign
+            }
+        }
+        WatchedOutputKind::Outcome => {
+            if competition.delta_broadcasted_at.is_some() {
+                return SpendResolution::Expected;
+            }
+            let Some(entry_id) =
+                spending_tx.and_then(|tx| revealed_ticket_entry(tx, &watched.outpoint, tickets))
+            else {
+                return SpendResolution::Foreign;
+            };
+            let handled = entries
+                .iter()
+                .any(|entry| entry.id == entry_id && entry.sellback_broadcasted_at.is_some());
+            if handled {
+                SpendResolution::Expected
+            } else {
+                SpendResolution::UnilateralSplit { entry_id }
+            }
+        }
+        WatchedOutputKind::Split => match watched.entry_id {
+            // Only unclosed winners are watched, anything spending their output is the winner
+            Some(entry_id) => SpendResolution::UnilateralSplit { entry_id },
+            None => SpendResolution::Foreign,
+        },
+    }
+}
+
+/// Why an escrow can no longer fund the contract, `None` while it still can. Before funding any
+/// spend of the escrow output is a double spend. `input_statuses` are the escrow transaction's
+/// inputs, only looked up while it is unconfirmed: one spent by another transaction means the
+/// escrow can never confirm.
+pub fn escrow_double_spend(
+    escrow_tx: &Transaction,
+    escrow_outpoint: OutPoint,
+    escrow_status: OutpointStatus,
+    input_statuses: &[(OutPoint, OutpointStatus)],
+) -> Option<String> {
+    if let OutpointStatus::Spent { txid } = escrow_status {
+        return Some(format!(
+            "escrow output {} was spent by {}",
+            escrow_outpoint, txid
+        ));
+    }
+    let escrow_txid = escrow_tx.compute_txid();
+    input_statuses
+        .iter()
+        .find_map(|(outpoint, status)| match status {
+            OutpointStatus::Spent { txid } if *txid != escrow_txid => Some(format!(
+                "escrow input {} was spent by {} instead of escrow transaction {}",
+                outpoint, txid, escrow_txid
+            )),
+            _ => None,
+        })
+}
+
+/// The outcome transaction for the oracle's attestation, which anyone holding it can broadcast
+fn attested_outcome_txid(
+    competition: &Competition,
+    signed_contract: &SignedContract,
+) -> Option<Txid> {
+    let attestation = competition.attestation?;
+    let Ok(Outcome::Attestation(outcome_index)) = competition.get_current_outcome() else {
+        return None;
+    };
+    signed_contract
+        .signed_outcome_tx(outcome_index, attestation)
+        .ok()
+        .map(|outcome_tx| outcome_tx.compute_txid())
+}
+
+/// The split transaction is unlocked with a winner's ticket preimage, find the entry whose
+/// ticket preimage shows up in the witness spending `outpoint`
+fn revealed_ticket_entry(
+    spending_tx: &Transaction,
+    outpoint: &OutPoint,
+    tickets: &HashMap<Uuid, Ticket>,
+) -> Option<Uuid> {
+    let input = spending_tx
+        .input
+        .iter()
+        .find(|input| input.previous_output == *outpoint)?;
+    input
+        .witness
+        .iter()
+        .filter(|item| item.len() == 32)
+        .map(|item| hex::encode(sha256::Hash::hash(item).to_byte_array()))
+        .find_map(|hash| {
+            tickets
+                .iter()
+                .find(|(_, ticket)| ticket.hash == hash)
+                .map(|(entry_id, _)| *entry_id)
+        })
+}
+
+fn winner_entry<'a>(
+    signed_contract: &SignedContract,
+    entries: &'a [UserEntry],
+    player_index: PlayerIndex,
+) -> Option<&'a UserEntry> {
+    let player = signed_contract.params().players.get(player_index)?;
+    entries.iter().find(|entry| {
+        Point::from_hex(&entry.ephemeral_pubkey).is_ok_and(|pubkey| pubkey == player.pubkey)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::CreateEvent;
+    use dlctix::bitcoin::{
+        absolute::LockTime, transaction::Version, ScriptBuf, TxIn, TxOut, Witness,
+    };
+    use time::{Duration, OffsetDateTime};
+
+    fn test_competition() -> Competition {
+        let now = OffsetDateTime::now_utc();
+        Competition::new(&CreateEvent::fixture(now + Duration::hours(6)))
+    }
+
+    fn spend(outpoint: OutPoint, witness: &[&[u8]]) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: outpoint,
+                witness: Witness::from_slice(witness),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(900),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_split_spend_is_traced_to_the_winner_ticket() {
+        let outpoint = OutPoint {
+            txid: Txid::from_byte_array([1; 32]),
+            vout: 0,
+        };
+        let preimage = [7u8; 32];
+        let entry_id = Uuid::now_v7();
+        let ticket = Ticket {
+            id: Uuid::now_v7(),
+            competition_id: Uuid::now_v7(),
+            entry_id: Some(entry_id),
+            ephemeral_pubkey: None,
+            encrypted_preimage: hex::encode(preimage),
+            hash: hex::encode(sha256::Hash::hash(&preimage).to_byte_array()),
+            payment_request: None,
+            invoice_expires_at: None,
+            expiry: OffsetDateTime::now_utc(),
+            reserved_by: None,
+            reserved_at: None,
+            paid_at: None,
+            settled_at: None,
+            escrow_transaction: None,
+            ln_backend_id: None,
+            comped_by: None,
+            comped_at: None,
+            comp_note: None,
+            payment_receipt: None,
+            settlement_error: None,
+            settlement_failed_at: None,
+            escrow_broadcasted_at: None,
+        };
+        let tickets = HashMap::from([(entry_id, ticket)]);
+
+        let split = spend(outpoint, &[&[1; 64], &preimage, &[2; 40]]);
+        assert_eq!(
+            revealed_ticket_entry(&split, &outpoint, &tickets),
+            Some(entry_id)
+        );
+
+        let sweep = spend(outpoint, &[&[1; 64]]);
+        assert_eq!(revealed_ticket_entry(&sweep, &outpoint, &tickets), None);
+
+        let mut competition = test_competition();
+        let watched = WatchedOutput {
+            kind: WatchedOutputKind::Outcome,
+            outpoint,
+            entry_id: None,
+        };
+        assert_eq!(
+            resolve_spend(
+                &competition,
+                &watched,
+                split.compute_txid(),
+                Some(&split),
+                &[],
+                &tickets
+            ),
+            SpendResolution::UnilateralSplit { entry_id }
+        );
+        assert_eq!(
+            resolve_spend(
+                &competition,
+                &watched,
+                sweep.compute_txid(),
+                Some(&sweep),
+                &[],
+                &tickets
+            ),
+            SpendResolution::Foreign
+        );
+
+        // Once the coordinator published its own close or split, the outcome output is its own
+        competition.delta_broadcasted_at = Some(OffsetDateTime::now_utc());
+        assert_eq!(
+            resolve_spend(
+                &competition,
+                &watched,
+                sweep.compute_txid(),
+                Some(&sweep),
+                &[],
+                &tickets
+            ),
+            SpendResolution::Expected
+        );
+    }
+
+    #[test]
+    fn test_escrow_double_spend_detects_spent_output_and_inputs() {
+        let funding_input = OutPoint {
+            txid: Txid::from_byte_array([3; 32]),
+            vout: 1,
+        };
+        let escrow_tx = spend(funding_input, &[]);
+        let escrow_outpoint = OutPoint {
+            txid: escrow_tx.compute_txid(),
+            vout: 0,
+        };
+        let other = Txid::from_byte_array([9; 32]);
+
+        assert_eq!(
+            escrow_double_spend(
+                &escrow_tx,
+                escrow_outpoint,
+                OutpointStatus::Unspent,
+                &[(funding_input, OutpointStatus::Unspent)]
+            ),
+            None
+        );
+        // The escrow transaction itself spending its input is what's expected
+        assert_eq!(
+            escrow_double_spend(
+                &escrow_tx,
+                escrow_outpoint,
+                OutpointStatus::Unspent,
+                &[(
+                    funding_input,
+                    OutpointStatus::Spent {
+                        txid: escrow_tx.compute_txid()
+                    }
+                )]
+            ),
+            None
+        );
+        assert!(escrow_double_spend(
+            &escrow_tx,
+            escrow_outpoint,
+            OutpointStatus::Unspent,
+            &[(funding_input, OutpointStatus::Spent { txid: other })]
+        )
+        .is_some_and(|reason| reason.contains(&other.to_string())));
+        assert!(escrow_double_spend(
+            &escrow_tx,
+            escrow_outpoint,
+            OutpointStatus::Spent { txid: other },
+            &[]
+        )
+        .is_some_and(|reason| reason.contains(&escrow_outpoint.to_string())));
+    }
+}
@@ -0,0 +1,94 @@
+use coordinator_core::Sats;
+use dlctix::bitcoin::OutPoint;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Where a sponsor's sats came from, checked against the coordinator's wallet or lightning node
+/// before they count toward a pool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SponsorFunds {
+    /// An unspent output of the coordinator's wallet
+    Utxo { outpoint: OutPoint },
+    /// A settled invoice of the coordinator's lightning node
+    Lightning { payment_hash: String },
+}
+
+impl SponsorFunds {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SponsorFunds::Utxo { .. } => "utxo",
+            SponsorFunds::Lightning { .. } => "lightning",
+        }
+    }
+
+    /// Identifies the funds, the same funds only sponsor a pool once
+    pub fn reference(&self) -> String {
+        match self {
+            SponsorFunds::Utxo { outpoint } => outpoint.to_string(),
+            SponsorFunds::Lightning { payment_hash } => payment_hash.clone(),
+        }
+    }
+
+    pub fn from_parts(kind: &str, reference: &str) -> Result<Self, anyhow::Error> {
+        match kind {
+            "utxo" => Ok(SponsorFunds::Utxo {
+                outpoint: OutPoint::from_str(reference)?,
+            }),
+            "lightning" => Ok(SponsorFunds::Lightning {
+                payment_hash: reference.to_string(),
+            }),
+            kind => Err(anyhow::anyhow!("Unknown sponsor funds kind: {}", kind)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddSponsorship {
+    pub amount_sats: Sats,
+    pub funds: SponsorFunds,
+    /// Who the sats came from, for display and accounting
+    #[serde(default)]
+    pub sponsor: Option<String>,
+}
+
+/// Sats added to a competition's pool on top of its entry fees
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sponsorship {
+    pub id: Uuid,
+    pub competition_id: Uuid,
+    pub amount_sats: Sats,
+    pub funds: SponsorFunds,
+    pub sponsor: Option<String>,
+    /// Admin pubkey, or anonymous without an allow-list
+    pub recorded_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sponsor_funds_round_trip_through_their_parts() {
+        let utxo = SponsorFunds::Utxo {
+            outpoint: OutPoint::from_str(
+                "6c3b5b3a2c1d4e5f60718293a4b5c6d7e8f90112233445566778899aabbccdde:1",
+            )
+            .unwrap(),
+        };
+        let lightning = SponsorFunds::Lightning {
+            payment_hash: hex::encode([7u8; 32]),
+        };
+        for funds in [utxo, lightning] {
+            assert_eq!(
+                SponsorFunds::from_parts(funds.kind(), &funds.reference()).unwrap(),
+                funds
+            );
+        }
+        assert!(SponsorFunds::from_parts("cash", "envelope").is_err());
+    }
+}
@@ -39,14 +39,13 @@ impl Failed {
 
     /// Reconstruct from an existing Competition loaded from DB.
     pub fn from_competition(competition: Competition) -> Self {
-        let error =
-            competition
-                .errors
-                .last()
-                .cloned()
-                .unwrap_or(CompetitionError::InvalidStateTransition(
-                    "Unknown error".to_string(),
-                ));
+        let error = competition
+            .errors
+            .last()
+            .map(|recorded| recorded.error.clone())
+            .unwrap_or(CompetitionError::InvalidStateTransition(
+                "Unknown error".to_string(),
+            ));
 
         Self {
             competition_id: competition.id,
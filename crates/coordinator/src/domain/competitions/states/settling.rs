@@ -78,7 +78,7 @@ impl ExpiryBroadcasted {
         }
     }
 
-    /// Transition to Completed after expiry refunds are processed.
+    /// Transition to Completed once the expiry transaction has confirmed.
     pub fn completed(mut self) -> CompetitionStatus {
         self.competition.completed_at = Some(OffsetDateTime::now_utc());
         CompetitionStatus::Completed(Completed::from_competition(self.competition))
@@ -1,17 +1,268 @@
-use dlctix::{bitcoin::XOnlyPublicKey, musig2::PubNonce, SigMap};
-use log::{debug, info};
-use sqlx::{Execute, Sqlite};
-use std::collections::HashMap;
-use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use coordinator_core::{Sats, SignedReceipt};
+use dlctix::{
+    bitcoin::XOnlyPublicKey,
+    musig2::{PartialSignature, PubNonce},
+    SigMap,
+};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteRow, Execute, Row, Sqlite};
+use std::collections::{BTreeMap, HashMap};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::{
     api::routes::FinalSignatures,
-    domain::{EntryPayout, PayoutError, PayoutStatus},
-    infra::db::DBConnection,
+    domain::{scoring::CachedObservationData, EntryPayout, PayoutError, PayoutStatus},
+    infra::{
+        db::{decode_blob, encode_blob, DBConnection, EncodedBlob},
+        db_timestamps::{format_timestamp, parse_required_datetime},
+    },
+};
+
+use super::{
+    merge_sig_map, Competition, CompetitionOverride, CompetitionPnl, CreateEvent, EntryStatus,
+    LedgerEntry, LedgerEntryKind, PayoutFailureCount, PendingEscrowReclaim, PlayerSlot, PnlReport,
+    SearchBy, SignatureChunkError, SponsorFunds, Sponsorship, Ticket, TicketStatus, UserEntry,
 };
 
-use super::{Competition, EntryStatus, SearchBy, Ticket, UserEntry};
+/// A save of a competition's state and the columns it changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompetitionChange {
+    pub id: Uuid,
+    pub competition_id: Uuid,
+    /// Version the save produced
+    pub version: u64,
+    pub changed_fields: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CompetitionUpdateError {
+    /// The competition was saved elsewhere since it was loaded, reload it and apply the change
+    /// again
+    #[error(
+        "competition {competition_id} was saved at version {stored} since version {expected} was loaded"
+    )]
+    Conflict {
+        competition_id: Uuid,
+        expected: u64,
+        stored: u64,
+    },
+    #[error(transparent)]
+    Db(#[from] sqlx::Error),
+}
+
+impl CompetitionUpdateError {
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, CompetitionUpdateError::Conflict { .. })
+    }
+}
+
+/// A value `update_competitions` writes to a column
+enum ColumnValue {
+    Text(Option<String>),
+    Blob(Option<EncodedBlob>),
+}
+
+impl ColumnValue {
+    fn bind<'q>(
+        &self,
+        query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    ) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+        match self {
+            ColumnValue::Text(value) => query.bind(value.clone()),
+            ColumnValue::Blob(value) => query.bind(value.clone()),
+        }
+    }
+}
+
+fn json_column<T: Serialize>(value: Option<&T>) -> Result<ColumnValue, sqlx::Error> {
+    Ok(ColumnValue::Text(
+        value
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?,
+    ))
+}
+
+fn timestamp_column(value: Option<OffsetDateTime>) -> Result<ColumnValue, sqlx::Error> {
+    Ok(ColumnValue::Text(
+        value
+            .map(format_timestamp)
+            .transpose()
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?,
+    ))
+}
+
+/// The hash and invoice an expired reservation held when it was released
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleasedReservation {
+    pub hash: String,
+    pub payment_request: Option<String>,
+    pub ln_backend_id: Option<String>,
+}
+
+/// A stored JSON blob that no longer decodes into the type the column holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndecodableBlob {
+    pub table: String,
+    pub row_id: String,
+    pub column: String,
+    pub error: String,
+}
+
+impl UndecodableBlob {
+    fn from_errors(table: &str, row: &SqliteRow, errors: Vec<sqlx::Error>) -> Vec<Self> {
+        let row_id: String = row.try_get("id").unwrap_or_default();
+        errors
+            .into_iter()
+            .map(|error| {
+                let (column, error) = match error {
+                    // sqlx quotes the column name in errors from `try_get`
+                    sqlx::Error::ColumnDecode { index, source } => {
+                        (index.trim_matches('"').to_string(), source.to_string())
+                    }
+                    other => (String::new(), other.to_string()),
+                };
+                UndecodableBlob {
+                    table: table.to_string(),
+                    row_id: row_id.clone(),
+                    column,
+                    error,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Competitions that haven't reached a terminal state, matches the partial index
+/// `idx_competitions_active` so the watcher doesn't scan finished competitions
+const ACTIVE_COMPETITIONS: &str =
+    "expiry_broadcasted_at IS NULL AND completed_at IS NULL AND cancelled_at IS NULL";
+
+/// Competitions with their entry counts, limited to the rows matching `filter` when given
+fn competitions_query(filter: Option<&str>) -> String {
+    let (entries_filter, competitions_filter) = match filter {
+        Some(filter) => (
+            format!(
+                "WHERE entries.event_id IN (SELECT id FROM competitions WHERE {})",
+                filter
+            ),
+            format!("WHERE {}", filter),
+        ),
+        None => (String::new(), String::new()),
+    };
+
+    format!(
+        r#"
+            WITH payout_stats AS (
+                SELECT
+                    entries.event_id,
+                    COUNT(DISTINCT CASE WHEN payouts.succeed_at IS NOT NULL THEN payouts.entry_id END) as total_paid_out_entries
+                FROM entries
+                LEFT JOIN payouts ON entries.id = payouts.entry_id
+                {entries_filter}
+                GROUP BY entries.event_id
+            )
+            SELECT
+                competitions.id as id,
+                competitions.name as name,
+                competitions.slug as slug,
+                competitions.coordinator_key_id as coordinator_key_id,
+                created_at as created_at,
+                event_submission,
+                event_announcement,
+                COUNT(entries.id) as total_entries,
+                COUNT(CASE WHEN entries.public_nonces IS NOT NULL THEN entries.id END) as total_entry_nonces,
+                COUNT(CASE WHEN entries.signed_at IS NOT NULL THEN entries.id END) as total_signed_entries,
+                COUNT(tickets.paid_at) as total_paid_entries,
+                COUNT(tickets.comped_at) as total_comped_entries,
+                COUNT(CASE WHEN tickets.paid_at IS NOT NULL AND tickets.comped_at IS NULL THEN tickets.id END) as total_invoices,
+                COUNT(CASE WHEN tickets.settled_at IS NOT NULL AND tickets.comped_at IS NULL THEN tickets.id END) as total_settled_invoices,
+                COUNT(CASE WHEN tickets.settlement_error IS NOT NULL AND tickets.settled_at IS NULL THEN tickets.id END) as total_failed_settlements,
+                COALESCE(payout_stats.total_paid_out_entries, 0) as total_paid_out_entries,
+                outcome_transaction,
+                competitions.funding_psbt_base64 as funding_psbt_base64,
+                funding_fee_rate,
+                funding_outpoint,
+                funding_transaction,
+                contract_parameters,
+                competitions.public_nonces as public_nonces,
+                nonce_seed_outpoint,
+                aggregated_nonces,
+                competitions.partial_signatures as partial_signatures,
+                signed_contract,
+                attestation,
+                cancelled_at as cancelled_at,
+                contracted_at as contracted_at,
+                competitions.signed_at as signed_at,
+                escrow_funds_confirmed_at as escrow_funds_confirmed_at,
+                event_created_at as event_created_at,
+                entries_submitted_at as entries_submitted_at,
+                oracle_submitted_entry_ids,
+                funding_broadcasted_at as funding_broadcasted_at,
+                funding_confirmed_at as funding_confirmed_at,
+                funding_settled_at as funding_settled_at,
+                awaiting_attestation_at as awaiting_attestation_at,
+                invoices_settled_at as invoices_settled_at,
+                expiry_broadcasted_at as expiry_broadcasted_at,
+                outcome_broadcasted_at as outcome_broadcasted_at,
+                delta_broadcasted_at as delta_broadcasted_at,
+                completed_at as completed_at,
+                failed_at as failed_at,
+                keymeld_keygen_completed_at as keymeld_keygen_completed_at,
+                competitions.version as version,
+                errors
+            FROM competitions
+            LEFT JOIN payout_stats ON competitions.id = payout_stats.event_id
+            LEFT JOIN entries ON entries.event_id = competitions.id AND entries.cancelled_at IS NULL
+            LEFT JOIN tickets ON entries.ticket_id = tickets.id
+            {competitions_filter}
+            GROUP BY
+                competitions.id,
+                competitions.name,
+                competitions.slug,
+                competitions.coordinator_key_id,
+                created_at,
+                event_submission,
+                event_announcement,
+                outcome_transaction,
+                competitions.funding_psbt_base64,
+                funding_fee_rate,
+                funding_outpoint,
+                funding_transaction,
+                contract_parameters,
+                competitions.public_nonces,
+                nonce_seed_outpoint,
+                aggregated_nonces,
+                competitions.partial_signatures,
+                signed_contract,
+                attestation,
+                cancelled_at,
+                contracted_at,
+                competitions.signed_at,
+                escrow_funds_confirmed_at,
+                event_created_at,
+                entries_submitted_at,
+                oracle_submitted_entry_ids,
+                funding_broadcasted_at,
+                funding_confirmed_at,
+                funding_settled_at,
+                awaiting_attestation_at,
+                invoices_settled_at,
+                expiry_broadcasted_at,
+                outcome_broadcasted_at,
+                delta_broadcasted_at,
+                completed_at,
+                failed_at,
+                keymeld_keygen_completed_at,
+                competitions.version,
+                errors,
+                payout_stats.total_paid_out_entries"#
+    )
+}
 
 #[derive(Debug, Clone)]
 pub struct CompetitionStore {
@@ -23,6 +274,11 @@ impl CompetitionStore {
         Self { db_connection }
     }
 
+    /// Encode one of the large contract or signature blobs in the configured format
+    fn encode_blob<T: Serialize>(&self, value: &T) -> Result<EncodedBlob, sqlx::Error> {
+        encode_blob(value, self.db_connection.blob_format())
+    }
+
     pub async fn ping(&self) -> Result<(), sqlx::Error> {
         self.db_connection.ping().await
     }
@@ -31,6 +287,29 @@ impl CompetitionStore {
         self.db_connection.quick_check().await
     }
 
+    /// Scan every competition and entry row for JSON blobs that are present but can't be decoded
+    pub async fn find_undecodable_blobs(&self) -> Result<Vec<UndecodableBlob>, sqlx::Error> {
+        let mut undecodable = Vec::new();
+
+        let competitions = sqlx::query("SELECT * FROM competitions")
+            .fetch_all(self.db_connection.read())
+            .await?;
+        for row in &competitions {
+            let errors = Competition::blob_decode_errors(row);
+            undecodable.extend(UndecodableBlob::from_errors("competitions", row, errors));
+        }
+
+        let entries = sqlx::query("SELECT * FROM entries")
+            .fetch_all(self.db_connection.read())
+            .await?;
+        for row in &entries {
+            let errors = UserEntry::blob_decode_errors(row);
+            undecodable.extend(UndecodableBlob::from_errors("entries", row, errors));
+        }
+
+        Ok(undecodable)
+    }
+
     pub async fn get_stored_public_key(&self) -> Result<XOnlyPublicKey, sqlx::Error> {
         let key_bytes: Vec<u8> = sqlx::query_scalar("SELECT pubkey FROM coordinator_metadata")
             .fetch_one(self.db_connection.read())
@@ -65,6 +344,9 @@ impl CompetitionStore {
             })
     }
 
+    /// Claim the ticket for the entry and insert it in one transaction. The ticket is re-read
+    /// inside the transaction and must still be reserved by the entry's user and paid without
+    /// an entry, otherwise nothing is written and `RowNotFound` is returned.
     pub async fn add_entry(
         &self,
         entry: UserEntry,
@@ -88,6 +370,59 @@ impl CompetitionStore {
 
         self.db_connection
             .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
+
+                // Writes are serialized through the db writer, so the ticket can't be claimed or
+                // released between this read and the insert below
+                let ticket = sqlx::query_as::<_, Ticket>(
+                    r#"SELECT tickets.id as id,
+                              tickets.event_id as competition_id,
+                              entries.id as entry_id,
+                              tickets.ephemeral_pubkey as ephemeral_pubkey,
+                              encrypted_preimage,
+                              hash,
+                              payment_request,
+                              invoice_expires_at,
+                              strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
+                              reserved_by,
+                              reserved_at,
+                              paid_at,
+                              settled_at,
+                              escrow_transaction,
+                              ln_backend_id,
+                              comped_by,
+                              comped_at,
+                              comp_note,
+                              payment_receipt,
+                              settlement_error,
+                              settlement_failed_at,
+                              escrow_broadcasted_at
+                       FROM tickets
+                       LEFT JOIN entries ON tickets.id = entries.ticket_id
+                       WHERE tickets.id = ?
+                         AND tickets.event_id = ?"#,
+                )
+                .bind(&ticket_id_str)
+                .bind(&event_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                let claimable = ticket.is_some_and(|ticket| {
+                    ticket.reserved_by.as_deref() == Some(pubkey.as_str())
+                        && matches!(
+                            ticket.get_status(),
+                            TicketStatus::Paid | TicketStatus::Settled
+                        )
+                });
+                if !claimable {
+                    debug!(
+                        "Ticket {} can't be claimed by entry {}",
+                        ticket_id_str, entry_id
+                    );
+                    tx.rollback().await?;
+                    return Err(sqlx::Error::RowNotFound);
+                }
+
                 sqlx::query(
                     "INSERT INTO entries (
                         id,
@@ -114,8 +449,9 @@ impl CompetitionStore {
                 .bind(entry_submission)
                 .bind(encrypted_keymeld_private_key)
                 .bind(keymeld_auth_pubkey)
-                .execute(&pool)
+                .execute(&mut *tx)
                 .await?;
+                tx.commit().await?;
                 Ok(())
             })
             .await
@@ -127,32 +463,24 @@ impl CompetitionStore {
         Ok(entry)
     }
 
-    pub async fn add_final_signatures(
+    /// Store the receipt signed when a ticket's hold invoice settled
+    pub async fn set_ticket_receipt(
         &self,
-        entry_id: Uuid,
-        final_signatures: FinalSignatures,
-    ) -> Result<bool, sqlx::Error> {
-        let sigs_json = serde_json::to_string(&final_signatures.partial_signatures)
-            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-
-        let entry_id_str = entry_id.to_string();
-        let funding_psbt = final_signatures.funding_psbt_base64.clone();
+        ticket_id: Uuid,
+        receipt: &SignedReceipt,
+    ) -> Result<(), sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+        let receipt =
+            serde_json::to_string(receipt).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE entries
-                    SET partial_signatures = ?,
-                        funding_psbt_base64 = ?,
-                        signed_at = datetime('now')
-                    WHERE id = ?",
-                )
-                .bind(sigs_json)
-                .bind(funding_psbt)
-                .bind(entry_id_str)
-                .execute(&pool)
-                .await?;
-                Ok(result.rows_affected() > 0)
+                sqlx::query("UPDATE tickets SET payment_receipt = ? WHERE id = ?")
+                    .bind(receipt)
+                    .bind(ticket_id_str)
+                    .execute(&pool)
+                    .await?;
+                Ok(())
             })
             .await
             .map_err(|e| match e {
@@ -161,28 +489,24 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn add_public_nonces(
+    /// Store the receipt signed when an entry was accepted
+    pub async fn set_entry_receipt(
         &self,
         entry_id: Uuid,
-        public_nonces: SigMap<PubNonce>,
-    ) -> Result<bool, sqlx::Error> {
-        let nonces_json =
-            serde_json::to_string(&public_nonces).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-
+        receipt: &SignedReceipt,
+    ) -> Result<(), sqlx::Error> {
         let entry_id_str = entry_id.to_string();
+        let receipt =
+            serde_json::to_string(receipt).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE entries
-                    SET public_nonces = ?
-                    WHERE id = ?",
-                )
-                .bind(nonces_json)
-                .bind(entry_id_str)
-                .execute(&pool)
-                .await?;
-                Ok(result.rows_affected() > 0)
+                sqlx::query("UPDATE entries SET entry_receipt = ? WHERE id = ?")
+                    .bind(receipt)
+                    .bind(entry_id_str)
+                    .execute(&pool)
+                    .await?;
+                Ok(())
             })
             .await
             .map_err(|e| match e {
@@ -191,29 +515,30 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn mark_entry_sellback_broadcast(
+    /// Record the outcome of sending an entry's backup DM, a success clears an earlier error
+    pub async fn record_backup_dm(
         &self,
         entry_id: Uuid,
-        broadcast_time: OffsetDateTime,
-    ) -> Result<bool, sqlx::Error> {
-        let broadcast_time_str = broadcast_time
-            .format(&Rfc3339)
-            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-
+        sent_at: Option<OffsetDateTime>,
+        error: Option<String>,
+    ) -> Result<(), sqlx::Error> {
         let entry_id_str = entry_id.to_string();
+        let sent_at = sent_at
+            .map(format_timestamp)
+            .transpose()
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE entries
-                    SET sellback_broadcasted_at = ?
-                    WHERE id = ?",
+                sqlx::query(
+                    "UPDATE entries SET backup_dm_sent_at = ?, backup_dm_error = ? WHERE id = ?",
                 )
-                .bind(broadcast_time_str)
+                .bind(sent_at)
+                .bind(error)
                 .bind(entry_id_str)
                 .execute(&pool)
                 .await?;
-                Ok(result.rows_affected() > 0)
+                Ok(())
             })
             .await
             .map_err(|e| match e {
@@ -222,25 +547,21 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn mark_entry_reclaim_broadcast(
-        &self,
-        entry_id: Uuid,
-        broadcast_time: OffsetDateTime,
-    ) -> Result<bool, sqlx::Error> {
-        let broadcast_time_str = broadcast_time
-            .format(&Rfc3339)
-            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-
+    /// Drop an entry from its competition, false when it was already cancelled
+    pub async fn cancel_entry(&self, entry_id: Uuid, reason: &str) -> Result<bool, sqlx::Error> {
         let entry_id_str = entry_id.to_string();
+        let reason = reason.to_string();
+        let cancelled_at = format_timestamp(OffsetDateTime::now_utc())
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
                 let result = sqlx::query(
-                    "UPDATE entries
-                    SET reclaimed_broadcasted_at = ?
-                    WHERE id = ?",
+                    "UPDATE entries SET cancelled_at = ?, cancelled_reason = ?
+                     WHERE id = ? AND cancelled_at IS NULL",
                 )
-                .bind(broadcast_time_str)
+                .bind(cancelled_at)
+                .bind(reason)
                 .bind(entry_id_str)
                 .execute(&pool)
                 .await?;
@@ -253,92 +574,164 @@ impl CompetitionStore {
             })
     }
 
-    /// Update the keymeld_auth_pubkey for an entry.
-    /// This is called after the keygen session is created and the user has derived their auth pubkey.
-    pub async fn update_keymeld_auth_pubkey(
+    /// Drop entries whose escrow was double spent while the competition waits on escrow, and
+    /// shrink its event to the entries left, each dropped entry takes its entry fee out of the
+    /// pool. Only done before the oracle event exists. Returns the updated event submission and
+    /// the competition's new version, `None` when the oracle event was already created.
+    pub async fn cancel_double_spent_entries(
         &self,
-        entry_id: Uuid,
-        keymeld_auth_pubkey: String,
-    ) -> Result<bool, sqlx::Error> {
-        let entry_id_str = entry_id.to_string();
+        competition_id: Uuid,
+        entries: Vec<(Uuid, String)>,
+    ) -> Result<Option<(CreateEvent, u64)>, sqlx::Error> {
+        let cancelled_at = format_timestamp(OffsetDateTime::now_utc())
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE entries
-                    SET keymeld_auth_pubkey = ?
-                    WHERE id = ?",
+                let mut tx = pool.begin().await?;
+                let event_submission: Option<Vec<u8>> = sqlx::query_scalar(
+                    "SELECT event_submission FROM competitions
+                    WHERE id = ? AND event_created_at IS NULL",
                 )
-                .bind(keymeld_auth_pubkey)
-                .bind(entry_id_str)
-                .execute(&pool)
+                .bind(competition_id.to_string())
+                .fetch_optional(&mut *tx)
                 .await?;
-                Ok(result.rows_affected() > 0)
-            })
-            .await
-            .map_err(|e| match e {
-                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
-                e => sqlx::Error::Protocol(e.to_string()),
-            })
-    }
+                let Some(event_submission) = event_submission else {
+                    return Ok(None);
+                };
+                let mut event_submission: CreateEvent =
+                    decode_blob(&event_submission, "event_submission")?;
+
+                let mut cancelled = 0;
+                for (entry_id, reason) in &entries {
+                    let result = sqlx::query(
+                        "UPDATE entries SET cancelled_at = ?, cancelled_reason = ?
+                         WHERE id = ? AND event_id = ? AND cancelled_at IS NULL",
+                    )
+                    .bind(&cancelled_at)
+                    .bind(reason)
+                    .bind(entry_id.to_string())
+                    .bind(competition_id.to_string())
+                    .execute(&mut *tx)
+                    .await?;
+                    cancelled += result.rows_affected() as usize;
+                }
 
-    pub async fn store_payout_info_pending(
+                event_submission.total_allowed_entries = event_submission
+                    .total_allowed_entries
+                    .saturating_sub(cancelled);
+                event_submission.total_competition_pool = event_submission
+                    .total_competition_pool
+                    .saturating_sub(event_submission.entry_fee.saturating_mul(cancelled as u64));
+                let encoded = serde_json::to_string(&event_submission)
+                    .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+                let version: i64 = sqlx::query_scalar(
+                    "UPDATE competitions SET event_submission = ?, version = version + 1
+                    WHERE id = ?
+                    RETURNING version",
+                )
+                .bind(&encoded)
+                .bind(competition_id.to_string())
+                .fetch_one(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                Ok(Some((event_submission, version as u64)))
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    pub async fn add_final_signatures(
         &self,
         entry_id: Uuid,
-        payout_preimage: String,
-        ephemeral_private_key: String,
-        ln_invoice: String,
-        payout_amount_sats: u64,
-    ) -> Result<Uuid, sqlx::Error> {
-        let payout_id = Uuid::now_v7();
-        let initiated_at = OffsetDateTime::now_utc();
+        final_signatures: FinalSignatures,
+    ) -> Result<bool, sqlx::Error> {
+        let sigs_blob = self.encode_blob(&final_signatures.partial_signatures)?;
+
         let entry_id_str = entry_id.to_string();
-        let payout_id_str = payout_id.to_string();
-        let initiated_at_str = initiated_at
-            .format(&time::format_description::well_known::Rfc3339)
-            .unwrap();
+        let funding_psbt = final_signatures.funding_psbt_base64.clone();
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let mut tx = pool.begin().await?;
-
-                sqlx::query(
-                    "INSERT INTO payouts (
-                        id,
-                        entry_id,
-                        payout_payment_request,
-                        payout_amount_sats,
-                        initiated_at,
-                        succeed_at,
-                        failed_at,
-                        error
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                let result = sqlx::query(
+                    "UPDATE entries
+                    SET partial_signatures = ?,
+                        funding_psbt_base64 = ?,
+                        signed_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+                    WHERE id = ?",
                 )
-                .bind(&payout_id_str)
-                .bind(&entry_id_str)
-                .bind(&ln_invoice)
-                .bind(payout_amount_sats as i64)
-                .bind(&initiated_at_str)
-                .bind(None::<String>) // succeed_at
-                .bind(None::<String>) // failed_at
-                .bind(None::<String>)
-                .execute(&mut *tx)
+                .bind(sigs_blob)
+                .bind(funding_psbt)
+                .bind(entry_id_str)
+                .execute(&pool)
                 .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    /// Merge a chunk of partial signatures into what the entry already sent, returning the
+    /// merged SigMap. The entry isn't marked signed here, that's left to `add_final_signatures`
+    /// once the merged SigMap covers the contract.
+    pub async fn merge_entry_partial_signatures(
+        &self,
+        entry_id: Uuid,
+        chunk: SigMap<PartialSignature>,
+        funding_psbt_base64: Option<String>,
+    ) -> Result<Result<SigMap<PartialSignature>, SignatureChunkError>, sqlx::Error> {
+        let entry_id_str = entry_id.to_string();
+        let blob_format = self.db_connection.blob_format();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
+                let existing: Option<Option<Vec<u8>>> =
+                    sqlx::query_scalar("SELECT partial_signatures FROM entries WHERE id = ?")
+                        .bind(&entry_id_str)
+                        .fetch_optional(&mut *tx)
+                        .await?;
+                let Some(existing) = existing else {
+                    tx.rollback().await?;
+                    return Err(sqlx::Error::RowNotFound);
+                };
+
+                let mut merged = match existing {
+                    Some(blob) => {
+                        decode_blob::<SigMap<PartialSignature>>(&blob, "partial_signatures")?
+                    }
+                    None => SigMap {
+                        by_outcome: BTreeMap::new(),
+                        by_win_condition: BTreeMap::new(),
+                    },
+                };
+                if let Err(e) = merge_sig_map(&mut merged, chunk) {
+                    tx.rollback().await?;
+                    return Ok(Err(e));
+                }
 
+                let sigs_blob = encode_blob(&merged, blob_format)?;
                 sqlx::query(
                     "UPDATE entries
-                    SET payout_preimage = ?,
-                        ephemeral_privatekey = ?
+                    SET partial_signatures = ?,
+                        funding_psbt_base64 = COALESCE(?, funding_psbt_base64)
                     WHERE id = ?",
                 )
-                .bind(&payout_preimage)
-                .bind(&ephemeral_private_key)
+                .bind(sigs_blob)
+                .bind(funding_psbt_base64)
                 .bind(&entry_id_str)
                 .execute(&mut *tx)
                 .await?;
-
                 tx.commit().await?;
-                Ok(payout_id)
+
+                Ok(Ok(merged))
             })
             .await
             .map_err(|e| match e {
@@ -347,25 +740,18 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn mark_payout_succeeded(
-        &self,
-        payout_id: Uuid,
-        succeed_at: OffsetDateTime,
-    ) -> Result<(), sqlx::Error> {
-        let succeed_at_str = succeed_at
-            .format(&time::format_description::well_known::Rfc3339)
-            .unwrap();
-        let payout_id_str = payout_id.to_string();
+    /// Drop the partial signatures an unsigned entry sent so far, so it can resend them all
+    pub async fn clear_entry_partial_signatures(&self, entry_id: Uuid) -> Result<(), sqlx::Error> {
+        let entry_id_str = entry_id.to_string();
 
         self.db_connection
             .execute_write(move |pool| async move {
                 sqlx::query(
-                    "UPDATE payouts
-                    SET succeed_at = ?
-                    WHERE id = ?",
+                    "UPDATE entries
+                    SET partial_signatures = NULL
+                    WHERE id = ? AND signed_at IS NULL",
                 )
-                .bind(succeed_at_str)
-                .bind(payout_id_str)
+                .bind(entry_id_str)
                 .execute(&pool)
                 .await?;
                 Ok(())
@@ -377,32 +763,27 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn mark_payout_failed(
+    pub async fn add_public_nonces(
         &self,
-        payout_id: Uuid,
-        failed_at: OffsetDateTime,
-        error: PayoutError,
-    ) -> Result<(), sqlx::Error> {
-        let error_blob =
-            serde_json::to_string(&error).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-        let failed_at_str = failed_at
-            .format(&time::format_description::well_known::Rfc3339)
-            .unwrap();
-        let payout_id_str = payout_id.to_string();
+        entry_id: Uuid,
+        public_nonces: SigMap<PubNonce>,
+    ) -> Result<bool, sqlx::Error> {
+        let nonces_blob = self.encode_blob(&public_nonces)?;
+
+        let entry_id_str = entry_id.to_string();
 
         self.db_connection
             .execute_write(move |pool| async move {
-                sqlx::query(
-                    "UPDATE payouts
-                    SET failed_at = ?, error = ?
+                let result = sqlx::query(
+                    "UPDATE entries
+                    SET public_nonces = ?
                     WHERE id = ?",
                 )
-                .bind(failed_at_str)
-                .bind(error_blob)
-                .bind(payout_id_str)
+                .bind(nonces_blob)
+                .bind(entry_id_str)
                 .execute(&pool)
                 .await?;
-                Ok(())
+                Ok(result.rows_affected() > 0)
             })
             .await
             .map_err(|e| match e {
@@ -411,98 +792,651 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn get_payout(&self, payout_id: Uuid) -> Result<Option<EntryPayout>, sqlx::Error> {
-        sqlx::query_as::<_, EntryPayout>(
-            "SELECT
-                id,
-                entry_id,
-                payout_payment_request,
-                payout_amount_sats,
-                initiated_at,
-                succeed_at,
-                failed_at,
-                error
-            FROM payouts
-            WHERE id = ?",
-        )
-        .bind(payout_id.to_string())
-        .fetch_optional(self.db_connection.read())
-        .await
-    }
+    /// Forget every entry's nonces and signatures so the competition's nonce round starts over,
+    /// returns how many entries had submitted anything
+    pub async fn clear_entry_signing_round(
+        &self,
+        event_id: Uuid,
+    ) -> Result<Vec<(Uuid, String)>, sqlx::Error> {
+        let event_id_str = event_id.to_string();
 
-    pub async fn get_all_pending_payouts(&self) -> Result<Vec<EntryPayout>, sqlx::Error> {
-        let entry_payouts = sqlx::query_as::<_, EntryPayout>(
-            "SELECT
-                id,
-                entry_id,
-                payout_payment_request,
-                payout_amount_sats,
-                initiated_at,
-                succeed_at,
-                failed_at,
-                error
-            FROM payouts
-            WHERE succeed_at IS NULL AND failed_at IS NULL
-            ORDER BY initiated_at ASC",
-        )
-        .fetch_all(self.db_connection.read())
-        .await?;
+        let cleared: Vec<(String, String)> = self
+            .db_connection
+            .execute_write(move |pool| async move {
+                let cleared = sqlx::query_as(
+                    "UPDATE entries
+                    SET public_nonces = NULL,
+                        partial_signatures = NULL,
+                        funding_psbt_base64 = NULL,
+                        signed_at = NULL
+                    WHERE event_id = ? AND public_nonces IS NOT NULL
+                    RETURNING id, pubkey",
+                )
+                .bind(event_id_str)
+                .fetch_all(&pool)
+                .await?;
+                Ok(cleared)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })?;
 
-        Ok(entry_payouts)
+        cleared
+            .into_iter()
+            .map(|(id, pubkey)| {
+                Uuid::parse_str(&id)
+                    .map(|id| (id, pubkey))
+                    .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+            })
+            .collect()
     }
 
-    pub async fn get_payout_by_payment_hash(
+    /// Replace the keymeld registration data an entry submitted, `None` clears it so the
+    /// player has to register again
+    pub async fn set_entry_keymeld_registration(
         &self,
-        payment_hash: &str,
-    ) -> Result<Option<EntryPayout>, sqlx::Error> {
-        use crate::infra::lightning::extract_payment_hash_from_invoice;
-
-        let pending_payouts = self.get_all_pending_payouts().await?;
-
-        for payout in pending_payouts {
-            if let Ok(hash) = extract_payment_hash_from_invoice(&payout.payout_payment_request) {
-                if hash == payment_hash {
-                    return Ok(Some(payout));
-                }
-            }
-        }
+        entry_id: Uuid,
+        encrypted_keymeld_private_key: Option<String>,
+        keymeld_auth_pubkey: Option<String>,
+    ) -> Result<(), sqlx::Error> {
+        let entry_id_str = entry_id.to_string();
 
-        Ok(None)
+        self.db_connection
+            .execute_write(move |pool| async move {
+                sqlx::query(
+                    "UPDATE entries
+                    SET encrypted_keymeld_private_key = ?,
+                        keymeld_auth_pubkey = ?
+                    WHERE id = ?",
+                )
+                .bind(encrypted_keymeld_private_key)
+                .bind(keymeld_auth_pubkey)
+                .bind(entry_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
     }
 
-    pub async fn get_entry_payouts(
+    pub async fn mark_entry_sellback_broadcast(
         &self,
         entry_id: Uuid,
-        status_filter: Option<PayoutStatus>,
-    ) -> Result<Vec<EntryPayout>, sqlx::Error> {
-        let mut query_builder = sqlx::QueryBuilder::<Sqlite>::new(
-            "SELECT
-                id,
-                entry_id,
-                payout_payment_request,
-                payout_amount_sats,
-                initiated_at,
-                succeed_at,
-                failed_at,
-                error
-            FROM payouts
-            WHERE entry_id = ",
-        );
+        broadcast_time: OffsetDateTime,
+    ) -> Result<bool, sqlx::Error> {
+        let broadcast_time_str =
+            format_timestamp(broadcast_time).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
-        query_builder.push_bind(entry_id.to_string());
+        let entry_id_str = entry_id.to_string();
 
-        match status_filter {
-            Some(PayoutStatus::Pending) => {
-                query_builder.push(" AND succeed_at IS NULL AND failed_at IS NULL");
-            }
-            Some(PayoutStatus::Succeeded) => {
-                query_builder.push(" AND succeed_at IS NOT NULL");
-            }
-            Some(PayoutStatus::Failed) => {
-                query_builder.push(" AND failed_at IS NOT NULL");
-            }
-            None => {
-                // No additional conditions
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE entries
+                    SET sellback_broadcasted_at = ?
+                    WHERE id = ?",
+                )
+                .bind(broadcast_time_str)
+                .bind(entry_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    pub async fn mark_entry_reclaim_broadcast(
+        &self,
+        entry_id: Uuid,
+        broadcast_time: OffsetDateTime,
+    ) -> Result<bool, sqlx::Error> {
+        let broadcast_time_str =
+            format_timestamp(broadcast_time).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+        let entry_id_str = entry_id.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE entries
+                    SET reclaimed_broadcasted_at = ?
+                    WHERE id = ?",
+                )
+                .bind(broadcast_time_str)
+                .bind(entry_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    /// Record where a no-show entry's escrow is sent and the ephemeral key that signs for it
+    pub async fn register_escrow_reclaim(
+        &self,
+        entry_id: Uuid,
+        address: String,
+        ephemeral_private_key: String,
+    ) -> Result<bool, sqlx::Error> {
+        let entry_id_str = entry_id.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE entries
+                    SET escrow_reclaim_address = ?,
+                        ephemeral_privatekey = ?
+                    WHERE id = ? AND reclaimed_broadcasted_at IS NULL",
+                )
+                .bind(address)
+                .bind(ephemeral_private_key)
+                .bind(entry_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    /// Entries of cancelled competitions that never signed and hold an escrowed ticket, once
+    /// their player registered a reclaim address and until the reclaim is broadcast
+    pub async fn get_pending_escrow_reclaims(
+        &self,
+    ) -> Result<Vec<PendingEscrowReclaim>, sqlx::Error> {
+        let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(
+            "SELECT
+                tickets.event_id,
+                entries.id,
+                tickets.id,
+                entries.escrow_reclaim_address,
+                entries.ephemeral_privatekey
+            FROM entries
+            JOIN tickets ON tickets.id = entries.ticket_id
+            JOIN competitions ON competitions.id = tickets.event_id
+            WHERE competitions.cancelled_at IS NOT NULL
+              AND tickets.escrow_transaction IS NOT NULL
+              AND entries.signed_at IS NULL
+              AND entries.reclaimed_broadcasted_at IS NULL
+              AND entries.escrow_reclaim_address IS NOT NULL
+              AND entries.ephemeral_privatekey IS NOT NULL",
+        )
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        let parse_uuid = |value: &str, index: &str| {
+            Uuid::parse_str(value).map_err(|e| sqlx::Error::ColumnDecode {
+                index: index.to_string(),
+                source: Box::new(e),
+            })
+        };
+        rows.into_iter()
+            .map(
+                |(competition_id, entry_id, ticket_id, address, ephemeral_private_key)| {
+                    Ok(PendingEscrowReclaim {
+                        competition_id: parse_uuid(&competition_id, "event_id")?,
+                        entry_id: parse_uuid(&entry_id, "entry_id")?,
+                        ticket_id: parse_uuid(&ticket_id, "ticket_id")?,
+                        address,
+                        ephemeral_private_key,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Update the keymeld_auth_pubkey for an entry.
+    /// This is called after the keygen session is created and the user has derived their auth pubkey.
+    pub async fn update_keymeld_auth_pubkey(
+        &self,
+        entry_id: Uuid,
+        keymeld_auth_pubkey: String,
+    ) -> Result<bool, sqlx::Error> {
+        let entry_id_str = entry_id.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE entries
+                    SET keymeld_auth_pubkey = ?
+                    WHERE id = ?",
+                )
+                .bind(keymeld_auth_pubkey)
+                .bind(entry_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    /// Persist the contract player index of each entry. Entries that already have an index keep
+    /// it, so the order can't change once the contract has been built.
+    pub async fn set_player_indices(
+        &self,
+        player_indices: Vec<(Uuid, usize)>,
+    ) -> Result<(), sqlx::Error> {
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
+                for (entry_id, player_index) in player_indices {
+                    sqlx::query(
+                        "UPDATE entries
+                        SET player_index = ?
+                        WHERE id = ?
+                        AND player_index IS NULL",
+                    )
+                    .bind(player_index as i64)
+                    .bind(entry_id.to_string())
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    pub async fn store_payout_info_pending(
+        &self,
+        entry_id: Uuid,
+        payout_preimage: String,
+        ephemeral_private_key: String,
+        ln_invoice: String,
+        payout_amount_sats: Sats,
+    ) -> Result<Uuid, sqlx::Error> {
+        let payout_id = Uuid::now_v7();
+        let initiated_at = OffsetDateTime::now_utc();
+        let entry_id_str = entry_id.to_string();
+        let payout_id_str = payout_id.to_string();
+        let initiated_at_str = format_timestamp(initiated_at).unwrap();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
+
+                sqlx::query(
+                    "INSERT INTO payouts (
+                        id,
+                        entry_id,
+                        payout_payment_request,
+                        payout_amount_sats,
+                        initiated_at,
+                        succeed_at,
+                        failed_at,
+                        error
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&payout_id_str)
+                .bind(&entry_id_str)
+                .bind(&ln_invoice)
+                .bind(payout_amount_sats.to_sat() as i64)
+                .bind(&initiated_at_str)
+                .bind(None::<String>) // succeed_at
+                .bind(None::<String>) // failed_at
+                .bind(None::<String>)
+                .execute(&mut *tx)
+                .await?;
+
+                sqlx::query(
+                    "UPDATE entries
+                    SET payout_preimage = ?,
+                        ephemeral_privatekey = ?
+                    WHERE id = ?",
+                )
+                .bind(&payout_preimage)
+                .bind(&ephemeral_private_key)
+                .bind(&entry_id_str)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                Ok(payout_id)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    /// Mark a payout as paid, recording the payout and its routing fee on the ledger in the same
+    /// transaction. An unknown fee is left for a later report of the same payment to record.
+    pub async fn mark_payout_succeeded(
+        &self,
+        payout_id: Uuid,
+        succeed_at: OffsetDateTime,
+        fee_sats: Option<u64>,
+    ) -> Result<(), sqlx::Error> {
+        let succeed_at_str = format_timestamp(succeed_at).unwrap();
+        let payout_id_str = payout_id.to_string();
+        let payout_ledger_id = Uuid::now_v7().to_string();
+        let fee_ledger_id = Uuid::now_v7().to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
+                sqlx::query(
+                    "UPDATE payouts
+                    SET succeed_at = ?
+                    WHERE id = ?",
+                )
+                .bind(&succeed_at_str)
+                .bind(&payout_id_str)
+                .execute(&mut *tx)
+                .await?;
+
+                let mut movements = vec![(&payout_ledger_id, LedgerEntryKind::PayoutSent, None)];
+                if let Some(fee_sats) = fee_sats {
+                    movements.push((&fee_ledger_id, LedgerEntryKind::RoutingFee, Some(fee_sats)));
+                }
+                for (ledger_id, kind, amount) in movements {
+                    sqlx::query(
+                        "INSERT OR IGNORE INTO ledger_entries
+                            (id, competition_id, entry_id, kind, amount_sats, reference, recorded_at)
+                        SELECT ?, entries.event_id, entries.id, ?,
+                            COALESCE(?, payouts.payout_amount_sats), payouts.id, ?
+                        FROM payouts
+                        JOIN entries ON entries.id = payouts.entry_id
+                        WHERE payouts.id = ?",
+                    )
+                    .bind(ledger_id)
+                    .bind(kind.as_str())
+                    .bind(amount.map(|amount| amount as i64))
+                    .bind(&succeed_at_str)
+                    .bind(&payout_id_str)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                tx.commit().await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    pub async fn mark_payout_failed(
+        &self,
+        payout_id: Uuid,
+        failed_at: OffsetDateTime,
+        error: PayoutError,
+    ) -> Result<(), sqlx::Error> {
+        let error_blob =
+            serde_json::to_string(&error).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        let failed_at_str = format_timestamp(failed_at).unwrap();
+        let payout_id_str = payout_id.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                sqlx::query(
+                    "UPDATE payouts
+                    SET failed_at = ?, error = ?
+                    WHERE id = ?",
+                )
+                .bind(failed_at_str)
+                .bind(error_blob)
+                .bind(payout_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    pub async fn get_payout(&self, payout_id: Uuid) -> Result<Option<EntryPayout>, sqlx::Error> {
+        sqlx::query_as::<_, EntryPayout>(
+            "SELECT
+                id,
+                entry_id,
+                payout_payment_request,
+                payout_amount_sats,
+                initiated_at,
+                succeed_at,
+                failed_at,
+                error
+            FROM payouts
+            WHERE id = ?",
+        )
+        .bind(payout_id.to_string())
+        .fetch_optional(self.db_connection.read())
+        .await
+    }
+
+    pub async fn get_all_pending_payouts(&self) -> Result<Vec<EntryPayout>, sqlx::Error> {
+        let entry_payouts = sqlx::query_as::<_, EntryPayout>(
+            "SELECT
+                id,
+                entry_id,
+                payout_payment_request,
+                payout_amount_sats,
+                initiated_at,
+                succeed_at,
+                failed_at,
+                error
+            FROM payouts
+            WHERE succeed_at IS NULL AND failed_at IS NULL
+            ORDER BY initiated_at ASC",
+        )
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        Ok(entry_payouts)
+    }
+
+    /// Failed payout attempts per entry, only for entries that have not been paid out yet
+    pub async fn get_payout_failure_counts(&self) -> Result<Vec<PayoutFailureCount>, sqlx::Error> {
+        let rows: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT
+                entries.event_id,
+                payouts.entry_id,
+                COUNT(*) as failures
+            FROM payouts
+            JOIN entries ON entries.id = payouts.entry_id
+            WHERE payouts.failed_at IS NOT NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM payouts succeeded
+                  WHERE succeeded.entry_id = payouts.entry_id
+                    AND succeeded.succeed_at IS NOT NULL
+              )
+            GROUP BY entries.event_id, payouts.entry_id",
+        )
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        rows.into_iter()
+            .map(|(competition_id, entry_id, failures)| {
+                Ok(PayoutFailureCount {
+                    competition_id: Uuid::parse_str(&competition_id).map_err(|e| {
+                        sqlx::Error::ColumnDecode {
+                            index: "event_id".to_string(),
+                            source: Box::new(e),
+                        }
+                    })?,
+                    entry_id: Uuid::parse_str(&entry_id).map_err(|e| {
+                        sqlx::Error::ColumnDecode {
+                            index: "entry_id".to_string(),
+                            source: Box::new(e),
+                        }
+                    })?,
+                    failures: failures as u32,
+                })
+            })
+            .collect()
+    }
+
+    /// Number of tickets per active competition whose hold invoice was paid but never settled
+    pub async fn get_unsettled_paid_ticket_counts(
+        &self,
+    ) -> Result<HashMap<Uuid, u64>, sqlx::Error> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT
+                tickets.event_id,
+                COUNT(*) as unsettled
+            FROM tickets
+            JOIN competitions ON competitions.id = tickets.event_id
+            WHERE tickets.paid_at IS NOT NULL
+              AND tickets.settled_at IS NULL
+              AND competitions.cancelled_at IS NULL
+              AND competitions.completed_at IS NULL
+            GROUP BY tickets.event_id",
+        )
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        let mut counts = HashMap::new();
+        for (competition_id, unsettled) in rows {
+            let competition_id =
+                Uuid::parse_str(&competition_id).map_err(|e| sqlx::Error::ColumnDecode {
+                    index: "event_id".to_string(),
+                    source: Box::new(e),
+                })?;
+            counts.insert(competition_id, unsettled as u64);
+        }
+
+        Ok(counts)
+    }
+
+    /// Tickets still obtainable per competition, using the same availability rules as ticket
+    /// reservation (unused, and either never reserved or reservation lapsed without payment)
+    /// plus the tickets not issued yet. Competitions with no available tickets are left out of
+    /// the map.
+    pub async fn get_available_ticket_counts(
+        &self,
+        competition_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, u64>, sqlx::Error> {
+        if competition_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::<Sqlite>::new(
+            "SELECT
+                competitions.id,
+                competitions.unissued_tickets + (
+                    SELECT COUNT(*)
+                    FROM tickets
+                    LEFT JOIN entries ON tickets.id = entries.ticket_id
+                    WHERE tickets.event_id = competitions.id
+                      AND entries.id IS NULL
+                      AND (
+                          tickets.reserved_at IS NULL
+                          OR (
+                              tickets.reserved_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-10 minutes')
+                              AND tickets.paid_at IS NULL
+                          )
+                      )
+                ) as available
+            FROM competitions
+            WHERE competitions.id IN (",
+        );
+        let mut separated = query_builder.separated(", ");
+        for competition_id in competition_ids {
+            separated.push_bind(competition_id.to_string());
+        }
+        separated.push_unseparated(")");
+
+        let rows: Vec<(String, i64)> = query_builder
+            .build_query_as()
+            .fetch_all(self.db_connection.read())
+            .await?;
+
+        let mut counts = HashMap::new();
+        for (competition_id, available) in rows.into_iter().filter(|(_, n)| *n > 0) {
+            let competition_id =
+                Uuid::parse_str(&competition_id).map_err(|e| sqlx::Error::ColumnDecode {
+                    index: "id".to_string(),
+                    source: Box::new(e),
+                })?;
+            counts.insert(competition_id, available as u64);
+        }
+
+        Ok(counts)
+    }
+
+    pub async fn get_payout_by_payment_hash(
+        &self,
+        payment_hash: &str,
+    ) -> Result<Option<EntryPayout>, sqlx::Error> {
+        use crate::infra::lightning::extract_payment_hash_from_invoice;
+
+        let pending_payouts = self.get_all_pending_payouts().await?;
+
+        for payout in pending_payouts {
+            if let Ok(hash) = extract_payment_hash_from_invoice(&payout.payout_payment_request) {
+                if hash == payment_hash {
+                    return Ok(Some(payout));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_entry_payouts(
+        &self,
+        entry_id: Uuid,
+        status_filter: Option<PayoutStatus>,
+    ) -> Result<Vec<EntryPayout>, sqlx::Error> {
+        let mut query_builder = sqlx::QueryBuilder::<Sqlite>::new(
+            "SELECT
+                id,
+                entry_id,
+                payout_payment_request,
+                payout_amount_sats,
+                initiated_at,
+                succeed_at,
+                failed_at,
+                error
+            FROM payouts
+            WHERE entry_id = ",
+        );
+
+        query_builder.push_bind(entry_id.to_string());
+
+        match status_filter {
+            Some(PayoutStatus::Pending) => {
+                query_builder.push(" AND succeed_at IS NULL AND failed_at IS NULL");
+            }
+            Some(PayoutStatus::Succeeded) => {
+                query_builder.push(" AND succeed_at IS NOT NULL");
+            }
+            Some(PayoutStatus::Failed) => {
+                query_builder.push(" AND failed_at IS NOT NULL");
+            }
+            None => {
+                // No additional conditions
             }
         }
 
@@ -557,11 +1491,17 @@ impl CompetitionStore {
                 sellback_broadcasted_at,
                 reclaimed_broadcasted_at,
                 latest_payouts.latest_payout_time as paid_out_at,
-                latest_payouts.payout_payment_request as payout_ln_invoice
+                latest_payouts.payout_payment_request as payout_ln_invoice,
+                player_index,
+                backup_dm_sent_at,
+                backup_dm_error,
+                entry_receipt,
+                entries.cancelled_at as cancelled_at,
+                cancelled_reason
             FROM entries
             LEFT JOIN tickets ON entries.ticket_id = tickets.id
             LEFT JOIN latest_payouts ON entries.id = latest_payouts.entry_id AND latest_payouts.rn = 1
-            WHERE entries.event_id = ?",
+            WHERE entries.event_id = ? AND entries.cancelled_at IS NULL",
         );
 
         // Add status filtering
@@ -625,25 +1565,35 @@ impl CompetitionStore {
               sellback_broadcasted_at,
               reclaimed_broadcasted_at,
               latest_payouts.latest_payout_time as paid_out_at,
-              latest_payouts.payout_payment_request as payout_ln_invoice
+              latest_payouts.payout_payment_request as payout_ln_invoice,
+              player_index,
+              backup_dm_sent_at,
+              backup_dm_error,
+              entry_receipt,
+              entries.cancelled_at as cancelled_at,
+              cancelled_reason
           FROM entries
           LEFT JOIN tickets ON entries.ticket_id = tickets.id
           LEFT JOIN latest_payouts ON entries.id = latest_payouts.entry_id AND latest_payouts.rn = 1
-          WHERE pubkey = ?";
-
-        let (final_query, params) = if let Some(event_ids) = filter.event_ids {
-            if !event_ids.is_empty() {
-                let placeholders = event_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-                let query = format!("{} AND entries.event_id IN ({})", base_query, placeholders);
-                let mut all_params = vec![pubkey];
-                all_params.extend(event_ids.into_iter().map(|id| id.to_string()));
-                (query, all_params)
-            } else {
-                (base_query.to_string(), vec![pubkey])
-            }
-        } else {
-            (base_query.to_string(), vec![pubkey])
-        };
+          WHERE pubkey = ?";
+
+        let mut final_query = base_query.to_string();
+        let mut params = vec![pubkey];
+        if let Some(event_ids) = filter.event_ids.filter(|ids| !ids.is_empty()) {
+            let placeholders = event_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            final_query.push_str(&format!(" AND entries.event_id IN ({})", placeholders));
+            params.extend(event_ids.into_iter().map(|id| id.to_string()));
+        }
+        // Tags live in the competition's stored CreateEvent
+        for tag in filter.tags.unwrap_or_default() {
+            final_query.push_str(
+                " AND entries.event_id IN (
+                    SELECT competitions.id FROM competitions, json_each(competitions.event_submission, '$.tags')
+                    WHERE json_each.value = ?
+                )",
+            );
+            params.push(tag);
+        }
 
         let mut query_builder = sqlx::query_as::<_, UserEntry>(&final_query);
 
@@ -658,7 +1608,8 @@ impl CompetitionStore {
 
     /// Lightweight query for the entries list page.
     /// Joins entries with competitions to get observation dates and payout status
-    /// in a single query, avoiding N+1 competition fetches.
+    /// in a single query, avoiding N+1 competition fetches. The on-chain txid comes from the
+    /// entry's contract sweep in the ledger.
     pub async fn get_user_entry_views(
         &self,
         pubkey: String,
@@ -667,6 +1618,8 @@ impl CompetitionStore {
             WITH latest_payouts AS (
                 SELECT
                     entry_id,
+                    payout_payment_request,
+                    payout_amount_sats,
                     ROW_NUMBER() OVER (
                         PARTITION BY entry_id
                         ORDER BY COALESCE(succeed_at, initiated_at) DESC
@@ -674,6 +1627,18 @@ impl CompetitionStore {
                     COALESCE(succeed_at, initiated_at) as latest_payout_time
                 FROM payouts
                 WHERE failed_at IS NULL
+            ),
+            entry_sweeps AS (
+                SELECT
+                    entry_id,
+                    reference,
+                    amount_sats,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY entry_id
+                        ORDER BY recorded_at DESC
+                    ) as rn
+                FROM ledger_entries
+                WHERE kind = 'contract_sweep' AND entry_id IS NOT NULL
             )
             SELECT
                 entries.id as entry_id,
@@ -682,11 +1647,20 @@ impl CompetitionStore {
                 json_extract(competitions.event_submission, '$.end_observation_date') as end_time,
                 entries.signed_at as signed_at,
                 tickets.paid_at as paid_at,
-                latest_payouts.latest_payout_time as paid_out_at
+                latest_payouts.latest_payout_time as paid_out_at,
+                latest_payouts.payout_payment_request as payout_payment_request,
+                latest_payouts.payout_amount_sats as payout_amount_sats,
+                entries.sellback_broadcasted_at as sellback_broadcasted_at,
+                entries.reclaimed_broadcasted_at as reclaimed_broadcasted_at,
+                entry_sweeps.reference as sweep_txid,
+                entry_sweeps.amount_sats as sweep_amount_sats,
+                entries.cancelled_at as cancelled_at,
+                entries.cancelled_reason as cancelled_reason
             FROM entries
             JOIN competitions ON entries.event_id = competitions.id
             LEFT JOIN tickets ON entries.ticket_id = tickets.id
             LEFT JOIN latest_payouts ON entries.id = latest_payouts.entry_id AND latest_payouts.rn = 1
+            LEFT JOIN entry_sweeps ON entries.id = entry_sweeps.entry_id AND entry_sweeps.rn = 1
             WHERE entries.pubkey = ?
             ORDER BY json_extract(competitions.event_submission, '$.start_observation_date') DESC";
 
@@ -703,15 +1677,21 @@ impl CompetitionStore {
         competition: Competition,
         tickets: Vec<Ticket>,
     ) -> Result<Competition, sqlx::Error> {
-        let created_at = competition
-            .created_at
-            .format(&Rfc3339)
+        let created_at = format_timestamp(competition.created_at)
             .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         let event_submission = serde_json::to_string(&competition.event_submission)
             .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         let competition_id_str = competition.id.to_string();
+        let name = competition.name.clone();
+        let slug = competition.slug.clone();
+        let coordinator_key_id = competition.coordinator_key_id.clone();
+        // Tickets not created here are issued as players reserve them
+        let unissued_tickets = competition
+            .event_submission
+            .total_allowed_entries
+            .saturating_sub(tickets.len()) as i64;
 
         // Prepare ticket data for the closure
         let ticket_data: Vec<(String, String, String, String, Option<String>)> = tickets
@@ -734,13 +1714,21 @@ impl CompetitionStore {
                 sqlx::query(
                     "INSERT INTO competitions (
                         id,
+                        name,
+                        slug,
+                        coordinator_key_id,
                         created_at,
-                        event_submission
-                    ) VALUES (?, ?, ?)",
+                        event_submission,
+                        unissued_tickets
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?)",
                 )
                 .bind(&competition_id_str)
+                .bind(&name)
+                .bind(&slug)
+                .bind(&coordinator_key_id)
                 .bind(&created_at)
                 .bind(&event_submission)
+                .bind(unissued_tickets)
                 .execute(&mut *tx)
                 .await?;
 
@@ -775,312 +1763,318 @@ impl CompetitionStore {
         Ok(competition)
     }
 
+    /// The columns `update_competitions` writes, with the values `competition` holds for them
+    fn competition_columns(
+        &self,
+        competition: &Competition,
+    ) -> Result<Vec<(&'static str, ColumnValue)>, sqlx::Error> {
+        let oracle_submitted_entry_ids = if competition.oracle_submitted_entry_ids.is_empty() {
+            None
+        } else {
+            Some(&competition.oracle_submitted_entry_ids)
+        };
+        let errors = if competition.errors.is_empty() {
+            None
+        } else {
+            Some(&competition.errors)
+        };
+
+        Ok(vec![
+            (
+                "event_announcement",
+                json_column(competition.event_announcement.as_ref())?,
+            ),
+            (
+                "outcome_transaction",
+                json_column(competition.outcome_transaction.as_ref())?,
+            ),
+            (
+                "funding_psbt_base64",
+                ColumnValue::Text(competition.funding_psbt_base64.clone()),
+            ),
+            (
+                "funding_fee_rate",
+                json_column(competition.funding_fee_rate.as_ref())?,
+            ),
+            (
+                "funding_transaction",
+                json_column(competition.funding_transaction.as_ref())?,
+            ),
+            (
+                "funding_outpoint",
+                json_column(competition.funding_outpoint.as_ref())?,
+            ),
+            (
+                "contract_parameters",
+                self.blob_column(competition.contract_parameters.as_ref())?,
+            ),
+            (
+                "public_nonces",
+                self.blob_column(competition.public_nonces.as_ref())?,
+            ),
+            (
+                "nonce_seed_outpoint",
+                json_column(competition.nonce_seed_outpoint.as_ref())?,
+            ),
+            (
+                "aggregated_nonces",
+                self.blob_column(competition.aggregated_nonces.as_ref())?,
+            ),
+            (
+                "partial_signatures",
+                self.blob_column(competition.partial_signatures.as_ref())?,
+            ),
+            (
+                "signed_contract",
+                self.blob_column(competition.signed_contract.as_ref())?,
+            ),
+            (
+                "attestation",
+                json_column(competition.attestation.as_ref())?,
+            ),
+            ("cancelled_at", timestamp_column(competition.cancelled_at)?),
+            (
+                "contracted_at",
+                timestamp_column(competition.contracted_at)?,
+            ),
+            ("signed_at", timestamp_column(competition.signed_at)?),
+            (
+                "escrow_funds_confirmed_at",
+                timestamp_column(competition.escrow_funds_confirmed_at)?,
+            ),
+            (
+                "event_created_at",
+                timestamp_column(competition.event_created_at)?,
+            ),
+            (
+                "entries_submitted_at",
+                timestamp_column(competition.entries_submitted_at)?,
+            ),
+            (
+                "oracle_submitted_entry_ids",
+                json_column(oracle_submitted_entry_ids)?,
+            ),
+            (
+                "funding_broadcasted_at",
+                timestamp_column(competition.funding_broadcasted_at)?,
+            ),
+            (
+                "funding_confirmed_at",
+                timestamp_column(competition.funding_confirmed_at)?,
+            ),
+            (
+                "funding_settled_at",
+                timestamp_column(competition.funding_settled_at)?,
+            ),
+            (
+                "awaiting_attestation_at",
+                timestamp_column(competition.awaiting_attestation_at)?,
+            ),
+            (
+                "expiry_broadcasted_at",
+                timestamp_column(competition.expiry_broadcasted_at)?,
+            ),
+            (
+                "outcome_broadcasted_at",
+                timestamp_column(competition.outcome_broadcasted_at)?,
+            ),
+            (
+                "delta_broadcasted_at",
+                timestamp_column(competition.delta_broadcasted_at)?,
+            ),
+            ("completed_at", timestamp_column(competition.completed_at)?),
+            ("failed_at", timestamp_column(competition.failed_at)?),
+            (
+                "keymeld_keygen_completed_at",
+                timestamp_column(competition.keymeld_keygen_completed_at)?,
+            ),
+            (
+                "invoices_settled_at",
+                timestamp_column(competition.invoices_settled_at)?,
+            ),
+            ("errors", json_column(errors)?),
+        ])
+    }
+
+    fn blob_column<T: Serialize>(&self, value: Option<&T>) -> Result<ColumnValue, sqlx::Error> {
+        Ok(ColumnValue::Blob(
+            value.map(|value| self.encode_blob(value)).transpose()?,
+        ))
+    }
+
+    /// Save the state of each competition. A competition is only saved when its `version` is
+    /// still the stored one, otherwise it was saved elsewhere since it was loaded and none of
+    /// them are saved. Saved competitions get their new `version`, and the columns that
+    /// changed are logged and recorded in `competition_changes`. Competitions with nothing
+    /// changed aren't written and keep their version.
     pub async fn update_competitions(
         &self,
-        competitions: Vec<Competition>,
-    ) -> Result<(), sqlx::Error> {
+        competitions: &mut [Competition],
+    ) -> Result<(), CompetitionUpdateError> {
         // Prepare all competition data before moving into closure
         let mut prepared_updates = Vec::with_capacity(competitions.len());
-
-        for competition in competitions {
-            let event_announcement = competition
-                .event_announcement
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let outcome_transaction = competition
-                .outcome_transaction
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let funding_psbt_base64 = competition.funding_psbt_base64.clone();
-            let funding_transaction = competition
-                .funding_transaction
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let funding_outpoint = competition
-                .funding_outpoint
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let contract_parameters = competition
-                .contract_parameters
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let public_nonces = competition
-                .public_nonces
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let aggregated_nonces = competition
-                .aggregated_nonces
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let partial_signatures = competition
-                .partial_signatures
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let signed_contract = competition
-                .signed_contract
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let attestation = competition
-                .attestation
-                .as_ref()
-                .map(serde_json::to_string)
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let cancelled_at = competition
-                .cancelled_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let contracted_at = competition
-                .contracted_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let signed_at = competition
-                .signed_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let escrow_funds_confirmed_at = competition
-                .escrow_funds_confirmed_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let event_created_at = competition
-                .event_created_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let entries_submitted_at = competition
-                .entries_submitted_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let funding_broadcasted_at = competition
-                .funding_broadcasted_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let funding_confirmed_at = competition
-                .funding_confirmed_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let funding_settled_at = competition
-                .funding_settled_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let awaiting_attestation_at = competition
-                .awaiting_attestation_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let expiry_broadcasted_at = competition
-                .expiry_broadcasted_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let outcome_broadcasted_at = competition
-                .outcome_broadcasted_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let delta_broadcasted_at = competition
-                .delta_broadcasted_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let completed_at = competition
-                .completed_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let failed_at = competition
-                .failed_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let keymeld_keygen_completed_at = competition
-                .keymeld_keygen_completed_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let invoices_settled_at = competition
-                .invoices_settled_at
-                .map(|ts| ts.format(&Rfc3339))
-                .transpose()
-                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-            let errors = if !competition.errors.is_empty() {
-                Some(
-                    serde_json::to_string(&competition.errors)
-                        .map_err(|e| sqlx::Error::Encode(Box::new(e)))?,
-                )
-            } else {
-                None
-            };
-            let competition_id = competition.id.to_string();
-
+        for competition in competitions.iter() {
             prepared_updates.push((
-                event_announcement,
-                outcome_transaction,
-                funding_psbt_base64,
-                funding_transaction,
-                funding_outpoint,
-                contract_parameters,
-                public_nonces,
-                aggregated_nonces,
-                partial_signatures,
-                signed_contract,
-                attestation,
-                cancelled_at,
-                contracted_at,
-                signed_at,
-                escrow_funds_confirmed_at,
-                event_created_at,
-                entries_submitted_at,
-                funding_broadcasted_at,
-                funding_confirmed_at,
-                funding_settled_at,
-                awaiting_attestation_at,
-                expiry_broadcasted_at,
-                outcome_broadcasted_at,
-                delta_broadcasted_at,
-                completed_at,
-                failed_at,
-                keymeld_keygen_completed_at,
-                invoices_settled_at,
-                errors,
-                competition_id,
+                competition.id,
+                competition.version,
+                self.competition_columns(competition)?,
             ));
         }
+        let now = format_timestamp(OffsetDateTime::now_utc())
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
-        self.db_connection
+        let outcome = self
+            .db_connection
             .execute_write(move |pool| async move {
-                let query = "UPDATE competitions SET
-                    event_announcement = ?,
-                    outcome_transaction = ?,
-                    funding_psbt_base64 = ?,
-                    funding_transaction = ?,
-                    funding_outpoint = ?,
-                    contract_parameters = ?,
-                    public_nonces = ?,
-                    aggregated_nonces = ?,
-                    partial_signatures = ?,
-                    signed_contract = ?,
-                    attestation = ?,
-                    cancelled_at = ?,
-                    contracted_at = ?,
-                    signed_at = ?,
-                    escrow_funds_confirmed_at = ?,
-                    event_created_at = ?,
-                    entries_submitted_at = ?,
-                    funding_broadcasted_at = ?,
-                    funding_confirmed_at = ?,
-                    funding_settled_at = ?,
-                    awaiting_attestation_at = ?,
-                    expiry_broadcasted_at = ?,
-                    outcome_broadcasted_at = ?,
-                    delta_broadcasted_at = ?,
-                    completed_at = ?,
-                    failed_at = ?,
-                    keymeld_keygen_completed_at = ?,
-                    invoices_settled_at = ?,
-                    errors = ?
-                    WHERE id = ?";
-
-                for (
-                    event_announcement,
-                    outcome_transaction,
-                    funding_psbt_base64,
-                    funding_transaction,
-                    funding_outpoint,
-                    contract_parameters,
-                    public_nonces,
-                    aggregated_nonces,
-                    partial_signatures,
-                    signed_contract,
-                    attestation,
-                    cancelled_at,
-                    contracted_at,
-                    signed_at,
-                    escrow_funds_confirmed_at,
-                    event_created_at,
-                    entries_submitted_at,
-                    funding_broadcasted_at,
-                    funding_confirmed_at,
-                    funding_settled_at,
-                    awaiting_attestation_at,
-                    expiry_broadcasted_at,
-                    outcome_broadcasted_at,
-                    delta_broadcasted_at,
-                    completed_at,
-                    failed_at,
-                    keymeld_keygen_completed_at,
-                    invoices_settled_at,
-                    errors,
-                    competition_id,
-                ) in prepared_updates
-                {
-                    sqlx::query(query)
-                        .bind(event_announcement)
-                        .bind(outcome_transaction)
-                        .bind(funding_psbt_base64)
-                        .bind(funding_transaction)
-                        .bind(funding_outpoint)
-                        .bind(contract_parameters)
-                        .bind(public_nonces)
-                        .bind(aggregated_nonces)
-                        .bind(partial_signatures)
-                        .bind(signed_contract)
-                        .bind(attestation)
-                        .bind(cancelled_at)
-                        .bind(contracted_at)
-                        .bind(signed_at)
-                        .bind(escrow_funds_confirmed_at)
-                        .bind(event_created_at)
-                        .bind(entries_submitted_at)
-                        .bind(funding_broadcasted_at)
-                        .bind(funding_confirmed_at)
-                        .bind(funding_settled_at)
-                        .bind(awaiting_attestation_at)
-                        .bind(expiry_broadcasted_at)
-                        .bind(outcome_broadcasted_at)
-                        .bind(delta_broadcasted_at)
-                        .bind(completed_at)
-                        .bind(failed_at)
-                        .bind(keymeld_keygen_completed_at)
-                        .bind(invoices_settled_at)
-                        .bind(errors)
-                        .bind(competition_id)
-                        .execute(&pool)
+                let mut tx = pool.begin().await?;
+                let mut saved = Vec::new();
+
+                for (competition_id, expected_version, columns) in prepared_updates {
+                    // `IS NOT` compares NULLs too, a stored value changes when it differs
+                    let changed_query = format!(
+                        "SELECT version, {} FROM competitions WHERE id = ?",
+                        columns
+                            .iter()
+                            .map(|(column, _)| format!("({column} IS NOT ?) AS {column}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                    let mut query = sqlx::query(&changed_query);
+                    for (_, value) in &columns {
+                        query = value.bind(query);
+                    }
+                    let Some(row) = query
+                        .bind(competition_id.to_string())
+                        .fetch_optional(&mut *tx)
+                        .await?
+                    else {
+                        warn!("Competition {} to update no longer exists", competition_id);
+                        continue;
+                    };
+
+                    let stored_version = row.try_get::<i64, _>("version")? as u64;
+                    if stored_version != expected_version {
+                        // Dropping the transaction rolls back the competitions saved before it
+                        return Ok(Err(CompetitionUpdateError::Conflict {
+                            competition_id,
+                            expected: expected_version,
+                            stored: stored_version,
+                        }));
+                    }
+                    let mut changed_fields = Vec::new();
+                    for (column, _) in &columns {
+                        if row.try_get::<bool, _>(*column)? {
+                            changed_fields.push(*column);
+                        }
+                    }
+                    if changed_fields.is_empty() {
+                        continue;
+                    }
+
+                    let update_query = format!(
+                        "UPDATE competitions SET {}, version = version + 1
+                         WHERE id = ? AND version = ?",
+                        columns
+                            .iter()
+                            .map(|(column, _)| format!("{column} = ?"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                    let mut query = sqlx::query(&update_query);
+                    for (_, value) in &columns {
+                        query = value.bind(query);
+                    }
+                    query
+                        .bind(competition_id.to_string())
+                        .bind(expected_version as i64)
+                        .execute(&mut *tx)
                         .await?;
+
+                    let version = expected_version + 1;
+                    let changed_fields_json = serde_json::to_string(&changed_fields)
+                        .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+                    sqlx::query(
+                        "INSERT INTO competition_changes (
+                            id, competition_id, version, changed_fields, created_at
+                        ) VALUES (?, ?, ?, ?, ?)",
+                    )
+                    .bind(Uuid::now_v7().to_string())
+                    .bind(competition_id.to_string())
+                    .bind(version as i64)
+                    .bind(changed_fields_json)
+                    .bind(&now)
+                    .execute(&mut *tx)
+                    .await?;
+
+                    saved.push((competition_id, version, changed_fields));
                 }
-                Ok(())
+
+                tx.commit().await?;
+                Ok(Ok(saved))
             })
             .await
             .map_err(|e| match e {
                 crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
                 e => sqlx::Error::Protocol(e.to_string()),
+            })??;
+
+        for (competition_id, version, changed_fields) in outcome {
+            debug!(
+                "Saved competition {} at version {}, changed: {}",
+                competition_id,
+                version,
+                changed_fields.join(", ")
+            );
+            if let Some(competition) = competitions
+                .iter_mut()
+                .find(|competition| competition.id == competition_id)
+            {
+                competition.version = version;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every recorded save of a competition, oldest first
+    pub async fn get_competition_changes(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<Vec<CompetitionChange>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, competition_id, version, changed_fields, created_at
+            FROM competition_changes
+            WHERE competition_id = ?
+            ORDER BY version",
+        )
+        .bind(competition_id.to_string())
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let parse_uuid = |column: &str| {
+                    Uuid::parse_str(&row.try_get::<String, _>(column)?).map_err(|e| {
+                        sqlx::Error::ColumnDecode {
+                            index: column.to_string(),
+                            source: Box::new(e),
+                        }
+                    })
+                };
+                let changed_fields: String = row.try_get("changed_fields")?;
+                Ok(CompetitionChange {
+                    id: parse_uuid("id")?,
+                    competition_id: parse_uuid("competition_id")?,
+                    version: row.try_get::<i64, _>("version")? as u64,
+                    changed_fields: serde_json::from_str(&changed_fields).map_err(|e| {
+                        sqlx::Error::ColumnDecode {
+                            index: "changed_fields".to_string(),
+                            source: Box::new(e),
+                        }
+                    })?,
+                    created_at: parse_required_datetime(row, "created_at")?,
+                })
             })
+            .collect()
     }
 
     pub async fn get_competitions(
@@ -1088,137 +2082,7 @@ impl CompetitionStore {
         active_only: bool,
         use_write_pool: bool,
     ) -> Result<Vec<Competition>, sqlx::Error> {
-        let base_query = r#"
-            WITH payout_stats AS (
-                SELECT
-                    entries.event_id,
-                    COUNT(DISTINCT CASE WHEN payouts.succeed_at IS NOT NULL THEN payouts.entry_id END) as total_paid_out_entries
-                FROM entries
-                LEFT JOIN payouts ON entries.id = payouts.entry_id
-                GROUP BY entries.event_id
-            )
-            SELECT
-                competitions.id as id,
-                created_at as created_at,
-                event_submission,
-                event_announcement,
-                COUNT(entries.id) as total_entries,
-                COUNT(CASE WHEN entries.public_nonces IS NOT NULL THEN entries.id END) as total_entry_nonces,
-                COUNT(CASE WHEN entries.signed_at IS NOT NULL THEN entries.id END) as total_signed_entries,
-                COUNT(tickets.paid_at) as total_paid_entries,
-                COALESCE(payout_stats.total_paid_out_entries, 0) as total_paid_out_entries,
-                outcome_transaction,
-                competitions.funding_psbt_base64 as funding_psbt_base64,
-                funding_outpoint,
-                funding_transaction,
-                contract_parameters,
-                competitions.public_nonces as public_nonces,
-                aggregated_nonces,
-                competitions.partial_signatures as partial_signatures,
-                signed_contract,
-                attestation,
-                cancelled_at as cancelled_at,
-                contracted_at as contracted_at,
-                competitions.signed_at as signed_at,
-                escrow_funds_confirmed_at as escrow_funds_confirmed_at,
-                event_created_at as event_created_at,
-                entries_submitted_at as entries_submitted_at,
-                funding_broadcasted_at as funding_broadcasted_at,
-                funding_confirmed_at as funding_confirmed_at,
-                funding_settled_at as funding_settled_at,
-                awaiting_attestation_at as awaiting_attestation_at,
-                invoices_settled_at as invoices_settled_at,
-                expiry_broadcasted_at as expiry_broadcasted_at,
-                outcome_broadcasted_at as outcome_broadcasted_at,
-                delta_broadcasted_at as delta_broadcasted_at,
-                completed_at as completed_at,
-                failed_at as failed_at,
-                keymeld_keygen_completed_at as keymeld_keygen_completed_at,
-                errors
-            FROM competitions
-            LEFT JOIN payout_stats ON competitions.id = payout_stats.event_id
-            LEFT JOIN entries ON entries.event_id = competitions.id
-            LEFT JOIN tickets ON entries.ticket_id = tickets.id"#;
-
-        let final_query = if active_only {
-            format!(
-                "{} WHERE expiry_broadcasted_at IS NULL AND completed_at IS NULL AND cancelled_at IS NULL
-                GROUP BY
-                    competitions.id,
-                    created_at,
-                    event_submission,
-                    event_announcement,
-                    outcome_transaction,
-                    competitions.funding_psbt_base64,
-                    funding_outpoint,
-                    funding_transaction,
-                    contract_parameters,
-                    competitions.public_nonces,
-                    aggregated_nonces,
-                    competitions.partial_signatures,
-                    signed_contract,
-                    attestation,
-                    cancelled_at,
-                    contracted_at,
-                    competitions.signed_at,
-                    escrow_funds_confirmed_at,
-                    event_created_at,
-                    entries_submitted_at,
-                    funding_broadcasted_at,
-                    funding_confirmed_at,
-                    funding_settled_at,
-                    awaiting_attestation_at,
-                    invoices_settled_at,
-                    expiry_broadcasted_at,
-                    outcome_broadcasted_at,
-                    delta_broadcasted_at,
-                    completed_at,
-                    failed_at,
-                    keymeld_keygen_completed_at,
-                    errors,
-                    payout_stats.total_paid_out_entries",
-                base_query
-            )
-        } else {
-            format!(
-                "{}
-                GROUP BY
-                    competitions.id,
-                    created_at,
-                    event_submission,
-                    event_announcement,
-                    outcome_transaction,
-                    competitions.funding_psbt_base64,
-                    funding_outpoint,
-                    funding_transaction,
-                    contract_parameters,
-                    competitions.public_nonces,
-                    aggregated_nonces,
-                    competitions.partial_signatures,
-                    signed_contract,
-                    attestation,
-                    cancelled_at,
-                    contracted_at,
-                    competitions.signed_at,
-                    escrow_funds_confirmed_at,
-                    event_created_at,
-                    entries_submitted_at,
-                    funding_broadcasted_at,
-                    funding_confirmed_at,
-                    funding_settled_at,
-                    awaiting_attestation_at,
-                    invoices_settled_at,
-                    expiry_broadcasted_at,
-                    outcome_broadcasted_at,
-                    delta_broadcasted_at,
-                    completed_at,
-                    failed_at,
-                    keymeld_keygen_completed_at,
-                    errors,
-                    payout_stats.total_paid_out_entries",
-                base_query
-            )
-        };
+        let query = competitions_query(active_only.then_some(ACTIVE_COMPETITIONS));
 
         let pool = if use_write_pool {
             self.db_connection.write_pool()
@@ -1226,13 +2090,99 @@ impl CompetitionStore {
             self.db_connection.read()
         };
 
-        let competitions = sqlx::query_as::<_, Competition>(&final_query)
+        let competitions = sqlx::query_as::<_, Competition>(&query)
             .fetch_all(pool)
             .await?;
 
         Ok(competitions)
     }
 
+    /// Competitions the watcher has work to do on, active ones that haven't failed. Read from
+    /// the write pool so a tick sees the previous tick's writes.
+    pub async fn get_competitions_to_process(&self) -> Result<Vec<Competition>, sqlx::Error> {
+        let query = competitions_query(Some(&format!(
+            "{} AND failed_at IS NULL",
+            ACTIVE_COMPETITIONS
+        )));
+
+        let competitions = sqlx::query_as::<_, Competition>(&query)
+            .fetch_all(self.db_connection.write_pool())
+            .await?;
+
+        Ok(competitions)
+    }
+
+    /// Cancel competitions that failed longer than `failed_for` ago, returning their ids
+    pub async fn cancel_failed_competitions(
+        &self,
+        failed_for: time::Duration,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let now = OffsetDateTime::now_utc();
+        let cancelled_at = format_timestamp(now).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        let failed_before =
+            format_timestamp(now - failed_for).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+        let ids: Vec<String> = self
+            .db_connection
+            .execute_write(move |pool| async move {
+                let ids = sqlx::query_scalar(
+                    "UPDATE competitions
+                    SET cancelled_at = ?, version = version + 1
+                    WHERE failed_at IS NOT NULL
+                    AND failed_at < ?
+                    AND expiry_broadcasted_at IS NULL
+                    AND completed_at IS NULL
+                    AND cancelled_at IS NULL
+                    RETURNING id",
+                )
+                .bind(cancelled_at)
+                .bind(failed_before)
+                .fetch_all(&pool)
+                .await?;
+                Ok(ids)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })?;
+
+        ids.iter()
+            .map(|id| Uuid::parse_str(id).map_err(|e| sqlx::Error::Decode(Box::new(e))))
+            .collect()
+    }
+
+    /// Id of the competition with this slug
+    pub async fn get_competition_id_by_slug(
+        &self,
+        slug: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id: Option<String> = sqlx::query_scalar("SELECT id FROM competitions WHERE slug = ?")
+            .bind(slug)
+            .fetch_optional(self.db_connection.read())
+            .await?;
+        id.map(|id| Uuid::parse_str(&id).map_err(|e| sqlx::Error::Decode(Box::new(e))))
+            .transpose()
+    }
+
+    /// Whether another competition already uses this name (case insensitive) or slug
+    pub async fn competition_name_or_slug_taken(
+        &self,
+        name: Option<&str>,
+        slug: Option<&str>,
+    ) -> Result<(bool, bool), sqlx::Error> {
+        let (name_taken, slug_taken): (bool, bool) = sqlx::query_as(
+            "SELECT
+                EXISTS(SELECT 1 FROM competitions WHERE name = ? COLLATE NOCASE),
+                EXISTS(SELECT 1 FROM competitions WHERE slug = ?)",
+        )
+        .bind(name)
+        .bind(slug)
+        .fetch_one(self.db_connection.read())
+        .await?;
+        Ok((name_taken, slug_taken))
+    }
+
     pub async fn get_competition(&self, competition_id: Uuid) -> Result<Competition, sqlx::Error> {
         let query_str = r#"
             WITH payout_stats AS (
@@ -1246,6 +2196,9 @@ impl CompetitionStore {
                     )
             SELECT
                 competitions.id as id,
+                competitions.name as name,
+                competitions.slug as slug,
+                competitions.coordinator_key_id as coordinator_key_id,
                 created_at as created_at,
                 event_submission,
                 event_announcement,
@@ -1253,13 +2206,19 @@ impl CompetitionStore {
                 COUNT(CASE WHEN entries.public_nonces IS NOT NULL THEN entries.id END) as total_entry_nonces,
                 COUNT(CASE WHEN entries.signed_at IS NOT NULL THEN entries.id END) as total_signed_entries,
                 COUNT(tickets.paid_at) as total_paid_entries,
+                COUNT(tickets.comped_at) as total_comped_entries,
+                COUNT(CASE WHEN tickets.paid_at IS NOT NULL AND tickets.comped_at IS NULL THEN tickets.id END) as total_invoices,
+                COUNT(CASE WHEN tickets.settled_at IS NOT NULL AND tickets.comped_at IS NULL THEN tickets.id END) as total_settled_invoices,
+                COUNT(CASE WHEN tickets.settlement_error IS NOT NULL AND tickets.settled_at IS NULL THEN tickets.id END) as total_failed_settlements,
                 COALESCE(payout_stats.total_paid_out_entries, 0) as total_paid_out_entries,
                 outcome_transaction,
                 competitions.funding_psbt_base64 as funding_psbt_base64,
+                funding_fee_rate,
                 funding_outpoint,
                 funding_transaction,
                 contract_parameters,
                 competitions.public_nonces as public_nonces,
+                nonce_seed_outpoint,
                 aggregated_nonces,
                 competitions.partial_signatures as partial_signatures,
                 signed_contract,
@@ -1270,6 +2229,7 @@ impl CompetitionStore {
                 escrow_funds_confirmed_at as escrow_funds_confirmed_at,
                 event_created_at as event_created_at,
                 entries_submitted_at as entries_submitted_at,
+                oracle_submitted_entry_ids,
                 funding_broadcasted_at as funding_broadcasted_at,
                 funding_confirmed_at as funding_confirmed_at,
                 funding_settled_at as funding_settled_at,
@@ -1281,23 +2241,29 @@ impl CompetitionStore {
                 completed_at as completed_at,
                 failed_at as failed_at,
                 keymeld_keygen_completed_at as keymeld_keygen_completed_at,
+                competitions.version as version,
                 errors
             FROM competitions
             LEFT JOIN payout_stats ON competitions.id = payout_stats.event_id
-            LEFT JOIN entries ON entries.event_id = competitions.id
+            LEFT JOIN entries ON entries.event_id = competitions.id AND entries.cancelled_at IS NULL
             LEFT JOIN tickets ON entries.ticket_id = tickets.id
             WHERE competitions.id = ?
             GROUP BY
                 competitions.id,
+                competitions.name,
+                competitions.slug,
+                competitions.coordinator_key_id,
                 created_at,
                 event_submission,
                 event_announcement,
                 outcome_transaction,
                 competitions.funding_psbt_base64,
+                funding_fee_rate,
                 funding_outpoint,
                 funding_transaction,
                 contract_parameters,
                 competitions.public_nonces,
+                nonce_seed_outpoint,
                 aggregated_nonces,
                 competitions.partial_signatures,
                 signed_contract,
@@ -1308,6 +2274,7 @@ impl CompetitionStore {
                 escrow_funds_confirmed_at,
                 event_created_at,
                 entries_submitted_at,
+                oracle_submitted_entry_ids,
                 funding_broadcasted_at,
                 funding_confirmed_at,
                 funding_settled_at,
@@ -1319,6 +2286,7 @@ impl CompetitionStore {
                 completed_at,
                 failed_at,
                 keymeld_keygen_completed_at,
+                competitions.version,
                 errors"#;
 
         let competition = sqlx::query_as::<_, Competition>(query_str)
@@ -1330,13 +2298,17 @@ impl CompetitionStore {
         Ok(competition)
     }
 
+    /// Reserve a ticket for `pubkey`, or return the one they already hold, binding it to the
+    /// player's `ephemeral_pubkey`. A paid ticket keeps the pubkey it was paid with.
     pub async fn get_and_reserve_ticket(
         &self,
         competition_id: Uuid,
         pubkey: &str,
+        ephemeral_pubkey: &str,
     ) -> Result<Ticket, sqlx::Error> {
         let competition_id_str = competition_id.to_string();
         let pubkey_owned = pubkey.to_string();
+        let ephemeral_pubkey = ephemeral_pubkey.to_string();
 
         self.db_connection
             .execute_write(move |pool| async move {
@@ -1353,115 +2325,525 @@ impl CompetitionStore {
                               hash,
                               payment_request,
                               invoice_expires_at,
-                              datetime('now', '+10 minutes') as expiry,
+                              strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
+                              reserved_by,
+                              reserved_at,
+                              paid_at,
+                              settled_at,
+                              escrow_transaction,
+                              ln_backend_id,
+                              comped_by,
+                              comped_at,
+                              comp_note,
+                              payment_receipt,
+                              settlement_error,
+                              settlement_failed_at,
+                              escrow_broadcasted_at
+                       FROM tickets
+                       LEFT JOIN entries ON tickets.id = entries.ticket_id
+                       WHERE tickets.event_id = ?
+                         AND tickets.reserved_by = ?
+                         AND entries.id IS NULL
+                       LIMIT 1"#,
+                )
+                .bind(&competition_id_str)
+                .bind(&pubkey_owned)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                if let Some(mut ticket) = existing_ticket {
+                    debug!("Found existing reserved ticket {} for user", ticket.id);
+                    if ticket.paid_at.is_none() {
+                        sqlx::query("UPDATE tickets SET ephemeral_pubkey = ? WHERE id = ?")
+                            .bind(&ephemeral_pubkey)
+                            .bind(ticket.id.to_string())
+                            .execute(&mut *tx)
+                            .await?;
+                        ticket.ephemeral_pubkey = Some(ephemeral_pubkey);
+                    }
+                    tx.commit().await?;
+                    return Ok(ticket);
+                }
+
+                // No existing ticket, find an available one
+                let ticket_id: Option<String> = sqlx::query_scalar(
+                    r#"SELECT tickets.id
+                       FROM tickets
+                       LEFT JOIN entries ON tickets.id = entries.ticket_id
+                       WHERE tickets.event_id = ?
+                         AND entries.id IS NULL
+                         AND (
+                             reserved_at IS NULL
+                             OR (
+                                 reserved_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-10 minutes')
+                                 AND paid_at IS NULL
+                             )
+                         )
+                       ORDER BY
+                           reserved_at IS NULL DESC,
+                           reserved_at,
+                           tickets.id
+                       LIMIT 1"#,
+                )
+                .bind(&competition_id_str)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                let ticket_id = match ticket_id {
+                    Some(id) => {
+                        debug!("Found available ticket: {}", id);
+                        id
+                    }
+                    None => {
+                        // Every created ticket is taken, issue one of the competition's remaining
+                        // tickets. Taking it off the count first keeps concurrent reservations
+                        // from issuing more than total_allowed_entries.
+                        let issued = sqlx::query(
+                            "UPDATE competitions
+                             SET unissued_tickets = unissued_tickets - 1
+                             WHERE id = ?
+                               AND unissued_tickets > 0",
+                        )
+                        .bind(&competition_id_str)
+                        .execute(&mut *tx)
+                        .await?
+                        .rows_affected();
+                        if issued == 0 {
+                            debug!("No available tickets found");
+                            tx.rollback().await?;
+                            return Err(sqlx::Error::RowNotFound);
+                        }
+
+                        let ticket = Ticket::generate(competition_id, OffsetDateTime::now_utc());
+                        sqlx::query(
+                            "INSERT INTO tickets (
+                                id,
+                                event_id,
+                                encrypted_preimage,
+                                hash
+                            ) VALUES (?, ?, ?, ?)",
+                        )
+                        .bind(ticket.id.to_string())
+                        .bind(&competition_id_str)
+                        .bind(&ticket.encrypted_preimage)
+                        .bind(&ticket.hash)
+                        .execute(&mut *tx)
+                        .await?;
+                        debug!("Issued ticket {}", ticket.id);
+                        ticket.id.to_string()
+                    }
+                };
+
+                // Update the ticket to reserve it
+                let rows_affected = sqlx::query(
+                    r#"UPDATE tickets
+                       SET reserved_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now'),
+                           reserved_by = ?,
+                           ephemeral_pubkey = ?
+                       WHERE id = ?
+                         AND event_id = ?"#,
+                )
+                .bind(&pubkey_owned)
+                .bind(&ephemeral_pubkey)
+                .bind(&ticket_id)
+                .bind(&competition_id_str)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+                if rows_affected == 0 {
+                    debug!("Failed to reserve ticket {}", ticket_id);
+                    tx.rollback().await?;
+                    return Err(sqlx::Error::RowNotFound);
+                }
+
+                // Get the updated ticket
+                let ticket = sqlx::query_as::<_, Ticket>(
+                    r#"SELECT tickets.id as id,
+                              tickets.event_id as competition_id,
+                              entries.id as entry_id,
+                              tickets.ephemeral_pubkey as ephemeral_pubkey,
+                              encrypted_preimage,
+                              hash,
+                              payment_request,
+                              invoice_expires_at,
+                              strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
                               reserved_by,
                               reserved_at,
                               paid_at,
                               settled_at,
-                              escrow_transaction
+                              escrow_transaction,
+                              ln_backend_id,
+                              comped_by,
+                              comped_at,
+                              comp_note,
+                              payment_receipt,
+                              settlement_error,
+                              settlement_failed_at,
+                              escrow_broadcasted_at
                        FROM tickets
                        LEFT JOIN entries ON tickets.id = entries.ticket_id
-                       WHERE tickets.event_id = ?
-                         AND tickets.reserved_by = ?
-                         AND entries.id IS NULL
-                       LIMIT 1"#,
+                       WHERE tickets.id = ?"#,
                 )
-                .bind(&competition_id_str)
-                .bind(&pubkey_owned)
-                .fetch_optional(&mut *tx)
+                .bind(&ticket_id)
+                .fetch_one(&mut *tx)
                 .await?;
+                tx.commit().await?;
 
-                if let Some(ticket) = existing_ticket {
-                    debug!("Found existing reserved ticket {} for user", ticket.id);
-                    tx.commit().await?;
-                    return Ok(ticket);
-                }
+                debug!("Successfully reserved ticket {}", ticket_id);
 
-                // No existing ticket, find an available one
-                let ticket_id: Option<String> = sqlx::query_scalar(
-                    r#"SELECT tickets.id
-                       FROM tickets
-                       LEFT JOIN entries ON tickets.id = entries.ticket_id
-                       WHERE tickets.event_id = ?
-                         AND entries.id IS NULL
-                         AND (
-                             reserved_at IS NULL
-                             OR (
-                                 reserved_at < datetime('now', '-10 minutes')
-                                 AND paid_at IS NULL
-                             )
-                         )
-                       ORDER BY
-                           reserved_at IS NULL DESC,
-                           reserved_at,
-                           tickets.id
-                       LIMIT 1"#,
+                Ok(ticket)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    pub async fn get_pending_tickets(&self) -> Result<Vec<Ticket>, sqlx::Error> {
+        let tickets = sqlx::query_as::<_, Ticket>(
+            r#"SELECT tickets.id as id,
+                      tickets.event_id as competition_id,
+                      entries.id as entry_id,
+                      tickets.ephemeral_pubkey as ephemeral_pubkey,
+                      encrypted_preimage,
+                      hash,
+                      payment_request,
+                      invoice_expires_at,
+                      strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
+                      reserved_by,
+                      reserved_at,
+                      paid_at,
+                      settled_at,
+                      escrow_transaction,
+                      ln_backend_id,
+                      comped_by,
+                      comped_at,
+                      comp_note,
+                      payment_receipt,
+                      settlement_error,
+                      settlement_failed_at,
+                      escrow_broadcasted_at
+               FROM tickets
+               LEFT JOIN entries ON tickets.id = entries.ticket_id
+               WHERE reserved_at IS NOT NULL
+                 AND paid_at IS NULL
+                 AND entry_id IS NULL
+                 AND reserved_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-10 minutes')"#,
+        )
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        Ok(tickets)
+    }
+
+    pub async fn get_paid_tickets(&self) -> Result<Vec<Ticket>, sqlx::Error> {
+        let tickets = sqlx::query_as::<_, Ticket>(
+            r#"SELECT tickets.id as id,
+                      tickets.event_id as competition_id,
+                      entries.id as entry_id,
+                      tickets.ephemeral_pubkey as ephemeral_pubkey,
+                      encrypted_preimage,
+                      hash,
+                      payment_request,
+                      invoice_expires_at,
+                      strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
+                      reserved_by,
+                      reserved_at,
+                      paid_at,
+                      settled_at,
+                      escrow_transaction,
+                      ln_backend_id,
+                      comped_by,
+                      comped_at,
+                      comp_note,
+                      payment_receipt,
+                      settlement_error,
+                      settlement_failed_at,
+                      escrow_broadcasted_at
+               FROM tickets
+               LEFT JOIN entries ON tickets.id = entries.ticket_id
+               WHERE paid_at IS NOT NULL
+                 AND settled_at IS NOT NULL
+                 AND reserved_at IS NOT NULL"#,
+        )
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        Ok(tickets)
+    }
+
+    pub async fn get_paid_tickets_for_competition(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<Vec<Ticket>, sqlx::Error> {
+        let tickets = sqlx::query_as::<_, Ticket>(
+            r#"SELECT tickets.id as id,
+                      tickets.event_id as competition_id,
+                      entries.id as entry_id,
+                      tickets.ephemeral_pubkey as ephemeral_pubkey,
+                      encrypted_preimage,
+                      hash,
+                      payment_request,
+                      invoice_expires_at,
+                      strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
+                      reserved_by,
+                      reserved_at,
+                      paid_at,
+                      settled_at,
+                      escrow_transaction,
+                      ln_backend_id,
+                      comped_by,
+                      comped_at,
+                      comp_note,
+                      payment_receipt,
+                      settlement_error,
+                      settlement_failed_at,
+                      escrow_broadcasted_at
+               FROM tickets
+               LEFT JOIN entries ON tickets.id = entries.ticket_id
+               WHERE paid_at IS NOT NULL
+                 AND settled_at IS NOT NULL
+                 AND reserved_at IS NOT NULL
+                 AND tickets.event_id = ?"#,
+        )
+        .bind(competition_id.to_string())
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        Ok(tickets)
+    }
+
+    pub async fn get_ticket(&self, ticket_id: Uuid) -> Result<Ticket, sqlx::Error> {
+        let ticket = sqlx::query_as::<_, Ticket>(
+            r#"SELECT tickets.id as id,
+                      tickets.event_id as competition_id,
+                      entries.id as entry_id,
+                      tickets.ephemeral_pubkey as ephemeral_pubkey,
+                      encrypted_preimage,
+                      hash,
+                      payment_request,
+                      invoice_expires_at,
+                      strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
+                      reserved_by,
+                      reserved_at,
+                      paid_at,
+                      settled_at,
+                      escrow_transaction,
+                      ln_backend_id,
+                      comped_by,
+                      comped_at,
+                      comp_note,
+                      payment_receipt,
+                      settlement_error,
+                      settlement_failed_at,
+                      escrow_broadcasted_at
+               FROM tickets
+               LEFT JOIN entries ON tickets.id = entries.ticket_id
+               WHERE tickets.id = ?"#,
+        )
+        .bind(ticket_id.to_string())
+        .fetch_one(self.db_connection.read())
+        .await?;
+
+        Ok(ticket)
+    }
+
+    pub async fn get_ticket_by_hash(&self, hash: &str) -> Result<Option<Ticket>, sqlx::Error> {
+        let ticket = sqlx::query_as::<_, Ticket>(
+            r#"SELECT tickets.id as id,
+                      tickets.event_id as competition_id,
+                      entries.id as entry_id,
+                      tickets.ephemeral_pubkey as ephemeral_pubkey,
+                      encrypted_preimage,
+                      hash,
+                      payment_request,
+                      invoice_expires_at,
+                      strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
+                      reserved_by,
+                      reserved_at,
+                      paid_at,
+                      settled_at,
+                      escrow_transaction,
+                      ln_backend_id,
+                      comped_by,
+                      comped_at,
+                      comp_note,
+                      payment_receipt,
+                      settlement_error,
+                      settlement_failed_at,
+                      escrow_broadcasted_at
+               FROM tickets
+               LEFT JOIN entries ON tickets.id = entries.ticket_id
+               WHERE tickets.hash = ?
+               AND tickets.paid_at IS NULL"#,
+        )
+        .bind(hash)
+        .fetch_optional(self.db_connection.read())
+        .await?;
+
+        Ok(ticket)
+    }
+
+    pub async fn get_tickets(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<HashMap<Uuid, Ticket>, sqlx::Error> {
+        let tickets = sqlx::query_as::<_, Ticket>(
+            r#"SELECT
+                t.id,
+                t.event_id as competition_id,
+                e.id as entry_id,
+                t.ephemeral_pubkey,
+                t.encrypted_preimage,
+                t.hash,
+                t.payment_request,
+                t.invoice_expires_at,
+                strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
+                t.reserved_by,
+                t.reserved_at,
+                t.paid_at,
+                t.settled_at,
+                t.escrow_transaction,
+                t.ln_backend_id,
+                t.comped_by,
+                t.comped_at,
+                t.comp_note,
+                t.payment_receipt,
+                t.settlement_error,
+                t.settlement_failed_at,
+                t.escrow_broadcasted_at
+               FROM tickets t
+               LEFT JOIN entries e ON e.ticket_id = t.id AND e.cancelled_at IS NULL
+               WHERE t.event_id = ?"#,
+        )
+        .bind(competition_id.to_string())
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        let mut ticket_map = HashMap::new();
+        for ticket in tickets {
+            if let Some(entry_id) = ticket.entry_id {
+                ticket_map.insert(entry_id, ticket);
+            }
+        }
+
+        Ok(ticket_map)
+    }
+
+    pub async fn mark_ticket_paid(
+        &self,
+        ticket_hash: &str,
+        competition_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let interval = format!("-{} minutes", 10);
+        let ticket_hash_owned = ticket_hash.to_string();
+        let competition_id_str = competition_id.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE tickets
+                    SET paid_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+                    WHERE hash = ?
+                    AND event_id = ?
+                    AND paid_at IS NULL
+                    AND settled_at IS NULL
+                    AND reserved_at IS NOT NULL
+                    AND reserved_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?)",
                 )
-                .bind(&competition_id_str)
-                .fetch_optional(&mut *tx)
+                .bind(ticket_hash_owned)
+                .bind(competition_id_str)
+                .bind(interval)
+                .execute(&pool)
                 .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
 
-                let ticket_id = match ticket_id {
-                    Some(id) => {
-                        debug!("Found available ticket: {}", id);
-                        id
-                    }
-                    None => {
-                        debug!("No available tickets found");
-                        tx.rollback().await?;
-                        return Err(sqlx::Error::RowNotFound);
-                    }
-                };
+    /// Mark a paid ticket's hold invoice as settled, recording the `amount_sats` it brought in
+    /// on the ledger in the same transaction
+    pub async fn mark_ticket_settled(
+        &self,
+        ticket_id: Uuid,
+        amount_sats: u64,
+    ) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+        let ledger_id = Uuid::now_v7().to_string();
 
-                // Update the ticket to reserve it
-                let rows_affected = sqlx::query(
-                    r#"UPDATE tickets
-                       SET reserved_at = datetime('now'),
-                           reserved_by = ?
-                       WHERE id = ?
-                         AND event_id = ?"#,
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
+                let result = sqlx::query(
+                    "UPDATE tickets SET settled_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now'),
+                        settlement_error = NULL,
+                        settlement_failed_at = NULL
+                    WHERE id = ?
+                    AND settled_at IS NULL
+                    AND paid_at IS NOT NULL
+                    AND reserved_at IS NOT NULL",
                 )
-                .bind(&pubkey_owned)
-                .bind(&ticket_id)
-                .bind(&competition_id_str)
+                .bind(&ticket_id_str)
                 .execute(&mut *tx)
-                .await?
-                .rows_affected();
-
-                if rows_affected == 0 {
-                    debug!("Failed to reserve ticket {}", ticket_id);
-                    tx.rollback().await?;
-                    return Err(sqlx::Error::RowNotFound);
+                .await?;
+                if result.rows_affected() == 0 {
+                    return Ok(false);
                 }
 
-                // Get the updated ticket
-                let ticket = sqlx::query_as::<_, Ticket>(
-                    r#"SELECT tickets.id as id,
-                              tickets.event_id as competition_id,
-                              entries.id as entry_id,
-                              tickets.ephemeral_pubkey as ephemeral_pubkey,
-                              encrypted_preimage,
-                              hash,
-                              payment_request,
-                              invoice_expires_at,
-                              datetime('now', '+10 minutes') as expiry,
-                              reserved_by,
-                              reserved_at,
-                              paid_at,
-                              settled_at,
-                              escrow_transaction
-                       FROM tickets
-                       LEFT JOIN entries ON tickets.id = entries.ticket_id
-                       WHERE tickets.id = ?"#,
+                sqlx::query(
+                    "INSERT OR IGNORE INTO ledger_entries
+                        (id, competition_id, entry_id, kind, amount_sats, reference, recorded_at)
+                    SELECT ?, tickets.event_id, entries.id, ?, ?, tickets.id, tickets.settled_at
+                    FROM tickets
+                    LEFT JOIN entries ON entries.ticket_id = tickets.id
+                    WHERE tickets.id = ?",
                 )
-                .bind(&ticket_id)
-                .fetch_one(&mut *tx)
+                .bind(&ledger_id)
+                .bind(LedgerEntryKind::InvoiceSettled.as_str())
+                .bind(amount_sats as i64)
+                .bind(&ticket_id_str)
+                .execute(&mut *tx)
                 .await?;
+
                 tx.commit().await?;
+                Ok(true)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
 
-                debug!("Successfully reserved ticket {}", ticket_id);
+    /// Record why settling a paid ticket's hold invoice failed, the next settle attempt retries it
+    pub async fn record_ticket_settlement_failure(
+        &self,
+        ticket_id: Uuid,
+        error: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+        let error = error.to_string();
 
-                Ok(ticket)
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE tickets
+                    SET settlement_error = ?,
+                        settlement_failed_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+                    WHERE id = ?
+                    AND settled_at IS NULL",
+                )
+                .bind(error)
+                .bind(ticket_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
             })
             .await
             .map_err(|e| match e {
@@ -1470,98 +2852,150 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn get_pending_tickets(&self) -> Result<Vec<Ticket>, sqlx::Error> {
-        let tickets = sqlx::query_as::<_, Ticket>(
-            r#"SELECT tickets.id as id,
-                      tickets.event_id as competition_id,
-                      entries.id as entry_id,
-                      tickets.ephemeral_pubkey as ephemeral_pubkey,
-                      encrypted_preimage,
-                      hash,
-                      payment_request,
-                      invoice_expires_at,
-                      datetime('now', '+10 minutes') as expiry,
-                      reserved_by,
-                      reserved_at,
-                      paid_at,
-                      settled_at,
-                      escrow_transaction
-               FROM tickets
-               LEFT JOIN entries ON tickets.id = entries.ticket_id
-               WHERE reserved_at IS NOT NULL
-                 AND paid_at IS NULL
-                 AND entry_id IS NULL
-                 AND reserved_at > datetime('now', '-10 minutes')"#,
-        )
-        .fetch_all(self.db_connection.read())
-        .await?;
+    /// Mark a reserved, unpaid ticket as paid and settled on the coordinator's behalf, recording
+    /// which admin comped it. Returns false when the ticket was paid or released meanwhile.
+    pub async fn comp_ticket(
+        &self,
+        ticket_id: Uuid,
+        comped_by: &str,
+        note: Option<String>,
+    ) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+        let comped_by = comped_by.to_string();
 
-        Ok(tickets)
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE tickets
+                    SET paid_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now'),
+                        settled_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now'),
+                        comped_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now'),
+                        comped_by = ?,
+                        comp_note = ?
+                    WHERE id = ?
+                    AND paid_at IS NULL
+                    AND settled_at IS NULL
+                    AND reserved_at IS NOT NULL
+                    AND reserved_by IS NOT NULL",
+                )
+                .bind(comped_by)
+                .bind(note)
+                .bind(ticket_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
     }
 
-    pub async fn get_paid_tickets(&self) -> Result<Vec<Ticket>, sqlx::Error> {
-        let tickets = sqlx::query_as::<_, Ticket>(
-            r#"SELECT tickets.id as id,
-                      tickets.event_id as competition_id,
-                      entries.id as entry_id,
-                      tickets.ephemeral_pubkey as ephemeral_pubkey,
-                      encrypted_preimage,
-                      hash,
-                      payment_request,
-                      invoice_expires_at,
-                      datetime('now', '+10 minutes') as expiry,
-                      reserved_by,
-                      reserved_at,
-                      paid_at,
-                      settled_at,
-                      escrow_transaction
-               FROM tickets
-               LEFT JOIN entries ON tickets.id = entries.ticket_id
-               WHERE paid_at IS NOT NULL
-                 AND settled_at IS NOT NULL
-                 AND reserved_at IS NOT NULL"#,
-        )
-        .fetch_all(self.db_connection.read())
-        .await?;
+    /// Test-only: Mark a ticket as both paid and settled, bypassing Lightning.
+    /// Used by the synthetic testing tool to simulate invoice payment.
+    pub async fn test_settle_ticket(&self, ticket_id: Uuid) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
 
-        Ok(tickets)
+        let result = self
+            .db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE tickets
+                    SET paid_at = COALESCE(paid_at, strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+                        settled_at = COALESCE(settled_at, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+                    WHERE id = ?
+                    AND reserved_at IS NOT NULL",
+                )
+                .bind(ticket_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })?;
+
+        // Force WAL checkpoint so read pool connections can see the update.
+        // The read pool uses shared_cache which can pin WAL snapshots.
+        if result {
+            self.db_connection.passive_checkpoint().await;
+        }
+
+        Ok(result)
     }
 
-    pub async fn get_paid_tickets_for_competition(
+    pub async fn update_ticket_payment_request(
         &self,
-        competition_id: Uuid,
-    ) -> Result<Vec<Ticket>, sqlx::Error> {
-        let tickets = sqlx::query_as::<_, Ticket>(
-            r#"SELECT tickets.id as id,
-                      tickets.event_id as competition_id,
-                      entries.id as entry_id,
-                      tickets.ephemeral_pubkey as ephemeral_pubkey,
-                      encrypted_preimage,
-                      hash,
-                      payment_request,
-                      invoice_expires_at,
-                      datetime('now', '+10 minutes') as expiry,
-                      reserved_by,
-                      reserved_at,
-                      paid_at,
-                      settled_at,
-                      escrow_transaction
-               FROM tickets
-               LEFT JOIN entries ON tickets.id = entries.ticket_id
-               WHERE paid_at IS NOT NULL
-                 AND settled_at IS NOT NULL
-                 AND reserved_at IS NOT NULL
-                 AND tickets.event_id = ?"#,
-        )
-        .bind(competition_id.to_string())
-        .fetch_all(self.db_connection.read())
-        .await?;
+        ticket_id: Uuid,
+        payment_request: &str,
+        invoice_expires_at: time::OffsetDateTime,
+        ln_backend_id: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+        let payment_request_owned = payment_request.to_string();
+        let ln_backend_id_owned = ln_backend_id.map(str::to_string);
+        let expires_at_str =
+            format_timestamp(invoice_expires_at).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
-        Ok(tickets)
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE tickets
+                    SET payment_request = ?, invoice_expires_at = ?, ln_backend_id = ?
+                    WHERE id = ?",
+                )
+                .bind(payment_request_owned)
+                .bind(expires_at_str)
+                .bind(ln_backend_id_owned)
+                .bind(ticket_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
     }
 
-    pub async fn get_ticket(&self, ticket_id: Uuid) -> Result<Ticket, sqlx::Error> {
-        let ticket = sqlx::query_as::<_, Ticket>(
+    pub async fn clear_ticket_reservation(&self, ticket_id: Uuid) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE tickets
+                    SET reserved_at = NULL,
+                        reserved_by = NULL,
+                        paid_at = NULL,
+                        escrow_transaction = NULL,
+                        ephemeral_pubkey = NULL,
+                        payment_request = NULL,
+                        invoice_expires_at = NULL,
+                        ln_backend_id = NULL
+                    WHERE id = ?
+                    AND settled_at IS NULL",
+                )
+                .bind(ticket_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    /// Tickets whose reservation ran past the 10 minute timeout without a payment or an entry,
+    /// they read as expired but stay reserved until something releases them
+    pub async fn get_expired_reservations(&self) -> Result<Vec<Ticket>, sqlx::Error> {
+        let tickets = sqlx::query_as::<_, Ticket>(
             r#"SELECT tickets.id as id,
                       tickets.event_id as competition_id,
                       entries.id as entry_id,
@@ -1570,113 +3004,242 @@ impl CompetitionStore {
                       hash,
                       payment_request,
                       invoice_expires_at,
-                      datetime('now', '+10 minutes') as expiry,
+                      strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+10 minutes') as expiry,
                       reserved_by,
                       reserved_at,
                       paid_at,
                       settled_at,
-                      escrow_transaction
+                      escrow_transaction,
+                      ln_backend_id,
+                      comped_by,
+                      comped_at,
+                      comp_note,
+                      payment_receipt,
+                      settlement_error,
+                      settlement_failed_at,
+                      escrow_broadcasted_at
                FROM tickets
                LEFT JOIN entries ON tickets.id = entries.ticket_id
-               WHERE tickets.id = ?"#,
+               WHERE reserved_at IS NOT NULL
+                 AND paid_at IS NULL
+                 AND settled_at IS NULL
+                 AND entries.id IS NULL
+                 AND reserved_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-10 minutes')"#,
         )
-        .bind(ticket_id.to_string())
-        .fetch_one(self.db_connection.read())
+        .fetch_all(self.db_connection.read())
         .await?;
 
-        Ok(ticket)
+        Ok(tickets)
     }
 
-    pub async fn get_ticket_by_hash(&self, hash: &str) -> Result<Option<Ticket>, sqlx::Error> {
-        let ticket = sqlx::query_as::<_, Ticket>(
-            r#"SELECT tickets.id as id,
-                      tickets.event_id as competition_id,
-                      entries.id as entry_id,
-                      tickets.ephemeral_pubkey as ephemeral_pubkey,
-                      encrypted_preimage,
-                      hash,
-                      payment_request,
-                      invoice_expires_at,
-                      datetime('now', '+10 minutes') as expiry,
-                      reserved_by,
-                      reserved_at,
-                      paid_at,
-                      settled_at,
-                      escrow_transaction
-               FROM tickets
-               LEFT JOIN entries ON tickets.id = entries.ticket_id
-               WHERE tickets.hash = ?
-               AND tickets.paid_at IS NULL"#,
-        )
-        .bind(hash)
-        .fetch_optional(self.db_connection.read())
-        .await?;
+    /// Return an expired reservation to `Created` with a fresh preimage and hash, handing back
+    /// the hash and invoice it held so the caller can cancel exactly that invoice. Returns `None`
+    /// if the ticket was paid or reserved again since it was read.
+    pub async fn release_expired_reservation(
+        &self,
+        ticket_id: Uuid,
+        new_encrypted_preimage: &str,
+        new_hash: &str,
+    ) -> Result<Option<ReleasedReservation>, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+        let new_encrypted_preimage_owned = new_encrypted_preimage.to_string();
+        let new_hash_owned = new_hash.to_string();
 
-        Ok(ticket)
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
+
+                let Some(row) = sqlx::query(
+                    "SELECT hash, payment_request, ln_backend_id
+                    FROM tickets
+                    WHERE id = ?
+                    AND reserved_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-10 minutes')
+                    AND paid_at IS NULL
+                    AND settled_at IS NULL
+                    AND NOT EXISTS (SELECT 1 FROM entries WHERE entries.ticket_id = tickets.id)",
+                )
+                .bind(&ticket_id_str)
+                .fetch_optional(&mut *tx)
+                .await?
+                else {
+                    tx.rollback().await?;
+                    return Ok(None);
+                };
+                let released = ReleasedReservation {
+                    hash: row.try_get("hash")?,
+                    payment_request: row.try_get("payment_request")?,
+                    ln_backend_id: row.try_get("ln_backend_id")?,
+                };
+
+                // Writes are serialized through the db writer, so nothing reserved or paid the
+                // ticket since the read above
+                sqlx::query(
+                    "UPDATE tickets
+                    SET encrypted_preimage = ?,
+                        hash = ?,
+                        payment_request = NULL,
+                        invoice_expires_at = NULL,
+                        ln_backend_id = NULL,
+                        escrow_transaction = NULL,
+                        ephemeral_pubkey = NULL,
+                        reserved_by = NULL,
+                        reserved_at = NULL
+                    WHERE id = ?",
+                )
+                .bind(new_encrypted_preimage_owned)
+                .bind(new_hash_owned)
+                .bind(&ticket_id_str)
+                .execute(&mut *tx)
+                .await?;
+                tx.commit().await?;
+                Ok(Some(released))
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
     }
 
-    pub async fn get_tickets(
+    /// Save the entries the oracle confirmed receiving as soon as a chunk lands, so a crash
+    /// before the competition is saved doesn't send them again
+    pub async fn update_oracle_submitted_entry_ids(
         &self,
         competition_id: Uuid,
-    ) -> Result<HashMap<Uuid, Ticket>, sqlx::Error> {
-        let tickets = sqlx::query_as::<_, Ticket>(
-            r#"SELECT
-                t.id,
-                t.event_id as competition_id,
-                e.id as entry_id,
-                t.ephemeral_pubkey,
-                t.encrypted_preimage,
-                t.hash,
-                t.payment_request,
-                t.invoice_expires_at,
-                datetime('now', '+10 minutes') as expiry,
-                t.reserved_by,
-                t.reserved_at,
-                t.paid_at,
-                t.settled_at,
-                t.escrow_transaction
-               FROM tickets t
-               LEFT JOIN entries e ON e.ticket_id = t.id
-               WHERE t.event_id = ?"#,
-        )
-        .bind(competition_id.to_string())
-        .fetch_all(self.db_connection.read())
-        .await?;
+        entry_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let competition_id = competition_id.to_string();
+        let entry_ids =
+            serde_json::to_string(entry_ids).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
-        let mut ticket_map = HashMap::new();
-        for ticket in tickets {
-            if let Some(entry_id) = ticket.entry_id {
-                ticket_map.insert(entry_id, ticket);
-            }
-        }
+        self.db_connection
+            .execute_write(move |pool| async move {
+                sqlx::query("UPDATE competitions SET oracle_submitted_entry_ids = ? WHERE id = ?")
+                    .bind(entry_ids)
+                    .bind(competition_id)
+                    .execute(&pool)
+                    .await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
 
-        Ok(ticket_map)
+    pub async fn update_ticket_escrow_transaction(
+        &self,
+        ticket_id: uuid::Uuid,
+        escrow_transaction: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+        let escrow_transaction_owned = escrow_transaction.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query("UPDATE tickets SET escrow_transaction = ? WHERE id = ?")
+                    .bind(escrow_transaction_owned)
+                    .bind(ticket_id_str)
+                    .execute(&pool)
+                    .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
     }
 
-    pub async fn mark_ticket_paid(
+    /// Record the coordinator's first broadcast of a ticket's escrow transaction, later
+    /// rebroadcasts keep the original time
+    pub async fn mark_ticket_escrow_broadcasted(
+        &self,
+        ticket_id: uuid::Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE tickets
+                    SET escrow_broadcasted_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+                    WHERE id = ?
+                    AND escrow_broadcasted_at IS NULL",
+                )
+                .bind(ticket_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    pub async fn reset_ticket_after_failed_escrow(
+        &self,
+        ticket_id: uuid::Uuid,
+        new_encrypted_preimage: &str,
+        new_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let ticket_id_str = ticket_id.to_string();
+        let new_encrypted_preimage_owned = new_encrypted_preimage.to_string();
+        let new_hash_owned = new_hash.to_string();
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE tickets
+                    SET
+                        encrypted_preimage = ?,
+                        hash = ?,
+                        payment_request = NULL,
+                        ln_backend_id = NULL,
+                        paid_at = NULL,
+                        settled_at = NULL,
+                        escrow_transaction = NULL,
+                        ephemeral_pubkey = NULL,
+                        reserved_by = NULL,
+                        reserved_at = NULL
+                        WHERE id = ?",
+                )
+                .bind(new_encrypted_preimage_owned)
+                .bind(new_hash_owned)
+                .bind(ticket_id_str)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected() > 0)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    /// Store a Keymeld session for a competition
+    pub async fn store_keymeld_session(
         &self,
-        ticket_hash: &str,
         competition_id: Uuid,
+        session: &crate::infra::keymeld::StoredDlcKeygenSession,
     ) -> Result<bool, sqlx::Error> {
-        let interval = format!("-{} minutes", 10);
-        let ticket_hash_owned = ticket_hash.to_string();
+        let session_json =
+            serde_json::to_vec(session).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
         let competition_id_str = competition_id.to_string();
 
         self.db_connection
             .execute_write(move |pool| async move {
                 let result = sqlx::query(
-                    "UPDATE tickets
-                    SET paid_at = datetime('now')
-                    WHERE hash = ?
-                    AND event_id = ?
-                    AND paid_at IS NULL
-                    AND settled_at IS NULL
-                    AND reserved_at IS NOT NULL
-                    AND reserved_at > datetime('now', ?)",
+                    "UPDATE competitions
+                    SET keymeld_session = ?
+                    WHERE id = ?",
                 )
-                .bind(ticket_hash_owned)
+                .bind(session_json)
                 .bind(competition_id_str)
-                .bind(interval)
                 .execute(&pool)
                 .await?;
                 Ok(result.rows_affected() > 0)
@@ -1688,18 +3251,67 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn mark_ticket_settled(&self, ticket_id: Uuid) -> Result<bool, sqlx::Error> {
-        let ticket_id_str = ticket_id.to_string();
+    /// Retrieve a Keymeld session for a competition
+    pub async fn get_keymeld_session(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<Option<crate::infra::keymeld::StoredDlcKeygenSession>, sqlx::Error> {
+        let session_bytes: Option<Option<Vec<u8>>> =
+            sqlx::query_scalar("SELECT keymeld_session FROM competitions WHERE id = ?")
+                .bind(competition_id.to_string())
+                .fetch_optional(self.db_connection.read())
+                .await?;
+
+        match session_bytes {
+            Some(Some(bytes)) => {
+                let session =
+                    serde_json::from_slice(&bytes).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                Ok(Some(session))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Every stored Keymeld session, by competition
+    pub async fn get_keymeld_sessions(
+        &self,
+    ) -> Result<Vec<(Uuid, crate::infra::keymeld::StoredDlcKeygenSession)>, sqlx::Error> {
+        let rows: Vec<(String, Vec<u8>)> = sqlx::query_as(
+            "SELECT id, keymeld_session FROM competitions WHERE keymeld_session IS NOT NULL",
+        )
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        rows.into_iter()
+            .map(|(id, bytes)| {
+                let competition_id =
+                    Uuid::parse_str(&id).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                let session =
+                    serde_json::from_slice(&bytes).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                Ok((competition_id, session))
+            })
+            .collect()
+    }
+
+    /// Store the ticket order a competition's keymeld subset definitions were built with
+    pub async fn set_player_order(
+        &self,
+        competition_id: Uuid,
+        player_order: &[PlayerSlot],
+    ) -> Result<bool, sqlx::Error> {
+        let player_order =
+            serde_json::to_vec(player_order).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        let competition_id_str = competition_id.to_string();
 
         self.db_connection
             .execute_write(move |pool| async move {
                 let result = sqlx::query(
-                    "UPDATE tickets SET settled_at = datetime('now') WHERE id = ?
-                    AND settled_at IS NULL
-                    AND paid_at IS NOT NULL
-                    AND reserved_at IS NOT NULL",
+                    "UPDATE competitions
+                    SET player_order = ?
+                    WHERE id = ?",
                 )
-                .bind(ticket_id_str)
+                .bind(player_order)
+                .bind(competition_id_str)
                 .execute(&pool)
                 .await?;
                 Ok(result.rows_affected() > 0)
@@ -1711,22 +3323,39 @@ impl CompetitionStore {
             })
     }
 
-    /// Test-only: Mark a ticket as both paid and settled, bypassing Lightning.
-    /// Used by the synthetic testing tool to simulate invoice payment.
-    pub async fn test_settle_ticket(&self, ticket_id: Uuid) -> Result<bool, sqlx::Error> {
-        let ticket_id_str = ticket_id.to_string();
+    /// The ticket order stored with `set_player_order`, `None` when none was recorded
+    pub async fn get_player_order(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<Option<Vec<PlayerSlot>>, sqlx::Error> {
+        let player_order: Option<Option<Vec<u8>>> =
+            sqlx::query_scalar("SELECT player_order FROM competitions WHERE id = ?")
+                .bind(competition_id.to_string())
+                .fetch_optional(self.db_connection.read())
+                .await?;
 
-        let result = self
-            .db_connection
+        match player_order {
+            Some(Some(bytes)) => {
+                let player_order =
+                    serde_json::from_slice(&bytes).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                Ok(Some(player_order))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Clear a Keymeld session for a competition (e.g., on failure or completion)
+    pub async fn clear_keymeld_session(&self, competition_id: Uuid) -> Result<bool, sqlx::Error> {
+        let competition_id_str = competition_id.to_string();
+
+        self.db_connection
             .execute_write(move |pool| async move {
                 let result = sqlx::query(
-                    "UPDATE tickets
-                    SET paid_at = COALESCE(paid_at, datetime('now')),
-                        settled_at = COALESCE(settled_at, datetime('now'))
-                    WHERE id = ?
-                    AND reserved_at IS NOT NULL",
+                    "UPDATE competitions
+                    SET keymeld_session = NULL
+                    WHERE id = ?",
                 )
-                .bind(ticket_id_str)
+                .bind(competition_id_str)
                 .execute(&pool)
                 .await?;
                 Ok(result.rows_affected() > 0)
@@ -1735,38 +3364,87 @@ impl CompetitionStore {
             .map_err(|e| match e {
                 crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
                 e => sqlx::Error::Protocol(e.to_string()),
-            })?;
+            })
+    }
 
-        // Force WAL checkpoint so read pool connections can see the update.
-        // The read pool uses shared_cache which can pin WAL snapshots.
-        if result {
-            self.db_connection.passive_checkpoint().await;
-        }
+    /// Get a single entry by its ID
+    pub async fn get_entry_by_id(&self, entry_id: Uuid) -> Result<Option<UserEntry>, sqlx::Error> {
+        let query = "WITH latest_payouts AS (
+              SELECT
+                  entry_id,
+                  payout_payment_request,
+                  ROW_NUMBER() OVER (
+                      PARTITION BY entry_id
+                      ORDER BY COALESCE(succeed_at, initiated_at) DESC
+                  ) as rn,
+                  COALESCE(succeed_at, initiated_at) as latest_payout_time
+              FROM payouts
+              WHERE failed_at IS NULL
+          )
+          SELECT
+              entries.id as id,
+              ticket_id,
+              entries.event_id as event_id,
+              pubkey,
+              entries.ephemeral_pubkey as ephemeral_pubkey,
+              ephemeral_privatekey_encrypted,
+              ephemeral_privatekey,
+              encrypted_keymeld_private_key,
+              keymeld_auth_pubkey,
+              public_nonces,
+              partial_signatures,
+              funding_psbt_base64,
+              entry_submission,
+              payout_preimage_encrypted,
+              payout_hash,
+              payout_preimage,
+              signed_at,
+              tickets.paid_at AS paid_at,
+              sellback_broadcasted_at,
+              reclaimed_broadcasted_at,
+              latest_payouts.latest_payout_time as paid_out_at,
+              latest_payouts.payout_payment_request as payout_ln_invoice,
+              player_index,
+              backup_dm_sent_at,
+              backup_dm_error,
+              entry_receipt,
+              entries.cancelled_at as cancelled_at,
+              cancelled_reason
+          FROM entries
+          LEFT JOIN tickets ON entries.ticket_id = tickets.id
+          LEFT JOIN latest_payouts ON entries.id = latest_payouts.entry_id AND latest_payouts.rn = 1
+          WHERE entries.id = ?";
 
-        Ok(result)
+        sqlx::query_as::<_, UserEntry>(query)
+            .bind(entry_id.to_string())
+            .fetch_optional(self.db_connection.read())
+            .await
     }
 
-    pub async fn update_ticket_escrow(
+    /// Invite `pubkeys` (hex) to a private competition, already invited ones are skipped
+    pub async fn add_invitations(
         &self,
-        ticket_id: Uuid,
-        ephemeral_pubkey: String,
-        escrow_tx: String,
-    ) -> Result<bool, sqlx::Error> {
-        let ticket_id_str = ticket_id.to_string();
-
+        competition_id: Uuid,
+        pubkeys: Vec<String>,
+    ) -> Result<u64, sqlx::Error> {
+        let competition_id = competition_id.to_string();
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE tickets
-                    SET escrow_transaction = ?, ephemeral_pubkey = ?
-                    WHERE id = ?",
-                )
-                .bind(escrow_tx)
-                .bind(ephemeral_pubkey)
-                .bind(ticket_id_str)
-                .execute(&pool)
-                .await?;
-                Ok(result.rows_affected() > 0)
+                let mut tx = pool.begin().await?;
+                let mut added = 0;
+                for pubkey in pubkeys {
+                    added += sqlx::query(
+                        "INSERT OR IGNORE INTO competition_invitations (competition_id, pubkey)
+                         VALUES (?, ?)",
+                    )
+                    .bind(&competition_id)
+                    .bind(pubkey)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+                }
+                tx.commit().await?;
+                Ok(added)
             })
             .await
             .map_err(|e| match e {
@@ -1775,31 +3453,83 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn update_ticket_payment_request(
+    pub async fn get_invitations(&self, competition_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT pubkey FROM competition_invitations WHERE competition_id = ? ORDER BY created_at, pubkey",
+        )
+        .bind(competition_id.to_string())
+        .fetch_all(self.db_connection.read())
+        .await
+    }
+
+    pub async fn is_invited(
         &self,
-        ticket_id: Uuid,
-        payment_request: &str,
-        invoice_expires_at: time::OffsetDateTime,
+        competition_id: Uuid,
+        pubkey: &str,
     ) -> Result<bool, sqlx::Error> {
-        let ticket_id_str = ticket_id.to_string();
-        let payment_request_owned = payment_request.to_string();
-        // Use SQLite datetime format: YYYY-MM-DD HH:MM:SS
-        let format =
-            time::format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]")
-                .expect("valid format");
-        let expires_at_str = invoice_expires_at.format(&format).unwrap_or_default();
+        let invited: Option<i64> = sqlx::query_scalar(
+            "SELECT 1 FROM competition_invitations WHERE competition_id = ? AND pubkey = ?",
+        )
+        .bind(competition_id.to_string())
+        .bind(pubkey)
+        .fetch_optional(self.db_connection.read())
+        .await?;
+        Ok(invited.is_some())
+    }
+
+    /// Ids of the competitions `pubkey` was invited to
+    pub async fn get_invited_competition_ids(
+        &self,
+        pubkey: &str,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT competition_id FROM competition_invitations WHERE pubkey = ?",
+        )
+        .bind(pubkey)
+        .fetch_all(self.db_connection.read())
+        .await?;
+        ids.into_iter()
+            .map(|id| {
+                Uuid::parse_str(&id).map_err(|e| sqlx::Error::ColumnDecode {
+                    index: "competition_id".to_string(),
+                    source: Box::new(e),
+                })
+            })
+            .collect()
+    }
+
+    /// Append money movements to the ledger, skipping any already recorded
+    pub async fn record_ledger_entries(
+        &self,
+        entries: Vec<LedgerEntry>,
+    ) -> Result<(), sqlx::Error> {
+        if entries.is_empty() {
+            return Ok(());
+        }
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE tickets SET payment_request = ?, invoice_expires_at = ? WHERE id = ?",
-                )
-                .bind(payment_request_owned)
-                .bind(expires_at_str)
-                .bind(ticket_id_str)
-                .execute(&pool)
-                .await?;
-                Ok(result.rows_affected() > 0)
+                let mut tx = pool.begin().await?;
+                for entry in &entries {
+                    let recorded_at = format_timestamp(entry.recorded_at)
+                        .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+                    sqlx::query(
+                        "INSERT OR IGNORE INTO ledger_entries
+                            (id, competition_id, entry_id, kind, amount_sats, reference, recorded_at)
+                        VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    )
+                    .bind(Uuid::now_v7().to_string())
+                    .bind(entry.competition_id.to_string())
+                    .bind(entry.entry_id.map(|id| id.to_string()))
+                    .bind(entry.kind.as_str())
+                    .bind(entry.amount_sats as i64)
+                    .bind(&entry.reference)
+                    .bind(recorded_at)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+                Ok(())
             })
             .await
             .map_err(|e| match e {
@@ -1808,26 +3538,49 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn clear_ticket_reservation(&self, ticket_id: Uuid) -> Result<bool, sqlx::Error> {
-        let ticket_id_str = ticket_id.to_string();
+    /// Set `completed_at` and record the override that forced it in one transaction. Returns
+    /// false when the competition had already completed.
+    pub async fn force_complete_competition(
+        &self,
+        competition_override: CompetitionOverride,
+    ) -> Result<bool, sqlx::Error> {
+        let created_at = format_timestamp(competition_override.created_at)
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
                 let result = sqlx::query(
-                    "UPDATE tickets
-                    SET reserved_at = NULL,
-                        reserved_by = NULL,
-                        paid_at = NULL,
-                        escrow_transaction = NULL,
-                        payment_request = NULL,
-                        invoice_expires_at = NULL
-                    WHERE id = ?
-                    AND settled_at IS NULL",
+                    "UPDATE competitions
+                    SET completed_at = ?, version = version + 1
+                    WHERE id = ? AND completed_at IS NULL",
                 )
-                .bind(ticket_id_str)
-                .execute(&pool)
+                .bind(&created_at)
+                .bind(competition_override.competition_id.to_string())
+                .execute(&mut *tx)
                 .await?;
-                Ok(result.rows_affected() > 0)
+                if result.rows_affected() == 0 {
+                    return Ok(false);
+                }
+
+                sqlx::query(
+                    "INSERT INTO competition_overrides
+                        (id, competition_id, action, from_state, to_state, reason, admin, created_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(competition_override.id.to_string())
+                .bind(competition_override.competition_id.to_string())
+                .bind(&competition_override.action)
+                .bind(&competition_override.from_state)
+                .bind(&competition_override.to_state)
+                .bind(&competition_override.reason)
+                .bind(&competition_override.admin)
+                .bind(&created_at)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                Ok(true)
             })
             .await
             .map_err(|e| match e {
@@ -1836,62 +3589,108 @@ impl CompetitionStore {
             })
     }
 
-    pub async fn update_ticket_escrow_transaction(
+    /// Manual overrides of a competition's state, oldest first
+    pub async fn get_competition_overrides(
         &self,
-        ticket_id: uuid::Uuid,
-        escrow_transaction: &str,
-    ) -> Result<bool, sqlx::Error> {
-        let ticket_id_str = ticket_id.to_string();
-        let escrow_transaction_owned = escrow_transaction.to_string();
+        competition_id: Uuid,
+    ) -> Result<Vec<CompetitionOverride>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, competition_id, action, from_state, to_state, reason, admin, created_at
+            FROM competition_overrides
+            WHERE competition_id = ?
+            ORDER BY created_at ASC",
+        )
+        .bind(competition_id.to_string())
+        .fetch_all(self.db_connection.read())
+        .await?;
 
-        self.db_connection
-            .execute_write(move |pool| async move {
-                let result = sqlx::query("UPDATE tickets SET escrow_transaction = ? WHERE id = ?")
-                    .bind(escrow_transaction_owned)
-                    .bind(ticket_id_str)
-                    .execute(&pool)
-                    .await?;
-                Ok(result.rows_affected() > 0)
-            })
-            .await
-            .map_err(|e| match e {
-                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
-                e => sqlx::Error::Protocol(e.to_string()),
+        rows.iter()
+            .map(|row| {
+                let parse_uuid = |column: &str| {
+                    Uuid::parse_str(&row.try_get::<String, _>(column)?).map_err(|e| {
+                        sqlx::Error::ColumnDecode {
+                            index: column.to_string(),
+                            source: Box::new(e),
+                        }
+                    })
+                };
+                Ok(CompetitionOverride {
+                    id: parse_uuid("id")?,
+                    competition_id: parse_uuid("competition_id")?,
+                    action: row.try_get("action")?,
+                    from_state: row.try_get("from_state")?,
+                    to_state: row.try_get("to_state")?,
+                    reason: row.try_get("reason")?,
+                    admin: row.try_get("admin")?,
+                    created_at: parse_required_datetime(row, "created_at")?,
+                })
             })
+            .collect()
     }
 
-    pub async fn reset_ticket_after_failed_escrow(
-        &self,
-        ticket_id: uuid::Uuid,
-        new_encrypted_preimage: &str,
-        new_hash: &str,
-    ) -> Result<bool, sqlx::Error> {
-        let ticket_id_str = ticket_id.to_string();
-        let new_encrypted_preimage_owned = new_encrypted_preimage.to_string();
-        let new_hash_owned = new_hash.to_string();
+    /// Record a sponsorship and add its sats to the competition's pool in one transaction.
+    /// Returns false when the pool was committed to a contract, or the competition cancelled
+    /// or failed, before the sponsorship landed.
+    pub async fn add_sponsorship(&self, sponsorship: Sponsorship) -> Result<bool, sqlx::Error> {
+        let created_at = format_timestamp(sponsorship.created_at)
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE tickets
-                    SET
-                        encrypted_preimage = ?,
-                        hash = ?,
-                        payment_request = NULL,
-                        paid_at = NULL,
-                        settled_at = NULL,
-                        escrow_transaction = NULL,
-                        ephemeral_pubkey = NULL,
-                        reserved_by = NULL,
-                        reserved_at = NULL
-                        WHERE id = ?",
+                let mut tx = pool.begin().await?;
+                let event_submission: Option<Vec<u8>> = sqlx::query_scalar(
+                    "SELECT event_submission FROM competitions
+                    WHERE id = ?
+                        AND contract_parameters IS NULL
+                        AND funding_psbt_base64 IS NULL
+                        AND cancelled_at IS NULL
+                        AND failed_at IS NULL",
                 )
-                .bind(new_encrypted_preimage_owned)
-                .bind(new_hash_owned)
-                .bind(ticket_id_str)
-                .execute(&pool)
+                .bind(sponsorship.competition_id.to_string())
+                .fetch_optional(&mut *tx)
                 .await?;
-                Ok(result.rows_affected() > 0)
+                let Some(event_submission) = event_submission else {
+                    return Ok(false);
+                };
+                let mut event_submission: CreateEvent =
+                    decode_blob(&event_submission, "event_submission")?;
+                event_submission.total_competition_pool = event_submission
+                    .total_competition_pool
+                    .checked_add(sponsorship.amount_sats)
+                    .ok_or_else(|| {
+                        sqlx::Error::Protocol("sponsored pool overflows u64 sats".to_string())
+                    })?;
+                let event_submission = serde_json::to_string(&event_submission)
+                    .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+                // A grown pool is a new version, a watcher holding the old pool can't save over it
+                sqlx::query(
+                    "UPDATE competitions SET event_submission = ?, version = version + 1
+                    WHERE id = ?",
+                )
+                .bind(&event_submission)
+                .bind(sponsorship.competition_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query(
+                    "INSERT INTO competition_sponsorships
+                        (id, competition_id, amount_sats, funds_kind, funds_reference, sponsor,
+                         recorded_by, created_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(sponsorship.id.to_string())
+                .bind(sponsorship.competition_id.to_string())
+                .bind(sponsorship.amount_sats.to_sat() as i64)
+                .bind(sponsorship.funds.kind())
+                .bind(sponsorship.funds.reference())
+                .bind(&sponsorship.sponsor)
+                .bind(&sponsorship.recorded_by)
+                .bind(&created_at)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                Ok(true)
             })
             .await
             .map_err(|e| match e {
@@ -1900,28 +3699,109 @@ impl CompetitionStore {
             })
     }
 
-    /// Store a Keymeld session for a competition
-    pub async fn store_keymeld_session(
+    /// A competition's sponsorships, oldest first
+    pub async fn get_sponsorships(
         &self,
         competition_id: Uuid,
-        session: &crate::infra::keymeld::StoredDlcKeygenSession,
-    ) -> Result<bool, sqlx::Error> {
-        let session_json =
-            serde_json::to_vec(session).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-        let competition_id_str = competition_id.to_string();
+    ) -> Result<Vec<Sponsorship>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, competition_id, amount_sats, funds_kind, funds_reference, sponsor,
+                recorded_by, created_at
+            FROM competition_sponsorships
+            WHERE competition_id = ?
+            ORDER BY created_at ASC",
+        )
+        .bind(competition_id.to_string())
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let parse_uuid = |column: &str| {
+                    Uuid::parse_str(&row.try_get::<String, _>(column)?).map_err(|e| {
+                        sqlx::Error::ColumnDecode {
+                            index: column.to_string(),
+                            source: Box::new(e),
+                        }
+                    })
+                };
+                let funds_kind: String = row.try_get("funds_kind")?;
+                let funds_reference: String = row.try_get("funds_reference")?;
+                Ok(Sponsorship {
+                    id: parse_uuid("id")?,
+                    competition_id: parse_uuid("competition_id")?,
+                    amount_sats: Sats(row.try_get::<i64, _>("amount_sats")? as u64),
+                    funds: SponsorFunds::from_parts(&funds_kind, &funds_reference).map_err(
+                        |e| sqlx::Error::ColumnDecode {
+                            index: "funds_reference".to_string(),
+                            source: e.into(),
+                        },
+                    )?,
+                    sponsor: row.try_get("sponsor")?,
+                    recorded_by: row.try_get("recorded_by")?,
+                    created_at: parse_required_datetime(row, "created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Observation data cached for a competition, whichever attestation it was fetched after
+    pub async fn get_observation_data(
+        &self,
+        competition_id: Uuid,
+    ) -> Result<Option<CachedObservationData>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT attestation, data, fetched_at
+            FROM competition_observation_data
+            WHERE competition_id = ?",
+        )
+        .bind(competition_id.to_string())
+        .fetch_optional(self.db_connection.read())
+        .await?;
+
+        row.map(|row| {
+            let data: String = row.try_get("data")?;
+            Ok(CachedObservationData {
+                competition_id,
+                attestation: row.try_get("attestation")?,
+                data: serde_json::from_str(&data).map_err(|e| sqlx::Error::ColumnDecode {
+                    index: "data".to_string(),
+                    source: Box::new(e),
+                })?,
+                fetched_at: parse_required_datetime(&row, "fetched_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Cache a competition's observation data, replacing data fetched for an earlier attestation
+    pub async fn save_observation_data(
+        &self,
+        cached: CachedObservationData,
+    ) -> Result<(), sqlx::Error> {
+        let data =
+            serde_json::to_string(&cached.data).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        let fetched_at =
+            format_timestamp(cached.fetched_at).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE competitions
-                    SET keymeld_session = ?
-                    WHERE id = ?",
+                sqlx::query(
+                    "INSERT INTO competition_observation_data
+                        (competition_id, attestation, data, fetched_at)
+                    VALUES (?, ?, ?, ?)
+                    ON CONFLICT (competition_id) DO UPDATE SET
+                        attestation = excluded.attestation,
+                        data = excluded.data,
+                        fetched_at = excluded.fetched_at",
                 )
-                .bind(session_json)
-                .bind(competition_id_str)
+                .bind(cached.competition_id.to_string())
+                .bind(&cached.attestation)
+                .bind(&data)
+                .bind(&fetched_at)
                 .execute(&pool)
                 .await?;
-                Ok(result.rows_affected() > 0)
+                Ok(())
             })
             .await
             .map_err(|e| match e {
@@ -1930,42 +3810,38 @@ impl CompetitionStore {
             })
     }
 
-    /// Retrieve a Keymeld session for a competition
-    pub async fn get_keymeld_session(
-        &self,
-        competition_id: Uuid,
-    ) -> Result<Option<crate::infra::keymeld::StoredDlcKeygenSession>, sqlx::Error> {
-        let session_bytes: Option<Option<Vec<u8>>> =
-            sqlx::query_scalar("SELECT keymeld_session FROM competitions WHERE id = ?")
-                .bind(competition_id.to_string())
+    /// Index the named lightning stream's catch-up sweep resumes after, 0 before its first sweep
+    pub async fn get_ln_sync_cursor(&self, stream: &str) -> Result<u64, sqlx::Error> {
+        let last_index: Option<i64> =
+            sqlx::query_scalar("SELECT last_index FROM ln_sync_cursors WHERE stream = ?")
+                .bind(stream)
                 .fetch_optional(self.db_connection.read())
                 .await?;
-
-        match session_bytes {
-            Some(Some(bytes)) => {
-                let session =
-                    serde_json::from_slice(&bytes).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
-                Ok(Some(session))
-            }
-            _ => Ok(None),
-        }
+        Ok(last_index.unwrap_or(0).max(0) as u64)
     }
 
-    /// Clear a Keymeld session for a competition (e.g., on failure or completion)
-    pub async fn clear_keymeld_session(&self, competition_id: Uuid) -> Result<bool, sqlx::Error> {
-        let competition_id_str = competition_id.to_string();
+    pub async fn set_ln_sync_cursor(
+        &self,
+        stream: &str,
+        last_index: u64,
+    ) -> Result<(), sqlx::Error> {
+        let stream = stream.to_string();
+        let last_index = i64::try_from(last_index).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
         self.db_connection
             .execute_write(move |pool| async move {
-                let result = sqlx::query(
-                    "UPDATE competitions
-                    SET keymeld_session = NULL
-                    WHERE id = ?",
+                sqlx::query(
+                    "INSERT INTO ln_sync_cursors (stream, last_index, updated_at)
+                    VALUES (?, ?, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+                    ON CONFLICT (stream) DO UPDATE SET
+                        last_index = excluded.last_index,
+                        updated_at = excluded.updated_at",
                 )
-                .bind(competition_id_str)
+                .bind(&stream)
+                .bind(last_index)
                 .execute(&pool)
                 .await?;
-                Ok(result.rows_affected() > 0)
+                Ok(())
             })
             .await
             .map_err(|e| match e {
@@ -1974,52 +3850,63 @@ impl CompetitionStore {
             })
     }
 
-    /// Get a single entry by its ID
-    pub async fn get_entry_by_id(&self, entry_id: Uuid) -> Result<Option<UserEntry>, sqlx::Error> {
-        let query = "WITH latest_payouts AS (
-              SELECT
-                  entry_id,
-                  payout_payment_request,
-                  ROW_NUMBER() OVER (
-                      PARTITION BY entry_id
-                      ORDER BY COALESCE(succeed_at, initiated_at) DESC
-                  ) as rn,
-                  COALESCE(succeed_at, initiated_at) as latest_payout_time
-              FROM payouts
-              WHERE failed_at IS NULL
-          )
-          SELECT
-              entries.id as id,
-              ticket_id,
-              entries.event_id as event_id,
-              pubkey,
-              entries.ephemeral_pubkey as ephemeral_pubkey,
-              ephemeral_privatekey_encrypted,
-              ephemeral_privatekey,
-              encrypted_keymeld_private_key,
-              keymeld_auth_pubkey,
-              public_nonces,
-              partial_signatures,
-              funding_psbt_base64,
-              entry_submission,
-              payout_preimage_encrypted,
-              payout_hash,
-              payout_preimage,
-              signed_at,
-              tickets.paid_at AS paid_at,
-              sellback_broadcasted_at,
-              reclaimed_broadcasted_at,
-              latest_payouts.latest_payout_time as paid_out_at,
-              latest_payouts.payout_payment_request as payout_ln_invoice
-          FROM entries
-          LEFT JOIN tickets ON entries.ticket_id = tickets.id
-          LEFT JOIN latest_payouts ON entries.id = latest_payouts.entry_id AND latest_payouts.rn = 1
-          WHERE entries.id = ?";
+    /// Revenue, costs and net of a competition from its ledger entries
+    pub async fn get_pnl(&self, competition_id: Uuid) -> Result<CompetitionPnl, sqlx::Error> {
+        let totals = self
+            .get_ledger_totals(Some(competition_id))
+            .await?
+            .remove(&competition_id)
+            .unwrap_or_default();
+        Ok(CompetitionPnl::from_totals(Some(competition_id), totals))
+    }
 
-        sqlx::query_as::<_, UserEntry>(query)
-            .bind(entry_id.to_string())
-            .fetch_optional(self.db_connection.read())
-            .await
+    /// Profit and loss of every competition with ledger entries, oldest first, and their total
+    pub async fn get_pnl_report(&self) -> Result<PnlReport, sqlx::Error> {
+        let competitions = self
+            .get_ledger_totals(None)
+            .await?
+            .into_iter()
+            .map(|(competition_id, totals)| {
+                CompetitionPnl::from_totals(Some(competition_id), totals)
+            })
+            .collect();
+        Ok(PnlReport::new(competitions))
+    }
+
+    async fn get_ledger_totals(
+        &self,
+        competition_id: Option<Uuid>,
+    ) -> Result<BTreeMap<Uuid, BTreeMap<LedgerEntryKind, u64>>, sqlx::Error> {
+        let rows: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT competition_id, kind, SUM(amount_sats)
+            FROM ledger_entries
+            WHERE ? IS NULL OR competition_id = ?
+            GROUP BY competition_id, kind",
+        )
+        .bind(competition_id.map(|id| id.to_string()))
+        .bind(competition_id.map(|id| id.to_string()))
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        let mut totals: BTreeMap<Uuid, BTreeMap<LedgerEntryKind, u64>> = BTreeMap::new();
+        for (competition_id, kind, amount) in rows {
+            let competition_id =
+                Uuid::parse_str(&competition_id).map_err(|e| sqlx::Error::ColumnDecode {
+                    index: "competition_id".to_string(),
+                    source: Box::new(e),
+                })?;
+            let kind = kind
+                .parse::<LedgerEntryKind>()
+                .map_err(|e| sqlx::Error::ColumnDecode {
+                    index: "kind".to_string(),
+                    source: e.into(),
+                })?;
+            totals
+                .entry(competition_id)
+                .or_default()
+                .insert(kind, amount.max(0) as u64);
+        }
+        Ok(totals)
     }
 
     /// Delete a competition and all related data (tickets, entries, payouts)
@@ -2036,12 +3923,42 @@ impl CompetitionStore {
                 .execute(&pool)
                 .await?;
 
+                sqlx::query("DELETE FROM ledger_entries WHERE competition_id = ?")
+                    .bind(&id_str)
+                    .execute(&pool)
+                    .await?;
+
+                sqlx::query("DELETE FROM competition_overrides WHERE competition_id = ?")
+                    .bind(&id_str)
+                    .execute(&pool)
+                    .await?;
+
+                sqlx::query("DELETE FROM competition_sponsorships WHERE competition_id = ?")
+                    .bind(&id_str)
+                    .execute(&pool)
+                    .await?;
+
+                sqlx::query("DELETE FROM competition_changes WHERE competition_id = ?")
+                    .bind(&id_str)
+                    .execute(&pool)
+                    .await?;
+
+                sqlx::query("DELETE FROM competition_observation_data WHERE competition_id = ?")
+                    .bind(&id_str)
+                    .execute(&pool)
+                    .await?;
+
                 // Delete entries for this competition
                 sqlx::query("DELETE FROM entries WHERE event_id = ?")
                     .bind(&id_str)
                     .execute(&pool)
                     .await?;
 
+                sqlx::query("DELETE FROM competition_invitations WHERE competition_id = ?")
+                    .bind(&id_str)
+                    .execute(&pool)
+                    .await?;
+
                 // Delete tickets for this competition
                 sqlx::query("DELETE FROM tickets WHERE event_id = ?")
                     .bind(&id_str)
@@ -2063,3 +3980,756 @@ impl CompetitionStore {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::CreateEvent,
+        infra::{
+            db::{DatabasePoolConfig, DatabaseType},
+            db_encryption::DbEncryptionKey,
+        },
+    };
+    use time::Duration;
+
+    /// Store tests run against a plaintext database, and against an encrypted one as well when
+    /// built with the `sqlcipher` feature
+    fn db_keys() -> Vec<Option<DbEncryptionKey>> {
+        let mut keys = vec![None];
+        if cfg!(feature = "sqlcipher") {
+            keys.push(Some(DbEncryptionKey::from_bytes(&[7u8; 32])));
+        }
+        keys
+    }
+
+    async fn test_store(
+        encryption_key: Option<DbEncryptionKey>,
+    ) -> (CompetitionStore, DBConnection, String) {
+        let dir = std::env::temp_dir().join(format!("coordinator-store-{}", Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.to_string_lossy().to_string();
+        let db = DBConnection::new(
+            &dir,
+            "competitions",
+            DatabasePoolConfig {
+                encryption_key,
+                ..Default::default()
+            },
+            DatabaseType::Competitions,
+        )
+        .await
+        .unwrap();
+        (CompetitionStore::new(db.clone()), db, dir)
+    }
+
+    async fn stored_competition(store: &CompetitionStore) -> (Competition, Vec<Ticket>) {
+        let start = OffsetDateTime::now_utc() + Duration::hours(6);
+        let competition = Competition::new(&CreateEvent {
+            total_allowed_entries: 1,
+            total_competition_pool: Sats(1000),
+            ..CreateEvent::fixture(start)
+        });
+        let tickets = competition.generate_competition_tickets(1).unwrap();
+        store
+            .add_competition_with_tickets(competition.clone(), tickets.clone())
+            .await
+            .unwrap();
+        (competition, tickets)
+    }
+
+    fn decode_column(error: sqlx::Error) -> String {
+        match error {
+            sqlx::Error::ColumnDecode { index, .. } => index.trim_matches('"').to_string(),
+            other => panic!("expected a column decode error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_competition_blobs_are_reported() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (competition, _) = stored_competition(&store).await;
+
+            // Undecodable blobs used to be indistinguishable from a competition that was never signed
+            sqlx::query("UPDATE competitions SET signed_contract = ?, errors = ? WHERE id = ?")
+                .bind(&b"{\"not\": \"a contract\"}"[..])
+                .bind(&b""[..])
+                .bind(competition.id.to_string())
+                .execute(db.write_pool())
+                .await
+                .unwrap();
+
+            let error = store.get_competition(competition.id).await.unwrap_err();
+            assert_eq!(decode_column(error), "signed_contract");
+
+            let undecodable = store.find_undecodable_blobs().await.unwrap();
+            let columns: Vec<_> = undecodable
+                .iter()
+                .map(|blob| (blob.table.as_str(), blob.column.as_str()))
+                .collect();
+            assert_eq!(
+                columns,
+                vec![
+                    ("competitions", "signed_contract"),
+                    ("competitions", "errors")
+                ]
+            );
+            assert!(undecodable
+                .iter()
+                .all(|blob| blob.row_id == competition.id.to_string()));
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_competition_found_by_slug_and_names_are_unique() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (unnamed, _) = stored_competition(&store).await;
+            let named = Competition::new(&CreateEvent {
+                id: Uuid::now_v7(),
+                name: Some("Chicago Heat Wave".to_string()),
+                ..unnamed.event_submission
+            });
+            let tickets = named.generate_competition_tickets(1).unwrap();
+            store
+                .add_competition_with_tickets(named.clone(), tickets)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                store
+                    .get_competition_id_by_slug("chicago-heat-wave")
+                    .await
+                    .unwrap(),
+                Some(named.id)
+            );
+            assert_eq!(
+                store.get_competition_id_by_slug("nope").await.unwrap(),
+                None
+            );
+            let stored = store.get_competition(named.id).await.unwrap();
+            assert_eq!(stored.name.as_deref(), Some("Chicago Heat Wave"));
+            assert_eq!(stored.slug.as_deref(), Some("chicago-heat-wave"));
+
+            assert_eq!(
+                store
+                    .competition_name_or_slug_taken(Some("chicago HEAT wave"), Some("other-slug"))
+                    .await
+                    .unwrap(),
+                (true, false)
+            );
+            assert_eq!(
+                store
+                    .competition_name_or_slug_taken(None, Some("chicago-heat-wave"))
+                    .await
+                    .unwrap(),
+                (false, true)
+            );
+
+            // The unique index backs up the check when two creations race
+            let mut duplicate = named.clone();
+            duplicate.id = Uuid::now_v7();
+            duplicate.name = None;
+            assert!(store
+                .add_competition_with_tickets(duplicate, vec![])
+                .await
+                .is_err());
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mistyped_ticket_column_is_an_error_not_a_panic() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (_, tickets) = stored_competition(&store).await;
+            assert!(store.find_undecodable_blobs().await.unwrap().is_empty());
+
+            sqlx::query("UPDATE tickets SET hash = X'00' WHERE id = ?")
+                .bind(tickets[0].id.to_string())
+                .execute(db.write_pool())
+                .await
+                .unwrap();
+
+            let error = store.get_ticket(tickets[0].id).await.unwrap_err();
+            assert_eq!(decode_column(error), "hash");
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_legacy_ticket_timestamps_load_and_normalize() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (_, tickets) = stored_competition(&store).await;
+            let ticket_id = tickets[0].id.to_string();
+
+            // Format SQLite's datetime() wrote before timestamps were RFC3339 everywhere
+            sqlx::query(
+            "UPDATE tickets SET reserved_at = '2026-01-02 03:04:05', paid_at = '2026-01-02 03:05:00'
+             WHERE id = ?",
+        )
+        .bind(&ticket_id)
+        .execute(db.write_pool())
+        .await
+        .unwrap();
+
+            let ticket = store.get_ticket(tickets[0].id).await.unwrap();
+            assert_eq!(
+                ticket.reserved_at,
+                Some(time::macros::datetime!(2026-01-02 03:04:05 UTC))
+            );
+
+            sqlx::raw_sql(include_str!(
+                "../../../migrations/competitions/20260301000000_normalize_timestamps.up.sql"
+            ))
+            .execute(db.write_pool())
+            .await
+            .unwrap();
+            let stored: String = sqlx::query_scalar("SELECT reserved_at FROM tickets WHERE id = ?")
+                .bind(&ticket_id)
+                .fetch_one(db.read())
+                .await
+                .unwrap();
+            assert_eq!(stored, "2026-01-02T03:04:05Z");
+            assert_eq!(
+                store.get_ticket(tickets[0].id).await.unwrap().paid_at,
+                ticket.paid_at
+            );
+
+            // An unparseable timestamp used to be papered over, now it fails the load
+            sqlx::query("UPDATE tickets SET reserved_at = 'yesterday' WHERE id = ?")
+                .bind(&ticket_id)
+                .execute(db.write_pool())
+                .await
+                .unwrap();
+            let error = store.get_ticket(tickets[0].id).await.unwrap_err();
+            assert_eq!(decode_column(error), "reserved_at");
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reservation_binds_ephemeral_pubkey_until_paid() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (competition, _) = stored_competition(&store).await;
+
+            let reserved = store
+                .get_and_reserve_ticket(competition.id, "player", "02ab")
+                .await
+                .unwrap();
+            assert_eq!(reserved.ephemeral_pubkey.as_deref(), Some("02ab"));
+            let stored = store.get_ticket(reserved.id).await.unwrap();
+            assert_eq!(stored.ephemeral_pubkey.as_deref(), Some("02ab"));
+
+            // Asking again before paying rebinds the same ticket
+            let again = store
+                .get_and_reserve_ticket(competition.id, "player", "03cd")
+                .await
+                .unwrap();
+            assert_eq!(again.id, reserved.id);
+            assert_eq!(again.ephemeral_pubkey.as_deref(), Some("03cd"));
+
+            assert!(store
+                .mark_ticket_paid(&reserved.hash, competition.id)
+                .await
+                .unwrap());
+            let paid = store
+                .get_and_reserve_ticket(competition.id, "player", "02ef")
+                .await
+                .unwrap();
+            assert_eq!(paid.id, reserved.id);
+            assert_eq!(paid.ephemeral_pubkey.as_deref(), Some("03cd"));
+            let stored = store.get_ticket(reserved.id).await.unwrap();
+            assert_eq!(stored.ephemeral_pubkey.as_deref(), Some("03cd"));
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reservations_issue_tickets_up_to_the_limit() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (competition, _) = stored_competition(&store).await;
+            let competition = Competition::new(&CreateEvent {
+                id: Uuid::now_v7(),
+                total_allowed_entries: 5,
+                total_competition_pool: Sats(5000),
+                ..competition.event_submission
+            });
+            // Two created up front, three left to issue
+            let buffer = competition.generate_competition_tickets(2).unwrap();
+            store
+                .add_competition_with_tickets(competition.clone(), buffer.clone())
+                .await
+                .unwrap();
+            let available = store
+                .get_available_ticket_counts(&[competition.id])
+                .await
+                .unwrap();
+            assert_eq!(available.get(&competition.id), Some(&5));
+
+            let players: Vec<String> = (0..8).map(|i| format!("player{}", i)).collect();
+            let reservations = futures::future::join_all(
+                players
+                    .iter()
+                    .map(|player| store.get_and_reserve_ticket(competition.id, player, "02ab")),
+            )
+            .await;
+
+            let reserved: Vec<Ticket> = reservations
+                .into_iter()
+                .filter_map(|reservation| match reservation {
+                    Ok(ticket) => Some(ticket),
+                    Err(sqlx::Error::RowNotFound) => None,
+                    Err(e) => panic!("reservation failed: {}", e),
+                })
+                .collect();
+            assert_eq!(reserved.len(), 5);
+            let ids: std::collections::BTreeSet<Uuid> = reserved.iter().map(|t| t.id).collect();
+            assert_eq!(ids.len(), 5);
+            for ticket in &buffer {
+                assert!(ids.contains(&ticket.id));
+            }
+            let hashes: std::collections::BTreeSet<&str> =
+                reserved.iter().map(|t| t.hash.as_str()).collect();
+            assert_eq!(hashes.len(), 5);
+
+            let (tickets, unissued): (i64, i64) = sqlx::query_as(
+                "SELECT (SELECT COUNT(*) FROM tickets WHERE event_id = ?1), unissued_tickets
+             FROM competitions WHERE id = ?1",
+            )
+            .bind(competition.id.to_string())
+            .fetch_one(db.write_pool())
+            .await
+            .unwrap();
+            assert_eq!((tickets, unissued), (5, 0));
+            assert!(store
+                .get_available_ticket_counts(&[competition.id])
+                .await
+                .unwrap()
+                .is_empty());
+
+            // A lapsed reservation frees its ticket rather than issuing another
+            sqlx::query(
+            "UPDATE tickets SET reserved_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-11 minutes')
+             WHERE id = ?",
+        )
+        .bind(reserved[0].id.to_string())
+        .execute(db.write_pool())
+        .await
+        .unwrap();
+            let late = store
+                .get_and_reserve_ticket(competition.id, "late", "02ab")
+                .await
+                .unwrap();
+            assert_eq!(late.id, reserved[0].id);
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_reservation_is_released_with_a_new_hash() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (competition, tickets) = stored_competition(&store).await;
+            let reserved = store
+                .get_and_reserve_ticket(competition.id, "player", "02ab")
+                .await
+                .unwrap();
+            assert_eq!(reserved.id, tickets[0].id);
+
+            // Still inside the reservation window
+            assert!(store.get_expired_reservations().await.unwrap().is_empty());
+            assert_eq!(
+                store
+                    .release_expired_reservation(reserved.id, "00", "11")
+                    .await
+                    .unwrap(),
+                None
+            );
+
+            sqlx::query(
+            "UPDATE tickets SET reserved_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-11 minutes')
+             WHERE id = ?",
+        )
+        .bind(reserved.id.to_string())
+        .execute(db.write_pool())
+        .await
+        .unwrap();
+
+            let expired = store.get_expired_reservations().await.unwrap();
+            assert_eq!(expired.len(), 1);
+            assert!(expired[0].is_expired());
+            let released_reservation = store
+                .release_expired_reservation(reserved.id, "00", "11")
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(released_reservation.hash, reserved.hash);
+            assert_eq!(
+                released_reservation.payment_request,
+                reserved.payment_request
+            );
+
+            let released = store.get_ticket(reserved.id).await.unwrap();
+            assert!(released.can_be_reserved());
+            assert_eq!(released.hash, "11");
+            assert!(released.reserved_by.is_none());
+            assert!(store.get_expired_reservations().await.unwrap().is_empty());
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    async fn query_plan(db: &DBConnection, query: &str) -> String {
+        let details: Vec<String> = sqlx::query(&format!("EXPLAIN QUERY PLAN {}", query))
+            .fetch_all(db.read())
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get::<String, _>("detail"))
+            .collect();
+        details.join("\n")
+    }
+
+    #[tokio::test]
+    async fn test_hot_queries_use_indexes() {
+        for encryption_key in db_keys() {
+            let (_store, db, dir) = test_store(encryption_key).await;
+
+            let watcher_plan = query_plan(
+                &db,
+                &competitions_query(Some(&format!(
+                    "{} AND failed_at IS NULL",
+                    ACTIVE_COMPETITIONS
+                ))),
+            )
+            .await;
+            assert!(
+                watcher_plan.contains("USING INDEX idx_competitions_active"),
+                "{}",
+                watcher_plan
+            );
+
+            let plans = [
+                (
+                    "SELECT tickets.id FROM tickets
+                 LEFT JOIN entries ON tickets.id = entries.ticket_id
+                 WHERE tickets.event_id = 'a' AND entries.id IS NULL
+                 ORDER BY reserved_at IS NULL DESC, reserved_at, tickets.id LIMIT 1",
+                    "idx_tickets_event_reserved_at",
+                ),
+                (
+                    "SELECT id FROM tickets WHERE hash = 'a' AND event_id = 'b'",
+                    "idx_tickets_hash",
+                ),
+                (
+                    "SELECT id FROM payouts WHERE entry_id = 'a' AND failed_at IS NULL",
+                    "idx_payouts_entry_failed_at",
+                ),
+                (
+                    "SELECT id FROM entries WHERE event_id = 'a'",
+                    "idx_entries_event_player_index",
+                ),
+            ];
+            for (query, index) in plans {
+                let plan = query_plan(&db, query).await;
+                assert!(
+                    plan.contains(index),
+                    "{} not used by {}:\n{}",
+                    index,
+                    query,
+                    plan
+                );
+            }
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    /// Watcher tick query time over 10k competitions without and with the query indexes, run
+    /// with `cargo test -p coordinator watcher_tick_time -- --ignored --nocapture`
+    #[tokio::test]
+    #[ignore]
+    async fn test_watcher_tick_time_on_seeded_db() {
+        let (store, db, dir) = test_store(None).await;
+        stored_competition(&store).await;
+
+        // 10k copies of the stored competition: 1% still active, 1% failed, the rest completed
+        sqlx::query(
+            "INSERT INTO competitions (id, created_at, event_submission, failed_at, completed_at)
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 10000)
+             SELECT printf('00000000-0000-4000-8000-%012d', i),
+                    template.created_at,
+                    template.event_submission,
+                    CASE WHEN i % 100 = 50 THEN template.created_at END,
+                    CASE WHEN i % 100 NOT IN (0, 50) THEN template.created_at END
+             FROM n, (SELECT created_at, event_submission FROM competitions LIMIT 1) AS template",
+        )
+        .execute(db.write_pool())
+        .await
+        .unwrap();
+
+        let migration = include_str!(
+            "../../../migrations/competitions/20260305000000_add_query_indexes.up.sql"
+        );
+        sqlx::raw_sql(include_str!(
+            "../../../migrations/competitions/20260305000000_add_query_indexes.down.sql"
+        ))
+        .execute(db.write_pool())
+        .await
+        .unwrap();
+
+        // Before: every non-terminal competition loaded without indexes, failed ones dropped in Rust
+        let started = std::time::Instant::now();
+        let mut before: Vec<Uuid> = store
+            .get_competitions(true, true)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|competition| !competition.skip_competition())
+            .map(|competition| competition.id)
+            .collect();
+        before.sort();
+        let before_elapsed = started.elapsed();
+
+        sqlx::raw_sql(migration)
+            .execute(db.write_pool())
+            .await
+            .unwrap();
+        let started = std::time::Instant::now();
+        let mut after: Vec<Uuid> = store
+            .get_competitions_to_process()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|competition| competition.id)
+            .collect();
+        let after_elapsed = started.elapsed();
+        after.sort();
+
+        println!(
+            "watcher tick query over 10001 competitions: before {:?}, after {:?}",
+            before_elapsed, after_elapsed
+        );
+        assert_eq!(before, after);
+        assert_eq!(after.len(), 101);
+
+        db.close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stale_competition_save_is_refused() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (competition, _) = stored_competition(&store).await;
+
+            let mut watcher_copy = store.get_competition(competition.id).await.unwrap();
+            let mut admin_copy = watcher_copy.clone();
+            assert_eq!(watcher_copy.version, 0);
+
+            admin_copy.cancelled_at = Some(OffsetDateTime::now_utc());
+            store
+                .update_competitions(std::slice::from_mut(&mut admin_copy))
+                .await
+                .unwrap();
+            assert_eq!(admin_copy.version, 1);
+
+            watcher_copy.failed_at = Some(OffsetDateTime::now_utc());
+            let err = store
+                .update_competitions(std::slice::from_mut(&mut watcher_copy))
+                .await
+                .unwrap_err();
+            assert!(err.is_conflict());
+            assert!(matches!(
+                err,
+                CompetitionUpdateError::Conflict {
+                    expected: 0,
+                    stored: 1,
+                    ..
+                }
+            ));
+            assert_eq!(watcher_copy.version, 0);
+
+            let stored = store.get_competition(competition.id).await.unwrap();
+            assert!(stored.cancelled_at.is_some());
+            assert!(stored.failed_at.is_none());
+            assert_eq!(stored.version, 1);
+
+            // Reloading picks up the newer version and the retried save goes through
+            let mut reloaded = stored;
+            reloaded.failed_at = Some(OffsetDateTime::now_utc());
+            store
+                .update_competitions(std::slice::from_mut(&mut reloaded))
+                .await
+                .unwrap();
+            assert_eq!(reloaded.version, 2);
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sponsorship_makes_a_stale_competition_save_conflict() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (competition, _) = stored_competition(&store).await;
+
+            let mut watcher_copy = store.get_competition(competition.id).await.unwrap();
+            assert!(store
+                .add_sponsorship(Sponsorship {
+                    id: Uuid::now_v7(),
+                    competition_id: competition.id,
+                    amount_sats: Sats(500),
+                    funds: SponsorFunds::Lightning {
+                        payment_hash: "ab".repeat(32),
+                    },
+                    sponsor: None,
+                    recorded_by: "admin".to_string(),
+                    created_at: OffsetDateTime::now_utc(),
+                })
+                .await
+                .unwrap());
+
+            // The watcher built on the pool before the sponsorship, its save mustn't drop it
+            watcher_copy.failed_at = Some(OffsetDateTime::now_utc());
+            let err = store
+                .update_competitions(std::slice::from_mut(&mut watcher_copy))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                CompetitionUpdateError::Conflict {
+                    expected: 0,
+                    stored: 1,
+                    ..
+                }
+            ));
+
+            let stored = store.get_competition(competition.id).await.unwrap();
+            assert_eq!(stored.version, 1);
+            assert!(stored.failed_at.is_none());
+            assert_eq!(
+                Some(stored.event_submission.total_competition_pool),
+                competition
+                    .event_submission
+                    .total_competition_pool
+                    .checked_add(Sats(500))
+            );
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_competition_saves_record_the_fields_they_changed() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (competition, _) = stored_competition(&store).await;
+
+            let mut competition = store.get_competition(competition.id).await.unwrap();
+            // Nothing changed, nothing is written
+            store
+                .update_competitions(std::slice::from_mut(&mut competition))
+                .await
+                .unwrap();
+            assert_eq!(competition.version, 0);
+            assert!(store
+                .get_competition_changes(competition.id)
+                .await
+                .unwrap()
+                .is_empty());
+
+            competition.event_created_at = Some(OffsetDateTime::now_utc());
+            competition.oracle_submitted_entry_ids = vec![Uuid::now_v7()];
+            store
+                .update_competitions(std::slice::from_mut(&mut competition))
+                .await
+                .unwrap();
+            competition.entries_submitted_at = Some(OffsetDateTime::now_utc());
+            store
+                .update_competitions(std::slice::from_mut(&mut competition))
+                .await
+                .unwrap();
+
+            let changes = store.get_competition_changes(competition.id).await.unwrap();
+            assert_eq!(changes.len(), 2);
+            assert_eq!(changes[0].version, 1);
+            assert_eq!(
+                changes[0].changed_fields,
+                vec!["event_created_at", "oracle_submitted_entry_ids"]
+            );
+            assert_eq!(changes[1].version, 2);
+            assert_eq!(changes[1].changed_fields, vec!["entries_submitted_at"]);
+            assert_eq!(
+                store.get_competition(competition.id).await.unwrap().version,
+                2
+            );
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_competitions_are_cancelled_after_a_while() {
+        for encryption_key in db_keys() {
+            let (store, db, dir) = test_store(encryption_key).await;
+            let (competition, _) = stored_competition(&store).await;
+
+            let mut failed = store.get_competition(competition.id).await.unwrap();
+            failed.failed_at = Some(OffsetDateTime::now_utc() - Duration::minutes(30));
+            store
+                .update_competitions(std::slice::from_mut(&mut failed))
+                .await
+                .unwrap();
+
+            assert!(store
+                .get_competitions_to_process()
+                .await
+                .unwrap()
+                .is_empty());
+            assert!(store
+                .cancel_failed_competitions(Duration::hours(1))
+                .await
+                .unwrap()
+                .is_empty());
+            assert_eq!(
+                store
+                    .cancel_failed_competitions(Duration::minutes(10))
+                    .await
+                    .unwrap(),
+                vec![competition.id]
+            );
+            assert!(store
+                .get_competition(competition.id)
+                .await
+                .unwrap()
+                .is_cancelled());
+
+            db.close().await;
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! Block heights and wall-clock estimates for a competition's contract timelocks, so entrants can
+//! see when each unilateral exit path opens once the funding transaction confirms.
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+/// Average time between blocks used to turn heights into wall-clock estimates
+pub const EXPECTED_BLOCK_INTERVAL_SECS: i64 = 600;
+
+/// A block height with its estimated arrival time, relative to the chain tip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockEstimate {
+    pub height: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub estimated_at: OffsetDateTime,
+    /// Zero once the tip has reached the height
+    pub blocks_remaining: u32,
+}
+
+impl BlockEstimate {
+    /// Heights at or below the tip are estimated backwards from `now` at the same interval
+    pub fn new(height: u32, tip_height: u32, now: OffsetDateTime) -> Self {
+        let blocks_ahead = i64::from(height) - i64::from(tip_height);
+        BlockEstimate {
+            height,
+            estimated_at: now + Duration::seconds(blocks_ahead * EXPECTED_BLOCK_INTERVAL_SECS),
+            blocks_remaining: height.saturating_sub(tip_height),
+        }
+    }
+
+    pub fn reached(&self) -> bool {
+        self.blocks_remaining == 0
+    }
+}
+
+/// The first block expected to be mined at or after `at`, never below the next block
+pub fn estimate_height_at(at: OffsetDateTime, tip_height: u32, now: OffsetDateTime) -> u32 {
+    let secs = (at - now).whole_seconds();
+    if secs <= 0 {
+        return tip_height + 1;
+    }
+    let blocks = (secs + EXPECTED_BLOCK_INTERVAL_SECS - 1) / EXPECTED_BLOCK_INTERVAL_SECS;
+    tip_height.saturating_add(u32::try_from(blocks).unwrap_or(u32::MAX))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockWindow {
+    pub opens: BlockEstimate,
+    pub closes: BlockEstimate,
+}
+
+impl BlockWindow {
+    fn new(opens: u32, closes: u32, tip_height: u32, now: OffsetDateTime) -> Self {
+        BlockWindow {
+            opens: BlockEstimate::new(opens, tip_height, now),
+            closes: BlockEstimate::new(closes, tip_height, now),
+        }
+    }
+}
+
+/// Exit windows for one of the requesting entrant's player slots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryTimeline {
+    pub entry_id: Uuid,
+    pub player_index: usize,
+    /// Unknown until the oracle attests
+    pub is_winner: Option<bool>,
+    /// Blocks in which a winner can still sell back their payout preimage over lightning,
+    /// after it closes the coordinator reclaims unclaimed split outputs
+    pub sellback: BlockWindow,
+    /// Blocks in which a winner can sweep their split output on chain themselves
+    pub onchain_claim: BlockWindow,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompetitionTimeline {
+    pub competition_id: Uuid,
+    pub tip_height: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    pub relative_locktime_block_delta: u16,
+    /// Nothing below is estimated until the funding transaction confirms
+    pub funding_confirmation_height: Option<u32>,
+    /// When the oracle attests and the outcome transaction can be broadcast
+    #[serde(with = "time::serde::rfc3339")]
+    pub outcome_eligible_at: OffsetDateTime,
+    /// After this every player can be refunded by the expiry transaction
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expiry_at: Option<OffsetDateTime>,
+    /// The confirmed height once the outcome transaction is mined, estimated before that
+    pub outcome_confirmation: Option<BlockEstimate>,
+    pub outcome_confirmed: bool,
+    /// Split transactions become valid one delta after the outcome confirms
+    pub first_delta: Option<BlockEstimate>,
+    /// Unclaimed split outputs can be reclaimed two deltas after the outcome confirms
+    pub second_delta: Option<BlockEstimate>,
+    /// The requesting entrant's windows, empty without NIP-98 auth
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub your_entries: Vec<EntryTimeline>,
+}
+
+/// Chain facts the timeline is computed from
+#[derive(Debug, Clone, Copy)]
+pub struct TimelineAnchors {
+    pub tip_height: u32,
+    pub now: OffsetDateTime,
+    pub funding_confirmation_height: Option<u32>,
+    pub outcome_confirmation_height: Option<u32>,
+}
+
+/// An entrant's player slot and whether it won, if known
+#[derive(Debug, Clone, Copy)]
+pub struct TimelinePlayer {
+    pub entry_id: Uuid,
+    pub player_index: usize,
+    pub is_winner: Option<bool>,
+}
+
+pub fn build_timeline(
+    competition_id: Uuid,
+    relative_locktime_block_delta: u16,
+    outcome_eligible_at: OffsetDateTime,
+    expiry_at: Option<OffsetDateTime>,
+    anchors: TimelineAnchors,
+    players: &[TimelinePlayer],
+) -> CompetitionTimeline {
+    let TimelineAnchors {
+        tip_height,
+        now,
+        funding_confirmation_height,
+        outcome_confirmation_height,
+    } = anchors;
+    let delta = u32::from(relative_locktime_block_delta);
+
+    // The outcome can't confirm before the funding transaction it spends
+    let outcome_height = funding_confirmation_height.map(|funding_height| {
+        outcome_confirmation_height.unwrap_or_else(|| {
+            estimate_height_at(outcome_eligible_at, tip_height, now).max(funding_height + 1)
+        })
+    });
+
+    let your_entries = match outcome_height {
+        Some(outcome_height) => players
+            .iter()
+            .map(|player| EntryTimeline {
+                entry_id: player.entry_id,
+                player_index: player.player_index,
+                is_winner: player.is_winner,
+                sellback: BlockWindow::new(
+                    outcome_height,
+                    outcome_height + 2 * delta,
+                    tip_height,
+                    now,
+                ),
+                onchain_claim: BlockWindow::new(
+                    outcome_height + delta,
+                    outcome_height + 2 * delta,
+                    tip_height,
+                    now,
+                ),
+            })
+            .collect(),
+        None => vec![],
+    };
+
+    CompetitionTimeline {
+        competition_id,
+        tip_height,
+        generated_at: now,
+        relative_locktime_block_delta,
+        funding_confirmation_height,
+        outcome_eligible_at,
+        expiry_at,
+        outcome_confirmation: outcome_height
+            .map(|height| BlockEstimate::new(height, tip_height, now)),
+        outcome_confirmed: outcome_confirmation_height.is_some(),
+        first_delta: outcome_height
+            .map(|height| BlockEstimate::new(height + delta, tip_height, now)),
+        second_delta: outcome_height
+            .map(|height| BlockEstimate::new(height + 2 * delta, tip_height, now)),
+        your_entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    const NOW: OffsetDateTime = datetime!(2026-10-17 12:00 UTC);
+
+    fn anchors(funding: Option<u32>, outcome: Option<u32>) -> TimelineAnchors {
+        TimelineAnchors {
+            tip_height: 1_000,
+            now: NOW,
+            funding_confirmation_height: funding,
+            outcome_confirmation_height: outcome,
+        }
+    }
+
+    #[test]
+    fn test_block_estimate_projects_from_tip() {
+        let ahead = BlockEstimate::new(1_006, 1_000, NOW);
+        assert_eq!(ahead.blocks_remaining, 6);
+        assert_eq!(ahead.estimated_at, NOW + Duration::hours(1));
+        assert!(!ahead.reached());
+
+        let behind = BlockEstimate::new(997, 1_000, NOW);
+        assert_eq!(behind.blocks_remaining, 0);
+        assert_eq!(behind.estimated_at, NOW - Duration::minutes(30));
+        assert!(behind.reached());
+    }
+
+    #[test]
+    fn test_estimate_height_at_rounds_up_to_the_next_block() {
+        assert_eq!(
+            estimate_height_at(NOW - Duration::hours(1), 1_000, NOW),
+            1_001
+        );
+        assert_eq!(estimate_height_at(NOW, 1_000, NOW), 1_001);
+        assert_eq!(
+            estimate_height_at(NOW + Duration::minutes(1), 1_000, NOW),
+            1_001
+        );
+        assert_eq!(
+            estimate_height_at(NOW + Duration::hours(1), 1_000, NOW),
+            1_006
+        );
+        assert_eq!(
+            estimate_height_at(NOW + Duration::minutes(61), 1_000, NOW),
+            1_007
+        );
+    }
+
+    #[test]
+    fn test_timeline_is_empty_until_funding_confirms() {
+        let player = TimelinePlayer {
+            entry_id: Uuid::now_v7(),
+            player_index: 0,
+            is_winner: None,
+        };
+        let timeline = build_timeline(
+            Uuid::now_v7(),
+            144,
+            NOW + Duration::days(1),
+            None,
+            anchors(None, None),
+            &[player],
+        );
+
+        assert!(timeline.outcome_confirmation.is_none());
+        assert!(timeline.first_delta.is_none());
+        assert!(timeline.second_delta.is_none());
+        assert!(timeline.your_entries.is_empty());
+    }
+
+    #[test]
+    fn test_deltas_are_estimated_from_the_outcome_eligibility_time() {
+        let timeline = build_timeline(
+            Uuid::now_v7(),
+            144,
+            NOW + Duration::days(1),
+            None,
+            anchors(Some(990), None),
+            &[],
+        );
+
+        let outcome = timeline.outcome_confirmation.unwrap();
+        assert_eq!(outcome.height, 1_144);
+        assert!(!timeline.outcome_confirmed);
+        assert_eq!(timeline.first_delta.unwrap().height, 1_288);
+        assert_eq!(
+            timeline.second_delta.unwrap().estimated_at,
+            NOW + Duration::days(3)
+        );
+    }
+
+    #[test]
+    fn test_confirmed_outcome_anchors_the_windows() {
+        let entry_id = Uuid::now_v7();
+        let timeline = build_timeline(
+            Uuid::now_v7(),
+            10,
+            NOW - Duration::hours(2),
+            None,
+            anchors(Some(900), Some(995)),
+            &[TimelinePlayer {
+                entry_id,
+                player_index: 3,
+                is_winner: Some(true),
+            }],
+        );
+
+        assert!(timeline.outcome_confirmed);
+        assert_eq!(timeline.first_delta.unwrap().height, 1_005);
+        assert_eq!(timeline.first_delta.unwrap().blocks_remaining, 5);
+        assert_eq!(timeline.second_delta.unwrap().height, 1_015);
+
+        let entry = &timeline.your_entries[0];
+        assert_eq!(entry.entry_id, entry_id);
+        assert_eq!(entry.is_winner, Some(true));
+        assert_eq!(entry.sellback.opens.height, 995);
+        assert!(entry.sellback.opens.reached());
+        assert_eq!(entry.sellback.closes.height, 1_015);
+        assert_eq!(entry.onchain_claim.opens.height, 1_005);
+        assert_eq!(entry.onchain_claim.closes, entry.sellback.closes);
+    }
+}
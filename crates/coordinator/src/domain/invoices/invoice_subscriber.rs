@@ -2,11 +2,20 @@ use log::{debug, error, info, warn};
 use std::sync::Arc;
 use tokio_util::sync::CancellationToken;
 
+use super::ReconnectBackoff;
 use crate::{
     domain::Coordinator,
-    infra::lightning::{InvoiceState, InvoiceUpdate, Ln},
+    infra::lightning::{InvoiceState, InvoiceUpdate, Ln, PRIMARY_LN_BACKEND},
 };
 
+/// Cursor of the invoice catch-up sweep, an lnd add_index. Each backend numbers its invoices on
+/// its own so each has its own cursor, `invoices:<backend_id>`.
+const INVOICE_CURSOR: &str = "invoices";
+
+fn invoice_cursor(backend_id: &str) -> String {
+    format!("{}:{}", INVOICE_CURSOR, backend_id)
+}
+
 pub struct InvoiceSubscriber {
     coordinator: Arc<Coordinator>,
     ln: Arc<dyn Ln>,
@@ -29,17 +38,23 @@ impl InvoiceSubscriber {
     pub async fn subscribe(&self) -> Result<(), anyhow::Error> {
         info!("Starting invoice subscriber");
 
+        let mut backoff = ReconnectBackoff::new();
         loop {
             if self.cancel_token.is_cancelled() {
                 break;
             }
 
-            if let Err(e) = self.run_subscription().await {
-                error!("Invoice subscription error: {}", e);
+            match self.run_subscription().await {
+                Ok(()) => backoff.reset(),
+                Err(e) => error!("Invoice subscription error: {}", e),
             }
 
+            let delay = backoff.next_delay();
+            if !self.cancel_token.is_cancelled() {
+                info!("Invoice subscription reconnecting in {:?}", delay);
+            }
             tokio::select! {
-                _ = tokio::time::sleep(tokio::time::Duration::from_secs(5)) => {}
+                _ = tokio::time::sleep(delay) => {}
                 _ = self.cancel_token.cancelled() => break,
             }
         }
@@ -51,6 +66,8 @@ impl InvoiceSubscriber {
     async fn run_subscription(&self) -> Result<(), anyhow::Error> {
         let mut rx = self.ln.subscribe_invoices().await?;
         info!("Invoice subscription connected");
+        // Subscribed first, so an invoice accepted during the sweep is still streamed
+        self.catch_up().await?;
 
         loop {
             tokio::select! {
@@ -67,6 +84,66 @@ impl InvoiceSubscriber {
         Ok(())
     }
 
+    /// Process every invoice added since each backend's stored cursor, picking up acceptances
+    /// missed while the subscription was down. A backend that can't be listed is retried on the
+    /// next reconnect without holding up the others.
+    async fn catch_up(&self) -> Result<(), anyhow::Error> {
+        let backend_ids = self.ln.backend_ids();
+        let mut listed = false;
+        for backend_id in &backend_ids {
+            match self.catch_up_backend(backend_id).await {
+                Ok(()) => listed = true,
+                Err(e) => warn!(
+                    "Invoice catch-up of lightning backend {} failed: {}",
+                    backend_id, e
+                ),
+            }
+        }
+        if !listed {
+            return Err(anyhow::anyhow!("No lightning backend listed its invoices"));
+        }
+        Ok(())
+    }
+
+    async fn catch_up_backend(&self, backend_id: &str) -> Result<(), anyhow::Error> {
+        let store = &self.coordinator.competition_store;
+        let cursor_name = invoice_cursor(backend_id);
+        let since = match store.get_ln_sync_cursor(&cursor_name).await? {
+            // Stored before cursors were kept per backend, when there was only the primary
+            0 if backend_id == PRIMARY_LN_BACKEND => {
+                store.get_ln_sync_cursor(INVOICE_CURSOR).await?
+            }
+            since => since,
+        };
+        let invoices = self.ln.list_invoices_since(backend_id, since).await?;
+        debug!(
+            "Invoice catch-up found {} invoice(s) backend {} added after index {}",
+            invoices.len(),
+            backend_id,
+            since
+        );
+
+        let mut last_index = since;
+        let mut oldest_open: Option<u64> = None;
+        for update in invoices {
+            if let Some(add_index) = update.add_index {
+                last_index = last_index.max(add_index);
+                if update.state == InvoiceState::Open {
+                    oldest_open =
+                        Some(oldest_open.map_or(add_index, |oldest| oldest.min(add_index)));
+                }
+            }
+            self.handle_invoice_update(update).await;
+        }
+
+        // An invoice still open can be accepted later, so the next sweep starts from it
+        let cursor = oldest_open.map_or(last_index, |add_index| add_index - 1);
+        if cursor != since {
+            store.set_ln_sync_cursor(&cursor_name, cursor).await?;
+        }
+        Ok(())
+    }
+
     async fn handle_invoice_update(&self, update: InvoiceUpdate) {
         if update.state != InvoiceState::Accepted {
             return;
@@ -98,6 +175,69 @@ impl InvoiceSubscriber {
             .await
         {
             error!("Failed to mark ticket {} as paid: {}", ticket.id, e);
+            // Let the polling watcher retry now rather than on its next tick
+            self.coordinator.watcher_kicks().kick_invoices();
+            return;
+        }
+        self.coordinator.watcher_kicks().kick_competitions();
+    }
+}
+
+#[cfg(all(test, any(feature = "e2e-testing", debug_assertions)))]
+mod tests {
+    use super::*;
+    use crate::domain::invoices::test_support::test_coordinator;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_invoice_accepted_while_disconnected_is_caught_up_on_reconnect() {
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.reserve_ticket(competition.id, 1).await;
+        let cancel_token = CancellationToken::new();
+        let subscriber = InvoiceSubscriber::new(
+            test.coordinator.clone(),
+            Arc::new(test.ln.clone()),
+            cancel_token.clone(),
+        );
+        let handle = tokio::spawn(async move { subscriber.subscribe().await });
+
+        while test.ln.invoice_subscriber_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        // The stream drops and the player pays before it comes back
+        test.ln.disconnect_subscribers();
+        test.ln.accept_invoice(&ticket.payment_hash).unwrap();
+
+        let mut paid_at = None;
+        for _ in 0..50 {
+            paid_at = test
+                .coordinator
+                .competition_store
+                .get_ticket(ticket.ticket_id)
+                .await
+                .unwrap()
+                .paid_at;
+            if paid_at.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(
+            paid_at.is_some(),
+            "catch-up sweep should mark the ticket paid"
+        );
+
+        // Nothing is left open, so the next sweep starts after the ticket's invoice
+        let cursor = test
+            .coordinator
+            .competition_store
+            .get_ln_sync_cursor(&invoice_cursor(PRIMARY_LN_BACKEND))
+            .await
+            .unwrap();
+        assert!(cursor > 0);
+
+        cancel_token.cancel();
+        handle.await.unwrap().unwrap();
     }
 }
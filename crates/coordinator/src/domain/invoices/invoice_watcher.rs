@@ -4,6 +4,7 @@ use bdk_wallet::bitcoin::{
     hashes::{sha256, Hash},
     PublicKey, Transaction,
 };
+use coordinator_core::Sats;
 use dlctix::{bitcoin::hex::DisplayHex, hashlock};
 use log::{debug, error, info, warn};
 use std::{str::FromStr, sync::Arc, time::Duration};
@@ -11,8 +12,10 @@ use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    domain::Coordinator,
+    config::SharedConfig,
+    domain::{jittered_interval, Coordinator},
     infra::{
+        bitcoin::{broadcast_transaction, BroadcastErrorKind},
         escrow::generate_escrow_tx,
         lightning::{InvoiceState, Ln},
     },
@@ -25,7 +28,7 @@ const RETRY_DELAY_MS: u64 = 1000;
 pub struct InvoiceWatcher {
     coordinator: Arc<Coordinator>,
     ln: Arc<dyn Ln>,
-    sync_interval: Duration,
+    settings: SharedConfig,
     cancel_token: CancellationToken,
 }
 
@@ -34,12 +37,12 @@ impl InvoiceWatcher {
         coordinator: Arc<Coordinator>,
         ln: Arc<dyn Ln>,
         cancel_token: CancellationToken,
-        sync_interval: Duration,
+        settings: SharedConfig,
     ) -> Self {
         Self {
             coordinator,
             ln,
-            sync_interval,
+            settings,
             cancel_token,
         }
     }
@@ -62,8 +65,17 @@ impl InvoiceWatcher {
                 }
             }
 
+            let interval = jittered_interval(
+                self.settings.invoice_watch_interval(),
+                self.settings.watcher_jitter_percent(),
+                &mut rand::rng(),
+            );
             tokio::select! {
-                _ = sleep(self.sync_interval) => continue,
+                _ = sleep(interval) => continue,
+                _ = self.coordinator.watcher_kicks().invoices_kicked() => {
+                    debug!("Invoice watcher kicked");
+                    continue;
+                }
                 _ = self.cancel_token.cancelled() => {
                     info!("Invoice watcher cancelled during sleep");
                     break;
@@ -84,7 +96,11 @@ impl InvoiceWatcher {
         debug!("Checking {} pending tickets", pending_tickets.len());
 
         for ticket in pending_tickets {
-            match self.ln.lookup_invoice(&ticket.hash).await {
+            match self
+                .ln
+                .lookup_invoice(&ticket.hash, ticket.ln_backend_id.as_deref())
+                .await
+            {
                 Ok(invoice) => {
                     debug!("Ticket {}: invoice state: {:?}", ticket.id, invoice.state);
 
@@ -119,6 +135,18 @@ impl InvoiceWatcher {
                     if invoice.state == InvoiceState::Accepted {
                         info!("Invoice accepted for ticket {}", ticket.id);
 
+                        // Booking an unreadable amount as 0 sats would skew the ledger and receipt
+                        let amount_sats: u64 = match invoice.value.parse() {
+                            Ok(amount_sats) => amount_sats,
+                            Err(e) => {
+                                warn!(
+                                    "Skipping ticket {}, its invoice amount {:?} isn't a number: {}",
+                                    ticket.id, invoice.value, e
+                                );
+                                continue;
+                            }
+                        };
+
                         debug!("Marking ticket as Paid {}: ", ticket.id);
 
                         match self
@@ -140,7 +168,11 @@ impl InvoiceWatcher {
                                                 ticket.id, ticket.competition_id, txid);
 
                                             // Proceed to settle the HODL invoice
-                                            self.settle_invoice_and_mark_ticket(&ticket).await;
+                                            self.settle_invoice_and_mark_ticket(
+                                                &ticket,
+                                                amount_sats,
+                                            )
+                                            .await;
                                         }
                                         Err(e) => {
                                             // All broadcast attempts failed, cancel the HODL invoice and reset ticket
@@ -184,23 +216,37 @@ impl InvoiceWatcher {
         Ok(())
     }
 
-    async fn settle_invoice_and_mark_ticket(&self, ticket: &crate::domain::competitions::Ticket) {
+    async fn settle_invoice_and_mark_ticket(
+        &self,
+        ticket: &crate::domain::competitions::Ticket,
+        amount_sats: u64,
+    ) {
         match self
             .ln
-            .settle_hold_invoice(ticket.encrypted_preimage.clone())
+            .settle_hold_invoice(
+                ticket.encrypted_preimage.clone(),
+                ticket.ln_backend_id.as_deref(),
+            )
             .await
         {
             Ok(_) => {
                 match self
                     .coordinator
                     .competition_store
-                    .mark_ticket_settled(ticket.id)
+                    .mark_ticket_settled(ticket.id, amount_sats)
                     .await
                 {
-                    Ok(_) => info!(
-                        "Ticket {} settled for competition {}",
-                        ticket.id, ticket.competition_id
-                    ),
+                    Ok(settled) => {
+                        info!(
+                            "Ticket {} settled for competition {}",
+                            ticket.id, ticket.competition_id
+                        );
+                        if settled {
+                            self.coordinator
+                                .issue_payment_receipt(ticket, Sats(amount_sats))
+                                .await;
+                        }
+                    }
                     Err(e) => error!("Failed to mark ticket {} as settled: {}", ticket.id, e),
                 }
             }
@@ -218,7 +264,11 @@ impl InvoiceWatcher {
         ticket: &crate::domain::competitions::Ticket,
     ) -> Result<(), anyhow::Error> {
         // First cancel the HODL invoice
-        match self.ln.cancel_hold_invoice(ticket.hash.clone()).await {
+        match self
+            .ln
+            .cancel_hold_invoice(ticket.hash.clone(), ticket.ln_backend_id.as_deref())
+            .await
+        {
             Ok(_) => {
                 info!(
                     "Successfully cancelled HODL invoice for ticket {}",
@@ -317,7 +367,7 @@ impl InvoiceWatcher {
         let preimage = hex::decode(&ticket.encrypted_preimage)
             .map_err(|e| anyhow!("Failed to decode preimage for ticket {}: {}", ticket.id, e))?;
         let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
-        let entry_fee = competition.event_submission.entry_fee as u64;
+        let entry_fee = competition.event_submission.entry_fee.to_sat();
 
         // Try regenerating escrow transaction multiple times with different UTXOs
         for attempt in 1..=MAX_ESCROW_REGENERATION_RETRIES {
@@ -413,7 +463,7 @@ impl InvoiceWatcher {
         let mut last_error = None;
 
         for attempt in 1..=MAX_BROADCAST_RETRIES {
-            match self.coordinator.bitcoin.broadcast(transaction).await {
+            match broadcast_transaction(self.coordinator.bitcoin.as_ref(), transaction).await {
                 Ok(_) => {
                     info!(
                         "Successfully broadcasted transaction for ticket {} (attempt {}/{})",
@@ -421,12 +471,19 @@ impl InvoiceWatcher {
                     );
                     return Ok(());
                 }
+                Err(e) if e.kind == BroadcastErrorKind::Rejected => {
+                    warn!(
+                        "Transaction for ticket {} rejected, not retrying: {}",
+                        ticket_id, e
+                    );
+                    return Err(e.into());
+                }
                 Err(e) => {
                     warn!(
                         "Failed to broadcast transaction for ticket {} (attempt {}/{}): {}",
                         ticket_id, attempt, MAX_BROADCAST_RETRIES, e
                     );
-                    last_error = Some(e);
+                    last_error = Some(anyhow::Error::from(e));
 
                     if attempt < MAX_BROADCAST_RETRIES {
                         // Exponential backoff: 1s, 2s, 4s
@@ -445,3 +502,49 @@ impl InvoiceWatcher {
         }))
     }
 }
+
+#[cfg(all(test, any(feature = "e2e-testing", debug_assertions)))]
+mod tests {
+    use super::*;
+    use crate::domain::invoices::test_support::test_coordinator;
+
+    #[tokio::test]
+    async fn test_marks_ticket_paid_when_invoice_accepted() {
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.reserve_ticket(competition.id, 1).await;
+        let watcher = InvoiceWatcher::new(
+            test.coordinator.clone(),
+            Arc::new(test.ln.clone()),
+            CancellationToken::new(),
+            test.settings.clone(),
+        );
+
+        // Still open, nothing to do
+        watcher.handle_pending_invoices().await.unwrap();
+        let stored = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        assert!(stored.paid_at.is_none());
+
+        test.ln.accept_invoice(&ticket.payment_hash).unwrap();
+        watcher.handle_pending_invoices().await.unwrap();
+
+        let stored = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        assert!(stored.paid_at.is_some());
+        // Without escrow the hold invoice stays accepted until the contract is funded
+        assert!(stored.settled_at.is_none());
+        assert_eq!(
+            test.ln.get_invoice_state(&ticket.payment_hash),
+            Some(InvoiceState::Accepted)
+        );
+    }
+}
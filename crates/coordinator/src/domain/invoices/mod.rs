@@ -2,12 +2,44 @@ mod invoice_subscriber;
 mod invoice_watcher;
 mod payment_subscriber;
 mod payout_watcher;
+mod reservation_sweeper;
+#[cfg(all(test, any(feature = "e2e-testing", debug_assertions)))]
+pub(crate) mod test_support;
 
 pub use invoice_subscriber::InvoiceSubscriber;
 pub use invoice_watcher::InvoiceWatcher;
 pub use payment_subscriber::PaymentSubscriber;
 pub use payout_watcher::PayoutWatcher;
+pub use reservation_sweeper::ReservationSweeper;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Wait before the lightning subscribers reconnect, doubled after every attempt that fails
+/// and reset once a connection is made
+struct ReconnectBackoff {
+    next: Duration,
+}
+
+impl ReconnectBackoff {
+    const INITIAL: Duration = Duration::from_secs(1);
+    const MAX: Duration = Duration::from_secs(60);
+
+    fn new() -> Self {
+        Self {
+            next: Self::INITIAL,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.next = Self::INITIAL;
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(Self::MAX);
+        delay
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaymentLookupResponse {
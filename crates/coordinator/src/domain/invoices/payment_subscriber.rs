@@ -3,11 +3,20 @@ use std::sync::Arc;
 use time::OffsetDateTime;
 use tokio_util::sync::CancellationToken;
 
+use super::ReconnectBackoff;
 use crate::{
-    domain::{competitions::PayoutError, Coordinator, PaymentStatus},
-    infra::lightning::{Ln, PaymentUpdate},
+    domain::{competitions::PayoutError, Coordinator, PaymentStatus, PayoutStatus},
+    infra::lightning::{Ln, PaymentUpdate, PRIMARY_LN_BACKEND},
 };
 
+/// Cursor of the payment catch-up sweep, an lnd payment_index. Each backend numbers its
+/// payments on its own so each has its own cursor, `payments:<backend_id>`.
+const PAYMENT_CURSOR: &str = "payments";
+
+fn payment_cursor(backend_id: &str) -> String {
+    format!("{}:{}", PAYMENT_CURSOR, backend_id)
+}
+
 pub struct PaymentSubscriber {
     coordinator: Arc<Coordinator>,
     ln: Arc<dyn Ln>,
@@ -30,17 +39,23 @@ impl PaymentSubscriber {
     pub async fn subscribe(&self) -> Result<(), anyhow::Error> {
         info!("Starting payment subscriber");
 
+        let mut backoff = ReconnectBackoff::new();
         loop {
             if self.cancel_token.is_cancelled() {
                 break;
             }
 
-            if let Err(e) = self.run_subscription().await {
-                error!("Payment subscription error: {}", e);
+            match self.run_subscription().await {
+                Ok(()) => backoff.reset(),
+                Err(e) => error!("Payment subscription error: {}", e),
             }
 
+            let delay = backoff.next_delay();
+            if !self.cancel_token.is_cancelled() {
+                info!("Payment subscription reconnecting in {:?}", delay);
+            }
             tokio::select! {
-                _ = tokio::time::sleep(tokio::time::Duration::from_secs(5)) => {}
+                _ = tokio::time::sleep(delay) => {}
                 _ = self.cancel_token.cancelled() => break,
             }
         }
@@ -52,6 +67,8 @@ impl PaymentSubscriber {
     async fn run_subscription(&self) -> Result<(), anyhow::Error> {
         let mut rx = self.ln.subscribe_payments().await?;
         info!("Payment subscription connected");
+        // Subscribed first, so a payment resolved during the sweep is still streamed
+        self.catch_up().await?;
 
         loop {
             tokio::select! {
@@ -68,6 +85,70 @@ impl PaymentSubscriber {
         Ok(())
     }
 
+    /// Process every payment sent since each backend's stored cursor, picking up results missed
+    /// while the subscription was down. A backend that can't be listed is retried on the next
+    /// reconnect without holding up the others.
+    async fn catch_up(&self) -> Result<(), anyhow::Error> {
+        let backend_ids = self.ln.backend_ids();
+        let mut listed = false;
+        for backend_id in &backend_ids {
+            match self.catch_up_backend(backend_id).await {
+                Ok(()) => listed = true,
+                Err(e) => warn!(
+                    "Payment catch-up of lightning backend {} failed: {}",
+                    backend_id, e
+                ),
+            }
+        }
+        if !listed {
+            return Err(anyhow::anyhow!("No lightning backend listed its payments"));
+        }
+        Ok(())
+    }
+
+    async fn catch_up_backend(&self, backend_id: &str) -> Result<(), anyhow::Error> {
+        let store = &self.coordinator.competition_store;
+        let cursor_name = payment_cursor(backend_id);
+        let since = match store.get_ln_sync_cursor(&cursor_name).await? {
+            // Stored before cursors were kept per backend, when there was only the primary
+            0 if backend_id == PRIMARY_LN_BACKEND => {
+                store.get_ln_sync_cursor(PAYMENT_CURSOR).await?
+            }
+            since => since,
+        };
+        let payments = self.ln.list_payments_since(backend_id, since).await?;
+        debug!(
+            "Payment catch-up found {} payment(s) backend {} sent after index {}",
+            payments.len(),
+            backend_id,
+            since
+        );
+
+        let mut last_index = since;
+        let mut oldest_unresolved: Option<u64> = None;
+        for update in payments {
+            if let Some(payment_index) = update.payment_index {
+                last_index = last_index.max(payment_index);
+                if !matches!(
+                    update.status,
+                    PaymentStatus::Succeeded | PaymentStatus::Failed
+                ) {
+                    oldest_unresolved = Some(
+                        oldest_unresolved.map_or(payment_index, |oldest| oldest.min(payment_index)),
+                    );
+                }
+            }
+            self.handle_payment_update(update).await;
+        }
+
+        // A payment still in flight resolves later, so the next sweep starts from it
+        let cursor = oldest_unresolved.map_or(last_index, |payment_index| payment_index - 1);
+        if cursor != since {
+            store.set_ln_sync_cursor(&cursor_name, cursor).await?;
+        }
+        Ok(())
+    }
+
     async fn handle_payment_update(&self, update: PaymentUpdate) {
         if !matches!(
             update.status,
@@ -93,13 +174,25 @@ impl PaymentSubscriber {
             }
         };
 
+        // A catch-up sweep lists payments the stream may already have delivered
+        if matches!(
+            (&payout.payout_status, &update.status),
+            (PayoutStatus::Succeeded, _) | (PayoutStatus::Failed, PaymentStatus::Failed)
+        ) {
+            debug!(
+                "Payout {} already recorded as {:?}",
+                payout.id, payout.payout_status
+            );
+            return;
+        }
+
         match update.status {
             PaymentStatus::Succeeded => {
                 info!("Payment succeeded for payout {} (subscription)", payout.id);
                 if let Err(e) = self
                     .coordinator
                     .competition_store
-                    .mark_payout_succeeded(payout.id, OffsetDateTime::now_utc())
+                    .mark_payout_succeeded(payout.id, OffsetDateTime::now_utc(), update.fee_sat)
                     .await
                 {
                     error!("Failed to mark payout {} as succeeded: {}", payout.id, e);
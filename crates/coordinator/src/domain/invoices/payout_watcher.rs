@@ -1,18 +1,19 @@
 use log::{debug, error, info, warn};
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 use time::OffsetDateTime;
 use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    domain::{competitions::PayoutError, Coordinator, PaymentStatus},
+    config::SharedConfig,
+    domain::{competitions::PayoutError, jittered_interval, Coordinator, PaymentStatus},
     infra::lightning::Ln,
 };
 
 pub struct PayoutWatcher {
     coordinator: Arc<Coordinator>,
     ln: Arc<dyn Ln>,
-    sync_interval: Duration,
+    settings: SharedConfig,
     cancel_token: CancellationToken,
 }
 
@@ -21,12 +22,12 @@ impl PayoutWatcher {
         coordinator: Arc<Coordinator>,
         ln: Arc<dyn Ln>,
         cancel_token: CancellationToken,
-        sync_interval: Duration,
+        settings: SharedConfig,
     ) -> Self {
         Self {
             coordinator,
             ln,
-            sync_interval,
+            settings,
             cancel_token,
         }
     }
@@ -49,8 +50,17 @@ impl PayoutWatcher {
                 }
             }
 
+            let interval = jittered_interval(
+                self.settings.payout_watch_interval(),
+                self.settings.watcher_jitter_percent(),
+                &mut rand::rng(),
+            );
             tokio::select! {
-                _ = sleep(self.sync_interval) => continue,
+                _ = sleep(interval) => continue,
+                _ = self.coordinator.watcher_kicks().payouts_kicked() => {
+                    debug!("Payout watcher kicked");
+                    continue;
+                }
                 _ = self.cancel_token.cancelled() => {
                     info!("Payout watcher cancelled during sleep");
                     break;
@@ -112,7 +122,11 @@ impl PayoutWatcher {
                             match self
                                 .coordinator
                                 .competition_store
-                                .mark_payout_succeeded(payout.id, OffsetDateTime::now_utc())
+                                .mark_payout_succeeded(
+                                    payout.id,
+                                    OffsetDateTime::now_utc(),
+                                    payment.fee_sat.parse().ok(),
+                                )
                                 .await
                             {
                                 Ok(_) => {
@@ -173,3 +187,78 @@ impl PayoutWatcher {
         Ok(())
     }
 }
+
+#[cfg(all(test, any(feature = "e2e-testing", debug_assertions)))]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::invoices::test_support::test_coordinator,
+        infra::lightning_mock::{mock_bolt11_invoice, MockPaymentBehavior, NO_ROUTE_FAILURE},
+    };
+    use coordinator_core::Sats;
+
+    #[tokio::test]
+    async fn test_in_flight_payout_is_marked_failed_once_payment_fails() {
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let entry_id = test.enter(competition.id, 1).await;
+
+        let payment_hash = "9".repeat(64);
+        let payout_invoice = mock_bolt11_invoice(&payment_hash, 1_500);
+        let payout_id = test
+            .coordinator
+            .competition_store
+            .store_payout_info_pending(
+                entry_id,
+                String::new(),
+                String::new(),
+                payout_invoice.clone(),
+                Sats(1_500),
+            )
+            .await
+            .unwrap();
+        test.ln
+            .script_payment(&payment_hash, MockPaymentBehavior::InFlight);
+        test.ln
+            .send_payment(payout_invoice, 1_500, 60, 10)
+            .await
+            .unwrap();
+
+        let watcher = PayoutWatcher::new(
+            test.coordinator.clone(),
+            Arc::new(test.ln.clone()),
+            CancellationToken::new(),
+            test.settings.clone(),
+        );
+
+        watcher.handle_pending_payouts().await.unwrap();
+        let payout = test
+            .coordinator
+            .competition_store
+            .get_payout(payout_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(payout.succeed_at.is_none());
+        assert!(payout.failed_at.is_none());
+
+        test.ln
+            .fail_payment(&payment_hash, NO_ROUTE_FAILURE)
+            .unwrap();
+        watcher.handle_pending_payouts().await.unwrap();
+
+        let payout = test
+            .coordinator
+            .competition_store
+            .get_payout(payout_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(payout.succeed_at.is_none());
+        assert!(payout.failed_at.is_some());
+        assert!(matches!(
+            payout.error,
+            Some(PayoutError::FailedToPayOut(ref reason)) if reason == NO_ROUTE_FAILURE
+        ));
+    }
+}
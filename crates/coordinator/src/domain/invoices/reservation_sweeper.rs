@@ -0,0 +1,262 @@
+use bdk_wallet::bitcoin::hashes::{sha256, Hash};
+use dlctix::{bitcoin::hex::DisplayHex, hashlock};
+use log::{debug, error, info, warn};
+use std::sync::Arc;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::{
+    config::SharedConfig,
+    domain::{jittered_interval, Coordinator, ReleasedReservation, Ticket},
+    infra::lightning::{InvoiceState, Ln},
+};
+
+/// Releases tickets whose reservation timed out unpaid. `get_status` already reports them as
+/// expired, but the row keeps its reservation and invoice until this sweep hands the ticket a
+/// fresh hash so the slot can be reserved again, then cancels the old invoice.
+pub struct ReservationSweeper {
+    coordinator: Arc<Coordinator>,
+    ln: Arc<dyn Ln>,
+    settings: SharedConfig,
+    cancel_token: CancellationToken,
+}
+
+impl ReservationSweeper {
+    pub fn new(
+        coordinator: Arc<Coordinator>,
+        ln: Arc<dyn Ln>,
+        cancel_token: CancellationToken,
+        settings: SharedConfig,
+    ) -> Self {
+        Self {
+            coordinator,
+            ln,
+            settings,
+            cancel_token,
+        }
+    }
+
+    pub async fn watch(&self) -> Result<(), anyhow::Error> {
+        info!("Starting reservation sweeper");
+
+        loop {
+            if self.cancel_token.is_cancelled() {
+                info!("Reservation sweeper received cancellation");
+                break;
+            }
+
+            match self.sweep_expired_reservations().await {
+                Ok(released) if released > 0 => {
+                    info!("Released {} expired ticket reservations", released);
+                }
+                Ok(_) => debug!("No expired ticket reservations to release"),
+                Err(e) => error!("Reservation sweep error: {}", e),
+            }
+
+            let interval = jittered_interval(
+                self.settings.reservation_sweep_interval(),
+                self.settings.watcher_jitter_percent(),
+                &mut rand::rng(),
+            );
+            tokio::select! {
+                _ = sleep(interval) => continue,
+                _ = self.cancel_token.cancelled() => {
+                    info!("Reservation sweeper cancelled during sleep");
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Release every expired reservation, returning how many tickets were freed
+    async fn sweep_expired_reservations(&self) -> Result<usize, anyhow::Error> {
+        let expired = self
+            .coordinator
+            .competition_store
+            .get_expired_reservations()
+            .await?;
+
+        let mut released = 0;
+        for ticket in expired {
+            if self.invoice_was_paid(&ticket).await {
+                continue;
+            }
+
+            // Released before its invoice is cancelled, so a player reserving the ticket again
+            // can't have their reservation's invoice cancelled by this sweep
+            let ticket_preimage = hashlock::preimage_random(&mut rand::rng());
+            let payment_hash = sha256::Hash::hash(&ticket_preimage).to_byte_array();
+            match self
+                .coordinator
+                .competition_store
+                .release_expired_reservation(
+                    ticket.id,
+                    &ticket_preimage.to_lower_hex_string(),
+                    &payment_hash.to_lower_hex_string(),
+                )
+                .await
+            {
+                Ok(Some(reservation)) => {
+                    debug!("Released expired reservation for ticket {}", ticket.id);
+                    released += 1;
+                    self.cancel_invoice(ticket.id, &reservation).await;
+                }
+                // Paid or reserved again since it was read
+                Ok(None) => debug!("Ticket {} no longer has an expired reservation", ticket.id),
+                Err(e) => error!(
+                    "Failed to release expired reservation for ticket {}: {}",
+                    ticket.id, e
+                ),
+            }
+        }
+
+        Ok(released)
+    }
+
+    /// Whether money arrived on the ticket's invoice after the reservation timed out, the
+    /// reservation is then left for an operator rather than refunded from a sweep
+    async fn invoice_was_paid(&self, ticket: &Ticket) -> bool {
+        if ticket.payment_request.is_none() {
+            return false;
+        }
+
+        match self
+            .ln
+            .lookup_invoice(&ticket.hash, ticket.ln_backend_id.as_deref())
+            .await
+        {
+            Ok(invoice)
+                if matches!(
+                    invoice.state,
+                    InvoiceState::Accepted | InvoiceState::Settled
+                ) =>
+            {
+                warn!(
+                    "Invoice for expired ticket {} is {:?}, not releasing the reservation",
+                    ticket.id, invoice.state
+                );
+                true
+            }
+            Ok(_) => false,
+            Err(e) => {
+                debug!(
+                    "Failed to lookup invoice for expired ticket {}: {}",
+                    ticket.id, e
+                );
+                false
+            }
+        }
+    }
+
+    /// Cancel the invoice the released reservation held so it can no longer be paid
+    async fn cancel_invoice(&self, ticket_id: Uuid, reservation: &ReleasedReservation) {
+        if reservation.payment_request.is_none() {
+            return;
+        }
+
+        match self
+            .ln
+            .cancel_hold_invoice(
+                reservation.hash.clone(),
+                reservation.ln_backend_id.as_deref(),
+            )
+            .await
+        {
+            Ok(_) => debug!("Cancelled invoice for expired ticket {}", ticket_id),
+            // It expires with the reservation anyway, and nothing settles a hash the ticket no
+            // longer holds, so a late payment is returned when the hold times out
+            Err(e) => warn!(
+                "Failed to cancel invoice {} for expired ticket {}: {}",
+                reservation.hash, ticket_id, e
+            ),
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "e2e-testing", debug_assertions)))]
+mod tests {
+    use super::*;
+    use crate::domain::invoices::test_support::test_coordinator;
+
+    #[tokio::test]
+    async fn test_expired_reservation_is_released_and_invoice_cancelled() {
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let expired = test.reserve_ticket(competition.id, 1).await;
+        let live = test.reserve_ticket(competition.id, 2).await;
+        let sweeper = ReservationSweeper::new(
+            test.coordinator.clone(),
+            Arc::new(test.ln.clone()),
+            CancellationToken::new(),
+            test.settings.clone(),
+        );
+
+        assert_eq!(sweeper.sweep_expired_reservations().await.unwrap(), 0);
+
+        test.expire_reservation(expired.ticket_id).await;
+        assert_eq!(sweeper.sweep_expired_reservations().await.unwrap(), 1);
+
+        assert_eq!(
+            test.ln.get_invoice_state(&expired.payment_hash),
+            Some(InvoiceState::Canceled)
+        );
+        let released = test
+            .coordinator
+            .competition_store
+            .get_ticket(expired.ticket_id)
+            .await
+            .unwrap();
+        assert!(released.can_be_reserved());
+        assert_ne!(released.hash, expired.payment_hash);
+
+        // The other player's reservation and invoice are untouched
+        assert_eq!(
+            test.ln.get_invoice_state(&live.payment_hash),
+            Some(InvoiceState::Open)
+        );
+        let still_reserved = test
+            .coordinator
+            .competition_store
+            .get_ticket(live.ticket_id)
+            .await
+            .unwrap();
+        assert!(still_reserved.is_reserved());
+
+        // The freed slot goes to the next player, with an invoice on the new hash
+        let next = test.reserve_ticket(competition.id, 3).await;
+        assert_eq!(next.ticket_id, expired.ticket_id);
+        assert_eq!(next.payment_hash, released.hash);
+    }
+
+    #[tokio::test]
+    async fn test_paid_invoice_keeps_its_reservation() {
+        let test = test_coordinator().await;
+        let competition = test.create_competition(2).await;
+        let ticket = test.reserve_ticket(competition.id, 1).await;
+        let sweeper = ReservationSweeper::new(
+            test.coordinator.clone(),
+            Arc::new(test.ln.clone()),
+            CancellationToken::new(),
+            test.settings.clone(),
+        );
+
+        test.ln.accept_invoice(&ticket.payment_hash).unwrap();
+        test.expire_reservation(ticket.ticket_id).await;
+        assert_eq!(sweeper.sweep_expired_reservations().await.unwrap(), 0);
+
+        assert_eq!(
+            test.ln.get_invoice_state(&ticket.payment_hash),
+            Some(InvoiceState::Accepted)
+        );
+        let stored = test
+            .coordinator
+            .competition_store
+            .get_ticket(ticket.ticket_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.hash, ticket.payment_hash);
+    }
+}
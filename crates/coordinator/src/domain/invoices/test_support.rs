@@ -0,0 +1,267 @@
+//! Coordinator wired to mock services on a throwaway database, so the invoice and payout
+//! watchers can be driven end to end against `MockLnClient`.
+use bdk_wallet::bitcoin::{
+    hashes::{sha256, Hash},
+    Network, PublicKey as BitcoinPublicKey,
+};
+use coordinator_core::Sats;
+use dlctix::secp::Scalar;
+use sqlx::{Connection, SqliteConnection};
+use std::{fs, path::Path, sync::Arc};
+use time::{Duration, OffsetDateTime};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        AddEntry, Competition, CompetitionStore, Coordinator, CreateEvent, TicketResponse,
+        WebhookNotifier,
+    },
+    infra::{
+        bitcoin::Bitcoin,
+        bitcoin_mock::MockBitcoinClient,
+        db::{DBConnection, DatabasePoolConfig, DatabaseType},
+        file_utils::create_folder,
+        lightning_mock::MockLnClient,
+        nostr_relays_mock::MockRelays,
+        oracle::{ValueOptions, WeatherChoices},
+        oracle_mock::{MockOracle, MockOracleHandle},
+    },
+    simulation::{KeymeldScript, ScriptedKeymeld, SimClock},
+    InvoiceSettlementMode, Settings, SharedConfig,
+};
+
+pub(crate) struct TestCoordinator {
+    pub coordinator: Arc<Coordinator>,
+    pub ln: MockLnClient,
+    pub oracle: MockOracleHandle,
+    pub relays: MockRelays,
+    pub settings: SharedConfig,
+    data_folder: String,
+}
+
+impl Drop for TestCoordinator {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(Path::new(&self.data_folder));
+    }
+}
+
+pub(crate) async fn test_coordinator() -> TestCoordinator {
+    test_coordinator_with(Arc::new(MockBitcoinClient::new(Network::Regtest)), |_| {}).await
+}
+
+/// Test coordinator on another chain mock, with its settings adjusted by `configure`
+pub(crate) async fn test_coordinator_with(
+    bitcoin: Arc<dyn Bitcoin>,
+    configure: impl FnOnce(&mut Settings),
+) -> TestCoordinator {
+    let data_folder = std::env::temp_dir()
+        .join(format!("coordinator-invoices-{}", Uuid::now_v7()))
+        .to_string_lossy()
+        .to_string();
+    create_folder(&data_folder);
+
+    let mut settings = Settings::default();
+    settings.db_settings.data_folder = data_folder.clone();
+    configure(&mut settings);
+    let pool_config: DatabasePoolConfig = settings.db_settings.clone().into();
+    let db = DBConnection::new(
+        &data_folder,
+        "competitions",
+        pool_config,
+        DatabaseType::Competitions,
+    )
+    .await
+    .expect("test db should open");
+
+    let oracle = MockOracle::new([7; 32]);
+    let oracle_handle = oracle.handle();
+    let ln = MockLnClient::new();
+    let relays = MockRelays::new();
+    let settings = SharedConfig::new(settings);
+    let (webhook_tx, _webhook_rx) = mpsc::unbounded_channel();
+    let coordinator = Coordinator::new(
+        Arc::new(oracle),
+        CompetitionStore::new(db),
+        bitcoin,
+        Arc::new(ln.clone()),
+        Arc::new(relays.clone()),
+        Arc::new(ScriptedKeymeld::new(
+            KeymeldScript::default(),
+            SimClock::default(),
+        )),
+        None,
+        144,
+        String::from("test"),
+        false,
+        InvoiceSettlementMode::Standard,
+        settings.clone(),
+        WebhookNotifier::new(webhook_tx),
+    )
+    .await
+    .expect("coordinator should build");
+
+    TestCoordinator {
+        coordinator: Arc::new(coordinator),
+        ln,
+        oracle: oracle_handle,
+        relays,
+        settings,
+        data_folder,
+    }
+}
+
+impl TestCoordinator {
+    pub async fn create_competition(&self, players: usize) -> Competition {
+        self.create_competition_with_relays(players, vec![]).await
+    }
+
+    /// Create an invite-only competition
+    pub async fn create_private_competition(&self, players: usize) -> Competition {
+        self.create_competition_from(players, vec![], true).await
+    }
+
+    /// Create a competition whose entries get backup DMs on `backup_relays`
+    pub async fn create_competition_with_relays(
+        &self,
+        players: usize,
+        backup_relays: Vec<String>,
+    ) -> Competition {
+        self.create_competition_from(players, backup_relays, false)
+            .await
+    }
+
+    async fn create_competition_from(
+        &self,
+        players: usize,
+        backup_relays: Vec<String>,
+        private: bool,
+    ) -> Competition {
+        let start = OffsetDateTime::now_utc() + Duration::hours(6);
+        self.coordinator
+            .create_competition(CreateEvent {
+                total_allowed_entries: players,
+                total_competition_pool: Sats(1_000 * players as u64),
+                relative_locktime_block_delta: Some(144),
+                backup_relays,
+                private,
+                ..CreateEvent::fixture(start)
+            })
+            .await
+            .expect("competition should be created")
+    }
+
+    /// Reserve a ticket for the player derived from `seed`, creating its hold invoice
+    pub async fn reserve_ticket(&self, competition_id: Uuid, seed: u8) -> TicketResponse {
+        self.coordinator
+            .request_ticket(
+                player_pubkey(seed),
+                competition_id,
+                player_bitcoin_pubkey(seed),
+            )
+            .await
+            .expect("ticket should be reserved")
+    }
+
+    /// Backdate a ticket's reservation past the 10 minute timeout
+    pub async fn expire_reservation(&self, ticket_id: Uuid) {
+        let mut conn =
+            SqliteConnection::connect(&format!("sqlite:{}/competitions.db", self.data_folder))
+                .await
+                .expect("test db should open");
+        sqlx::query(
+            "UPDATE tickets SET reserved_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-11 minutes')
+             WHERE id = ?",
+        )
+        .bind(ticket_id.to_string())
+        .execute(&mut conn)
+        .await
+        .expect("reservation should be backdated");
+    }
+
+    /// Backdate a ticket's invoice expiry so it looks lapsed while the reservation still holds
+    pub async fn expire_invoice(&self, ticket_id: Uuid) {
+        let mut conn =
+            SqliteConnection::connect(&format!("sqlite:{}/competitions.db", self.data_folder))
+                .await
+                .expect("test db should open");
+        sqlx::query(
+            "UPDATE tickets SET invoice_expires_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '-1 minutes')
+             WHERE id = ?",
+        )
+        .bind(ticket_id.to_string())
+        .execute(&mut conn)
+        .await
+        .expect("invoice expiry should be backdated");
+    }
+
+    /// Reserve a ticket for the player derived from `seed` and pay its invoice
+    pub async fn pay_for_ticket(&self, competition_id: Uuid, seed: u8) -> TicketResponse {
+        let ticket = self.reserve_ticket(competition_id, seed).await;
+        self.ln.accept_invoice(&ticket.payment_hash).unwrap();
+        self.coordinator
+            .handle_invoice_accepted(competition_id, &ticket.payment_hash)
+            .await
+            .expect("payment should be recorded");
+        ticket
+    }
+
+    /// The entry the player derived from `seed` submits for their paid ticket
+    pub fn entry(&self, competition_id: Uuid, ticket_id: Uuid, seed: u8) -> AddEntry {
+        AddEntry {
+            id: Uuid::now_v7(),
+            ticket_id,
+            ephemeral_pubkey: player_bitcoin_pubkey(seed).to_string(),
+            ephemeral_privatekey_encrypted: encrypt_to_player(seed, &hex::encode([seed; 32])),
+            payout_hash: sha256::Hash::hash(&[seed; 32]).to_string(),
+            payout_preimage_encrypted: encrypt_to_player(seed, &hex::encode([seed; 32])),
+            event_id: competition_id,
+            expected_observations: vec![WeatherChoices {
+                stations: "KORD".to_string(),
+                wind_speed: Some(ValueOptions::Over),
+                temp_high: Some(ValueOptions::Par),
+                temp_low: Some(ValueOptions::Under),
+            }],
+            encrypted_keymeld_private_key: None,
+            keymeld_auth_pubkey: None,
+        }
+    }
+
+    /// Pay for a ticket and add the player's entry, returning the entry id
+    pub async fn enter(&self, competition_id: Uuid, seed: u8) -> Uuid {
+        let ticket = self.pay_for_ticket(competition_id, seed).await;
+        self.coordinator
+            .add_entry(
+                player_pubkey(seed),
+                self.entry(competition_id, ticket.ticket_id, seed),
+            )
+            .await
+            .expect("entry should be added")
+            .id
+    }
+}
+
+/// The player's nostr pubkey, x-only hex like clients send
+pub(crate) fn player_pubkey(seed: u8) -> String {
+    hex::encode(&player_bitcoin_pubkey(seed).to_bytes()[1..])
+}
+
+/// NIP-44 encrypted by the player's nostr key to itself, the way clients store entry secrets
+pub(crate) fn encrypt_to_player(seed: u8, secret: &str) -> String {
+    let keys = nostr_sdk::Keys::new(
+        nostr_sdk::SecretKey::from_slice(&[seed; 32]).expect("seed should be a valid key"),
+    );
+    nostr_sdk::nips::nip44::encrypt(
+        keys.secret_key(),
+        &keys.public_key(),
+        secret,
+        nostr_sdk::nips::nip44::Version::V2,
+    )
+    .expect("encryption should succeed")
+}
+
+pub(crate) fn player_bitcoin_pubkey(seed: u8) -> BitcoinPublicKey {
+    let seckey = Scalar::from_slice(&[seed; 32]).expect("seed should be a valid scalar");
+    BitcoinPublicKey::from_slice(&seckey.base_point_mul().serialize())
+        .expect("seed should give a valid pubkey")
+}
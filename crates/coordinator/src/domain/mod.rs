@@ -2,14 +2,20 @@ mod competitions;
 mod invoices;
 pub mod scoring;
 pub mod users;
+mod watchers;
+mod webhooks;
 
 pub use competitions::*;
 pub use invoices::*;
 use thiserror::Error;
 use time::OffsetDateTime;
 pub use users::*;
+use uuid::Uuid;
+pub use watchers::*;
+pub use webhooks::*;
 
 use crate::infra::oracle::Error as OracleError;
+use coordinator_core::ValidationErrors;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -17,6 +23,8 @@ pub enum Error {
     NotFound(String),
     #[error("{0}")]
     BadRequest(String),
+    #[error("invalid request: {0}")]
+    Validation(#[from] ValidationErrors),
     #[error("problem querying db: {0}")]
     DbError(#[from] sqlx::Error),
     #[error("{0}")]
@@ -43,4 +51,21 @@ pub enum Error {
     TooLateToSign(OffsetDateTime, OffsetDateTime),
     #[error("Payout payment failed: {0}")]
     PaymentFailed(String),
+    #[error("Not invited to private competition {0}")]
+    NotInvited(Uuid),
+    #[error("invalid funding psbt: {0}")]
+    InvalidFundingPsbt(#[from] EntrantPsbtError),
+    #[error("coordinator key error: {0}")]
+    Keyring(#[from] KeyringError),
+    #[error("{0}, try again")]
+    Conflict(String),
+}
+
+impl From<CompetitionUpdateError> for Error {
+    fn from(e: CompetitionUpdateError) -> Self {
+        match e {
+            CompetitionUpdateError::Db(e) => Error::DbError(e),
+            e => Error::Conflict(e.to_string()),
+        }
+    }
 }
@@ -1,10 +1,23 @@
 //! Score calculation for competition entries
 //!
-//! Ported from frontend/public/leader_board.js
+//! Ported from frontend/public/leader_board.js. Everything here is a pure function of the
+//! entries' picks and the oracle's forecasts and observations, so anyone holding those can
+//! reproduce who won.
+//!
+//! Each pick says whether a station's observed value ends up under, at or over the forecast
+//! value for a metric. A correct `Under` or `Over` scores 10, a correct `Par` (observation equal
+//! to the forecast) scores 20, anything else or missing data scores 0. An entry's raw score is
+//! the sum over its picks, see [`score_entries`] for how entries are ranked on it.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
-use crate::infra::oracle::{ValueOptions, WeatherChoices};
+use crate::{
+    domain::{CreateEvent, TiePolicy},
+    infra::oracle::{ValueOptions, WeatherChoices},
+};
 
 /// Observation data from the oracle
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,10 +37,36 @@ pub struct Forecast {
     pub temp_low: Option<f64>,
 }
 
+/// An entry's score as the oracle ranks it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleEntryScore {
+    pub id: Uuid,
+    pub score: Option<i64>,
+}
+
+/// Everything scoring a competition needs from the oracle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObservationData {
+    pub forecasts: Vec<Forecast>,
+    pub observations: Vec<Observation>,
+    pub entry_scores: Vec<OracleEntryScore>,
+}
+
+/// Observation data fetched once the competition was attested. It's final unless the oracle
+/// corrects its attestation, so it's kept with the attestation it was fetched for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedObservationData {
+    pub competition_id: Uuid,
+    /// Hex of the attestation scalar the data was fetched after
+    pub attestation: String,
+    pub data: ObservationData,
+    pub fetched_at: OffsetDateTime,
+}
+
 /// Entry with score details
 #[derive(Debug, Clone)]
 pub struct ScoredEntry {
-    pub entry_id: String,
+    pub entry_id: Uuid,
     pub raw_score: i32,
     /// Final score with timestamp tiebreaker factored in
     pub final_score: i64,
@@ -92,95 +131,254 @@ pub fn calculate_option_score(
     }
 }
 
-/// Calculate scores for a list of entries
+/// Score one entry's picks against the forecasts and observations by station
+pub fn score_entry(
+    entry_id: Uuid,
+    expected_observations: &[WeatherChoices],
+    forecasts: &HashMap<String, Forecast>,
+    observations: &HashMap<String, Observation>,
+) -> ScoredEntry {
+    let mut raw_score = 0i32;
+    let mut details = Vec::new();
+
+    for choice in expected_observations {
+        let station_id = &choice.stations;
+        let forecast = forecasts.get(station_id);
+        let observation = observations.get(station_id);
+
+        let mut detail = |pick: &ValueOptions, forecast: Option<f64>, observation: Option<f64>| {
+            let score = calculate_option_score(forecast, observation, pick);
+            raw_score += score;
+            ScoreDetail {
+                pick: pick.clone(),
+                forecast,
+                observation,
+                score,
+            }
+        };
+
+        let wind_speed = choice.wind_speed.as_ref().map(|pick| {
+            detail(
+                pick,
+                forecast.and_then(|f| f.wind_speed),
+                observation.and_then(|o| o.wind_speed),
+            )
+        });
+        let temp_high = choice.temp_high.as_ref().map(|pick| {
+            detail(
+                pick,
+                forecast.and_then(|f| f.temp_high),
+                observation.and_then(|o| o.temp_high),
+            )
+        });
+        let temp_low = choice.temp_low.as_ref().map(|pick| {
+            detail(
+                pick,
+                forecast.and_then(|f| f.temp_low),
+                observation.and_then(|o| o.temp_low),
+            )
+        });
+
+        details.push(ScoredStation {
+            station_id: station_id.clone(),
+            wind_speed,
+            temp_high,
+            temp_low,
+        });
+    }
+
+    ScoredEntry {
+        entry_id,
+        raw_score,
+        final_score: calculate_final_score(&entry_id.to_string(), raw_score),
+        details,
+    }
+}
+
+/// An entry's place in a competition, see [`score_entries`]
+#[derive(Debug, Clone)]
+pub struct RankedEntry {
+    /// 1 based, entries tied under `TiePolicy::Split` share the place of the first of them
+    pub rank: usize,
+    pub entry_id: Uuid,
+    pub raw_score: i32,
+    /// Final score with the timestamp tiebreaker factored in, the way the oracle reports it
+    pub final_score: i64,
+    pub details: Vec<ScoredStation>,
+}
+
+/// Score and rank entries, highest raw score first. The same inputs always give the same
+/// ranking:
 ///
-/// Returns entries sorted by final_score (highest first)
-pub fn calculate_scores(
-    entries: &[(String, Vec<WeatherChoices>)],
-    forecasts: &std::collections::HashMap<String, Forecast>,
-    observations: &std::collections::HashMap<String, Observation>,
-) -> Vec<ScoredEntry> {
-    let mut scored_entries: Vec<ScoredEntry> = entries
+/// - Picks at stations that aren't one of the event's locations don't score. When the oracle
+///   reports a station more than once the last report counts.
+/// - Entries on the same raw score are ordered by entry id. Ids are UUIDv7, so that's by when
+///   they were made and then by the id's random bits, the same tiebreak the oracle applies.
+/// - Under `TiePolicy::Tiebreak` that order decides every place. Under `TiePolicy::Split`
+///   entries on the same raw score share a rank and the places they span.
+pub fn score_entries(
+    entries: &[(Uuid, Vec<WeatherChoices>)],
+    data: &ObservationData,
+    event: &CreateEvent,
+) -> Vec<RankedEntry> {
+    let forecasts: HashMap<String, Forecast> = data
+        .forecasts
+        .iter()
+        .filter(|f| event.locations.contains(&f.station_id))
+        .map(|f| (f.station_id.clone(), f.clone()))
+        .collect();
+    let observations: HashMap<String, Observation> = data
+        .observations
+        .iter()
+        .filter(|o| event.locations.contains(&o.station_id))
+        .map(|o| (o.station_id.clone(), o.clone()))
+        .collect();
+
+    let mut scored: Vec<ScoredEntry> = entries
         .iter()
         .map(|(entry_id, expected_observations)| {
-            let mut raw_score = 0i32;
-            let mut details = Vec::new();
-
-            for choice in expected_observations {
-                let station_id = &choice.stations;
-                let forecast = forecasts.get(station_id);
-                let observation = observations.get(station_id);
-
-                let wind_speed_detail = choice.wind_speed.as_ref().map(|pick| {
-                    let score = calculate_option_score(
-                        forecast.and_then(|f| f.wind_speed),
-                        observation.and_then(|o| o.wind_speed),
-                        pick,
-                    );
-                    raw_score += score;
-                    ScoreDetail {
-                        pick: pick.clone(),
-                        forecast: forecast.and_then(|f| f.wind_speed),
-                        observation: observation.and_then(|o| o.wind_speed),
-                        score,
-                    }
-                });
-
-                let temp_high_detail = choice.temp_high.as_ref().map(|pick| {
-                    let score = calculate_option_score(
-                        forecast.and_then(|f| f.temp_high),
-                        observation.and_then(|o| o.temp_high),
-                        pick,
-                    );
-                    raw_score += score;
-                    ScoreDetail {
-                        pick: pick.clone(),
-                        forecast: forecast.and_then(|f| f.temp_high),
-                        observation: observation.and_then(|o| o.temp_high),
-                        score,
-                    }
-                });
-
-                let temp_low_detail = choice.temp_low.as_ref().map(|pick| {
-                    let score = calculate_option_score(
-                        forecast.and_then(|f| f.temp_low),
-                        observation.and_then(|o| o.temp_low),
-                        pick,
-                    );
-                    raw_score += score;
-                    ScoreDetail {
-                        pick: pick.clone(),
-                        forecast: forecast.and_then(|f| f.temp_low),
-                        observation: observation.and_then(|o| o.temp_low),
-                        score,
-                    }
-                });
-
-                details.push(ScoredStation {
-                    station_id: station_id.clone(),
-                    wind_speed: wind_speed_detail,
-                    temp_high: temp_high_detail,
-                    temp_low: temp_low_detail,
-                });
+            score_entry(*entry_id, expected_observations, &forecasts, &observations)
+        })
+        .collect();
+    scored.sort_by(|a, b| {
+        b.raw_score
+            .cmp(&a.raw_score)
+            .then_with(|| a.entry_id.cmp(&b.entry_id))
+    });
+
+    let mut ranked: Vec<RankedEntry> = Vec::with_capacity(scored.len());
+    for (index, entry) in scored.into_iter().enumerate() {
+        let rank = match ranked.last() {
+            Some(previous)
+                if event.tie_policy == TiePolicy::Split
+                    && previous.raw_score == entry.raw_score =>
+            {
+                previous.rank
             }
+            _ => index + 1,
+        };
+        ranked.push(RankedEntry {
+            rank,
+            entry_id: entry.entry_id,
+            raw_score: entry.raw_score,
+            final_score: entry.final_score,
+            details: entry.details,
+        });
+    }
+    ranked
+}
 
-            // Calculate final score with timestamp tiebreaker
-            // Extract timestamp from UUID v7 (first 48 bits)
-            let final_score = calculate_final_score(entry_id, raw_score);
+/// Entry ids of the paid places in finishing order, one group per rank, until `places` are
+/// covered. Ids within a group are sorted. This is who the attested outcome should pay.
+pub fn winning_groups(ranked: &[RankedEntry], places: usize) -> Vec<Vec<Uuid>> {
+    let mut groups: Vec<Vec<Uuid>> = Vec::new();
+    let mut covered = 0;
+    for (index, entry) in ranked.iter().enumerate() {
+        let same_rank = index > 0 && ranked[index - 1].rank == entry.rank;
+        match groups.last_mut() {
+            Some(group) if same_rank => group.push(entry.entry_id),
+            _ if covered >= places => break,
+            _ => groups.push(vec![entry.entry_id]),
+        }
+        covered += 1;
+    }
+    for group in groups.iter_mut() {
+        group.sort();
+    }
+    groups
+}
 
-            ScoredEntry {
-                entry_id: entry_id.clone(),
-                raw_score,
-                final_score,
-                details,
-            }
-        })
-        .collect();
+/// How much the leaderboard scores can still change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardStatus {
+    /// Observation window hasn't started, every entry is at zero
+    NotStarted,
+    /// Scored from the observations the oracle has so far, may still change
+    Projected,
+    /// Oracle has attested, scores are final
+    Final,
+}
 
-    // Sort by final score (highest first)
-    scored_entries.sort_by(|a, b| b.final_score.cmp(&a.final_score));
+impl LeaderboardStatus {
+    pub fn for_competition(
+        is_attested: bool,
+        start_observation_date: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Self {
+        if is_attested {
+            LeaderboardStatus::Final
+        } else if now >= start_observation_date {
+            LeaderboardStatus::Projected
+        } else {
+            LeaderboardStatus::NotStarted
+        }
+    }
+}
+
+/// Ranked entry on the leaderboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub entry_id: Uuid,
+    pub username: Option<String>,
+    /// Points earned from picks, what players see
+    pub raw_score: i32,
+    /// Score used for ranking, includes the entry timestamp tiebreaker
+    pub final_score: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub competition_id: Uuid,
+    pub status: LeaderboardStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// Rank entries from [`score_entries`], preferring the oracle's score (it's what the payout
+    /// is based on) and falling back to the locally computed final score when the oracle hasn't
+    /// scored an entry
+    pub fn build(
+        competition_id: Uuid,
+        status: LeaderboardStatus,
+        ranked_entries: Vec<RankedEntry>,
+        oracle_scores: &HashMap<Uuid, i64>,
+        usernames: &HashMap<Uuid, String>,
+    ) -> Self {
+        let mut entries: Vec<LeaderboardEntry> = ranked_entries
+            .into_iter()
+            .map(|ranked| LeaderboardEntry {
+                rank: 0,
+                entry_id: ranked.entry_id,
+                username: usernames.get(&ranked.entry_id).cloned(),
+                raw_score: ranked.raw_score,
+                final_score: oracle_scores
+                    .get(&ranked.entry_id)
+                    .copied()
+                    .unwrap_or(ranked.final_score),
+            })
+            .collect();
+
+        entries.sort_by(|a, b| {
+            b.final_score
+                .cmp(&a.final_score)
+                .then_with(|| a.entry_id.cmp(&b.entry_id))
+        });
+
+        for (idx, entry) in entries.iter_mut().enumerate() {
+            entry.rank = idx + 1;
+        }
 
-    scored_entries
+        Self {
+            competition_id,
+            status,
+            generated_at: OffsetDateTime::now_utc(),
+            entries,
+        }
+    }
 }
 
 /// Calculate final score with timestamp tiebreaker
@@ -261,4 +459,218 @@ mod tests {
         );
         assert_eq!(calculate_option_score(None, None, &ValueOptions::Over), 0);
     }
+
+    fn ranked(entry_id: Uuid, raw_score: i32) -> RankedEntry {
+        RankedEntry {
+            rank: 0,
+            entry_id,
+            raw_score,
+            final_score: calculate_final_score(&entry_id.to_string(), raw_score),
+            details: vec![],
+        }
+    }
+
+    fn event(tie_policy: TiePolicy) -> CreateEvent {
+        let start = OffsetDateTime::now_utc();
+        CreateEvent {
+            locations: vec!["KORD".to_string(), "KSEA".to_string()],
+            number_of_values_per_entry: 6,
+            number_of_places_win: 2,
+            total_allowed_entries: 4,
+            total_competition_pool: coordinator_core::Sats(4000),
+            tie_policy,
+            ..CreateEvent::fixture(start)
+        }
+    }
+
+    /// KORD came in over the wind forecast, at the high temperature forecast and under the low
+    /// one. KSEA has a forecast but no observations yet, KJFK isn't one of the event's stations.
+    fn fixture_data() -> ObservationData {
+        let station = |id: &str, wind: f64, high: f64, low: f64| Forecast {
+            station_id: id.to_string(),
+            wind_speed: Some(wind),
+            temp_high: Some(high),
+            temp_low: Some(low),
+        };
+        ObservationData {
+            forecasts: vec![
+                station("KORD", 10.0, 80.0, 60.0),
+                station("KSEA", 5.0, 70.0, 50.0),
+                station("KJFK", 8.0, 75.0, 55.0),
+            ],
+            observations: vec![
+                Observation {
+                    station_id: "KORD".to_string(),
+                    wind_speed: Some(12.0),
+                    temp_high: Some(80.0),
+                    temp_low: Some(58.0),
+                },
+                Observation {
+                    station_id: "KJFK".to_string(),
+                    wind_speed: Some(9.0),
+                    temp_high: Some(76.0),
+                    temp_low: Some(56.0),
+                },
+            ],
+            entry_scores: vec![],
+        }
+    }
+
+    fn picks(
+        station: &str,
+        wind_speed: Option<ValueOptions>,
+        temp_high: Option<ValueOptions>,
+        temp_low: Option<ValueOptions>,
+    ) -> Vec<WeatherChoices> {
+        vec![WeatherChoices {
+            stations: station.to_string(),
+            wind_speed,
+            temp_high,
+            temp_low,
+        }]
+    }
+
+    /// Ids in the order they were made, as UUIDv7s from successive milliseconds
+    fn entry_ids(count: u64) -> Vec<Uuid> {
+        (0..count)
+            .map(|i| {
+                let ts = uuid::Timestamp::from_unix(uuid::NoContext, 1_700_000_000 + i, 0);
+                Uuid::new_v7(ts)
+            })
+            .collect()
+    }
+
+    fn fixture_entries(ids: &[Uuid]) -> Vec<(Uuid, Vec<WeatherChoices>)> {
+        use ValueOptions::{Over, Par, Under};
+        vec![
+            // 10 + 20 + 10
+            (ids[0], picks("KORD", Some(Over), Some(Par), Some(Under))),
+            // 10 + 0 + 10, ties with the next entry
+            (ids[1], picks("KORD", Some(Over), Some(Over), Some(Under))),
+            (ids[2], picks("KORD", Some(Over), Some(Under), Some(Under))),
+            // Nothing observed at KSEA
+            (ids[3], picks("KSEA", Some(Over), Some(Par), Some(Under))),
+        ]
+    }
+
+    fn ranks(ranked: &[RankedEntry]) -> Vec<(usize, Uuid, i32)> {
+        ranked
+            .iter()
+            .map(|entry| (entry.rank, entry.entry_id, entry.raw_score))
+            .collect()
+    }
+
+    #[test]
+    fn test_score_entries_breaks_ties_by_entry_id() {
+        let ids = entry_ids(4);
+        let mut entries = fixture_entries(&ids);
+        // Input order doesn't matter
+        entries.reverse();
+
+        let ranked = score_entries(&entries, &fixture_data(), &event(TiePolicy::Tiebreak));
+        assert_eq!(
+            ranks(&ranked),
+            vec![
+                (1, ids[0], 40),
+                (2, ids[1], 20),
+                (3, ids[2], 20),
+                (4, ids[3], 0),
+            ]
+        );
+        assert_eq!(winning_groups(&ranked, 2), vec![vec![ids[0]], vec![ids[1]]]);
+    }
+
+    #[test]
+    fn test_score_entries_shares_ranks_when_ties_split() {
+        let ids = entry_ids(4);
+        let ranked = score_entries(
+            &fixture_entries(&ids),
+            &fixture_data(),
+            &event(TiePolicy::Split),
+        );
+        assert_eq!(
+            ranks(&ranked),
+            vec![
+                (1, ids[0], 40),
+                (2, ids[1], 20),
+                (2, ids[2], 20),
+                (4, ids[3], 0),
+            ]
+        );
+        assert_eq!(
+            winning_groups(&ranked, 2),
+            vec![vec![ids[0]], vec![ids[1], ids[2]]]
+        );
+        // A tie across the last paid place pulls in the whole group
+        assert_eq!(
+            winning_groups(&ranked, 3),
+            vec![vec![ids[0]], vec![ids[1], ids[2]]]
+        );
+    }
+
+    #[test]
+    fn test_picks_outside_the_event_locations_do_not_score() {
+        let ids = entry_ids(2);
+        let entries = vec![
+            (ids[0], picks("KJFK", Some(ValueOptions::Over), None, None)),
+            (ids[1], picks("KORD", Some(ValueOptions::Over), None, None)),
+        ];
+        let ranked = score_entries(&entries, &fixture_data(), &event(TiePolicy::Tiebreak));
+        assert_eq!(ranks(&ranked), vec![(1, ids[1], 10), (2, ids[0], 0)]);
+        assert_eq!(
+            ranked[1].details[0].wind_speed.as_ref().unwrap().forecast,
+            None
+        );
+    }
+
+    #[test]
+    fn test_leaderboard_ranks_by_oracle_score_first() {
+        let first = Uuid::now_v7();
+        let second = Uuid::now_v7();
+        let third = Uuid::now_v7();
+        // Oracle has more observations for `first` than we scored locally
+        let oracle_scores = HashMap::from([
+            (first, calculate_final_score(&first.to_string(), 40)),
+            (second, calculate_final_score(&second.to_string(), 30)),
+        ]);
+        let usernames = HashMap::from([(second, "alice".to_string())]);
+
+        let leaderboard = Leaderboard::build(
+            Uuid::now_v7(),
+            LeaderboardStatus::Projected,
+            vec![ranked(third, 0), ranked(first, 10), ranked(second, 30)],
+            &oracle_scores,
+            &usernames,
+        );
+
+        let ranked: Vec<(usize, Uuid)> = leaderboard
+            .entries
+            .iter()
+            .map(|entry| (entry.rank, entry.entry_id))
+            .collect();
+        assert_eq!(ranked, vec![(1, first), (2, second), (3, third)]);
+        assert_eq!(leaderboard.entries[0].raw_score, 10);
+        assert_eq!(leaderboard.entries[1].username.as_deref(), Some("alice"));
+        assert_eq!(leaderboard.entries[2].username, None);
+    }
+
+    #[test]
+    fn test_leaderboard_status() {
+        let now = OffsetDateTime::now_utc();
+        let later = now + time::Duration::hours(1);
+        let earlier = now - time::Duration::hours(1);
+
+        assert_eq!(
+            LeaderboardStatus::for_competition(false, later, now),
+            LeaderboardStatus::NotStarted
+        );
+        assert_eq!(
+            LeaderboardStatus::for_competition(false, earlier, now),
+            LeaderboardStatus::Projected
+        );
+        assert_eq!(
+            LeaderboardStatus::for_competition(true, earlier, now),
+            LeaderboardStatus::Final
+        );
+    }
 }
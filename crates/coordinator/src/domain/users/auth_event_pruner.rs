@@ -0,0 +1,52 @@
+use log::{debug, error, info};
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+
+use super::UserInfo;
+
+/// Retention is counted in days, checking hourly keeps the log within an hour of it
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Deletes sign-in log rows past their retention. Kept off the request path so recording a
+/// sign-in is a single insert on the users DB's one writer.
+pub struct AuthEventPruner {
+    users_info: Arc<UserInfo>,
+    cancel_token: CancellationToken,
+}
+
+impl AuthEventPruner {
+    pub fn new(users_info: Arc<UserInfo>, cancel_token: CancellationToken) -> Self {
+        Self {
+            users_info,
+            cancel_token,
+        }
+    }
+
+    pub async fn watch(&self) -> Result<(), anyhow::Error> {
+        info!("Starting auth event pruner");
+
+        loop {
+            if self.cancel_token.is_cancelled() {
+                info!("Auth event pruner received cancellation");
+                break;
+            }
+
+            match self.users_info.prune_auth_events().await {
+                Ok(pruned) if pruned > 0 => info!("Pruned {} old sign-ins", pruned),
+                Ok(_) => debug!("No old sign-ins to prune"),
+                Err(e) => error!("Auth event pruning error: {}", e),
+            }
+
+            tokio::select! {
+                _ = sleep(PRUNE_INTERVAL) => continue,
+                _ = self.cancel_token.cancelled() => {
+                    info!("Auth event pruner cancelled during sleep");
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
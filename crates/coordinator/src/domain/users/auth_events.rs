@@ -0,0 +1,143 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::config::IpPrivacy;
+
+/// A request that authenticated with a NIP-98 header, before it is written to the sign-in log
+#[derive(Debug, Clone)]
+pub struct AuthAttempt {
+    pub nostr_pubkey: String,
+    /// Id of the NIP-98 event, a header replayed inside its freshness window has the same one
+    pub event_id: String,
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub method: String,
+    pub path: String,
+    /// When the server stops accepting the event
+    pub expires_at: OffsetDateTime,
+}
+
+/// One row of the sign-in log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthEvent {
+    pub id: Uuid,
+    pub nostr_pubkey: String,
+    pub event_id: String,
+    /// Truncated, hashed or left out per `api_settings.auth_event_ip_privacy`
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub method: String,
+    pub path: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl AuthEvent {
+    /// Whether the event would still be accepted if it was sent again
+    pub fn is_active(&self, now: OffsetDateTime) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+}
+
+/// A user's recent sign-ins, and the ones that could still be replayed until they are revoked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSessions {
+    pub active: Vec<AuthEvent>,
+    pub recent: Vec<AuthEvent>,
+}
+
+/// Sign-ins of one pubkey over a period, for spotting shared or abused accounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthActivity {
+    pub nostr_pubkey: String,
+    pub events: i64,
+    pub distinct_ips: i64,
+    pub distinct_user_agents: i64,
+    pub revoked: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub first_seen: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_seen: OffsetDateTime,
+}
+
+const IP_HASH_KEY_TAG: &[u8] = b"5day4cast/auth-event-ip";
+
+/// Key `IpPrivacy::Hash` hashes addresses under. It is derived from the coordinator's secret so
+/// it stays the same across restarts, and without it nobody can hash every IPv4 address to
+/// reverse the log.
+pub fn ip_hash_key(coordinator_secret: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(IP_HASH_KEY_TAG);
+    hasher.update(coordinator_secret);
+    hasher.finalize().into()
+}
+
+impl IpPrivacy {
+    /// The form of `ip` the sign-in log keeps, `hash_key` keys the hash of `IpPrivacy::Hash`
+    pub fn redact(&self, ip: IpAddr, hash_key: &[u8; 32]) -> Option<String> {
+        match self {
+            IpPrivacy::Truncate => Some(match ip {
+                IpAddr::V4(ip) => {
+                    let [a, b, c, _] = ip.octets();
+                    format!("{}.{}.{}.0/24", a, b, c)
+                }
+                IpAddr::V6(ip) => {
+                    let segments = ip.segments();
+                    format!("{:x}:{:x}:{:x}::/48", segments[0], segments[1], segments[2])
+                }
+            }),
+            IpPrivacy::Hash => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(hash_key)
+                    .expect("HMAC accepts keys of any size");
+                mac.update(ip.to_string().as_bytes());
+                Some(hex::encode(mac.finalize().into_bytes()))
+            }
+            IpPrivacy::Omit => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ips_are_stored_per_the_privacy_setting() {
+        let key = ip_hash_key(&[7u8; 32]);
+        let v4: IpAddr = "203.0.113.77".parse().unwrap();
+        let v6: IpAddr = "2001:db8:85a3:8d3:1319:8a2e:370:7348".parse().unwrap();
+
+        assert_eq!(
+            IpPrivacy::Truncate.redact(v4, &key).as_deref(),
+            Some("203.0.113.0/24")
+        );
+        assert_eq!(
+            IpPrivacy::Truncate.redact(v6, &key).as_deref(),
+            Some("2001:db8:85a3::/48")
+        );
+
+        let hashed = IpPrivacy::Hash.redact(v4, &key).unwrap();
+        assert_eq!(hashed.len(), 64);
+        assert!(!hashed.contains("203.0.113"));
+        assert_eq!(IpPrivacy::Hash.redact(v4, &key), Some(hashed.clone()));
+        // Without the coordinator's key the hash can't be recomputed from the address
+        assert_ne!(
+            hashed,
+            hex::encode(Sha256::digest(v4.to_string().as_bytes()))
+        );
+        assert_ne!(
+            IpPrivacy::Hash.redact(v4, &ip_hash_key(&[8u8; 32])),
+            Some(hashed)
+        );
+
+        assert_eq!(IpPrivacy::Omit.redact(v4, &key), None);
+    }
+}
@@ -1,19 +1,85 @@
 use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
 
-use super::{User, UserStore};
-use crate::{api::routes::RegisterPayload, domain::Error};
+use super::{AuthActivity, AuthAttempt, AuthEvent, User, UserSessions, UserStore};
+use crate::{api::routes::RegisterPayload, config::IpPrivacy, domain::Error};
+
+/// Sign-ins listed at `/api/v1/users/me/sessions`
+const RECENT_AUTH_EVENTS: i64 = 50;
 
 pub struct UserInfo {
     user_store: Arc<UserStore>,
+    ip_privacy: IpPrivacy,
+    ip_hash_key: [u8; 32],
+    auth_event_retention: Duration,
 }
 
 impl UserInfo {
-    pub fn new(user_store: UserStore) -> Self {
+    pub fn new(
+        user_store: UserStore,
+        ip_privacy: IpPrivacy,
+        ip_hash_key: [u8; 32],
+        auth_event_retention: Duration,
+    ) -> Self {
         Self {
             user_store: Arc::new(user_store),
+            ip_privacy,
+            ip_hash_key,
+            auth_event_retention,
         }
     }
 
+    /// Adds a sign-in to the log, returning `false` when its owner revoked the event
+    pub async fn record_authentication(&self, attempt: AuthAttempt) -> Result<bool, Error> {
+        let now = OffsetDateTime::now_utc();
+        let event = AuthEvent {
+            id: Uuid::now_v7(),
+            nostr_pubkey: attempt.nostr_pubkey,
+            event_id: attempt.event_id,
+            ip: attempt
+                .ip
+                .and_then(|ip| self.ip_privacy.redact(ip, &self.ip_hash_key)),
+            user_agent: attempt.user_agent,
+            method: attempt.method,
+            path: attempt.path,
+            expires_at: attempt.expires_at,
+            revoked_at: None,
+            created_at: now,
+        };
+        self.user_store.record_auth_event(event).await
+    }
+
+    /// Deletes sign-ins older than `api_settings.auth_event_retention_days`
+    pub async fn prune_auth_events(&self) -> Result<u64, Error> {
+        self.user_store
+            .prune_auth_events(OffsetDateTime::now_utc() - self.auth_event_retention)
+            .await
+    }
+
+    pub async fn get_sessions(&self, nostr_pubkey: &str) -> Result<UserSessions, Error> {
+        let active = self
+            .user_store
+            .get_active_auth_events(nostr_pubkey, OffsetDateTime::now_utc())
+            .await?;
+        let recent = self
+            .user_store
+            .get_auth_events(nostr_pubkey, RECENT_AUTH_EVENTS)
+            .await?;
+        Ok(UserSessions { active, recent })
+    }
+
+    pub async fn revoke_session(&self, nostr_pubkey: &str, id: Uuid) -> Result<(), Error> {
+        self.user_store.revoke_auth_event(nostr_pubkey, id).await
+    }
+
+    pub async fn get_auth_activity(
+        &self,
+        since: OffsetDateTime,
+    ) -> Result<Vec<AuthActivity>, Error> {
+        self.user_store.get_auth_activity(since).await
+    }
+
     pub async fn register(&self, pubkey: String, payload: RegisterPayload) -> Result<User, Error> {
         self.user_store.register_user(pubkey, payload).await
     }
@@ -1,7 +1,11 @@
+mod auth_event_pruner;
+mod auth_events;
 mod info;
 mod password;
 mod store;
 
+pub use auth_event_pruner::*;
+pub use auth_events::*;
 pub use info::*;
 pub use password::*;
 pub use store::*;
@@ -1,11 +1,16 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{sqlite::SqliteRow, FromRow, Row};
 use time::OffsetDateTime;
+use uuid::Uuid;
 
+use super::{AuthActivity, AuthEvent};
 use crate::{
     api::routes::RegisterPayload,
     domain::Error,
-    infra::db::{parse_required_datetime, DBConnection},
+    infra::{
+        db::DBConnection,
+        db_timestamps::{format_timestamp, parse_optional_datetime, parse_required_datetime},
+    },
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +27,32 @@ pub struct User {
     pub updated_at: OffsetDateTime,
 }
 
+fn now_timestamp() -> Result<String, Error> {
+    format_timestamp(OffsetDateTime::now_utc())
+        .map_err(|e| Error::DbError(sqlx::Error::Encode(Box::new(e))))
+}
+
+impl FromRow<'_, SqliteRow> for AuthEvent {
+    fn from_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
+        let id: String = row.get("id");
+        Ok(AuthEvent {
+            id: Uuid::parse_str(&id).map_err(|e| sqlx::Error::ColumnDecode {
+                index: "id".to_string(),
+                source: Box::new(e),
+            })?,
+            nostr_pubkey: row.get("nostr_pubkey"),
+            event_id: row.get("event_id"),
+            ip: row.get("ip"),
+            user_agent: row.get("user_agent"),
+            method: row.get("method"),
+            path: row.get("path"),
+            expires_at: parse_required_datetime(row, "expires_at")?,
+            revoked_at: parse_optional_datetime(row, "revoked_at")?,
+            created_at: parse_required_datetime(row, "created_at")?,
+        })
+    }
+}
+
 impl FromRow<'_, SqliteRow> for User {
     fn from_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
         Ok(User {
@@ -56,7 +87,7 @@ impl UserStore {
         nostr_pubkey: String,
         user: RegisterPayload,
     ) -> Result<User, Error> {
-        let now = OffsetDateTime::now_utc();
+        let now = now_timestamp()?;
         let encrypted_key = user.encrypted_bitcoin_private_key.clone();
         let network = user.network.clone();
 
@@ -76,7 +107,7 @@ impl UserStore {
                 .bind(nostr_pubkey)
                 .bind(encrypted_key)
                 .bind(network)
-                .bind(now)
+                .bind(now.clone())
                 .bind(now)
                 .fetch_one(&pool)
                 .await
@@ -116,7 +147,7 @@ impl UserStore {
         nostr_pubkey: &str,
         user: RegisterPayload,
     ) -> Result<User, Error> {
-        let now = OffsetDateTime::now_utc();
+        let now = now_timestamp()?;
         let nostr_pubkey_owned = nostr_pubkey.to_string();
         let encrypted_key = user.encrypted_bitcoin_private_key.clone();
         let network = user.network.clone();
@@ -289,7 +320,7 @@ impl UserStore {
         encrypted_bitcoin_private_key: String,
         network: String,
     ) -> Result<User, Error> {
-        let now = OffsetDateTime::now_utc();
+        let now = now_timestamp()?;
 
         let user = self
             .db_connection
@@ -313,7 +344,7 @@ impl UserStore {
                 .bind(username)
                 .bind(password_hash)
                 .bind(encrypted_nsec)
-                .bind(now)
+                .bind(now.clone())
                 .bind(now)
                 .fetch_one(&pool)
                 .await
@@ -364,7 +395,7 @@ impl UserStore {
         new_password_hash: String,
         new_encrypted_nsec: String,
     ) -> Result<(), Error> {
-        let now = OffsetDateTime::now_utc();
+        let now = now_timestamp()?;
         let nostr_pubkey_owned = nostr_pubkey.to_string();
 
         let rows_affected = self
@@ -412,7 +443,7 @@ impl UserStore {
     }
 
     pub async fn update_username(&self, nostr_pubkey: &str, username: String) -> Result<(), Error> {
-        let now = OffsetDateTime::now_utc();
+        let now = now_timestamp()?;
         let nostr_pubkey_owned = nostr_pubkey.to_string();
 
         let rows_affected = self
@@ -446,6 +477,198 @@ impl UserStore {
 
         Ok(())
     }
+
+    /// Writes a sign-in to the log. Returns `false` when the event was revoked by its owner, the
+    /// request must then be turned away.
+    pub async fn record_auth_event(&self, event: AuthEvent) -> Result<bool, Error> {
+        let id = event.id.to_string();
+        let expires_at = format_timestamp(event.expires_at)
+            .map_err(|e| Error::DbError(sqlx::Error::Encode(Box::new(e))))?;
+        let created_at = format_timestamp(event.created_at)
+            .map_err(|e| Error::DbError(sqlx::Error::Encode(Box::new(e))))?;
+
+        let revoked = self
+            .db_connection
+            .execute_write(move |pool| async move {
+                let mut tx = pool.begin().await?;
+                // A header replayed inside its freshness window keeps its first row
+                sqlx::query(
+                    "INSERT INTO auth_events (
+                        id, nostr_pubkey, event_id, ip, user_agent, method, path, expires_at,
+                        created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT (event_id) DO NOTHING",
+                )
+                .bind(id)
+                .bind(&event.nostr_pubkey)
+                .bind(&event.event_id)
+                .bind(event.ip)
+                .bind(event.user_agent)
+                .bind(event.method)
+                .bind(event.path)
+                .bind(expires_at)
+                .bind(created_at)
+                .execute(&mut *tx)
+                .await?;
+                let revoked: bool = sqlx::query_scalar(
+                    "SELECT revoked_at IS NOT NULL FROM auth_events
+                     WHERE event_id = ? AND nostr_pubkey = ?",
+                )
+                .bind(&event.event_id)
+                .bind(&event.nostr_pubkey)
+                .fetch_optional(&mut *tx)
+                .await?
+                .unwrap_or(false);
+                tx.commit().await?;
+                Ok(revoked)
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => Error::DbError(e),
+                e => Error::BadRequest(e.to_string()),
+            })?;
+
+        Ok(!revoked)
+    }
+
+    /// Deletes sign-ins older than `before`, returning how many were removed
+    pub async fn prune_auth_events(&self, before: OffsetDateTime) -> Result<u64, Error> {
+        let before = format_timestamp(before)
+            .map_err(|e| Error::DbError(sqlx::Error::Encode(Box::new(e))))?;
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query("DELETE FROM auth_events WHERE created_at < ?")
+                    .bind(before)
+                    .execute(&pool)
+                    .await?;
+                Ok(result.rows_affected())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => Error::DbError(e),
+                e => Error::BadRequest(e.to_string()),
+            })
+    }
+
+    /// The pubkey's most recent sign-ins, newest first
+    pub async fn get_auth_events(
+        &self,
+        nostr_pubkey: &str,
+        limit: i64,
+    ) -> Result<Vec<AuthEvent>, Error> {
+        let events = sqlx::query_as::<_, AuthEvent>(
+            "SELECT id, nostr_pubkey, event_id, ip, user_agent, method, path, expires_at,
+                revoked_at, created_at
+            FROM auth_events
+            WHERE nostr_pubkey = ?
+            ORDER BY created_at DESC
+            LIMIT ?",
+        )
+        .bind(nostr_pubkey)
+        .bind(limit)
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        Ok(events)
+    }
+
+    /// The pubkey's sign-ins whose event would still be accepted, newest first
+    pub async fn get_active_auth_events(
+        &self,
+        nostr_pubkey: &str,
+        now: OffsetDateTime,
+    ) -> Result<Vec<AuthEvent>, Error> {
+        let now =
+            format_timestamp(now).map_err(|e| Error::DbError(sqlx::Error::Encode(Box::new(e))))?;
+        let events = sqlx::query_as::<_, AuthEvent>(
+            "SELECT id, nostr_pubkey, event_id, ip, user_agent, method, path, expires_at,
+                revoked_at, created_at
+            FROM auth_events
+            WHERE nostr_pubkey = ? AND revoked_at IS NULL AND expires_at > ?
+            ORDER BY created_at DESC",
+        )
+        .bind(nostr_pubkey)
+        .bind(now)
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Stops the sign-in's event from being accepted again, only its owner can revoke it
+    pub async fn revoke_auth_event(&self, nostr_pubkey: &str, id: Uuid) -> Result<(), Error> {
+        let now = now_timestamp()?;
+        let nostr_pubkey_owned = nostr_pubkey.to_string();
+
+        let rows_affected = self
+            .db_connection
+            .execute_write(move |pool| async move {
+                let result = sqlx::query(
+                    "UPDATE auth_events
+                     SET revoked_at = COALESCE(revoked_at, ?)
+                     WHERE id = ? AND nostr_pubkey = ?",
+                )
+                .bind(now)
+                .bind(id.to_string())
+                .bind(nostr_pubkey_owned)
+                .execute(&pool)
+                .await?;
+                Ok(result.rows_affected())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => Error::DbError(e),
+                e => Error::BadRequest(e.to_string()),
+            })?;
+
+        if rows_affected == 0 {
+            return Err(Error::NotFound(format!("Sign-in {} not found", id)));
+        }
+
+        Ok(())
+    }
+
+    /// Sign-ins since `since` grouped by pubkey, busiest first
+    pub async fn get_auth_activity(
+        &self,
+        since: OffsetDateTime,
+    ) -> Result<Vec<AuthActivity>, Error> {
+        let since = format_timestamp(since)
+            .map_err(|e| Error::DbError(sqlx::Error::Encode(Box::new(e))))?;
+        let rows = sqlx::query(
+            "SELECT
+                nostr_pubkey,
+                COUNT(*) AS events,
+                COUNT(DISTINCT ip) AS distinct_ips,
+                COUNT(DISTINCT user_agent) AS distinct_user_agents,
+                COUNT(revoked_at) AS revoked,
+                MIN(created_at) AS first_seen,
+                MAX(created_at) AS last_seen
+            FROM auth_events
+            WHERE created_at >= ?
+            GROUP BY nostr_pubkey
+            ORDER BY events DESC, nostr_pubkey",
+        )
+        .bind(since)
+        .fetch_all(self.db_connection.read())
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(AuthActivity {
+                    nostr_pubkey: row.get("nostr_pubkey"),
+                    events: row.get("events"),
+                    distinct_ips: row.get("distinct_ips"),
+                    distinct_user_agents: row.get("distinct_user_agents"),
+                    revoked: row.get("revoked"),
+                    first_seen: parse_required_datetime(row, "first_seen")?,
+                    last_seen: parse_required_datetime(row, "last_seen")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .map_err(Error::DbError)
+    }
 }
 
 #[cfg(test)]
@@ -637,6 +860,117 @@ mod tests {
         assert!(matches!(result, Err(Error::NotFound(_))));
     }
 
+    fn auth_event(nostr_pubkey: &str, event_id: &str) -> AuthEvent {
+        let now = OffsetDateTime::now_utc();
+        AuthEvent {
+            id: Uuid::now_v7(),
+            nostr_pubkey: nostr_pubkey.to_string(),
+            event_id: event_id.to_string(),
+            ip: Some("203.0.113.0/24".to_string()),
+            user_agent: Some("Mozilla/5.0".to_string()),
+            method: "GET".to_string(),
+            path: "/api/v1/entries".to_string(),
+            expires_at: now + time::Duration::seconds(60),
+            revoked_at: None,
+            created_at: now,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations/users")]
+    async fn test_auth_events_are_listed_for_their_pubkey_only(pool: SqlitePool) {
+        let store = create_store(pool);
+        let since = OffsetDateTime::now_utc() - time::Duration::days(30);
+
+        let alice = auth_event("alice", "event_1");
+        assert!(store.record_auth_event(alice.clone()).await.unwrap());
+        // A replay inside the freshness window keeps the first row
+        let mut replay = auth_event("alice", "event_1");
+        replay.ip = Some("198.51.100.0/24".to_string());
+        assert!(store.record_auth_event(replay).await.unwrap());
+        assert!(store
+            .record_auth_event(auth_event("bob", "event_2"))
+            .await
+            .unwrap());
+
+        let events = store.get_auth_events("alice", 10).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, alice.id);
+        assert_eq!(events[0].ip.as_deref(), Some("203.0.113.0/24"));
+        assert_eq!(events[0].user_agent.as_deref(), Some("Mozilla/5.0"));
+
+        let active = store
+            .get_active_auth_events("alice", OffsetDateTime::now_utc())
+            .await
+            .unwrap();
+        assert_eq!(active.len(), 1);
+        assert!(store
+            .get_active_auth_events("alice", alice.expires_at)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let activity = store.get_auth_activity(since).await.unwrap();
+        assert_eq!(activity.len(), 2);
+        assert!(activity.iter().all(|pubkey| pubkey.events == 1));
+    }
+
+    #[sqlx::test(migrations = "./migrations/users")]
+    async fn test_revoked_auth_event_is_refused_when_replayed(pool: SqlitePool) {
+        let store = create_store(pool);
+        let since = OffsetDateTime::now_utc() - time::Duration::days(30);
+
+        let event = auth_event("alice", "event_1");
+        store.record_auth_event(event.clone()).await.unwrap();
+
+        // Only the owner can revoke it
+        let result = store.revoke_auth_event("bob", event.id).await;
+        assert!(matches!(result, Err(Error::NotFound(_))));
+        store.revoke_auth_event("alice", event.id).await.unwrap();
+
+        assert!(!store
+            .record_auth_event(auth_event("alice", "event_1"))
+            .await
+            .unwrap());
+        // The user's next request signs a new event
+        assert!(store
+            .record_auth_event(auth_event("alice", "event_2"))
+            .await
+            .unwrap());
+
+        let active = store
+            .get_active_auth_events("alice", OffsetDateTime::now_utc())
+            .await
+            .unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].event_id, "event_2");
+        let activity = store.get_auth_activity(since).await.unwrap();
+        assert_eq!(activity[0].revoked, 1);
+    }
+
+    #[sqlx::test(migrations = "./migrations/users")]
+    async fn test_old_auth_events_are_pruned(pool: SqlitePool) {
+        let store = create_store(pool);
+
+        let mut old = auth_event("alice", "event_1");
+        old.created_at -= time::Duration::days(31);
+        store.record_auth_event(old).await.unwrap();
+        store
+            .record_auth_event(auth_event("alice", "event_2"))
+            .await
+            .unwrap();
+        // Recording doesn't prune, that's left to the periodic task
+        assert_eq!(store.get_auth_events("alice", 10).await.unwrap().len(), 2);
+
+        let pruned = store
+            .prune_auth_events(OffsetDateTime::now_utc() - time::Duration::days(30))
+            .await
+            .unwrap();
+        assert_eq!(pruned, 1);
+        let events = store.get_auth_events("alice", 10).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_id, "event_2");
+    }
+
     #[sqlx::test(migrations = "./migrations/users")]
     async fn test_ping(pool: SqlitePool) {
         let store = create_store(pool);
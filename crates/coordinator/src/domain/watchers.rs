@@ -0,0 +1,254 @@
+//! Timing shared by the polling watchers: jittered intervals so their ticks don't line up, and
+//! kicks so a mutation can wake a watcher without waiting for its next tick. Also tracks the
+//! competitions being processed so shutdown can say what it is waiting on, and when each
+//! competition last asked the oracle for its attestation.
+use rand::Rng;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use time::OffsetDateTime;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+/// Wake-up handles for the polling watchers. A kick sent while the watcher is busy is kept and
+/// ends its next sleep right away, repeated kicks collapse into a single extra run.
+#[derive(Clone, Default)]
+pub struct WatcherKicks {
+    competitions: Arc<Notify>,
+    invoices: Arc<Notify>,
+    payouts: Arc<Notify>,
+}
+
+impl WatcherKicks {
+    pub fn kick_competitions(&self) {
+        self.competitions.notify_one();
+    }
+
+    pub fn kick_invoices(&self) {
+        self.invoices.notify_one();
+    }
+
+    pub fn kick_payouts(&self) {
+        self.payouts.notify_one();
+    }
+
+    pub async fn competitions_kicked(&self) {
+        self.competitions.notified().await
+    }
+
+    pub async fn invoices_kicked(&self) {
+        self.invoices.notified().await
+    }
+
+    pub async fn payouts_kicked(&self) {
+        self.payouts.notified().await
+    }
+}
+
+/// Competitions the competition watcher is currently moving through their states, with the
+/// state each was in when picked up
+#[derive(Clone, Default)]
+pub struct InFlightCompetitions {
+    competitions: Arc<Mutex<BTreeMap<Uuid, String>>>,
+}
+
+impl InFlightCompetitions {
+    /// Mark `competition_id` in flight until the returned guard is dropped
+    pub fn start(&self, competition_id: Uuid, state: impl Into<String>) -> InFlightGuard {
+        self.lock().insert(competition_id, state.into());
+        InFlightGuard {
+            competitions: self.clone(),
+            competition_id,
+        }
+    }
+
+    pub fn contains(&self, competition_id: Uuid) -> bool {
+        self.lock().contains_key(&competition_id)
+    }
+
+    pub fn snapshot(&self) -> Vec<(Uuid, String)> {
+        self.lock()
+            .iter()
+            .map(|(id, state)| (*id, state.clone()))
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<Uuid, String>> {
+        self.competitions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Clears its competition from [`InFlightCompetitions`] when processing ends, however it ends
+pub struct InFlightGuard {
+    competitions: InFlightCompetitions,
+    competition_id: Uuid,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.competitions.lock().remove(&self.competition_id);
+    }
+}
+
+/// Whether the oracle may have attested a competition signing at `signing_date`, it isn't asked
+/// earlier than `pre_window` ahead of that
+pub fn attestation_window_open(
+    signing_date: OffsetDateTime,
+    now: OffsetDateTime,
+    pre_window: Duration,
+) -> bool {
+    now + pre_window >= signing_date
+}
+
+/// Competitions inside their attestation window and when the oracle was last asked for each
+/// one's attestation, so the attestation poller can ask on its own interval between competition
+/// watcher runs
+#[derive(Clone, Default)]
+pub struct AttestationPolls {
+    polled_at: Arc<Mutex<BTreeMap<Uuid, OffsetDateTime>>>,
+}
+
+impl AttestationPolls {
+    /// Start polling `competition_id`, the competition watcher just asked the oracle at `now`
+    pub fn track(&self, competition_id: Uuid, now: OffsetDateTime) {
+        self.lock().insert(competition_id, now);
+    }
+
+    /// Competitions due a poll at `now`, recording the poll for each of them
+    pub fn due(&self, now: OffsetDateTime, interval: Duration) -> Vec<Uuid> {
+        let mut polled_at = self.lock();
+        let due: Vec<Uuid> = polled_at
+            .iter()
+            .filter(|(_, last)| now >= **last + interval)
+            .map(|(competition_id, _)| *competition_id)
+            .collect();
+        for competition_id in &due {
+            polled_at.insert(*competition_id, now);
+        }
+        due
+    }
+
+    /// Forget competitions that aren't in `awaiting` anymore
+    pub fn retain(&self, awaiting: &[Uuid]) {
+        self.lock()
+            .retain(|competition_id, _| awaiting.contains(competition_id));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<Uuid, OffsetDateTime>> {
+        self.polled_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Spread `interval` uniformly by up to `jitter_percent` of itself in either direction
+pub fn jittered_interval(interval: Duration, jitter_percent: u8, rng: &mut impl Rng) -> Duration {
+    if jitter_percent == 0 {
+        return interval;
+    }
+    let spread = interval.mul_f64(f64::from(jitter_percent.min(100)) / 100.0);
+    interval.saturating_sub(spread) + spread.mul_f64(rng.random::<f64>() * 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_jittered_interval_stays_within_spread() {
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let interval = Duration::from_secs(10);
+
+        assert_eq!(jittered_interval(interval, 0, &mut rng), interval);
+
+        let samples: Vec<Duration> = (0..200)
+            .map(|_| jittered_interval(interval, 20, &mut rng))
+            .collect();
+        assert!(samples
+            .iter()
+            .all(|sample| *sample >= Duration::from_secs(8) && *sample <= Duration::from_secs(12)));
+        assert!(samples.iter().any(|sample| *sample != samples[0]));
+    }
+
+    #[test]
+    fn test_in_flight_guard_clears_on_drop() {
+        let in_flight = InFlightCompetitions::default();
+        let first = Uuid::now_v7();
+        let second = Uuid::now_v7();
+
+        let first_guard = in_flight.start(first, "attested");
+        {
+            let _second_guard = in_flight.start(second, "funding_confirmed");
+            assert_eq!(
+                in_flight.snapshot(),
+                vec![
+                    (first, "attested".to_string()),
+                    (second, "funding_confirmed".to_string())
+                ]
+            );
+        }
+        assert_eq!(in_flight.snapshot(), vec![(first, "attested".to_string())]);
+
+        drop(first_guard);
+        assert!(in_flight.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_attestation_polls_follow_signing_date_and_interval() {
+        let signing_date = OffsetDateTime::now_utc() + time::Duration::days(3);
+        let pre_window = Duration::from_secs(600);
+        assert!(!attestation_window_open(
+            signing_date,
+            signing_date - time::Duration::minutes(11),
+            pre_window
+        ));
+        assert!(attestation_window_open(
+            signing_date,
+            signing_date - time::Duration::minutes(10),
+            pre_window
+        ));
+
+        let polls = AttestationPolls::default();
+        let competition_id = Uuid::now_v7();
+        let interval = Duration::from_secs(5);
+        assert!(polls.due(signing_date, interval).is_empty());
+        polls.track(competition_id, signing_date);
+        assert!(polls
+            .due(signing_date + time::Duration::seconds(4), interval)
+            .is_empty());
+        assert_eq!(
+            polls.due(signing_date + time::Duration::seconds(5), interval),
+            vec![competition_id]
+        );
+        assert!(polls
+            .due(signing_date + time::Duration::seconds(9), interval)
+            .is_empty());
+
+        polls.retain(&[]);
+        assert!(polls
+            .due(signing_date + time::Duration::seconds(60), interval)
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_kick_before_wait_is_not_lost() {
+        let kicks = WatcherKicks::default();
+        kicks.kick_payouts();
+        kicks.kick_payouts();
+
+        tokio::time::timeout(Duration::from_millis(100), kicks.payouts_kicked())
+            .await
+            .expect("stored kick should wake the watcher");
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), kicks.payouts_kicked())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+mod sender;
+mod store;
+mod worker;
+
+pub use sender::*;
+pub use store::*;
+pub use worker::*;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Body posted to webhook endpoints, the raw JSON is what gets signed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub competition_id: Uuid,
+    /// Competition state the event is about (ie. `created`, `attested`, `completed`)
+    pub event_type: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub summary: String,
+}
+
+impl WebhookPayload {
+    pub fn new(competition_id: Uuid, event_type: &str, summary: String) -> Self {
+        Self {
+            competition_id,
+            event_type: event_type.to_string(),
+            timestamp: OffsetDateTime::now_utc(),
+            summary,
+        }
+    }
+}
+
+/// A single payload sent (or being sent) to a single endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub competition_id: Uuid,
+    pub event_type: String,
+    pub endpoint_url: String,
+    pub payload: String,
+    pub attempts: u32,
+    pub last_status_code: Option<u16>,
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_attempt_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub delivered_at: Option<OffsetDateTime>,
+}
+
+impl WebhookDelivery {
+    pub fn new(endpoint_url: &str, payload: &WebhookPayload, body: String) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            competition_id: payload.competition_id,
+            event_type: payload.event_type.clone(),
+            endpoint_url: endpoint_url.to_string(),
+            payload: body,
+            attempts: 0,
+            last_status_code: None,
+            last_error: None,
+            created_at: OffsetDateTime::now_utc(),
+            last_attempt_at: None,
+            delivered_at: None,
+        }
+    }
+
+    pub fn is_delivered(&self) -> bool {
+        self.delivered_at.is_some()
+    }
+
+    pub fn status(&self) -> &'static str {
+        if self.is_delivered() {
+            "delivered"
+        } else if self.attempts == 0 {
+            "pending"
+        } else {
+            "failed"
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WebhookJob {
+    Event(WebhookPayload),
+    Replay(Uuid),
+}
+
+/// Cheap handle used by the coordinator and admin routes to queue work for the `WebhookWorker`
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    sender: mpsc::UnboundedSender<WebhookJob>,
+}
+
+impl WebhookNotifier {
+    pub fn new(sender: mpsc::UnboundedSender<WebhookJob>) -> Self {
+        Self { sender }
+    }
+
+    pub fn notify(&self, competition_id: Uuid, event_type: &str, summary: String) {
+        let payload = WebhookPayload::new(competition_id, event_type, summary);
+        if let Err(e) = self.sender.send(WebhookJob::Event(payload)) {
+            warn!(
+                "Webhook worker is not running, dropping {} event for competition {}: {}",
+                event_type, competition_id, e
+            );
+        }
+    }
+
+    pub fn replay(&self, delivery_id: Uuid) -> Result<(), anyhow::Error> {
+        self.sender
+            .send(WebhookJob::Replay(delivery_id))
+            .map_err(|e| anyhow::anyhow!("webhook worker is not running: {}", e))
+    }
+}
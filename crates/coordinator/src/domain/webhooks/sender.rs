@@ -0,0 +1,278 @@
+use hmac::{Hmac, Mac};
+use log::{debug, warn};
+use reqwest_middleware::reqwest::{header::CONTENT_TYPE, Client, StatusCode};
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::WebhookSettings;
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+/// Longest wait between two delivery attempts, however many failed before
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Hex encoded HMAC-SHA256 of the raw body, prefixed with the algorithm (ie. `sha256=ab12..`)
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Result of delivering one payload to one endpoint, including all retries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryOutcome {
+    pub attempts: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl DeliveryOutcome {
+    pub fn delivered(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookSender {
+    client: Client,
+    secret: String,
+    max_attempts: u32,
+    initial_backoff: Duration,
+}
+
+impl WebhookSender {
+    pub fn new(settings: &WebhookSettings) -> Result<Self, anyhow::Error> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(settings.request_timeout_secs))
+            .build()?;
+
+        Ok(Self {
+            client,
+            secret: settings.secret.clone(),
+            max_attempts: settings.max_attempts.max(1),
+            initial_backoff: Duration::from_millis(settings.initial_backoff_ms),
+        })
+    }
+
+    /// Post the signed body, retrying with exponential backoff on connection errors,
+    /// 5xx and 429 responses. Other 4xx responses are not retried.
+    pub async fn deliver(&self, url: &str, event_type: &str, body: &str) -> DeliveryOutcome {
+        let signature = sign_payload(&self.secret, body.as_bytes());
+        let mut outcome = DeliveryOutcome {
+            attempts: 0,
+            status_code: None,
+            error: None,
+        };
+
+        for attempt in 1..=self.max_attempts {
+            outcome.attempts = attempt;
+
+            let retryable = match self
+                .client
+                .post(url)
+                .header(CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .header(EVENT_HEADER, event_type)
+                .body(body.to_string())
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    let status = response.status();
+                    outcome.status_code = Some(status.as_u16());
+                    if status.is_success() {
+                        debug!(
+                            "Delivered {} webhook to {} (attempt {}/{})",
+                            event_type, url, attempt, self.max_attempts
+                        );
+                        outcome.error = None;
+                        return outcome;
+                    }
+                    outcome.error = Some(format!("endpoint responded with {}", status));
+                    is_retryable_status(status)
+                }
+                Err(e) => {
+                    outcome.status_code = None;
+                    outcome.error = Some(e.to_string());
+                    true
+                }
+            };
+
+            warn!(
+                "Failed to deliver {} webhook to {} (attempt {}/{}): {}",
+                event_type,
+                url,
+                attempt,
+                self.max_attempts,
+                outcome.error.as_deref().unwrap_or_default()
+            );
+
+            if !retryable {
+                break;
+            }
+            if attempt < self.max_attempts {
+                sleep(self.backoff(attempt)).await;
+            }
+        }
+
+        outcome
+    }
+
+    /// Wait after the failed `attempt`, doubling from the initial backoff up to `MAX_BACKOFF`
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_backoff.saturating_mul(factor).min(MAX_BACKOFF)
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::HeaderMap, routing::post, Router};
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    };
+    use tokio::net::TcpListener;
+
+    #[derive(Clone, Default)]
+    struct TestEndpoint {
+        calls: Arc<AtomicU32>,
+        fail_first: u32,
+        status_on_failure: u16,
+        received: Arc<Mutex<Vec<(Option<String>, String)>>>,
+    }
+
+    async fn receive(
+        State(endpoint): State<TestEndpoint>,
+        headers: HeaderMap,
+        body: String,
+    ) -> StatusCode {
+        let call = endpoint.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        let signature = headers
+            .get(SIGNATURE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        endpoint.received.lock().unwrap().push((signature, body));
+
+        if call <= endpoint.fail_first {
+            StatusCode::from_u16(endpoint.status_on_failure).unwrap()
+        } else {
+            StatusCode::OK
+        }
+    }
+
+    async fn spawn_endpoint(endpoint: TestEndpoint) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(endpoint);
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}/hook", addr)
+    }
+
+    fn test_sender(max_attempts: u32) -> WebhookSender {
+        WebhookSender::new(&WebhookSettings {
+            secret: "shared-secret".to_string(),
+            endpoints: vec![],
+            max_attempts,
+            initial_backoff_ms: 10,
+            request_timeout_secs: 5,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_signed_with_shared_secret() {
+        let endpoint = TestEndpoint::default();
+        let url = spawn_endpoint(endpoint.clone()).await;
+        let body = r#"{"competition_id":"abc","event_type":"created"}"#;
+
+        let outcome = test_sender(3).deliver(&url, "created", body).await;
+
+        assert!(outcome.delivered());
+        assert_eq!(outcome.attempts, 1);
+        let received = endpoint.received.lock().unwrap();
+        let (signature, received_body) = &received[0];
+        assert_eq!(received_body, body);
+        assert_eq!(
+            signature.as_deref(),
+            Some(sign_payload("shared-secret", body.as_bytes()).as_str())
+        );
+        assert_ne!(
+            signature.as_deref(),
+            Some(sign_payload("other-secret", body.as_bytes()).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retries_on_server_error() {
+        let endpoint = TestEndpoint {
+            fail_first: 2,
+            status_on_failure: 500,
+            ..Default::default()
+        };
+        let url = spawn_endpoint(endpoint.clone()).await;
+
+        let outcome = test_sender(5).deliver(&url, "attested", "{}").await;
+
+        assert!(outcome.delivered());
+        assert_eq!(outcome.attempts, 3);
+        assert_eq!(outcome.status_code, Some(200));
+        assert_eq!(endpoint.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let endpoint = TestEndpoint {
+            fail_first: u32::MAX,
+            status_on_failure: 503,
+            ..Default::default()
+        };
+        let url = spawn_endpoint(endpoint.clone()).await;
+
+        let outcome = test_sender(3).deliver(&url, "completed", "{}").await;
+
+        assert!(!outcome.delivered());
+        assert_eq!(outcome.attempts, 3);
+        assert_eq!(outcome.status_code, Some(503));
+        assert_eq!(endpoint.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let sender = test_sender(5);
+        assert_eq!(sender.backoff(1), Duration::from_millis(10));
+        assert_eq!(sender.backoff(3), Duration::from_millis(40));
+        // Far past where the shift would overflow
+        assert_eq!(sender.backoff(40), MAX_BACKOFF);
+        assert_eq!(sender.backoff(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_client_error_not_retried() {
+        let endpoint = TestEndpoint {
+            fail_first: u32::MAX,
+            status_on_failure: 400,
+            ..Default::default()
+        };
+        let url = spawn_endpoint(endpoint.clone()).await;
+
+        let outcome = test_sender(5).deliver(&url, "completed", "{}").await;
+
+        assert!(!outcome.delivered());
+        assert_eq!(outcome.attempts, 1);
+        assert_eq!(endpoint.calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,183 @@
+use sqlx::{sqlite::SqliteRow, Row};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::infra::{
+    db::DBConnection,
+    db_timestamps::{format_timestamp, parse_optional_datetime, parse_required_datetime},
+};
+
+use super::{DeliveryOutcome, WebhookDelivery};
+
+#[derive(Debug, Clone)]
+pub struct WebhookStore {
+    db_connection: DBConnection,
+}
+
+impl WebhookStore {
+    pub fn new(db_connection: DBConnection) -> Self {
+        Self { db_connection }
+    }
+
+    pub async fn add_delivery(&self, delivery: &WebhookDelivery) -> Result<(), sqlx::Error> {
+        let id = delivery.id.to_string();
+        let competition_id = delivery.competition_id.to_string();
+        let event_type = delivery.event_type.clone();
+        let endpoint_url = delivery.endpoint_url.clone();
+        let payload = delivery.payload.clone();
+        let created_at =
+            format_timestamp(delivery.created_at).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                sqlx::query(
+                    "INSERT INTO webhook_deliveries (
+                        id,
+                        competition_id,
+                        event_type,
+                        endpoint_url,
+                        payload,
+                        attempts,
+                        created_at
+                    ) VALUES (?, ?, ?, ?, ?, 0, ?)",
+                )
+                .bind(&id)
+                .bind(&competition_id)
+                .bind(&event_type)
+                .bind(&endpoint_url)
+                .bind(&payload)
+                .bind(&created_at)
+                .execute(&pool)
+                .await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    /// Add the attempts from a delivery run (initial or replay) to the stored delivery
+    pub async fn record_outcome(
+        &self,
+        delivery_id: Uuid,
+        outcome: &DeliveryOutcome,
+    ) -> Result<(), sqlx::Error> {
+        let id = delivery_id.to_string();
+        let attempts = outcome.attempts as i64;
+        let status_code = outcome.status_code.map(|code| code as i64);
+        let error = outcome.error.clone();
+        let now = format_timestamp(OffsetDateTime::now_utc())
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        let delivered_at = outcome.delivered().then(|| now.clone());
+
+        self.db_connection
+            .execute_write(move |pool| async move {
+                sqlx::query(
+                    "UPDATE webhook_deliveries
+                    SET attempts = attempts + ?,
+                        last_status_code = ?,
+                        last_error = ?,
+                        last_attempt_at = ?,
+                        delivered_at = COALESCE(?, delivered_at)
+                    WHERE id = ?",
+                )
+                .bind(attempts)
+                .bind(status_code)
+                .bind(&error)
+                .bind(&now)
+                .bind(&delivered_at)
+                .bind(&id)
+                .execute(&pool)
+                .await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| match e {
+                crate::infra::db::DatabaseWriteError::Sqlx(e) => e,
+                e => sqlx::Error::Protocol(e.to_string()),
+            })
+    }
+
+    pub async fn get_delivery(
+        &self,
+        delivery_id: Uuid,
+    ) -> Result<Option<WebhookDelivery>, sqlx::Error> {
+        sqlx::query(
+            "SELECT
+                id,
+                competition_id,
+                event_type,
+                endpoint_url,
+                payload,
+                attempts,
+                last_status_code,
+                last_error,
+                created_at,
+                last_attempt_at,
+                delivered_at
+            FROM webhook_deliveries
+            WHERE id = ?",
+        )
+        .bind(delivery_id.to_string())
+        .fetch_optional(self.db_connection.read())
+        .await?
+        .map(|row| map_delivery(&row))
+        .transpose()
+    }
+
+    pub async fn get_recent_deliveries(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+        sqlx::query(
+            "SELECT
+                id,
+                competition_id,
+                event_type,
+                endpoint_url,
+                payload,
+                attempts,
+                last_status_code,
+                last_error,
+                created_at,
+                last_attempt_at,
+                delivered_at
+            FROM webhook_deliveries
+            ORDER BY created_at DESC
+            LIMIT ?",
+        )
+        .bind(limit as i64)
+        .fetch_all(self.db_connection.read())
+        .await?
+        .iter()
+        .map(map_delivery)
+        .collect()
+    }
+}
+
+fn map_delivery(row: &SqliteRow) -> Result<WebhookDelivery, sqlx::Error> {
+    let parse_uuid = |column: &str| {
+        Uuid::parse_str(&row.get::<String, _>(column)).map_err(|e| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: Box::new(e),
+        })
+    };
+
+    Ok(WebhookDelivery {
+        id: parse_uuid("id")?,
+        competition_id: parse_uuid("competition_id")?,
+        event_type: row.get("event_type"),
+        endpoint_url: row.get("endpoint_url"),
+        payload: row.get("payload"),
+        attempts: row.get::<i64, _>("attempts") as u32,
+        last_status_code: row
+            .get::<Option<i64>, _>("last_status_code")
+            .map(|code| code as u16),
+        last_error: row.get("last_error"),
+        created_at: parse_required_datetime(row, "created_at")?,
+        last_attempt_at: parse_optional_datetime(row, "last_attempt_at")?,
+        delivered_at: parse_optional_datetime(row, "delivered_at")?,
+    })
+}
@@ -0,0 +1,145 @@
+use log::{debug, error, info, warn};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::{WebhookEndpoint, WebhookSettings};
+
+use super::{
+    WebhookDelivery, WebhookJob, WebhookNotifier, WebhookPayload, WebhookSender, WebhookStore,
+};
+
+/// Receives lifecycle events from the `WebhookNotifier`, records a delivery per matching endpoint
+/// and posts the signed payload in the background so slow endpoints never block the coordinator
+pub struct WebhookWorker {
+    store: WebhookStore,
+    sender: WebhookSender,
+    endpoints: Vec<WebhookEndpoint>,
+    receiver: mpsc::UnboundedReceiver<WebhookJob>,
+}
+
+impl WebhookWorker {
+    pub fn new(
+        store: WebhookStore,
+        settings: &WebhookSettings,
+    ) -> Result<(Self, WebhookNotifier), anyhow::Error> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let worker = Self {
+            store,
+            sender: WebhookSender::new(settings)?,
+            endpoints: settings.endpoints.clone(),
+            receiver,
+        };
+        Ok((worker, WebhookNotifier::new(sender)))
+    }
+
+    /// Borrows the worker so it can be watched again after a restart without losing the jobs
+    /// still queued on its receiver
+    pub async fn watch(&mut self, cancel_token: CancellationToken) -> Result<(), anyhow::Error> {
+        info!(
+            "Starting webhook worker with {} endpoint(s)",
+            self.endpoints.len()
+        );
+
+        loop {
+            tokio::select! {
+                job = self.receiver.recv() => match job {
+                    Some(WebhookJob::Event(payload)) => self.handle_event(payload).await,
+                    Some(WebhookJob::Replay(delivery_id)) => self.handle_replay(delivery_id).await,
+                    None => break,
+                },
+                _ = cancel_token.cancelled() => {
+                    info!("Webhook worker received cancellation");
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_event(&self, payload: WebhookPayload) {
+        let endpoints: Vec<&WebhookEndpoint> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.accepts(&payload.event_type))
+            .collect();
+        if endpoints.is_empty() {
+            debug!(
+                "No webhook endpoints subscribed to {} for competition {}",
+                payload.event_type, payload.competition_id
+            );
+            return;
+        }
+
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                error!(
+                    "Failed to serialize {} webhook for competition {}: {}",
+                    payload.event_type, payload.competition_id, e
+                );
+                return;
+            }
+        };
+
+        for endpoint in endpoints {
+            let delivery = WebhookDelivery::new(&endpoint.url, &payload, body.clone());
+            if let Err(e) = self.store.add_delivery(&delivery).await {
+                error!(
+                    "Failed to record {} webhook delivery to {}: {}",
+                    delivery.event_type, delivery.endpoint_url, e
+                );
+                continue;
+            }
+            self.spawn_delivery(delivery);
+        }
+    }
+
+    async fn handle_replay(&self, delivery_id: Uuid) {
+        match self.store.get_delivery(delivery_id).await {
+            Ok(Some(delivery)) => {
+                info!(
+                    "Replaying {} webhook delivery {} to {}",
+                    delivery.event_type, delivery.id, delivery.endpoint_url
+                );
+                self.spawn_delivery(delivery);
+            }
+            Ok(None) => warn!("Webhook delivery {} not found for replay", delivery_id),
+            Err(e) => error!(
+                "Failed to load webhook delivery {} for replay: {}",
+                delivery_id, e
+            ),
+        }
+    }
+
+    fn spawn_delivery(&self, delivery: WebhookDelivery) {
+        let sender = self.sender.clone();
+        let store = self.store.clone();
+        tokio::spawn(async move {
+            let outcome = sender
+                .deliver(
+                    &delivery.endpoint_url,
+                    &delivery.event_type,
+                    &delivery.payload,
+                )
+                .await;
+            if !outcome.delivered() {
+                error!(
+                    "Giving up on {} webhook delivery {} to {} after {} attempt(s): {}",
+                    delivery.event_type,
+                    delivery.id,
+                    delivery.endpoint_url,
+                    outcome.attempts,
+                    outcome.error.as_deref().unwrap_or_default()
+                );
+            }
+            if let Err(e) = store.record_outcome(delivery.id, &outcome).await {
+                error!(
+                    "Failed to record outcome of webhook delivery {}: {}",
+                    delivery.id, e
+                );
+            }
+        });
+    }
+}
@@ -1,5 +1,8 @@
 #![allow(deprecated)] // SignOptions is deprecated but no replacement API exists yet in bdk_wallet 2.3
-use crate::{get_key, BitcoinSettings};
+use crate::{
+    infra::secrets::{load_key, SecretBackend},
+    BitcoinSettings, SharedConfig,
+};
 use anyhow::anyhow;
 use async_trait::async_trait;
 use bdk_esplora::{
@@ -11,13 +14,14 @@ use bdk_wallet::{
     bitcoin::{
         address::NetworkChecked,
         bip32::{ChildNumber, Xpriv},
+        constants::genesis_block,
         ecdsa,
         hashes::{sha256, Hash},
         psbt::Input,
         secp256k1::{Message, Secp256k1, SecretKey as BdkSecretKey},
         sighash::{EcdsaSighashType, SighashCache},
-        Address, Amount, Network, NetworkKind, OutPoint, Psbt, PublicKey, ScriptBuf, Transaction,
-        Txid, Weight, Witness,
+        Address, Amount, BlockHash, Network, NetworkKind, OutPoint, Psbt, PublicKey, ScriptBuf,
+        Transaction, Txid, Weight, Witness,
     },
     coin_selection::DefaultCoinSelectionAlgorithm,
     descriptor::calc_checksum,
@@ -29,7 +33,6 @@ use dlctix::{
     secp::Scalar,
 };
 use log::{debug, error, info};
-use secrecy::{ExposeSecret, SecretString};
 use serde::Deserialize;
 use std::{
     collections::{BTreeSet, HashMap},
@@ -37,7 +40,6 @@ use std::{
     path::Path,
     str::FromStr,
     sync::Arc,
-    time::Duration,
 };
 use tokio::{sync::RwLock, time::sleep};
 use tokio_util::sync::CancellationToken;
@@ -71,7 +73,39 @@ pub trait Bitcoin: Send + Sync {
     async fn get_confirmed_blockchain_time(&self, blocks: usize) -> Result<u64, anyhow::Error>;
     async fn get_estimated_fee_rates(&self) -> Result<HashMap<u16, f64>, anyhow::Error>;
     async fn get_tx_confirmation_height(&self, txid: &Txid) -> Result<Option<u32>, anyhow::Error>;
+    /// Where the transaction currently sits relative to the best chain
+    async fn get_tx_chain_status(&self, txid: &Txid) -> Result<TxChainStatus, anyhow::Error>;
     async fn broadcast(&self, transaction: &Transaction) -> Result<(), anyhow::Error>;
+    /// Ask the backend whether its mempool would accept the transaction, like bitcoind's
+    /// testmempoolaccept
+    async fn test_mempool_accept(&self, tx_hex: &str) -> Result<MempoolAcceptance, anyhow::Error>;
+    /// Whether the output has been spent, by a transaction in the mempool or the chain
+    async fn get_outpoint_status(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<OutpointStatus, anyhow::Error>;
+    /// Broadcast a transaction spending a relative timelocked output of `locked_txid` once that
+    /// output is `csv_blocks` deep in the best chain. Returns false without broadcasting until then.
+    async fn broadcast_when_mature(
+        &self,
+        transaction: &Transaction,
+        locked_txid: &Txid,
+        csv_blocks: u16,
+    ) -> Result<bool, anyhow::Error> {
+        let confirmations = self.get_tx_chain_status(locked_txid).await?.confirmations();
+        if confirmations < u32::from(csv_blocks) {
+            debug!(
+                "Transaction {} waits for {} of {} confirmations on {}",
+                transaction.compute_txid(),
+                confirmations,
+                csv_blocks,
+                locked_txid
+            );
+            return Ok(false);
+        }
+        self.broadcast(transaction).await?;
+        Ok(true)
+    }
     async fn get_next_address(&self) -> Result<AddressInfo, anyhow::Error>;
     async fn get_public_key(&self) -> Result<bdk_wallet::bitcoin::PublicKey, anyhow::Error>;
     async fn get_derived_private_key(&self) -> Result<Scalar, anyhow::Error>;
@@ -92,9 +126,230 @@ pub trait Bitcoin: Send + Sync {
     ) -> Result<Txid, anyhow::Error>;
 }
 
+/// Whether an output is still available to spend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutpointStatus {
+    /// Not spent by anything the backend knows of, also returned for outputs it has never seen
+    Unspent,
+    /// Spent by `txid`, which may still be unconfirmed
+    Spent { txid: Txid },
+}
+
+/// Position of a transaction relative to the best chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxChainStatus {
+    /// Neither in the mempool nor in the best chain (never seen, evicted, or reorged out and dropped)
+    NotFound,
+    /// Known to the mempool but not in a best chain block
+    Unconfirmed,
+    /// In a block that is part of the current best chain
+    Confirmed { height: u32, confirmations: u32 },
+}
+
+impl TxChainStatus {
+    pub fn confirmations(&self) -> u32 {
+        match self {
+            TxChainStatus::Confirmed { confirmations, .. } => *confirmations,
+            _ => 0,
+        }
+    }
+}
+
+/// What the backend's mempool would do with a transaction, asked without broadcasting it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MempoolAcceptance {
+    Accepted,
+    /// Refused, with bitcoind's reject-reason
+    Rejected {
+        reason: String,
+    },
+    /// The backend can't test transactions, the broadcast itself is the only check
+    Unsupported,
+}
+
+#[derive(Deserialize)]
+struct TestMempoolAcceptResult {
+    allowed: bool,
+    #[serde(rename = "reject-reason")]
+    reject_reason: Option<String>,
+}
+
+/// How a failed sendrawtransaction response should be handled by the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BroadcastErrorKind {
+    /// Node already has the transaction in its mempool or chain, safe to treat as success
+    AlreadyKnown,
+    /// Transaction is invalid (bad signature, missing inputs, fee too low), retrying won't help
+    Rejected,
+    /// Backend unreachable or transaction not final yet, retry on the next pass
+    Transient,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("error broadcasting {txid} ({kind:?}): {message}")]
+pub struct BroadcastError {
+    pub txid: Txid,
+    pub kind: BroadcastErrorKind,
+    pub message: String,
+}
+
+impl BroadcastError {
+    pub fn is_transient(&self) -> bool {
+        self.kind == BroadcastErrorKind::Transient
+    }
+}
+
+const ALREADY_KNOWN_MARKERS: &[&str] = &[
+    "txn-already-in-mempool",
+    "txn-already-known",
+    "already in block chain",
+    "already in blockchain",
+    "outputs already in utxo set",
+];
+
+const TRANSIENT_MARKERS: &[&str] = &[
+    // Relative/absolute timelocks that haven't matured yet
+    "non-bip68-final",
+    "non-final",
+    // Transport level failures talking to esplora/bitcoind
+    "timed out",
+    "timeout",
+    "connection",
+    "error sending request",
+    "status: 429",
+    "status: 500",
+    "status: 502",
+    "status: 503",
+    "status: 504",
+    "too many requests",
+    "service unavailable",
+];
+
+/// Classify the error text returned from sendrawtransaction (directly or relayed by esplora).
+/// Anything not recognized is treated as `Rejected` so real failures still fail competitions.
+pub fn classify_broadcast_error(message: &str) -> BroadcastErrorKind {
+    let message = message.to_lowercase();
+
+    if ALREADY_KNOWN_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+    {
+        return BroadcastErrorKind::AlreadyKnown;
+    }
+
+    if TRANSIENT_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+    {
+        return BroadcastErrorKind::Transient;
+    }
+
+    BroadcastErrorKind::Rejected
+}
+
+/// Broadcast a transaction, treating "already in mempool/chain" responses as success.
+/// All coordinator broadcast paths should go through this instead of calling `broadcast` directly.
+pub async fn broadcast_transaction(
+    bitcoin: &dyn Bitcoin,
+    transaction: &Transaction,
+) -> Result<(), BroadcastError> {
+    let txid = transaction.compute_txid();
+    let Err(e) = bitcoin.broadcast(transaction).await else {
+        return Ok(());
+    };
+
+    let message = e.to_string();
+    match classify_broadcast_error(&message) {
+        BroadcastErrorKind::AlreadyKnown => {
+            info!(
+                "Transaction {} already known to the network, treating broadcast as success",
+                txid
+            );
+            Ok(())
+        }
+        kind => Err(BroadcastError {
+            txid,
+            kind,
+            message,
+        }),
+    }
+}
+
+/// Make sure the chain backend is serving the configured network by comparing genesis blocks
+pub fn check_chain_network(network: Network, genesis_hash: BlockHash) -> Result<(), anyhow::Error> {
+    if genesis_block(network).block_hash() == genesis_hash {
+        return Ok(());
+    }
+
+    let backend_network = [
+        Network::Bitcoin,
+        Network::Testnet,
+        Network::Testnet4,
+        Network::Signet,
+        Network::Regtest,
+    ]
+    .into_iter()
+    .find(|candidate| genesis_block(*candidate).block_hash() == genesis_hash)
+    .map(|candidate| candidate.to_string())
+    .unwrap_or_else(|| String::from("unknown"));
+
+    Err(anyhow!(
+        "Configured network {} doesn't match the chain backend (genesis {} is {}), refusing to start",
+        network,
+        genesis_hash,
+        backend_network
+    ))
+}
+
+/// Reject PSBTs carrying extended keys for a different network than the coordinator runs on,
+/// or spending from or paying to scripts that aren't an address on that network
+pub fn validate_psbt_network(psbt: &Psbt, network: Network) -> Result<(), anyhow::Error> {
+    let expected = NetworkKind::from(network);
+    if let Some(xpub) = psbt.xpub.keys().find(|xpub| xpub.network != expected) {
+        return Err(anyhow!(
+            "PSBT extended key {} is for {:?}, coordinator is running on {}",
+            xpub.fingerprint(),
+            xpub.network,
+            network
+        ));
+    }
+
+    for (index, (input, txin)) in psbt.inputs.iter().zip(&psbt.unsigned_tx.input).enumerate() {
+        let spent = input.witness_utxo.as_ref().or_else(|| {
+            input
+                .non_witness_utxo
+                .as_ref()
+                .and_then(|tx| tx.output.get(txin.previous_output.vout as usize))
+        });
+        if let Some(spent) = spent {
+            if Address::from_script(&spent.script_pubkey, network).is_err() {
+                return Err(anyhow!(
+                    "PSBT input {} spends a script that isn't an address on {}",
+                    index,
+                    network
+                ));
+            }
+        }
+    }
+
+    for (index, output) in psbt.unsigned_tx.output.iter().enumerate() {
+        if output.script_pubkey.is_op_return() {
+            continue;
+        }
+        if Address::from_script(&output.script_pubkey, network).is_err() {
+            return Err(anyhow!(
+                "PSBT output {} pays to a script that isn't an address on {}",
+                index,
+                network
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub struct BitcoinClient {
     pub network: Network,
-    seed_path: SecretString,
+    secrets: Arc<dyn SecretBackend>,
     wallet: RwLock<PersistedWallet<Store>>,
     client: AsyncClient,
     wallet_store: RwLock<Store>,
@@ -191,7 +446,7 @@ impl Bitcoin for BitcoinClient {
     }
 
     async fn get_public_key(&self) -> Result<bdk_wallet::bitcoin::PublicKey, anyhow::Error> {
-        let secret_key = get_key::<BdkSecretKey>(self.seed_path.expose_secret())?;
+        let secret_key = load_key::<BdkSecretKey>(self.secrets.as_ref())?;
         let private_key = bdk_wallet::bitcoin::PrivateKey {
             compressed: true,
             network: NetworkKind::from(self.network),
@@ -204,7 +459,7 @@ impl Bitcoin for BitcoinClient {
     }
 
     async fn get_derived_private_key(&self) -> Result<Scalar, anyhow::Error> {
-        let secret_key = get_key::<BdkSecretKey>(self.seed_path.expose_secret())?;
+        let secret_key = load_key::<BdkSecretKey>(self.secrets.as_ref())?;
         let dlc_key = Scalar::from_hex(&hex::encode(secret_key.secret_bytes()))
             .map_err(|e| anyhow!("Failed to convert private key to scalar: {}", e))?;
 
@@ -431,6 +686,55 @@ impl Bitcoin for BitcoinClient {
         Ok(tx_status.block_height)
     }
 
+    async fn get_tx_chain_status(&self, txid: &Txid) -> Result<TxChainStatus, anyhow::Error> {
+        let tx_status = self.client.get_tx_status(txid).await?;
+
+        if let (true, Some(height), Some(block_hash)) = (
+            tx_status.confirmed,
+            tx_status.block_height,
+            tx_status.block_hash,
+        ) {
+            // Make sure the confirming block wasn't replaced by a reorg since the backend indexed it
+            let best_hash = self.client.get_block_hash(height).await?;
+            if best_hash == block_hash {
+                let tip = self.get_current_height().await?;
+                return Ok(TxChainStatus::Confirmed {
+                    height,
+                    confirmations: tip.saturating_sub(height) + 1,
+                });
+            }
+            debug!(
+                "Transaction {} confirmed in stale block {} at height {}",
+                txid, block_hash, height
+            );
+        }
+
+        match self.client.get_tx(txid).await? {
+            Some(_) => Ok(TxChainStatus::Unconfirmed),
+            None => Ok(TxChainStatus::NotFound),
+        }
+    }
+
+    async fn get_outpoint_status(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<OutpointStatus, anyhow::Error> {
+        let status = self
+            .client
+            .get_output_status(&outpoint.txid, u64::from(outpoint.vout))
+            .await?;
+        debug!("Output {} status: {:?}", outpoint, status);
+        match status {
+            Some(status) if status.spent => {
+                let txid = status
+                    .txid
+                    .ok_or_else(|| anyhow!("Output {} spent by unknown tx", outpoint))?;
+                Ok(OutpointStatus::Spent { txid })
+            }
+            _ => Ok(OutpointStatus::Unspent),
+        }
+    }
+
     async fn get_spendable_utxo(&self, amount_sats: u64) -> Result<LocalOutput, anyhow::Error> {
         let amount = Amount::from_sat(amount_sats);
         let current_height = self.get_current_height().await?;
@@ -514,6 +818,51 @@ impl Bitcoin for BitcoinClient {
             .map_err(|e| anyhow!("error broadcasting: {}", e))
     }
 
+    /// Esplora has no testmempoolaccept, mempool.space's backend relays it on `POST /txs/test`.
+    /// Plain esplora answers that with a 404, which is reported as unsupported.
+    async fn test_mempool_accept(&self, tx_hex: &str) -> Result<MempoolAcceptance, anyhow::Error> {
+        let response = self
+            .client
+            .client()
+            .post(format!("{}/txs/test", self.client.url()))
+            .header("content-type", "application/json")
+            .body(serde_json::to_string(&[tx_hex])?)
+            .send()
+            .await
+            .map_err(|e| anyhow!("error testing mempool acceptance: {}", e))?;
+
+        let status = response.status().as_u16();
+        if status == 404 || status == 405 {
+            return Ok(MempoolAcceptance::Unsupported);
+        }
+        let body = response
+            .text()
+            .await
+            .map_err(|e| anyhow!("error reading mempool acceptance: {}", e))?;
+        if !(200..300).contains(&status) {
+            return Err(anyhow!(
+                "error testing mempool acceptance, status: {} {}",
+                status,
+                body
+            ));
+        }
+
+        let results: Vec<TestMempoolAcceptResult> = serde_json::from_str(&body)?;
+        let result = results
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("empty mempool acceptance response"))?;
+        if result.allowed {
+            Ok(MempoolAcceptance::Accepted)
+        } else {
+            Ok(MempoolAcceptance::Rejected {
+                reason: result
+                    .reject_reason
+                    .unwrap_or_else(|| String::from("unknown")),
+            })
+        }
+    }
+
     async fn list_utxos(&self) -> Vec<LocalOutput> {
         let wallet = self.wallet.read().await;
         wallet.list_unspent().collect()
@@ -609,11 +958,16 @@ impl Bitcoin for BitcoinClient {
 }
 
 impl BitcoinClient {
-    pub async fn new(settings: &BitcoinSettings) -> Result<BitcoinClient, anyhow::Error> {
+    pub async fn new(
+        settings: &BitcoinSettings,
+        secrets: Arc<dyn SecretBackend>,
+    ) -> Result<BitcoinClient, anyhow::Error> {
         info!("Creating Bitcoin client with settings:");
         info!("  Storage file: {}", settings.storage_file);
         info!("  Seed path: {}", settings.seed_path);
+        info!("  Secrets backend: {}", secrets.name());
         info!("  Network: {}", settings.network);
+        let network = settings.chain();
         info!("  Esplora URL: {}", settings.esplora_url);
 
         let path = Path::new(&settings.storage_file);
@@ -632,14 +986,14 @@ impl BitcoinClient {
         }
         // Set up wallet with EC private key
         let (external_desc, internal_desc) =
-            setup_wallet_descriptors(&settings.seed_path, settings.network.into())
+            setup_wallet_descriptors(secrets.as_ref(), network.into())
                 .map_err(|e| anyhow!("Failed to load bitcoin private key: {}", e))?;
 
         let wallet_opt = Wallet::load()
             .descriptor(KeychainKind::External, Some(external_desc.clone()))
             .descriptor(KeychainKind::Internal, Some(internal_desc.clone()))
             .extract_keys()
-            .check_network(settings.network)
+            .check_network(network)
             .load_wallet_async(&mut db)
             .await
             .map_err(|e| anyhow!("Failed to load bitcoin wallet store: {}", e))?;
@@ -648,7 +1002,7 @@ impl BitcoinClient {
         let mut wallet = match wallet_opt {
             Some(wallet) => wallet,
             None => Wallet::create(external_desc, internal_desc)
-                .network(settings.network)
+                .network(network)
                 .create_wallet_async(&mut db)
                 .await
                 .map_err(|e| anyhow!("Failed to create bitcoin wallet from keys: {}", e))?,
@@ -658,18 +1012,26 @@ impl BitcoinClient {
             wallet.list_unspent().collect::<Vec<_>>().len()
         );
 
-        let esplora_api =
-            if settings.network == Network::Regtest || settings.network == Network::Testnet {
-                format!("{}/{}/api", settings.esplora_url, settings.network)
-            } else {
-                // For mutinynet and mainnet network is not needed in the path
-                format!("{}/api", settings.esplora_url)
-            };
+        let esplora_api = if network == Network::Regtest || network == Network::Testnet {
+            format!("{}/{}/api", settings.esplora_url, network)
+        } else {
+            // For mutinynet and mainnet network is not needed in the path
+            format!("{}/api", settings.esplora_url)
+        };
 
         let client = Builder::new(&esplora_api)
             .build_async_with_sleeper::<DefaultSleeper>()
             .map_err(|e| anyhow!("Failed to create esplora client: {}", e))?;
 
+        // Fail fast if the backend serves a different chain than configured, otherwise addresses
+        // and descriptors for one network end up funded on another
+        let genesis_hash = client
+            .get_block_hash(0)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch genesis block from esplora: {}", e))?;
+        check_chain_network(network, genesis_hash)?;
+        info!("Chain backend matches configured network {}", network);
+
         // Perform initial full scan
         info!("Starting initial full scan...");
         let request = wallet
@@ -708,9 +1070,9 @@ impl BitcoinClient {
         info!("Unconfirmed balance: {} sats", balance.untrusted_pending);
 
         Ok(BitcoinClient {
-            network: settings.network,
+            network,
             wallet: RwLock::new(wallet),
-            seed_path: SecretString::from(settings.seed_path.clone()),
+            secrets,
             client,
             wallet_store: RwLock::new(db),
         })
@@ -735,7 +1097,7 @@ impl BitcoinClient {
 
     async fn sign_escrow_inputs(&self, psbt: &mut Psbt) -> Result<usize, anyhow::Error> {
         // Load the private key from the seed file
-        let secret_key = get_key::<BdkSecretKey>(self.seed_path.expose_secret())?;
+        let secret_key = load_key::<BdkSecretKey>(self.secrets.as_ref())?;
         let coordinator_privkey = bdk_wallet::bitcoin::PrivateKey {
             compressed: true,
             network: NetworkKind::from(self.network),
@@ -880,10 +1242,10 @@ impl BitcoinClient {
 }
 
 fn setup_wallet_descriptors(
-    seed_path: &str,
+    secrets: &dyn SecretBackend,
     network: NetworkKind,
 ) -> Result<(String, String), anyhow::Error> {
-    let xpriv = derive_wallet_key(seed_path, network)?;
+    let xpriv = derive_wallet_key(secrets, network)?;
 
     // Use standard tr descriptor
     let external_base = format!("tr({}/0/*)", xpriv);
@@ -898,9 +1260,12 @@ fn setup_wallet_descriptors(
     Ok((external_descriptor, internal_descriptor))
 }
 
-fn derive_wallet_key(seed_path: &str, network: NetworkKind) -> Result<Xpriv, anyhow::Error> {
-    // Get the secret key from the provided path
-    let secret_key: BdkSecretKey = get_key(seed_path)?;
+fn derive_wallet_key(
+    secrets: &dyn SecretBackend,
+    network: NetworkKind,
+) -> Result<Xpriv, anyhow::Error> {
+    // Get the secret key from the configured secrets backend
+    let secret_key: BdkSecretKey = load_key(secrets)?;
     let chain_code = ChainCode::from(secret_key.secret_bytes());
 
     // Create extended private key with network support
@@ -919,19 +1284,19 @@ fn derive_wallet_key(seed_path: &str, network: NetworkKind) -> Result<Xpriv, any
 pub struct BitcoinSyncWatcher {
     bitcoin: Arc<dyn Bitcoin>,
     cancel_token: CancellationToken,
-    sync_interval: Duration,
+    settings: SharedConfig,
 }
 
 impl BitcoinSyncWatcher {
     pub fn new(
         bitcoin: Arc<dyn Bitcoin>,
         cancel_token: CancellationToken,
-        sync_interval: Duration,
+        settings: SharedConfig,
     ) -> Self {
         Self {
             bitcoin,
             cancel_token,
-            sync_interval,
+            settings,
         }
     }
 
@@ -954,7 +1319,7 @@ impl BitcoinSyncWatcher {
             }
 
             tokio::select! {
-                _ = sleep(self.sync_interval) => continue,
+                _ = sleep(self.settings.bitcoin_refresh_interval()) => continue,
                 _ = self.cancel_token.cancelled() => {
                     info!("Bitcoin sync watcher cancelled during sleep");
                     break;
@@ -965,3 +1330,114 @@ impl BitcoinSyncWatcher {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_network_matches() {
+        for network in [Network::Bitcoin, Network::Signet, Network::Regtest] {
+            assert!(check_chain_network(network, genesis_block(network).block_hash()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_chain_network_mismatch_rejected() {
+        let err = check_chain_network(
+            Network::Signet,
+            genesis_block(Network::Bitcoin).block_hash(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("is bitcoin"));
+
+        let err = check_chain_network(
+            Network::Bitcoin,
+            genesis_block(Network::Regtest).block_hash(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("is regtest"));
+    }
+
+    fn psbt_paying_to(script_pubkey: ScriptBuf) -> Psbt {
+        Psbt::from_unsigned_tx(Transaction {
+            version: bdk_wallet::bitcoin::transaction::Version::TWO,
+            lock_time: bdk_wallet::bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![bdk_wallet::bitcoin::TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey,
+            }],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_psbt_scripts_must_be_addresses_on_the_network() {
+        let secp = Secp256k1::new();
+        let key = BdkSecretKey::from_slice(&[3u8; 32]).unwrap();
+        let (xonly, _) = key.x_only_public_key(&secp);
+        let taproot = ScriptBuf::new_p2tr(&secp, xonly, None);
+        assert!(validate_psbt_network(&psbt_paying_to(taproot), Network::Regtest).is_ok());
+
+        let op_return = ScriptBuf::new_op_return([1u8; 8]);
+        assert!(validate_psbt_network(&psbt_paying_to(op_return), Network::Regtest).is_ok());
+
+        let nonstandard = ScriptBuf::from_bytes(vec![0x51, 0x52]);
+        let err =
+            validate_psbt_network(&psbt_paying_to(nonstandard), Network::Regtest).unwrap_err();
+        assert!(err.to_string().contains("output 0"));
+    }
+
+    #[test]
+    fn test_classify_already_known() {
+        assert_eq!(
+            classify_broadcast_error(
+                r#"HttpResponse { status: 400, message: "sendrawtransaction RPC error: {\"code\":-26,\"message\":\"txn-already-in-mempool\"}" }"#
+            ),
+            BroadcastErrorKind::AlreadyKnown
+        );
+        assert_eq!(
+            classify_broadcast_error("Transaction already in block chain"),
+            BroadcastErrorKind::AlreadyKnown
+        );
+        assert_eq!(
+            classify_broadcast_error("Transaction outputs already in utxo set"),
+            BroadcastErrorKind::AlreadyKnown
+        );
+    }
+
+    #[test]
+    fn test_classify_rejected() {
+        assert_eq!(
+            classify_broadcast_error(
+                "mandatory-script-verify-flag-failed (Invalid Schnorr signature)"
+            ),
+            BroadcastErrorKind::Rejected
+        );
+        assert_eq!(
+            classify_broadcast_error("bad-txns-inputs-missingorspent"),
+            BroadcastErrorKind::Rejected
+        );
+        assert_eq!(
+            classify_broadcast_error("something unexpected"),
+            BroadcastErrorKind::Rejected
+        );
+    }
+
+    #[test]
+    fn test_classify_transient() {
+        assert_eq!(
+            classify_broadcast_error("non-BIP68-final"),
+            BroadcastErrorKind::Transient
+        );
+        assert_eq!(
+            classify_broadcast_error("error sending request for url (http://localhost:9102/tx)"),
+            BroadcastErrorKind::Transient
+        );
+        assert_eq!(
+            classify_broadcast_error("HttpResponse { status: 503, message: \"\" }"),
+            BroadcastErrorKind::Transient
+        );
+    }
+}
@@ -20,7 +20,9 @@ use std::{
 };
 use time::OffsetDateTime;
 
-use super::bitcoin::{Bitcoin, ForeignUtxo, SendOptions};
+use super::bitcoin::{
+    Bitcoin, ForeignUtxo, MempoolAcceptance, OutpointStatus, SendOptions, TxChainStatus,
+};
 
 /// Mock Bitcoin client for E2E testing
 pub struct MockBitcoinClient {
@@ -122,12 +124,35 @@ impl Bitcoin for MockBitcoinClient {
         Ok(Some(height.saturating_sub(3)))
     }
 
+    async fn get_tx_chain_status(&self, _txid: &Txid) -> Result<TxChainStatus, anyhow::Error> {
+        // Mock: confirmed 3 blocks ago, same as get_tx_confirmation_height
+        let tip = self.block_height.load(Ordering::SeqCst);
+        let height = tip.saturating_sub(3);
+        Ok(TxChainStatus::Confirmed {
+            height,
+            confirmations: tip - height + 1,
+        })
+    }
+
     async fn broadcast(&self, _transaction: &Transaction) -> Result<(), anyhow::Error> {
         // Mock: pretend broadcast succeeded
         info!("MockBitcoinClient: broadcast transaction (mock - not actually sent)");
         Ok(())
     }
 
+    async fn test_mempool_accept(&self, _tx_hex: &str) -> Result<MempoolAcceptance, anyhow::Error> {
+        // Mock: every transaction would be accepted
+        Ok(MempoolAcceptance::Accepted)
+    }
+
+    async fn get_outpoint_status(
+        &self,
+        _outpoint: &OutPoint,
+    ) -> Result<OutpointStatus, anyhow::Error> {
+        // Mock: nothing is ever spent outside the coordinator's flow
+        Ok(OutpointStatus::Unspent)
+    }
+
     async fn get_next_address(&self) -> Result<AddressInfo, anyhow::Error> {
         let index = self.address_counter.fetch_add(1, Ordering::SeqCst);
 
@@ -1,9 +1,17 @@
-use crate::SqliteConfigSerde;
+use crate::{
+    infra::{
+        db_encryption::DbEncryptionKey,
+        db_migrations::{pending_migrations, run_migrations, PendingMigration},
+    },
+    BlobFormat, SqliteConfigSerde,
+};
 use log::{debug, error, info};
 use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
     migrate::MigrateDatabase,
-    sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow},
-    Row, Sqlite, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow, SqliteTypeInfo},
+    Database, Encode, Row, Sqlite, SqlitePool, Type,
 };
 use std::{
     env,
@@ -13,7 +21,6 @@ use std::{
     sync::Arc,
     time::Duration as StdDuration,
 };
-use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 use tokio::sync::{mpsc, oneshot};
 
 // ============================================================================
@@ -414,6 +421,10 @@ pub struct DatabasePoolConfig {
     pub idle_timeout_secs: u64,
     pub acquire_timeout_secs: u64,
     pub sqlite_config: SqliteConfig,
+    /// SQLCipher key applied to every connection of file databases
+    pub encryption_key: Option<DbEncryptionKey>,
+    /// Encoding the stores write large blobs in
+    pub blob_format: BlobFormat,
 }
 
 impl Default for DatabasePoolConfig {
@@ -425,7 +436,9 @@ impl Default for DatabasePoolConfig {
             write_min_connections: 1,
             idle_timeout_secs: 600,   // 10 minutes
             acquire_timeout_secs: 15, // 15 seconds
+            blob_format: BlobFormat::default(),
             sqlite_config: SqliteConfig::default(),
+            encryption_key: None,
         }
     }
 }
@@ -520,13 +533,13 @@ impl From<crate::config::DBSettings> for DatabasePoolConfig {
             idle_timeout_secs: config.idle_timeout_secs,
             acquire_timeout_secs: config.acquire_timeout_secs,
             sqlite_config: config.sqlite_config.into(),
+            // Loaded separately through the secrets backend, see `load_db_encryption_key`
+            encryption_key: None,
+            blob_format: config.blob_format,
         }
     }
 }
 
-static COMPETITIONS_MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations/competitions");
-static USERS_MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations/users");
-
 pub enum DatabaseType {
     Competitions,
     Users,
@@ -568,14 +581,29 @@ pub struct DBConnection {
     read_pool: SqlitePool,
     write_pool: SqlitePool,
     writer: DatabaseWriter,
+    blob_format: BlobFormat,
 }
 
 impl DBConnection {
+    /// Open the database and apply any pending migrations
     pub async fn new(
         path: &str,
         db_name: &str,
         database_pool_config: DatabasePoolConfig,
         db_type: DatabaseType,
+    ) -> Result<Self, sqlx::Error> {
+        let connection = Self::open(path, db_name, database_pool_config).await?;
+        run_migrations(&connection.write_pool, &db_type)
+            .await
+            .map_err(|e| sqlx::Error::Migrate(Box::new(e)))?;
+        Ok(connection)
+    }
+
+    /// Open the database without touching its schema
+    pub async fn open(
+        path: &str,
+        db_name: &str,
+        database_pool_config: DatabasePoolConfig,
     ) -> Result<Self, sqlx::Error> {
         let database_path = format!("{}/{}.db", path, db_name);
 
@@ -594,25 +622,26 @@ impl DBConnection {
         // Create the serialized writer for WAL-safe writes
         let writer = DatabaseWriter::new();
 
-        let migrator = match db_type {
-            DatabaseType::Competitions => &COMPETITIONS_MIGRATOR,
-            DatabaseType::Users => &USERS_MIGRATOR,
-        };
-
-        migrator
-            .run(&write_pool)
-            .await
-            .map_err(|e| sqlx::Error::Migrate(Box::new(e)))?;
-
         Ok(Self {
             database_name: db_name.to_string(),
             database_path: database_path.clone(),
             read_pool,
             write_pool,
             writer,
+            blob_format: database_pool_config.blob_format,
         })
     }
 
+    /// Migrations that opening this database with `new` would apply
+    pub async fn pending_migrations(
+        &self,
+        db_type: &DatabaseType,
+    ) -> Result<Vec<PendingMigration>, sqlx::Error> {
+        pending_migrations(&self.write_pool, db_type)
+            .await
+            .map_err(|e| sqlx::Error::Migrate(Box::new(e)))
+    }
+
     #[cfg(test)]
     pub fn new_with_pools(
         database_name: String,
@@ -626,6 +655,7 @@ impl DBConnection {
             read_pool,
             write_pool,
             writer: DatabaseWriter::new(),
+            blob_format: BlobFormat::default(),
         }
     }
 
@@ -663,7 +693,14 @@ impl DBConnection {
                 (read_config, write_config)
             };
 
-        let write_connection = write_config.build_connect_options(database_path);
+        let mut write_connection = write_config.build_connect_options(database_path);
+        let mut read_connection = read_config.build_connect_options(database_path);
+        if let Some(key) = database_pool_config.encryption_key.as_ref() {
+            if !matches!(database_pool_config.sqlite_config.mode, SqliteMode::Memory) {
+                write_connection = key.apply(write_connection);
+                read_connection = key.apply(read_connection);
+            }
+        }
         debug!("Write connection: {:?}", write_connection);
 
         let write_pool = SqlitePoolOptions::new()
@@ -678,7 +715,6 @@ impl DBConnection {
             .connect_with(write_connection)
             .await?;
 
-        let read_connection = read_config.build_connect_options(database_path);
         debug!("Read connection: {:?}", read_connection);
 
         let read_pool = SqlitePoolOptions::new()
@@ -834,83 +870,110 @@ impl DBConnection {
     pub fn writer(&self) -> &DatabaseWriter {
         &self.writer
     }
+
+    /// Encoding large blobs are written in
+    pub fn blob_format(&self) -> BlobFormat {
+        self.blob_format
+    }
 }
 
-pub fn parse_required_datetime(
-    row: &SqliteRow,
-    column: &str,
-) -> Result<OffsetDateTime, sqlx::Error> {
-    let date_str: String = row.get(column);
-    OffsetDateTime::parse(&date_str, &Rfc3339).map_err(|e| sqlx::Error::ColumnDecode {
-        index: column.to_string(),
-        source: Box::new(e),
-    })
+/// Decode an aggregate count column, queries that don't select it or a `NULL` aggregate read
+/// as 0 while a value of the wrong type is still an error
+pub fn parse_optional_count(row: &SqliteRow, column: &str) -> Result<u64, sqlx::Error> {
+    match row.try_get::<Option<i64>, _>(column) {
+        Ok(count) => Ok(count.unwrap_or(0) as u64),
+        Err(sqlx::Error::ColumnNotFound(_)) => Ok(0),
+        Err(e) => Err(e),
+    }
 }
 
-pub fn parse_optional_datetime(
-    row: &SqliteRow,
-    column: &str,
-) -> Result<Option<OffsetDateTime>, sqlx::Error> {
-    row.get::<Option<String>, _>(column)
-        .map(|s| OffsetDateTime::parse(&s, &Rfc3339))
-        .transpose()
-        .map_err(|e| sqlx::Error::ColumnDecode {
-            index: column.to_string(),
-            source: Box::new(e),
-        })
+/// First byte of a CBOR blob. JSON text can't start with it, so untagged blobs are JSON.
+const CBOR_BLOB_TAG: u8 = 0x01;
+
+/// A blob encoded for writing, JSON stays `TEXT` so sqlite's JSON functions still work on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodedBlob {
+    Json(String),
+    Cbor(Vec<u8>),
 }
 
-pub fn parse_optional_sqlite_datetime(
-    row: &SqliteRow,
-    column: &str,
-) -> Result<Option<OffsetDateTime>, sqlx::Error> {
-    row.get::<Option<String>, _>(column)
-        .map(|s| {
-            // SQLite datetime format: "YYYY-MM-DD HH:MM:SS"
-            // Parse as UTC since SQLite doesn't include timezone info
-            let format =
-                time::format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]")
-                    .map_err(|e| sqlx::Error::ColumnDecode {
-                        index: column.to_string(),
-                        source: Box::new(e),
-                    })?;
-
-            let pdt = time::PrimitiveDateTime::parse(&s, &format).map_err(|e| {
-                sqlx::Error::ColumnDecode {
-                    index: column.to_string(),
-                    source: Box::new(e),
-                }
-            })?;
+impl Type<Sqlite> for EncodedBlob {
+    fn type_info() -> SqliteTypeInfo {
+        <Vec<u8> as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <String as Type<Sqlite>>::compatible(ty) || <Vec<u8> as Type<Sqlite>>::compatible(ty)
+    }
+}
 
-            Ok(pdt.assume_utc())
+impl<'q> Encode<'q, Sqlite> for EncodedBlob {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Sqlite as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        match self {
+            EncodedBlob::Json(json) => <String as Encode<'q, Sqlite>>::encode_by_ref(json, buf),
+            EncodedBlob::Cbor(bytes) => <Vec<u8> as Encode<'q, Sqlite>>::encode_by_ref(bytes, buf),
+        }
+    }
+
+    fn produces(&self) -> Option<SqliteTypeInfo> {
+        Some(match self {
+            EncodedBlob::Json(_) => <String as Type<Sqlite>>::type_info(),
+            EncodedBlob::Cbor(_) => <Vec<u8> as Type<Sqlite>>::type_info(),
         })
-        .transpose()
+    }
 }
 
-pub fn parse_optional_blob_json<T>(row: &SqliteRow, column: &str) -> Result<Option<T>, sqlx::Error>
+/// Encode a value for a blob column, CBOR gets the tag byte that tells it apart on read
+pub fn encode_blob<T>(value: &T, format: BlobFormat) -> Result<EncodedBlob, sqlx::Error>
 where
-    T: serde::de::DeserializeOwned,
+    T: serde::Serialize + ?Sized,
 {
-    let bytes: Option<Vec<u8>> = row.get(column);
-    match bytes {
-        Some(data) => {
-            let parsed = serde_json::from_slice(&data).map_err(|e| sqlx::Error::ColumnDecode {
-                index: column.to_string(),
-                source: Box::new(e),
-            })?;
-            Ok(Some(parsed))
+    match format {
+        BlobFormat::Json => serde_json::to_string(value)
+            .map(EncodedBlob::Json)
+            .map_err(|e| sqlx::Error::Encode(Box::new(e))),
+        BlobFormat::Cbor => {
+            let mut bytes = vec![CBOR_BLOB_TAG];
+            serde_cbor::to_writer(&mut bytes, value)
+                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+            Ok(EncodedBlob::Cbor(bytes))
         }
-        None => Ok(None),
     }
 }
 
+/// Decode a nullable blob column written as JSON or tagged CBOR. Only SQL `NULL` is `None`, a
+/// value that is present but can't be decoded (including an empty blob) is a `ColumnDecode`
+/// error naming the column.
+pub fn parse_optional_blob_json<T>(row: &SqliteRow, column: &str) -> Result<Option<T>, sqlx::Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let bytes: Option<Vec<u8>> = row.try_get(column)?;
+    bytes.map(|data| decode_blob(&data, column)).transpose()
+}
+
 pub fn parse_required_blob_json<T>(row: &SqliteRow, column: &str) -> Result<T, sqlx::Error>
 where
     T: serde::de::DeserializeOwned,
 {
-    let bytes: Vec<u8> = row.get(column);
-    serde_json::from_slice(&bytes).map_err(|e| sqlx::Error::ColumnDecode {
+    let bytes: Vec<u8> = row.try_get(column)?;
+    decode_blob(&bytes, column)
+}
+
+/// Decode blob bytes read outside a row helper, e.g. by `query_scalar`
+pub fn decode_blob<T>(data: &[u8], column: &str) -> Result<T, sqlx::Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let decoded = match data.split_first() {
+        Some((&CBOR_BLOB_TAG, cbor)) => serde_cbor::from_slice(cbor).map_err(BoxDynError::from),
+        _ => serde_json::from_slice(data).map_err(BoxDynError::from),
+    };
+    decoded.map_err(|source| sqlx::Error::ColumnDecode {
         index: column.to_string(),
-        source: Box::new(e),
+        source,
     })
 }
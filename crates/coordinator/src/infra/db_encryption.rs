@@ -0,0 +1,283 @@
+//! Optional SQLCipher at-rest encryption of the sqlite databases, enabled by the `sqlcipher`
+//! cargo feature which builds sqlx against a bundled SQLCipher.
+use anyhow::anyhow;
+use log::info;
+use secrecy::ExposeSecret;
+use sqlx::{sqlite::SqliteConnectOptions, ConnectOptions, Connection, Row, SqliteConnection};
+use std::{fmt, fs, path::Path, sync::Arc};
+use zeroize::Zeroizing;
+
+use crate::{
+    infra::secrets::{load_key_material, secret_backend},
+    DBSettings, SecretsSettings,
+};
+
+/// Raw 32 byte SQLCipher key, hex encoded for `PRAGMA key` and zeroized on drop
+#[derive(Clone)]
+pub struct DbEncryptionKey(Arc<Zeroizing<String>>);
+
+impl fmt::Debug for DbEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DbEncryptionKey([REDACTED])")
+    }
+}
+
+impl DbEncryptionKey {
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Self(Arc::new(Zeroizing::new(hex::encode(bytes))))
+    }
+
+    /// Raw key literal, SQLCipher skips its passphrase KDF for keys in this form
+    fn key_literal(&self) -> Zeroizing<String> {
+        Zeroizing::new(format!("\"x'{}'\"", self.0.as_str()))
+    }
+
+    /// sqlx always issues `PRAGMA key` before any other pragma, as SQLCipher requires
+    pub fn apply(&self, options: SqliteConnectOptions) -> SqliteConnectOptions {
+        options.pragma("key", self.key_literal().to_string())
+    }
+}
+
+/// Load the database key configured in `db_settings`. Fails when the build and the config
+/// disagree so the coordinator never silently runs on a plaintext database.
+pub fn load_db_encryption_key(
+    settings: &DBSettings,
+) -> Result<Option<DbEncryptionKey>, anyhow::Error> {
+    let Some(secrets) = settings.encryption_key.as_ref() else {
+        if cfg!(feature = "sqlcipher") {
+            return Err(anyhow!(
+                "built with the sqlcipher feature but db_settings.encryption_key is not set, \
+                 refusing to open the databases unencrypted"
+            ));
+        }
+        return Ok(None);
+    };
+    if !cfg!(feature = "sqlcipher") {
+        return Err(anyhow!(
+            "db_settings.encryption_key is set but the coordinator was built without the \
+             sqlcipher feature"
+        ));
+    }
+
+    let key_file = match (secrets, settings.encryption_key_file.as_deref()) {
+        (SecretsSettings::File, None) => {
+            return Err(anyhow!(
+            "db_settings.encryption_key uses the file backend but encryption_key_file is not set"
+        ))
+        }
+        (_, key_file) => key_file.unwrap_or_default(),
+    };
+    let backend = secret_backend(secrets, key_file);
+    let key_material = load_key_material(backend.as_ref())?;
+    info!(
+        "Database encryption key loaded from {} secrets backend",
+        backend.name()
+    );
+
+    Ok(Some(DbEncryptionKey::from_bytes(
+        key_material.expose_secret(),
+    )))
+}
+
+/// Re-encrypt an existing plaintext database in place: export it into an encrypted copy, check
+/// that every table kept its row count, then swap the copy over the original. The coordinator
+/// must be stopped while this runs. Returns the row count of each table.
+pub async fn encrypt_database(
+    database_path: &str,
+    key: &DbEncryptionKey,
+) -> Result<Vec<(String, i64)>, anyhow::Error> {
+    let encrypted_path = format!("{}.encrypting", database_path);
+    if Path::new(&encrypted_path).exists() {
+        fs::remove_file(&encrypted_path)?;
+    }
+
+    let mut plaintext = SqliteConnectOptions::new()
+        .filename(database_path)
+        .connect()
+        .await?;
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+        .execute(&mut plaintext)
+        .await?;
+    let attach = Zeroizing::new(format!(
+        "ATTACH DATABASE '{}' AS encrypted KEY {};",
+        encrypted_path.replace('\'', "''"),
+        key.key_literal().as_str()
+    ));
+    sqlx::query(attach.as_str()).execute(&mut plaintext).await?;
+    sqlx::query("SELECT sqlcipher_export('encrypted');")
+        .execute(&mut plaintext)
+        .await?;
+    sqlx::query("DETACH DATABASE encrypted;")
+        .execute(&mut plaintext)
+        .await?;
+    let expected = table_row_counts(&mut plaintext).await?;
+    plaintext.close().await?;
+
+    let mut encrypted = key
+        .apply(SqliteConnectOptions::new().filename(&encrypted_path))
+        .connect()
+        .await?;
+    let exported = table_row_counts(&mut encrypted).await;
+    encrypted.close().await?;
+
+    match exported {
+        Ok(exported) if exported == expected => {}
+        Ok(_) => {
+            fs::remove_file(&encrypted_path)?;
+            return Err(anyhow!(
+                "row counts of the encrypted copy don't match, {} left untouched",
+                database_path
+            ));
+        }
+        Err(e) => {
+            fs::remove_file(&encrypted_path)?;
+            return Err(anyhow!(
+                "failed to read the encrypted copy, {} left untouched: {}",
+                database_path,
+                e
+            ));
+        }
+    }
+
+    fs::rename(&encrypted_path, database_path)?;
+    // Stale WAL/SHM files belong to the plaintext database
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{}", database_path, suffix));
+    }
+
+    Ok(expected)
+}
+
+async fn table_row_counts(
+    connection: &mut SqliteConnection,
+) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    let tables: Vec<String> = sqlx::query(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(&mut *connection)
+    .await?
+    .iter()
+    .map(|row| row.get("name"))
+    .collect();
+
+    let mut counts = Vec::with_capacity(tables.len());
+    for table in tables {
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM \"{}\"",
+            table.replace('"', "\"\"")
+        ))
+        .fetch_one(&mut *connection)
+        .await?;
+        counts.push((table, count));
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coordinator_core::Sats;
+
+    #[test]
+    fn test_encryption_key_required_matches_build() {
+        let mut settings = DBSettings::default();
+        let result = load_db_encryption_key(&settings);
+        assert_eq!(result.is_err(), cfg!(feature = "sqlcipher"));
+
+        settings.encryption_key = Some(SecretsSettings::File);
+        let err = load_db_encryption_key(&settings).unwrap_err();
+        if cfg!(feature = "sqlcipher") {
+            assert!(err.to_string().contains("encryption_key_file"));
+        } else {
+            assert!(err.to_string().contains("without the sqlcipher feature"));
+        }
+    }
+
+    #[test]
+    fn test_encryption_key_debug_is_redacted() {
+        let key = DbEncryptionKey::from_bytes(&[7u8; 32]);
+        assert_eq!(format!("{:?}", key), "DbEncryptionKey([REDACTED])");
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    mod sqlcipher {
+        use super::*;
+        use crate::{
+            domain::{Competition, CreateEvent},
+            CompetitionStore, DBConnection, DatabasePoolConfig, DatabaseType,
+        };
+        use time::OffsetDateTime;
+        use uuid::Uuid;
+
+        fn test_dir() -> String {
+            let dir = std::env::temp_dir().join(format!("coordinator-db-{}", Uuid::now_v7()));
+            fs::create_dir_all(&dir).unwrap();
+            dir.to_string_lossy().to_string()
+        }
+
+        async fn open_db(dir: &str, key: Option<DbEncryptionKey>) -> DBConnection {
+            let pool_config = DatabasePoolConfig {
+                encryption_key: key,
+                ..Default::default()
+            };
+            DBConnection::new(dir, "competitions", pool_config, DatabaseType::Competitions)
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_store_round_trip_on_encrypted_db() {
+            let dir = test_dir();
+            let key = DbEncryptionKey::from_bytes(&[7u8; 32]);
+            let db = open_db(&dir, Some(key)).await;
+            let store = CompetitionStore::new(db.clone());
+            let competition = Competition::new(&CreateEvent::fixture(OffsetDateTime::now_utc()));
+
+            store
+                .add_competition_with_tickets(competition.clone(), vec![])
+                .await
+                .unwrap();
+            let stored = store.get_competition(competition.id).await.unwrap();
+            assert_eq!(stored.id, competition.id);
+            db.close().await;
+
+            // Without the key the file is unreadable
+            let mut plaintext = SqliteConnectOptions::new()
+                .filename(format!("{}/competitions.db", dir))
+                .connect()
+                .await
+                .unwrap();
+            assert!(table_row_counts(&mut plaintext).await.is_err());
+            fs::remove_dir_all(&dir).unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_encrypt_existing_plaintext_db() {
+            let dir = test_dir();
+            let competition = Competition::new(&CreateEvent::fixture(OffsetDateTime::now_utc()));
+            let db = open_db(&dir, None).await;
+            CompetitionStore::new(db.clone())
+                .add_competition_with_tickets(competition.clone(), vec![])
+                .await
+                .unwrap();
+            db.close().await;
+
+            let key = DbEncryptionKey::from_bytes(&[9u8; 32]);
+            let counts = encrypt_database(&format!("{}/competitions.db", dir), &key)
+                .await
+                .unwrap();
+            assert!(counts
+                .iter()
+                .any(|(table, count)| table == "competitions" && *count == 1));
+
+            let db = open_db(&dir, Some(key)).await;
+            let stored = CompetitionStore::new(db.clone())
+                .get_competition(competition.id)
+                .await
+                .unwrap();
+            assert_eq!(stored.id, competition.id);
+            db.close().await;
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Versioned schema migrations for the competitions and users databases.
+//!
+//! sqlx records every applied migration's version, checksum and install time in
+//! `_sqlx_migrations`, applies pending ones in version order each inside its own transaction,
+//! and refuses to run when an applied migration's checksum no longer matches the file. On top
+//! of that this adopts databases initialized before migrations were tracked, and lists what
+//! would be applied for `db migrate --dry-run`.
+use log::warn;
+use serde::Serialize;
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migration, Migrator},
+    SqlitePool,
+};
+use std::collections::HashMap;
+
+use super::db::DatabaseType;
+
+static COMPETITIONS_MIGRATOR: Migrator = sqlx::migrate!("./migrations/competitions");
+static USERS_MIGRATOR: Migrator = sqlx::migrate!("./migrations/users");
+
+/// Where sqlx records applied migrations
+const MIGRATIONS_TABLE: &str = "_sqlx_migrations";
+
+/// A migration that hasn't been applied to the database yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+    pub sql: String,
+}
+
+impl From<&Migration> for PendingMigration {
+    fn from(migration: &Migration) -> Self {
+        Self {
+            version: migration.version,
+            description: migration.description.to_string(),
+            sql: migration.sql.to_string(),
+        }
+    }
+}
+
+fn migrator(db_type: &DatabaseType) -> &'static Migrator {
+    match db_type {
+        DatabaseType::Competitions => &COMPETITIONS_MIGRATOR,
+        DatabaseType::Users => &USERS_MIGRATOR,
+    }
+}
+
+/// A table created by the first migration, its presence without any migration history means
+/// the schema was created before migrations were tracked
+fn baseline_table(db_type: &DatabaseType) -> &'static str {
+    match db_type {
+        DatabaseType::Competitions => "competitions",
+        DatabaseType::Users => "user",
+    }
+}
+
+/// Apply every pending migration, adopting an untracked but initialized schema first
+pub async fn run_migrations(pool: &SqlitePool, db_type: &DatabaseType) -> Result<(), MigrateError> {
+    let migrator = migrator(db_type);
+    adopt_existing_schema(pool, migrator, baseline_table(db_type)).await?;
+    migrator.run(pool).await
+}
+
+/// Migrations `run_migrations` would apply, in order, without changing the database. Fails the
+/// same way the real run would when an applied migration was edited or is no longer known.
+pub async fn pending_migrations(
+    pool: &SqlitePool,
+    db_type: &DatabaseType,
+) -> Result<Vec<PendingMigration>, MigrateError> {
+    let migrator = migrator(db_type);
+    let applied = match applied_migrations(pool).await? {
+        Some(applied) => applied,
+        // Would be adopted as the baseline instead of re-created
+        None if table_exists(pool, baseline_table(db_type)).await? => {
+            let baseline = baseline_migration(migrator)?;
+            HashMap::from([(baseline.version, baseline.checksum.to_vec())])
+        }
+        None => HashMap::new(),
+    };
+
+    let up_migrations: Vec<&Migration> = migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .collect();
+    if let Some(version) = applied
+        .keys()
+        .find(|version| !up_migrations.iter().any(|m| m.version == **version))
+    {
+        return Err(MigrateError::VersionMissing(*version));
+    }
+
+    let mut pending = Vec::new();
+    for migration in up_migrations {
+        match applied.get(&migration.version) {
+            Some(checksum) if checksum.as_slice() != &*migration.checksum => {
+                return Err(MigrateError::VersionMismatch(migration.version));
+            }
+            Some(_) => {}
+            None => pending.push(PendingMigration::from(migration)),
+        }
+    }
+    Ok(pending)
+}
+
+/// Record the first migration as applied when its tables already exist but nothing tracks it,
+/// so it isn't run again over the existing schema
+async fn adopt_existing_schema(
+    pool: &SqlitePool,
+    migrator: &Migrator,
+    baseline_table: &str,
+) -> Result<(), MigrateError> {
+    if table_exists(pool, MIGRATIONS_TABLE).await? || !table_exists(pool, baseline_table).await? {
+        return Ok(());
+    }
+
+    let baseline = baseline_migration(migrator)?;
+    warn!(
+        "Found table {} without migration history, adopting it as migration {} ({})",
+        baseline_table, baseline.version, baseline.description
+    );
+
+    let mut conn = pool.acquire().await.map_err(MigrateError::Execute)?;
+    conn.ensure_migrations_table().await?;
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+         VALUES (?, ?, TRUE, ?, 0)",
+    )
+    .bind(baseline.version)
+    .bind(&*baseline.description)
+    .bind(&*baseline.checksum)
+    .execute(&mut *conn)
+    .await
+    .map_err(MigrateError::Execute)?;
+    Ok(())
+}
+
+fn baseline_migration(migrator: &Migrator) -> Result<&Migration, MigrateError> {
+    migrator
+        .iter()
+        .find(|migration| !migration.migration_type.is_down_migration())
+        .ok_or_else(|| MigrateError::Source("no migrations to adopt as the baseline".into()))
+}
+
+/// Checksum of every applied migration by version, `None` when nothing has been tracked yet
+async fn applied_migrations(
+    pool: &SqlitePool,
+) -> Result<Option<HashMap<i64, Vec<u8>>>, MigrateError> {
+    if !table_exists(pool, MIGRATIONS_TABLE).await? {
+        return Ok(None);
+    }
+
+    let applied: Vec<(i64, Vec<u8>)> =
+        sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .map_err(MigrateError::Execute)?;
+    Ok(Some(applied.into_iter().collect()))
+}
+
+async fn table_exists(pool: &SqlitePool, table: &str) -> Result<bool, MigrateError> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+    )
+    .bind(table)
+    .fetch_one(pool)
+    .await
+    .map(|count| count > 0)
+    .map_err(MigrateError::Execute)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> SqlitePool {
+        // A single connection so every query sees the same in-memory database
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    fn latest(db_type: &DatabaseType) -> &'static Migration {
+        migrator(db_type)
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .last()
+            .unwrap()
+    }
+
+    fn down_sql(db_type: &DatabaseType, version: i64) -> &'static str {
+        &migrator(db_type)
+            .iter()
+            .find(|m| m.version == version && m.migration_type.is_down_migration())
+            .unwrap()
+            .sql
+    }
+
+    #[tokio::test]
+    async fn test_fresh_database_applies_every_migration() {
+        let pool = memory_pool().await;
+        let db_type = DatabaseType::Competitions;
+        let all = pending_migrations(&pool, &db_type).await.unwrap();
+        assert!(!all.is_empty());
+        assert!(all.windows(2).all(|w| w[0].version < w[1].version));
+
+        run_migrations(&pool, &db_type).await.unwrap();
+        assert!(pending_migrations(&pool, &db_type)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let applied = applied_migrations(&pool).await.unwrap().unwrap();
+        assert_eq!(applied.len(), all.len());
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_applies_only_the_new_migration() {
+        let pool = memory_pool().await;
+        let db_type = DatabaseType::Competitions;
+        run_migrations(&pool, &db_type).await.unwrap();
+
+        // Roll the database back to before the latest migration existed
+        let latest = latest(&db_type);
+        sqlx::raw_sql(down_sql(&db_type, latest.version))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = ?")
+            .bind(latest.version)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let pending = pending_migrations(&pool, &db_type).await.unwrap();
+        assert_eq!(pending, vec![PendingMigration::from(latest)]);
+
+        run_migrations(&pool, &db_type).await.unwrap();
+        assert!(pending_migrations(&pool, &db_type)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_edited_migration_is_refused() {
+        let pool = memory_pool().await;
+        let db_type = DatabaseType::Users;
+        run_migrations(&pool, &db_type).await.unwrap();
+
+        let baseline = baseline_migration(migrator(&db_type)).unwrap();
+        sqlx::query("UPDATE _sqlx_migrations SET checksum = X'00' WHERE version = ?")
+            .bind(baseline.version)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            run_migrations(&pool, &db_type).await,
+            Err(MigrateError::VersionMismatch(version)) if version == baseline.version
+        ));
+        assert!(matches!(
+            pending_migrations(&pool, &db_type).await,
+            Err(MigrateError::VersionMismatch(version)) if version == baseline.version
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_untracked_schema_is_adopted() {
+        let pool = memory_pool().await;
+        let db_type = DatabaseType::Competitions;
+        let baseline = baseline_migration(migrator(&db_type)).unwrap();
+        sqlx::raw_sql(&*baseline.sql).execute(&pool).await.unwrap();
+
+        let pending = pending_migrations(&pool, &db_type).await.unwrap();
+        assert!(pending.iter().all(|m| m.version != baseline.version));
+
+        run_migrations(&pool, &db_type).await.unwrap();
+        let applied = applied_migrations(&pool).await.unwrap().unwrap();
+        assert_eq!(
+            applied.get(&baseline.version),
+            Some(&baseline.checksum.to_vec())
+        );
+        assert_eq!(applied.len(), pending.len() + 1);
+    }
+}
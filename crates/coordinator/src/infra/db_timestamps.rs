@@ -0,0 +1,115 @@
+//! Timestamp codec shared by the competitions and users stores. Timestamps are written as
+//! RFC3339 in UTC, reads also accept the `YYYY-MM-DD HH:MM:SS` (UTC) form SQLite's
+//! `datetime()` stamped older ticket and entry rows with. A value matching neither is a decode
+//! error naming the column, never a default.
+//!
+//! Timestamps stamped inside SQL use `strftime('%Y-%m-%dT%H:%M:%SZ', 'now')` so they compare
+//! as strings against the ones written from here.
+use sqlx::{sqlite::SqliteRow, Row};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+/// Layout SQLite's `datetime()` produces, always in UTC
+const SQLITE_DATETIME_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]:[second]";
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid timestamp {value:?}, expected RFC3339 or YYYY-MM-DD HH:MM:SS")]
+pub struct TimestampError {
+    pub value: String,
+}
+
+pub fn format_timestamp(timestamp: OffsetDateTime) -> Result<String, time::error::Format> {
+    timestamp.to_offset(UtcOffset::UTC).format(&Rfc3339)
+}
+
+pub fn parse_timestamp(value: &str) -> Result<OffsetDateTime, TimestampError> {
+    if let Ok(timestamp) = OffsetDateTime::parse(value, &Rfc3339) {
+        return Ok(timestamp.to_offset(UtcOffset::UTC));
+    }
+
+    time::format_description::parse(SQLITE_DATETIME_FORMAT)
+        .ok()
+        .and_then(|format| PrimitiveDateTime::parse(value, &format).ok())
+        .map(PrimitiveDateTime::assume_utc)
+        .ok_or_else(|| TimestampError {
+            value: value.to_string(),
+        })
+}
+
+pub fn parse_required_datetime(
+    row: &SqliteRow,
+    column: &str,
+) -> Result<OffsetDateTime, sqlx::Error> {
+    let value: String = row.try_get(column)?;
+    decode_timestamp(&value, column)
+}
+
+pub fn parse_optional_datetime(
+    row: &SqliteRow,
+    column: &str,
+) -> Result<Option<OffsetDateTime>, sqlx::Error> {
+    row.try_get::<Option<String>, _>(column)?
+        .map(|value| decode_timestamp(&value, column))
+        .transpose()
+}
+
+fn decode_timestamp(value: &str, column: &str) -> Result<OffsetDateTime, sqlx::Error> {
+    parse_timestamp(value).map_err(|e| sqlx::Error::ColumnDecode {
+        index: column.to_string(),
+        source: Box::new(e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Connection, SqliteConnection};
+    use time::macros::datetime;
+
+    #[test]
+    fn test_reads_legacy_and_rfc3339_timestamps() {
+        assert_eq!(
+            parse_timestamp("2026-01-02 03:04:05").unwrap(),
+            datetime!(2026-01-02 03:04:05 UTC)
+        );
+        assert_eq!(
+            parse_timestamp("2026-01-02T05:04:05.5+02:00").unwrap(),
+            datetime!(2026-01-02 03:04:05.5 UTC)
+        );
+        assert!(parse_timestamp("2026-01-02").is_err());
+        assert!(parse_timestamp("").is_err());
+    }
+
+    #[test]
+    fn test_writes_rfc3339_in_utc() {
+        let timestamp = datetime!(2026-01-02 05:04:05 +02:00);
+        let formatted = format_timestamp(timestamp).unwrap();
+        assert_eq!(formatted, "2026-01-02T03:04:05Z");
+        assert_eq!(parse_timestamp(&formatted).unwrap(), timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_unparseable_column_is_a_decode_error() {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        let row = sqlx::query(
+            "SELECT '2026-01-02 03:04:05' AS legacy,
+                    strftime('%Y-%m-%dT%H:%M:%SZ', 'now') AS stamped,
+                    NULL AS missing,
+                    'tomorrow' AS broken",
+        )
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            parse_required_datetime(&row, "legacy").unwrap(),
+            datetime!(2026-01-02 03:04:05 UTC)
+        );
+        assert!(parse_required_datetime(&row, "stamped").is_ok());
+        assert_eq!(parse_optional_datetime(&row, "missing").unwrap(), None);
+        assert!(parse_required_datetime(&row, "missing").is_err());
+        match parse_optional_datetime(&row, "broken").unwrap_err() {
+            sqlx::Error::ColumnDecode { index, .. } => assert_eq!(index, "broken"),
+            other => panic!("expected a column decode error, got {:?}", other),
+        }
+    }
+}
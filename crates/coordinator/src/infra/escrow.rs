@@ -2,7 +2,16 @@
 use crate::infra::bitcoin::Bitcoin;
 use anyhow::anyhow;
 use bdk_wallet::{
-    bitcoin::{psbt::raw::ProprietaryKey, Amount, OutPoint, PublicKey, Transaction},
+    bitcoin::{
+        absolute::LockTime,
+        ecdsa,
+        hashes::Hash,
+        psbt::raw::ProprietaryKey,
+        secp256k1::{Message, Secp256k1, SecretKey},
+        sighash::{EcdsaSighashType, SighashCache},
+        transaction::Version,
+        Amount, OutPoint, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    },
     miniscript::Descriptor,
     SignOptions,
 };
@@ -11,6 +20,9 @@ use log::debug;
 use std::{str::FromStr, sync::Arc};
 use uuid::Uuid;
 
+/// Relative timelock, in blocks, on the escrow's player + preimage branch
+pub const ESCROW_CSV_BLOCKS: u16 = 144;
+
 pub async fn generate_escrow_tx(
     bitcoin: Arc<dyn Bitcoin>,
     ticket_id: Uuid,
@@ -20,7 +32,8 @@ pub async fn generate_escrow_tx(
 ) -> Result<Transaction, anyhow::Error> {
     let fee_rates = bitcoin.get_estimated_fee_rates().await?;
 
-    // Choose the fee rate for 2-block confirmation
+    // Choose the fee rate for next-block confirmation, the escrow has to confirm before the
+    // hold invoice can be settled
     // TODO(@tee8z): Make this configurable
     let esplora_fee_rate = fee_rates.get(&1u16).cloned().unwrap_or(1.0);
     debug!("Esplora fee rate: {} sats/vB", esplora_fee_rate);
@@ -59,6 +72,10 @@ pub async fn generate_escrow_tx(
 
     let final_tx = psbt.extract_tx()?;
 
+    // The wallet picks the inputs and change, make sure what it signed still locks the ticket's
+    // amount to this ticket's escrow script before it's stored and handed to the player
+    check_escrow_amount(&final_tx, &escrow_descriptor, amount_sats, ticket_id)?;
+
     debug!(
         "Generated escrow transaction with ID: {} for ticket: {}",
         final_tx.compute_wtxid(),
@@ -81,8 +98,8 @@ pub fn create_escrow_descriptor(
     // We may add another spending condition later that allows the coordinator to reclaim the utxo after a certain period of time.
     // Leaving out for now to avoid that complexity
     let descriptor_str = format!(
-        "wsh(or_d(multi(2,{},{}),and_v(v:pk({}),and_v(v:sha256({}),older(144)))))",
-        coordinator_pubkey, user_pubkey, user_pubkey, payment_hash_hex
+        "wsh(or_d(multi(2,{},{}),and_v(v:pk({}),and_v(v:sha256({}),older({})))))",
+        coordinator_pubkey, user_pubkey, user_pubkey, payment_hash_hex, ESCROW_CSV_BLOCKS
     );
 
     Descriptor::from_str(&descriptor_str)
@@ -109,6 +126,182 @@ pub fn get_escrow_outpoint(
     Err(anyhow!("Escrow output not found for transaction {}", txid))
 }
 
+/// Fail unless the escrow transaction locks exactly `amount_sats` to the ticket's escrow script
+fn check_escrow_amount(
+    transaction: &Transaction,
+    descriptor: &Descriptor<PublicKey>,
+    amount_sats: u64,
+    ticket_id: Uuid,
+) -> Result<(), anyhow::Error> {
+    let (_, escrow_output) = find_escrow_output(transaction, descriptor)?;
+    if escrow_output.value != Amount::from_sat(amount_sats) {
+        return Err(anyhow!(
+            "Escrow transaction {} locks {} instead of {} for ticket {}",
+            transaction.compute_txid(),
+            escrow_output.value,
+            Amount::from_sat(amount_sats),
+            ticket_id
+        ));
+    }
+    Ok(())
+}
+
+/// Find the escrow output paying to `descriptor` in the ticket's escrow transaction
+pub fn find_escrow_output(
+    transaction: &Transaction,
+    descriptor: &Descriptor<PublicKey>,
+) -> Result<(OutPoint, TxOut), anyhow::Error> {
+    let txid = transaction.compute_txid();
+    let script_pubkey = descriptor.script_pubkey();
+
+    transaction
+        .output
+        .iter()
+        .enumerate()
+        .find(|(_, output)| output.script_pubkey == script_pubkey)
+        .map(|(index, output)| {
+            (
+                OutPoint {
+                    txid,
+                    vout: index as u32,
+                },
+                output.clone(),
+            )
+        })
+        .ok_or_else(|| anyhow!("Escrow output not found for transaction {}", txid))
+}
+
+/// Which branch of the escrow script a reclaim transaction spends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscrowReclaimPath {
+    /// 2-of-2 of the coordinator and the player, spendable as soon as the escrow confirms
+    Cooperative,
+    /// The player's key and the ticket's payment preimage, once the escrow is
+    /// `ESCROW_CSV_BLOCKS` deep
+    Preimage,
+}
+
+/// An escrow output to send back to a player
+pub struct EscrowReclaim {
+    pub descriptor: Descriptor<PublicKey>,
+    pub outpoint: OutPoint,
+    pub prevout: TxOut,
+    pub destination: ScriptBuf,
+    pub fee_rate: FeeRate,
+}
+
+/// Build and sign the transaction spending the escrow output to `destination` through `path`.
+/// The cooperative branch is signed by both keys, the preimage branch only by the player.
+pub fn build_escrow_reclaim_tx(
+    reclaim: &EscrowReclaim,
+    path: EscrowReclaimPath,
+    coordinator_key: &SecretKey,
+    user_key: &SecretKey,
+    preimage: &[u8; 32],
+) -> Result<Transaction, anyhow::Error> {
+    let witness_script = reclaim.descriptor.explicit_script()?;
+    let sequence = match path {
+        EscrowReclaimPath::Cooperative => Sequence::ENABLE_RBF_NO_LOCKTIME,
+        EscrowReclaimPath::Preimage => Sequence::from_height(ESCROW_CSV_BLOCKS),
+    };
+    let mut transaction = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: reclaim.outpoint,
+            script_sig: ScriptBuf::new(),
+            sequence,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: reclaim.prevout.value,
+            script_pubkey: reclaim.destination.clone(),
+        }],
+    };
+
+    // Size the fee with the largest possible signatures before signing for real
+    let placeholder_signatures = vec![vec![0u8; 73]; 2];
+    transaction.input[0].witness =
+        reclaim_witness(path, &witness_script, &placeholder_signatures, preimage);
+    let fee = reclaim
+        .fee_rate
+        .fee_wu(transaction.weight())
+        .ok_or_else(|| anyhow!("Fee overflow for escrow reclaim of {}", reclaim.outpoint))?;
+    let value = reclaim
+        .prevout
+        .value
+        .checked_sub(fee)
+        .filter(|value| *value >= reclaim.destination.minimal_non_dust())
+        .ok_or_else(|| {
+            anyhow!(
+                "Escrow output {} of {} can't cover the {} reclaim fee",
+                reclaim.outpoint,
+                reclaim.prevout.value,
+                fee
+            )
+        })?;
+    transaction.output[0].value = value;
+
+    let secp = Secp256k1::new();
+    let sighash = SighashCache::new(&transaction).p2wsh_signature_hash(
+        0,
+        &witness_script,
+        reclaim.prevout.value,
+        EcdsaSighashType::All,
+    )?;
+    let message = Message::from_digest(sighash.to_byte_array());
+    let sign = |key: &SecretKey| {
+        ecdsa::Signature {
+            signature: secp.sign_ecdsa(&message, key),
+            sighash_type: EcdsaSighashType::All,
+        }
+        .to_vec()
+    };
+    // Multisig signatures go in the same order as the keys in the script
+    let signatures = match path {
+        EscrowReclaimPath::Cooperative => vec![sign(coordinator_key), sign(user_key)],
+        EscrowReclaimPath::Preimage => vec![sign(user_key)],
+    };
+    transaction.input[0].witness = reclaim_witness(path, &witness_script, &signatures, preimage);
+
+    debug!(
+        "Built {:?} escrow reclaim {} spending {}",
+        path,
+        transaction.compute_txid(),
+        reclaim.outpoint
+    );
+
+    Ok(transaction)
+}
+
+fn reclaim_witness(
+    path: EscrowReclaimPath,
+    witness_script: &ScriptBuf,
+    signatures: &[Vec<u8>],
+    preimage: &[u8; 32],
+) -> Witness {
+    let mut witness = Witness::new();
+    match path {
+        EscrowReclaimPath::Cooperative => {
+            // OP_CHECKMULTISIG dummy, then the coordinator and player signatures
+            witness.push([]);
+            witness.push(&signatures[0]);
+            witness.push(&signatures[1]);
+        }
+        EscrowReclaimPath::Preimage => {
+            // Preimage and player signature for the second branch, then the dummy and two empty
+            // signatures that fail the multisig and select it
+            witness.push(preimage);
+            witness.push(&signatures[0]);
+            witness.push([]);
+            witness.push([]);
+            witness.push([]);
+        }
+    }
+    witness.push(witness_script.as_bytes());
+    witness
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,4 +345,186 @@ mod tests {
         println!("Created descriptor: {}", descriptor);
         println!("Derived address: {}", addr);
     }
+
+    fn reclaim_for(
+        coordinator_key: &SecretKey,
+        user_key: &SecretKey,
+        preimage: &[u8; 32],
+    ) -> EscrowReclaim {
+        let secp = Secp256k1::new();
+        let payment_hash = bdk_wallet::bitcoin::hashes::sha256::Hash::hash(preimage);
+        let descriptor = create_escrow_descriptor(
+            &PublicKey::new(coordinator_key.public_key(&secp)),
+            &PublicKey::new(user_key.public_key(&secp)),
+            &payment_hash.to_byte_array(),
+        )
+        .unwrap();
+        let escrow_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: descriptor.script_pubkey(),
+            }],
+        };
+        let (outpoint, prevout) = find_escrow_output(&escrow_tx, &descriptor).unwrap();
+
+        EscrowReclaim {
+            destination: descriptor.script_pubkey(),
+            descriptor,
+            outpoint,
+            prevout,
+            fee_rate: FeeRate::from_sat_per_vb_unchecked(2),
+        }
+    }
+
+    fn assert_signed_by(
+        transaction: &Transaction,
+        reclaim: &EscrowReclaim,
+        sig: &[u8],
+        key: &SecretKey,
+    ) {
+        let secp = Secp256k1::new();
+        let witness_script = reclaim.descriptor.explicit_script().unwrap();
+        let sighash = SighashCache::new(transaction)
+            .p2wsh_signature_hash(
+                0,
+                &witness_script,
+                reclaim.prevout.value,
+                EcdsaSighashType::All,
+            )
+            .unwrap();
+        let signature = ecdsa::Signature::from_slice(sig).unwrap();
+        secp.verify_ecdsa(
+            &Message::from_digest(sighash.to_byte_array()),
+            &signature.signature,
+            &key.public_key(&secp),
+        )
+        .expect("signature should be valid for the key");
+    }
+
+    #[test]
+    fn test_escrow_tx_must_lock_the_ticket_amount() {
+        let reclaim = reclaim_for(
+            &SecretKey::from_slice(&[1; 32]).unwrap(),
+            &SecretKey::from_slice(&[2; 32]).unwrap(),
+            &[3; 32],
+        );
+        let escrow_tx = |value: u64, script_pubkey: ScriptBuf| Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey,
+            }],
+        };
+        let ticket_id = Uuid::now_v7();
+        let script_pubkey = reclaim.descriptor.script_pubkey();
+
+        assert!(check_escrow_amount(
+            &escrow_tx(10_000, script_pubkey.clone()),
+            &reclaim.descriptor,
+            10_000,
+            ticket_id
+        )
+        .is_ok());
+
+        // The wallet shaved the escrow output, ie. to cover fees
+        let err = check_escrow_amount(
+            &escrow_tx(9_000, script_pubkey),
+            &reclaim.descriptor,
+            10_000,
+            ticket_id,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("instead of"), "{}", err);
+
+        // Paid somewhere other than the ticket's escrow script
+        let err = check_escrow_amount(
+            &escrow_tx(10_000, ScriptBuf::new_op_return([0u8; 4])),
+            &reclaim.descriptor,
+            10_000,
+            ticket_id,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+
+    #[test]
+    fn test_cooperative_reclaim_is_signed_by_both_keys() {
+        let coordinator_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let user_key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let preimage = [3; 32];
+        let reclaim = reclaim_for(&coordinator_key, &user_key, &preimage);
+
+        let transaction = build_escrow_reclaim_tx(
+            &reclaim,
+            EscrowReclaimPath::Cooperative,
+            &coordinator_key,
+            &user_key,
+            &preimage,
+        )
+        .unwrap();
+
+        let input = &transaction.input[0];
+        assert_eq!(input.previous_output, reclaim.outpoint);
+        assert!(!input.sequence.is_relative_lock_time());
+        let witness: Vec<&[u8]> = input.witness.iter().collect();
+        assert_eq!(witness.len(), 4);
+        assert!(witness[0].is_empty());
+        assert_signed_by(&transaction, &reclaim, witness[1], &coordinator_key);
+        assert_signed_by(&transaction, &reclaim, witness[2], &user_key);
+        assert_eq!(
+            witness[3],
+            reclaim.descriptor.explicit_script().unwrap().as_bytes()
+        );
+
+        let value = transaction.output[0].value;
+        assert!(value < reclaim.prevout.value && value > Amount::ZERO);
+    }
+
+    #[test]
+    fn test_preimage_reclaim_waits_for_the_timelock() {
+        let coordinator_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let user_key = SecretKey::from_slice(&[2; 32]).unwrap();
+        let preimage = [3; 32];
+        let reclaim = reclaim_for(&coordinator_key, &user_key, &preimage);
+
+        let transaction = build_escrow_reclaim_tx(
+            &reclaim,
+            EscrowReclaimPath::Preimage,
+            &coordinator_key,
+            &user_key,
+            &preimage,
+        )
+        .unwrap();
+
+        let input = &transaction.input[0];
+        assert_eq!(input.sequence, Sequence::from_height(ESCROW_CSV_BLOCKS));
+        assert_eq!(transaction.version, Version::TWO);
+        let witness: Vec<&[u8]> = input.witness.iter().collect();
+        assert_eq!(witness.len(), 6);
+        assert_eq!(witness[0], preimage.as_slice());
+        assert_signed_by(&transaction, &reclaim, witness[1], &user_key);
+        assert!(witness[2..5].iter().all(|item| item.is_empty()));
+
+        // Nothing is left for a fee on dust sized escrows
+        let dust = EscrowReclaim {
+            prevout: TxOut {
+                value: Amount::from_sat(300),
+                ..reclaim.prevout.clone()
+            },
+            ..reclaim
+        };
+        assert!(build_escrow_reclaim_tx(
+            &dust,
+            EscrowReclaimPath::Preimage,
+            &coordinator_key,
+            &user_key,
+            &preimage,
+        )
+        .is_err());
+    }
 }
@@ -33,6 +33,9 @@ pub enum KeymeldError {
 
     #[error("Keymeld is not enabled")]
     NotEnabled,
+
+    #[error("Keymeld gateway does not support {0}")]
+    Unsupported(String),
 }
 
 /// Status of a keygen session for polling
@@ -92,6 +95,16 @@ pub trait Keymeld: Send + Sync {
         registration_data: &ParticipantRegistrationData,
     ) -> Result<(), KeymeldError>;
 
+    /// Remove a participant's registration from a keygen session that hasn't completed, so they
+    /// can register again. Errors with `KeymeldError::Session` when the session is gone or
+    /// already completed, and `KeymeldError::Unsupported` when the gateway turns the removal
+    /// down for a session it has.
+    async fn remove_participant(
+        &self,
+        session: &DlcKeygenSession,
+        user_id: UserId,
+    ) -> Result<(), KeymeldError>;
+
     /// Wait for keygen to complete and get the aggregate key
     /// Called after all participants have registered
     async fn wait_for_keygen_completion(
@@ -150,6 +163,9 @@ pub struct StoredDlcKeygenSession {
     /// Session secret encrypted with NIP-44 to coordinator's nostr pubkey
     /// This ensures the secret is never stored in plaintext
     pub encrypted_session_secret: String,
+    /// Id of the coordinator key the secret is encrypted to, `None` for the wallet seed key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_key_id: Option<String>,
     /// Aggregate public key bytes (hex encoded)
     #[serde(with = "hex_vec")]
     pub aggregate_key: Vec<u8>,
@@ -164,11 +180,17 @@ impl StoredDlcKeygenSession {
         Self {
             session_id: session.session_id.to_string(),
             encrypted_session_secret,
+            encryption_key_id: None,
             aggregate_key: session.aggregate_key,
             outcome_subset_ids: session.outcome_subset_ids,
         }
     }
 
+    pub fn with_encryption_key_id(mut self, key_id: String) -> Self {
+        self.encryption_key_id = Some(key_id);
+        self
+    }
+
     /// Convert to DlcKeygenSession with the decrypted session secret
     pub fn to_session(&self, session_secret: [u8; 32]) -> DlcKeygenSession {
         DlcKeygenSession {
@@ -570,6 +592,74 @@ impl Keymeld for KeymeldService {
         Ok(())
     }
 
+    async fn remove_participant(
+        &self,
+        session: &DlcKeygenSession,
+        user_id: UserId,
+    ) -> Result<(), KeymeldError> {
+        let client = self.get_client()?;
+
+        info!(
+            "Removing participant {} from keygen session {}",
+            user_id, session.session_id
+        );
+
+        // Checked first so a missing session isn't mistaken for a gateway without removal
+        let restored_session = client
+            .keygen()
+            .restore_session(
+                session.session_id.clone(),
+                SessionCredentials::from_session_secret(&session.session_secret)?,
+            )
+            .await
+            .map_err(|e| {
+                KeymeldError::Session(format!(
+                    "Keygen session {} not found: {}",
+                    session.session_id, e
+                ))
+            })?;
+        if matches!(
+            restored_session.status(),
+            keymeld_sdk::prelude::KeygenStatusKind::Completed
+        ) {
+            return Err(KeymeldError::Session(format!(
+                "Keygen session {} already completed, its participants can't change",
+                session.session_id
+            )));
+        }
+
+        let credentials = SessionCredentials::from_session_secret(&session.session_secret)?;
+        let session_signature = credentials
+            .sign_session_request(&session.session_id.to_string())
+            .map_err(|e| KeymeldError::Session(format!("Failed to sign session request: {}", e)))?;
+
+        let _response: serde_json::Value = client
+            .http()
+            .delete(
+                &format!(
+                    "{}/api/v1/keygen/{}/participants/{}",
+                    self.settings.gateway_url, session.session_id, user_id
+                ),
+                &[("X-Session-Signature", &session_signature)],
+            )
+            .await
+            .map_err(|e| {
+                // The session exists, so the gateway turned down the removal itself
+                error!(
+                    "Keymeld gateway failed removing participant {} from keygen session {}: {}",
+                    user_id, session.session_id, e
+                );
+                KeymeldError::Unsupported(format!("removing keygen participants ({})", e))
+            })?;
+
+        info!(
+            "Participant {} removed from keygen session {}",
+            user_id, session.session_id
+        );
+
+        Ok(())
+    }
+
     async fn get_user_enclave_pubkey(
         &self,
         session: &DlcKeygenSession,
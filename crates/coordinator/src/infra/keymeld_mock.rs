@@ -95,6 +95,15 @@ impl Keymeld for MockKeymeld {
         Ok(())
     }
 
+    async fn remove_participant(
+        &self,
+        _session: &DlcKeygenSession,
+        _user_id: UserId,
+    ) -> Result<(), KeymeldError> {
+        // Mock removal always succeeds
+        Ok(())
+    }
+
     async fn get_user_enclave_pubkey(
         &self,
         _session: &DlcKeygenSession,
@@ -1,7 +1,6 @@
 use anyhow::anyhow;
 use async_trait::async_trait;
 use base64::Engine;
-use bdk_wallet::bitcoin::hashes::{sha256, Hash};
 use futures::StreamExt;
 use lightning_invoice::Bolt11Invoice;
 use log::{debug, info, warn};
@@ -28,6 +27,8 @@ pub struct InvoiceUpdate {
     pub payment_hash: String,
     pub state: InvoiceState,
     pub amt_paid_sat: Option<u64>,
+    /// Position of the invoice in lnd's add order, where a catch-up sweep resumes from
+    pub add_index: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +37,10 @@ pub struct PaymentUpdate {
     pub status: PaymentStatus,
     pub failure_reason: Option<String>,
     pub preimage: Option<String>,
+    /// Routing fee paid, reported once the payment succeeded
+    pub fee_sat: Option<u64>,
+    /// Position of the payment in lnd's send order, where a catch-up sweep resumes from
+    pub payment_index: Option<u64>,
 }
 
 #[async_trait]
@@ -47,7 +52,7 @@ pub trait Ln: Send + Sync {
         expiry_time_secs: u64,
         ticket_hash: String,
         competition_id: Uuid,
-        hex_refund_tx: String,
+        memo: String,
     ) -> Result<InvoiceAddResponse, anyhow::Error>;
     async fn add_invoice(
         &self,
@@ -61,9 +66,24 @@ pub trait Ln: Send + Sync {
         value: u64,
         expiry_time_secs: u64,
     ) -> Result<String, anyhow::Error>;
-    async fn cancel_hold_invoice(&self, ticket_hash: String) -> Result<(), anyhow::Error>;
-    async fn settle_hold_invoice(&self, ticket_preimage: String) -> Result<(), anyhow::Error>;
-    async fn lookup_invoice(&self, r_hash: &str) -> Result<InvoiceLookupResponse, anyhow::Error>;
+    /// `backend_id` is the backend that created the invoice, as stored with its ticket. Only
+    /// implementations wrapping several backends use it, a hold invoice can only be cancelled
+    /// or settled by the node holding its HTLCs.
+    async fn cancel_hold_invoice(
+        &self,
+        ticket_hash: String,
+        backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error>;
+    async fn settle_hold_invoice(
+        &self,
+        ticket_preimage: String,
+        backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error>;
+    async fn lookup_invoice(
+        &self,
+        r_hash: &str,
+        backend_id: Option<&str>,
+    ) -> Result<InvoiceLookupResponse, anyhow::Error>;
     async fn lookup_payment(&self, r_hash: &str) -> Result<PaymentLookupResponse, anyhow::Error>;
     async fn send_payment(
         &self,
@@ -73,8 +93,48 @@ pub trait Ln: Send + Sync {
         fee_limit_sat: u64,
     ) -> Result<(), anyhow::Error>;
 
+    /// Stream invoice updates until the connection drops, when the receiver closes
     async fn subscribe_invoices(&self) -> Result<mpsc::Receiver<InvoiceUpdate>, anyhow::Error>;
+    /// Stream payment updates until the connection drops, when the receiver closes
     async fn subscribe_payments(&self) -> Result<mpsc::Receiver<PaymentUpdate>, anyhow::Error>;
+    /// Current state of every invoice `backend_id` added after `add_index`, oldest first
+    async fn list_invoices_since(
+        &self,
+        backend_id: &str,
+        add_index: u64,
+    ) -> Result<Vec<InvoiceUpdate>, anyhow::Error>;
+    /// Current state of every payment `backend_id` sent after `payment_index`, oldest first
+    async fn list_payments_since(
+        &self,
+        backend_id: &str,
+        payment_index: u64,
+    ) -> Result<Vec<PaymentUpdate>, anyhow::Error>;
+
+    /// Backends behind this client, each numbers its invoices and payments on its own
+    fn backend_ids(&self) -> Vec<String> {
+        vec![PRIMARY_LN_BACKEND.to_string()]
+    }
+
+    /// Health of each backend behind this client
+    async fn backend_health(&self) -> Vec<LnBackendHealth> {
+        let error = self.ping().await.err().map(|e| e.to_string());
+        vec![LnBackendHealth {
+            id: PRIMARY_LN_BACKEND.to_string(),
+            healthy: error.is_none(),
+            error,
+        }]
+    }
+}
+
+/// Id of the backend configured at the top level of `[ln_settings]`
+pub const PRIMARY_LN_BACKEND: &str = "primary";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LnBackendHealth {
+    pub id: String,
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -214,6 +274,9 @@ pub struct InvoiceAddResponse {
     pub payment_request: String,
     pub add_index: String,
     pub payment_addr: String,
+    /// Backend that created the invoice, set by `FailoverLn`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -253,6 +316,14 @@ pub struct SubscribeInvoiceResponse {
     pub amt_paid_sat: Option<String>,
     #[serde(default)]
     pub settled: bool,
+    pub add_index: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListInvoicesResponse {
+    #[serde(default)]
+    pub invoices: Vec<SubscribeInvoiceResponse>,
+    pub last_index_offset: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -266,8 +337,20 @@ pub struct PaymentTrackResult {
     pub status: Option<PaymentStatus>,
     pub failure_reason: Option<String>,
     pub payment_preimage: Option<String>,
+    pub fee_sat: Option<String>,
+    pub payment_index: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListPaymentsResponse {
+    #[serde(default)]
+    pub payments: Vec<PaymentTrackResult>,
+    pub last_index_offset: Option<String>,
+}
+
+/// Invoices or payments fetched per page by the catch-up listings
+const LIST_PAGE_SIZE: usize = 100;
+
 #[async_trait]
 impl Ln for LnClient {
     async fn ping(&self) -> Result<(), anyhow::Error> {
@@ -305,8 +388,8 @@ impl Ln for LnClient {
         value: u64,
         expiry_time_secs: u64,
         ticket_hash_hex: String,
-        competition_id: Uuid,
-        hex_refund_tx: String,
+        _competition_id: Uuid,
+        memo: String,
     ) -> Result<InvoiceAddResponse, anyhow::Error> {
         info!("ticket_hash_hex: {:?}", ticket_hash_hex);
 
@@ -320,10 +403,6 @@ impl Ln for LnClient {
             ));
         }
 
-        let refund_tx_hash = sha256::Hash::hash(hex_refund_tx.as_bytes()).to_byte_array();
-
-        let memo = format!("c:{};r:{:?}", competition_id, refund_tx_hash);
-
         let hash_base64 = base64::engine::general_purpose::STANDARD.encode(&hash_bytes);
 
         let body = HoldInvoiceRequest {
@@ -388,7 +467,11 @@ impl Ln for LnClient {
         Ok(invoice_response)
     }
 
-    async fn cancel_hold_invoice(&self, ticket_hash_hex: String) -> Result<(), anyhow::Error> {
+    async fn cancel_hold_invoice(
+        &self,
+        ticket_hash_hex: String,
+        _backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
         let hash_bytes = hex::decode(&ticket_hash_hex)
             .map_err(|e| anyhow!("Failed to decode hex hash: {}", e))?;
 
@@ -422,7 +505,11 @@ impl Ln for LnClient {
         Ok(())
     }
 
-    async fn settle_hold_invoice(&self, ticket_preimage: String) -> Result<(), anyhow::Error> {
+    async fn settle_hold_invoice(
+        &self,
+        ticket_preimage: String,
+        _backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
         let preimage_bytes = hex::decode(&ticket_preimage)
             .map_err(|e| anyhow!("Failed to decode hex preimage: {}", e))?;
 
@@ -482,6 +569,7 @@ impl Ln for LnClient {
     async fn lookup_invoice(
         &self,
         ticket_hash_hex: &str,
+        _backend_id: Option<&str>,
     ) -> Result<InvoiceLookupResponse, anyhow::Error> {
         let hash_bytes = hex::decode(ticket_hash_hex)
             .map_err(|e| anyhow!("Failed to decode hex hash: {}", e))?;
@@ -615,17 +703,15 @@ impl Ln for LnClient {
             .danger_accept_invalid_certs(true)
             .build()?;
 
+        // Dropping `tx` when the stream ends tells the subscriber to reconnect and catch up
         tokio::spawn(async move {
             let url = format!("{}v1/invoices/subscribe", base_url);
             info!("Starting invoice subscription at {}", url);
 
-            loop {
-                if let Err(e) = process_invoice_stream(&client, &url, &macaroon, &tx).await {
-                    warn!("Invoice subscription error: {}", e);
-                }
-                info!("Invoice subscription reconnecting...");
-                tokio::time::sleep(Duration::from_secs(1)).await;
+            if let Err(e) = process_invoice_stream(&client, &url, &macaroon, &tx).await {
+                warn!("Invoice subscription error: {}", e);
             }
+            info!("Invoice subscription closed");
         });
 
         Ok(rx)
@@ -640,21 +726,97 @@ impl Ln for LnClient {
             .danger_accept_invalid_certs(true)
             .build()?;
 
+        // Dropping `tx` when the stream ends tells the subscriber to reconnect and catch up
         tokio::spawn(async move {
             let url = format!("{}v2/router/payments", base_url);
             info!("Starting payment subscription at {}", url);
 
-            loop {
-                if let Err(e) = process_payment_stream(&client, &url, &macaroon, &tx).await {
-                    warn!("Payment subscription error: {}", e);
-                }
-                info!("Payment subscription reconnecting...");
-                tokio::time::sleep(Duration::from_secs(1)).await;
+            if let Err(e) = process_payment_stream(&client, &url, &macaroon, &tx).await {
+                warn!("Payment subscription error: {}", e);
             }
+            info!("Payment subscription closed");
         });
 
         Ok(rx)
     }
+
+    async fn list_invoices_since(
+        &self,
+        _backend_id: &str,
+        add_index: u64,
+    ) -> Result<Vec<InvoiceUpdate>, anyhow::Error> {
+        let mut updates = Vec::new();
+        let mut index_offset = add_index;
+        loop {
+            let response = self
+                .client
+                .get(format!(
+                    "{}v1/invoices?index_offset={}&num_max_invoices={}",
+                    self.base_url, index_offset, LIST_PAGE_SIZE
+                ))
+                .header(MACAROON_HEADER, self.macaroon.expose_secret())
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow!("Failed to list invoices: {}", response.status()));
+            }
+
+            let page = response.json::<ListInvoicesResponse>().await?;
+            let page_len = page.invoices.len();
+            updates.extend(page.invoices.into_iter().filter_map(invoice_update_from));
+
+            let next_offset = page
+                .last_index_offset
+                .and_then(|offset| offset.parse::<u64>().ok())
+                .unwrap_or(index_offset);
+            if page_len < LIST_PAGE_SIZE || next_offset <= index_offset {
+                break;
+            }
+            index_offset = next_offset;
+        }
+
+        Ok(updates)
+    }
+
+    async fn list_payments_since(
+        &self,
+        _backend_id: &str,
+        payment_index: u64,
+    ) -> Result<Vec<PaymentUpdate>, anyhow::Error> {
+        let mut updates = Vec::new();
+        let mut index_offset = payment_index;
+        loop {
+            let response = self
+                .client
+                .get(format!(
+                    "{}v1/payments?index_offset={}&max_payments={}&include_incomplete=true",
+                    self.base_url, index_offset, LIST_PAGE_SIZE
+                ))
+                .header(MACAROON_HEADER, self.macaroon.expose_secret())
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow!("Failed to list payments: {}", response.status()));
+            }
+
+            let page = response.json::<ListPaymentsResponse>().await?;
+            let page_len = page.payments.len();
+            updates.extend(page.payments.into_iter().filter_map(payment_update_from));
+
+            let next_offset = page
+                .last_index_offset
+                .and_then(|offset| offset.parse::<u64>().ok())
+                .unwrap_or(index_offset);
+            if page_len < LIST_PAGE_SIZE || next_offset <= index_offset {
+                break;
+            }
+            index_offset = next_offset;
+        }
+
+        Ok(updates)
+    }
 }
 
 fn decode_base64_to_hex(encoded: &str) -> Option<String> {
@@ -665,8 +827,20 @@ fn decode_base64_to_hex(encoded: &str) -> Option<String> {
         .map(hex::encode)
 }
 
+/// lnd's REST payment responses carry hashes as hex, its invoice responses as base64
+fn decode_hash_to_hex(encoded: &str) -> Option<String> {
+    if encoded.len() == 64 && hex::decode(encoded).is_ok() {
+        return Some(encoded.to_lowercase());
+    }
+    decode_base64_to_hex(encoded)
+}
+
 fn parse_invoice_update(line: &str) -> Option<InvoiceUpdate> {
     let resp: SubscribeInvoiceResponse = serde_json::from_str(line).ok()?;
+    invoice_update_from(resp)
+}
+
+fn invoice_update_from(resp: SubscribeInvoiceResponse) -> Option<InvoiceUpdate> {
     let r_hash = resp.r_hash.as_ref()?;
     let state = resp.state.as_ref()?;
     let payment_hash = decode_base64_to_hex(r_hash)?;
@@ -676,25 +850,34 @@ fn parse_invoice_update(line: &str) -> Option<InvoiceUpdate> {
         payment_hash,
         state: state.clone(),
         amt_paid_sat,
+        add_index: resp.add_index.as_ref().and_then(|index| index.parse().ok()),
     })
 }
 
 fn parse_payment_update(line: &str) -> Option<PaymentUpdate> {
     let resp: TrackPaymentResponse = serde_json::from_str(line).ok()?;
-    let result = resp.result?;
+    payment_update_from(resp.result?)
+}
+
+fn payment_update_from(result: PaymentTrackResult) -> Option<PaymentUpdate> {
     let hash = result.payment_hash.as_ref()?;
     let status = result.status.as_ref()?;
-    let payment_hash = decode_base64_to_hex(hash)?;
+    let payment_hash = decode_hash_to_hex(hash)?;
     let preimage = result
         .payment_preimage
         .as_ref()
-        .and_then(|p| decode_base64_to_hex(p));
+        .and_then(|p| decode_hash_to_hex(p));
 
     Some(PaymentUpdate {
         payment_hash,
         status: status.clone(),
         failure_reason: result.failure_reason.clone(),
         preimage,
+        fee_sat: result.fee_sat.as_ref().and_then(|fee| fee.parse().ok()),
+        payment_index: result
+            .payment_index
+            .as_ref()
+            .and_then(|index| index.parse().ok()),
     })
 }
 
@@ -0,0 +1,708 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bdk_wallet::bitcoin::hashes::{sha256, Hash};
+use log::{debug, info, warn};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, RwLock},
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::lightning::{
+    extract_payment_hash_from_invoice, InvoiceAddResponse, InvoiceLookupResponse, InvoiceState,
+    InvoiceUpdate, Ln, LnBackendHealth, PaymentLookupResponse, PaymentUpdate,
+};
+use crate::domain::PaymentStatus;
+
+/// Wraps an ordered list of lightning backends. Invoice creation and payouts go to the
+/// first backend that succeeds, every later call for the same payment hash goes back to
+/// the backend that handled it, since a hold invoice can only be settled by the node
+/// holding its HTLCs. Callers pass the backend id stored with the invoice, the routes kept
+/// here cover the rest and are dropped once the invoice or payment is final.
+pub struct FailoverLn {
+    backends: Vec<(String, Arc<dyn Ln>)>,
+    /// payment hash -> index of the backend that created the invoice
+    invoice_routes: RwLock<HashMap<String, usize>>,
+    /// payment hash -> index of the backend that sent the payment
+    payment_routes: RwLock<HashMap<String, usize>>,
+}
+
+impl FailoverLn {
+    pub fn new(backends: Vec<(String, Arc<dyn Ln>)>) -> Result<Self, anyhow::Error> {
+        if backends.is_empty() {
+            return Err(anyhow!("FailoverLn requires at least one backend"));
+        }
+        for (i, (id, _)) in backends.iter().enumerate() {
+            if backends[..i].iter().any(|(other, _)| other == id) {
+                return Err(anyhow!("Duplicate lightning backend id: {}", id));
+            }
+        }
+        Ok(Self {
+            backends,
+            invoice_routes: RwLock::new(HashMap::new()),
+            payment_routes: RwLock::new(HashMap::new()),
+        })
+    }
+
+    fn backend_index(&self, backend_id: &str) -> Option<usize> {
+        self.backends.iter().position(|(id, _)| id == backend_id)
+    }
+
+    fn backend(&self, backend_id: &str) -> Result<&Arc<dyn Ln>, anyhow::Error> {
+        self.backend_index(backend_id)
+            .map(|idx| &self.backends[idx].1)
+            .ok_or_else(|| anyhow!("Unknown lightning backend {}", backend_id))
+    }
+
+    fn route_for(routes: &RwLock<HashMap<String, usize>>, payment_hash: &str) -> Option<usize> {
+        routes
+            .read()
+            .ok()
+            .and_then(|routes| routes.get(payment_hash).copied())
+    }
+
+    fn record_route(routes: &RwLock<HashMap<String, usize>>, payment_hash: &str, idx: usize) {
+        if let Ok(mut routes) = routes.write() {
+            routes.insert(payment_hash.to_string(), idx);
+        }
+    }
+
+    fn forget_route(routes: &RwLock<HashMap<String, usize>>, payment_hash: &str) {
+        if let Ok(mut routes) = routes.write() {
+            routes.remove(payment_hash);
+        }
+    }
+
+    /// Runs `call` against each backend in order until one succeeds
+    async fn first_success<T, F, Fut>(
+        &self,
+        action: &str,
+        call: F,
+    ) -> Result<(usize, T), anyhow::Error>
+    where
+        F: Fn(Arc<dyn Ln>) -> Fut,
+        Fut: Future<Output = Result<T, anyhow::Error>>,
+    {
+        let mut errors = Vec::new();
+        for (idx, (id, backend)) in self.backends.iter().enumerate() {
+            match call(backend.clone()).await {
+                Ok(value) => {
+                    if idx > 0 {
+                        info!("Lightning backend {} handled {} after failover", id, action);
+                    }
+                    return Ok((idx, value));
+                }
+                Err(e) => {
+                    warn!("Lightning backend {} failed to {}: {}", id, action, e);
+                    errors.push(format!("{}: {}", id, e));
+                }
+            }
+        }
+        Err(anyhow!(
+            "All lightning backends failed to {}: {}",
+            action,
+            errors.join("; ")
+        ))
+    }
+
+    /// Runs `call` against `backend_id`, else the backend the payment hash was routed to, or
+    /// against each backend in order when neither is known (e.g. an invoice created before
+    /// backend ids were stored)
+    async fn routed<T, F, Fut>(
+        &self,
+        routes: &RwLock<HashMap<String, usize>>,
+        payment_hash: &str,
+        backend_id: Option<&str>,
+        action: &str,
+        call: F,
+    ) -> Result<T, anyhow::Error>
+    where
+        F: Fn(Arc<dyn Ln>) -> Fut,
+        Fut: Future<Output = Result<T, anyhow::Error>>,
+    {
+        let stored = backend_id.and_then(|backend_id| {
+            let idx = self.backend_index(backend_id);
+            if idx.is_none() {
+                warn!(
+                    "Unknown lightning backend {} for {}, was it removed from the config?",
+                    backend_id, payment_hash
+                );
+            }
+            idx
+        });
+        if let Some(idx) = stored.or_else(|| Self::route_for(routes, payment_hash)) {
+            let (id, backend) = &self.backends[idx];
+            debug!(
+                "Routing {} for {} to lightning backend {}",
+                action, payment_hash, id
+            );
+            return call(backend.clone()).await;
+        }
+        let (idx, value) = self.first_success(action, call).await?;
+        Self::record_route(routes, payment_hash, idx);
+        Ok(value)
+    }
+
+    fn with_backend_id(&self, idx: usize, mut invoice: InvoiceAddResponse) -> InvoiceAddResponse {
+        invoice.backend_id = Some(self.backends[idx].0.clone());
+        invoice
+    }
+}
+
+/// Forward every source into one receiver, closed as soon as any source closes so the
+/// subscriber reconnects to every backend and catches up on the one that dropped
+fn merge_receivers<T: Send + 'static>(sources: Vec<mpsc::Receiver<T>>) -> mpsc::Receiver<T> {
+    let (tx, rx) = mpsc::channel(100);
+    let closed = CancellationToken::new();
+    for mut source in sources {
+        let tx = tx.clone();
+        let closed = closed.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    update = source.recv() => {
+                        let Some(update) = update else {
+                            break;
+                        };
+                        if tx.send(update).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ = closed.cancelled() => break,
+                }
+            }
+            closed.cancel();
+        });
+    }
+    rx
+}
+
+#[async_trait]
+impl Ln for FailoverLn {
+    async fn ping(&self) -> Result<(), anyhow::Error> {
+        self.first_success(
+            "respond to ping",
+            |backend| async move { backend.ping().await },
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn add_hold_invoice(
+        &self,
+        value: u64,
+        expiry_time_secs: u64,
+        ticket_hash: String,
+        competition_id: Uuid,
+        memo: String,
+    ) -> Result<InvoiceAddResponse, anyhow::Error> {
+        let (idx, invoice) = self
+            .first_success("create hold invoice", |backend| {
+                let ticket_hash = ticket_hash.clone();
+                let memo = memo.clone();
+                async move {
+                    backend
+                        .add_hold_invoice(
+                            value,
+                            expiry_time_secs,
+                            ticket_hash,
+                            competition_id,
+                            memo,
+                        )
+                        .await
+                }
+            })
+            .await?;
+        Self::record_route(&self.invoice_routes, &ticket_hash, idx);
+        Ok(self.with_backend_id(idx, invoice))
+    }
+
+    async fn add_invoice(
+        &self,
+        value: u64,
+        expiry_time_secs: u64,
+        memo: String,
+        competition_id: Uuid,
+    ) -> Result<InvoiceAddResponse, anyhow::Error> {
+        let (idx, invoice) = self
+            .first_success("create invoice", |backend| {
+                let memo = memo.clone();
+                async move {
+                    backend
+                        .add_invoice(value, expiry_time_secs, memo, competition_id)
+                        .await
+                }
+            })
+            .await?;
+        if let Ok(payment_hash) = extract_payment_hash_from_invoice(&invoice.payment_request) {
+            Self::record_route(&self.invoice_routes, &payment_hash, idx);
+        }
+        Ok(self.with_backend_id(idx, invoice))
+    }
+
+    async fn create_invoice(
+        &self,
+        value: u64,
+        expiry_time_secs: u64,
+    ) -> Result<String, anyhow::Error> {
+        let (idx, payment_request) = self
+            .first_success("create invoice", |backend| async move {
+                backend.create_invoice(value, expiry_time_secs).await
+            })
+            .await?;
+        if let Ok(payment_hash) = extract_payment_hash_from_invoice(&payment_request) {
+            Self::record_route(&self.invoice_routes, &payment_hash, idx);
+        }
+        Ok(payment_request)
+    }
+
+    async fn cancel_hold_invoice(
+        &self,
+        ticket_hash: String,
+        backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        self.routed(
+            &self.invoice_routes,
+            &ticket_hash,
+            backend_id,
+            "cancel hold invoice",
+            |backend| {
+                let ticket_hash = ticket_hash.clone();
+                async move { backend.cancel_hold_invoice(ticket_hash, None).await }
+            },
+        )
+        .await?;
+        Self::forget_route(&self.invoice_routes, &ticket_hash);
+        Ok(())
+    }
+
+    async fn settle_hold_invoice(
+        &self,
+        ticket_preimage: String,
+        backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        let preimage_bytes = hex::decode(&ticket_preimage)
+            .map_err(|e| anyhow!("Failed to decode hex preimage: {}", e))?;
+        let payment_hash = hex::encode(sha256::Hash::hash(&preimage_bytes).to_byte_array());
+
+        self.routed(
+            &self.invoice_routes,
+            &payment_hash,
+            backend_id,
+            "settle hold invoice",
+            |backend| {
+                let ticket_preimage = ticket_preimage.clone();
+                async move { backend.settle_hold_invoice(ticket_preimage, None).await }
+            },
+        )
+        .await?;
+        Self::forget_route(&self.invoice_routes, &payment_hash);
+        Ok(())
+    }
+
+    async fn lookup_invoice(
+        &self,
+        r_hash: &str,
+        backend_id: Option<&str>,
+    ) -> Result<InvoiceLookupResponse, anyhow::Error> {
+        let invoice = self
+            .routed(
+                &self.invoice_routes,
+                r_hash,
+                backend_id,
+                "lookup invoice",
+                |backend| {
+                    let r_hash = r_hash.to_string();
+                    async move { backend.lookup_invoice(&r_hash, None).await }
+                },
+            )
+            .await?;
+        if matches!(
+            invoice.state,
+            InvoiceState::Settled | InvoiceState::Canceled
+        ) {
+            Self::forget_route(&self.invoice_routes, r_hash);
+        }
+        Ok(invoice)
+    }
+
+    async fn lookup_payment(&self, r_hash: &str) -> Result<PaymentLookupResponse, anyhow::Error> {
+        let payment = self
+            .routed(
+                &self.payment_routes,
+                r_hash,
+                None,
+                "lookup payment",
+                |backend| {
+                    let r_hash = r_hash.to_string();
+                    async move { backend.lookup_payment(&r_hash).await }
+                },
+            )
+            .await?;
+        if matches!(
+            payment.status,
+            PaymentStatus::Succeeded | PaymentStatus::Failed
+        ) {
+            Self::forget_route(&self.payment_routes, r_hash);
+        }
+        Ok(payment)
+    }
+
+    async fn send_payment(
+        &self,
+        payout_payment_request: String,
+        amount_sats: u64,
+        timeout_seconds: u64,
+        fee_limit_sat: u64,
+    ) -> Result<(), anyhow::Error> {
+        // Retrying the same bolt11 invoice from another node is safe, the recipient
+        // settles the payment hash at most once
+        let (idx, ()) = self
+            .first_success("send payment", |backend| {
+                let payout_payment_request = payout_payment_request.clone();
+                async move {
+                    backend
+                        .send_payment(
+                            payout_payment_request,
+                            amount_sats,
+                            timeout_seconds,
+                            fee_limit_sat,
+                        )
+                        .await
+                }
+            })
+            .await?;
+        if let Ok(payment_hash) = extract_payment_hash_from_invoice(&payout_payment_request) {
+            Self::record_route(&self.payment_routes, &payment_hash, idx);
+        }
+        Ok(())
+    }
+
+    async fn subscribe_invoices(&self) -> Result<mpsc::Receiver<InvoiceUpdate>, anyhow::Error> {
+        let mut sources = Vec::new();
+        for (id, backend) in &self.backends {
+            match backend.subscribe_invoices().await {
+                Ok(rx) => sources.push(rx),
+                Err(e) => warn!(
+                    "Lightning backend {} invoice subscription failed: {}",
+                    id, e
+                ),
+            }
+        }
+        if sources.is_empty() {
+            return Err(anyhow!(
+                "No lightning backend accepted an invoice subscription"
+            ));
+        }
+        Ok(merge_receivers(sources))
+    }
+
+    async fn subscribe_payments(&self) -> Result<mpsc::Receiver<PaymentUpdate>, anyhow::Error> {
+        let mut sources = Vec::new();
+        for (id, backend) in &self.backends {
+            match backend.subscribe_payments().await {
+                Ok(rx) => sources.push(rx),
+                Err(e) => warn!(
+                    "Lightning backend {} payment subscription failed: {}",
+                    id, e
+                ),
+            }
+        }
+        if sources.is_empty() {
+            return Err(anyhow!(
+                "No lightning backend accepted a payment subscription"
+            ));
+        }
+        Ok(merge_receivers(sources))
+    }
+
+    async fn list_invoices_since(
+        &self,
+        backend_id: &str,
+        add_index: u64,
+    ) -> Result<Vec<InvoiceUpdate>, anyhow::Error> {
+        self.backend(backend_id)?
+            .list_invoices_since(backend_id, add_index)
+            .await
+    }
+
+    async fn list_payments_since(
+        &self,
+        backend_id: &str,
+        payment_index: u64,
+    ) -> Result<Vec<PaymentUpdate>, anyhow::Error> {
+        self.backend(backend_id)?
+            .list_payments_since(backend_id, payment_index)
+            .await
+    }
+
+    fn backend_ids(&self) -> Vec<String> {
+        self.backends.iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    async fn backend_health(&self) -> Vec<LnBackendHealth> {
+        let mut health = Vec::with_capacity(self.backends.len());
+        for (id, backend) in &self.backends {
+            let error = backend.ping().await.err().map(|e| e.to_string());
+            health.push(LnBackendHealth {
+                id: id.clone(),
+                healthy: error.is_none(),
+                error,
+            });
+        }
+        health
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::lightning_mock::MockLnClient;
+
+    /// Backend that is unreachable for every call
+    struct DownLn;
+
+    #[async_trait]
+    impl Ln for DownLn {
+        async fn ping(&self) -> Result<(), anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn add_hold_invoice(
+            &self,
+            _value: u64,
+            _expiry_time_secs: u64,
+            _ticket_hash: String,
+            _competition_id: Uuid,
+            _memo: String,
+        ) -> Result<InvoiceAddResponse, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn add_invoice(
+            &self,
+            _value: u64,
+            _expiry_time_secs: u64,
+            _memo: String,
+            _competition_id: Uuid,
+        ) -> Result<InvoiceAddResponse, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn create_invoice(
+            &self,
+            _value: u64,
+            _expiry_time_secs: u64,
+        ) -> Result<String, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn cancel_hold_invoice(
+            &self,
+            _ticket_hash: String,
+            _backend_id: Option<&str>,
+        ) -> Result<(), anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn settle_hold_invoice(
+            &self,
+            _ticket_preimage: String,
+            _backend_id: Option<&str>,
+        ) -> Result<(), anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn lookup_invoice(
+            &self,
+            _r_hash: &str,
+            _backend_id: Option<&str>,
+        ) -> Result<InvoiceLookupResponse, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn lookup_payment(
+            &self,
+            _r_hash: &str,
+        ) -> Result<PaymentLookupResponse, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn send_payment(
+            &self,
+            _payout_payment_request: String,
+            _amount_sats: u64,
+            _timeout_seconds: u64,
+            _fee_limit_sat: u64,
+        ) -> Result<(), anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn subscribe_invoices(&self) -> Result<mpsc::Receiver<InvoiceUpdate>, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn subscribe_payments(&self) -> Result<mpsc::Receiver<PaymentUpdate>, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn list_invoices_since(
+            &self,
+            _backend_id: &str,
+            _add_index: u64,
+        ) -> Result<Vec<InvoiceUpdate>, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+        async fn list_payments_since(
+            &self,
+            _backend_id: &str,
+            _payment_index: u64,
+        ) -> Result<Vec<PaymentUpdate>, anyhow::Error> {
+            Err(anyhow!("connection refused"))
+        }
+    }
+
+    fn preimage_and_hash(byte: u8) -> (String, String) {
+        let preimage = [byte; 32];
+        let hash = sha256::Hash::hash(&preimage).to_byte_array();
+        (hex::encode(preimage), hex::encode(hash))
+    }
+
+    #[tokio::test]
+    async fn test_hold_invoice_fails_over_to_next_backend() {
+        let secondary = MockLnClient::new();
+        let ln = FailoverLn::new(vec![
+            ("primary".to_string(), Arc::new(DownLn) as Arc<dyn Ln>),
+            ("secondary".to_string(), Arc::new(secondary.clone())),
+        ])
+        .unwrap();
+        let (_, hash) = preimage_and_hash(1);
+
+        let invoice = ln
+            .add_hold_invoice(1000, 3600, hash.clone(), Uuid::now_v7(), String::new())
+            .await
+            .unwrap();
+
+        assert_eq!(invoice.backend_id.as_deref(), Some("secondary"));
+        assert_eq!(secondary.get_invoice_state(&hash), Some(InvoiceState::Open));
+    }
+
+    #[tokio::test]
+    async fn test_settle_goes_to_the_backend_that_created_the_invoice() {
+        let first = MockLnClient::new();
+        let second = MockLnClient::new();
+        let (preimage, hash) = preimage_and_hash(2);
+        second
+            .add_hold_invoice(1000, 3600, hash.clone(), Uuid::now_v7(), String::new())
+            .await
+            .unwrap();
+        second.accept_invoice(&hash).unwrap();
+
+        // A fresh FailoverLn has no routes, as after a restart, so the stored backend id decides
+        let ln = FailoverLn::new(vec![
+            ("first".to_string(), Arc::new(first.clone()) as Arc<dyn Ln>),
+            ("second".to_string(), Arc::new(second.clone())),
+        ])
+        .unwrap();
+        ln.settle_hold_invoice(preimage, Some("second"))
+            .await
+            .unwrap();
+
+        assert_eq!(second.get_invoice_state(&hash), Some(InvoiceState::Settled));
+        assert_eq!(first.get_invoice_state(&hash), None);
+    }
+
+    #[tokio::test]
+    async fn test_routes_are_dropped_once_the_invoice_is_final() {
+        let first = MockLnClient::new();
+        let ln = FailoverLn::new(vec![
+            ("first".to_string(), Arc::new(first.clone()) as Arc<dyn Ln>),
+            ("second".to_string(), Arc::new(MockLnClient::new())),
+        ])
+        .unwrap();
+        let (preimage, settled) = preimage_and_hash(3);
+        let (_, cancelled) = preimage_and_hash(4);
+        for hash in [&settled, &cancelled] {
+            ln.add_hold_invoice(1000, 3600, hash.clone(), Uuid::now_v7(), String::new())
+                .await
+                .unwrap();
+        }
+        assert_eq!(ln.invoice_routes.read().unwrap().len(), 2);
+
+        first.accept_invoice(&settled).unwrap();
+        ln.settle_hold_invoice(preimage, None).await.unwrap();
+        ln.cancel_hold_invoice(cancelled.clone(), None)
+            .await
+            .unwrap();
+
+        assert!(ln.invoice_routes.read().unwrap().is_empty());
+        // Without a route, lookups still find the invoice on the backend that has it
+        let invoice = ln.lookup_invoice(&cancelled, None).await.unwrap();
+        assert_eq!(invoice.state, InvoiceState::Canceled);
+    }
+
+    #[tokio::test]
+    async fn test_all_backends_down_reports_each_failure() {
+        let ln = FailoverLn::new(vec![
+            ("a".to_string(), Arc::new(DownLn) as Arc<dyn Ln>),
+            ("b".to_string(), Arc::new(DownLn)),
+        ])
+        .unwrap();
+
+        let err = ln.create_invoice(1000, 3600).await.unwrap_err().to_string();
+        assert!(err.contains("a: connection refused"));
+        assert!(err.contains("b: connection refused"));
+        assert!(ln.ping().await.is_err());
+
+        let health = ln.backend_health().await;
+        assert_eq!(health.len(), 2);
+        assert!(health.iter().all(|backend| !backend.healthy));
+    }
+
+    #[tokio::test]
+    async fn test_invoices_are_listed_per_backend() {
+        let first = MockLnClient::new();
+        let second = MockLnClient::new();
+        let ln = FailoverLn::new(vec![
+            ("first".to_string(), Arc::new(first.clone()) as Arc<dyn Ln>),
+            ("second".to_string(), Arc::new(second.clone())),
+        ])
+        .unwrap();
+        let (_, first_hash) = preimage_and_hash(5);
+        let (_, second_hash) = preimage_and_hash(6);
+        first
+            .add_hold_invoice(
+                1000,
+                3600,
+                first_hash.clone(),
+                Uuid::now_v7(),
+                String::new(),
+            )
+            .await
+            .unwrap();
+        second
+            .add_hold_invoice(
+                1000,
+                3600,
+                second_hash.clone(),
+                Uuid::now_v7(),
+                String::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(ln.backend_ids(), vec!["first", "second"]);
+        let listed = ln.list_invoices_since("second", 0).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].payment_hash, second_hash);
+        // Each backend's own index, where its cursor resumes from
+        let add_index = listed[0].add_index.unwrap();
+        assert!(ln
+            .list_invoices_since("second", add_index)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(ln.list_invoices_since("removed", 0).await.is_err());
+    }
+
+    #[test]
+    fn test_duplicate_backend_ids_are_rejected() {
+        let result = FailoverLn::new(vec![
+            ("a".to_string(), Arc::new(DownLn) as Arc<dyn Ln>),
+            ("a".to_string(), Arc::new(DownLn)),
+        ]);
+        assert!(result.is_err());
+    }
+}
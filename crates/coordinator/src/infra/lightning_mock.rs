@@ -1,5 +1,9 @@
 use async_trait::async_trait;
-use bdk_wallet::bitcoin::hashes::{sha256, Hash};
+use bdk_wallet::bitcoin::{
+    hashes::{sha256, Hash},
+    secp256k1::{Secp256k1, SecretKey},
+};
+use lightning_invoice::{Currency, InvoiceBuilder, PaymentSecret};
 use log::{debug, info};
 use std::{
     collections::HashMap,
@@ -11,8 +15,8 @@ use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use super::lightning::{
-    InvoiceAddResponse, InvoiceLookupResponse, InvoiceState, InvoiceUpdate, Ln,
-    PaymentLookupResponse, PaymentUpdate,
+    extract_payment_hash_from_invoice, InvoiceAddResponse, InvoiceLookupResponse, InvoiceState,
+    InvoiceUpdate, Ln, PaymentLookupResponse, PaymentUpdate,
 };
 use crate::domain::PaymentStatus;
 
@@ -26,19 +30,54 @@ struct MockInvoice {
     memo: Option<String>,
     created_at: OffsetDateTime,
     preimage: Option<String>,
+    add_index: u64,
+}
+
+/// A payment sent through the MockLnClient
+#[derive(Debug, Clone)]
+struct MockPayment {
+    payment_request: String,
+    amount_sats: u64,
+    status: PaymentStatus,
+    failure_reason: Option<String>,
+    payment_index: u64,
+}
+
+/// How the mock resolves a payment sent with `send_payment`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum MockPaymentBehavior {
+    /// Succeeds as soon as it is sent
+    #[default]
+    Succeed,
+    /// Stays in flight for the delay, then succeeds
+    SucceedAfter(Duration),
+    /// Fails the way lnd reports a payment it can't find a route for
+    NoRoute,
+    /// Stays in flight until `complete_payment` or `fail_payment` is called
+    InFlight,
 }
 
+/// Failure reason lnd reports when no route to the destination was found
+pub const NO_ROUTE_FAILURE: &str = "FAILURE_REASON_NO_ROUTE";
+
 /// A mock Lightning Network client for E2E testing.
 ///
 /// This client simulates LND behavior without requiring a real Lightning node.
-/// Invoices can be auto-accepted after a configurable delay, or manually
-/// controlled via the `accept_invoice` and `settle_invoice` methods.
+/// Invoices move Open -> Accepted -> Settled/Canceled, either auto-accepted after a
+/// configurable delay or driven by `accept_invoice`, `settle_invoice_by_hash` and
+/// `cancel_invoice_by_hash`. Payments resolve according to a `MockPaymentBehavior`.
+/// Every transition is pushed to the invoice and payment subscription streams.
 #[derive(Clone)]
 pub struct MockLnClient {
     invoices: Arc<RwLock<HashMap<String, MockInvoice>>>,
-    payments: Arc<RwLock<HashMap<String, PaymentStatus>>>,
+    payments: Arc<RwLock<HashMap<String, MockPayment>>>,
+    /// Behavior for payments without a scripted behavior
+    payment_behavior: Arc<RwLock<MockPaymentBehavior>>,
+    /// Behavior for specific payment hashes
+    scripted_payments: Arc<RwLock<HashMap<String, MockPaymentBehavior>>>,
     auto_accept_delay: Option<Duration>,
     invoice_counter: Arc<RwLock<u64>>,
+    payment_counter: Arc<RwLock<u64>>,
     /// Senders for invoice update subscriptions
     invoice_subscribers: Arc<RwLock<Vec<mpsc::Sender<InvoiceUpdate>>>>,
     /// Senders for payment update subscriptions
@@ -57,8 +96,11 @@ impl MockLnClient {
         Self {
             invoices: Arc::new(RwLock::new(HashMap::new())),
             payments: Arc::new(RwLock::new(HashMap::new())),
+            payment_behavior: Arc::new(RwLock::new(MockPaymentBehavior::default())),
+            scripted_payments: Arc::new(RwLock::new(HashMap::new())),
             auto_accept_delay: None,
             invoice_counter: Arc::new(RwLock::new(0)),
+            payment_counter: Arc::new(RwLock::new(0)),
             invoice_subscribers: Arc::new(RwLock::new(Vec::new())),
             payment_subscribers: Arc::new(RwLock::new(Vec::new())),
         }
@@ -69,8 +111,11 @@ impl MockLnClient {
         Self {
             invoices: Arc::new(RwLock::new(HashMap::new())),
             payments: Arc::new(RwLock::new(HashMap::new())),
+            payment_behavior: Arc::new(RwLock::new(MockPaymentBehavior::default())),
+            scripted_payments: Arc::new(RwLock::new(HashMap::new())),
             auto_accept_delay: Some(delay),
             invoice_counter: Arc::new(RwLock::new(0)),
+            payment_counter: Arc::new(RwLock::new(0)),
             invoice_subscribers: Arc::new(RwLock::new(Vec::new())),
             payment_subscribers: Arc::new(RwLock::new(Vec::new())),
         }
@@ -79,13 +124,13 @@ impl MockLnClient {
     /// Manually accept an invoice by its payment hash (hex-encoded).
     /// This simulates a user paying the invoice.
     pub fn accept_invoice(&self, payment_hash_hex: &str) -> Result<(), String> {
-        let (update, value_sats) = {
+        let (update, value_sats, add_index) = {
             let mut invoices = self.invoices.write().map_err(|e| e.to_string())?;
             if let Some(invoice) = invoices.get_mut(payment_hash_hex) {
                 if invoice.state == InvoiceState::Open {
                     invoice.state = InvoiceState::Accepted;
                     info!("Mock: Invoice {} accepted", payment_hash_hex);
-                    (true, invoice.value_sats)
+                    (true, invoice.value_sats, invoice.add_index)
                 } else {
                     return Err(format!(
                         "Invoice {} is not in Open state (current: {:?})",
@@ -102,6 +147,7 @@ impl MockLnClient {
                 payment_hash: payment_hash_hex.to_string(),
                 state: InvoiceState::Accepted,
                 amt_paid_sat: Some(value_sats),
+                add_index: Some(add_index),
             });
         }
         Ok(())
@@ -109,13 +155,13 @@ impl MockLnClient {
 
     /// Manually settle an invoice by its payment hash (hex-encoded).
     pub fn settle_invoice_by_hash(&self, payment_hash_hex: &str) -> Result<(), String> {
-        let (update, value_sats) = {
+        let (update, value_sats, add_index) = {
             let mut invoices = self.invoices.write().map_err(|e| e.to_string())?;
             if let Some(invoice) = invoices.get_mut(payment_hash_hex) {
                 if invoice.state == InvoiceState::Accepted || invoice.state == InvoiceState::Open {
                     invoice.state = InvoiceState::Settled;
                     info!("Mock: Invoice {} settled", payment_hash_hex);
-                    (true, invoice.value_sats)
+                    (true, invoice.value_sats, invoice.add_index)
                 } else {
                     return Err(format!(
                         "Invoice {} cannot be settled (current: {:?})",
@@ -132,11 +178,113 @@ impl MockLnClient {
                 payment_hash: payment_hash_hex.to_string(),
                 state: InvoiceState::Settled,
                 amt_paid_sat: Some(value_sats),
+                add_index: Some(add_index),
             });
         }
         Ok(())
     }
 
+    /// Cancel an invoice by its payment hash (hex-encoded), as lnd does when a hold
+    /// invoice expires before it is settled.
+    pub fn cancel_invoice_by_hash(&self, payment_hash_hex: &str) -> Result<(), String> {
+        let add_index = {
+            let mut invoices = self.invoices.write().map_err(|e| e.to_string())?;
+            let invoice = invoices
+                .get_mut(payment_hash_hex)
+                .ok_or_else(|| format!("Invoice {} not found", payment_hash_hex))?;
+            if invoice.state == InvoiceState::Settled {
+                return Err(format!(
+                    "Invoice {} is already settled and can't be canceled",
+                    payment_hash_hex
+                ));
+            }
+            invoice.state = InvoiceState::Canceled;
+            info!("Mock: Invoice {} canceled", payment_hash_hex);
+            invoice.add_index
+        };
+
+        self.broadcast_invoice_update(InvoiceUpdate {
+            payment_hash: payment_hash_hex.to_string(),
+            state: InvoiceState::Canceled,
+            amt_paid_sat: None,
+            add_index: Some(add_index),
+        });
+        Ok(())
+    }
+
+    /// Set how payments without a scripted behavior resolve.
+    pub fn set_payment_behavior(&self, behavior: MockPaymentBehavior) {
+        if let Ok(mut current) = self.payment_behavior.write() {
+            *current = behavior;
+        }
+    }
+
+    /// Set how the payment for a specific payment hash (hex-encoded) resolves.
+    pub fn script_payment(&self, payment_hash_hex: &str, behavior: MockPaymentBehavior) {
+        if let Ok(mut scripted) = self.scripted_payments.write() {
+            scripted.insert(payment_hash_hex.to_string(), behavior);
+        }
+    }
+
+    /// Complete an in-flight payment.
+    pub fn complete_payment(&self, payment_hash_hex: &str) -> Result<(), String> {
+        self.resolve_payment(payment_hash_hex, PaymentStatus::Succeeded, None)
+    }
+
+    /// Fail an in-flight payment with the given lnd failure reason.
+    pub fn fail_payment(&self, payment_hash_hex: &str, reason: &str) -> Result<(), String> {
+        self.resolve_payment(
+            payment_hash_hex,
+            PaymentStatus::Failed,
+            Some(reason.to_string()),
+        )
+    }
+
+    /// Get the current status of a payment by payment hash.
+    pub fn get_payment_status(&self, payment_hash_hex: &str) -> Option<PaymentStatus> {
+        self.payments
+            .read()
+            .ok()
+            .and_then(|payments| payments.get(payment_hash_hex).map(|p| p.status.clone()))
+    }
+
+    fn resolve_payment(
+        &self,
+        payment_hash_hex: &str,
+        status: PaymentStatus,
+        failure_reason: Option<String>,
+    ) -> Result<(), String> {
+        let payment_index = {
+            let mut payments = self.payments.write().map_err(|e| e.to_string())?;
+            let payment = payments
+                .get_mut(payment_hash_hex)
+                .ok_or_else(|| format!("Payment {} not found", payment_hash_hex))?;
+            if payment.status != PaymentStatus::InFlight {
+                return Err(format!(
+                    "Payment {} is not in flight (current: {:?})",
+                    payment_hash_hex, payment.status
+                ));
+            }
+            payment.status = status.clone();
+            payment.failure_reason = failure_reason.clone();
+            info!(
+                "Mock: Payment {} resolved as {:?}",
+                payment_hash_hex, status
+            );
+            payment.payment_index
+        };
+
+        self.broadcast_payment_update(PaymentUpdate {
+            payment_hash: payment_hash_hex.to_string(),
+            fee_sat: (status == PaymentStatus::Succeeded).then_some(0),
+            status,
+            failure_reason,
+            preimage: None,
+            payment_index: Some(payment_index),
+        });
+        Ok(())
+    }
+
     /// Reset all mock state (invoices and payments).
     pub fn reset(&self) {
         if let Ok(mut invoices) = self.invoices.write() {
@@ -145,16 +293,47 @@ impl MockLnClient {
         if let Ok(mut payments) = self.payments.write() {
             payments.clear();
         }
+        if let Ok(mut scripted) = self.scripted_payments.write() {
+            scripted.clear();
+        }
+        if let Ok(mut behavior) = self.payment_behavior.write() {
+            *behavior = MockPaymentBehavior::default();
+        }
         if let Ok(mut counter) = self.invoice_counter.write() {
             *counter = 0;
         }
+        if let Ok(mut counter) = self.payment_counter.write() {
+            *counter = 0;
+        }
+        self.disconnect_subscribers();
+        info!("Mock LN client state reset");
+    }
+
+    /// Close every invoice and payment subscription stream, as when the connection to lnd
+    /// drops. Updates sent before the subscribers reconnect only show up in the listings.
+    pub fn disconnect_subscribers(&self) {
         if let Ok(mut subs) = self.invoice_subscribers.write() {
             subs.clear();
         }
         if let Ok(mut subs) = self.payment_subscribers.write() {
             subs.clear();
         }
-        info!("Mock LN client state reset");
+    }
+
+    /// Number of open invoice subscription streams
+    pub fn invoice_subscriber_count(&self) -> usize {
+        self.invoice_subscribers
+            .read()
+            .map(|subs| subs.iter().filter(|tx| !tx.is_closed()).count())
+            .unwrap_or(0)
+    }
+
+    fn next_index(counter: &RwLock<u64>) -> Result<u64, anyhow::Error> {
+        let mut counter = counter
+            .write()
+            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        *counter += 1;
+        Ok(*counter)
     }
 
     /// Get the current state of an invoice by payment hash.
@@ -195,6 +374,7 @@ impl MockLnClient {
                                 payment_hash: payment_hash_hex.clone(),
                                 state: InvoiceState::Accepted,
                                 amt_paid_sat: Some(invoice.value_sats),
+                                add_index: Some(invoice.add_index),
                             })
                         } else {
                             None
@@ -254,7 +434,7 @@ impl Ln for MockLnClient {
         _expiry_time_secs: u64,
         ticket_hash_hex: String,
         competition_id: Uuid,
-        hex_refund_tx: String,
+        memo: String,
     ) -> Result<InvoiceAddResponse, anyhow::Error> {
         debug!(
             "Mock LN: Creating hold invoice for {} sats, competition {}",
@@ -263,9 +443,7 @@ impl Ln for MockLnClient {
 
         let payment_request = self.generate_mock_invoice(value, &ticket_hash_hex);
 
-        let refund_tx_hash = sha256::Hash::hash(hex_refund_tx.as_bytes()).to_byte_array();
-        let memo = format!("c:{};r:{:?}", competition_id, refund_tx_hash);
-
+        let add_index = Self::next_index(&self.invoice_counter)?;
         let invoice = MockInvoice {
             payment_hash: ticket_hash_hex.clone(),
             payment_request: payment_request.clone(),
@@ -274,6 +452,7 @@ impl Ln for MockLnClient {
             memo: Some(memo),
             created_at: OffsetDateTime::now_utc(),
             preimage: None,
+            add_index,
         };
 
         {
@@ -289,17 +468,11 @@ impl Ln for MockLnClient {
             self.spawn_auto_accept(ticket_hash_hex, delay);
         }
 
-        let mut counter = self
-            .invoice_counter
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        *counter += 1;
-        let add_index = counter.to_string();
-
         Ok(InvoiceAddResponse {
             payment_request,
-            add_index,
+            add_index: add_index.to_string(),
             payment_addr: String::new(),
+            backend_id: None,
         })
     }
 
@@ -322,6 +495,7 @@ impl Ln for MockLnClient {
 
         let payment_request = self.generate_mock_invoice(value, &payment_hash_hex);
 
+        let add_index = Self::next_index(&self.invoice_counter)?;
         let invoice = MockInvoice {
             payment_hash: payment_hash_hex.clone(),
             payment_request: payment_request.clone(),
@@ -330,6 +504,7 @@ impl Ln for MockLnClient {
             memo: Some(format!("{} - competition_id:{}", memo, competition_id)),
             created_at: OffsetDateTime::now_utc(),
             preimage: None,
+            add_index,
         };
 
         {
@@ -340,17 +515,11 @@ impl Ln for MockLnClient {
             invoices.insert(payment_hash_hex, invoice);
         }
 
-        let mut counter = self
-            .invoice_counter
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        *counter += 1;
-        let add_index = counter.to_string();
-
         Ok(InvoiceAddResponse {
             payment_request,
-            add_index,
+            add_index: add_index.to_string(),
             payment_addr: String::new(),
+            backend_id: None,
         })
     }
 
@@ -369,6 +538,7 @@ impl Ln for MockLnClient {
 
         let payment_request = self.generate_mock_invoice(value, &payment_hash_hex);
 
+        let add_index = Self::next_index(&self.invoice_counter)?;
         let invoice = MockInvoice {
             payment_hash: payment_hash_hex.clone(),
             payment_request: payment_request.clone(),
@@ -377,6 +547,7 @@ impl Ln for MockLnClient {
             memo: None,
             created_at: OffsetDateTime::now_utc(),
             preimage: None,
+            add_index,
         };
 
         {
@@ -390,51 +561,62 @@ impl Ln for MockLnClient {
         Ok(payment_request)
     }
 
-    async fn cancel_hold_invoice(&self, ticket_hash_hex: String) -> Result<(), anyhow::Error> {
-        let mut invoices = self
-            .invoices
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-
-        if let Some(invoice) = invoices.get_mut(&ticket_hash_hex) {
-            invoice.state = InvoiceState::Canceled;
-            info!("Mock LN: Canceled invoice {}", ticket_hash_hex);
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("Invoice {} not found", ticket_hash_hex))
-        }
+    async fn cancel_hold_invoice(
+        &self,
+        ticket_hash_hex: String,
+        _backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        self.cancel_invoice_by_hash(&ticket_hash_hex)
+            .map_err(|e| anyhow::anyhow!(e))
     }
 
-    async fn settle_hold_invoice(&self, ticket_preimage: String) -> Result<(), anyhow::Error> {
+    async fn settle_hold_invoice(
+        &self,
+        ticket_preimage: String,
+        _backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
         // Compute the payment hash from the preimage
         let preimage_bytes = hex::decode(&ticket_preimage)
             .map_err(|e| anyhow::anyhow!("Failed to decode preimage: {}", e))?;
         let payment_hash = sha256::Hash::hash(&preimage_bytes);
         let payment_hash_hex = hex::encode(payment_hash.to_byte_array());
 
-        let mut invoices = self
-            .invoices
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let (value_sats, add_index) = {
+            let mut invoices = self
+                .invoices
+                .write()
+                .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
 
-        if let Some(invoice) = invoices.get_mut(&payment_hash_hex) {
-            if invoice.state == InvoiceState::Accepted {
-                invoice.state = InvoiceState::Settled;
-                invoice.preimage = Some(ticket_preimage);
-                info!("Mock LN: Settled invoice {}", payment_hash_hex);
-                Ok(())
-            } else {
-                Err(anyhow::anyhow!(
-                    "Invoice {} is not in Accepted state",
-                    payment_hash_hex
-                ))
+            let invoice = invoices
+                .get_mut(&payment_hash_hex)
+                .ok_or_else(|| anyhow::anyhow!("Invoice {} not found", payment_hash_hex))?;
+            if invoice.state != InvoiceState::Accepted {
+                return Err(anyhow::anyhow!(
+                    "Invoice {} is not in Accepted state (current: {:?})",
+                    payment_hash_hex,
+                    invoice.state
+                ));
             }
-        } else {
-            Err(anyhow::anyhow!("Invoice {} not found", payment_hash_hex))
-        }
+            invoice.state = InvoiceState::Settled;
+            invoice.preimage = Some(ticket_preimage);
+            info!("Mock LN: Settled invoice {}", payment_hash_hex);
+            (invoice.value_sats, invoice.add_index)
+        };
+
+        self.broadcast_invoice_update(InvoiceUpdate {
+            payment_hash: payment_hash_hex,
+            state: InvoiceState::Settled,
+            amt_paid_sat: Some(value_sats),
+            add_index: Some(add_index),
+        });
+        Ok(())
     }
 
-    async fn lookup_invoice(&self, r_hash: &str) -> Result<InvoiceLookupResponse, anyhow::Error> {
+    async fn lookup_invoice(
+        &self,
+        r_hash: &str,
+        _backend_id: Option<&str>,
+    ) -> Result<InvoiceLookupResponse, anyhow::Error> {
         let invoices = self
             .invoices
             .read()
@@ -467,25 +649,29 @@ impl Ln for MockLnClient {
             .read()
             .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
 
-        let status = payments
-            .get(r_hash)
-            .cloned()
-            .unwrap_or(PaymentStatus::Unknown);
+        let payment = payments.get(r_hash);
+        let amount_sats = payment.map(|p| p.amount_sats).unwrap_or(0);
 
         Ok(PaymentLookupResponse {
             payment_hash: r_hash.to_string(),
-            value: "0".to_string(),
+            value: amount_sats.to_string(),
             creation_date: OffsetDateTime::now_utc().unix_timestamp().to_string(),
             fee: "0".to_string(),
             payment_preimage: None,
-            value_sat: "0".to_string(),
-            value_msat: "0".to_string(),
-            payment_request: String::new(),
-            status,
+            value_sat: amount_sats.to_string(),
+            value_msat: (amount_sats * 1000).to_string(),
+            payment_request: payment
+                .map(|p| p.payment_request.clone())
+                .unwrap_or_default(),
+            status: payment
+                .map(|p| p.status.clone())
+                .unwrap_or(PaymentStatus::Unknown),
             fee_sat: "0".to_string(),
             fee_msat: "0".to_string(),
             creation_time_ns: "0".to_string(),
-            failure_reason: String::new(),
+            failure_reason: payment
+                .and_then(|p| p.failure_reason.clone())
+                .unwrap_or_default(),
         })
     }
 
@@ -501,28 +687,71 @@ impl Ln for MockLnClient {
             amount_sats, payout_payment_request
         );
 
-        // Extract or generate a payment hash for tracking
-        let hash_input = format!("payment:{}:{}", payout_payment_request, amount_sats);
-        let payment_hash = sha256::Hash::hash(hash_input.as_bytes());
-        let payment_hash_hex = hex::encode(payment_hash.to_byte_array());
+        // Track by the invoice's payment hash like lnd does, mock invoices aren't
+        // real bolt11 so they get a hash derived from the request instead
+        let payment_hash_hex = extract_payment_hash_from_invoice(&payout_payment_request)
+            .unwrap_or_else(|_| {
+                let hash_input = format!("payment:{}:{}", payout_payment_request, amount_sats);
+                hex::encode(sha256::Hash::hash(hash_input.as_bytes()).to_byte_array())
+            });
 
+        let behavior = self
+            .scripted_payments
+            .read()
+            .ok()
+            .and_then(|scripted| scripted.get(&payment_hash_hex).cloned())
+            .or_else(|| self.payment_behavior.read().ok().map(|b| b.clone()))
+            .unwrap_or_default();
+
+        let payment_index = Self::next_index(&self.payment_counter)?;
         {
             let mut payments = self
                 .payments
                 .write()
                 .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-            payments.insert(payment_hash_hex.clone(), PaymentStatus::Succeeded);
+            payments.insert(
+                payment_hash_hex.clone(),
+                MockPayment {
+                    payment_request: payout_payment_request,
+                    amount_sats,
+                    status: PaymentStatus::InFlight,
+                    failure_reason: None,
+                    payment_index,
+                },
+            );
         }
 
-        // Broadcast payment update to subscribers
         self.broadcast_payment_update(PaymentUpdate {
-            payment_hash: payment_hash_hex,
-            status: PaymentStatus::Succeeded,
+            payment_hash: payment_hash_hex.clone(),
+            status: PaymentStatus::InFlight,
             failure_reason: None,
             preimage: None,
+            fee_sat: None,
+            payment_index: Some(payment_index),
         });
 
-        info!("Mock LN: Payment sent successfully");
+        // Like lnd's router/send, the call returns once the payment is dispatched and
+        // the outcome arrives through lookups and the payment subscription
+        match behavior {
+            MockPaymentBehavior::Succeed => {
+                let _ = self.complete_payment(&payment_hash_hex);
+            }
+            MockPaymentBehavior::SucceedAfter(delay) => {
+                let client = self.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let _ = client.complete_payment(&payment_hash_hex);
+                });
+            }
+            MockPaymentBehavior::NoRoute => {
+                let _ = self.fail_payment(&payment_hash_hex, NO_ROUTE_FAILURE);
+            }
+            MockPaymentBehavior::InFlight => {
+                debug!("Mock LN: Payment {} left in flight", payment_hash_hex);
+            }
+        }
+
+        info!("Mock LN: Payment dispatched ({:?})", behavior);
         Ok(())
     }
 
@@ -555,6 +784,82 @@ impl Ln for MockLnClient {
         info!("Mock LN: New payment subscription registered");
         Ok(rx)
     }
+
+    async fn list_invoices_since(
+        &self,
+        _backend_id: &str,
+        add_index: u64,
+    ) -> Result<Vec<InvoiceUpdate>, anyhow::Error> {
+        let invoices = self
+            .invoices
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let mut updates: Vec<InvoiceUpdate> = invoices
+            .values()
+            .filter(|invoice| invoice.add_index > add_index)
+            .map(|invoice| InvoiceUpdate {
+                payment_hash: invoice.payment_hash.clone(),
+                state: invoice.state.clone(),
+                amt_paid_sat: matches!(
+                    invoice.state,
+                    InvoiceState::Accepted | InvoiceState::Settled
+                )
+                .then_some(invoice.value_sats),
+                add_index: Some(invoice.add_index),
+            })
+            .collect();
+        updates.sort_by_key(|update| update.add_index);
+        Ok(updates)
+    }
+
+    async fn list_payments_since(
+        &self,
+        _backend_id: &str,
+        payment_index: u64,
+    ) -> Result<Vec<PaymentUpdate>, anyhow::Error> {
+        let payments = self
+            .payments
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let mut updates: Vec<PaymentUpdate> = payments
+            .iter()
+            .filter(|(_, payment)| payment.payment_index > payment_index)
+            .map(|(payment_hash, payment)| PaymentUpdate {
+                payment_hash: payment_hash.clone(),
+                status: payment.status.clone(),
+                failure_reason: payment.failure_reason.clone(),
+                preimage: None,
+                fee_sat: (payment.status == PaymentStatus::Succeeded).then_some(0),
+                payment_index: Some(payment.payment_index),
+            })
+            .collect();
+        updates.sort_by_key(|update| update.payment_index);
+        Ok(updates)
+    }
+}
+
+/// Build a signed regtest bolt11 invoice for the payment hash, for tests that need an
+/// invoice the coordinator can parse (e.g. payout requests).
+pub fn mock_bolt11_invoice(payment_hash_hex: &str, amount_sats: u64) -> String {
+    let payment_hash = sha256::Hash::from_slice(
+        &hex::decode(payment_hash_hex).expect("payment hash should be hex"),
+    )
+    .expect("payment hash should be 32 bytes");
+    let node_key = SecretKey::from_slice(&[0x42; 32]).expect("valid secret key");
+    let secp = Secp256k1::new();
+
+    InvoiceBuilder::new(Currency::Regtest)
+        .description("mock invoice".to_string())
+        .payment_hash(payment_hash)
+        .payment_secret(PaymentSecret([0x24; 32]))
+        .amount_milli_satoshis(amount_sats * 1000)
+        .current_timestamp()
+        .min_final_cltv_expiry_delta(144)
+        .build_signed(|hash| secp.sign_ecdsa_recoverable(hash, &node_key))
+        .expect("mock invoice should build")
+        .to_string()
 }
 
 #[cfg(test)]
@@ -656,7 +961,7 @@ mod tests {
             .await
             .unwrap();
 
-        let lookup = client.lookup_invoice(&ticket_hash).await.unwrap();
+        let lookup = client.lookup_invoice(&ticket_hash, None).await.unwrap();
         assert_eq!(lookup.value, "1000");
         assert_eq!(lookup.state, InvoiceState::Open);
     }
@@ -682,4 +987,139 @@ mod tests {
 
         assert!(client.get_invoice_state(&ticket_hash).is_none());
     }
+
+    #[tokio::test]
+    async fn test_mock_ln_hold_invoice_transitions_reach_subscribers() {
+        let client = MockLnClient::new();
+        let mut updates = client.subscribe_invoices().await.unwrap();
+        let preimage = [7u8; 32];
+        let ticket_hash = hex::encode(sha256::Hash::hash(&preimage).to_byte_array());
+
+        client
+            .add_hold_invoice(
+                1000,
+                3600,
+                ticket_hash.clone(),
+                Uuid::now_v7(),
+                String::new(),
+            )
+            .await
+            .unwrap();
+
+        // Settling before the player paid is the ordering bug lnd rejects
+        assert!(client
+            .settle_hold_invoice(hex::encode(preimage), None)
+            .await
+            .is_err());
+
+        client.accept_invoice(&ticket_hash).unwrap();
+        client
+            .settle_hold_invoice(hex::encode(preimage), None)
+            .await
+            .unwrap();
+        assert!(client
+            .cancel_hold_invoice(ticket_hash.clone(), None)
+            .await
+            .is_err());
+
+        let accepted = updates.recv().await.unwrap();
+        assert_eq!(accepted.state, InvoiceState::Accepted);
+        let settled = updates.recv().await.unwrap();
+        assert_eq!(settled.state, InvoiceState::Settled);
+        assert_eq!(settled.payment_hash, ticket_hash);
+        assert_eq!(settled.amt_paid_sat, Some(1000));
+    }
+
+    #[tokio::test]
+    async fn test_mock_ln_cancel_reaches_subscribers() {
+        let client = MockLnClient::new();
+        let mut updates = client.subscribe_invoices().await.unwrap();
+        let ticket_hash = "f".repeat(64);
+
+        client
+            .add_hold_invoice(
+                1000,
+                3600,
+                ticket_hash.clone(),
+                Uuid::now_v7(),
+                String::new(),
+            )
+            .await
+            .unwrap();
+        client.cancel_invoice_by_hash(&ticket_hash).unwrap();
+
+        assert_eq!(
+            client.get_invoice_state(&ticket_hash),
+            Some(InvoiceState::Canceled)
+        );
+        assert_eq!(updates.recv().await.unwrap().state, InvoiceState::Canceled);
+        assert!(client.accept_invoice(&ticket_hash).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_ln_payment_behaviors() {
+        let client = MockLnClient::new();
+        let mut updates = client.subscribe_payments().await.unwrap();
+        let no_route_hash = "1".repeat(64);
+        let delayed_hash = "2".repeat(64);
+        client.script_payment(&no_route_hash, MockPaymentBehavior::NoRoute);
+        client.script_payment(
+            &delayed_hash,
+            MockPaymentBehavior::SucceedAfter(Duration::from_millis(50)),
+        );
+
+        client
+            .send_payment(mock_bolt11_invoice(&no_route_hash, 500), 500, 60, 10)
+            .await
+            .unwrap();
+        let lookup = client.lookup_payment(&no_route_hash).await.unwrap();
+        assert_eq!(lookup.status, PaymentStatus::Failed);
+        assert_eq!(lookup.failure_reason, NO_ROUTE_FAILURE);
+        assert_eq!(
+            updates.recv().await.unwrap().status,
+            PaymentStatus::InFlight
+        );
+        let failed = updates.recv().await.unwrap();
+        assert_eq!(failed.status, PaymentStatus::Failed);
+        assert_eq!(failed.failure_reason.as_deref(), Some(NO_ROUTE_FAILURE));
+
+        client
+            .send_payment(mock_bolt11_invoice(&delayed_hash, 500), 500, 60, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.get_payment_status(&delayed_hash),
+            Some(PaymentStatus::InFlight)
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            client.get_payment_status(&delayed_hash),
+            Some(PaymentStatus::Succeeded)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_ln_in_flight_payment_is_resolved_manually() {
+        let client = MockLnClient::new();
+        client.set_payment_behavior(MockPaymentBehavior::InFlight);
+        let payment_hash = "3".repeat(64);
+
+        client
+            .send_payment(mock_bolt11_invoice(&payment_hash, 500), 500, 60, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.get_payment_status(&payment_hash),
+            Some(PaymentStatus::InFlight)
+        );
+
+        client.complete_payment(&payment_hash).unwrap();
+        assert_eq!(
+            client.get_payment_status(&payment_hash),
+            Some(PaymentStatus::Succeeded)
+        );
+        assert!(client
+            .fail_payment(&payment_hash, NO_ROUTE_FAILURE)
+            .is_err());
+    }
 }
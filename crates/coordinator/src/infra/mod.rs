@@ -1,10 +1,15 @@
 pub mod bitcoin;
 pub mod db;
+pub mod db_encryption;
+pub mod db_migrations;
+pub mod db_timestamps;
 pub mod escrow;
 pub mod file_utils;
 pub mod keymeld;
 pub mod keymeld_mock;
 pub mod lightning;
+pub mod lightning_failover;
+pub mod nostr_relays;
 pub mod oracle;
 pub mod secrets;
 
@@ -14,4 +19,6 @@ pub mod bitcoin_mock;
 #[cfg(any(feature = "e2e-testing", debug_assertions))]
 pub mod lightning_mock;
 #[cfg(any(feature = "e2e-testing", debug_assertions))]
+pub mod nostr_relays_mock;
+#[cfg(any(feature = "e2e-testing", debug_assertions))]
 pub mod oracle_mock;
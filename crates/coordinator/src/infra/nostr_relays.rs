@@ -0,0 +1,83 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use log::debug;
+use nostr_sdk::{Client, Event};
+use std::time::Duration;
+
+/// How long to wait for relays to connect and acknowledge an event
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[async_trait]
+pub trait NostrRelays: Send + Sync {
+    /// Publish a signed event to `relays`, succeeding once at least one relay accepted it
+    async fn publish(&self, relays: &[String], event: Event) -> Result<(), anyhow::Error>;
+}
+
+/// Connects to the requested relays for each publish, backup DMs are rare enough that
+/// holding relay connections open between entries isn't worth it
+#[derive(Default)]
+pub struct RelayPublisher;
+
+impl RelayPublisher {
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn publish_to(
+        client: &Client,
+        relays: &[String],
+        event: Event,
+    ) -> Result<(), anyhow::Error> {
+        for relay in relays {
+            client
+                .add_relay(relay.as_str())
+                .await
+                .map_err(|e| anyhow!("Invalid relay {}: {}", relay, e))?;
+        }
+        client.connect().await;
+
+        let output = client
+            .send_event_to(relays.iter().map(String::as_str), event)
+            .await?;
+        debug!(
+            "Event {} accepted by {} relays, rejected by {}",
+            output.val,
+            output.success.len(),
+            output.failed.len()
+        );
+        if output.success.is_empty() {
+            let reasons: Vec<String> = output
+                .failed
+                .iter()
+                .map(|(relay, reason)| format!("{}: {:?}", relay, reason))
+                .collect();
+            return Err(anyhow!(
+                "No relay accepted the event ({})",
+                reasons.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NostrRelays for RelayPublisher {
+    async fn publish(&self, relays: &[String], event: Event) -> Result<(), anyhow::Error> {
+        if relays.is_empty() {
+            return Err(anyhow!("No relays to publish to"));
+        }
+
+        let client = Client::default();
+        let result =
+            tokio::time::timeout(PUBLISH_TIMEOUT, Self::publish_to(&client, relays, event))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(anyhow!(
+                        "Timed out publishing to relays after {:?}",
+                        PUBLISH_TIMEOUT
+                    ))
+                });
+        let _ = client.disconnect().await;
+        result
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use nostr_sdk::Event;
+
+use super::nostr_relays::NostrRelays;
+
+/// Keeps published events in memory instead of sending them to relays
+#[derive(Clone, Default)]
+pub struct MockRelays {
+    published: Arc<Mutex<Vec<(Vec<String>, Event)>>>,
+    unreachable: Arc<AtomicBool>,
+}
+
+impl MockRelays {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make every publish fail as if no relay could be reached
+    pub fn set_unreachable(&self, unreachable: bool) {
+        self.unreachable.store(unreachable, Ordering::SeqCst);
+    }
+
+    /// Events published so far with the relays they were sent to
+    pub fn published(&self) -> Vec<(Vec<String>, Event)> {
+        self.published
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[async_trait]
+impl NostrRelays for MockRelays {
+    async fn publish(&self, relays: &[String], event: Event) -> Result<(), anyhow::Error> {
+        if self.unreachable.load(Ordering::SeqCst) {
+            return Err(anyhow!("No relay accepted the event"));
+        }
+        self.published
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((relays.to_vec(), event));
+        Ok(())
+    }
+}
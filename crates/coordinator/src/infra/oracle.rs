@@ -18,12 +18,16 @@ use reqwest_middleware::{
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 use uuid::Uuid;
 
 use crate::{
     api::extractors::create_auth_event,
-    domain::{AddEntry, CreateEvent},
-    infra::secrets::get_key,
+    domain::{
+        scoring::{Forecast, Observation, ObservationData, OracleEntryScore},
+        AddEntry, ContractSizeEstimate, CreateEvent,
+    },
+    infra::secrets::{load_key, SecretBackend},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -35,6 +39,94 @@ pub struct Event {
     pub event_announcement: EventLockingConditions,
     /// When added it means the oracle has signed that the current data is the final result
     pub attestation: Option<MaybeScalar>,
+    /// What the event was created with, oracles that don't report these leave them out
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub signing_date: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub start_observation_date: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub end_observation_date: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub locations: Vec<String>,
+    #[serde(default)]
+    pub total_allowed_entries: Option<usize>,
+    #[serde(default)]
+    pub number_of_places_win: Option<usize>,
+}
+
+impl Event {
+    /// Where this event differs from what creating `expected` gives. Used to tell a retried
+    /// creation apart from another event with the id. The outcomes and expiry the contract is
+    /// built on are always compared, the fields the oracle echoes back only when it reports
+    /// them, and an event reporting none of those can't be told apart so it always differs.
+    pub fn differences(&self, expected: &CreateEvent) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.id != expected.id {
+            differences.push(format!("id {} instead of {}", self.id, expected.id));
+        }
+
+        let outcomes = ContractSizeEstimate::for_event(expected).attested_outcomes();
+        let locking_points = self.event_announcement.locking_points.len() as u64;
+        if locking_points != outcomes {
+            differences.push(format!(
+                "{} outcomes instead of {}",
+                locking_points, outcomes
+            ));
+        }
+        match self.event_announcement.expiry {
+            Some(expiry) if i64::from(expiry) > expected.signing_date.unix_timestamp() => {}
+            Some(expiry) => {
+                differences.push(format!("expiry {} is not after the signing_date", expiry))
+            }
+            None => differences.push("no expiry".to_string()),
+        }
+
+        let mut compared = 0;
+        let mut compare = |name: &str, actual: Option<String>, wanted: String| {
+            let Some(actual) = actual else {
+                return;
+            };
+            compared += 1;
+            if actual != wanted {
+                differences.push(format!("{} {} instead of {}", name, actual, wanted));
+            }
+        };
+        let date = |date: OffsetDateTime| date.format(&Rfc3339).unwrap_or_default();
+        compare(
+            "signing_date",
+            self.signing_date.map(date),
+            date(expected.signing_date),
+        );
+        compare(
+            "start_observation_date",
+            self.start_observation_date.map(date),
+            date(expected.start_observation_date),
+        );
+        compare(
+            "end_observation_date",
+            self.end_observation_date.map(date),
+            date(expected.end_observation_date),
+        );
+        compare(
+            "locations",
+            (!self.locations.is_empty()).then(|| self.locations.join(",")),
+            expected.locations.join(","),
+        );
+        compare(
+            "total_allowed_entries",
+            self.total_allowed_entries.map(|count| count.to_string()),
+            expected.total_allowed_entries.to_string(),
+        );
+        compare(
+            "number_of_places_win",
+            self.number_of_places_win.map(|count| count.to_string()),
+            expected.number_of_places_win.to_string(),
+        );
+        if compared == 0 {
+            differences.push("no dates, locations or counts reported to compare".to_string());
+        }
+        differences
+    }
 }
 
 #[derive(Error, Debug)]
@@ -49,6 +141,9 @@ pub enum Error {
     Request(String),
     #[error("{0}")]
     BadRequest(String),
+    /// The oracle already has what was being created
+    #[error("already exists on the oracle: {0}")]
+    Conflict(String),
     #[error("oracle temporarily unavailable: {0}")]
     Transient(String),
 }
@@ -160,15 +255,24 @@ pub trait Oracle: Send + Sync {
     async fn create_event(&self, event: CreateEvent) -> Result<Event, Error>;
     async fn get_event(&self, event_id: &Uuid) -> Result<Event, Error>;
     async fn submit_entries(&self, event_entries: AddEventEntries) -> Result<(), Error>;
+    /// Forecasts and observations for the stations over the observation window, along with the
+    /// oracle's entry scores for the event
+    async fn get_observation_data(
+        &self,
+        event_id: &Uuid,
+        station_ids: &[String],
+        start: OffsetDateTime,
+        end: OffsetDateTime,
+    ) -> Result<ObservationData, Error>;
 }
 
 impl OracleClient {
     pub fn new(
         client: ClientWithMiddleware,
         base_url: &Url,
-        nostr_key_path: &str,
+        secrets: &dyn SecretBackend,
     ) -> Result<Self, Error> {
-        let secret_key: Secp256k1SecretKey = get_key(nostr_key_path)
+        let secret_key: Secp256k1SecretKey = load_key(secrets)
             .map_err(|e| Error::Request(format!("Failed to load nostr key: {}", e)))?;
 
         let nostr_keys = secp256k1_to_nostr_keys(&secret_key)
@@ -232,6 +336,13 @@ impl OracleClient {
                     .await
                     .unwrap_or(String::from("bad request to oracle")),
             ))
+        } else if response.status() == StatusCode::CONFLICT {
+            Err(Error::Conflict(
+                response
+                    .text()
+                    .await
+                    .unwrap_or(String::from("conflict with the oracle")),
+            ))
         } else if response.status() == StatusCode::SERVICE_UNAVAILABLE
             || response.status() == StatusCode::BAD_GATEWAY
             || response.status() == StatusCode::GATEWAY_TIMEOUT
@@ -270,6 +381,29 @@ impl OracleClient {
         response.json::<T>().await.map_err(Into::into)
     }
 
+    fn stations_url(
+        &self,
+        path: &str,
+        station_ids: &[String],
+        start: OffsetDateTime,
+        end: OffsetDateTime,
+    ) -> Result<Url, Error> {
+        let format = |timestamp: OffsetDateTime| {
+            timestamp
+                .format(&Rfc3339)
+                .map_err(|e| Error::Request(format!("Failed to format timestamp: {}", e)))
+        };
+        let mut url = self
+            .base_url
+            .join(path)
+            .map_err(|e| Error::Request(e.to_string()))?;
+        url.query_pairs_mut()
+            .append_pair("station_ids", &station_ids.join(","))
+            .append_pair("start", &format(start)?)
+            .append_pair("end", &format(end)?);
+        Ok(url)
+    }
+
     pub async fn send_authenticated_request_ignore_body(
         &self,
         method: Method,
@@ -340,4 +474,92 @@ impl Oracle for OracleClient {
         )
         .await
     }
+
+    async fn get_observation_data(
+        &self,
+        event_id: &Uuid,
+        station_ids: &[String],
+        start: OffsetDateTime,
+        end: OffsetDateTime,
+    ) -> Result<ObservationData, Error> {
+        // The oracle reports forecast temperatures as integers and observed ones as floats
+        #[derive(Deserialize)]
+        struct RawForecast {
+            station_id: String,
+            temp_high: i64,
+            temp_low: i64,
+            #[serde(default)]
+            wind_speed: Option<f64>,
+        }
+        #[derive(Deserialize)]
+        struct RawObservation {
+            station_id: String,
+            temp_high: f64,
+            temp_low: f64,
+            #[serde(default)]
+            wind_speed: Option<f64>,
+        }
+        #[derive(Deserialize)]
+        struct EventEntries {
+            entries: Vec<OracleEntryScore>,
+        }
+
+        let event_url = self
+            .base_url
+            .join(&format!("/oracle/events/{}", event_id))
+            .map_err(|e| Error::Request(e.to_string()))?;
+        let event_entries = self.send_authenticated_request::<EventEntries>(
+            Method::GET,
+            event_url,
+            None,
+            format!("event with id {} not found", event_id),
+        );
+        if station_ids.is_empty() {
+            return Ok(ObservationData {
+                entry_scores: event_entries.await?.entries,
+                ..Default::default()
+            });
+        }
+
+        let forecasts_url = self.stations_url("/stations/forecasts", station_ids, start, end)?;
+        let observations_url =
+            self.stations_url("/stations/observations", station_ids, start, end)?;
+        let (event_entries, forecasts, observations) = tokio::join!(
+            event_entries,
+            self.send_authenticated_request::<Vec<RawForecast>>(
+                Method::GET,
+                forecasts_url,
+                None,
+                String::from("forecasts not found"),
+            ),
+            self.send_authenticated_request::<Vec<RawObservation>>(
+                Method::GET,
+                observations_url,
+                None,
+                String::from("observations not found"),
+            ),
+        );
+
+        Ok(ObservationData {
+            forecasts: forecasts?
+                .into_iter()
+                .map(|f| Forecast {
+                    station_id: f.station_id,
+                    temp_high: Some(f.temp_high as f64),
+                    temp_low: Some(f.temp_low as f64),
+                    wind_speed: f.wind_speed,
+                })
+                .collect(),
+            observations: observations?
+                .into_iter()
+                .map(|o| Observation {
+                    station_id: o.station_id,
+                    temp_high: Some(o.temp_high),
+                    temp_low: Some(o.temp_low),
+                    wind_speed: o.wind_speed,
+                })
+                .collect(),
+            entry_scores: event_entries?.entries,
+        })
+    }
 }
@@ -6,14 +6,15 @@ use std::{
 use async_trait::async_trait;
 use blake2::{Blake2s256, Digest};
 use dlctix::{
-    attestation_locking_point,
+    attestation_locking_point, attestation_secret,
     secp::{MaybeScalar, Scalar},
     EventLockingConditions,
 };
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use super::oracle::{AddEventEntries, Error, Event, Oracle};
-use crate::domain::CreateEvent;
+use crate::domain::{scoring::ObservationData, ContractSizeEstimate, CreateEvent};
 
 #[derive(Debug, Clone)]
 pub struct Outcome {
@@ -40,7 +41,6 @@ impl Outcome {
 }
 
 struct MockEvent {
-    #[allow(dead_code)]
     config: CreateEvent,
     nonce: Scalar,
     locking_conditions: EventLockingConditions,
@@ -48,10 +48,145 @@ struct MockEvent {
     attestation: Option<MaybeScalar>,
 }
 
+/// What the mock oracle publishes for an event once it's fetched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptedAttestation {
+    /// Attest to the outcome at this index, the attestation unlocks its locking point
+    Outcome(usize),
+    /// Never attest, the event runs into its expiry
+    Expiry,
+    /// Publish the zero scalar, which unlocks none of the locking points
+    InvalidScalar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OracleEndpoint {
+    CreateEvent,
+    GetEvent,
+    SubmitEntries,
+    GetObservationData,
+}
+
+impl OracleEndpoint {
+    pub const ALL: [OracleEndpoint; 4] = [
+        OracleEndpoint::CreateEvent,
+        OracleEndpoint::GetEvent,
+        OracleEndpoint::SubmitEntries,
+        OracleEndpoint::GetObservationData,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OracleFailure {
+    /// Reported as `Error::Transient`, callers are expected to retry
+    Transient,
+    /// Reported as `Error::BadRequest`
+    Permanent,
+    /// Reported as `Error::Request`, like an unexpected status from the oracle
+    Unexpected,
+}
+
+/// Programmable behavior of a `MockOracle`. Clones share state, so a test can keep a handle and
+/// reconfigure the oracle after it was handed to the coordinator.
+#[derive(Debug, Clone, Default)]
+pub struct MockOracleHandle {
+    attestations: Arc<RwLock<HashMap<Uuid, ScriptedAttestation>>>,
+    failures: Arc<RwLock<HashMap<OracleEndpoint, (OracleFailure, usize)>>>,
+    received_entries: Arc<RwLock<Vec<AddEventEntries>>>,
+    stored_entry_ids: Arc<RwLock<HashMap<Uuid, Vec<Uuid>>>>,
+    entries_chunk_failure: Arc<RwLock<Option<(usize, bool)>>>,
+    observation_data: Arc<RwLock<HashMap<Uuid, ObservationData>>>,
+    observation_fetches: Arc<RwLock<usize>>,
+}
+
+impl MockOracleHandle {
+    /// Replace whatever the oracle would attest to for `event_id`, applied on the next fetch
+    pub fn set_attestation_for(&self, event_id: Uuid, attestation: ScriptedAttestation) {
+        self.attestations
+            .write()
+            .unwrap()
+            .insert(event_id, attestation);
+    }
+
+    /// Fail the next `count` calls to `endpoint`, a count of 0 clears the failure
+    pub fn set_failure_mode(&self, endpoint: OracleEndpoint, failure: OracleFailure, count: usize) {
+        let mut failures = self.failures.write().unwrap();
+        if count == 0 {
+            failures.remove(&endpoint);
+        } else {
+            failures.insert(endpoint, (failure, count));
+        }
+    }
+
+    /// Every `AddEventEntries` the oracle received, including ones rejected by an injected failure
+    pub fn received_entries(&self) -> Vec<AddEventEntries> {
+        self.received_entries.read().unwrap().clone()
+    }
+
+    /// Fail the `chunk`th `submit_entries` call from now, once. With `stored` the oracle keeps the
+    /// chunk's entries before failing, as when the response is lost on the way back.
+    pub fn fail_entries_chunk_once(&self, chunk: usize, stored: bool) {
+        let call = self.received_entries.read().unwrap().len() + chunk;
+        *self.entries_chunk_failure.write().unwrap() = Some((call, stored));
+    }
+
+    /// Ids of the entries the oracle holds for `event_id`, in the order they were stored
+    pub fn stored_entry_ids(&self, event_id: Uuid) -> Vec<Uuid> {
+        self.stored_entry_ids
+            .read()
+            .unwrap()
+            .get(&event_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Observation data served for `event_id`, events without any get empty data
+    pub fn set_observation_data_for(&self, event_id: Uuid, data: ObservationData) {
+        self.observation_data
+            .write()
+            .unwrap()
+            .insert(event_id, data);
+    }
+
+    /// How many times observation data was fetched, including injected failures
+    pub fn observation_fetches(&self) -> usize {
+        *self.observation_fetches.read().unwrap()
+    }
+
+    fn reset(&self) {
+        self.attestations.write().unwrap().clear();
+        self.failures.write().unwrap().clear();
+        self.received_entries.write().unwrap().clear();
+        self.stored_entry_ids.write().unwrap().clear();
+        *self.entries_chunk_failure.write().unwrap() = None;
+        self.observation_data.write().unwrap().clear();
+        *self.observation_fetches.write().unwrap() = 0;
+    }
+
+    fn take_failure(&self, endpoint: OracleEndpoint) -> Result<(), Error> {
+        let mut failures = self.failures.write().unwrap();
+        let Some((failure, remaining)) = failures.get_mut(&endpoint) else {
+            return Ok(());
+        };
+        let failure = *failure;
+        *remaining -= 1;
+        if *remaining == 0 {
+            failures.remove(&endpoint);
+        }
+        let message = format!("injected {:?} failure", endpoint);
+        Err(match failure {
+            OracleFailure::Transient => Error::Transient(message),
+            OracleFailure::Permanent => Error::BadRequest(message),
+            OracleFailure::Unexpected => Error::Request(message),
+        })
+    }
+}
+
 pub struct MockOracle {
     seed: [u8; 32],
     events: Arc<RwLock<HashMap<Uuid, MockEvent>>>,
     pending_attestations: Arc<RwLock<HashMap<Uuid, Outcome>>>,
+    script: MockOracleHandle,
 }
 
 impl MockOracle {
@@ -60,9 +195,15 @@ impl MockOracle {
             seed,
             events: Arc::new(RwLock::new(HashMap::new())),
             pending_attestations: Arc::new(RwLock::new(HashMap::new())),
+            script: MockOracleHandle::default(),
         }
     }
 
+    /// Handle for scripting attestations and failures while the oracle is in use
+    pub fn handle(&self) -> MockOracleHandle {
+        self.script.clone()
+    }
+
     pub fn queue_attestation(&self, event_id: Uuid, outcome: Outcome) {
         self.pending_attestations
             .write()
@@ -70,6 +211,17 @@ impl MockOracle {
             .insert(event_id, outcome);
     }
 
+    /// Attest to the outcome at `outcome_index` with the secret behind its locking point, unlike
+    /// `queue_attestation` the result unlocks the contract's outcome transaction
+    pub fn attest_outcome(&self, event_id: &Uuid, outcome_index: usize) -> Result<(), Error> {
+        let mut events = self.events.write().unwrap();
+        let event = events
+            .get_mut(event_id)
+            .ok_or_else(|| Error::NotFound(format!("Event {} not found", event_id)))?;
+        event.attestation = Some(self.outcome_attestation(event_id, event, outcome_index)?);
+        Ok(())
+    }
+
     pub fn has_pending_attestation(&self, event_id: &Uuid) -> bool {
         self.pending_attestations
             .read()
@@ -92,6 +244,7 @@ impl MockOracle {
     pub fn reset(&self) {
         self.events.write().unwrap().clear();
         self.pending_attestations.write().unwrap().clear();
+        self.script.reset();
     }
 
     fn hash_with_context(&self, context: &[u8]) -> [u8; 32] {
@@ -128,7 +281,9 @@ impl MockOracle {
         let oracle_pubkey = oracle_seckey.base_point_mul();
         let nonce_point = nonce.base_point_mul();
 
-        let total_outcomes = config.total_allowed_entries.min(10);
+        // One outcome per ranking the coordinator pays out on, tied ones included with
+        // `TiePolicy::Split`
+        let total_outcomes = ContractSizeEstimate::for_event(config).attested_outcomes();
         let locking_points: Vec<_> = (0..total_outcomes)
             .map(|i| {
                 let msg = format!("outcome_{}", i);
@@ -144,6 +299,26 @@ impl MockOracle {
         }
     }
 
+    fn outcome_attestation(
+        &self,
+        event_id: &Uuid,
+        event: &MockEvent,
+        outcome_index: usize,
+    ) -> Result<MaybeScalar, Error> {
+        if outcome_index >= event.locking_conditions.locking_points.len() {
+            return Err(Error::BadRequest(format!(
+                "Event {} has no outcome {}",
+                event_id, outcome_index
+            )));
+        }
+        let msg = format!("outcome_{}", outcome_index);
+        Ok(attestation_secret(
+            self.generate_oracle_key(),
+            event.nonce,
+            msg.as_bytes(),
+        ))
+    }
+
     fn generate_attestation(&self, event_id: &Uuid, outcome: &Outcome) -> MaybeScalar {
         let mut context = event_id.as_bytes().to_vec();
         context.extend(outcome.to_bytes());
@@ -151,9 +326,32 @@ impl MockOracle {
     }
 }
 
+/// The event as the oracle reports it, with what it was created with
+fn mock_event(id: Uuid, event: &MockEvent) -> Event {
+    Event {
+        id,
+        nonce: event.nonce,
+        event_announcement: event.locking_conditions.clone(),
+        attestation: event.attestation,
+        signing_date: Some(event.config.signing_date),
+        start_observation_date: Some(event.config.start_observation_date),
+        end_observation_date: Some(event.config.end_observation_date),
+        locations: event.config.locations.clone(),
+        total_allowed_entries: Some(event.config.total_allowed_entries),
+        number_of_places_win: Some(event.config.number_of_places_win),
+    }
+}
+
 #[async_trait]
 impl Oracle for MockOracle {
     async fn create_event(&self, config: CreateEvent) -> Result<Event, Error> {
+        self.script.take_failure(OracleEndpoint::CreateEvent)?;
+        if self.events.read().unwrap().contains_key(&config.id) {
+            return Err(Error::Conflict(format!(
+                "Event {} already exists",
+                config.id
+            )));
+        }
         let nonce = self.generate_nonce(&config.id);
         let locking_conditions = self.generate_locking_conditions(&config, &nonce);
 
@@ -165,66 +363,132 @@ impl Oracle for MockOracle {
             attestation: None,
         };
 
+        let created = mock_event(config.id, &event);
         self.events.write().unwrap().insert(config.id, event);
-
-        Ok(Event {
-            id: config.id,
-            nonce,
-            event_announcement: locking_conditions,
-            attestation: None,
-        })
+        Ok(created)
     }
 
     async fn get_event(&self, event_id: &Uuid) -> Result<Event, Error> {
+        self.script.take_failure(OracleEndpoint::GetEvent)?;
         let mut events = self.events.write().unwrap();
         let event = events
             .get_mut(event_id)
             .ok_or_else(|| Error::NotFound(format!("Event {} not found", event_id)))?;
 
-        if event.attestation.is_none() {
+        let scripted = self
+            .script
+            .attestations
+            .read()
+            .unwrap()
+            .get(event_id)
+            .copied();
+        if let Some(scripted) = scripted {
+            event.attestation = match scripted {
+                ScriptedAttestation::Outcome(index) => {
+                    Some(self.outcome_attestation(event_id, event, index)?)
+                }
+                ScriptedAttestation::Expiry => None,
+                ScriptedAttestation::InvalidScalar => Some(MaybeScalar::Zero),
+            };
+        } else if event.attestation.is_none() {
             if let Some(outcome) = self.pending_attestations.write().unwrap().remove(event_id) {
                 event.attestation = Some(self.generate_attestation(event_id, &outcome));
             }
         }
 
-        Ok(Event {
-            id: *event_id,
-            nonce: event.nonce,
-            event_announcement: event.locking_conditions.clone(),
-            attestation: event.attestation,
-        })
+        Ok(mock_event(*event_id, event))
     }
 
     async fn submit_entries(&self, event_entries: AddEventEntries) -> Result<(), Error> {
+        let call = {
+            let mut received = self.script.received_entries.write().unwrap();
+            received.push(event_entries.clone());
+            received.len() - 1
+        };
+        self.script.take_failure(OracleEndpoint::SubmitEntries)?;
+        let chunk_failure = {
+            let mut failure = self.script.entries_chunk_failure.write().unwrap();
+            match *failure {
+                Some((failing_call, stored)) if failing_call == call => {
+                    *failure = None;
+                    Some(stored)
+                }
+                _ => None,
+            }
+        };
+        if chunk_failure == Some(false) {
+            return Err(Error::Transient(format!(
+                "injected failure of chunk {}",
+                call
+            )));
+        }
+
         let mut events = self.events.write().unwrap();
         let event = events.get_mut(&event_entries.event_id).ok_or_else(|| {
             Error::NotFound(format!("Event {} not found", event_entries.event_id))
         })?;
+        let mut stored_entry_ids = self.script.stored_entry_ids.write().unwrap();
+        let stored = stored_entry_ids.entry(event_entries.event_id).or_default();
+        // Like the oracle, the whole request is rejected when any of its entries is already in
+        if let Some(duplicate) = event_entries
+            .entries
+            .iter()
+            .find(|entry| stored.contains(&entry.id))
+        {
+            return Err(Error::Conflict(format!(
+                "Entry {} already exists",
+                duplicate.id
+            )));
+        }
 
+        stored.extend(event_entries.entries.iter().map(|entry| entry.id));
         event.entries.push(event_entries);
+        if chunk_failure == Some(true) {
+            return Err(Error::Transient(format!(
+                "injected failure of chunk {} after storing it",
+                call
+            )));
+        }
         Ok(())
     }
+
+    async fn get_observation_data(
+        &self,
+        event_id: &Uuid,
+        _station_ids: &[String],
+        _start: OffsetDateTime,
+        _end: OffsetDateTime,
+    ) -> Result<ObservationData, Error> {
+        *self.script.observation_fetches.write().unwrap() += 1;
+        self.script
+            .take_failure(OracleEndpoint::GetObservationData)?;
+        Ok(self
+            .script
+            .observation_data
+            .read()
+            .unwrap()
+            .get(event_id)
+            .cloned()
+            .unwrap_or_default())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{domain::TiePolicy, infra::oracle::AddEventEntry};
+    use coordinator_core::Sats;
     use time::OffsetDateTime;
 
     fn test_config() -> CreateEvent {
+        let start = OffsetDateTime::now_utc();
         CreateEvent {
-            id: Uuid::now_v7(),
-            signing_date: OffsetDateTime::now_utc() + time::Duration::days(1),
-            start_observation_date: OffsetDateTime::now_utc(),
-            end_observation_date: OffsetDateTime::now_utc() + time::Duration::hours(12),
+            signing_date: start + time::Duration::days(1),
+            end_observation_date: start + time::Duration::hours(12),
             locations: vec!["KLAX".to_string()],
-            number_of_values_per_entry: 3,
-            number_of_places_win: 1,
             total_allowed_entries: 10,
-            entry_fee: 1000,
-            coordinator_fee_percentage: 10,
-            total_competition_pool: 9000,
-            relative_locktime_block_delta: None,
+            total_competition_pool: Sats(9000),
+            ..CreateEvent::fixture(start)
         }
     }
 
@@ -241,6 +505,54 @@ mod tests {
         assert_eq!(fetched.id, config.id);
     }
 
+    #[tokio::test]
+    async fn test_create_event_twice_conflicts() {
+        let oracle = MockOracle::new([0u8; 32]);
+        let config = test_config();
+
+        let created = oracle.create_event(config.clone()).await.unwrap();
+        assert!(matches!(
+            oracle.create_event(config.clone()).await,
+            Err(Error::Conflict(_))
+        ));
+
+        let existing = oracle.get_event(&config.id).await.unwrap();
+        assert_eq!(existing.event_announcement, created.event_announcement);
+        assert!(existing.differences(&config).is_empty());
+
+        let mut moved = config.clone();
+        moved.locations = vec!["KSFO".to_string()];
+        assert_eq!(
+            existing.differences(&moved),
+            vec!["locations KLAX instead of KSFO".to_string()]
+        );
+
+        // The outcomes and expiry are compared even when the oracle echoes nothing back
+        let mut bare = existing.clone();
+        bare.signing_date = None;
+        bare.start_observation_date = None;
+        bare.end_observation_date = None;
+        bare.locations = vec![];
+        bare.total_allowed_entries = None;
+        bare.number_of_places_win = None;
+        assert_eq!(
+            bare.differences(&config),
+            vec!["no dates, locations or counts reported to compare".to_string()]
+        );
+
+        let mut short = existing.clone();
+        short.event_announcement.locking_points.pop();
+        short.event_announcement.expiry = None;
+        let outcomes = existing.event_announcement.locking_points.len();
+        assert_eq!(
+            short.differences(&config),
+            vec![
+                format!("{} outcomes instead of {}", outcomes - 1, outcomes),
+                "no expiry".to_string(),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_queue_attestation() {
         let oracle = MockOracle::new([0u8; 32]);
@@ -253,6 +565,145 @@ mod tests {
         assert!(event.attestation.is_some());
     }
 
+    #[tokio::test]
+    async fn test_attest_outcome_matches_locking_point() {
+        let oracle = MockOracle::new([3u8; 32]);
+        let config = test_config();
+
+        let event = oracle.create_event(config.clone()).await.unwrap();
+        oracle.attest_outcome(&config.id, 2).unwrap();
+
+        let attestation = oracle.get_event(&config.id).await.unwrap().attestation;
+        assert_eq!(
+            attestation.unwrap().base_point_mul(),
+            event.event_announcement.locking_points[2]
+        );
+        // Ten entries over one place, plus the refund outcome
+        assert_eq!(event.event_announcement.locking_points.len(), 11);
+        assert!(oracle.attest_outcome(&config.id, 11).is_err());
+
+        // Tied rankings get their own outcomes when ties split the places
+        let mut split = test_config();
+        split.id = Uuid::now_v7();
+        split.total_allowed_entries = 3;
+        split.tie_policy = TiePolicy::Split;
+        let event = oracle.create_event(split).await.unwrap();
+        assert_eq!(event.event_announcement.locking_points.len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_attestation_outcome() {
+        let oracle = MockOracle::new([5u8; 32]);
+        let handle = oracle.handle();
+        let config = test_config();
+
+        // Scripting before the event exists applies once it's fetched
+        handle.set_attestation_for(config.id, ScriptedAttestation::Outcome(4));
+        let event = oracle.create_event(config.clone()).await.unwrap();
+
+        let attestation = oracle.get_event(&config.id).await.unwrap().attestation;
+        assert_eq!(
+            attestation.unwrap().base_point_mul(),
+            event.event_announcement.locking_points[4]
+        );
+
+        handle.set_attestation_for(config.id, ScriptedAttestation::Outcome(11));
+        assert!(matches!(
+            oracle.get_event(&config.id).await,
+            Err(Error::BadRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_scripted_expiry_overrides_queued_attestation() {
+        let oracle = MockOracle::new([5u8; 32]);
+        let config = test_config();
+
+        oracle.create_event(config.clone()).await.unwrap();
+        oracle.queue_attestation(config.id, Outcome::single_winner(0));
+        oracle
+            .handle()
+            .set_attestation_for(config.id, ScriptedAttestation::Expiry);
+
+        let event = oracle.get_event(&config.id).await.unwrap();
+        assert!(event.attestation.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_scalar_unlocks_no_outcome() {
+        let oracle = MockOracle::new([5u8; 32]);
+        let config = test_config();
+
+        let event = oracle.create_event(config.clone()).await.unwrap();
+        oracle
+            .handle()
+            .set_attestation_for(config.id, ScriptedAttestation::InvalidScalar);
+
+        let attestation = oracle.get_event(&config.id).await.unwrap().attestation;
+        let point = attestation.unwrap().base_point_mul();
+        assert!(event
+            .event_announcement
+            .locking_points
+            .iter()
+            .all(|locking_point| *locking_point != point));
+    }
+
+    #[tokio::test]
+    async fn test_failure_mode_counts_down() {
+        let oracle = MockOracle::new([5u8; 32]);
+        let handle = oracle.handle();
+        let config = test_config();
+
+        handle.set_failure_mode(OracleEndpoint::CreateEvent, OracleFailure::Permanent, 1);
+        let err = oracle.create_event(config.clone()).await.unwrap_err();
+        assert!(!err.is_transient());
+        oracle.create_event(config.clone()).await.unwrap();
+
+        handle.set_failure_mode(OracleEndpoint::GetEvent, OracleFailure::Transient, 2);
+        assert!(oracle
+            .get_event(&config.id)
+            .await
+            .unwrap_err()
+            .is_transient());
+        assert!(oracle
+            .get_event(&config.id)
+            .await
+            .unwrap_err()
+            .is_transient());
+        oracle.get_event(&config.id).await.unwrap();
+
+        handle.set_failure_mode(OracleEndpoint::GetEvent, OracleFailure::Transient, 5);
+        handle.set_failure_mode(OracleEndpoint::GetEvent, OracleFailure::Transient, 0);
+        oracle.get_event(&config.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_records_received_entries() {
+        let oracle = MockOracle::new([5u8; 32]);
+        let handle = oracle.handle();
+        let config = test_config();
+        oracle.create_event(config.clone()).await.unwrap();
+
+        let entries = AddEventEntries {
+            event_id: config.id,
+            entries: vec![AddEventEntry {
+                id: Uuid::now_v7(),
+                event_id: config.id,
+                expected_observations: vec![],
+            }],
+        };
+        handle.set_failure_mode(OracleEndpoint::SubmitEntries, OracleFailure::Transient, 1);
+        assert!(oracle.submit_entries(entries.clone()).await.is_err());
+        oracle.submit_entries(entries).await.unwrap();
+
+        let received = handle.received_entries();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[1].entries.len(), 1);
+
+        oracle.reset();
+        assert!(handle.received_entries().is_empty());
+    }
+
     #[tokio::test]
     async fn test_deterministic() {
         let config = test_config();
@@ -1,13 +1,170 @@
 use anyhow::anyhow;
-use bdk_wallet::bitcoin::secp256k1::SecretKey as BitcoinSecretKey;
+use bdk_wallet::bitcoin::{secp256k1::SecretKey as BitcoinSecretKey, PrivateKey};
 use dlctix::musig2::secp256k1::SecretKey as DlctixSecretKey;
 use pem_rfc7468::{decode_vec, encode_string};
 use rand::RngCore;
+use secrecy::{ExposeSecret, SecretBox, SecretString};
 use std::{
+    env,
     fs::{metadata, File},
     io::{Read, Write},
     path::Path,
+    process::Command,
+    sync::Arc,
 };
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::SecretsSettings;
+
+/// Raw private key bytes, zeroized when dropped
+pub type KeyMaterial = SecretBox<[u8; 32]>;
+
+#[derive(thiserror::Error, Debug)]
+#[error("failed to load coordinator key from {backend} secrets backend: {reason}")]
+pub struct SecretsError {
+    pub backend: &'static str,
+    reason: String,
+}
+
+/// Source of the coordinator private key. Implementations must never log or put key material in
+/// their errors.
+pub trait SecretBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn load(&self) -> Result<KeyMaterial, anyhow::Error>;
+}
+
+/// PEM file on disk, a new key is generated and saved when the file doesn't exist yet
+pub struct FileSecretBackend {
+    file_path: String,
+}
+
+impl FileSecretBackend {
+    pub fn new(file_path: &str) -> Self {
+        Self {
+            file_path: file_path.to_owned(),
+        }
+    }
+}
+
+impl SecretBackend for FileSecretBackend {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn load(&self) -> Result<KeyMaterial, anyhow::Error> {
+        let key: BitcoinSecretKey = get_key(&self.file_path)?;
+        Ok(KeyMaterial::new(Box::new(key.secret_bytes())))
+    }
+}
+
+/// Hex or WIF encoded key injected through an environment variable
+pub struct EnvSecretBackend {
+    var: String,
+}
+
+impl EnvSecretBackend {
+    pub fn new(var: &str) -> Self {
+        Self {
+            var: var.to_owned(),
+        }
+    }
+}
+
+impl SecretBackend for EnvSecretBackend {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn load(&self) -> Result<KeyMaterial, anyhow::Error> {
+        let value = SecretString::from(
+            env::var(&self.var).map_err(|e| anyhow!("environment variable {}: {}", self.var, e))?,
+        );
+        decode_key_material(value.expose_secret())
+    }
+}
+
+/// Runs a user-specified command and reads a hex or WIF encoded key from its stdout
+pub struct CommandSecretBackend {
+    command: String,
+    args: Vec<String>,
+}
+
+impl CommandSecretBackend {
+    pub fn new(command: &str, args: &[String]) -> Self {
+        Self {
+            command: command.to_owned(),
+            args: args.to_vec(),
+        }
+    }
+}
+
+impl SecretBackend for CommandSecretBackend {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn load(&self) -> Result<KeyMaterial, anyhow::Error> {
+        let output = Command::new(&self.command)
+            .args(&self.args)
+            .output()
+            .map_err(|e| anyhow!("failed to run {}: {}", self.command, e))?;
+        let stdout = Zeroizing::new(output.stdout);
+        if !output.status.success() {
+            return Err(anyhow!("{} exited with {}", self.command, output.status));
+        }
+
+        let value = std::str::from_utf8(&stdout)
+            .map_err(|_| anyhow!("{} printed a non utf-8 key", self.command))?;
+        decode_key_material(value)
+    }
+}
+
+/// Build the backend selected in config, `file_path` is only used by the file backend
+pub fn secret_backend(settings: &SecretsSettings, file_path: &str) -> Arc<dyn SecretBackend> {
+    match settings {
+        SecretsSettings::File => Arc::new(FileSecretBackend::new(file_path)),
+        SecretsSettings::Env { var } => Arc::new(EnvSecretBackend::new(var)),
+        SecretsSettings::Command { command, args } => {
+            Arc::new(CommandSecretBackend::new(command, args))
+        }
+    }
+}
+
+/// Load a key through a backend, errors name the backend so a failed start is easy to trace
+pub fn load_key<T: SecretKeyHandler>(backend: &dyn SecretBackend) -> Result<T, SecretsError> {
+    let key_material = load_key_material(backend)?;
+    T::from_slice(key_material.expose_secret()).map_err(|e| SecretsError {
+        backend: backend.name(),
+        reason: e.to_string(),
+    })
+}
+
+/// Raw key bytes for keys that aren't secp256k1 secret keys, ie. the database encryption key
+pub fn load_key_material(backend: &dyn SecretBackend) -> Result<KeyMaterial, SecretsError> {
+    backend.load().map_err(|e| SecretsError {
+        backend: backend.name(),
+        reason: e.to_string(),
+    })
+}
+
+/// Accepts a 32 byte hex key or a WIF private key, surrounding whitespace is ignored
+fn decode_key_material(value: &str) -> Result<KeyMaterial, anyhow::Error> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(anyhow!("key is empty"));
+    }
+
+    if let Ok(mut decoded) = hex::decode(value) {
+        let key =
+            <[u8; 32]>::try_from(decoded.as_slice()).map(|bytes| KeyMaterial::new(Box::new(bytes)));
+        decoded.zeroize();
+        return key.map_err(|_| anyhow!("hex key must be 32 bytes"));
+    }
+
+    let private_key =
+        PrivateKey::from_wif(value).map_err(|_| anyhow!("key is neither 32 byte hex nor WIF"))?;
+    Ok(KeyMaterial::new(Box::new(private_key.inner.secret_bytes())))
+}
 
 pub trait SecretKeyHandler: Sized {
     fn generate() -> Self;
@@ -110,3 +267,82 @@ fn save_key<T: SecretKeyHandler>(file_path: &str, key: &T) -> Result<(), anyhow:
     file.write_all(pem.as_bytes())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const TEST_KEY_HEX: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+
+    fn test_key() -> BitcoinSecretKey {
+        BitcoinSecretKey::from_slice(&[1u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_file_backend_generates_then_reloads_key() {
+        let path = env::temp_dir().join(format!("coordinator-key-{}.pem", uuid::Uuid::now_v7()));
+        let backend = FileSecretBackend::new(&path.to_string_lossy());
+
+        let generated: BitcoinSecretKey = load_key(&backend).unwrap();
+        let reloaded: BitcoinSecretKey = load_key(&backend).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(generated, reloaded);
+    }
+
+    #[test]
+    fn test_env_backend_reads_hex_and_wif() {
+        let var = format!("COORDINATOR_TEST_KEY_{}", uuid::Uuid::now_v7().simple());
+        let backend = EnvSecretBackend::new(&var);
+
+        env::set_var(&var, format!("  {}\n", TEST_KEY_HEX));
+        let from_hex: BitcoinSecretKey = load_key(&backend).unwrap();
+
+        let wif = PrivateKey::new(test_key(), bdk_wallet::bitcoin::Network::Regtest).to_wif();
+        env::set_var(&var, wif);
+        let from_wif: BitcoinSecretKey = load_key(&backend).unwrap();
+        env::remove_var(&var);
+
+        assert_eq!(from_hex, test_key());
+        assert_eq!(from_wif, test_key());
+    }
+
+    #[test]
+    fn test_env_backend_missing_var_names_backend() {
+        let var = format!("COORDINATOR_TEST_KEY_{}", uuid::Uuid::now_v7().simple());
+        let err = load_key::<BitcoinSecretKey>(&EnvSecretBackend::new(&var)).unwrap_err();
+
+        assert_eq!(err.backend, "env");
+        assert!(err.to_string().contains("env secrets backend"));
+        assert!(err.to_string().contains(&var));
+    }
+
+    #[test]
+    fn test_command_backend_reads_stdout() {
+        let script = env::temp_dir().join(format!("coordinator-key-{}.sh", uuid::Uuid::now_v7()));
+        fs::write(&script, format!("#!/bin/sh\necho {}\n", TEST_KEY_HEX)).unwrap();
+        let backend = CommandSecretBackend::new("sh", &[script.to_string_lossy().to_string()]);
+
+        let key: BitcoinSecretKey = load_key(&backend).unwrap();
+        fs::remove_file(&script).unwrap();
+
+        assert_eq!(key, test_key());
+    }
+
+    #[test]
+    fn test_command_backend_failure_never_leaks_key() {
+        let backend = CommandSecretBackend::new(
+            "sh",
+            &[String::from("-c"), format!("echo {}; exit 3", TEST_KEY_HEX)],
+        );
+        let err = load_key::<BitcoinSecretKey>(&backend).unwrap_err();
+
+        assert_eq!(err.backend, "command");
+        assert!(!err.to_string().contains(TEST_KEY_HEX));
+
+        let backend = CommandSecretBackend::new("echo", &[String::from("not-a-key")]);
+        let err = load_key::<BitcoinSecretKey>(&backend).unwrap_err();
+        assert!(!err.to_string().contains("not-a-key"));
+    }
+}
@@ -1,8 +1,12 @@
 pub mod api;
+pub mod cli;
 pub mod config;
 pub mod domain;
 pub mod infra;
+#[cfg(any(feature = "e2e-testing", debug_assertions))]
+pub mod simulation;
 pub mod startup;
+pub mod supervisor;
 pub mod templates;
 
 // Re-exports for backward compatibility during migration
@@ -14,6 +18,7 @@ pub use domain::{
 };
 pub use infra::bitcoin::*;
 pub use infra::db::*;
+pub use infra::db_encryption::{encrypt_database, load_db_encryption_key, DbEncryptionKey};
 pub use infra::escrow::{generate_escrow_tx, get_escrow_outpoint};
 pub use infra::file_utils::*;
 pub use infra::lightning::*;
@@ -21,5 +26,7 @@ pub use infra::oracle::{
     AddEventEntries, AddEventEntry, Error as OracleError, Event as OracleEvent, Oracle,
     OracleClient, ValueOptions, WeatherChoices,
 };
-pub use infra::secrets::{get_key, SecretKeyHandler};
+pub use infra::secrets::{
+    get_key, load_key, secret_backend, SecretBackend, SecretKeyHandler, SecretsError,
+};
 pub use startup::*;
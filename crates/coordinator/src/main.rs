@@ -1,11 +1,28 @@
-use coordinator::{get_settings, setup_logger, Application};
+use clap::Parser;
+use coordinator::{
+    cli::{run_command, Command},
+    get_settings_with_cli, setup_logger, Application, Cli,
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let settings = get_settings()?;
-    setup_logger(settings.level.clone(), vec![String::from("hyper")])?;
-    let application = Application::build(settings).await?;
+    let cli = Cli::parse();
+    let command = cli.command.clone().unwrap_or(Command::Serve);
+    let json = cli.json;
+    let settings = get_settings_with_cli(cli.into())?;
 
-    application.run_until_stopped().await?;
-    Ok(())
+    if let Command::Serve = command {
+        setup_logger(settings.level.clone(), vec![String::from("hyper")])?;
+        let application = Application::build(settings).await?;
+
+        application.run_until_stopped().await?;
+        return Ok(());
+    }
+
+    // Keep stdout clean for --json output unless a log level was asked for
+    setup_logger(
+        settings.level.clone().or(Some(String::from("error"))),
+        vec![String::from("hyper"), String::from("sqlx")],
+    )?;
+    run_command(&settings, command, json, &mut std::io::stdout()).await
 }
@@ -0,0 +1,611 @@
+//! Scripted wrappers around the infra mocks. Each one answers from the scenario and the shared
+//! tick clock instead of the wall clock, so a run never depends on timing.
+#![allow(deprecated)] // SignOptions is deprecated but no replacement API exists yet in bdk_wallet 2.3
+
+use async_trait::async_trait;
+use bdk_wallet::{
+    bitcoin::{
+        absolute::LockTime,
+        hashes::{sha256, Hash},
+        transaction::Version,
+        Amount, FeeRate, Network, OutPoint, Psbt, PublicKey, ScriptBuf, Transaction, TxIn, TxOut,
+        Txid, Witness,
+    },
+    AddressInfo, Balance, LocalOutput, SignOptions,
+};
+use dlctix::secp::Scalar;
+use keymeld_sdk::{
+    dlctix::{
+        dlctix::{ContractParameters, SigningData},
+        DlcSignatureResults,
+    },
+    prelude::*,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use super::scenario::{in_any, BitcoinScript, KeymeldScript, LightningScript};
+use crate::infra::{
+    bitcoin::{
+        Bitcoin, ForeignUtxo, MempoolAcceptance, OutpointStatus, SendOptions, TxChainStatus,
+    },
+    bitcoin_mock::MockBitcoinClient,
+    keymeld::{
+        DlcKeygenSession, DlcSubsetInfo, KeygenSessionStatus, Keymeld, KeymeldError,
+        ParticipantRegistrationData,
+    },
+    keymeld_mock::MockKeymeld,
+    lightning::{
+        InvoiceAddResponse, InvoiceLookupResponse, InvoiceUpdate, Ln, PaymentLookupResponse,
+        PaymentUpdate,
+    },
+    lightning_mock::MockLnClient,
+};
+
+/// Fee the simulated funding input leaves for the funding transaction
+const SIMULATED_FUNDING_FEE: Amount = Amount::from_sat(1_000);
+
+/// Current simulation tick, shared by the runner and every scripted service
+#[derive(Debug, Clone, Default)]
+pub struct SimClock(Arc<AtomicU32>);
+
+impl SimClock {
+    pub fn tick(&self) -> u32 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, tick: u32) {
+        self.0.store(tick, Ordering::SeqCst);
+    }
+}
+
+/// Chain whose tip only moves when the runner advances it. Transactions confirm a scripted
+/// number of blocks after they were broadcast.
+pub struct ScriptedBitcoin {
+    inner: MockBitcoinClient,
+    script: BitcoinScript,
+    clock: SimClock,
+    height: AtomicU32,
+    broadcasts: Mutex<HashMap<Txid, u32>>,
+    transactions: Mutex<HashMap<Txid, Transaction>>,
+    spends: Mutex<HashMap<OutPoint, Txid>>,
+}
+
+impl ScriptedBitcoin {
+    pub fn new(script: BitcoinScript, clock: SimClock) -> Self {
+        let height = AtomicU32::new(script.start_height);
+        Self {
+            inner: MockBitcoinClient::new(Network::Regtest),
+            script,
+            clock,
+            height,
+            broadcasts: Mutex::new(HashMap::new()),
+            transactions: Mutex::new(HashMap::new()),
+            spends: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Mine this tick's blocks unless the chain is scripted to stall
+    pub fn advance(&self, tick: u32) {
+        if !in_any(&self.script.stalls, tick) {
+            self.height
+                .fetch_add(self.script.blocks_per_tick, Ordering::SeqCst);
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height.load(Ordering::SeqCst)
+    }
+
+    /// Put a transaction on the simulated chain, it's rejected when another transaction already
+    /// spends one of its inputs. Also used for transactions someone else broadcasts.
+    pub fn record_broadcast(&self, transaction: &Transaction) -> Result<(), anyhow::Error> {
+        let txid = transaction.compute_txid();
+        let mut spends = self.spends.lock().unwrap();
+        if let Some(input) = transaction.input.iter().find(|input| {
+            spends
+                .get(&input.previous_output)
+                .is_some_and(|spent_by| *spent_by != txid)
+        }) {
+            return Err(anyhow::anyhow!(
+                "bad-txns-inputs-missingorspent: {} is already spent (simulated)",
+                input.previous_output
+            ));
+        }
+        for input in &transaction.input {
+            spends.insert(input.previous_output, txid);
+        }
+        self.transactions
+            .lock()
+            .unwrap()
+            .insert(txid, transaction.clone());
+        self.broadcasts
+            .lock()
+            .unwrap()
+            .entry(txid)
+            .or_insert(self.height());
+        Ok(())
+    }
+
+    fn confirmation_height(&self, txid: &Txid) -> Option<u32> {
+        let broadcast_height = *self.broadcasts.lock().unwrap().get(txid)?;
+        let mined_at = broadcast_height + 1 + self.script.confirmation_delay_blocks;
+        (mined_at <= self.height()).then_some(mined_at)
+    }
+}
+
+#[async_trait]
+impl Bitcoin for ScriptedBitcoin {
+    fn get_network(&self) -> Network {
+        self.inner.get_network()
+    }
+
+    async fn sign_psbt_with_escrow_support(
+        &self,
+        psbt: &mut Psbt,
+        options: SignOptions,
+    ) -> Result<bool, anyhow::Error> {
+        self.inner
+            .sign_psbt_with_escrow_support(psbt, options)
+            .await
+    }
+
+    async fn finalize_psbt_with_escrow_support(
+        &self,
+        psbt: &mut Psbt,
+    ) -> Result<bool, anyhow::Error> {
+        self.inner.finalize_psbt_with_escrow_support(psbt).await
+    }
+
+    /// Fund the contract from a single already finalized input, its previous txid is derived
+    /// from the funding script so the funding outpoint is stable for a given contract
+    async fn build_psbt(
+        &self,
+        script_pubkey: ScriptBuf,
+        amount: Amount,
+        _fee_rate: FeeRate,
+        _selected_utxos: Vec<OutPoint>,
+        _foreign_utxos: Vec<ForeignUtxo>,
+    ) -> Result<Psbt, anyhow::Error> {
+        let previous_output = OutPoint {
+            txid: Txid::from_byte_array(
+                sha256::Hash::hash(script_pubkey.as_bytes()).to_byte_array(),
+            ),
+            vout: 0,
+        };
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output,
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: amount,
+                script_pubkey,
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx)?;
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: amount + SIMULATED_FUNDING_FEE,
+            script_pubkey: ScriptBuf::new(),
+        });
+        psbt.inputs[0].final_script_witness = Some(Witness::from_slice(&[[0u8; 64]]));
+        Ok(psbt)
+    }
+
+    async fn get_spendable_utxo(&self, amount_sats: u64) -> Result<LocalOutput, anyhow::Error> {
+        self.inner.get_spendable_utxo(amount_sats).await
+    }
+
+    async fn get_current_height(&self) -> Result<u32, anyhow::Error> {
+        Ok(self.height())
+    }
+
+    async fn get_confirmed_blockchain_time(&self, blocks: usize) -> Result<u64, anyhow::Error> {
+        self.inner.get_confirmed_blockchain_time(blocks).await
+    }
+
+    async fn get_estimated_fee_rates(&self) -> Result<HashMap<u16, f64>, anyhow::Error> {
+        self.inner.get_estimated_fee_rates().await
+    }
+
+    async fn get_tx_confirmation_height(&self, txid: &Txid) -> Result<Option<u32>, anyhow::Error> {
+        Ok(self.confirmation_height(txid))
+    }
+
+    async fn get_tx_chain_status(&self, txid: &Txid) -> Result<TxChainStatus, anyhow::Error> {
+        if let Some(height) = self.confirmation_height(txid) {
+            return Ok(TxChainStatus::Confirmed {
+                height,
+                confirmations: self.height() - height + 1,
+            });
+        }
+        if self.broadcasts.lock().unwrap().contains_key(txid) {
+            Ok(TxChainStatus::Unconfirmed)
+        } else {
+            Ok(TxChainStatus::NotFound)
+        }
+    }
+
+    async fn broadcast(&self, transaction: &Transaction) -> Result<(), anyhow::Error> {
+        if in_any(&self.script.broadcast_outages, self.clock.tick()) {
+            return Err(anyhow::anyhow!("service unavailable (simulated)"));
+        }
+        self.record_broadcast(transaction)
+    }
+
+    async fn test_mempool_accept(&self, tx_hex: &str) -> Result<MempoolAcceptance, anyhow::Error> {
+        if in_any(&self.script.mempool_rejections, self.clock.tick()) {
+            return Ok(MempoolAcceptance::Rejected {
+                reason: String::from(
+                    "mandatory-script-verify-flag-failed (Invalid Schnorr signature) (simulated)",
+                ),
+            });
+        }
+        self.inner.test_mempool_accept(tx_hex).await
+    }
+
+    async fn get_outpoint_status(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<OutpointStatus, anyhow::Error> {
+        Ok(match self.spends.lock().unwrap().get(outpoint) {
+            Some(txid) => OutpointStatus::Spent { txid: *txid },
+            None => OutpointStatus::Unspent,
+        })
+    }
+
+    async fn get_next_address(&self) -> Result<AddressInfo, anyhow::Error> {
+        self.inner.get_next_address().await
+    }
+
+    async fn get_public_key(&self) -> Result<PublicKey, anyhow::Error> {
+        self.inner.get_public_key().await
+    }
+
+    async fn get_derived_private_key(&self) -> Result<Scalar, anyhow::Error> {
+        self.inner.get_derived_private_key().await
+    }
+
+    async fn get_raw_transaction(&self, txid: &Txid) -> Result<Transaction, anyhow::Error> {
+        let transaction = self.transactions.lock().unwrap().get(txid).cloned();
+        match transaction {
+            Some(transaction) => Ok(transaction),
+            None => self.inner.get_raw_transaction(txid).await,
+        }
+    }
+
+    async fn sign_psbt(
+        &self,
+        psbt: &mut Psbt,
+        sign_options: SignOptions,
+    ) -> Result<bool, anyhow::Error> {
+        self.inner.sign_psbt(psbt, sign_options).await
+    }
+
+    async fn list_utxos(&self) -> Vec<LocalOutput> {
+        self.inner.list_utxos().await
+    }
+
+    async fn sync(&self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn get_balance(&self) -> Result<Balance, anyhow::Error> {
+        let topped_up = self
+            .script
+            .wallet_topped_up_at
+            .is_some_and(|tick| self.clock.tick() >= tick);
+        let confirmed = match self.script.wallet_balance_sats {
+            Some(sats) if !topped_up => Amount::from_sat(sats),
+            _ => Amount::MAX_MONEY,
+        };
+        Ok(Balance {
+            confirmed,
+            ..Default::default()
+        })
+    }
+
+    async fn get_outputs(&self) -> Result<Vec<LocalOutput>, anyhow::Error> {
+        self.inner.get_outputs().await
+    }
+
+    async fn send_to_address(
+        &self,
+        send_options: SendOptions,
+        selected_utxos: Vec<OutPoint>,
+    ) -> Result<Txid, anyhow::Error> {
+        self.inner
+            .send_to_address(send_options, selected_utxos)
+            .await
+    }
+}
+
+/// Lightning node whose hold invoice settlement fails during scripted ticks
+pub struct ScriptedLn {
+    inner: MockLnClient,
+    script: LightningScript,
+    clock: SimClock,
+}
+
+impl ScriptedLn {
+    pub fn new(inner: MockLnClient, script: LightningScript, clock: SimClock) -> Self {
+        Self {
+            inner,
+            script,
+            clock,
+        }
+    }
+}
+
+#[async_trait]
+impl Ln for ScriptedLn {
+    async fn ping(&self) -> Result<(), anyhow::Error> {
+        self.inner.ping().await
+    }
+
+    async fn add_hold_invoice(
+        &self,
+        value: u64,
+        expiry_time_secs: u64,
+        ticket_hash: String,
+        competition_id: Uuid,
+        memo: String,
+    ) -> Result<InvoiceAddResponse, anyhow::Error> {
+        self.inner
+            .add_hold_invoice(value, expiry_time_secs, ticket_hash, competition_id, memo)
+            .await
+    }
+
+    async fn add_invoice(
+        &self,
+        value: u64,
+        expiry_time_secs: u64,
+        memo: String,
+        competition_id: Uuid,
+    ) -> Result<InvoiceAddResponse, anyhow::Error> {
+        self.inner
+            .add_invoice(value, expiry_time_secs, memo, competition_id)
+            .await
+    }
+
+    async fn create_invoice(
+        &self,
+        value: u64,
+        expiry_time_secs: u64,
+    ) -> Result<String, anyhow::Error> {
+        self.inner.create_invoice(value, expiry_time_secs).await
+    }
+
+    async fn cancel_hold_invoice(
+        &self,
+        ticket_hash: String,
+        backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        self.inner
+            .cancel_hold_invoice(ticket_hash, backend_id)
+            .await
+    }
+
+    async fn settle_hold_invoice(
+        &self,
+        ticket_preimage: String,
+        backend_id: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        if in_any(&self.script.settle_failures, self.clock.tick()) {
+            return Err(anyhow::anyhow!("failed to settle hold invoice (simulated)"));
+        }
+        self.inner
+            .settle_hold_invoice(ticket_preimage, backend_id)
+            .await
+    }
+
+    async fn lookup_invoice(
+        &self,
+        r_hash: &str,
+        backend_id: Option<&str>,
+    ) -> Result<InvoiceLookupResponse, anyhow::Error> {
+        self.inner.lookup_invoice(r_hash, backend_id).await
+    }
+
+    async fn lookup_payment(&self, r_hash: &str) -> Result<PaymentLookupResponse, anyhow::Error> {
+        self.inner.lookup_payment(r_hash).await
+    }
+
+    async fn send_payment(
+        &self,
+        payout_payment_request: String,
+        amount_sats: u64,
+        timeout_seconds: u64,
+        fee_limit_sat: u64,
+    ) -> Result<(), anyhow::Error> {
+        self.inner
+            .send_payment(
+                payout_payment_request,
+                amount_sats,
+                timeout_seconds,
+                fee_limit_sat,
+            )
+            .await
+    }
+
+    async fn subscribe_invoices(&self) -> Result<mpsc::Receiver<InvoiceUpdate>, anyhow::Error> {
+        self.inner.subscribe_invoices().await
+    }
+
+    async fn subscribe_payments(&self) -> Result<mpsc::Receiver<PaymentUpdate>, anyhow::Error> {
+        self.inner.subscribe_payments().await
+    }
+
+    async fn list_invoices_since(
+        &self,
+        backend_id: &str,
+        add_index: u64,
+    ) -> Result<Vec<InvoiceUpdate>, anyhow::Error> {
+        self.inner.list_invoices_since(backend_id, add_index).await
+    }
+
+    async fn list_payments_since(
+        &self,
+        backend_id: &str,
+        payment_index: u64,
+    ) -> Result<Vec<PaymentUpdate>, anyhow::Error> {
+        self.inner
+            .list_payments_since(backend_id, payment_index)
+            .await
+    }
+}
+
+/// Prefix of the simulated encrypted keys `ScriptedKeymeld` treats as unusable registrations
+pub const BAD_REGISTRATION_PREFIX: &str = "simulated-bad-key";
+
+/// Keymeld whose keygen only completes once every expected participant registered, unless the
+/// script overrides the reported status
+pub struct ScriptedKeymeld {
+    inner: MockKeymeld,
+    script: KeymeldScript,
+    clock: SimClock,
+    expected: Mutex<BTreeSet<String>>,
+    registered: Mutex<BTreeSet<String>>,
+}
+
+impl ScriptedKeymeld {
+    pub fn new(script: KeymeldScript, clock: SimClock) -> Self {
+        Self {
+            inner: MockKeymeld,
+            script,
+            clock,
+            expected: Mutex::new(BTreeSet::new()),
+            registered: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    fn all_registered(&self) -> bool {
+        let registered = self.registered.lock().unwrap();
+        self.expected
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|user_id| registered.contains(user_id))
+    }
+}
+
+#[async_trait]
+impl Keymeld for ScriptedKeymeld {
+    async fn init_keygen_session(
+        &self,
+        competition_id: Uuid,
+        player_user_ids: Vec<UserId>,
+        subset_info: DlcSubsetInfo,
+    ) -> Result<DlcKeygenSession, KeymeldError> {
+        self.expected
+            .lock()
+            .unwrap()
+            .extend(player_user_ids.iter().map(|user_id| user_id.to_string()));
+        self.inner
+            .init_keygen_session(competition_id, player_user_ids, subset_info)
+            .await
+    }
+
+    async fn register_participant(
+        &self,
+        session: &DlcKeygenSession,
+        user_id: UserId,
+        registration_data: &ParticipantRegistrationData,
+    ) -> Result<(), KeymeldError> {
+        // Stands in for a key encrypted to the wrong enclave, accepted but never usable
+        if !registration_data
+            .encrypted_private_key
+            .starts_with(BAD_REGISTRATION_PREFIX)
+        {
+            self.registered.lock().unwrap().insert(user_id.to_string());
+        }
+        self.inner
+            .register_participant(session, user_id, registration_data)
+            .await
+    }
+
+    async fn remove_participant(
+        &self,
+        session: &DlcKeygenSession,
+        user_id: UserId,
+    ) -> Result<(), KeymeldError> {
+        self.registered.lock().unwrap().remove(&user_id.to_string());
+        self.inner.remove_participant(session, user_id).await
+    }
+
+    async fn wait_for_keygen_completion(
+        &self,
+        session: &DlcKeygenSession,
+    ) -> Result<Vec<u8>, KeymeldError> {
+        if !self.all_registered() {
+            return Err(KeymeldError::Session(
+                "keygen still waiting for participants (simulated)".to_string(),
+            ));
+        }
+        self.inner.wait_for_keygen_completion(session).await
+    }
+
+    async fn get_keygen_status(
+        &self,
+        session: &DlcKeygenSession,
+    ) -> Result<KeygenSessionStatus, KeymeldError> {
+        let tick = self.clock.tick();
+        let status = match self
+            .script
+            .statuses
+            .iter()
+            .find(|status| status.from <= tick && tick < status.until)
+        {
+            Some(scripted) => scripted.status.clone(),
+            None if self.all_registered() => "completed".to_string(),
+            None => "waiting_for_participants".to_string(),
+        };
+        Ok(KeygenSessionStatus {
+            session_id: session.session_id.to_string(),
+            is_completed: status == "completed",
+            status,
+        })
+    }
+
+    async fn sign_dlc_batch(
+        &self,
+        keygen_session: &DlcKeygenSession,
+        signing_data: &SigningData,
+        contract_params: &ContractParameters,
+        player_user_ids: Vec<UserId>,
+    ) -> Result<DlcSignatureResults, KeymeldError> {
+        self.inner
+            .sign_dlc_batch(
+                keygen_session,
+                signing_data,
+                contract_params,
+                player_user_ids,
+            )
+            .await
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.script.enabled
+    }
+
+    fn coordinator_user_id(&self) -> UserId {
+        self.inner.coordinator_user_id()
+    }
+
+    async fn get_user_enclave_pubkey(
+        &self,
+        session: &DlcKeygenSession,
+        user_id: UserId,
+    ) -> Result<String, KeymeldError> {
+        self.inner.get_user_enclave_pubkey(session, user_id).await
+    }
+}
@@ -0,0 +1,1173 @@
+//! Deterministic replay of a competition through the coordinator's state machine. A scenario
+//! scripts the oracle, bitcoin, lightning and keymeld, simulated players enter and sign the
+//! contract, and the runner ticks `competition_handler` recording every transition.
+//!
+//! For a given scenario the trace of states is reproducible. Ids and timestamps are not, the
+//! coordinator still generates tickets and uuids itself.
+mod mocks;
+mod scenario;
+
+pub use mocks::{ScriptedBitcoin, ScriptedKeymeld, ScriptedLn, SimClock};
+pub use scenario::*;
+
+use anyhow::anyhow;
+use bdk_wallet::bitcoin::{
+    absolute::LockTime,
+    hashes::{sha256, Hash},
+    transaction::Version,
+    OutPoint, Psbt, PublicKey as BitcoinPublicKey, ScriptBuf, Transaction, TxIn, TxOut, Witness,
+};
+use dlctix::{
+    musig2::PartialSignature,
+    secp::{MaybeScalar, Scalar},
+    NonceSharingRound, SigMap, SigningSession, TicketedDLC, WinCondition,
+};
+use itertools::Itertools;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::Serialize;
+use std::{fs, path::Path, str::FromStr, sync::Arc};
+use time::{Duration, OffsetDateTime};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use mocks::BAD_REGISTRATION_PREFIX;
+
+use crate::{
+    api::routes::{FinalSignatures, PartialSignatureChunk},
+    domain::{
+        reencrypt_keymeld_sessions, AddEntry, Competition, CompetitionPnl, CompetitionStore,
+        Coordinator, CoordinatorKeyring, CreateEvent, EntryStatus, Error, ExtendCompetition,
+        KeymeldRegistration, SearchBy, WebhookJob, WebhookNotifier,
+    },
+    infra::{
+        bitcoin::Bitcoin,
+        db::{DBConnection, DatabasePoolConfig, DatabaseType},
+        file_utils::create_folder,
+        keymeld::Keymeld,
+        lightning::Ln,
+        lightning_mock::MockLnClient,
+        nostr_relays_mock::MockRelays,
+        oracle::{Oracle, ValueOptions, WeatherChoices},
+        oracle_mock::{
+            MockOracle, MockOracleHandle, OracleEndpoint, OracleFailure, ScriptedAttestation,
+        },
+    },
+    InvoiceSettlementMode, SecretsSettings, Settings, SharedConfig, SigningKeySettings,
+};
+
+/// One state transition observed during a run
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceStep {
+    pub tick: u32,
+    pub block_height: u32,
+    pub state: String,
+    pub summary: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulationReport {
+    pub scenario: String,
+    pub seed: u64,
+    pub ticks_run: u32,
+    pub final_state: String,
+    pub trace: Vec<TraceStep>,
+    pub competition: ExtendCompetition,
+    /// What the accounting ledger recorded for the competition
+    pub pnl: CompetitionPnl,
+}
+
+impl SimulationReport {
+    /// Compare the run against the scenario's expectation
+    pub fn check(&self, expect: &Expectation) -> Result<(), anyhow::Error> {
+        if self.final_state != expect.final_state {
+            return Err(anyhow!(
+                "scenario {} ended in {} but expected {}",
+                self.scenario,
+                self.final_state,
+                expect.final_state
+            ));
+        }
+        let missing: Vec<&str> = expect
+            .visits
+            .iter()
+            .filter(|state| !self.trace.iter().any(|step| &step.state == *state))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "scenario {} never visited {}",
+                self.scenario,
+                missing.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn trace_lines(&self) -> Vec<String> {
+        self.trace
+            .iter()
+            .map(|step| {
+                format!(
+                    "tick {:>3}  height {:>5}  {:<22} {}",
+                    step.tick, step.block_height, step.state, step.summary
+                )
+            })
+            .collect()
+    }
+}
+
+struct SimPlayer {
+    index: usize,
+    seckey: Scalar,
+    /// Hex encoded ephemeral pubkey, also used as the player's nostr pubkey
+    pubkey: String,
+    payout_preimage: [u8; 32],
+    nonce_seed: [u8; 32],
+    entry_id: Option<Uuid>,
+}
+
+impl SimPlayer {
+    fn new(index: usize, rng: &mut ChaCha20Rng) -> Self {
+        let seckey = loop {
+            let bytes: [u8; 32] = rng.random();
+            if let Ok(seckey) = Scalar::from_slice(&bytes) {
+                break seckey;
+            }
+        };
+        Self {
+            index,
+            seckey,
+            pubkey: hex::encode(seckey.base_point_mul().serialize()),
+            payout_preimage: rng.random(),
+            nonce_seed: rng.random(),
+            entry_id: None,
+        }
+    }
+
+    /// NIP-44 encrypted to the player's own nostr key, like the entry secrets clients store
+    fn encrypt_to_self(&self, secret: &str) -> Result<String, anyhow::Error> {
+        let keys =
+            nostr_sdk::Keys::new(nostr_sdk::SecretKey::from_slice(&self.seckey.serialize())?);
+        Ok(nostr_sdk::nips::nip44::encrypt(
+            keys.secret_key(),
+            &keys.public_key(),
+            secret,
+            nostr_sdk::nips::nip44::Version::V2,
+        )?)
+    }
+
+    /// Sessions for both signing rounds come from the same seed, so the nonces used for the
+    /// partial signatures are the ones that were shared
+    fn signing_session(
+        &self,
+        competition: &Competition,
+    ) -> Result<SigningSession<NonceSharingRound>, anyhow::Error> {
+        let (Some(params), Some(funding_outpoint)) = (
+            competition.contract_parameters.clone(),
+            competition.funding_outpoint,
+        ) else {
+            return Err(anyhow!(
+                "contract for competition {} not built yet",
+                competition.id
+            ));
+        };
+        let contract = TicketedDLC::new(params, funding_outpoint)?;
+        let mut rng = ChaCha20Rng::from_seed(self.nonce_seed);
+        Ok(SigningSession::<NonceSharingRound>::new(
+            contract,
+            &mut rng,
+            self.seckey,
+        )?)
+    }
+
+    /// Submit nonces and then partial signatures as soon as the coordinator asks for them. A
+    /// player the script tampers with first sends a broken SigMap, which has to be rejected
+    /// without anything being stored, the rejection is returned for the trace.
+    async fn sign(
+        &self,
+        coordinator: &Coordinator,
+        competition: &Competition,
+        script: &SigningScript,
+    ) -> Result<Option<String>, anyhow::Error> {
+        let Some(entry_id) = self.entry_id else {
+            return Ok(None);
+        };
+        if competition.public_nonces.is_none() || competition.signed_contract.is_some() {
+            return Ok(None);
+        }
+        let entries = coordinator
+            .get_entries(
+                self.pubkey.clone(),
+                SearchBy {
+                    event_ids: Some(vec![competition.id]),
+                    tags: None,
+                },
+            )
+            .await?;
+        let entry = entries
+            .into_iter()
+            .find(|entry| entry.id == entry_id)
+            .ok_or_else(|| anyhow!("player {} lost entry {}", self.index, entry_id))?;
+
+        if entry.public_nonces.is_none() {
+            let session = self.signing_session(competition)?;
+            coordinator
+                .submit_public_nonces(
+                    self.pubkey.clone(),
+                    competition.id,
+                    entry_id,
+                    session.our_public_nonces().to_owned(),
+                )
+                .await?;
+        } else if entry.partial_signatures.is_none() {
+            let Some(aggregated_nonces) = competition.aggregated_nonces.clone() else {
+                return Ok(None);
+            };
+            let partial_signatures = self
+                .signing_session(competition)?
+                .compute_partial_signatures(aggregated_nonces)?
+                .our_partial_signatures()
+                .to_owned();
+            let funding_psbt_base64 = competition.funding_psbt_base64.clone().unwrap_or_default();
+
+            let rejection = match self.tamper(&partial_signatures, script) {
+                Some(tampered) => Some(
+                    self.submit_rejected(
+                        coordinator,
+                        competition,
+                        entry_id,
+                        FinalSignatures {
+                            funding_psbt_base64: funding_psbt_base64.clone(),
+                            partial_signatures: tampered,
+                        },
+                    )
+                    .await?,
+                ),
+                None => None,
+            };
+            if script.chunked.contains(&self.index) {
+                self.submit_chunks(
+                    coordinator,
+                    competition,
+                    entry_id,
+                    funding_psbt_base64,
+                    partial_signatures,
+                )
+                .await?;
+                return Ok(rejection);
+            }
+            coordinator
+                .submit_final_signatures(
+                    self.pubkey.clone(),
+                    competition.id,
+                    entry_id,
+                    FinalSignatures {
+                        funding_psbt_base64,
+                        partial_signatures,
+                    },
+                )
+                .await?;
+            return Ok(rejection);
+        }
+        Ok(None)
+    }
+
+    /// Upload `partial_signatures` in two chunks, the entry may only count as signed once the
+    /// second one has arrived
+    async fn submit_chunks(
+        &self,
+        coordinator: &Coordinator,
+        competition: &Competition,
+        entry_id: Uuid,
+        funding_psbt_base64: String,
+        mut partial_signatures: SigMap<PartialSignature>,
+    ) -> Result<(), anyhow::Error> {
+        // The first half of the outcome signatures, the rest and every win condition after
+        let mut first = SigMap {
+            by_outcome: Default::default(),
+            by_win_condition: Default::default(),
+        };
+        let outcomes = partial_signatures.by_outcome.len();
+        if let Some(split_at) = partial_signatures
+            .by_outcome
+            .keys()
+            .nth(outcomes / 2)
+            .copied()
+        {
+            let rest = partial_signatures.by_outcome.split_off(&split_at);
+            first.by_outcome = std::mem::replace(&mut partial_signatures.by_outcome, rest);
+        }
+
+        let chunks = [
+            (Some(funding_psbt_base64), first),
+            (None, partial_signatures),
+        ];
+        let last = chunks.len() - 1;
+        for (index, (funding_psbt_base64, partial_signatures)) in chunks.into_iter().enumerate() {
+            let progress = coordinator
+                .submit_partial_signature_chunk(
+                    self.pubkey.clone(),
+                    competition.id,
+                    entry_id,
+                    PartialSignatureChunk {
+                        funding_psbt_base64,
+                        partial_signatures,
+                    },
+                )
+                .await?;
+            if progress.complete != (index == last) {
+                return Err(anyhow!(
+                    "player {} chunk {} of {} reported complete={} ({} of {} signatures)",
+                    self.index,
+                    index + 1,
+                    last + 1,
+                    progress.complete,
+                    progress.received,
+                    progress.expected
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The broken copy of `partial_signatures` the script has this player send first, if any
+    fn tamper(
+        &self,
+        partial_signatures: &SigMap<PartialSignature>,
+        script: &SigningScript,
+    ) -> Option<SigMap<PartialSignature>> {
+        let mut tampered = partial_signatures.clone();
+        if script.missing_win_condition.contains(&self.index) {
+            // A player that wins no outcome has no win conditions, drop an outcome instead
+            match tampered.by_win_condition.keys().next().copied() {
+                Some(win_condition) => tampered.by_win_condition.remove(&win_condition),
+                None => tampered
+                    .by_outcome
+                    .pop_first()
+                    .map(|(_, signature)| signature),
+            };
+            return Some(tampered);
+        }
+        if script.corrupted_signature.contains(&self.index) {
+            let signature = tampered.by_outcome.values_mut().next()?;
+            *signature = MaybeScalar::Valid(Scalar::one());
+            return Some(tampered);
+        }
+        None
+    }
+
+    /// Submit signatures the coordinator has to turn away, checking nothing was stored
+    async fn submit_rejected(
+        &self,
+        coordinator: &Coordinator,
+        competition: &Competition,
+        entry_id: Uuid,
+        final_signatures: FinalSignatures,
+    ) -> Result<String, anyhow::Error> {
+        let errors = match coordinator
+            .submit_final_signatures(
+                self.pubkey.clone(),
+                competition.id,
+                entry_id,
+                final_signatures,
+            )
+            .await
+        {
+            Err(Error::Validation(errors)) => errors,
+            Err(e) => return Err(anyhow!("player {} tampered signatures: {}", self.index, e)),
+            Ok(()) => {
+                return Err(anyhow!(
+                    "player {} tampered signatures were accepted",
+                    self.index
+                ))
+            }
+        };
+
+        let after = coordinator.get_competition(competition.id).await?;
+        if after.get_state() != competition.get_state() {
+            return Err(anyhow!(
+                "player {} tampered signatures moved the competition to {}",
+                self.index,
+                after.get_state()
+            ));
+        }
+        let stored = coordinator
+            .get_entries(
+                self.pubkey.clone(),
+                SearchBy {
+                    event_ids: Some(vec![competition.id]),
+                    tags: None,
+                },
+            )
+            .await?
+            .into_iter()
+            .any(|entry| entry.id == entry_id && entry.partial_signatures.is_some());
+        if stored {
+            return Err(anyhow!(
+                "player {} tampered signatures were stored",
+                self.index
+            ));
+        }
+
+        Ok(format!(
+            "player {} signatures rejected: {}",
+            self.index,
+            errors
+                .errors
+                .iter()
+                .map(|error| format!("{} {}", error.code, error.field))
+                .join(", ")
+        ))
+    }
+}
+
+/// Run a scenario against a throwaway competitions database
+pub async fn run_scenario(scenario: &Scenario) -> Result<SimulationReport, anyhow::Error> {
+    let data_folder = std::env::temp_dir()
+        .join(format!("coordinator-sim-{}", Uuid::now_v7()))
+        .to_string_lossy()
+        .to_string();
+    create_folder(&data_folder);
+    let result = run_in(scenario, &data_folder).await;
+    let _ = fs::remove_dir_all(Path::new(&data_folder));
+    result
+}
+
+async fn run_in(scenario: &Scenario, data_folder: &str) -> Result<SimulationReport, anyhow::Error> {
+    let mut settings = Settings::default();
+    settings.db_settings.data_folder = data_folder.to_string();
+    settings.db_settings.blob_format = scenario.blob_format;
+    settings.coordinator_settings.relative_locktime_block_delta =
+        scenario.relative_locktime_block_delta;
+    // Scenario competitions sign hours out and ticks don't follow the wall clock, the scripted
+    // oracle decides when the attestation shows up
+    settings.coordinator_settings.attestation_pre_window_secs = 2 * 24 * 60 * 60;
+    settings.coordinator_settings.attestation_poll_interval_secs = 0;
+    settings.coordinator_settings.insufficient_funding_policy =
+        scenario.insufficient_funding_policy;
+    let pool_config: DatabasePoolConfig = settings.db_settings.clone().into();
+    let db = DBConnection::new(
+        data_folder,
+        "competitions",
+        pool_config,
+        DatabaseType::Competitions,
+    )
+    .await
+    .map_err(|e| anyhow!("Error setting up simulation db: {}", e))?;
+
+    let mut rng = ChaCha20Rng::seed_from_u64(scenario.seed);
+    let clock = SimClock::default();
+    let oracle = Arc::new(MockOracle::new(rng.random()));
+    let ln = MockLnClient::new();
+    let bitcoin = Arc::new(ScriptedBitcoin::new(
+        scenario.bitcoin.clone(),
+        clock.clone(),
+    ));
+    let (webhook_tx, mut webhook_rx) = mpsc::unbounded_channel();
+    let webhooks = WebhookNotifier::new(webhook_tx);
+
+    // Built once so a restarted coordinator talks to the same scripted services
+    script_oracle_outage(&oracle.handle(), &scenario.oracle, clock.tick());
+    let scripted_oracle: Arc<dyn Oracle> = oracle.clone();
+    let scripted_ln: Arc<dyn Ln> = Arc::new(ScriptedLn::new(
+        ln.clone(),
+        scenario.lightning.clone(),
+        clock.clone(),
+    ));
+    let scripted_keymeld: Arc<dyn Keymeld> = Arc::new(ScriptedKeymeld::new(
+        scenario.keymeld.clone(),
+        clock.clone(),
+    ));
+    let relays = Arc::new(MockRelays::new());
+    let start_coordinator = |settings: Settings| {
+        Coordinator::new(
+            scripted_oracle.clone(),
+            CompetitionStore::new(db.clone()),
+            bitcoin.clone(),
+            scripted_ln.clone(),
+            relays.clone(),
+            scripted_keymeld.clone(),
+            None,
+            scenario.relative_locktime_block_delta.into(),
+            scenario.name.clone(),
+            false,
+            InvoiceSettlementMode::Standard,
+            SharedConfig::new(settings),
+            webhooks.clone(),
+        )
+    };
+    let mut coordinator = start_coordinator(settings.clone()).await?;
+
+    let mut players: Vec<SimPlayer> = (0..scenario.players)
+        .map(|index| SimPlayer::new(index, &mut rng))
+        .collect();
+
+    let total_competition_pool = scenario
+        .entry_fee
+        .checked_mul(scenario.players as u64)
+        .ok_or_else(|| anyhow!("scenario entry fee times players overflows"))?;
+    let start = OffsetDateTime::now_utc() + Duration::hours(6);
+    let competition = coordinator
+        .create_competition(CreateEvent {
+            total_allowed_entries: scenario.players,
+            entry_fee: scenario.entry_fee,
+            coordinator_fee_percentage: scenario.coordinator_fee_percentage,
+            total_competition_pool,
+            relative_locktime_block_delta: Some(scenario.relative_locktime_block_delta),
+            ..CreateEvent::fixture(start)
+        })
+        .await?;
+
+    for player in players.iter_mut() {
+        let ticket = coordinator
+            .request_ticket(
+                player.pubkey.clone(),
+                competition.id,
+                BitcoinPublicKey::from_slice(&player.seckey.base_point_mul().serialize())?,
+            )
+            .await?;
+        if scenario.lightning.unpaid_players.contains(&player.index) {
+            continue;
+        }
+        if scenario.lightning.comped_players.contains(&player.index) {
+            coordinator
+                .comp_ticket(ticket.ticket_id, "simulation", None)
+                .await?;
+        } else {
+            ln.accept_invoice(&ticket.payment_hash)
+                .map_err(|e| anyhow!("player {} failed to pay: {}", player.index, e))?;
+            coordinator
+                .handle_invoice_accepted(competition.id, &ticket.payment_hash)
+                .await?;
+        }
+
+        let registers_with_keymeld =
+            scenario.keymeld.enabled && !scenario.keymeld.dropouts.contains(&player.index);
+        let entry = coordinator
+            .add_entry(
+                player.pubkey.clone(),
+                AddEntry {
+                    id: Uuid::now_v7(),
+                    ticket_id: ticket.ticket_id,
+                    ephemeral_pubkey: player.pubkey.clone(),
+                    ephemeral_privatekey_encrypted: player
+                        .encrypt_to_self(&hex::encode(player.seckey.serialize()))?,
+                    payout_hash: sha256::Hash::hash(&player.payout_preimage).to_string(),
+                    payout_preimage_encrypted: player
+                        .encrypt_to_self(&hex::encode(player.payout_preimage))?,
+                    event_id: competition.id,
+                    expected_observations: vec![WeatherChoices {
+                        stations: "KORD".to_string(),
+                        wind_speed: Some(ValueOptions::Over),
+                        temp_high: Some(ValueOptions::Par),
+                        temp_low: Some(ValueOptions::Under),
+                    }],
+                    encrypted_keymeld_private_key: registers_with_keymeld.then(|| {
+                        if scenario.keymeld.bad_registrations.contains(&player.index) {
+                            format!("{}-{}", BAD_REGISTRATION_PREFIX, player.index)
+                        } else {
+                            format!("simulated-key-{}", player.index)
+                        }
+                    }),
+                    keymeld_auth_pubkey: registers_with_keymeld.then(|| player.pubkey.clone()),
+                },
+            )
+            .await?;
+        player.entry_id = Some(entry.id);
+    }
+
+    let mut trace = Vec::new();
+    let mut ticks_run = 0;
+    let mut funding_psbt_rebuilt = false;
+    for tick in 0..scenario.ticks {
+        clock.set(tick);
+        if tick > 0 {
+            bitcoin.advance(tick);
+        }
+        script_oracle_outage(&oracle.handle(), &scenario.oracle, tick);
+        if scenario.oracle.attest_at_tick == Some(tick) {
+            let attestation = if scenario.oracle.invalid_attestation {
+                ScriptedAttestation::InvalidScalar
+            } else {
+                ScriptedAttestation::Outcome(scenario.oracle.outcome)
+            };
+            oracle
+                .handle()
+                .set_attestation_for(competition.id, attestation);
+        }
+
+        for spend in scenario
+            .bitcoin
+            .outcome_spends
+            .iter()
+            .filter(|spend| spend.tick == tick)
+        {
+            let current = coordinator.get_competition(competition.id).await?;
+            let transaction = outcome_spend(&coordinator, &current, spend.by_winner).await?;
+            bitcoin.record_broadcast(&transaction)?;
+        }
+
+        if scenario.rotate_key_at == Some(tick) {
+            settings
+                .coordinator_settings
+                .signing_keys
+                .push(SigningKeySettings {
+                    id: format!("rotated-{}", tick),
+                    key_file: format!("{}/rotated_key_{}.pem", data_folder, tick),
+                    secrets: SecretsSettings::File,
+                });
+            coordinator = start_coordinator(settings.clone()).await?;
+            let keys = CoordinatorKeyring::load(
+                bitcoin.get_derived_private_key().await?,
+                &settings.coordinator_settings.signing_keys,
+            )?;
+            let rotation =
+                reencrypt_keymeld_sessions(&coordinator.competition_store, &keys).await?;
+            trace.push(TraceStep {
+                tick,
+                block_height: bitcoin.height(),
+                state: "key_rotated".to_string(),
+                summary: format!(
+                    "coordinator restarted on key {}, {} keymeld session secrets re-encrypted",
+                    rotation.key_id,
+                    rotation.reencrypted_sessions.len()
+                ),
+            });
+        }
+
+        if scenario.keymeld.reregister_at == Some(tick) {
+            for player in players
+                .iter()
+                .filter(|player| scenario.keymeld.bad_registrations.contains(&player.index))
+            {
+                let entry_id = player
+                    .entry_id
+                    .ok_or_else(|| anyhow!("player {} has no entry", player.index))?;
+                coordinator
+                    .reset_keymeld_registration(competition.id, entry_id)
+                    .await?;
+                coordinator
+                    .reregister_keymeld_participant(
+                        player.pubkey.clone(),
+                        competition.id,
+                        entry_id,
+                        KeymeldRegistration {
+                            encrypted_keymeld_private_key: format!(
+                                "simulated-key-{}",
+                                player.index
+                            ),
+                            keymeld_auth_pubkey: player.pubkey.clone(),
+                        },
+                    )
+                    .await?;
+                trace.push(TraceStep {
+                    tick,
+                    block_height: bitcoin.height(),
+                    state: "keymeld_reregistered".to_string(),
+                    summary: format!("player {} registered with keymeld again", player.index),
+                });
+            }
+        }
+
+        if !scenario.keymeld.enabled {
+            let current = coordinator.get_competition(competition.id).await?;
+            for player in &players {
+                if let Some(summary) = player
+                    .sign(&coordinator, &current, &scenario.signing)
+                    .await?
+                {
+                    trace.push(TraceStep {
+                        tick,
+                        block_height: bitcoin.height(),
+                        state: "signatures_rejected".to_string(),
+                        summary,
+                    });
+                }
+            }
+        }
+
+        coordinator.competition_handler().await?;
+        ticks_run = tick + 1;
+
+        if scenario.bitcoin.rebuild_funding_psbt && !funding_psbt_rebuilt {
+            let current = coordinator.get_competition(competition.id).await?;
+            if current.aggregated_nonces.is_some() && current.signed_contract.is_none() {
+                rebuild_funding_psbt(&coordinator, current).await?;
+                funding_psbt_rebuilt = true;
+            }
+        }
+
+        while let Ok(job) = webhook_rx.try_recv() {
+            if let WebhookJob::Event(payload) = job {
+                trace.push(TraceStep {
+                    tick,
+                    block_height: bitcoin.height(),
+                    state: payload.event_type,
+                    summary: payload.summary,
+                });
+            }
+        }
+
+        if coordinator
+            .get_competition(competition.id)
+            .await?
+            .skip_competition()
+        {
+            break;
+        }
+    }
+
+    let competition = coordinator.get_competition(competition.id).await?;
+    let pnl = coordinator
+        .competition_store
+        .get_pnl(competition.id)
+        .await?;
+    db.close().await;
+
+    Ok(SimulationReport {
+        scenario: scenario.name.clone(),
+        seed: scenario.seed,
+        ticks_run,
+        final_state: competition.get_state().to_string(),
+        trace,
+        competition: ExtendCompetition::from(competition),
+        pnl,
+    })
+}
+
+/// Swap the funding psbt for one spending a different wallet output, as a rebuild with another
+/// coin selection would. The contract is unchanged but the funding outpoint moves.
+/// Fail every oracle request with a transient error while `tick` is inside one of the script's
+/// outages, and let them through again once it's over
+fn script_oracle_outage(oracle: &MockOracleHandle, script: &OracleScript, tick: u32) {
+    let failing = if scenario::in_any(&script.outages, tick) {
+        usize::MAX
+    } else {
+        0
+    };
+    for endpoint in OracleEndpoint::ALL {
+        oracle.set_failure_mode(endpoint, OracleFailure::Transient, failing);
+    }
+}
+
+async fn rebuild_funding_psbt(
+    coordinator: &Coordinator,
+    mut competition: Competition,
+) -> Result<(), anyhow::Error> {
+    let mut psbt = Psbt::from_str(
+        competition
+            .funding_psbt_base64
+            .as_deref()
+            .ok_or_else(|| anyhow!("competition {} has no funding psbt", competition.id))?,
+    )?;
+    let funding_outpoint = competition
+        .funding_outpoint
+        .ok_or_else(|| anyhow!("competition {} has no funding outpoint", competition.id))?;
+    psbt.unsigned_tx.input[0].previous_output.vout += 1;
+
+    competition.funding_outpoint = Some(OutPoint {
+        txid: psbt.unsigned_tx.compute_txid(),
+        vout: funding_outpoint.vout,
+    });
+    competition.funding_psbt_base64 = Some(psbt.to_string());
+    coordinator
+        .competition_store
+        .update_competitions(std::slice::from_mut(&mut competition))
+        .await?;
+    Ok(())
+}
+
+/// A transaction spending the competition's outcome output that the coordinator didn't
+/// broadcast: the split transaction unlocked with a winner's ticket preimage, or a sweep that has
+/// nothing to do with the contract
+async fn outcome_spend(
+    coordinator: &Coordinator,
+    competition: &Competition,
+    by_winner: bool,
+) -> Result<Transaction, anyhow::Error> {
+    let (Some(signed_contract), Some(_)) = (
+        competition.signed_contract.as_ref(),
+        competition.outcome_transaction.as_ref(),
+    ) else {
+        return Err(anyhow!(
+            "competition {} has no outcome transaction to spend yet",
+            competition.id
+        ));
+    };
+    let outcome = competition.get_current_outcome()?;
+
+    if !by_winner {
+        let (input, prevout) = signed_contract.outcome_close_tx_input_and_prevout(&outcome)?;
+        return Ok(Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: input.previous_output,
+                witness: Witness::from_slice(&[[0u8; 64]]),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: prevout.value,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        });
+    }
+
+    let player_index = signed_contract
+        .params()
+        .outcome_payouts
+        .get(&outcome)
+        .and_then(|winners| winners.keys().next().copied())
+        .ok_or_else(|| anyhow!("outcome {:?} has no winners", outcome))?;
+    let entry = coordinator
+        .competition_store
+        .get_competition_entries(competition.id, vec![EntryStatus::Paid])
+        .await?
+        .into_iter()
+        .find(|entry| entry.player_index == Some(player_index))
+        .ok_or_else(|| anyhow!("no entry for winning player {}", player_index))?;
+    let ticket = coordinator
+        .competition_store
+        .get_ticket(entry.ticket_id)
+        .await?;
+    let preimage = dlctix::hashlock::preimage_from_hex(&ticket.encrypted_preimage)
+        .map_err(|e| anyhow!("ticket {} has a bad preimage: {}", ticket.id, e))?;
+    signed_contract
+        .signed_split_tx(
+            &WinCondition {
+                outcome,
+                player_index,
+            },
+            preimage,
+        )
+        .map_err(|e| anyhow!("failed to sign the split tx: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{CompetitionError, LedgerEntryKind},
+        infra::db::{decode_blob, encode_blob, EncodedBlob},
+        BlobFormat,
+    };
+    use dlctix::SignedContract;
+
+    async fn run(contents: &str) -> SimulationReport {
+        let scenario = Scenario::from_toml(contents).unwrap();
+        let report = run_scenario(&scenario).await.unwrap();
+        report.check(&scenario.expect).unwrap();
+        report
+    }
+
+    fn states(report: &SimulationReport) -> Vec<&str> {
+        report
+            .trace
+            .iter()
+            .map(|step| step.state.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_happy_path_completes() {
+        let report = run(include_str!("../../scenarios/happy_path.toml")).await;
+        assert_eq!(report.final_state, "completed");
+        assert!(report.competition.signed_contract.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_happy_path_completes_with_cbor_blobs() {
+        let mut scenario =
+            Scenario::from_toml(include_str!("../../scenarios/happy_path.toml")).unwrap();
+        scenario.blob_format = BlobFormat::Cbor;
+        let report = run_scenario(&scenario).await.unwrap();
+        report.check(&scenario.expect).unwrap();
+
+        // The signed contract, the largest blob, round trips in each format and is smaller as CBOR
+        let signed_contract = report.competition.signed_contract.unwrap();
+        let reference = serde_json::to_string(&signed_contract).unwrap();
+        let mut sizes = Vec::new();
+        for format in [BlobFormat::Json, BlobFormat::Cbor] {
+            let bytes = signed_contract_blob(&signed_contract, format);
+            let decoded: SignedContract = decode_blob(&bytes, "signed_contract").unwrap();
+            assert_eq!(serde_json::to_string(&decoded).unwrap(), reference);
+            sizes.push(bytes.len());
+        }
+        assert!(sizes[1] < sizes[0]);
+    }
+
+    fn signed_contract_blob(signed_contract: &SignedContract, format: BlobFormat) -> Vec<u8> {
+        match encode_blob(signed_contract, format).unwrap() {
+            EncodedBlob::Json(json) => json.into_bytes(),
+            EncodedBlob::Cbor(cbor) => cbor,
+        }
+    }
+
+    /// Size and decode time of the happy path's signed contract in each blob format, run with
+    /// `cargo test -p coordinator signed_contract_decode_time -- --ignored --nocapture`
+    #[tokio::test]
+    #[ignore]
+    async fn test_signed_contract_decode_time() {
+        let report = run(include_str!("../../scenarios/happy_path.toml")).await;
+        let signed_contract = report.competition.signed_contract.unwrap();
+        for format in [BlobFormat::Json, BlobFormat::Cbor] {
+            let bytes = signed_contract_blob(&signed_contract, format);
+            let started = std::time::Instant::now();
+            for _ in 0..20 {
+                decode_blob::<SignedContract>(&bytes, "signed_contract").unwrap();
+            }
+            println!(
+                "signed contract as {:?}: {} bytes, {:?} per decode",
+                format,
+                bytes.len(),
+                started.elapsed() / 20
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_same_scenario_replays_same_trace() {
+        let first = run(include_str!("../../scenarios/happy_path.toml")).await;
+        let second = run(include_str!("../../scenarios/happy_path.toml")).await;
+        assert_eq!(states(&first), states(&second));
+        let ticks = |report: &SimulationReport| -> Vec<(u32, u32)> {
+            report
+                .trace
+                .iter()
+                .map(|step| (step.tick, step.block_height))
+                .collect()
+        };
+        assert_eq!(ticks(&first), ticks(&second));
+    }
+
+    #[tokio::test]
+    async fn test_oracle_outage_delays_attestation() {
+        let report = run(include_str!("../../scenarios/oracle_outage.toml")).await;
+        let attested = report
+            .trace
+            .iter()
+            .find(|step| step.state == "attested")
+            .unwrap();
+        assert!(attested.tick >= 12);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_attestation_is_rejected() {
+        let report = run(include_str!("../../scenarios/invalid_attestation.toml")).await;
+        assert!(report.competition.attestation.is_none());
+        assert!(!states(&report).contains(&"attested"));
+    }
+
+    #[tokio::test]
+    async fn test_comped_entry_is_funded_by_the_coordinator() {
+        let report = run(include_str!("../../scenarios/comped_entry.toml")).await;
+        assert!(states(&report).contains(&"funding_broadcasted"));
+        assert_eq!(report.competition.total_paid_entries, 3);
+        assert_eq!(report.competition.total_comped_entries, 1);
+        assert_eq!(
+            report
+                .competition
+                .event_submission
+                .coordinator_fee_percentage,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ledger_accounts_for_every_sat() {
+        let report = run(include_str!("../../scenarios/happy_path.toml")).await;
+        let pnl = &report.pnl;
+
+        // 100k entry fee plus the 10% coordinator fee from each of the three players
+        assert_eq!(pnl.amount(LedgerEntryKind::InvoiceSettled), 3 * 110_000);
+
+        // Everything the coordinator's wallet put into the funding transaction is either the
+        // contract's funding output or the miner fee
+        let funding_psbt =
+            Psbt::from_str(report.competition.funding_psbt_base64.as_ref().unwrap()).unwrap();
+        let funded: u64 = funding_psbt
+            .inputs
+            .iter()
+            .filter_map(|input| input.witness_utxo.as_ref())
+            .map(|utxo| utxo.value.to_sat())
+            .sum();
+        let funding_output = pnl.amount(LedgerEntryKind::FundingOutputCreated);
+        assert_eq!(
+            funding_output,
+            report
+                .competition
+                .event_submission
+                .total_competition_pool
+                .to_sat()
+        );
+        assert_eq!(
+            funded,
+            funding_output + pnl.amount(LedgerEntryKind::MinerFee)
+        );
+        assert_eq!(pnl.amount(LedgerEntryKind::EscrowInputConsumed), 0);
+
+        // The unpaid winner's share came back on chain, less the fees it paid from the contract
+        let swept = pnl.amount(LedgerEntryKind::ContractSweep);
+        assert!(swept > 0 && swept < funding_output);
+        assert_eq!(pnl.amount(LedgerEntryKind::PayoutSent), 0);
+
+        assert_eq!(
+            pnl.net_sats,
+            pnl.revenue_sats as i64 - pnl.costs_sats as i64
+        );
+        assert_eq!(pnl.revenue_sats, 3 * 110_000 + swept);
+    }
+
+    #[tokio::test]
+    async fn test_competition_completes_on_its_key_after_rotation() {
+        let report = run(include_str!("../../scenarios/key_rotation.toml")).await;
+        assert_eq!(report.final_state, "completed");
+        assert_eq!(
+            report.competition.coordinator_key_id.as_deref(),
+            Some("default")
+        );
+        assert!(states(&report).contains(&"key_rotated"));
+    }
+
+    #[tokio::test]
+    async fn test_keymeld_dropout_stalls_at_contract_created() {
+        let report = run(include_str!("../../scenarios/keymeld_dropout.toml")).await;
+        assert_eq!(report.final_state, "contract_created");
+        assert!(!states(&report).contains(&"awaiting_signatures"));
+    }
+
+    #[tokio::test]
+    async fn test_keymeld_bad_registration_recovers_after_reregistering() {
+        let report = run(include_str!("../../scenarios/keymeld_reregistration.toml")).await;
+        // Keygen stalled on the bad registration until it was replaced, then completed
+        assert!(report.competition.keymeld_keygen_completed_at.is_some());
+        assert!(report
+            .trace
+            .iter()
+            .any(|step| step.state == "keymeld_reregistered"));
+    }
+
+    #[tokio::test]
+    async fn test_winner_split_spend_leaves_their_share_alone() {
+        let report = run(include_str!("../../scenarios/winner_split_spend.toml")).await;
+        assert_eq!(report.final_state, "completed");
+        // The happy path sweeps the unpaid winner's split output back, here the winner went on
+        // chain first so the coordinator has nothing to reclaim
+        assert_eq!(report.pnl.amount(LedgerEntryKind::ContractSweep), 0);
+    }
+
+    #[tokio::test]
+    async fn test_foreign_outcome_spend_fails_the_competition() {
+        let report = run(include_str!("../../scenarios/foreign_outcome_spend.toml")).await;
+        assert!(!states(&report).contains(&"delta_broadcasted"));
+        assert!(report.competition.errors.iter().any(|recorded| matches!(
+            &recorded.error,
+            CompetitionError::UnexpectedSpend(message) if message.starts_with("outcome output")
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_mempool_rejection_is_retried() {
+        let report = run(include_str!("../../scenarios/mempool_rejection.toml")).await;
+        // Nothing was broadcast while the mempool refused it, the competition stayed attested
+        let outcome_broadcasted = report
+            .trace
+            .iter()
+            .find(|step| step.state == "outcome_broadcasted")
+            .unwrap();
+        assert!(outcome_broadcasted.tick >= 10);
+        assert!(!states(&report).contains(&"failed"));
+    }
+
+    #[tokio::test]
+    async fn test_underfunded_wallet_fails_the_competition() {
+        let report = run(include_str!("../../scenarios/underfunded_wallet.toml")).await;
+        assert!(report.competition.funding_psbt_base64.is_none());
+        assert!(report.competition.errors.iter().any(|recorded| matches!(
+            &recorded.error,
+            CompetitionError::InsufficientFunds(message) if message.contains("sats short")
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_competition_waits_for_the_wallet_to_be_topped_up() {
+        let report = run(include_str!("../../scenarios/wallet_top_up.toml")).await;
+        let contract_created = report
+            .trace
+            .iter()
+            .find(|step| step.state == "contract_created")
+            .unwrap();
+        assert!(contract_created.tick >= 8);
+    }
+
+    #[tokio::test]
+    async fn test_funding_psbt_rebuild_restarts_nonce_round() {
+        let report = run(include_str!("../../scenarios/funding_psbt_rebuild.toml")).await;
+        let restarted = report
+            .trace
+            .iter()
+            .position(|step| step.state == "nonce_round_restarted")
+            .unwrap();
+        let funded = report
+            .trace
+            .iter()
+            .position(|step| step.state == "funding_broadcasted")
+            .unwrap();
+        assert!(restarted < funded);
+        assert!(!states(&report).contains(&"failed"));
+        // The contract was signed with nonces seeded by the rebuilt funding outpoint
+        assert!(report.competition.signed_contract.is_some());
+        assert_eq!(
+            report.competition.nonce_seed_outpoint,
+            report.competition.funding_outpoint
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tampered_signatures_are_rejected_on_submission() {
+        let report = run(include_str!("../../scenarios/tampered_signatures.toml")).await;
+        let rejections: Vec<&TraceStep> = report
+            .trace
+            .iter()
+            .filter(|step| step.state == "signatures_rejected")
+            .collect();
+        assert_eq!(rejections.len(), 2);
+        assert!(rejections[0].summary.starts_with("player 0"));
+        assert!(rejections[0].summary.contains("missing_key"));
+        assert!(rejections[1].summary.starts_with("player 2"));
+        assert!(rejections[1].summary.contains("invalid_signature"));
+        // The honest signatures sent right after were accepted and signed the contract
+        assert!(report.competition.signed_contract.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_chunked_signature_uploads_sign_the_contract() {
+        let mut scenario =
+            Scenario::from_toml(include_str!("../../scenarios/happy_path.toml")).unwrap();
+        scenario.signing.chunked = vec![0, 2];
+        let report = run_scenario(&scenario).await.unwrap();
+        report.check(&scenario.expect).unwrap();
+        assert!(report.competition.signed_contract.is_some());
+    }
+
+    #[test]
+    fn test_scenario_rejects_unknown_player() {
+        let err = Scenario::from_toml(
+            r#"
+            name = "bad"
+            seed = 1
+            ticks = 5
+            players = 2
+
+            [lightning]
+            unpaid_players = [2]
+
+            [expect]
+            final_state = "completed"
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("player 2"));
+    }
+}
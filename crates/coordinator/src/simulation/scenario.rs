@@ -0,0 +1,280 @@
+use coordinator_core::Sats;
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+use crate::{BlobFormat, InsufficientFundingPolicy};
+
+/// A scripted run of one competition through the state machine. Every external service answers
+/// from this script, so the same scenario and seed always produce the same transition trace.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Seeds player keys, payout preimages, nonces and the mock oracle
+    pub seed: u64,
+    /// Upper bound on competition watcher ticks, the run stops early once the competition is
+    /// completed, failed or cancelled
+    pub ticks: u32,
+    pub players: usize,
+    #[serde(default = "default_entry_fee")]
+    pub entry_fee: Sats,
+    #[serde(default = "default_coordinator_fee_percentage")]
+    pub coordinator_fee_percentage: usize,
+    #[serde(default = "default_relative_locktime_block_delta")]
+    pub relative_locktime_block_delta: u16,
+    #[serde(default)]
+    pub oracle: OracleScript,
+    #[serde(default)]
+    pub bitcoin: BitcoinScript,
+    #[serde(default)]
+    pub lightning: LightningScript,
+    #[serde(default)]
+    pub keymeld: KeymeldScript,
+    #[serde(default)]
+    pub signing: SigningScript,
+    /// Encoding the run's database writes the large contract and signature blobs in
+    #[serde(default)]
+    pub blob_format: BlobFormat,
+    /// Tick the coordinator restarts with a new signing key and rotates stored keymeld session
+    /// secrets to it, the competition has to finish on the key it was created under
+    #[serde(default)]
+    pub rotate_key_at: Option<u32>,
+    /// What the coordinator does when its wallet can't fund the contract
+    #[serde(default)]
+    pub insufficient_funding_policy: InsufficientFundingPolicy,
+    pub expect: Expectation,
+}
+
+fn default_entry_fee() -> Sats {
+    Sats(100_000)
+}
+
+fn default_coordinator_fee_percentage() -> usize {
+    10
+}
+
+fn default_relative_locktime_block_delta() -> u16 {
+    2
+}
+
+/// Ticks `from..until`, `until` is exclusive
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TickRange {
+    pub from: u32,
+    pub until: u32,
+}
+
+impl TickRange {
+    pub fn contains(&self, tick: u32) -> bool {
+        self.from <= tick && tick < self.until
+    }
+}
+
+pub(crate) fn in_any(ranges: &[TickRange], tick: u32) -> bool {
+    ranges.iter().any(|range| range.contains(tick))
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OracleScript {
+    /// Tick at which the oracle signs `outcome`, it never attests when unset
+    pub attest_at_tick: Option<u32>,
+    /// Index of the attested outcome, with one winning place this is the winning player
+    #[serde(default)]
+    pub outcome: usize,
+    /// Publish an attestation that unlocks none of the outcomes instead of `outcome`
+    #[serde(default)]
+    pub invalid_attestation: bool,
+    /// Ticks during which every oracle request fails with a transient error
+    #[serde(default)]
+    pub outages: Vec<TickRange>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BitcoinScript {
+    #[serde(default = "default_start_height")]
+    pub start_height: u32,
+    #[serde(default = "default_blocks_per_tick")]
+    pub blocks_per_tick: u32,
+    /// Blocks between a broadcast and the block that confirms it
+    #[serde(default)]
+    pub confirmation_delay_blocks: u32,
+    /// Ticks without new blocks
+    #[serde(default)]
+    pub stalls: Vec<TickRange>,
+    /// Ticks during which bitcoind can't be reached for broadcasts
+    #[serde(default)]
+    pub broadcast_outages: Vec<TickRange>,
+    /// Ticks during which the mempool refuses the coordinator's contract transactions as having
+    /// invalid signatures
+    #[serde(default)]
+    pub mempool_rejections: Vec<TickRange>,
+    /// Spends of the outcome output broadcast by someone other than the coordinator
+    #[serde(default)]
+    pub outcome_spends: Vec<OutcomeSpendScript>,
+    /// Rebuild the funding psbt with a different coin selection once the coordinator has
+    /// aggregated the players' nonces, moving the funding outpoint out from under them
+    #[serde(default)]
+    pub rebuild_funding_psbt: bool,
+    /// Confirmed balance of the coordinator wallet, enough to fund any pool when unset
+    #[serde(default)]
+    pub wallet_balance_sats: Option<u64>,
+    /// Tick the operator tops the wallet up, from then on it can fund any pool
+    #[serde(default)]
+    pub wallet_topped_up_at: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutcomeSpendScript {
+    /// Tick of the spend, the outcome transaction has to be published by then
+    pub tick: u32,
+    /// A winner broadcasts the split transaction with their ticket preimage. Otherwise the output
+    /// is swept by a transaction unrelated to the contract, as with a leaked key.
+    #[serde(default)]
+    pub by_winner: bool,
+}
+
+fn default_start_height() -> u32 {
+    100
+}
+
+fn default_blocks_per_tick() -> u32 {
+    1
+}
+
+impl Default for BitcoinScript {
+    fn default() -> Self {
+        Self {
+            start_height: default_start_height(),
+            blocks_per_tick: default_blocks_per_tick(),
+            confirmation_delay_blocks: 0,
+            stalls: vec![],
+            broadcast_outages: vec![],
+            mempool_rejections: vec![],
+            outcome_spends: vec![],
+            rebuild_funding_psbt: false,
+            wallet_balance_sats: None,
+            wallet_topped_up_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LightningScript {
+    /// Players that reserve a ticket but never pay its hold invoice
+    #[serde(default)]
+    pub unpaid_players: Vec<usize>,
+    /// Players whose reserved ticket an admin comps instead of them paying the hold invoice
+    #[serde(default)]
+    pub comped_players: Vec<usize>,
+    /// Ticks during which settling hold invoices fails
+    #[serde(default)]
+    pub settle_failures: Vec<TickRange>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeymeldScript {
+    /// Sign through keymeld instead of collecting nonces and partial signatures from players
+    #[serde(default)]
+    pub enabled: bool,
+    /// Players that never register with the keygen session
+    #[serde(default)]
+    pub dropouts: Vec<usize>,
+    /// Keygen status reported by keymeld, overriding the registration count
+    #[serde(default)]
+    pub statuses: Vec<KeymeldStatusScript>,
+    /// Players whose registration keymeld accepts but can't use, so keygen never completes
+    #[serde(default)]
+    pub bad_registrations: Vec<usize>,
+    /// Tick the operator removes the bad registrations and those players register again
+    #[serde(default)]
+    pub reregister_at: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SigningScript {
+    /// Players that first submit partial signatures missing one of their win conditions
+    #[serde(default)]
+    pub missing_win_condition: Vec<usize>,
+    /// Players that first submit partial signatures with one signature corrupted
+    #[serde(default)]
+    pub corrupted_signature: Vec<usize>,
+    /// Players that upload their partial signatures in two chunks rather than all at once
+    #[serde(default)]
+    pub chunked: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeymeldStatusScript {
+    pub from: u32,
+    pub until: u32,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    pub final_state: String,
+    /// States the competition has to pass through on the way
+    #[serde(default)]
+    pub visits: Vec<String>,
+}
+
+impl Scenario {
+    pub fn from_toml(contents: &str) -> Result<Self, anyhow::Error> {
+        let scenario: Scenario = toml::from_str(contents)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read scenario {}: {}", path.display(), e))?;
+        Self::from_toml(&contents)
+    }
+
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.players < 2 {
+            return Err(anyhow::anyhow!(
+                "scenario {} needs at least 2 players",
+                self.name
+            ));
+        }
+        let out_of_range = self
+            .lightning
+            .unpaid_players
+            .iter()
+            .chain(self.lightning.comped_players.iter())
+            .chain(self.keymeld.dropouts.iter())
+            .chain(self.keymeld.bad_registrations.iter())
+            .chain(self.signing.missing_win_condition.iter())
+            .chain(self.signing.corrupted_signature.iter())
+            .find(|player| **player >= self.players);
+        if let Some(player) = out_of_range {
+            return Err(anyhow::anyhow!(
+                "scenario {} refers to player {} but only has {} players",
+                self.name,
+                player,
+                self.players
+            ));
+        }
+        if self.oracle.outcome >= self.players {
+            return Err(anyhow::anyhow!(
+                "scenario {} attests outcome {} but only has {} outcomes",
+                self.name,
+                self.oracle.outcome,
+                self.players
+            ));
+        }
+        Ok(())
+    }
+}
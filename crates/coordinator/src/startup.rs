@@ -1,32 +1,65 @@
 use crate::{
-    api::routes::{
-        add_event_entry, admin_competition_fragment, admin_create_competition_handler,
-        admin_delete_competition_handler, admin_fee_estimates_fragment, admin_page_handler,
-        admin_send_bitcoin_handler, admin_settle_test_invoice_handler,
-        admin_wallet_address_fragment, admin_wallet_balance_fragment, admin_wallet_fragment,
-        admin_wallet_outputs_fragment, change_password, competitions_fragment,
-        competitions_rows_fragment, create_competition, entries_fragment, entry_detail_fragment,
-        entry_form_fragment, forgot_password_challenge, forgot_password_reset,
-        get_aggregate_nonces, get_balance, get_competition, get_competitions,
-        get_contract_parameters, get_entries, get_estimated_fee_rates, get_next_address,
-        get_outputs, get_ticket_status, health, leaderboard_fragment, leaderboard_rows_fragment,
-        login, login_username, payouts_fragment, public_page_handler, register, register_username,
-        request_competition_ticket, send_to_address, submit_final_signatures, submit_public_nonces,
-        submit_ticket_payout,
+    api::{
+        admin_session::{session_token, AdminSessions},
+        body_limits::{reject_unexpected_bodies, BodyLimits},
+        extractors::{AuthError, NostrAuth, NIP98_MAX_AGE_SECS},
+        routes::{
+            add_event_entry, admin_alerts_fragment, admin_auth_activity_handler,
+            admin_blob_integrity_handler, admin_cancel_competition_handler,
+            admin_clone_competition_handler, admin_comp_ticket_handler,
+            admin_competition_changes_handler, admin_competition_fragment,
+            admin_consistency_report_handler, admin_contract_estimate_fragment,
+            admin_contract_estimate_handler, admin_create_competition_handler,
+            admin_delete_competition_handler, admin_fee_estimates_fragment,
+            admin_force_complete_competition_handler, admin_invite_handler,
+            admin_list_payouts_handler, admin_list_sponsorships_handler, admin_login_handler,
+            admin_login_page_handler, admin_logout_handler, admin_page_handler,
+            admin_player_order_handler, admin_pnl_fragment, admin_pnl_report_handler,
+            admin_rebroadcast_expiry_handler, admin_release_ticket_handler,
+            admin_replay_webhook_handler, admin_reset_keymeld_registration_handler,
+            admin_retry_competition_handler, admin_send_bitcoin_handler,
+            admin_settle_test_invoice_handler, admin_solvency_handler,
+            admin_sponsor_competition_handler, admin_wallet_address_fragment,
+            admin_wallet_balance_fragment, admin_wallet_fragment, admin_wallet_outputs_fragment,
+            admin_webhooks_fragment, change_password, competitions_fragment,
+            competitions_rows_fragment, create_competition, entries_fragment,
+            entry_detail_fragment, entry_form_fragment, forgot_password_challenge,
+            forgot_password_reset, get_aggregate_nonces, get_balance, get_competition,
+            get_competition_by_slug, get_competition_leaderboard, get_competition_oracle_event,
+            get_competition_timeline, get_competitions, get_contract_disclosure,
+            get_contract_parameters, get_coordinator_info, get_eligible_payouts, get_entries,
+            get_entry_preview, get_entry_receipt, get_estimated_fee_rates, get_next_address,
+            get_outputs, get_sessions, get_ticket_receipt, get_ticket_status, health,
+            leaderboard_fragment, leaderboard_rows_fragment, login, login_username,
+            open_competitions_atom_feed, open_competitions_json_feed, payouts_fragment,
+            public_page_handler, ready, regenerate_ticket_invoice, register,
+            register_escrow_reclaim, register_username, reload_config, request_competition_ticket,
+            reregister_keymeld_participant, revoke_session, send_to_address,
+            submit_final_signatures, submit_partial_signature_chunk, submit_public_nonces,
+            submit_ticket_payout, task_metrics, verify_funding_psbt,
+        },
     },
-    config::Settings,
+    config::{Settings, SharedConfig},
     domain::{
-        CompetitionStore, CompetitionWatcher, Coordinator, InvoiceSubscriber, InvoiceWatcher,
-        PaymentSubscriber, PayoutWatcher, UserInfo, UserStore,
+        ip_hash_key, AttestationPoller, AuthAttempt, AuthEventPruner, CompetitionStore,
+        CompetitionWatcher, Coordinator, InFlightCompetitions, InvoiceSubscriber, InvoiceWatcher,
+        PaymentSubscriber, PayoutWatcher, ReservationSweeper, UserInfo, UserStore, WebhookNotifier,
+        WebhookStore, WebhookWorker,
     },
     infra::{
         bitcoin::{Bitcoin, BitcoinClient, BitcoinSyncWatcher},
         db::{DBConnection, DatabasePoolConfig, DatabaseType},
+        db_encryption::load_db_encryption_key,
         file_utils::create_folder,
         keymeld::create_keymeld_service,
-        lightning::{Ln, LnClient},
+        lightning::{Ln, LnClient, PRIMARY_LN_BACKEND},
+        lightning_failover::FailoverLn,
+        nostr_relays::RelayPublisher,
         oracle::{Oracle, OracleClient},
+        secrets::secret_backend,
     },
+    supervisor::{RestartPolicy, TaskSupervisor},
+    templates::admin::admin_login_page,
 };
 
 // Mock implementations only available with e2e-testing feature or debug builds
@@ -37,41 +70,56 @@ use crate::infra::{
 use anyhow::anyhow;
 use axum::{
     body::Body,
-    extract::{connect_info::IntoMakeServiceWithConnectInfo, ConnectInfo, Path, Request, State},
-    http::{header, Extensions, HeaderValue, StatusCode},
+    extract::{
+        connect_info::IntoMakeServiceWithConnectInfo, ConnectInfo, DefaultBodyLimit,
+        FromRequestParts, Path, Request, State,
+    },
+    http::{header, Extensions, HeaderMap, HeaderValue, StatusCode},
     middleware::{self, AddExtension, Next},
-    response::{IntoResponse, Response},
+    response::{Html, IntoResponse, Response},
     routing::{get, post},
     serve::Serve,
     Router,
 };
+use coordinator_core::BitcoinNetwork;
 use dlctix::secp::Scalar;
 use hyper::{
-    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    header::{ACCEPT, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE},
     Method,
 };
 use log::{error, info, warn};
+use nostr_sdk::ToBech32;
 use reqwest_middleware::{
     reqwest::{self, Client, Url},
     ClientBuilder, ClientWithMiddleware, Middleware,
 };
 use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
-use std::{collections::HashMap, net::SocketAddr, str::FromStr};
+use secrecy::ExposeSecret;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
 use std::{sync::Arc, time::Duration};
 use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::RwLock;
-use tokio::{net::TcpListener, select, task::JoinHandle};
-use tokio_util::{sync::CancellationToken, task::TaskTracker};
-use tower_http::cors::{AllowOrigin, CorsLayer};
+use tokio::{net::TcpListener, select};
+use tokio_util::sync::CancellationToken;
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, CorsLayer},
+    decompression::RequestDecompressionLayer,
+};
 pub struct Application {
     server: Serve<
         TcpListener,
         IntoMakeServiceWithConnectInfo<Router, SocketAddr>,
         AddExtension<Router, ConnectInfo<SocketAddr>>,
     >,
-    cancellation_token: CancellationToken,
-    background_tasks: TaskTracker,
+    supervisor: TaskSupervisor,
     db_connections: Vec<DBConnection>,
+    in_flight: InFlightCompetitions,
+    drain_timeout: std::time::Duration,
 }
 
 impl Application {
@@ -81,31 +129,60 @@ impl Application {
             config.api_settings.domain, config.api_settings.port
         );
         let listener = SocketAddr::from_str(&address)?;
-        let (app_state, background_tasks, cancellation_token, db_connections) =
-            build_app(config.clone()).await?;
+        let (app_state, supervisor, db_connections) = build_app(config.clone()).await?;
+        let in_flight = app_state.coordinator.in_flight().clone();
         let server = build_server(listener, app_state, config.api_settings.origins).await?;
         Ok(Self {
             server,
-            cancellation_token,
-            background_tasks,
+            supervisor,
             db_connections,
+            in_flight,
+            drain_timeout: std::time::Duration::from_secs(
+                config.coordinator_settings.shutdown_drain_timeout_secs,
+            ),
         })
     }
 
     pub async fn run_until_stopped(self) -> Result<(), anyhow::Error> {
         info!("Starting server...");
-        match self.server.with_graceful_shutdown(shutdown_signal()).await {
+        // Start draining the watchers as soon as the signal arrives, not once the last HTTP
+        // connection has closed
+        let supervisor = self.supervisor.clone();
+        let shutdown = async move {
+            shutdown_signal().await;
+            tokio::spawn(async move { supervisor.shutdown().await });
+        };
+        match self.server.with_graceful_shutdown(shutdown).await {
             Ok(_) => {
                 info!("Server shutdown initiated");
-                self.cancellation_token.cancel();
 
-                let timeout = tokio::time::sleep(std::time::Duration::from_secs(10));
+                let in_flight = self.in_flight.snapshot();
+                if in_flight.is_empty() {
+                    info!("No competitions in flight at shutdown");
+                }
+                for (competition_id, state) in &in_flight {
+                    info!(
+                        "Waiting up to {}s for competition {} (picked up in {}) to finish",
+                        self.drain_timeout.as_secs(),
+                        competition_id,
+                        state
+                    );
+                }
+
+                let timeout = tokio::time::sleep(self.drain_timeout);
                 select! {
-                    _ = self.background_tasks.wait() => {
+                    _ = self.supervisor.shutdown() => {
                         info!("Background tasks completed gracefully");
                     }
                     _ = timeout => {
                         warn!("Background tasks timed out during shutdown");
+                        for (competition_id, state) in self.in_flight.snapshot() {
+                            warn!(
+                                "Competition {} (picked up in {}) was still processing at shutdown, \
+                                 it is picked up again from its last saved state on the next start",
+                                competition_id, state
+                            );
+                        }
                     }
                 }
 
@@ -120,13 +197,8 @@ impl Application {
             }
             Err(e) => {
                 error!("Server shutdown error: {}", e);
-                self.cancellation_token.cancel();
 
-                let _ = tokio::time::timeout(
-                    std::time::Duration::from_secs(5),
-                    self.background_tasks.wait(),
-                )
-                .await;
+                let _ = tokio::time::timeout(self.drain_timeout, self.supervisor.shutdown()).await;
 
                 Err(anyhow!("Error during server shutdown: {}", e))
             }
@@ -141,29 +213,42 @@ pub struct AppState {
     pub remote_url: String,
     pub oracle_url: String,
     pub esplora_url: String,
-    pub network: String,
+    pub network: BitcoinNetwork,
     pub bitcoin: Arc<dyn Bitcoin>,
     pub coordinator: Arc<Coordinator>,
     pub users_info: Arc<UserInfo>,
-    pub background_threads: Arc<HashMap<String, JoinHandle<()>>>,
+    pub tasks: TaskSupervisor,
+    pub admin_sessions: AdminSessions,
     pub forgot_password_challenges: Arc<RwLock<HashMap<String, (String, std::time::Instant)>>>,
+    pub settings: SharedConfig,
+    pub webhooks: WebhookNotifier,
+    pub webhook_store: WebhookStore,
 }
 
 pub async fn build_app(
     config: Settings,
-) -> Result<(AppState, TaskTracker, CancellationToken, Vec<DBConnection>), anyhow::Error> {
+) -> Result<(AppState, TaskSupervisor, Vec<DBConnection>), anyhow::Error> {
+    config.validate()?;
     info!(
         "Static UI assets configured at {}",
         config.ui_settings.ui_dir
     );
 
+    let wallet_secrets =
+        secret_backend(&config.secrets_settings, &config.bitcoin_settings.seed_path);
+    let nostr_secrets = secret_backend(
+        &config.secrets_settings,
+        &config.coordinator_settings.private_key_file,
+    );
+    info!("Secrets backend: {}", wallet_secrets.name());
+
     // Create Bitcoin client (real or mock based on config)
     #[cfg(any(feature = "e2e-testing", debug_assertions))]
     let bitcoin_client: Arc<dyn Bitcoin> = if config.bitcoin_settings.mock_enabled {
         info!("Mock Bitcoin client configured");
-        Arc::new(MockBitcoinClient::new(config.bitcoin_settings.network))
+        Arc::new(MockBitcoinClient::new(config.bitcoin_settings.chain()))
     } else {
-        let client = BitcoinClient::new(&config.bitcoin_settings)
+        let client = BitcoinClient::new(&config.bitcoin_settings, wallet_secrets.clone())
             .await
             .map(Arc::new)?;
         info!("Bitcoin service configured");
@@ -177,7 +262,7 @@ pub async fn build_app(
                 "Mock Bitcoin client requires e2e-testing feature or debug build"
             ));
         }
-        let client = BitcoinClient::new(&config.bitcoin_settings)
+        let client = BitcoinClient::new(&config.bitcoin_settings, wallet_secrets.clone())
             .await
             .map(Arc::new)?;
         info!("Bitcoin service configured");
@@ -225,6 +310,26 @@ pub async fn build_app(
         ln_client
     };
 
+    let ln: Arc<dyn Ln> = if config.ln_settings.failover.is_empty() {
+        ln
+    } else {
+        let mut backends = vec![(PRIMARY_LN_BACKEND.to_string(), ln)];
+        for backend in &config.ln_settings.failover {
+            let ln_client: Arc<dyn Ln> = LnClient::new(
+                reqwest_client.clone(),
+                config.ln_settings.for_backend(backend),
+            )
+            .await
+            .map(Arc::new)?;
+            if let Err(e) = ln_client.ping().await {
+                warn!("Failover LND backend {} is unreachable: {}", backend.id, e);
+            }
+            backends.push((backend.id.clone(), ln_client));
+        }
+        info!("LND failover configured with {} backends", backends.len());
+        Arc::new(FailoverLn::new(backends)?)
+    };
+
     // Create Oracle client (real or mock based on config)
     #[cfg(any(feature = "e2e-testing", debug_assertions))]
     let oracle_client: Arc<dyn Oracle> = if config.coordinator_settings.mock_oracle {
@@ -236,7 +341,7 @@ pub async fn build_app(
         let real_oracle = OracleClient::new(
             build_oracle_reqwest_client(http_client.clone()),
             &oracle_url,
-            &config.coordinator_settings.private_key_file,
+            nostr_secrets.as_ref(),
         )?;
         info!("Oracle client configured");
         Arc::new(real_oracle)
@@ -254,14 +359,16 @@ pub async fn build_app(
         let real_oracle = OracleClient::new(
             build_oracle_reqwest_client(http_client.clone()),
             &oracle_url,
-            &config.coordinator_settings.private_key_file,
+            nostr_secrets.as_ref(),
         )?;
         info!("Oracle client configured");
         Arc::new(real_oracle)
     };
     create_folder(&config.db_settings.data_folder.clone());
 
-    let pool_config: DatabasePoolConfig = config.db_settings.clone().into();
+    let mut pool_config: DatabasePoolConfig = config.db_settings.clone().into();
+    pool_config.encryption_key = load_db_encryption_key(&config.db_settings)
+        .map_err(|e| anyhow!("Error loading database encryption key: {}", e))?;
 
     let competition_db = DBConnection::new(
         &config.db_settings.data_folder,
@@ -273,6 +380,7 @@ pub async fn build_app(
     .map_err(|e| anyhow!("Error setting up competition db: {}", e))?;
 
     let competition_db_clone = competition_db.clone();
+    let webhook_store = WebhookStore::new(competition_db.clone());
     let competition_store = CompetitionStore::new(competition_db);
 
     let users_db = DBConnection::new(
@@ -317,21 +425,27 @@ pub async fn build_app(
         None
     };
 
+    let shared_config = SharedConfig::new(config.clone());
+    let (webhook_worker, webhooks) =
+        WebhookWorker::new(webhook_store.clone(), &config.webhook_settings)?;
+
     let coordinator = Coordinator::new(
         oracle_client,
         competition_store,
         bitcoin_client.clone(),
         ln.clone(),
+        Arc::new(RelayPublisher::new()),
         keymeld_service,
         keymeld_gateway_url,
         config
             .coordinator_settings
             .relative_locktime_block_delta
             .into(),
-        config.coordinator_settings.required_confirmations,
         config.coordinator_settings.name,
         config.coordinator_settings.escrow_enabled,
-        config.coordinator_settings.invoice_settlement_confirmations,
+        config.coordinator_settings.invoice_settlement_mode,
+        shared_config.clone(),
+        webhooks.clone(),
     )
     .await
     .map(Arc::new)?;
@@ -344,121 +458,157 @@ pub async fn build_app(
 
     info!("Coordinator service configured");
 
-    let tracker = TaskTracker::new();
-    let mut threads = HashMap::new();
-    let cancel_token = CancellationToken::new();
-    let competition_watcher = CompetitionWatcher::new(
-        coordinator.clone(),
-        cancel_token.clone(),
-        Duration::from_secs(config.coordinator_settings.sync_interval_secs),
-    );
-    let competition_watcher_task = tracker.spawn(async move {
-        match competition_watcher.watch().await {
-            Ok(_) => {
-                info!("Successfully shutdown competition watcher")
-            }
-            Err(e) => {
-                error!("Error in competition watcher: {}", e)
-            }
-        }
+    // Tasks are stopped in the reverse of this order at shutdown: the subscribers and polling
+    // watchers first, then the competition watcher, so webhooks and the config reloader stay up
+    // while in-flight competitions drain
+    let supervisor =
+        TaskSupervisor::new(RestartPolicy::from_settings(&config.coordinator_settings));
+
+    let config_reloader = shared_config.clone();
+    supervisor.spawn("config_reloader", move |cancel_token| {
+        reload_config_on_sighup(config_reloader.clone(), cancel_token)
     });
 
-    let bitcoin_watcher = BitcoinSyncWatcher::new(
-        bitcoin_client.clone(),
-        cancel_token.clone(),
-        Duration::from_secs(config.bitcoin_settings.refresh_blocks_secs),
-    );
+    let webhook_worker = Arc::new(tokio::sync::Mutex::new(webhook_worker));
+    supervisor.spawn("webhook_worker", move |cancel_token| {
+        let webhook_worker = webhook_worker.clone();
+        async move { webhook_worker.lock().await.watch(cancel_token).await }
+    });
 
-    let bitcoin_watcher_task = tracker.spawn(async move {
-        match bitcoin_watcher.watch().await {
-            Ok(_) => {
-                info!("Successfully shutdown Bitcoin sync watcher")
-            }
-            Err(e) => {
-                error!("Error in Bitcoin sync watcher: {}", e)
-            }
-        }
+    let sync_bitcoin = bitcoin_client.clone();
+    let sync_config = shared_config.clone();
+    supervisor.spawn("bitcoin_sync_watcher", move |cancel_token| {
+        let watcher =
+            BitcoinSyncWatcher::new(sync_bitcoin.clone(), cancel_token, sync_config.clone());
+        async move { watcher.watch().await }
     });
 
-    tracker.close();
-    threads.insert(
-        String::from("competition_watcher"),
-        competition_watcher_task,
-    );
-    threads.insert(String::from("bitcoin_sync_watcher"), bitcoin_watcher_task);
+    let watcher_coordinator = coordinator.clone();
+    let watcher_config = shared_config.clone();
+    supervisor.spawn("competition_watcher", move |cancel_token| {
+        let watcher = CompetitionWatcher::new(
+            watcher_coordinator.clone(),
+            cancel_token,
+            watcher_config.clone(),
+        );
+        async move { watcher.watch().await }
+    });
 
-    let invoice_watcher = InvoiceWatcher::new(
-        coordinator.clone(),
-        ln.clone(),
-        cancel_token.clone(),
-        Duration::from_secs(config.ln_settings.invoice_watch_interval),
-    );
+    let poller_coordinator = coordinator.clone();
+    let poller_config = shared_config.clone();
+    supervisor.spawn("attestation_poller", move |cancel_token| {
+        let poller = AttestationPoller::new(
+            poller_coordinator.clone(),
+            cancel_token,
+            poller_config.clone(),
+        );
+        async move { poller.watch().await }
+    });
 
-    let invoice_watcher_handle = tokio::spawn(async move {
-        if let Err(e) = invoice_watcher.watch().await {
-            error!("Invoice watcher error: {}", e);
-        }
+    let payout_coordinator = coordinator.clone();
+    let payout_ln = ln.clone();
+    let payout_config = shared_config.clone();
+    supervisor.spawn("payout_watcher", move |cancel_token| {
+        let watcher = PayoutWatcher::new(
+            payout_coordinator.clone(),
+            payout_ln.clone(),
+            cancel_token,
+            payout_config.clone(),
+        );
+        async move { watcher.watch().await }
     });
 
-    threads.insert("invoice_watcher".to_string(), invoice_watcher_handle);
+    let invoice_coordinator = coordinator.clone();
+    let invoice_ln = ln.clone();
+    let invoice_config = shared_config.clone();
+    supervisor.spawn("invoice_watcher", move |cancel_token| {
+        let watcher = InvoiceWatcher::new(
+            invoice_coordinator.clone(),
+            invoice_ln.clone(),
+            cancel_token,
+            invoice_config.clone(),
+        );
+        async move { watcher.watch().await }
+    });
 
-    let payout_watcher = PayoutWatcher::new(
-        coordinator.clone(),
-        ln.clone(),
-        cancel_token.clone(),
-        Duration::from_secs(config.ln_settings.payout_watch_interval),
+    let ip_hash_key = ip_hash_key(
+        nostr_secrets
+            .load()
+            .map_err(|e| anyhow!("Error loading the sign-in log's address key: {}", e))?
+            .expose_secret(),
     );
-
-    let payout_watcher_handle = tokio::spawn(async move {
-        if let Err(e) = payout_watcher.watch().await {
-            error!("Payout watcher error: {}", e);
-        }
+    let users_info = Arc::new(UserInfo::new(
+        users_store,
+        config.api_settings.auth_event_ip_privacy,
+        ip_hash_key,
+        time::Duration::days(config.api_settings.auth_event_retention_days as i64),
+    ));
+    let pruner_users_info = users_info.clone();
+    supervisor.spawn("auth_event_pruner", move |cancel_token| {
+        let pruner = AuthEventPruner::new(pruner_users_info.clone(), cancel_token);
+        async move { pruner.watch().await }
     });
 
-    threads.insert("payout_watcher".to_string(), payout_watcher_handle);
+    let sweeper_coordinator = coordinator.clone();
+    let sweeper_ln = ln.clone();
+    let sweeper_config = shared_config.clone();
+    supervisor.spawn("reservation_sweeper", move |cancel_token| {
+        let sweeper = ReservationSweeper::new(
+            sweeper_coordinator.clone(),
+            sweeper_ln.clone(),
+            cancel_token,
+            sweeper_config.clone(),
+        );
+        async move { sweeper.watch().await }
+    });
 
     // Subscription-based watchers for faster payment detection
     // These run alongside the polling watchers as the primary mechanism,
     // with polling serving as a fallback
-    let invoice_subscriber =
-        InvoiceSubscriber::new(coordinator.clone(), ln.clone(), cancel_token.clone());
-
-    let invoice_subscriber_handle = tokio::spawn(async move {
-        if let Err(e) = invoice_subscriber.subscribe().await {
-            error!("Invoice subscriber error: {}", e);
-        }
+    let invoice_subscriber_coordinator = coordinator.clone();
+    let invoice_subscriber_ln = ln.clone();
+    supervisor.spawn("invoice_subscriber", move |cancel_token| {
+        let subscriber = InvoiceSubscriber::new(
+            invoice_subscriber_coordinator.clone(),
+            invoice_subscriber_ln.clone(),
+            cancel_token,
+        );
+        async move { subscriber.subscribe().await }
     });
 
-    threads.insert("invoice_subscriber".to_string(), invoice_subscriber_handle);
-
-    let payment_subscriber =
-        PaymentSubscriber::new(coordinator.clone(), ln.clone(), cancel_token.clone());
-
-    let payment_subscriber_handle = tokio::spawn(async move {
-        if let Err(e) = payment_subscriber.subscribe().await {
-            error!("Payment subscriber error: {}", e);
-        }
+    let payment_subscriber_coordinator = coordinator.clone();
+    let payment_subscriber_ln = ln.clone();
+    supervisor.spawn("payment_subscriber", move |cancel_token| {
+        let subscriber = PaymentSubscriber::new(
+            payment_subscriber_coordinator.clone(),
+            payment_subscriber_ln.clone(),
+            cancel_token,
+        );
+        async move { subscriber.subscribe().await }
     });
 
-    threads.insert("payment_subscriber".to_string(), payment_subscriber_handle);
-
     let app_state = AppState {
         ui_dir: config.ui_settings.ui_dir,
         private_url: config.ui_settings.private_url,
         remote_url: config.ui_settings.remote_url,
         esplora_url: config.bitcoin_settings.esplora_url,
         oracle_url: config.coordinator_settings.oracle_url,
-        network: config.bitcoin_settings.network.to_string(),
+        network: config.bitcoin_settings.network,
         coordinator,
-        users_info: Arc::new(UserInfo::new(users_store)),
+        users_info,
         bitcoin: bitcoin_client,
-        background_threads: Arc::new(threads),
+        tasks: supervisor.clone(),
+        admin_sessions: AdminSessions::new(Duration::from_secs(
+            config.api_settings.admin_session_ttl_secs,
+        )),
         forgot_password_challenges: Arc::new(RwLock::new(HashMap::new())),
+        settings: shared_config,
+        webhooks,
+        webhook_store,
     };
     Ok((
         app_state,
-        tracker,
-        cancel_token,
+        supervisor,
         vec![competition_db_clone, users_db_clone],
     ))
 }
@@ -492,23 +642,39 @@ pub async fn build_server(
 }
 
 pub fn app(app_state: AppState, origins: Vec<String>) -> Router {
+    let app_state = Arc::new(app_state);
     let origins: Vec<HeaderValue> = origins
         .into_iter()
         .filter_map(|origin| origin.parse().ok())
         .collect();
 
+    // SigMaps grow with the number of outcomes, clients may gzip them and must chunk anything
+    // past the limit, which applies to the decompressed body
+    let max_signature_body_bytes = app_state.settings.max_signature_body_bytes();
+    let max_entry_body_bytes = app_state.settings.max_entry_body_bytes();
+    let body_limits = BodyLimits {
+        signatures: max_signature_body_bytes,
+        entries: max_entry_body_bytes,
+        default: app_state.settings.max_body_bytes(),
+    };
+
     let cors = CorsLayer::new()
         .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([ACCEPT, CONTENT_TYPE, AUTHORIZATION])
+        .allow_headers([ACCEPT, CONTENT_TYPE, CONTENT_ENCODING, AUTHORIZATION])
         .allow_origin(AllowOrigin::list(origins))
         .allow_credentials(true);
 
+    // The coordinator's own wallet, spending from it is for operators only
     let wallet_endpoints = Router::new()
         .route("/balance", get(get_balance))
         .route("/address", get(get_next_address))
         .route("/outputs", get(get_outputs))
         .route("/send", post(send_to_address))
-        .route("/estimated_fees", get(get_estimated_fee_rates));
+        .route("/estimated_fees", get(get_estimated_fee_rates))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            require_admin,
+        ));
 
     let users_endpoints = Router::new()
         .route("/login", post(login))
@@ -517,12 +683,23 @@ pub fn app(app_state: AppState, origins: Vec<String>) -> Router {
         .route("/username/login", post(login_username))
         .route("/username/change-password", post(change_password))
         .route("/username/forgot-password", post(forgot_password_challenge))
-        .route("/username/reset-password", post(forgot_password_reset));
+        .route("/username/reset-password", post(forgot_password_reset))
+        .route("/me/sessions", get(get_sessions))
+        .route("/me/sessions/{session_id}/revoke", post(revoke_session));
 
     // HTMX admin routes (pure server-side rendering, no WASM)
     let admin_htmx_routes = Router::new()
         .route("/", get(admin_page_handler))
         .route("/competition", get(admin_competition_fragment))
+        .route("/alerts", get(admin_alerts_fragment))
+        .route("/webhooks", get(admin_webhooks_fragment))
+        .route("/pnl", get(admin_pnl_fragment))
+        .route("/solvency", get(admin_solvency_handler))
+        .route("/config/reload", post(reload_config))
+        .route(
+            "/webhooks/{delivery_id}/replay",
+            post(admin_replay_webhook_handler),
+        )
         .route("/wallet", get(admin_wallet_fragment))
         .route("/wallet/balance", get(admin_wallet_balance_fragment))
         .route("/wallet/address", get(admin_wallet_address_fragment))
@@ -530,14 +707,78 @@ pub fn app(app_state: AppState, origins: Vec<String>) -> Router {
         .route("/wallet/outputs", get(admin_wallet_outputs_fragment))
         .route("/wallet/send", post(admin_send_bitcoin_handler))
         .route("/api/competitions", post(admin_create_competition_handler))
+        .route("/api/consistency", get(admin_consistency_report_handler))
+        .route("/api/integrity", get(admin_blob_integrity_handler))
+        .route("/api/pnl", get(admin_pnl_report_handler))
+        .route("/api/auth-activity", get(admin_auth_activity_handler))
         .route(
             "/api/competitions/delete",
             post(admin_delete_competition_handler),
         )
+        .route("/api/competitions/invitations", post(admin_invite_handler))
+        .route(
+            "/api/competitions/{competition_id}/expiry/rebroadcast",
+            post(admin_rebroadcast_expiry_handler),
+        )
+        .route(
+            "/api/competitions/{competition_id}/changes",
+            get(admin_competition_changes_handler),
+        )
+        .route(
+            "/api/competitions/{competition_id}/player-order",
+            get(admin_player_order_handler),
+        )
+        .route(
+            "/api/competitions/{competition_id}/cancel",
+            post(admin_cancel_competition_handler),
+        )
+        .route(
+            "/api/competitions/estimate",
+            get(admin_contract_estimate_handler).post(admin_contract_estimate_fragment),
+        )
+        .route(
+            "/api/competitions/{competition_id}/clone",
+            post(admin_clone_competition_handler),
+        )
+        .route(
+            "/api/competitions/{competition_id}/retry",
+            post(admin_retry_competition_handler),
+        )
+        .route(
+            "/api/competitions/{competition_id}/force-complete",
+            post(admin_force_complete_competition_handler),
+        )
+        .route(
+            "/api/competitions/{competition_id}/sponsor",
+            get(admin_list_sponsorships_handler).post(admin_sponsor_competition_handler),
+        )
+        .route(
+            "/api/competitions/{competition_id}/entries/{entry_id}/keymeld/reset",
+            post(admin_reset_keymeld_registration_handler),
+        )
+        .route("/api/payouts", get(admin_list_payouts_handler))
+        .route(
+            "/api/tickets/{ticket_id}/release",
+            post(admin_release_ticket_handler),
+        )
+        .route(
+            "/api/tickets/{ticket_id}/comp",
+            post(admin_comp_ticket_handler),
+        )
         .route(
             "/api/test/settle-invoice/{ticket_id}",
             post(admin_settle_test_invoice_handler),
-        );
+        )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            require_admin,
+        ))
+        // Added after the admin layer so operators without a session can reach them
+        .route(
+            "/login",
+            get(admin_login_page_handler).post(admin_login_handler),
+        )
+        .route("/logout", post(admin_logout_handler));
 
     // HTMX public routes (some require JS bridge for auth)
     let htmx_routes = Router::new()
@@ -564,13 +805,40 @@ pub fn app(app_state: AppState, origins: Vec<String>) -> Router {
         .nest("/admin", admin_htmx_routes)
         .merge(htmx_routes)
         .fallback(public_page_handler)
+        .route("/feed/competitions.json", get(open_competitions_json_feed))
+        .route("/feed/competitions.atom", get(open_competitions_atom_feed))
         .route("/api/v1/health_check", get(health))
-        .route("/api/v1/competitions", post(create_competition))
+        .route("/api/v1/health/ready", get(ready))
+        .route("/api/v1/metrics", get(task_metrics))
+        .route("/api/v1/info", get(get_coordinator_info))
+        .route(
+            "/api/v1/competitions",
+            post(create_competition).route_layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                require_admin,
+            )),
+        )
         .route("/api/v1/competitions", get(get_competitions))
         .route(
             "/api/v1/competitions/{competition_id}",
             get(get_competition),
         )
+        .route(
+            "/api/v1/competitions/by-slug/{slug}",
+            get(get_competition_by_slug),
+        )
+        .route(
+            "/api/v1/competitions/{competition_id}/leaderboard",
+            get(get_competition_leaderboard),
+        )
+        .route(
+            "/api/v1/competitions/{competition_id}/oracle",
+            get(get_competition_oracle_event),
+        )
+        .route(
+            "/api/v1/competitions/{competition_id}/entry-preview",
+            get(get_entry_preview),
+        )
         .route(
             "/api/v1/competitions/{competition_id}/ticket",
             post(request_competition_ticket),
@@ -579,33 +847,83 @@ pub fn app(app_state: AppState, origins: Vec<String>) -> Router {
             "/api/v1/competitions/{competition_id}/tickets/{ticket_id}/status",
             get(get_ticket_status),
         )
+        .route(
+            "/api/v1/competitions/{competition_id}/tickets/{ticket_id}/invoice",
+            get(regenerate_ticket_invoice).post(regenerate_ticket_invoice),
+        )
+        .route(
+            "/api/v1/competitions/{competition_id}/tickets/{ticket_id}/receipt",
+            get(get_ticket_receipt),
+        )
         .route(
             "/api/v1/competitions/{id}/contract",
             get(get_contract_parameters),
         )
+        .route(
+            "/api/v1/competitions/{id}/contract/disclosure",
+            get(get_contract_disclosure),
+        )
+        .route(
+            "/api/v1/competitions/{id}/timeline",
+            get(get_competition_timeline),
+        )
         .route(
             "/api/v1/competitions/{competition_id}/entries/{entry_id}/public_nonces",
-            post(submit_public_nonces),
+            post(submit_public_nonces).layer(DefaultBodyLimit::max(max_signature_body_bytes)),
         )
         .route(
             "/api/v1/competitions/{id}/aggregate_nonces",
-            get(get_aggregate_nonces),
+            get(get_aggregate_nonces).layer(CompressionLayer::new()),
         )
         .route(
             "/api/v1/competitions/{competition_id}/entries/{entry_id}/final_signatures",
-            post(submit_final_signatures),
+            post(submit_final_signatures)
+                .layer(DefaultBodyLimit::max(max_signature_body_bytes))
+                .layer(RequestDecompressionLayer::new()),
+        )
+        .route(
+            "/api/v1/competitions/{competition_id}/entries/{entry_id}/partial_signatures",
+            post(submit_partial_signature_chunk)
+                .layer(DefaultBodyLimit::max(max_signature_body_bytes))
+                .layer(RequestDecompressionLayer::new()),
+        )
+        .route(
+            "/api/v1/competitions/{competition_id}/entries/{entry_id}/funding_psbt/verify",
+            post(verify_funding_psbt),
         )
         .route(
             "/api/v1/competitions/{competitionId}/entries/{entryId}/payout",
             post(submit_ticket_payout),
         )
-        .route("/api/v1/entries", post(add_event_entry))
+        .route(
+            "/api/v1/competitions/{competition_id}/entries/{entry_id}/escrow_reclaim",
+            post(register_escrow_reclaim),
+        )
+        .route(
+            "/api/v1/competitions/{competition_id}/entries/{entry_id}/keymeld_registration",
+            post(reregister_keymeld_participant),
+        )
+        .route(
+            "/api/v1/entries",
+            post(add_event_entry).layer(DefaultBodyLimit::max(max_entry_body_bytes)),
+        )
         .route("/api/v1/entries", get(get_entries))
+        .route("/api/v1/entries/{entry_id}/receipt", get(get_entry_receipt))
+        .route("/api/v1/payouts", get(get_eligible_payouts))
         .nest("/api/v1/wallet", wallet_endpoints)
         .nest("/api/v1/users", users_endpoints)
         .route("/ui/{*path}", get(serve_static_file))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            record_authentication,
+        ))
+        .layer(DefaultBodyLimit::max(body_limits.default))
+        .layer(middleware::from_fn_with_state(
+            body_limits,
+            reject_unexpected_bodies,
+        ))
         .layer(middleware::from_fn(log_request))
-        .with_state(Arc::new(app_state))
+        .with_state(app_state)
         .layer(cors)
 }
 
@@ -625,6 +943,142 @@ async fn log_request(request: Request<Body>, next: Next) -> impl IntoResponse {
     response
 }
 
+/// Writes every request carrying a valid NIP-98 header to the sign-in log and turns away
+/// replays of events their owner revoked. Headers that don't verify pass through untouched so
+/// the handlers reject them as before.
+async fn record_authentication(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if !request.headers().contains_key(AUTHORIZATION) {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let Ok(NostrAuth { pubkey, event, .. }) =
+        NostrAuth::from_request_parts(&mut parts, &state).await
+    else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+
+    let attempt = AuthAttempt {
+        nostr_pubkey: pubkey.to_bech32().expect("public bech32 format"),
+        event_id: event.id.to_hex(),
+        ip: client_ip(
+            &parts.headers,
+            &parts.extensions,
+            &state.settings.trusted_proxies(),
+        ),
+        user_agent: parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|agent| agent.to_str().ok())
+            .map(|agent| agent.chars().take(MAX_USER_AGENT_CHARS).collect()),
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        expires_at: time::OffsetDateTime::from_unix_timestamp(event.created_at.as_u64() as i64)
+            .unwrap_or_else(|_| time::OffsetDateTime::now_utc())
+            + time::Duration::seconds(NIP98_MAX_AGE_SECS),
+    };
+    match state.users_info.record_authentication(attempt).await {
+        Ok(true) => {}
+        Ok(false) => return AuthError::Revoked(event.id.to_hex()).into_response(),
+        // The log is for the user's benefit, an outage of it shouldn't lock them out
+        Err(e) => error!("Failed to record sign-in of {}: {}", pubkey, e),
+    }
+
+    next.run(Request::from_parts(parts, body)).await
+}
+
+const MAX_USER_AGENT_CHARS: usize = 256;
+
+/// The peer address, unless it's one of `api_settings.trusted_proxies`. Then the
+/// `X-Forwarded-For` hops are walked back from the peer and the first one that isn't a trusted
+/// proxy is the client, hops in front of it are whatever the client chose to send.
+fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let peer = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+
+    let mut client = peer;
+    for hop in headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|forwarded| forwarded.to_str().ok())
+        .flat_map(|forwarded| forwarded.split(','))
+        .rev()
+    {
+        let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = hop;
+        if !trusted_proxies.contains(&hop) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// Only lets through requests with a dashboard session cookie from `/admin/login` or a NIP-98
+/// auth header, either belonging to one of `api_settings.admin_pubkeys`. The allow-list is read
+/// on every request so reloads apply immediately, removing a key also ends its sessions. An
+/// empty allow-list keeps the routes open. Browsers without either get the sign-in page.
+async fn require_admin(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if state.settings.admin_pubkeys().is_empty() {
+        return next.run(request).await;
+    }
+
+    if let Some(pubkey) =
+        session_token(request.headers()).and_then(|token| state.admin_sessions.verify(token))
+    {
+        if state.settings.is_admin(&pubkey) {
+            return next.run(request).await;
+        }
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let NostrAuth { pubkey, .. } = match NostrAuth::from_request_parts(&mut parts, &state).await {
+        Ok(auth) => auth,
+        Err(AuthError::NoAuthHeader) if parts.headers.contains_key("HX-Request") => {
+            // Fragment requests would swap the page into a tab, send the whole window instead
+            return (StatusCode::UNAUTHORIZED, [("HX-Redirect", "/admin/login")]).into_response();
+        }
+        Err(AuthError::NoAuthHeader) if accepts_html(&parts.headers) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Html(admin_login_page().into_string()),
+            )
+                .into_response();
+        }
+        Err(e) => return e.into_response(),
+    };
+    // Unparseable entries match nobody rather than opening the routes
+    if !state.settings.is_admin(&pubkey) {
+        return AuthError::NotAdmin(pubkey.to_hex()).into_response();
+    }
+
+    next.run(Request::from_parts(parts, body)).await
+}
+
+fn accepts_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"))
+}
+
 async fn serve_static_file(
     State(state): State<Arc<AppState>>,
     Path(path): Path<String>,
@@ -734,6 +1188,33 @@ impl Middleware for LoggingMiddleware {
     }
 }
 
+/// Re-read the settings file whenever the process receives SIGHUP
+async fn reload_config_on_sighup(
+    settings: SharedConfig,
+    cancel_token: CancellationToken,
+) -> Result<(), anyhow::Error> {
+    let mut sighup = signal(SignalKind::hangup())?;
+
+    loop {
+        select! {
+            _ = sighup.recv() => {
+                info!("Received SIGHUP signal, reloading settings");
+                match settings.reload() {
+                    Ok(changed) if changed.is_empty() => info!("Settings reloaded, nothing changed"),
+                    Ok(changed) => info!("Settings reloaded, changed: {}", changed.join(", ")),
+                    Err(e) => error!("Failed to reload settings, keeping current settings: {}", e),
+                }
+            }
+            _ = cancel_token.cancelled() => {
+                info!("Config reloader received cancellation");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 async fn shutdown_signal() {
     let mut sigint = signal(SignalKind::interrupt()).expect("Failed to install SIGINT handler");
     let mut sigterm = signal(SignalKind::terminate()).expect("Failed to install SIGTERM handler");
@@ -743,3 +1224,49 @@ async fn shutdown_signal() {
         _ = sigterm.recv() => info!("Received SIGTERM signal"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_from(peer: &str, forwarded_for: &[&str]) -> (HeaderMap, Extensions) {
+        let mut headers = HeaderMap::new();
+        for forwarded in forwarded_for {
+            headers.append("x-forwarded-for", HeaderValue::from_str(forwarded).unwrap());
+        }
+        let mut extensions = Extensions::new();
+        extensions.insert(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 443)));
+        (headers, extensions)
+    }
+
+    fn ip(ip: &str) -> Option<IpAddr> {
+        Some(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn test_client_ip_only_trusts_forwarded_for_from_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+
+        // Sent straight to the coordinator, the header is the client's to make up
+        let (headers, extensions) = request_from("203.0.113.7", &["198.51.100.1"]);
+        assert_eq!(client_ip(&headers, &extensions, &[]), ip("203.0.113.7"));
+        assert_eq!(
+            client_ip(&headers, &extensions, &[proxy]),
+            ip("203.0.113.7")
+        );
+
+        // The proxy appends the address it saw after whatever the client sent
+        let (headers, extensions) =
+            request_from("10.0.0.1", &["198.51.100.1, 203.0.113.7", "10.0.0.2"]);
+        assert_eq!(client_ip(&headers, &extensions, &[proxy]), ip("10.0.0.2"));
+        let proxies = [proxy, "10.0.0.2".parse().unwrap()];
+        assert_eq!(
+            client_ip(&headers, &extensions, &proxies),
+            ip("203.0.113.7")
+        );
+
+        // Without the header the proxy itself is all there is
+        let (headers, extensions) = request_from("10.0.0.1", &[]);
+        assert_eq!(client_ip(&headers, &extensions, &[proxy]), ip("10.0.0.1"));
+    }
+}
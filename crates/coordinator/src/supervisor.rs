@@ -0,0 +1,371 @@
+use log::{error, info, warn};
+use serde::Serialize;
+use std::{
+    any::Any,
+    future::Future,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use time::OffsetDateTime;
+use tokio::{select, task::JoinHandle};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use crate::config::CoordinatorSettings;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// Panicked or exited on its own and is waiting out its backoff before starting again
+    Restarting,
+    /// Failed more than `max_restarts` times in a row and is no longer restarted
+    Degraded,
+    /// Shut down through the supervisor
+    Stopped,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TaskHealth {
+    pub name: String,
+    pub state: TaskState,
+    /// Restarts since the coordinator started
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_restart_at: Option<OffsetDateTime>,
+}
+
+impl TaskHealth {
+    pub fn healthy(&self) -> bool {
+        matches!(self.state, TaskState::Running | TaskState::Restarting)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RestartPolicy {
+    /// Failures in a row a task is restarted for before it is marked degraded
+    pub max_restarts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// A run lasting at least this long resets the count of failures in a row
+    pub stable_after: Duration,
+}
+
+impl RestartPolicy {
+    pub fn from_settings(settings: &CoordinatorSettings) -> Self {
+        Self {
+            max_restarts: settings.task_max_restarts,
+            initial_backoff: Duration::from_secs(settings.task_restart_backoff_secs),
+            max_backoff: Duration::from_secs(settings.task_restart_max_backoff_secs),
+            stable_after: Duration::from_secs(300),
+        }
+    }
+
+    fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+struct SupervisedTask {
+    name: String,
+    cancel_token: CancellationToken,
+    handle: Mutex<Option<JoinHandle<()>>>,
+    health: RwLock<TaskHealth>,
+}
+
+impl SupervisedTask {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            cancel_token: CancellationToken::new(),
+            handle: Mutex::new(None),
+            health: RwLock::new(TaskHealth {
+                name: name.to_string(),
+                state: TaskState::Running,
+                restarts: 0,
+                last_error: None,
+                last_restart_at: None,
+            }),
+        }
+    }
+
+    fn health(&self) -> TaskHealth {
+        match self.health.read() {
+            Ok(health) => health.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    fn update_health(&self, update: impl FnOnce(&mut TaskHealth)) {
+        match self.health.write() {
+            Ok(mut health) => update(&mut health),
+            Err(poisoned) => update(&mut poisoned.into_inner()),
+        }
+    }
+
+    async fn supervise<F, Fut>(&self, factory: F, policy: RestartPolicy)
+    where
+        F: Fn(CancellationToken) -> Fut,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        let mut failures = 0;
+        loop {
+            let started = Instant::now();
+            // Run each attempt as its own tokio task so a panic surfaces as a JoinError here
+            // instead of taking the supervisor down with it
+            let outcome = tokio::spawn(factory(self.cancel_token.clone())).await;
+
+            if self.cancel_token.is_cancelled() {
+                match outcome {
+                    Ok(Ok(())) => info!("Background task {} stopped", self.name),
+                    Ok(Err(e)) => error!("Background task {} stopped with error: {}", self.name, e),
+                    Err(e) => error!("Background task {} failed while stopping: {}", self.name, e),
+                }
+                self.update_health(|health| health.state = TaskState::Stopped);
+                return;
+            }
+
+            let reason = match outcome {
+                Ok(Ok(())) => String::from("exited unexpectedly"),
+                Ok(Err(e)) => format!("failed: {}", e),
+                Err(e) if e.is_panic() => format!("panicked: {}", panic_message(e.into_panic())),
+                Err(e) => format!("was aborted: {}", e),
+            };
+
+            if started.elapsed() >= policy.stable_after {
+                failures = 0;
+            }
+            failures += 1;
+            if failures > policy.max_restarts {
+                error!(
+                    "Background task {} {}, giving up after {} restarts in a row and marking it degraded",
+                    self.name, reason, policy.max_restarts
+                );
+                self.update_health(|health| {
+                    health.state = TaskState::Degraded;
+                    health.last_error = Some(reason);
+                });
+                return;
+            }
+
+            let backoff = policy.backoff(failures);
+            warn!(
+                "Background task {} {}, restarting in {}ms",
+                self.name,
+                reason,
+                backoff.as_millis()
+            );
+            self.update_health(|health| {
+                health.state = TaskState::Restarting;
+                health.restarts += 1;
+                health.last_error = Some(reason);
+                health.last_restart_at = Some(OffsetDateTime::now_utc());
+            });
+
+            select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = self.cancel_token.cancelled() => {
+                    self.update_health(|health| health.state = TaskState::Stopped);
+                    return;
+                }
+            }
+            self.update_health(|health| health.state = TaskState::Running);
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+/// Runs the coordinator's long-lived background tasks. Each task is spawned under a name with
+/// its own cancellation token, restarted with exponential backoff when it panics or exits on
+/// its own, and marked degraded once it keeps failing. Shutdown stops the tasks one at a time
+/// in the reverse of the order they were spawned.
+#[derive(Clone)]
+pub struct TaskSupervisor {
+    policy: RestartPolicy,
+    tasks: Arc<RwLock<Vec<Arc<SupervisedTask>>>>,
+    tracker: TaskTracker,
+}
+
+impl TaskSupervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            tasks: Arc::new(RwLock::new(Vec::new())),
+            tracker: TaskTracker::new(),
+        }
+    }
+
+    /// Starts a supervised task, `factory` is called again with the task's cancellation token
+    /// every time it is restarted
+    pub fn spawn<F, Fut>(&self, name: &str, factory: F)
+    where
+        F: Fn(CancellationToken) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+    {
+        let task = Arc::new(SupervisedTask::new(name));
+        let supervised = task.clone();
+        let policy = self.policy.clone();
+        let handle = self
+            .tracker
+            .spawn(async move { supervised.supervise(factory, policy).await });
+        if let Ok(mut slot) = task.handle.lock() {
+            *slot = Some(handle);
+        }
+        if let Ok(mut tasks) = self.tasks.write() {
+            tasks.push(task);
+        }
+        info!("Started background task {}", name);
+    }
+
+    pub fn health(&self) -> Vec<TaskHealth> {
+        match self.tasks.read() {
+            Ok(tasks) => tasks.iter().map(|task| task.health()).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    pub fn all_healthy(&self) -> bool {
+        self.health().iter().all(TaskHealth::healthy)
+    }
+
+    /// Cancels each task and waits for it to finish before moving on to the one spawned before
+    /// it. Calling it again while a shutdown is running waits for that shutdown to complete.
+    pub async fn shutdown(&self) {
+        let tasks = match self.tasks.read() {
+            Ok(tasks) => tasks.clone(),
+            Err(_) => Vec::new(),
+        };
+        for task in tasks.iter().rev() {
+            let handle = task.handle.lock().ok().and_then(|mut slot| slot.take());
+            let Some(handle) = handle else {
+                continue;
+            };
+            info!("Stopping background task {}", task.name);
+            task.cancel_token.cancel();
+            if let Err(e) = handle.await {
+                error!("Supervisor for background task {} failed: {}", task.name, e);
+            }
+        }
+        self.tracker.close();
+        self.tracker.wait().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn test_policy() -> RestartPolicy {
+        RestartPolicy {
+            max_restarts: 2,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(40),
+            stable_after: Duration::from_secs(60),
+        }
+    }
+
+    async fn wait_for(supervisor: &TaskSupervisor, check: impl Fn(&TaskHealth) -> bool) {
+        for _ in 0..200 {
+            if supervisor.health().iter().any(&check) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!(
+            "timed out waiting on task health: {:?}",
+            supervisor.health()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_panicking_watcher_is_restarted_and_health_reflects_it() {
+        let supervisor = TaskSupervisor::new(test_policy());
+        let runs = Arc::new(AtomicU32::new(0));
+        let watcher_runs = runs.clone();
+        supervisor.spawn("mock_watcher", move |cancel_token| {
+            let runs = watcher_runs.clone();
+            async move {
+                if runs.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("mock watcher blew up");
+                }
+                cancel_token.cancelled().await;
+                Ok(())
+            }
+        });
+
+        wait_for(&supervisor, |health| {
+            health.restarts == 1
+                && health.state == TaskState::Running
+                && runs.load(Ordering::SeqCst) == 2
+        })
+        .await;
+
+        let health = supervisor.health().remove(0);
+        assert_eq!(health.name, "mock_watcher");
+        assert!(health.last_error.unwrap().contains("mock watcher blew up"));
+        assert!(health.last_restart_at.is_some());
+        assert!(supervisor.all_healthy());
+
+        supervisor.shutdown().await;
+        assert_eq!(supervisor.health()[0].state, TaskState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_failing_past_max_restarts_is_degraded() {
+        let supervisor = TaskSupervisor::new(test_policy());
+        let runs = Arc::new(AtomicU32::new(0));
+        let watcher_runs = runs.clone();
+        supervisor.spawn("failing_watcher", move |_| {
+            let runs = watcher_runs.clone();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Err(anyhow::anyhow!("backend unreachable"))
+            }
+        });
+
+        wait_for(&supervisor, |health| health.state == TaskState::Degraded).await;
+        // The first run plus one per allowed restart
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(supervisor.health()[0].restarts, 2);
+        assert!(!supervisor.all_healthy());
+
+        supervisor.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_tasks_in_reverse_spawn_order() {
+        let supervisor = TaskSupervisor::new(test_policy());
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+        for name in ["first", "second", "third"] {
+            let stopped = stopped.clone();
+            supervisor.spawn(name, move |cancel_token| {
+                let stopped = stopped.clone();
+                async move {
+                    cancel_token.cancelled().await;
+                    stopped.lock().unwrap().push(name);
+                    Ok(())
+                }
+            });
+        }
+
+        supervisor.shutdown().await;
+        assert_eq!(*stopped.lock().unwrap(), vec!["third", "second", "first"]);
+        assert!(supervisor
+            .health()
+            .iter()
+            .all(|health| health.state == TaskState::Stopped));
+    }
+}
@@ -0,0 +1,64 @@
+use maud::{html, Markup};
+
+use crate::domain::StuckCompetitionReport;
+
+/// "Needs attention" panel listing competitions that look stuck
+pub fn stuck_competitions_panel(report: &StuckCompetitionReport) -> Markup {
+    html! {
+        h2 class="subtitle has-text-weight-bold" { "Needs Attention" }
+        @if report.is_empty() {
+            p class="has-text-grey" { "All active competitions are progressing normally." }
+        } @else {
+            div class="table-container" {
+                table class="table is-fullwidth is-striped is-narrow is-card-mobile" {
+                    thead {
+                        tr {
+                            th { "Competition" }
+                            th { "State" }
+                            th { "Reasons" }
+                            th { "Last Error" }
+                        }
+                    }
+                    tbody {
+                        @for stuck in &report.competitions {
+                            tr {
+                                td data-label="Competition" {
+                                    a href=(format!("/competitions/{}/leaderboard", stuck.competition_id)) {
+                                        (stuck.competition_id)
+                                    }
+                                }
+                                td data-label="State" {
+                                    span class="tag is-warning" { (stuck.state) }
+                                }
+                                td data-label="Reasons" {
+                                    ul {
+                                        @for reason in &stuck.reasons {
+                                            li { (reason.description()) }
+                                        }
+                                    }
+                                }
+                                td data-label="Last Error" {
+                                    @if let Some(error) = &stuck.last_error {
+                                        span class="has-text-danger is-size-7" { (error) }
+                                    } @else {
+                                        span class="has-text-grey" { "-" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Error state for the panel when the report couldn't be built
+pub fn stuck_competitions_error(message: &str) -> Markup {
+    html! {
+        h2 class="subtitle has-text-weight-bold" { "Needs Attention" }
+        div class="notification is-danger is-light" {
+            "Failed to load stuck competitions: " (message)
+        }
+    }
+}
@@ -4,6 +4,7 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 use super::location_selector::location_selector;
+use crate::domain::{ContractLimits, ContractSizeEstimate, MAX_STANDARD_TX_WEIGHT, MIB};
 
 /// Station data from the oracle
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,6 +102,16 @@ pub fn admin_dashboard(stations: &[StationWithWeather], defaults: &CompetitionDe
                 h1 class="title" { "Fantasy Weather Admin" }
             }
 
+            div class="container" {
+                div class="box"
+                    id="stuck-competitions"
+                    hx-get="/admin/alerts"
+                    hx-trigger="load, every 60s"
+                    hx-swap="innerHTML" {
+                    p class="has-text-grey" { "Checking competitions..." }
+                }
+            }
+
             div class="container" {
                 h6 class="subtitle" { "Create Competition" }
 
@@ -121,6 +132,7 @@ pub fn admin_dashboard(stations: &[StationWithWeather], defaults: &CompetitionDe
                           value=(defaults.end_observation_date.format(&time::format_description::well_known::Rfc3339).unwrap_or_default());
                     input type="hidden" name="signing_date" id="signing_date_hidden"
                           value=(defaults.signing_date.format(&time::format_description::well_known::Rfc3339).unwrap_or_default());
+                    input type="hidden" name="signing_deadline" id="signing_deadline_hidden" value="";
 
                     // Timing parameters
                     div class="box" {
@@ -159,6 +171,20 @@ pub fn admin_dashboard(stations: &[StationWithWeather], defaults: &CompetitionDe
                                     }
                                 }
                             }
+                            div class="column" {
+                                div class="field" {
+                                    label class="label" { "Signing Deadline" }
+                                    div class="control" {
+                                        input class="input" type="datetime-local"
+                                              id="signing_deadline_input"
+                                              data-optional="true"
+                                              onchange="syncDateField('signing_deadline')";
+                                    }
+                                    p class="help" {
+                                        "Optional, defaults to 1 minute before observation start"
+                                    }
+                                }
+                            }
                         }
                     }
 
@@ -232,9 +258,106 @@ pub fn admin_dashboard(stations: &[StationWithWeather], defaults: &CompetitionDe
                                     }
                                 }
                             }
+
+                            div class="column" {
+                                div class="field" {
+                                    label class="label" { "Settlement Confs" }
+                                    div class="control" {
+                                        input class="input" type="number"
+                                              name="invoice_settlement_confirmations"
+                                              value="" min="0";
+                                    }
+                                    p class="help" {
+                                        "Funding confirmations before settling invoices"
+                                    }
+                                }
+                            }
+                        }
+                        div class="columns" {
+                            div class="column" {
+                                div class="field" {
+                                    label class="label" { "Name" }
+                                    div class="control" {
+                                        input class="input" type="text" name="name"
+                                              maxlength="100" placeholder="Chicago Heat Wave";
+                                    }
+                                    p class="help" { "Optional, unique" }
+                                }
+                            }
+                            div class="column" {
+                                div class="field" {
+                                    label class="label" { "Slug" }
+                                    div class="control" {
+                                        input class="input" type="text" name="slug"
+                                              maxlength="64" pattern="[a-z0-9-]+"
+                                              placeholder="chicago-heat-wave";
+                                    }
+                                    p class="help" { "Optional, derived from the name when empty" }
+                                }
+                            }
+                            div class="column" {
+                                div class="field" {
+                                    label class="label" { "Tags" }
+                                    div class="control" {
+                                        input class="input" type="text" name="tags"
+                                              placeholder="northeast, temperature, daily";
+                                    }
+                                    p class="help" { "Optional, comma separated" }
+                                }
+                            }
+                            div class="column" {
+                                div class="field" {
+                                    label class="label" { "Ties" }
+                                    div class="control" {
+                                        div class="select" {
+                                            select name="tie_policy" {
+                                                option value="tiebreak" selected { "Tiebreak" }
+                                                option value="split" { "Split" }
+                                            }
+                                        }
+                                    }
+                                    p class="help" {
+                                        "Tiebreak orders tied entries by entry time, split shares the places"
+                                    }
+                                }
+                            }
+                        }
+                        div class="field" {
+                            label class="label" { "Backup Relays" }
+                            div class="control" {
+                                input class="input" type="text" name="backup_relays"
+                                      placeholder="wss://relay.example.com, wss://relay2.example.com";
+                            }
+                            p class="help" {
+                                "Optional, relays for entry backup DMs, defaults to the coordinator's relays"
+                            }
+                        }
+                        div class="field" {
+                            div class="control" {
+                                label class="checkbox" {
+                                    input type="checkbox" name="private" value="true";
+                                    " Private (invited pubkeys only)"
+                                }
+                            }
+                        }
+                        div class="field" {
+                            label class="label" { "Invited Pubkeys" }
+                            div class="control" {
+                                textarea class="textarea" name="invited_pubkeys" rows="2"
+                                         placeholder="npub1... or hex, comma or newline separated" {}
+                            }
+                            p class="help" {
+                                "Only used for private competitions, more can be invited until entries close"
+                            }
                         }
                     }
 
+                    // Contract size for the entries, places and tie policy, refreshed as they change
+                    div id="contract-estimate" class="mb-4"
+                        hx-post="/admin/api/competitions/estimate"
+                        hx-trigger="load, change from:#competition-form"
+                        hx-swap="innerHTML" {}
+
                     // Location selector with map, table, and Create Competition button
                     (location_selector(stations))
                 }
@@ -269,6 +392,59 @@ pub fn admin_dashboard(stations: &[StationWithWeather], defaults: &CompetitionDe
                 }
             }
 
+            // Invite pubkeys to a private competition
+            div class="container mt-5" {
+                h6 class="subtitle" { "Invite to Private Competition" }
+
+                div class="box" {
+                    form id="invite-competition-form"
+                         hx-post="/admin/api/competitions/invitations"
+                         hx-target="#invite-notification"
+                         hx-swap="innerHTML" {
+
+                        div class="field" {
+                            div class="control" {
+                                input class="input" type="text" name="competition_id"
+                                      placeholder="Competition ID";
+                            }
+                        }
+                        div class="field" {
+                            div class="control" {
+                                textarea class="textarea" name="pubkeys" rows="2"
+                                         placeholder="npub1... or hex, comma or newline separated" {}
+                            }
+                        }
+                        div class="control" {
+                            button class="button is-link" type="submit" { "Invite" }
+                        }
+                    }
+                    div id="invite-notification" class="mt-3" {}
+                }
+            }
+
+            // Revenue and costs per competition from the accounting ledger
+            div class="container mt-5" {
+                div id="clone-notification" {}
+                div class="box"
+                    id="pnl-report"
+                    hx-get="/admin/pnl"
+                    hx-trigger="load, every 60s"
+                    hx-swap="innerHTML" {
+                    p class="has-text-grey" { "Loading profit & loss..." }
+                }
+            }
+
+            // Outbound webhook deliveries with replay
+            div class="container mt-5" {
+                div class="box"
+                    id="webhook-deliveries"
+                    hx-get="/admin/webhooks"
+                    hx-trigger="load, every 60s"
+                    hx-swap="innerHTML" {
+                    p class="has-text-grey" { "Loading webhook deliveries..." }
+                }
+            }
+
         // Include location selector JavaScript
         script src="/ui/location_selector.js" {}
 
@@ -282,6 +458,8 @@ pub fn admin_dashboard(stations: &[StationWithWeather], defaults: &CompetitionDe
                     // datetime-local gives local time; convert to UTC via Date object
                     var date = new Date(input.value);
                     hidden.value = date.toISOString();
+                } else if (input && hidden && input.dataset.optional) {
+                    hidden.value = '';
                 }
             }
             "#))
@@ -383,3 +561,29 @@ pub fn competition_success_message(message: &str) -> Markup {
         }
     }
 }
+
+/// Estimated contract size for the create form, flagging what goes over the coordinator's limits
+pub fn contract_estimate_panel(estimate: &ContractSizeEstimate, limits: &ContractLimits) -> Markup {
+    let rejection = estimate.check(limits).err();
+    html! {
+        article class=(if rejection.is_some() { "message is-danger is-small" } else { "message is-info is-small" }) {
+            div class="message-body" {
+                p {
+                    strong { "Contract size: " }
+                    (estimate.outcomes) " outcomes (max " (limits.max_outcomes) "), "
+                    (estimate.signatures) " signatures, about "
+                    (estimate.approx_memory_bytes.div_ceil(MIB)) " MiB (max "
+                    (limits.max_memory_bytes / MIB) " MiB), widest split transaction "
+                    (estimate.max_split_tx_weight) " WU (max " (MAX_STANDARD_TX_WEIGHT) " WU)"
+                }
+                @if let Some(errors) = rejection {
+                    ul {
+                        @for error in &errors.errors {
+                            li { (error.message) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use maud::{html, Markup, PreEscaped, DOCTYPE};
+
+/// Sign-in page served in place of any admin page requested without a session. The operator
+/// signs a NIP-98 event for `POST /admin/login` with their NIP-07 browser signer, which sets the
+/// session cookie, then the page they asked for is loaded again.
+pub fn admin_login_page() -> Markup {
+    html! {
+        (DOCTYPE)
+        html lang="en" {
+            head {
+                meta charset="UTF-8";
+                meta name="viewport" content="width=device-width, initial-scale=1.0";
+                title { "5day4cast Admin - Sign in" }
+                link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/bulma@1.0.2/css/bulma.min.css";
+            }
+            body {
+                section class="section" {
+                    div class="container" style="max-width: 480px;" {
+                        h1 class="title" { "Admin sign in" }
+                        p class="block" {
+                            "Sign in with a nostr key on the coordinator's admin allow-list, \
+                             using a NIP-07 browser extension."
+                        }
+                        button id="admin-login" class="button is-primary" { "Sign in with nostr" }
+                        p id="admin-login-status" class="help is-danger mt-3" {}
+                    }
+                }
+                script {
+                    (PreEscaped(r#"
+                        document.getElementById('admin-login').addEventListener('click', async () => {
+                            const status = document.getElementById('admin-login-status');
+                            status.textContent = '';
+                            if (!window.nostr) {
+                                status.textContent = 'No NIP-07 signer found, install or unlock a nostr extension';
+                                return;
+                            }
+                            try {
+                                const url = new URL('/admin/login', window.location.origin).href;
+                                const event = await window.nostr.signEvent({
+                                    kind: 27235,
+                                    created_at: Math.floor(Date.now() / 1000),
+                                    tags: [['u', url], ['method', 'POST']],
+                                    content: '',
+                                });
+                                const response = await fetch('/admin/login', {
+                                    method: 'POST',
+                                    credentials: 'same-origin',
+                                    headers: { Authorization: 'Nostr ' + btoa(JSON.stringify(event)) },
+                                });
+                                if (!response.ok) {
+                                    const body = await response.json().catch(() => ({}));
+                                    status.textContent = body.error || 'Sign in failed (' + response.status + ')';
+                                    return;
+                                }
+                                window.location.href = window.location.pathname === '/admin/login'
+                                    ? '/admin'
+                                    : window.location.href;
+                            } catch (error) {
+                                status.textContent = 'Sign in failed: ' + error;
+                            }
+                        });
+                    "#))
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,17 @@
+pub mod alerts;
 pub mod dashboard;
 pub mod location_selector;
+pub mod login;
+pub mod pnl;
 pub mod top_cities;
 pub mod wallet;
+pub mod webhooks;
 
+pub use alerts::{stuck_competitions_error, stuck_competitions_panel};
 pub use dashboard::admin_dashboard;
 pub use location_selector::location_selector;
+pub use login::admin_login_page;
+pub use pnl::{pnl_report_error, pnl_report_panel};
 pub use top_cities::{get_allowed_station_ids, is_allowed_station};
 pub use wallet::wallet_page;
+pub use webhooks::{webhook_deliveries_error, webhook_deliveries_panel};
@@ -0,0 +1,91 @@
+use maud::{html, Markup};
+
+use crate::domain::{CompetitionPnl, LedgerEntryKind, PnlReport};
+
+/// Revenue, costs and net per competition from the accounting ledger, with the total last
+pub fn pnl_report_panel(report: &PnlReport) -> Markup {
+    html! {
+        h2 class="subtitle has-text-weight-bold" { "Profit & Loss" }
+        @if report.competitions.is_empty() {
+            p class="has-text-grey" { "No money has moved yet." }
+        } @else {
+            div class="table-container" {
+                table class="table is-fullwidth is-striped is-narrow is-card-mobile" {
+                    thead {
+                        tr {
+                            th { "Competition" }
+                            th { "Invoices" }
+                            th { "Swept Back" }
+                            th { "Sponsored" }
+                            th { "Funding" }
+                            th { "Miner Fees" }
+                            th { "Payouts" }
+                            th { "Routing Fees" }
+                            th { "Escrow Used" }
+                            th { "Net (sats)" }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        @for pnl in &report.competitions {
+                            (pnl_row(pnl))
+                        }
+                    }
+                    tfoot {
+                        (pnl_row(&report.total))
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn pnl_row(pnl: &CompetitionPnl) -> Markup {
+    html! {
+        tr {
+            td data-label="Competition" {
+                @if let Some(competition_id) = pnl.competition_id {
+                    a href=(format!("/competitions/{}/leaderboard", competition_id)) {
+                        (competition_id)
+                    }
+                } @else {
+                    strong { "Total" }
+                }
+            }
+            td data-label="Invoices" { (pnl.amount(LedgerEntryKind::InvoiceSettled)) }
+            td data-label="Swept Back" { (pnl.amount(LedgerEntryKind::ContractSweep)) }
+            td data-label="Sponsored" { (pnl.amount(LedgerEntryKind::SponsorContribution)) }
+            td data-label="Funding" { (pnl.amount(LedgerEntryKind::FundingOutputCreated)) }
+            td data-label="Miner Fees" { (pnl.amount(LedgerEntryKind::MinerFee)) }
+            td data-label="Payouts" { (pnl.amount(LedgerEntryKind::PayoutSent)) }
+            td data-label="Routing Fees" { (pnl.amount(LedgerEntryKind::RoutingFee)) }
+            td data-label="Escrow Used" { (pnl.amount(LedgerEntryKind::EscrowInputConsumed)) }
+            td data-label="Net (sats)" {
+                span class=(if pnl.net_sats < 0 { "has-text-danger" } else { "has-text-success" }) {
+                    (pnl.net_sats)
+                }
+            }
+            td data-label="" {
+                @if let Some(competition_id) = pnl.competition_id {
+                    button class="button is-small is-link is-light"
+                           hx-post=(format!("/admin/api/competitions/{}/clone", competition_id))
+                           hx-prompt="Start of the clone's observations (RFC 3339), blank keeps the source's dates"
+                           hx-target="#clone-notification"
+                           hx-swap="innerHTML" {
+                        "Clone"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Error state for the panel when the report couldn't be built
+pub fn pnl_report_error(message: &str) -> Markup {
+    html! {
+        h2 class="subtitle has-text-weight-bold" { "Profit & Loss" }
+        div class="notification is-danger is-light" {
+            "Failed to load the ledger: " (message)
+        }
+    }
+}
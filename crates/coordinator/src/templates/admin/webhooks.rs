@@ -0,0 +1,80 @@
+use maud::{html, Markup};
+use time::format_description::well_known::Rfc3339;
+
+use crate::domain::WebhookDelivery;
+
+/// Recent outbound webhook deliveries with a replay action per row
+pub fn webhook_deliveries_panel(deliveries: &[WebhookDelivery], notice: Option<&str>) -> Markup {
+    html! {
+        h2 class="subtitle has-text-weight-bold" { "Webhook Deliveries" }
+        @if let Some(notice) = notice {
+            div class="notification is-info is-light" { (notice) }
+        }
+        @if deliveries.is_empty() {
+            p class="has-text-grey" { "No webhooks have been sent." }
+        } @else {
+            div class="table-container" {
+                table class="table is-fullwidth is-striped is-narrow is-card-mobile" {
+                    thead {
+                        tr {
+                            th { "Created" }
+                            th { "Competition" }
+                            th { "Event" }
+                            th { "Endpoint" }
+                            th { "Status" }
+                            th { "Attempts" }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        @for delivery in deliveries {
+                            tr {
+                                td data-label="Created" {
+                                    (delivery.created_at.format(&Rfc3339).unwrap_or_default())
+                                }
+                                td data-label="Competition" { (delivery.competition_id) }
+                                td data-label="Event" { (delivery.event_type) }
+                                td data-label="Endpoint" class="is-size-7" { (delivery.endpoint_url) }
+                                td data-label="Status" {
+                                    span class=(status_class(delivery)) { (delivery.status()) }
+                                    @if let Some(error) = &delivery.last_error {
+                                        @if !delivery.is_delivered() {
+                                            p class="has-text-danger is-size-7" { (error) }
+                                        }
+                                    }
+                                }
+                                td data-label="Attempts" { (delivery.attempts) }
+                                td {
+                                    button class="button is-small"
+                                        hx-post=(format!("/admin/webhooks/{}/replay", delivery.id))
+                                        hx-target="#webhook-deliveries"
+                                        hx-swap="innerHTML" {
+                                        "Replay"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Error state for the panel when deliveries couldn't be loaded
+pub fn webhook_deliveries_error(message: &str) -> Markup {
+    html! {
+        h2 class="subtitle has-text-weight-bold" { "Webhook Deliveries" }
+        div class="notification is-danger is-light" {
+            "Failed to load webhook deliveries: " (message)
+        }
+    }
+}
+
+fn status_class(delivery: &WebhookDelivery) -> &'static str {
+    match delivery.status() {
+        "delivered" => "tag is-success",
+        "pending" => "tag is-info",
+        _ => "tag is-danger",
+    }
+}
@@ -298,6 +298,8 @@ fn payment_modal() -> Markup {
 
                         // QR Code container
                         div id="qrContainer" class="has-text-centered mb-4" {}
+                        p id="paymentBreakdown" class="has-text-centered" {}
+                        p id="invoiceExpiry" class="help has-text-centered" {}
 
                         div class="field" {
                             label class="label" { "Payment Request (click to copy)" }
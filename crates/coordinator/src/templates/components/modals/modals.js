@@ -134,7 +134,20 @@ function setupAuthModals(authManager) {
     });
 }
 
+// Shows the nostr client's relay warning, if it has one, above the page content
+function showRelayWarning() {
+  const warning = window.nostrClient?.relayWarning?.();
+  if (!warning) return;
+  console.warn(warning, window.nostrClient.getRelayStatus());
+  const element = document.getElementById("relayWarning");
+  if (element) {
+    element.textContent = warning;
+    element.classList.remove("is-hidden");
+  }
+}
+
 window.resetLoginModal = resetLoginModal;
+window.showRelayWarning = showRelayWarning;
 window.resetRegisterModal = resetRegisterModal;
 window.resetForgotPasswordModal = resetForgotPasswordModal;
 window.setupAuthModals = setupAuthModals;
@@ -254,7 +267,11 @@ class AuthManager {
         password,
       );
 
-      await window.nostrClient.initialize(window.SignerType.PrivateKey, nsec);
+      await window.nostrClient.initialize(
+        window.SignerType.PrivateKey,
+        nsec,
+        await this.relays(),
+      );
       this.authorizedClient = new window.AuthorizedClient(
         window.nostrClient,
         this.apiBase,
@@ -284,7 +301,11 @@ class AuthManager {
     if (errorElement) errorElement.textContent = "";
 
     try {
-      await window.nostrClient.initialize(window.SignerType.NIP07, null);
+      await window.nostrClient.initialize(
+        window.SignerType.NIP07,
+        null,
+        await this.relays(),
+      );
       this.authorizedClient = new window.AuthorizedClient(
         window.nostrClient,
         this.apiBase,
@@ -341,7 +362,11 @@ class AuthManager {
     }
 
     try {
-      await window.nostrClient.initialize(window.SignerType.PrivateKey, null);
+      await window.nostrClient.initialize(
+        window.SignerType.PrivateKey,
+        null,
+        await this.relays(),
+      );
       const nsec = await window.nostrClient.getPrivateKey();
 
       const encryptedNsec = await window.encryptNsecWithPassword(
@@ -454,7 +479,11 @@ class AuthManager {
     if (errorElement) errorElement.textContent = "";
 
     try {
-      await window.nostrClient.initialize(window.SignerType.NIP07, null);
+      await window.nostrClient.initialize(
+        window.SignerType.NIP07,
+        null,
+        await this.relays(),
+      );
       this.authorizedClient = new window.AuthorizedClient(
         window.nostrClient,
         this.apiBase,
@@ -567,7 +596,11 @@ class AuthManager {
     }
 
     try {
-      await window.nostrClient.initialize(window.SignerType.PrivateKey, nsec);
+      await window.nostrClient.initialize(
+        window.SignerType.PrivateKey,
+        nsec,
+        await this.relays(),
+      );
       const derivedNpub = await window.nostrClient.getPublicKey();
 
       if (derivedNpub !== this.forgotNpub) {
@@ -734,10 +767,37 @@ class AuthManager {
     document.querySelector('[hx-get="/competitions"]')?.click();
   }
 
+  // The player's own relays from localStorage, else the coordinator's. The wasm client falls
+  // back to its defaults when neither is set.
+  async relays() {
+    try {
+      const preferred = JSON.parse(localStorage.getItem("nostrRelays") || "null");
+      if (Array.isArray(preferred) && preferred.length > 0) return preferred;
+    } catch (error) {
+      console.warn("Ignoring invalid nostrRelays preference:", error);
+    }
+    if (!this.coordinatorRelays) {
+      try {
+        const response = await fetch(`${this.apiBase}/api/v1/info`);
+        if (response.ok) this.coordinatorRelays = (await response.json()).relays;
+      } catch (error) {
+        console.warn("Failed to fetch coordinator relays:", error);
+      }
+    }
+    return this.coordinatorRelays || undefined;
+  }
+
+  // Login only needs local signing, so it goes ahead without relays. Once they had time to
+  // connect, warn if none did since DMs from the coordinator won't arrive.
+  checkRelayHealth() {
+    setTimeout(showRelayWarning, 15000);
+  }
+
   onLoginSuccess() {
     document.getElementById("authButtons")?.classList.add("is-hidden");
     document.getElementById("logoutContainer")?.classList.remove("is-hidden");
     window.closeAllModals?.();
+    this.checkRelayHealth();
   }
 
   switchLoginTab(tab) {
@@ -1,14 +1,38 @@
 use maud::{html, Markup};
 
-use crate::templates::pages::competitions::CompetitionView;
+use crate::{domain::UserFacingPhase, templates::pages::competitions::CompetitionView};
 
 /// Single competition row for the table
 pub fn competition_row(comp: &CompetitionView) -> Markup {
     html! {
-        tr data-competition-id=(comp.id) {
-            td data-label="Status" {
-                span class=(status_class(&comp.status)) {
-                    (comp.status)
+        tr data-competition-id=(comp.id) data-phase=(comp.phase.label().to_lowercase()) {
+            td data-label="Progress" {
+                @if let Some(name) = &comp.name {
+                    div class="competition-name has-text-weight-semibold" title=(comp.id) { (name) }
+                }
+                (phase_progress(comp.phase))
+                @if comp.private {
+                    span class="tag is-dark is-light ml-2" title="Invited players only" { "Private" }
+                }
+                @if !comp.tags.is_empty() {
+                    div class="tags mt-1" {
+                        @for tag in &comp.tags {
+                            a class="tag is-info is-light"
+                              hx-get=(format!("/competitions?tags={}", tag))
+                              hx-target="#main-content"
+                              hx-push-url="true" { (tag) }
+                        }
+                    }
+                }
+            }
+            td data-label="Next" {
+                @if let Some((label, deadline)) = &comp.next_deadline {
+                    span class="phase-deadline" {
+                        span class="phase-deadline-label" { (label) " in " }
+                        span class="phase-countdown" data-deadline=(deadline) { (deadline) }
+                    }
+                } @else {
+                    span class="has-text-grey" { "-" }
                 }
             }
             td data-label="Start" {
@@ -46,14 +70,35 @@ pub fn competition_row(comp: &CompetitionView) -> Markup {
     }
 }
 
-fn status_class(status: &str) -> &'static str {
-    match status.to_lowercase().as_str() {
-        "registration" => "tag is-success",
-        "setup" | "live" => "tag is-warning",
-        "signing" => "tag is-warning",
-        "awaiting results" => "tag is-info is-light",
-        "completed" => "tag is-info",
-        "failed" | "cancelled" => "tag is-danger",
-        _ => "tag",
+/// Step indicator for Entries -> Funding -> Live -> Results, cancelled competitions
+/// show a single tag instead
+fn phase_progress(phase: UserFacingPhase) -> Markup {
+    let Some(current) = phase.step_index() else {
+        return html! {
+            span class="tag is-danger" title=(phase.description()) { (phase.label()) }
+        };
+    };
+
+    html! {
+        div class="phase-progress" title=(phase.description()) {
+            ol class="phase-steps" {
+                @for (i, step) in UserFacingPhase::STEPS.iter().enumerate() {
+                    li class=(step_class(i, current)) title=(step.description()) {
+                        span class="phase-dot" {}
+                        span class="phase-step-label" { (step.label()) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn step_class(step: usize, current: usize) -> &'static str {
+    if step < current {
+        "phase-step is-done"
+    } else if step == current {
+        "phase-step is-current"
+    } else {
+        "phase-step"
     }
 }
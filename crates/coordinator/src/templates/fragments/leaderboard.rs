@@ -1,6 +1,6 @@
 use maud::{html, Markup};
 
-use crate::domain::scoring::ScoredEntry;
+use crate::domain::scoring::{LeaderboardEntry, ScoredEntry};
 
 /// Entry score for the leaderboard (simplified view)
 #[derive(Debug, Clone)]
@@ -15,11 +15,20 @@ impl EntryScore {
     pub fn from_scored_entry(entry: &ScoredEntry, rank: usize) -> Self {
         Self {
             rank,
-            entry_id: entry.entry_id.clone(),
+            entry_id: entry.entry_id.to_string(),
             username: String::new(),
             score: entry.raw_score,
         }
     }
+
+    pub fn from_leaderboard_entry(entry: &LeaderboardEntry) -> Self {
+        Self {
+            rank: entry.rank,
+            entry_id: entry.entry_id.to_string(),
+            username: entry.username.clone().unwrap_or_default(),
+            score: entry.raw_score,
+        }
+    }
 }
 
 /// Competition info for the leaderboard header
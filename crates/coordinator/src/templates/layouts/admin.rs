@@ -51,6 +51,9 @@ pub fn admin_base(config: &AdminPageConfig, content: Markup) -> Markup {
                         li hx-get="/admin/wallet" hx-target="#admin-content" hx-swap="innerHTML" hx-push-url="true" {
                             a { "Wallet" }
                         }
+                        li hx-post="/admin/logout" {
+                            a { "Sign out" }
+                        }
                     }
                 }
 
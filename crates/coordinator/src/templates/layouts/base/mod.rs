@@ -31,6 +31,7 @@ pub fn base(config: &PageConfig, content: Markup) -> Markup {
 
                 section class="section pt-3" {
                     div class="container" {
+                        div id="relayWarning" class="notification is-warning is-hidden" {}
                         div id="main-content" {
                             (content)
                         }
@@ -1,34 +1,62 @@
 use maud::{html, Markup};
 
-use crate::templates::fragments::competition_row;
+use crate::{domain::UserFacingPhase, templates::fragments::competition_row};
 
 /// View data for a competition
 #[derive(Debug, Clone)]
 pub struct CompetitionView {
     pub id: String,
+    /// Name the operator gave the competition, if any
+    pub name: Option<String>,
     pub start_time: String,
     pub end_time: String,
     pub signing_time: String,
     pub status: String,
+    pub phase: UserFacingPhase,
+    /// Label and RFC3339 time of the next deadline for the current phase
+    pub next_deadline: Option<(String, String)>,
     pub entry_fee: u64,
     pub total_pool: u64,
     pub total_entries: u64,
     pub num_winners: u64,
     pub can_enter: bool,
     pub number_of_values_per_entry: usize,
+    /// Invite-only, only listed for invited players and admins
+    pub private: bool,
+    /// Operator assigned tags, each links to the list filtered by it
+    pub tags: Vec<String>,
 }
 
-/// Competitions page content
-pub fn competitions_page(competitions: &[CompetitionView]) -> Markup {
+/// Competitions page content, `active_tags` is the tag filter the list was narrowed by
+pub fn competitions_page(competitions: &[CompetitionView], active_tags: &[String]) -> Markup {
+    let rows_url = if active_tags.is_empty() {
+        "/competitions/rows".to_string()
+    } else {
+        format!("/competitions/rows?tags={}", active_tags.join(","))
+    };
     html! {
         div id="allCompetitions" class="container" {
+            @if !active_tags.is_empty() {
+                div class="tags has-addons mb-3" {
+                    span class="tag" { "Tagged" }
+                    @for tag in active_tags {
+                        span class="tag is-info is-light" { (tag) }
+                    }
+                    a class="tag is-delete"
+                      title="Show all competitions"
+                      hx-get="/competitions"
+                      hx-target="#main-content"
+                      hx-push-url="true" {}
+                }
+            }
             div class="box" {
                 div class="table-container" {
                     table id="competitionsDataTable"
                           class="table is-fullwidth is-striped is-hoverable competitions-table is-card-mobile" {
                         thead {
                             tr {
-                                th { "Status" }
+                                th { "Progress" }
+                                th { "Next" }
                                 th { "Start" }
                                 th { "End" }
                                 th { "Signing" }
@@ -39,7 +67,7 @@ pub fn competitions_page(competitions: &[CompetitionView]) -> Markup {
                                 th { "" }
                             }
                         }
-                        tbody hx-get="/competitions/rows"
+                        tbody hx-get=(rows_url)
                               hx-trigger="every 30s"
                               hx-swap="innerHTML" {
                             @for comp in competitions {
@@ -70,10 +98,35 @@ pub fn competitions_page(competitions: &[CompetitionView]) -> Markup {
                         }
                     });
                 }
+                function formatCountdown(ms) {
+                    const minutes = Math.floor(ms / 60000);
+                    const days = Math.floor(minutes / 1440);
+                    const hours = Math.floor((minutes % 1440) / 60);
+                    const mins = minutes % 60;
+                    if (days > 0) return days + 'd ' + hours + 'h';
+                    if (hours > 0) return hours + 'h ' + mins + 'm';
+                    return mins + 'm';
+                }
+                function updateCountdowns() {
+                    document.querySelectorAll('.phase-countdown').forEach(function(el) {
+                        const deadline = el.dataset.deadline;
+                        if (!deadline) return;
+                        const remaining = new Date(deadline) - new Date();
+                        el.textContent = remaining > 0 ? formatCountdown(remaining) : 'now';
+                        el.title = new Date(deadline).toLocaleString();
+                    });
+                }
                 // Run immediately for this fragment
                 formatLocalTimes();
+                updateCountdowns();
                 // Also run after HTMX swaps (for auto-refresh of rows)
-                document.body.addEventListener('htmx:afterSwap', formatLocalTimes);
+                document.body.addEventListener('htmx:afterSwap', function() {
+                    formatLocalTimes();
+                    updateCountdowns();
+                });
+                if (!window.phaseCountdownTimer) {
+                    window.phaseCountdownTimer = setInterval(updateCountdowns, 30000);
+                }
             })();
             "#))
         }
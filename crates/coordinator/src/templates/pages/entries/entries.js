@@ -123,16 +123,50 @@ class Entry {
     if (!response.ok)
       throw new Error(`Failed to get ticket: ${response.status}`);
 
-    const ticketData = await response.json();
-    this.ticket = {
+    this.ticket = this.ticketFromResponse(await response.json());
+
+    return this.showPaymentModal();
+  }
+
+  ticketFromResponse(ticketData) {
+    // Make sure the invoice's memo names this competition and ticket, and its fees add up
+    try {
+      window.checkTicketInvoice(
+        JSON.stringify(ticketData),
+        this.competition.id,
+      );
+    } catch (error) {
+      throw new Error(`Ticket invoice looks wrong: ${error}`);
+    }
+
+    return {
       id: ticketData.ticket_id,
       payment_request: ticketData.payment_request,
+      payment_hash: ticketData.payment_hash,
+      amount_sats: ticketData.amount_sats,
+      entry_fee: ticketData.entry_fee,
+      coordinator_fee: ticketData.coordinator_fee,
+      expires_at: new Date(ticketData.expires_at),
+      invoice_memo: ticketData.invoice_memo,
       keymeld_session_id: ticketData.keymeld_session_id,
       keymeld_enclave_public_key: ticketData.keymeld_enclave_public_key,
       keymeld_user_id: ticketData.keymeld_user_id,
     };
+  }
 
-    return this.showPaymentModal();
+  // A reserved ticket whose invoice lapsed gets a new one for the same payment hash
+  async refreshTicketInvoice() {
+    const response = await this.client.get(
+      `${this.coordinator_url}/api/v1/competitions/${this.competition.id}/tickets/${this.ticket.id}/invoice`,
+    );
+
+    if (!response.ok)
+      throw new Error(`Failed to refresh invoice: ${response.status}`);
+
+    const ticket = this.ticketFromResponse(await response.json());
+    if (ticket.payment_hash !== this.ticket.payment_hash)
+      throw new Error("Refreshed invoice is for a different payment");
+    this.ticket = ticket;
   }
 
   showPaymentModal() {
@@ -142,6 +176,8 @@ class Entry {
     const $error = document.getElementById("ticketPaymentError");
     const $paymentStatus = document.getElementById("paymentStatus");
     const $qrContainer = document.getElementById("qrContainer");
+    const $paymentBreakdown = document.getElementById("paymentBreakdown");
+    const $invoiceExpiry = document.getElementById("invoiceExpiry");
 
     const updateStatus = (message, type = "info") => {
       $paymentStatus.innerHTML = `
@@ -153,7 +189,6 @@ class Entry {
     const $qrCode = document.createElement("bitcoin-qr");
     Object.assign($qrCode, {
       id: "paymentQR",
-      lightning: this.ticket.payment_request,
       width: 300,
       height: 300,
       type: "svg",
@@ -195,9 +230,17 @@ class Entry {
 
     $paymentRequest.addEventListener("click", handleCopy);
 
+    const showInvoice = () => {
+      $qrCode.lightning = this.ticket.payment_request;
+      $qrCode.setAttribute("lightning", this.ticket.payment_request);
+      $paymentRequest.value = this.ticket.payment_request;
+      $paymentBreakdown.textContent = `${this.ticket.entry_fee} sats entry + ${this.ticket.coordinator_fee} sats coordinator fee = ${this.ticket.amount_sats} sats`;
+      $invoiceExpiry.textContent = `Invoice expires at ${this.ticket.expires_at.toLocaleTimeString()}`;
+    };
+
     $qrContainer.innerHTML = "";
     $qrContainer.appendChild($qrCode);
-    $paymentRequest.value = this.ticket.payment_request;
+    showInvoice();
     updateStatus("Waiting for payment...");
     $error.classList.add("is-hidden");
     $modal.classList.add("is-active");
@@ -221,7 +264,7 @@ class Entry {
               `Failed to check ticket status: ${response.status}`,
             );
 
-          const status = await response.json();
+          const { status } = await response.json();
           currentStatus = status;
 
           if (status === "Settled" || status === "Paid") {
@@ -238,6 +281,15 @@ class Entry {
             resolve(true);
             return true;
           } else if (status === "Reserved") {
+            if (this.ticket.expires_at <= new Date()) {
+              try {
+                await this.refreshTicketInvoice();
+                showInvoice();
+              } catch (error) {
+                // The reservation may have run out too, the next status check says so
+                console.warn("Failed to refresh ticket invoice:", error);
+              }
+            }
             return false;
           }
 
@@ -281,7 +333,7 @@ class Entry {
               `${this.coordinator_url}/api/v1/competitions/${this.competition.id}/tickets/${this.ticket.id}/status`,
             );
             if (response.ok) {
-              const status = await response.json();
+              const { status } = await response.json();
               if (status === "Settled" || status === "Paid") {
                 resolved = true;
                 clearInterval(backgroundPollingInterval);
@@ -382,6 +434,10 @@ class Entry {
         entry_body,
       );
 
+      if (response.status === 422) {
+        const { errors } = await response.json();
+        throw new Error(errors.map((error) => error.message).join("; "));
+      }
       if (!response.ok)
         throw new Error(`Failed to create entry, status: ${response.status}`);
 
@@ -552,6 +608,9 @@ async function submitEntry() {
     return;
   }
 
+  // The entry doesn't need relays, but its backup DM won't arrive without one
+  window.showRelayWarning?.();
+
   // Disable button during submission
   submitBtn.disabled = true;
   submitBtn.classList.add("is-loading");
@@ -3,7 +3,7 @@ use maud::{html, Markup};
 use crate::domain::UserEntryView;
 
 /// Entries page content (requires auth)
-pub fn entries_page(entries: &[UserEntryView]) -> Markup {
+pub fn entries_page(entries: &[UserEntryView], esplora_url: &str) -> Markup {
     html! {
         div id="allEntries" class="container" {
             div class="box" {
@@ -17,6 +17,7 @@ pub fn entries_page(entries: &[UserEntryView]) -> Markup {
                                 th { "Start Time" }
                                 th { "End Time" }
                                 th { "Status" }
+                                th { "Payout" }
                             }
                         }
                         tbody {
@@ -35,7 +36,14 @@ pub fn entries_page(entries: &[UserEntryView]) -> Markup {
                                     td data-label="End" {
                                         span class="utc-time" data-utc=(entry.end_time) { (entry.end_time) }
                                     }
-                                    td data-label="Status" { (entry.status) }
+                                    td data-label="Status" {
+                                        (entry.status)
+                                        @if let Some(reason) = &entry.cancelled_reason {
+                                            br;
+                                            span class="is-size-7 has-text-danger" { (reason) }
+                                        }
+                                    }
+                                    td data-label="Payout" { (payout_cell(entry, esplora_url)) }
                                 }
                             }
                         }
@@ -65,6 +73,33 @@ pub fn entries_page(entries: &[UserEntryView]) -> Markup {
     }
 }
 
+/// How the entry was paid, linking on-chain payouts to the explorer
+fn payout_cell(entry: &UserEntryView, esplora_url: &str) -> Markup {
+    html! {
+        (entry.payout_method.label())
+        @if let Some(amount) = entry.payout_amount_sats {
+            " (" (amount) " sats)"
+        }
+        @if let Some(reference) = &entry.payout_reference {
+            br;
+            @let short = reference.get(..8).unwrap_or(reference);
+            @if entry.payout_method.is_on_chain() {
+                a href=(format!("{}/tx/{}", esplora_url.trim_end_matches('/'), reference))
+                  target="_blank"
+                  rel="noopener noreferrer"
+                  title=(reference)
+                  onclick="event.stopPropagation()" {
+                    span class="is-family-monospace is-size-7" { (short) }
+                }
+            } @else {
+                span class="is-family-monospace is-size-7" title=(format!("Payment hash {}", reference)) {
+                    (short)
+                }
+            }
+        }
+    }
+}
+
 /// Empty entries message
 pub fn no_entries() -> Markup {
     html! {
@@ -1,5 +1,7 @@
 const AUTH_REQUIRED_ROUTES = ["/entries", "/payouts", "/entry-form"];
 const PUBLIC_ROUTES = ["/entries/", "/detail"]; // Entry detail pages are public (leaderboard)
+// Public, but signed when logged in so invited players see their private competitions
+const OPTIONAL_AUTH_ROUTES = ["/competitions", "/competitions/rows"];
 
 function requiresAuth(url) {
   // Entry detail routes are public (accessed from leaderboard)
@@ -9,6 +11,11 @@ function requiresAuth(url) {
   return AUTH_REQUIRED_ROUTES.some((route) => url.includes(route));
 }
 
+function signsWhenLoggedIn(url) {
+  const path = new URL(url, window.location.origin).pathname;
+  return OPTIONAL_AUTH_ROUTES.includes(path);
+}
+
 function isLoggedIn() {
   // Check that nostrClient exists, has an initialized signer, and taprootWallet exists
   return (
@@ -54,8 +61,18 @@ function setupHtmxAuth() {
   document.body.addEventListener("htmx:confirm", async (event) => {
     const { verb, path } = event.detail;
 
-    // If route doesn't require auth, let HTMX proceed normally
-    if (!requiresAuth(path)) return;
+    // Sign optional-auth routes when possible, never block them on logging in
+    if (!requiresAuth(path)) {
+      if (!signsWhenLoggedIn(path) || !isLoggedIn()) return;
+
+      event.preventDefault();
+      const authHeader = await generateAuthHeader(verb, path);
+      if (authHeader) {
+        event.detail.elt._pendingAuthHeader = authHeader;
+      }
+      event.detail.issueRequest();
+      return;
+    }
 
     // If user is not logged in, show login modal instead of making request
     if (!isLoggedIn()) {
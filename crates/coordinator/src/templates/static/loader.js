@@ -6,6 +6,7 @@ import init, {
   encryptNsecWithPassword,
   decryptNsecWithPassword,
   signForgotPasswordChallenge,
+  checkTicketInvoice,
 } from "/ui/pkg/coordinator_wasm.js";
 
 window.NostrClientWrapper = NostrClientWrapper;
@@ -15,6 +16,7 @@ window.SignerType = SignerType;
 window.encryptNsecWithPassword = encryptNsecWithPassword;
 window.decryptNsecWithPassword = decryptNsecWithPassword;
 window.signForgotPasswordChallenge = signForgotPasswordChallenge;
+window.checkTicketInvoice = checkTicketInvoice;
 
 window.wasmInitialized = false;
 window.wasmError = null;
@@ -71,6 +71,10 @@ enum CompetitionCommands {
         /// Observation window in minutes
         #[arg(long, default_value = "5")]
         observation_window: u64,
+        /// Minutes to sell tickets before observation starts, has to cover the coordinator's
+        /// minimum creation lead time
+        #[arg(long, default_value = "2")]
+        entry_window: u64,
     },
 }
 
@@ -158,17 +162,19 @@ async fn main() -> Result<()> {
                 entry_fee,
                 max_entries,
                 observation_window,
+                entry_window,
             } => {
                 let station_list: Vec<String> =
                     stations.split(',').map(|s| s.trim().to_string()).collect();
                 let now = OffsetDateTime::now_utc();
                 let window = time::Duration::minutes(observation_window as i64);
+                let start = now + time::Duration::minutes(entry_window as i64);
 
                 let competition = CreateCompetition {
                     id: Uuid::now_v7(),
-                    signing_date: now + window + time::Duration::seconds(60),
-                    start_observation_date: now,
-                    end_observation_date: now + window,
+                    signing_date: start + window + time::Duration::seconds(60),
+                    start_observation_date: start,
+                    end_observation_date: start + window,
                     locations: station_list.clone(),
                     number_of_values_per_entry: station_list.len() * 3,
                     number_of_places_win: 1,
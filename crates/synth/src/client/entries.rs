@@ -90,6 +90,12 @@ pub enum TicketStatus {
     Cancelled,
 }
 
+/// Ticket status response, the escrow details aren't needed by the scenarios
+#[derive(Debug, Clone, Deserialize)]
+struct TicketStatusResponse {
+    status: TicketStatus,
+}
+
 impl CoordinatorClient {
     /// Request a competition ticket (requires Nostr auth)
     pub async fn request_ticket(
@@ -158,9 +164,11 @@ impl CoordinatorClient {
             anyhow::bail!("Check ticket status failed ({}): {}", status, body);
         }
 
-        resp.json()
+        let response: TicketStatusResponse = resp
+            .json()
             .await
-            .context("Failed to parse ticket status response")
+            .context("Failed to parse ticket status response")?;
+        Ok(response.status)
     }
 
     /// Submit an entry (requires Nostr auth)